@@ -0,0 +1,209 @@
+//! Extracts the fields of the structs in the type modules so the `_template_context.html`
+//! reference (see src/template_docs.rs) always matches what templates are rendered with.
+//!
+//! Fields are listed as they are serialized, which is what templates see: `#[serde(skip)]` and
+//! `#[serde(skip_serializing)]` fields are left out, `#[serde(rename = "...")]` ones go by
+//! their new name, and a `#[serde(flatten)]` struct is replaced by its own fields (a flattened
+//! map by a `*` entry standing for any other key).
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const TYPE_SOURCES: [&str; 2] = ["src/types.rs", "src/vllm/types.rs"];
+
+struct Field {
+    name: String,
+    ty: String,
+    doc: String,
+    flatten: bool,
+}
+
+struct Struct {
+    name: String,
+    doc: String,
+    source: &'static str,
+    fields: Vec<Field>,
+}
+
+fn doc_line(line: &str) -> Option<&str> {
+    line.strip_prefix("///").map(str::trim)
+}
+
+/// The arguments of a `#[serde(...)]` attribute line, e.g. `rename = "x"` and `default`
+fn serde_args(line: &str) -> Vec<String> {
+    let Some(args) = line
+        .strip_prefix("#[serde(")
+        .and_then(|rest| rest.strip_suffix(")]"))
+    else {
+        return Vec::new();
+    };
+    // Split on commas outside of string literals
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut in_string = false;
+    for c in args.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                result.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    result.push(current.trim().to_string());
+    result
+}
+
+fn parse_structs(source: &'static str, contents: &str) -> Vec<Struct> {
+    let mut structs = Vec::new();
+    let mut doc: Vec<&str> = Vec::new();
+    let mut lines = contents.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(d) = doc_line(line) {
+            doc.push(d);
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+        let header = line
+            .strip_prefix("pub struct ")
+            .filter(|rest| rest.ends_with('{'));
+        let Some(header) = header else {
+            doc.clear();
+            continue;
+        };
+        let name: String = header
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        let mut fields = Vec::new();
+        let mut field_doc: Vec<&str> = Vec::new();
+        let mut field_serde: Vec<String> = Vec::new();
+        let mut pending = String::new();
+        for line in lines.by_ref() {
+            let line = line.trim();
+            if line == "}" {
+                break;
+            }
+            if let Some(d) = doc_line(line) {
+                field_doc.push(d);
+                continue;
+            }
+            if line.starts_with("#[") {
+                field_serde.extend(serde_args(line));
+                continue;
+            }
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            // Field types can span several lines; they end with a comma at nesting depth 0
+            pending.push_str(line);
+            let depth: i32 = pending
+                .chars()
+                .map(|c| match c {
+                    '<' | '(' | '[' => 1,
+                    '>' | ')' | ']' => -1,
+                    _ => 0,
+                })
+                .sum();
+            if depth > 0 || !pending.ends_with(',') {
+                continue;
+            }
+            let decl = pending.trim_end_matches(',').to_string();
+            pending.clear();
+            let decl = decl.strip_prefix("pub ").unwrap_or(&decl);
+            let skip = field_serde
+                .iter()
+                .any(|arg| arg == "skip" || arg == "skip_serializing");
+            if let Some((field, ty)) = decl.split_once(':').filter(|_| !skip) {
+                let rename = field_serde.iter().find_map(|arg| {
+                    let (key, value) = arg.split_once('=')?;
+                    (key.trim() == "rename").then(|| value.trim().trim_matches('"').to_string())
+                });
+                fields.push(Field {
+                    name: rename.unwrap_or_else(|| field.trim().to_string()),
+                    ty: ty.trim().to_string(),
+                    doc: field_doc.join(" "),
+                    flatten: field_serde.iter().any(|arg| arg == "flatten"),
+                });
+            }
+            field_doc.clear();
+            field_serde.clear();
+        }
+        structs.push(Struct {
+            name,
+            doc: doc.join(" "),
+            source,
+            fields,
+        });
+        doc.clear();
+    }
+    structs
+}
+
+/// The fields of `s` as serialized, with flattened structs replaced by their fields
+fn serialized_fields(s: &Struct, structs: &[Struct]) -> Vec<(String, String, String)> {
+    let mut fields = Vec::new();
+    for f in &s.fields {
+        if !f.flatten {
+            fields.push((f.name.clone(), f.ty.clone(), f.doc.clone()));
+            continue;
+        }
+        let inner =
+            f.ty.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .find_map(|name| structs.iter().find(|s| s.name == name));
+        match inner {
+            Some(inner) => fields.extend(serialized_fields(inner, structs)),
+            None => {
+                let value_ty =
+                    f.ty.rsplit_once(',')
+                        .map_or(f.ty.as_str(), |(_, value)| value.trim_end_matches('>'))
+                        .trim();
+                let doc = format!("{} (any other key, flattened in)", f.doc);
+                fields.push((
+                    "*".to_string(),
+                    value_ty.to_string(),
+                    doc.trim().to_string(),
+                ));
+            }
+        }
+    }
+    fields
+}
+
+fn main() {
+    let mut generated = String::from("pub static CONTEXT_STRUCTS: &[ContextStruct] = &[\n");
+    let mut structs = Vec::new();
+    for source in TYPE_SOURCES {
+        println!("cargo:rerun-if-changed={source}");
+        let contents = fs::read_to_string(source).expect("reading type sources");
+        structs.extend(parse_structs(source, &contents));
+    }
+    for s in &structs {
+        writeln!(
+            generated,
+            "    ContextStruct {{ name: {:?}, doc: {:?}, source: {:?}, fields: &[",
+            s.name, s.doc, s.source
+        )
+        .unwrap();
+        for (name, ty, doc) in serialized_fields(s, &structs) {
+            writeln!(
+                generated,
+                "        ContextField {{ name: {name:?}, ty: {ty:?}, doc: {doc:?} }},"
+            )
+            .unwrap();
+        }
+        generated.push_str("    ] },\n");
+    }
+    generated.push_str("];\n");
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("context_structs.rs");
+    fs::write(out, generated).unwrap();
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,110 @@
+//! Notice runs whose log stops while a compile is in flight or right after an exception is
+//! logged, which is typically where the process crashed or was killed, so the index can say
+//! where compilation was when it died.
+
+use fxhash::FxHashMap;
+
+use crate::cancellation;
+use crate::parsers::format_stack;
+use crate::types::{AbnormalEnd, CompileId, Envelope, FxIndexMap, OutputFile, StackSummary};
+
+/// Artifacts carrying an exception, e.g. `dynamo_error`
+fn is_error_artifact(name: &str) -> bool {
+    ["error", "exception", "traceback", "crash"]
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+/// The line of a traceback saying what was raised, normally its last
+fn exception_message(payload: &str) -> String {
+    payload
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[derive(Debug, Default)]
+pub struct RunEndTracker {
+    /// The most recently started compile, until its compilation_metrics arrive
+    in_flight: Option<Option<CompileId>>,
+    /// Most recent stack logged for each compile
+    stacks: FxHashMap<Option<CompileId>, StackSummary>,
+    /// (artifact name, compile id, message) if the last entry so far is an exception
+    last_error: Option<(String, Option<CompileId>, String)>,
+}
+
+impl RunEndTracker {
+    /// Record an entry; `compile_id` is its directory key
+    pub fn envelope(
+        &mut self,
+        e: &Envelope,
+        compile_id: &Option<CompileId>,
+        level: &str,
+        payload: &str,
+    ) {
+        self.last_error = None;
+        if let Some(start) = &e.dynamo_start {
+            self.in_flight = Some(compile_id.clone());
+            if let Some(stack) = &start.stack {
+                self.stacks.insert(compile_id.clone(), stack.clone());
+            }
+        }
+        if let Some(stack) = &e.stack {
+            self.stacks.insert(compile_id.clone(), stack.clone());
+        }
+        if e.compilation_metrics.is_some() && self.in_flight.as_ref() == Some(compile_id) {
+            self.in_flight = None;
+        }
+        if let Some(artifact) = &e.artifact {
+            if is_error_artifact(&artifact.name) || matches!(level, "E" | "C") {
+                self.last_error = Some((
+                    artifact.name.clone(),
+                    compile_id.clone(),
+                    exception_message(payload),
+                ));
+            }
+        }
+    }
+
+    pub fn finish(
+        self,
+        directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    ) -> Option<AbnormalEnd> {
+        let (reason, compile_id, message) = match (self.last_error, self.in_flight) {
+            (Some((name, cid, message)), _) => (
+                format!("The log ends with a <code>{name}</code> artifact."),
+                cid,
+                Some(message).filter(|m| !m.is_empty()),
+            ),
+            (None, Some(cid)) => (
+                "The log ends in the middle of a compile, before its compilation metrics were logged."
+                    .to_string(),
+                cid,
+                None,
+            ),
+            _ => return None,
+        };
+        let phase = directory
+            .get(&compile_id)
+            .map_or(Some("Dynamo graph capture"), |files| {
+                cancellation::current_phase(files)
+            })
+            .unwrap_or("after compilation metrics");
+        let stack_html = self
+            .stacks
+            .get(&compile_id)
+            .map_or(String::new(), |stack| format_stack(stack, "Stack", true));
+        Some(AbnormalEnd {
+            reason,
+            compile_id: compile_id
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            phase,
+            message,
+            stack_html,
+        })
+    }
+}
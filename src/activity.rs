@@ -0,0 +1,138 @@
+//! When compiles happened over the run: a strip of bars across the run's wall-clock duration
+//! at the top of the index, one bar per slice of the run, as tall as the compiles started in
+//! it.  Compiles confined to warmup show as bars at the left edge; compiles started in the
+//! second half of the run, when a job should be in steady state, are drawn in red.
+//!
+//! Times are taken from the glog prefix of each line, which has no year, so they are seconds
+//! since the start of a (leap) year; that is enough to line up the ranks of a run.  Each
+//! report keeps its times in `compile_activity.json` so the multi-rank landing page can draw
+//! every rank against the same axis.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::units;
+
+pub const ACTIVITY_FILENAME: &str = "compile_activity.json";
+
+/// Bars of a strip
+const BUCKETS: usize = 60;
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 32.0;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CompileActivity {
+    /// Time of the first and last lines of the log
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    /// Compile id and start time of each compile, in log order
+    pub compiles: Vec<(String, f64)>,
+}
+
+/// Seconds since the start of the year of a glog timestamp, `None` for an invalid date
+pub fn glog_time(
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    micros: u32,
+) -> Option<f64> {
+    use chrono::Datelike;
+    let day_of_year = chrono::NaiveDate::from_ymd_opt(2000, month, day)?.ordinal0();
+    Some(
+        ((day_of_year * 24 + hour) * 60 + minute) as f64 * 60.0
+            + second as f64
+            + micros as f64 / 1e6,
+    )
+}
+
+impl CompileActivity {
+    /// A line of the log logged at `time`
+    pub fn add_line(&mut self, time: f64) {
+        self.start.get_or_insert(time);
+        self.end = Some(time);
+    }
+
+    /// A `dynamo_start` of `compile_id` logged at `time`
+    pub fn add_compile(&mut self, compile_id: String, time: f64) {
+        self.compiles.push((compile_id, time));
+    }
+
+    /// Compiles started in the second half of `[start, end]`
+    fn num_late(&self, start: f64, end: f64) -> usize {
+        let midpoint = start + (end - start) / 2.0;
+        self.compiles.iter().filter(|(_, t)| *t > midpoint).count()
+    }
+
+    /// e.g. "12 compiles over 3.5 min; 2 started in the second half of the run"
+    pub fn summary(&self) -> Option<String> {
+        let (start, end) = self.start.zip(self.end)?;
+        if self.compiles.is_empty() {
+            return None;
+        }
+        let compiles = match self.compiles.len() {
+            1 => "1 compile".to_string(),
+            n => format!("{n} compiles"),
+        };
+        let late = match self.num_late(start, end) {
+            0 => "all in the first half of the run".to_string(),
+            1 => "1 started in the second half of the run".to_string(),
+            n => format!("{n} started in the second half of the run"),
+        };
+        Some(format!(
+            "{compiles} over {}; {late}",
+            units::seconds(end - start, 1)
+        ))
+    }
+
+    /// The strip of this run, over `[start, end]` (those of the whole run when the strips of
+    /// several ranks are shown together); `None` without compiles
+    pub fn sparkline(&self, start: f64, end: f64) -> Option<String> {
+        if self.compiles.is_empty() {
+            return None;
+        }
+        let duration = (end - start).max(1e-6);
+        let mut buckets = [0usize; BUCKETS];
+        for (_, t) in &self.compiles {
+            let bucket = ((t - start) / duration * BUCKETS as f64) as usize;
+            buckets[bucket.min(BUCKETS - 1)] += 1;
+        }
+        let max = buckets.iter().copied().max().unwrap_or(1).max(1) as f64;
+        let bar_width = WIDTH / BUCKETS as f64;
+        let mut svg = format!(
+            "<svg class='compile-activity' width='{WIDTH}' height='{HEIGHT}' xmlns='http://www.w3.org/2000/svg'>\n<rect width='{WIDTH}' height='{HEIGHT}' fill='#f4f4f4'/>\n"
+        );
+        for (i, &count) in buckets.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let height = (count as f64 / max * (HEIGHT - 2.0)).max(2.0);
+            let color = if i >= BUCKETS / 2 {
+                "#d62728"
+            } else {
+                "#1f77b4"
+            };
+            let from = duration * i as f64 / BUCKETS as f64;
+            writeln!(
+                svg,
+                "<rect x='{:.1}' y='{:.1}' width='{:.1}' height='{height:.1}' fill='{color}'><title>{count} compile{} at +{} to +{}</title></rect>",
+                bar_width * i as f64,
+                HEIGHT - height,
+                bar_width - 1.0,
+                if count == 1 { "" } else { "s" },
+                units::seconds(from, 1),
+                units::seconds(from + duration / BUCKETS as f64, 1),
+            )
+            .unwrap();
+        }
+        writeln!(
+            svg,
+            "<line x1='{x}' y1='0' x2='{x}' y2='{HEIGHT}' stroke='#888' stroke-dasharray='2,2'><title>Half of the run</title></line>",
+            x = WIDTH / 2.0
+        )
+        .unwrap();
+        svg.push_str("</svg>");
+        Some(svg)
+    }
+}
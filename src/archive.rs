@@ -0,0 +1,225 @@
+//! Trace directories handed over as one tarball (`.tar`, `.tar.gz`, `.tar.zst`), as log
+//! collection tooling uploads them.  The archive is decompressed like a log (see
+//! `compression`), its members unpacked into a temporary directory and the rank logs found
+//! there parsed: several ranks as with `--all-ranks-html`, a single log on its own.
+//!
+//! Only what a trace directory holds is supported: regular files and directories, with GNU
+//! long names and pax `path` records.  Links and members escaping the archive's root are
+//! skipped.
+
+use anyhow::{bail, Context};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use crate::compression::LogReader;
+
+const BLOCK: usize = 512;
+
+/// `dedicated_log_torch_trace_rank_3.log` (or `_rank_3_abc123.log.gz`, ...) -> 3
+pub fn rank_of_log(path: &Path) -> Option<u32> {
+    let filename = crate::compression::strip_extension(path.file_name()?.to_str()?);
+    filename
+        .strip_prefix("dedicated_log_torch_trace_rank_")?
+        .strip_suffix(".log")?
+        .split('_')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Whether the file at `path`, once decompressed, is a tar archive
+pub fn is_tar(path: &Path) -> anyhow::Result<bool> {
+    let mut header = [0u8; BLOCK];
+    let mut len = 0;
+    let mut reader = LogReader::open(path)?;
+    while len < BLOCK {
+        match reader.read(&mut header[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            // Not the compressed stream it looked like at first, so not an archive either
+            Err(_) => return Ok(false),
+        }
+    }
+    Ok(len == BLOCK && &header[257..262] == b"ustar")
+}
+
+/// The NUL-terminated string of a header field
+fn field(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// Octal size field, or base-256 when its high bit is set (GNU, for members of 8GiB or more)
+fn size(bytes: &[u8]) -> anyhow::Result<u64> {
+    if bytes[0] & 0x80 != 0 {
+        return Ok(bytes[1..]
+            .iter()
+            .fold(0, |size, b| (size << 8) | u64::from(*b)));
+    }
+    let octal = field(bytes);
+    let octal = octal.trim_matches(|c: char| c == ' ' || c == '\0');
+    if octal.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(octal, 8).with_context(|| format!("Bad tar member size `{octal}`"))
+}
+
+/// The `path` record of a pax extended header
+fn pax_path(records: &str) -> Option<String> {
+    records.lines().find_map(|record| {
+        let (_, record) = record.split_once(' ')?;
+        record.strip_prefix("path=").map(str::to_string)
+    })
+}
+
+/// `name` relative to the root of the archive; None for members outside of it
+fn member_path(name: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(c) => path.push(c),
+            Component::CurDir | Component::RootDir => {}
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// Unpack the archive at `path` into `dir`, returning the files written
+pub fn unpack(path: &Path, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut reader = LogReader::open(path)?;
+    let mut files = Vec::new();
+    let mut long_name: Option<String> = None;
+    let mut header = [0u8; BLOCK];
+    loop {
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            // Archives missing their two terminating zero blocks still end here
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e).with_context(|| format!("Reading {}", path.display())),
+        }
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+        let bytes = size(&header[124..136])?;
+        let padding = (BLOCK as u64 - bytes % BLOCK as u64) % BLOCK as u64;
+        let mut data = (&mut reader).take(bytes);
+        let name = match long_name.take() {
+            Some(name) => name,
+            // POSIX ustar splits long names into a prefix and a name; GNU uses those bytes
+            // for other fields
+            None if &header[257..263] == b"ustar\0" => match field(&header[345..500]) {
+                prefix if prefix.is_empty() => field(&header[..100]),
+                prefix => format!("{prefix}/{}", field(&header[..100])),
+            },
+            None => field(&header[..100]),
+        };
+        match header[156] {
+            b'L' | b'x' => {
+                let mut contents = String::new();
+                data.read_to_string(&mut contents)?;
+                long_name = if header[156] == b'L' {
+                    Some(contents.trim_end_matches('\0').to_string())
+                } else {
+                    pax_path(&contents)
+                };
+            }
+            b'0' | b'\0' | b'7' => match member_path(&name) {
+                Some(member) => {
+                    let out = dir.join(&member);
+                    if let Some(parent) = out.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    let mut file = File::create(&out)
+                        .with_context(|| format!("Creating {}", out.display()))?;
+                    io::copy(&mut data, &mut file)
+                        .with_context(|| format!("Unpacking {name} from {}", path.display()))?;
+                    files.push(out);
+                }
+                None => {
+                    io::copy(&mut data, &mut io::sink())?;
+                }
+            },
+            b'5' => {
+                if let Some(member) = member_path(&name) {
+                    fs::create_dir_all(dir.join(member))?;
+                }
+            }
+            _ => {
+                io::copy(&mut data, &mut io::sink())?;
+            }
+        }
+        io::copy(&mut (&mut reader).take(padding), &mut io::sink())?;
+    }
+    Ok(files)
+}
+
+/// What was found in an archive to parse
+#[derive(Debug)]
+pub enum ArchiveLogs {
+    /// The directory holding the most rank logs, when it holds more than one
+    Ranks(PathBuf),
+    /// The archive's only log
+    Single(PathBuf),
+}
+
+/// Pick the logs to parse among the `files` unpacked from `archive`
+pub fn find_logs(archive: &Path, files: &[PathBuf]) -> anyhow::Result<ArchiveLogs> {
+    let mut rank_dirs: BTreeMap<&Path, Vec<&PathBuf>> = BTreeMap::new();
+    for file in files.iter().filter(|f| rank_of_log(f).is_some()) {
+        rank_dirs
+            .entry(file.parent().unwrap())
+            .or_default()
+            .push(file);
+    }
+    if let Some((dir, logs)) = rank_dirs.into_iter().max_by_key(|(_, logs)| logs.len()) {
+        return Ok(match &logs[..] {
+            [log] => ArchiveLogs::Single(log.to_path_buf()),
+            _ => ArchiveLogs::Ranks(dir.to_path_buf()),
+        });
+    }
+    let logs: Vec<&PathBuf> = match files {
+        [file] => vec![file],
+        _ => files
+            .iter()
+            .filter(|f| {
+                let name = f.file_name().unwrap_or_default().to_string_lossy();
+                crate::compression::strip_extension(&name).ends_with(".log")
+            })
+            .collect(),
+    };
+    match &logs[..] {
+        [log] => Ok(ArchiveLogs::Single(log.to_path_buf())),
+        [] => bail!("No torch trace logs found in {}", archive.display()),
+        _ => bail!(
+            "{} holds {} logs but no dedicated_log_torch_trace_rank_N.log rank logs; unpack it \
+             and pick one",
+            archive.display(),
+            logs.len()
+        ),
+    }
+}
+
+/// A temporary directory removed when dropped
+pub struct UnpackDir {
+    pub path: PathBuf,
+}
+
+impl UnpackDir {
+    pub fn create() -> anyhow::Result<Self> {
+        let path = std::env::temp_dir().join(format!("tlparse-archive-{}", std::process::id()));
+        if path.exists() {
+            fs::remove_dir_all(&path)?;
+        }
+        fs::create_dir_all(&path).with_context(|| format!("Creating {}", path.display()))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for UnpackDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,144 @@
+//! Attempts of one compile as versions of it.  When Dynamo restarts its analysis (e.g. to skip
+//! a frame at a graph break) the compile is retried under the same frame and frame compile id
+//! with the next attempt number: `[0/0]`, `[0/0_1]`, `[0/0_2]`.  Only the last attempt produces
+//! the compiled code, so the index rolls attempts up under the latest one and each compile page
+//! links to its other attempts.
+
+use html_escape::encode_text;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::types::{AttemptRollup, CompilationMetricsIndex, CompileId, FxIndexMap};
+
+/// Placeholder in compilation metrics pages, replaced with the attempt switcher once every
+/// attempt of the compile has been seen
+pub const ATTEMPTS_MARKER: &str = "<!-- tlparse:attempts -->";
+
+/// The compile id without its attempt, shared by every attempt of a compile
+fn logical(compile_id: &CompileId) -> CompileId {
+    CompileId {
+        attempt: None,
+        ..compile_id.clone()
+    }
+}
+
+fn attempt_of(compile_id: &CompileId) -> u32 {
+    compile_id.attempt.unwrap_or(0)
+}
+
+/// Old logs have no attempt on some envelopes and attempt 0 on others
+fn same_attempt(a: &CompileId, b: &CompileId) -> bool {
+    logical(a) == logical(b) && attempt_of(a) == attempt_of(b)
+}
+
+/// Logical compile -> its attempts, by attempt number
+#[derive(Debug, Default)]
+pub struct AttemptIndex {
+    attempts: FxIndexMap<CompileId, Vec<CompileId>>,
+    /// Compilation metrics pages and the attempt they belong to
+    pages: Vec<(PathBuf, CompileId)>,
+}
+
+impl AttemptIndex {
+    /// A compilation metrics page of `compile_id`, as logged (the metrics index files legacy
+    /// envelopes under attempt 0)
+    pub fn add_page(&mut self, compile_id: &Option<CompileId>, page: PathBuf) {
+        if let Some(compile_id) = compile_id {
+            self.pages.push((page, compile_id.clone()));
+        }
+    }
+
+    /// Every compile of the run, once its output directories are known
+    pub fn add_compiles<'a>(&mut self, compile_ids: impl Iterator<Item = &'a Option<CompileId>>) {
+        for compile_id in compile_ids.flatten() {
+            let versions = self.attempts.entry(logical(compile_id)).or_default();
+            if !versions.iter().any(|v| same_attempt(v, compile_id)) {
+                versions.push(compile_id.clone());
+            }
+        }
+        for versions in self.attempts.values_mut() {
+            versions.sort_by_key(attempt_of);
+        }
+    }
+
+    /// Every attempt of `compile_id`'s compile, if it has more than one
+    pub fn attempts(&self, compile_id: &Option<CompileId>) -> &[CompileId] {
+        compile_id
+            .as_ref()
+            .and_then(|c| self.attempts.get(&logical(c)))
+            .filter(|versions| versions.len() > 1)
+            .map_or(&[], |versions| versions.as_slice())
+    }
+
+    /// The badge of `compile_id` on the index: the latest attempt carries the compile's
+    /// metrics and the count, earlier ones point at it
+    pub fn rollup(
+        &self,
+        compile_id: &Option<CompileId>,
+        metrics_index: &CompilationMetricsIndex,
+    ) -> Option<AttemptRollup> {
+        let versions = self.attempts(compile_id);
+        let (compile_id, latest) = (compile_id.as_ref()?, versions.last()?);
+        let position = versions.iter().position(|v| same_attempt(v, compile_id))?;
+        // Metrics are filed under the logical compile, the last ones being the latest attempt's
+        let latest_metrics = metrics_index
+            .iter()
+            .find(|(cid, _)| cid.as_ref().is_some_and(|c| logical(c) == logical(latest)))
+            .and_then(|(_, metrics)| metrics.last());
+        Some(AttemptRollup {
+            attempt: position + 1,
+            num_attempts: versions.len(),
+            is_latest: position + 1 == versions.len(),
+            latest: latest.to_string(),
+            latest_compile_time: latest_metrics
+                .and_then(|m| m.entire_frame_compile_time_s)
+                .map(|s| crate::units::seconds(s, 2)),
+            latest_failed: latest_metrics.is_some_and(|m| m.fail_type.is_some()),
+        })
+    }
+
+    /// Links from a compilation metrics page to the other attempts of its compile, each to its
+    /// first metrics page or else its entry on the index; empty for compiles with one attempt
+    pub fn switcher_html(&self, page: &Path) -> String {
+        let Some((_, compile_id)) = self.pages.iter().find(|(p, _)| p == page) else {
+            return String::new();
+        };
+        let versions = self.attempts(&Some(compile_id.clone()));
+        if versions.is_empty() {
+            return String::new();
+        }
+        let position = versions
+            .iter()
+            .position(|v| same_attempt(v, compile_id))
+            .unwrap_or_default();
+        let mut links = Vec::new();
+        for version in versions {
+            let label = encode_text(&version.to_string()).into_owned();
+            if same_attempt(version, compile_id) {
+                links.push(format!("<strong>{label}</strong>"));
+                continue;
+            }
+            let page = self.pages.iter().find(|(_, c)| same_attempt(c, version));
+            let href = match page {
+                Some((page, _)) => crate::paths::url(page),
+                None => format!("index.html#{}", version),
+            };
+            links.push(format!("<a href='{}'>{label}</a>", encode_text(&href)));
+        }
+        let mut html = String::new();
+        write!(
+            html,
+            "<p class='attempt-switcher'>Attempt {} of {}: {}{}</p>",
+            position + 1,
+            versions.len(),
+            links.join(" · "),
+            if position + 1 == versions.len() {
+                ""
+            } else {
+                " (a later attempt superseded this one)"
+            }
+        )
+        .unwrap();
+        html
+    }
+}
@@ -0,0 +1,185 @@
+//! `/browse/` in serve mode: a navigable listing of the output directory with the size and kind
+//! of every file, since the report pages don't link everything tlparse writes (payloads,
+//! manifests, raw logs, ...).
+//!
+//! The same listing, linking straight to the directories, is the page of a served directory
+//! without an `index.html`: one holding a report per rank, or several runs.
+
+use anyhow::Context;
+use std::fs;
+use std::path::{Component, Path};
+
+use crate::lazy::{LazyArtifactIndex, ARTIFACT_INDEX_FILENAME};
+use crate::manifest::{artifact_kind, size_heat};
+use crate::paths;
+use crate::templates::CSS;
+use crate::types::{BrowseContext, BrowseCrumb, BrowseEntry};
+use crate::units;
+
+/// URL prefix of the listing; `/browse/<dir>/` lists `<dir>` of the output directory
+pub const BROWSE_PREFIX: &str = "browse";
+
+/// Number of files and bytes under `dir`
+fn dir_totals(dir: &Path) -> (usize, u64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    let mut totals = (0, 0);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let (files, bytes) = dir_totals(&path);
+            totals.0 += files;
+            totals.1 += bytes;
+        } else {
+            totals.0 += 1;
+            totals.1 += entry.metadata().map_or(0, |m| m.len());
+        }
+    }
+    totals
+}
+
+/// Artifacts deferred with --lazy-artifacts that are directly in `rel` and not extracted yet
+fn deferred_files(root: &Path, rel: &Path) -> Vec<String> {
+    let Ok(contents) = fs::read_to_string(root.join(ARTIFACT_INDEX_FILENAME)) else {
+        return Vec::new();
+    };
+    let Ok(index) = serde_json::from_str::<LazyArtifactIndex>(&contents) else {
+        return Vec::new();
+    };
+    index
+        .artifacts
+        .into_keys()
+        .filter(|path| {
+            let path = Path::new(path);
+            path.parent() == Some(rel) && !root.join(path).exists()
+        })
+        .collect()
+}
+
+/// Where the links of a listing go
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Links {
+    /// Directories to their listing under `/browse/`
+    Browse,
+    /// Directories to themselves, so reports in them open on their index
+    Direct,
+}
+
+/// Render the listing of `rel` (relative to `root`), or `None` if it isn't a directory of the
+/// report; `base` is the URL the report is served under (`/runs/<name>` in a workspace)
+pub fn render_listing(root: &Path, rel: &Path, base: &str) -> anyhow::Result<Option<String>> {
+    listing(root, rel, base, Links::Browse)
+}
+
+/// Render the page of the served directory `rel` when it has no `index.html`, or `None` if it
+/// isn't a directory
+pub fn render_directory(root: &Path, rel: &Path, base: &str) -> anyhow::Result<Option<String>> {
+    listing(root, rel, base, Links::Direct)
+}
+
+fn listing(root: &Path, rel: &Path, base: &str, links: Links) -> anyhow::Result<Option<String>> {
+    if rel.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Ok(None);
+    }
+    let dir = root.join(rel);
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("Listing {}", dir.display()))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let rel_path = rel.join(&name);
+        if entry.path().is_dir() {
+            let (num_files, bytes) = dir_totals(&entry.path());
+            let num_files = units::count(num_files as u64);
+            let (url, kind) = match links {
+                Links::Browse => (
+                    format!("{base}/{BROWSE_PREFIX}/{}/", paths::url(&rel_path)),
+                    format!("{num_files} files"),
+                ),
+                Links::Direct if entry.path().join("index.html").is_file() => (
+                    format!("{base}/{}/", paths::url(&rel_path)),
+                    format!("tlparse report, {num_files} files"),
+                ),
+                Links::Direct => (
+                    format!("{base}/{}/", paths::url(&rel_path)),
+                    format!("{num_files} files"),
+                ),
+            };
+            dirs.push(BrowseEntry {
+                url,
+                name: format!("{name}/"),
+                kind,
+                size: units::bytes(bytes),
+                size_heat: size_heat(bytes),
+                deferred: false,
+            });
+        } else {
+            let bytes = entry.metadata().map_or(0, |m| m.len());
+            files.push(BrowseEntry {
+                url: format!("{base}/{}", paths::url(&rel_path)),
+                name,
+                kind: artifact_kind(&rel_path),
+                size: units::bytes(bytes),
+                size_heat: size_heat(bytes),
+                deferred: false,
+            });
+        }
+    }
+    for path in deferred_files(root, rel) {
+        let rel_path = Path::new(&path);
+        files.push(BrowseEntry {
+            url: format!("{base}/{}", paths::url(rel_path)),
+            name: rel_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            kind: artifact_kind(rel_path),
+            size: String::new(),
+            size_heat: "",
+            deferred: true,
+        });
+    }
+    dirs.sort_by(|a, b| a.name.cmp(&b.name));
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let crumb_base = match links {
+        Links::Browse => format!("{base}/{BROWSE_PREFIX}/"),
+        Links::Direct => format!("{base}/"),
+    };
+    let mut breadcrumbs = vec![BrowseCrumb {
+        name: root
+            .file_name()
+            .map_or("report".to_string(), |n| n.to_string_lossy().into_owned()),
+        url: crumb_base.clone(),
+    }];
+    let mut prefix = String::new();
+    for component in rel.components() {
+        let name = component.as_os_str().to_string_lossy();
+        prefix.push_str(&name);
+        prefix.push('/');
+        breadcrumbs.push(BrowseCrumb {
+            name: name.into_owned(),
+            url: format!("{crumb_base}{prefix}"),
+        });
+    }
+    let (num_files, total_bytes) = dir_totals(&dir);
+
+    let tt = crate::report_model::templates(false)?;
+    let context = BrowseContext {
+        css: CSS,
+        base: base.to_string(),
+        report_url: (links == Links::Browse).then(|| format!("{base}/index.html")),
+        path: format!("/{}", paths::url(rel)),
+        breadcrumbs,
+        num_files,
+        total_size: units::bytes(total_bytes),
+        dirs,
+        files,
+    };
+    Ok(Some(tt.render("browse.html", &context)?))
+}
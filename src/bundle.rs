@@ -0,0 +1,175 @@
+//! The whole report as one HTML file (`--single-file`), to attach to a bug report or an email
+//! instead of zipping the output directory.
+//!
+//! Every file of the report is embedded in a `<script type="text/plain">` block, keyed by its
+//! path.  The bundle shows `index.html` in a frame and follows links between the embedded files
+//! itself; the files pages `fetch` (the search index, artifacts on the compare page) are served
+//! from the bundle too.  Files that aren't HTML are shown as text.  The page shown is kept in
+//! the bundle's `#fragment`, so links to it and the back button work.
+
+use anyhow::Context;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+pub const BUNDLE_FILENAME: &str = "tlparse_report.html";
+
+const BUNDLE_HEAD: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>tlparse report</title>
+<style>
+html, body { margin: 0; height: 100%; }
+#tlparse-page { border: 0; width: 100%; height: 100%; display: block; }
+</style>
+</head>
+<body>
+<iframe id="tlparse-page"></iframe>
+"#;
+
+const BUNDLE_SCRIPT: &str = r#"<script>
+(function () {
+  const files = {};
+  for (const block of document.querySelectorAll('script[type="text/plain"][data-path]')) {
+    files[block.dataset.path] = block.textContent.replace(/<\\([\\\/!])/g, '<$1');
+  }
+  const frame = document.getElementById('tlparse-page');
+  let current = 'index.html';
+
+  // `href` relative to the embedded file `base`, without its query and fragment
+  function resolve(base, href) {
+    const [path, hash] = href.split('#');
+    const parts = base.split('/').slice(0, -1);
+    for (const part of path.split('?')[0].split('/')) {
+      if (part === '..') parts.pop();
+      else if (part !== '.' && part !== '') parts.push(part);
+    }
+    let resolved = parts.join('/');
+    try { resolved = decodeURIComponent(resolved); } catch (e) {}
+    return { path: path === '' ? base : resolved, hash: hash || '' };
+  }
+
+  function escapeHtml(text) {
+    return text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;');
+  }
+
+  // Runs in the frame before the page's own scripts
+  const shim = '<script>window.fetch = function (url) { return parent.tlparseFetch(url); };<\/script>';
+
+  function show(path, hash) {
+    current = path;
+    let html = files[path];
+    if (!path.endsWith('.html')) {
+      html = '<!DOCTYPE html><html><head><meta charset="utf-8"></head><body><pre>' +
+        escapeHtml(html) + '</pre></body></html>';
+    }
+    const head = html.search(/<head[^>]*>/i);
+    if (head >= 0) {
+      const end = html.indexOf('>', head) + 1;
+      html = html.slice(0, end) + shim + html.slice(end);
+    } else {
+      html = shim + html;
+    }
+    frame.onload = function () {
+      frame.contentWindow.scrollTo(0, 0);
+      if (hash) {
+        const target = frame.contentDocument.getElementById(hash) ||
+          frame.contentDocument.getElementsByName(hash)[0];
+        if (target) target.scrollIntoView();
+      }
+      frame.contentDocument.addEventListener('click', onClick);
+    };
+    frame.srcdoc = html;
+  }
+
+  function onClick(event) {
+    const link = event.target.closest && event.target.closest('a[href]');
+    if (!link) return;
+    const href = link.getAttribute('href');
+    if (/^[a-z][a-z0-9+.-]*:/i.test(href)) {
+      link.target = '_blank';
+      return;
+    }
+    const target = resolve(current, href);
+    if (!(target.path in files)) return;
+    event.preventDefault();
+    location.hash = target.path + (target.hash ? '#' + target.hash : '');
+  }
+
+  window.tlparseFetch = function (url) {
+    const target = resolve(current, String(url));
+    if (!(target.path in files)) {
+      return Promise.resolve(new Response('Not in the bundle: ' + target.path, { status: 404 }));
+    }
+    return Promise.resolve(new Response(files[target.path], { status: 200 }));
+  };
+
+  function route() {
+    const [path, hash] = decodeURIComponent(location.hash.slice(1)).split('#');
+    show(path in files ? path : 'index.html', hash);
+  }
+  window.addEventListener('hashchange', route);
+  route();
+})();
+</script>
+</body>
+</html>
+"#;
+
+/// `contents` made safe to embed in a `<script>` block: the bundle's script undoes it
+fn escape_script(contents: &str) -> String {
+    let mut escaped = String::with_capacity(contents.len());
+    let mut rest = contents;
+    while let Some(i) = rest.find('<') {
+        escaped.push_str(&rest[..=i]);
+        rest = &rest[i + 1..];
+        let starts_with = |prefix: &str| {
+            rest.get(..prefix.len())
+                .is_some_and(|r| r.eq_ignore_ascii_case(prefix))
+        };
+        if starts_with("\\") || starts_with("/script") || starts_with("!--") {
+            escaped.push('\\');
+        }
+    }
+    escaped.push_str(rest);
+    escaped
+}
+
+fn collect(dir: &Path, root: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Reading {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect(&path, root, files)?;
+        } else {
+            files.push(path.strip_prefix(root).unwrap().to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Bundle the report in `out_dir` into `out_dir/tlparse_report.html`, returning its path
+pub fn write_bundle(out_dir: &Path) -> anyhow::Result<PathBuf> {
+    let mut files = Vec::new();
+    collect(out_dir, out_dir, &mut files)?;
+    let mut bundle = String::from(BUNDLE_HEAD);
+    for file in files.iter().filter(|f| *f != Path::new(BUNDLE_FILENAME)) {
+        let bytes = fs::read(out_dir.join(file))
+            .with_context(|| format!("Reading {}", out_dir.join(file).display()))?;
+        bundle.push_str(&format!(
+            "<script type=\"text/plain\" data-path=\"{}\">",
+            html_escape::encode_double_quoted_attribute(&paths::url(file))
+        ));
+        bundle.push_str(&escape_script(&String::from_utf8_lossy(&bytes)));
+        bundle.push_str("</script>\n");
+    }
+    bundle.push_str(BUNDLE_SCRIPT);
+    let path = out_dir.join(BUNDLE_FILENAME);
+    fs::write(&path, bundle).with_context(|| format!("Writing {}", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,287 @@
+//! `--baseline`: explain why a warm cache missed (`cache_key_changes.html`).
+//!
+//! FX graph and AOT autograd cache lookups log the components their key is hashed from, one
+//! `[hash] name: value` string each.  Every run records its lookups in `cache_keys.json`; given
+//! a prior run, each cache miss of this run is matched with the same lookup there (same cache,
+//! compile id and position within the compile) and the components whose hash changed are
+//! listed and grouped into config, source, tensor metadata and environment changes.
+
+use anyhow::{bail, Context};
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::types::{CacheKeyChange, CacheKeyChangesContext, CacheMissExplanation, CompileId};
+
+pub const CACHE_KEYS_FILENAME: &str = "cache_keys.json";
+
+/// Longest component value kept, e.g. of the `gm` source; changes are found by hash anyway
+const MAX_VALUE_CHARS: usize = 300;
+
+static COMPONENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)^\[(?P<hash>[^\]]*)\] (?P<name>[^:]+): ?(?P<value>.*)$").unwrap()
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheKeyComponent {
+    pub name: String,
+    pub hash: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheLookup {
+    /// `fx_graph_cache`, `aotautograd_cache`, ...
+    pub cache: String,
+    pub compile_id: String,
+    /// How many lookups of the same cache the compile made before this one (forward/backward)
+    pub index: usize,
+    /// `hit`, `miss` or `bypass`
+    pub state: String,
+    pub key: String,
+    pub components: Vec<CacheKeyComponent>,
+}
+
+/// `--baseline`: the prior run to compare cache misses against
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    /// Path it was loaded from, for display
+    pub name: String,
+    pub keys: CacheKeys,
+}
+
+/// The cache lookups of a run, in log order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheKeys {
+    pub lookups: Vec<CacheLookup>,
+}
+
+#[derive(Deserialize)]
+struct LookupPayload {
+    key: Option<String>,
+    #[serde(default)]
+    components: Vec<String>,
+}
+
+fn category(name: &str) -> &'static str {
+    // Config first: option names like `inductor_config[disable_cpp_codegen]` mention the rest
+    if name.contains("config") || name.starts_with("fx_kwargs") || name.ends_with("_settings") {
+        "config"
+    } else if name == "gm" || name.starts_with("graph") || name.contains("source") {
+        "source"
+    } else if name.starts_with("example_inputs")
+        || name.starts_with("inputs_to_check")
+        || name.contains("tensor")
+        || name.contains("shape_env")
+        || name.contains("guards")
+    {
+        "tensor metadata"
+    } else if name.starts_with("system_info") || name.contains("version") {
+        "environment"
+    } else {
+        "other"
+    }
+}
+
+fn truncate(value: &str) -> String {
+    match value.char_indices().nth(MAX_VALUE_CHARS) {
+        Some((end, _)) => format!("{}…", &value[..end]),
+        None => value.to_string(),
+    }
+}
+
+impl CacheKeys {
+    pub fn is_empty(&self) -> bool {
+        self.lookups.is_empty()
+    }
+
+    /// Record an artifact if it is a cache lookup (`fx_graph_cache_miss`, ...) with key
+    /// components
+    pub fn add(&mut self, compile_id: &Option<CompileId>, artifact_name: &str, payload: &str) {
+        let Some((cache, state)) = ["hit", "miss", "bypass"].iter().find_map(|state| {
+            artifact_name
+                .strip_suffix(&format!("_{state}"))
+                .filter(|cache| cache.ends_with("_cache"))
+                .map(|cache| (cache, *state))
+        }) else {
+            return;
+        };
+        let Ok(lookup) = serde_json::from_str::<LookupPayload>(payload) else {
+            return;
+        };
+        if lookup.components.is_empty() {
+            return;
+        }
+        let compile_id = compile_id
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string());
+        let index = self
+            .lookups
+            .iter()
+            .filter(|l| l.cache == cache && l.compile_id == compile_id)
+            .count();
+        let components = lookup
+            .components
+            .iter()
+            .filter_map(|c| {
+                let caps = COMPONENT.captures(c)?;
+                Some(CacheKeyComponent {
+                    name: caps["name"].to_string(),
+                    hash: caps["hash"].to_string(),
+                    value: truncate(&caps["value"]),
+                })
+            })
+            .collect();
+        self.lookups.push(CacheLookup {
+            cache: cache.to_string(),
+            compile_id,
+            index,
+            state: state.to_string(),
+            key: lookup.key.unwrap_or_default(),
+            components,
+        });
+    }
+
+    fn find(&self, lookup: &CacheLookup) -> Option<&CacheLookup> {
+        self.lookups.iter().find(|l| {
+            l.cache == lookup.cache && l.compile_id == lookup.compile_id && l.index == lookup.index
+        })
+    }
+
+    /// Why each cache miss of this run missed, compared with the `baseline` run
+    pub fn explain(&self, baseline: &Baseline) -> CacheKeyChangesContext {
+        let misses: Vec<CacheMissExplanation> = self
+            .lookups
+            .iter()
+            .filter(|l| l.state == "miss")
+            .map(|lookup| {
+                let Some(before) = baseline.keys.find(lookup) else {
+                    return CacheMissExplanation {
+                        compile_id: lookup.compile_id.clone(),
+                        cache: lookup.cache.clone(),
+                        key: lookup.key.clone(),
+                        summary: "The baseline made no such lookup for this compile.".to_string(),
+                        changes: Vec::new(),
+                    };
+                };
+                let old: IndexMap<&str, &CacheKeyComponent> = before
+                    .components
+                    .iter()
+                    .map(|c| (c.name.as_str(), c))
+                    .collect();
+                let new: IndexMap<&str, &CacheKeyComponent> = lookup
+                    .components
+                    .iter()
+                    .map(|c| (c.name.as_str(), c))
+                    .collect();
+                let mut changes: Vec<CacheKeyChange> = Vec::new();
+                for (name, component) in &new {
+                    let status = match old.get(name) {
+                        Some(o) if o.hash == component.hash => continue,
+                        Some(_) => "changed",
+                        None => "added",
+                    };
+                    changes.push(CacheKeyChange {
+                        category: category(name),
+                        name: name.to_string(),
+                        status,
+                        baseline_value: old.get(name).map(|o| o.value.clone()),
+                        value: Some(component.value.clone()),
+                    });
+                }
+                for (name, component) in &old {
+                    if !new.contains_key(name) {
+                        changes.push(CacheKeyChange {
+                            category: category(name),
+                            name: name.to_string(),
+                            status: "removed",
+                            baseline_value: Some(component.value.clone()),
+                            value: None,
+                        });
+                    }
+                }
+                let summary = if before.key == lookup.key {
+                    "Same key as the baseline: the entry was missing from the cache (evicted, or \
+                     a different cache directory or remote cache)."
+                        .to_string()
+                } else if changes.is_empty() {
+                    "The key changed, but none of its logged components did.".to_string()
+                } else {
+                    let mut by_category: IndexMap<&str, usize> = IndexMap::new();
+                    for change in &changes {
+                        *by_category.entry(change.category).or_default() += 1;
+                    }
+                    let parts: Vec<String> = by_category
+                        .iter()
+                        .map(|(category, n)| match n {
+                            1 => format!("{category} (1 component)"),
+                            n => format!("{category} ({n} components)"),
+                        })
+                        .collect();
+                    format!("Changed since the baseline: {}.", parts.join(", "))
+                };
+                CacheMissExplanation {
+                    compile_id: lookup.compile_id.clone(),
+                    cache: lookup.cache.clone(),
+                    key: lookup.key.clone(),
+                    summary,
+                    changes,
+                }
+            })
+            .collect();
+        CacheKeyChangesContext {
+            css: crate::templates::CSS,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            baseline: baseline.name.clone(),
+            num_misses: misses.len(),
+            num_changed: misses.iter().filter(|m| !m.changes.is_empty()).count(),
+            misses,
+        }
+    }
+}
+
+/// The cache lookups of a prior run: its output directory, its `cache_keys.json`, or its log
+/// (which is parsed)
+pub fn load_baseline(path: &Path) -> anyhow::Result<Baseline> {
+    let json_path: Option<PathBuf> = if path.is_dir() {
+        let file = path.join(CACHE_KEYS_FILENAME);
+        if !file.is_file() {
+            bail!(
+                "{} has no {CACHE_KEYS_FILENAME}; pass the baseline's log or a tlparse output \
+                 directory with cache lookups",
+                path.display()
+            );
+        }
+        Some(file)
+    } else if path.extension().is_some_and(|e| e == "json") {
+        Some(path.to_path_buf())
+    } else {
+        None
+    };
+    let keys = match json_path {
+        Some(json_path) => {
+            let contents = fs::read_to_string(&json_path)
+                .with_context(|| format!("Reading baseline {}", json_path.display()))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Parsing baseline {}", json_path.display()))?
+        }
+        None => {
+            let output = crate::parse_path(&path.to_path_buf(), &crate::ParseConfig::default())
+                .with_context(|| format!("Parsing baseline log {}", path.display()))?;
+            match output
+                .into_iter()
+                .find(|(p, _)| p == Path::new(CACHE_KEYS_FILENAME))
+            {
+                Some((_, contents)) => serde_json::from_str(&contents)?,
+                None => CacheKeys::default(),
+            }
+        }
+    };
+    Ok(Baseline {
+        name: path.display().to_string(),
+        keys,
+    })
+}
@@ -0,0 +1,93 @@
+//! Detect compiles that were timed out or cancelled (e.g. an async compile worker was killed),
+//! which otherwise show up as half-populated compile directories with no explanation.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{CompilationMetricsMetadata, CompileCancellation, OutputFile};
+
+static TIMEOUT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)time[_ -]?out|timed[_ -]out").unwrap());
+static CANCELLED: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)cancel|\bkill(ed)?\b|_killed|KeyboardInterrupt").unwrap());
+
+/// Compile phases and the artifact name prefixes they produce, in pipeline order
+const PHASES: [(&str, &[&str]); 5] = [
+    ("Dynamo graph capture", &["dynamo_output_graph"]),
+    (
+        "AOTAutograd graphs",
+        &[
+            "aot_forward_graph",
+            "aot_backward_graph",
+            "aot_joint_graph",
+            "aot_inference_graph",
+        ],
+    ),
+    ("Inductor post-grad graph", &["inductor_post_grad_graph"]),
+    ("Inductor output code", &["inductor_output_code"]),
+    ("Compilation metrics", &["compilation_metrics"]),
+];
+
+fn kind_of(text: &str) -> Option<&'static str> {
+    if TIMEOUT.is_match(text) {
+        Some("Timeout")
+    } else if CANCELLED.is_match(text) {
+        Some("Cancelled")
+    } else {
+        None
+    }
+}
+
+/// Artifacts named like `compile_timeout` or `async_compile_cancelled`; the payload (or its
+/// `reason`/`message` field, for JSON) explains what happened
+pub fn from_artifact(name: &str, payload: &str) -> Option<CompileCancellation> {
+    let kind = kind_of(name)?;
+    let json = serde_json::from_str::<serde_json::Value>(payload).ok();
+    let reason = json
+        .as_ref()
+        .and_then(|v| v.get("reason").or_else(|| v.get("message")))
+        .and_then(|v| v.as_str())
+        .map_or_else(|| payload.trim().to_string(), |s| s.to_string());
+    Some(CompileCancellation {
+        kind,
+        source: name.to_string(),
+        reason,
+    })
+}
+
+/// A compile that failed because it timed out or was interrupted
+pub fn from_metrics(m: &CompilationMetricsMetadata) -> Option<CompileCancellation> {
+    let fail_type = m.fail_type.as_deref()?;
+    let reason = m.fail_reason.clone().unwrap_or_default();
+    let kind = kind_of(fail_type).or_else(|| kind_of(&reason))?;
+    Some(CompileCancellation {
+        kind,
+        source: "compilation_metrics".to_string(),
+        reason: format!("{fail_type}: {reason}"),
+    })
+}
+
+/// The first phase that produced no output for a compile, i.e. the one it was in if it
+/// stopped early
+pub fn current_phase(produced: &[OutputFile]) -> Option<&'static str> {
+    PHASES
+        .iter()
+        .find(|(_, prefixes)| {
+            !produced
+                .iter()
+                .any(|f| prefixes.iter().any(|p| f.name.contains(p)))
+        })
+        .map(|(phase, _)| *phase)
+}
+
+/// The phases that never produced output for a compile, given what it did produce
+pub fn missing_phases(produced: &[OutputFile]) -> Vec<String> {
+    PHASES
+        .iter()
+        .filter(|(_, prefixes)| {
+            !produced
+                .iter()
+                .any(|f| prefixes.iter().any(|p| f.name.contains(p)))
+        })
+        .map(|(phase, prefixes)| format!("{phase} ({})", prefixes.join(", ")))
+        .collect()
+}
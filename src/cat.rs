@@ -0,0 +1,140 @@
+//! `tlparse cat LOG --compile-id 2/0 --artifact dynamo_output_graph`: print the payload of one
+//! envelope to stdout, for shell pipelines, without generating a report.
+//!
+//! `--artifact` is the kind of the envelope (`dynamo_output_graph`, `inductor_output_code`,
+//! ...) or the name of an `artifact` / `graph_dump` envelope (`before_post_grad_graph`,
+//! `inductor_collective_schedule`, ...).  The payload is printed without the tabs it is
+//! indented with in the log, JSON payloads pretty-printed.
+
+use anyhow::bail;
+use serde_json::Value;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::rank_prefix::strip_rank_prefix;
+use crate::tail::{CompileFilter, ENVELOPE_FIELDS, GLOG_LINE};
+use crate::types::CompileId;
+
+#[derive(Debug, Clone)]
+pub struct CatOptions {
+    /// Only envelopes of this compile; every envelope without one
+    pub compile_id: Option<CompileFilter>,
+    pub artifact: String,
+    /// Which of the matching payloads to print, from 1
+    pub nth: usize,
+}
+
+/// The kind of an envelope and, for `artifact` and `graph_dump`, its name
+fn names(envelope: &serde_json::Map<String, Value>) -> Vec<String> {
+    let Some((kind, metadata)) = envelope
+        .iter()
+        .find(|(key, _)| !ENVELOPE_FIELDS.contains(&key.as_str()))
+    else {
+        return Vec::new();
+    };
+    let mut names = vec![kind.clone()];
+    if let Some(name) = metadata["name"].as_str() {
+        names.push(name.to_string());
+    }
+    names
+}
+
+fn reformat(payload: String) -> String {
+    match serde_json::from_str::<Value>(&payload) {
+        Ok(value @ (Value::Object(_) | Value::Array(_))) => {
+            serde_json::to_string_pretty(&value).unwrap_or(payload)
+        }
+        _ => payload,
+    }
+}
+
+/// Write the `options.nth` payload of `options.artifact` in `path` to `out`
+pub fn cat(path: &Path, options: &CatOptions, out: &mut dyn Write) -> anyhow::Result<()> {
+    if options.nth == 0 {
+        bail!("--nth counts from 1");
+    }
+    let file = crate::compression::LogReader::open(path)?;
+    let mut lines = file.split(b'\n').peekable();
+    let mut matches = 0;
+    let mut payload: Option<String> = None;
+    // Payload names of the compile, for the error if the artifact isn't there
+    let mut available: Vec<String> = Vec::new();
+    while let Some(line) = lines.next() {
+        let line = String::from_utf8_lossy(&line?).into_owned();
+        let (_, line) = strip_rank_prefix(line.trim_end_matches('\r'));
+        let Some(caps) = GLOG_LINE.captures(line) else {
+            continue;
+        };
+        let Ok(Value::Object(envelope)) = serde_json::from_str::<Value>(&caps["json"]) else {
+            continue;
+        };
+        if !envelope.contains_key("has_payload") {
+            continue;
+        }
+        let compile_id: Option<CompileId> =
+            serde_json::from_value(Value::Object(envelope.clone())).ok();
+        let compile_id = compile_id.filter(|c| c.frame_id.is_some());
+        let wanted = match (&options.compile_id, &compile_id) {
+            (Some(filter), Some(compile_id)) => filter.matches(compile_id),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        if !wanted {
+            continue;
+        }
+        let names = names(&envelope);
+        if !names.contains(&options.artifact) {
+            if let Some(name) = names.last().filter(|n| !available.contains(n)) {
+                available.push(name.clone());
+            }
+            continue;
+        }
+        matches += 1;
+        if matches != options.nth {
+            continue;
+        }
+        let mut payload_lines = Vec::new();
+        while let Some(Ok(next)) = lines.peek() {
+            let next = String::from_utf8_lossy(next).into_owned();
+            let (_, next) = strip_rank_prefix(next.trim_end_matches('\r'));
+            match next.strip_prefix('\t') {
+                Some(payload_line) => payload_lines.push(payload_line.to_string()),
+                // The parser skips blank lines, so they don't end the payload either
+                None if next.is_empty() => {}
+                None => break,
+            }
+            lines.next();
+        }
+        payload = Some(payload_lines.join("\n"));
+    }
+
+    let what = match &options.compile_id {
+        Some(filter) => format!("{} of compile {filter}", options.artifact),
+        None => options.artifact.clone(),
+    };
+    let Some(payload) = payload else {
+        if matches > 0 {
+            bail!("Only {matches} {what} in {}", path.display());
+        }
+        if available.is_empty() {
+            bail!("No {what} in {}", path.display());
+        }
+        bail!(
+            "No {what} in {}; its payloads are: {}",
+            path.display(),
+            available.join(", ")
+        );
+    };
+    // `tlparse cat ... | head` closes the pipe early, which isn't an error
+    match writeln!(out, "{}", reformat(payload)).and_then(|_| out.flush()) {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+        result => result?,
+    }
+    if matches > options.nth {
+        eprintln!(
+            "{} more {what} in the log; pass --nth to print another",
+            matches - options.nth
+        );
+    }
+    Ok(())
+}
@@ -1,24 +1,50 @@
-use clap::Parser;
+use clap::builder::{ArgAction, FalseyValueParser};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 
 use anyhow::{bail, Context};
 use std::fs;
 use std::io::Read;
-use std::path::PathBuf;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use tlparse::serve::{AccessLog, BasicAuth, CacheHeaders, Validators};
+use tlparse::watch::{ChangeFeed, Snapshot};
 use tlparse::{
+    archive,
+    check_rank_logs,
+    config::ConfigFile,
     // New reusable library API for multi-rank landing generation
     generate_multi_rank_landing,
+    lock::OutputLock,
+    manifest::{Manifest, ManifestEntry, MANIFEST_FILENAME},
     parse_path,
+    parser_config::ParserConfigFile,
+    paths,
+    rank_prefix,
+    strict::{StrictError, STRICT_FAILURES_FILENAME},
     // Context used to pass rank list; other fields are recomputed inside the API
+    Diagnostics,
     MultiRankContext,
     ParseConfig,
+    StackRenderOptions,
 };
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+#[command(
+    after_help = "Every option can also be set with a TLPARSE_<OPTION> environment variable, e.g. \
+        TLPARSE_OUT=reports or TLPARSE_NO_BROWSER=1; options on the command line take precedence."
+)]
 pub struct Cli {
-    path: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(required_unless_present = "vllm_merge")]
+    path: Option<PathBuf>,
     /// Parse most recent log
     #[arg(long)]
     latest: bool,
@@ -28,6 +54,9 @@ pub struct Cli {
     /// Delete out directory if it already exists
     #[arg(long)]
     overwrite: bool,
+    /// Take over the lock of the output directory even though another run holds it
+    #[arg(long, global = true)]
+    break_lock: bool,
     /// Return non-zero exit code if unrecognized log lines are found.  Mostly useful for unit
     /// testing.
     #[arg(long)]
@@ -58,27 +87,549 @@ pub struct Cli {
     /// Parse all ranks and create a unified multi-rank report
     #[arg(long)]
     all_ranks_html: bool,
+    /// With --all-ranks-html or --vllm-merge, parse this many logs at a time (default: one per
+    /// CPU)
+    #[arg(long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+    /// Parse the torch trace logs of vLLM's engine-core workers in DIR, each into its own
+    /// worker_N/ report, with a landing page grouping their subgraphs and cudagraph captures
+    /// by worker
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["path", "all_ranks_html", "latest", "follow", "watch"]
+    )]
+    vllm_merge: Option<PathBuf>,
     /// Start a local HTTP server to serve the output directory
     #[arg(long)]
     serve: bool,
     /// Port for the HTTP server (used with --serve). If not specified, finds an available port.
     #[arg(long)]
     port: Option<u16>,
+    /// With --serve, require HTTP basic auth with these credentials
+    #[arg(long, value_name = "USER:PASSWORD", requires = "serve")]
+    auth: Option<String>,
+    /// With --serve, log every request to FILE (default `-`, stdout) in the Common Log Format
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        default_missing_value = "-",
+        requires = "serve"
+    )]
+    access_log: Option<String>,
+    /// JSON config file, e.g. with `derived_metrics` expressions to add to the report
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// JSON file of declarative parsers (artifact name pattern => output filename and format)
+    /// writing your own structured-log artifacts to the compile directories
+    #[arg(long, value_name = "FILE")]
+    parser_config: Option<PathBuf>,
+    /// URL prefix the report will be hosted under (e.g. https://host/reports/run1). Intra-report
+    /// links are rewritten to absolute links under it, so they survive nested mount points.
+    #[arg(long)]
+    base_url: Option<String>,
+    /// With --serve, re-parse the log whenever it changes and list what each regeneration
+    /// added (new artifacts and warnings) in changes.html and changes.json
+    #[arg(long, requires = "serve", conflicts_with = "all_ranks_html")]
+    watch: bool,
+    /// With --watch, seconds between the checks an open index page makes for a regenerated
+    /// report, reloading itself when there is one; 0 leaves reloading to you
+    #[arg(
+        long,
+        value_name = "SECS",
+        requires = "watch",
+        default_value_t = tlparse::watch::DEFAULT_REFRESH_INTERVAL_S
+    )]
+    refresh_interval: u64,
+    /// Keep following the log as it grows, like `tail -f`: each time lines are appended the
+    /// log is parsed again from the start and only the files that changed are rewritten.
+    /// Reparses wait at least as long as the last one took, so on large logs updates get rarer.
+    #[arg(long, conflicts_with_all = ["serve", "all_ranks_html", "low_memory"])]
+    follow: bool,
+    /// With --serve, leave raw artifacts of at least this many bytes (default 64 KiB) in the
+    /// log and extract them on first request, using the byte offsets in artifact_index.json
+    #[arg(long, value_name = "MIN_BYTES", num_args = 0..=1, default_missing_value = "65536")]
+    lazy_artifacts: Option<usize>,
+    /// Bound memory use on large logs, at the cost of speed: raw payloads of at least 16 KiB
+    /// are dropped from memory once parsed and copied out of the log after the report is
+    /// written
+    #[arg(long)]
+    low_memory: bool,
+    /// Cap the memory the report holds during the parse (e.g. 4G, 512M): once its files take
+    /// more, the oldest are spilled to a file in the temp directory and copied out after the
+    /// rest of the report is written.  For rank logs of tens of gigabytes.
+    #[arg(long, value_name = "SIZE", value_parser = parse_memory_size)]
+    max_memory: Option<u64>,
+    /// Also write report_model.json, the context every page was rendered from, so `tlparse
+    /// rerender` can regenerate the pages with other templates.  Holds the contexts in memory
+    /// until the end of the parse.
+    #[arg(long)]
+    report_model: bool,
+    /// Set the modification time of each artifact to the log timestamp it was logged at
+    /// (recorded as `log_time` in manifest.json), so sorting by date follows the run
+    #[arg(long)]
+    mtime_from_log: bool,
+    /// Collapse runs of identical consecutive stack frames into one frame with a repeat count
+    #[arg(long)]
+    collapse_repeated_frames: bool,
+    /// Strip everything up to site-packages/ (or dist-packages/) from stack frame filenames
+    #[arg(long)]
+    strip_site_packages: bool,
+    /// Rewrite source paths starting with FROM (in stack frames, guards, tracebacks and graph
+    /// code) to start with TO, for logs produced with other mount points; may be repeated
+    #[arg(long, visible_alias = "stack-path-map", value_name = "FROM=TO", value_parser = parse_path_mapping)]
+    path_map: Vec<(String, String)>,
+    /// Show LABEL next to the compile ids matching PATTERN (a compile id like 7/0_1, or a
+    /// regex over them) throughout the report; may be repeated, the first match wins
+    #[arg(long, value_name = "PATTERN=LABEL", value_parser = parse_compile_label)]
+    compile_label: Vec<(String, String)>,
+    /// Eager op trace (`op [count]` lines, a JSON op -> count map or a profiler Chrome trace)
+    /// to compare against the ops in compiled graphs, written to op_coverage.html
+    #[arg(long, value_name = "FILE")]
+    eager_ops: Option<PathBuf>,
+    /// JSON file of config option explanations ({"dynamo": {option: text}, "inductor": ...,
+    /// "vllm": ...}) shown as tooltips, adding to or replacing the bundled ones
+    #[arg(long, value_name = "FILE")]
+    config_docs: Option<PathBuf>,
+    /// Parse only this fraction (0-1] of the compiles, chosen deterministically by compile id;
+    /// failed compiles and the slowest ones are always included.  For logs too large to parse
+    /// in full.
+    #[arg(long, value_name = "RATE")]
+    sample_rate: Option<f64>,
+    /// Parse at most this many compiles (plus every failed one); see --sample-rate
+    #[arg(long, value_name = "N")]
+    max_compiles: Option<usize>,
+    /// Metadata about the run (e.g. git_sha=abc123) to show on the index page and record in
+    /// summary.json and manifest.json; may be repeated
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    metadata: Vec<(String, String)>,
+    /// Start the index page with only the compiles rooted in user code shown, hiding those
+    /// whose compiled frame is in PyTorch, vLLM or an installed package (the page shows how
+    /// many are hidden and can show them again)
+    #[arg(long)]
+    hide_framework_frames: bool,
+    /// A prior run (its log, tlparse output directory or cache_keys.json) to compare cache
+    /// keys against: each cache miss is explained by the key components that changed since,
+    /// in cache_key_changes.html
+    #[arg(long, value_name = "PATH")]
+    baseline: Option<PathBuf>,
+    /// After writing the report, publish its runs and compiles as JSON events to a message
+    /// queue: nats://HOST[:PORT]/SUBJECT or kafka://HOST[:PORT]/TOPIC (a Kafka REST Proxy); may
+    /// be repeated
+    #[cfg(feature = "forward")]
+    #[arg(long, value_name = "URL", value_parser = tlparse::forward::ForwardTarget::parse)]
+    forward: Vec<tlparse::forward::ForwardTarget>,
+    /// Also write _template_context.html, a reference of the fields every template is rendered
+    /// with (for template development)
+    #[arg(long, hide = true)]
+    template_context_docs: bool,
+    /// Preset defaults for a use case: `ci` writes only report.json, fails on unrecognized log
+    /// lines and doesn't open a browser; `deep-dive` writes the HTML report and report.json
+    /// with inductor provenance tracking.  Options given explicitly take precedence.
+    #[arg(long, value_enum)]
+    profile: Option<Profile>,
+    /// What to write: the HTML report, report.json (compile ids, artifact paths, metrics,
+    /// failures and restarts, for CI systems), or both with `html,json`
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "html")]
+    format: Vec<ReportFormat>,
+    /// Also write the whole report as one self-contained HTML file, tlparse_report.html, to
+    /// attach to bug reports and emails
+    #[arg(long, conflicts_with_all = ["follow", "watch"])]
+    single_file: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ReportFormat {
+    Html,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Profile {
+    Ci,
+    DeepDive,
+}
+
+impl Profile {
+    /// Option => the default value(s) it takes under this profile
+    fn defaults(self) -> &'static [(&'static str, &'static [&'static str])] {
+        match self {
+            Profile::Ci => &[
+                ("format", &["json"]),
+                ("strict", &["true"]),
+                ("no_browser", &["true"]),
+            ],
+            Profile::DeepDive => &[
+                ("format", &["html", "json"]),
+                ("inductor_provenance", &["true"]),
+            ],
+        }
+    }
+}
+
+/// The command line parser, with every named option also read from `TLPARSE_<OPTION>`
+fn command() -> clap::Command {
+    Cli::command().mut_args(|arg| {
+        if arg.is_positional() {
+            return arg;
+        }
+        let env = format!("TLPARSE_{}", arg.get_id().as_str().to_uppercase());
+        let arg = arg.env(env).hide_env(true);
+        // Flags take 1/0, yes/no, on/off and true/false from the environment
+        if matches!(arg.get_action(), ArgAction::SetTrue) {
+            arg.value_parser(FalseyValueParser::new())
+        } else {
+            arg
+        }
+    })
+}
+
+/// Parse the command line, environment variables and profile, in that order of precedence
+fn parse_cli() -> Cli {
+    let matches = command().get_matches();
+    let mut command = command();
+    if let Some(profile) = matches.get_one::<Profile>("profile") {
+        for (id, values) in profile.defaults() {
+            command = command.mut_arg(*id, |arg| arg.default_values(*values));
+        }
+    }
+    Cli::from_arg_matches(&command.get_matches()).unwrap_or_else(|e| e.exit())
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Summarize what a previous run generated (pages, artifacts by kind, sizes, parse time)
+    AboutRun {
+        /// Output directory of the run
+        out_dir: PathBuf,
+        /// Print the manifest as JSON instead
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check a previous run's report: internal links of every page resolve, JSON files parse
+    /// and the manifest matches the files.  Exits non-zero on problems
+    Selftest {
+        /// Output directory of the run
+        out_dir: PathBuf,
+    },
+    /// Compare the artifacts produced by two logs, per compile id
+    Diff {
+        old_log: PathBuf,
+        new_log: PathBuf,
+        /// Output directory, defaults to `tl_diff`
+        #[arg(short, default_value = "tl_diff")]
+        out: PathBuf,
+        /// Delete out directory if it already exists
+        #[arg(long)]
+        overwrite: bool,
+        /// Only emit compile ids and artifacts that differ; the identical rest is summarized
+        #[arg(long)]
+        only_changed: bool,
+        /// Don't open browser at the end
+        #[arg(long)]
+        no_browser: bool,
+    },
+    /// Merge previously generated reports (e.g. parsed on different nodes of a job) into one
+    /// multi-rank report, without their logs
+    Merge {
+        /// Output directories of tlparse runs, single-log or --all-ranks-html
+        #[arg(required = true, num_args = 2..)]
+        reports: Vec<PathBuf>,
+        /// Output directory, defaults to `tl_merged`
+        #[arg(short, default_value = "tl_merged")]
+        out: PathBuf,
+        /// Delete out directory if it already exists
+        #[arg(long)]
+        overwrite: bool,
+        /// Don't open browser at the end
+        #[arg(long)]
+        no_browser: bool,
+    },
+    /// Answer JSON-RPC requests for the compiles and guards of a source location (e.g.
+    /// getCompileForSource(file, line)) on stdio, for editor integrations
+    LspIsh {
+        /// tlparse output directory, or a log to parse first
+        report: PathBuf,
+        /// Talk JSON-RPC on stdin/stdout, the only transport for now
+        #[arg(long, required = true)]
+        stdio: bool,
+    },
+    /// Regenerate the HTML pages of a previous run from its report_model.json, without
+    /// re-parsing the log
+    Rerender {
+        /// Output directory of the run
+        out_dir: PathBuf,
+        /// Directory of templates replacing the built-in ones of the same name, e.g. index.html
+        #[arg(long)]
+        template_dir: Option<PathBuf>,
+        /// HTML to use as the header of every page instead of the one the run was parsed with
+        #[arg(long)]
+        custom_header_html: Option<String>,
+    },
+    /// Print a one line summary of each envelope in a log, optionally of one compile only,
+    /// and with --follow keep printing new envelopes as the log grows
+    Tail {
+        log: PathBuf,
+        /// Keep reading the log as it grows, until interrupted
+        #[arg(short, long)]
+        follow: bool,
+        /// Only show envelopes of this compile, e.g. 7/0 (every attempt) or 7/0_1
+        #[arg(long, value_parser = tlparse::tail::CompileFilter::parse)]
+        compile_id: Option<tlparse::tail::CompileFilter>,
+        /// Number of the envelopes already in the log to show first
+        #[arg(short = 'n', long, default_value_t = 10)]
+        lines: usize,
+    },
+    /// Print the payload of one artifact to stdout (JSON pretty-printed), without generating
+    /// a report
+    Cat {
+        log: PathBuf,
+        /// Only look at envelopes of this compile, e.g. 2/0 (every attempt) or 2/0_1
+        #[arg(long, value_parser = tlparse::tail::CompileFilter::parse)]
+        compile_id: Option<tlparse::tail::CompileFilter>,
+        /// Kind of the envelope (dynamo_output_graph, inductor_output_code, ...) or name of
+        /// the artifact (before_post_grad_graph, ...)
+        #[arg(long)]
+        artifact: String,
+        /// Print the Nth matching payload instead of the first
+        #[arg(long, default_value_t = 1)]
+        nth: usize,
+    },
+    /// Serve every report in a directory under /runs/<name>/, with a landing page listing
+    /// the runs; runs added to or removed from the directory show up without a restart
+    Workspace {
+        /// Directory whose subdirectories are tlparse output directories
+        dir: PathBuf,
+        /// Port for the HTTP server. If not specified, finds an available port.
+        #[arg(long)]
+        port: Option<u16>,
+        /// Require HTTP basic auth with these credentials
+        #[arg(long, value_name = "USER:PASSWORD")]
+        auth: Option<String>,
+        /// Log every request to FILE (default `-`, stdout) in the Common Log Format
+        #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+        access_log: Option<String>,
+    },
+}
+
+fn parse_path_mapping(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .ok_or_else(|| format!("expected FROM=TO, got `{s}`"))
+}
+
+fn parse_compile_label(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(pattern, label)| (pattern.to_string(), label.to_string()))
+        .ok_or_else(|| format!("expected PATTERN=LABEL, got `{s}`"))
+}
+
+/// `4G`, `512MiB`, `1.5g` or plain bytes; binary units
+fn parse_memory_size(s: &str) -> Result<u64, String> {
+    let number_end = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(number_end);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("expected a size like 4G or 512M, got `{s}`"))?;
+    let unit = unit.trim().to_ascii_lowercase();
+    let shift = match unit.trim_end_matches("ib").trim_end_matches('b') {
+        "" => 0,
+        "k" => 10,
+        "m" => 20,
+        "g" => 30,
+        "t" => 40,
+        _ => return Err(format!("unknown size unit `{unit}` in `{s}`")),
+    };
+    Ok((number * (1u64 << shift) as f64) as u64)
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{s}`"))
 }
 
 fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+    let cli = parse_cli();
+    tlparse::report_model::validate_templates()?;
+
+    match &cli.command {
+        Some(Command::AboutRun { out_dir, json }) => return about_run(out_dir, *json),
+        Some(Command::Selftest { out_dir }) => {
+            let report = tlparse::selftest::selftest(out_dir)?;
+            for problem in &report.problems {
+                eprintln!("{problem}");
+            }
+            if !report.is_ok() {
+                bail!("{}: {}", out_dir.display(), report.summary());
+            }
+            println!("{}: {}", out_dir.display(), report.summary());
+            return Ok(());
+        }
+        Some(Command::Diff {
+            old_log,
+            new_log,
+            out,
+            overwrite,
+            only_changed,
+            no_browser,
+        }) => {
+            let _lock = OutputLock::acquire(out, cli.break_lock)?;
+            setup_output_directory(out, *overwrite)?;
+            let config = ParseConfig::default();
+            let old = parse_path(old_log, &config)?;
+            let new = parse_path(new_log, &config)?;
+            let output = tlparse::diff::render_diff(old_log, new_log, old, new, *only_changed)?;
+            paths::write_output(out, output)?;
+            println!(
+                "Diff report generated: {}",
+                out.join("index.html").display()
+            );
+            if !*no_browser {
+                open_in_browser(&out.join("index.html"));
+            }
+            return Ok(());
+        }
+        Some(Command::Tail {
+            log,
+            follow,
+            compile_id,
+            lines,
+        }) => {
+            let options = tlparse::tail::TailOptions {
+                follow: *follow,
+                compile_id: compile_id.clone(),
+                lines: *lines,
+            };
+            return tlparse::tail::tail(log, &options, &mut std::io::stdout().lock());
+        }
+        Some(Command::Cat {
+            log,
+            compile_id,
+            artifact,
+            nth,
+        }) => {
+            let options = tlparse::cat::CatOptions {
+                compile_id: compile_id.clone(),
+                artifact: artifact.clone(),
+                nth: *nth,
+            };
+            return tlparse::cat::cat(log, &options, &mut std::io::stdout().lock());
+        }
+        Some(Command::Workspace {
+            dir,
+            port,
+            auth,
+            access_log,
+        }) => {
+            let options = ServeOptions {
+                auth: auth.as_deref().map(BasicAuth::parse).transpose()?,
+                access_log: access_log
+                    .as_deref()
+                    .map(AccessLog::open)
+                    .transpose()?
+                    .map(Arc::new),
+            };
+            return serve_workspace(dir, *port, &options);
+        }
+        Some(Command::Merge {
+            reports,
+            out,
+            overwrite,
+            no_browser,
+        }) => {
+            let _lock = OutputLock::acquire(out, cli.break_lock)?;
+            setup_output_directory(out, *overwrite)?;
+            let merged = tlparse::merge::merge_reports(reports, out)?;
+            for rank in &merged {
+                match rank.original_rank {
+                    Some(original) => println!(
+                        "rank {original} of {} → rank_{}",
+                        rank.source.display(),
+                        rank.rank
+                    ),
+                    None => println!("{} → rank_{}", rank.source.display(), rank.rank),
+                }
+            }
+            let ranks: Vec<String> = merged.iter().map(|m| m.rank.to_string()).collect();
+            let ctx = MultiRankContext {
+                css: "",
+                custom_header_html: "",
+                num_ranks: ranks.len(),
+                ranks,
+                qps: "",
+                has_chromium_events: false,
+                show_desync_warning: false,
+                compile_id_divergence: false,
+                diagnostics: Diagnostics::default(),
+            };
+            let landing_page_path =
+                generate_multi_rank_landing(&ParseConfig::default(), &ctx, out)?;
+            println!("Merged report generated: {}", landing_page_path.display());
+            if !*no_browser {
+                open_in_browser(&landing_page_path);
+            }
+            return Ok(());
+        }
+        Some(Command::LspIsh { report, .. }) => {
+            let index = tlparse::rpc::ReportIndex::load(report)?;
+            eprintln!(
+                "tlparse: serving {} compiles of {} on stdio",
+                index.len(),
+                report.display()
+            );
+            return index.serve(std::io::stdin().lock(), std::io::stdout().lock());
+        }
+        Some(Command::Rerender {
+            out_dir,
+            template_dir,
+            custom_header_html,
+        }) => {
+            let _lock = OutputLock::acquire(out_dir, cli.break_lock)?;
+            let pages = tlparse::report_model::rerender(
+                out_dir,
+                template_dir.as_deref(),
+                custom_header_html.as_deref(),
+            )?;
+            println!("Re-rendered {pages} pages in {}", out_dir.display());
+            return Ok(());
+        }
+        None => {}
+    }
+    let input_path = match &cli.vllm_merge {
+        Some(dir) => dir.clone(),
+        None => cli.path.context("Missing log path")?,
+    };
+    if cli.jobs.is_some() && !cli.all_ranks_html && cli.vllm_merge.is_none() {
+        bail!("--jobs requires --all-ranks-html or --vllm-merge");
+    }
 
     // Early validation of incompatible flags
     if cli.all_ranks_html && cli.latest {
         bail!("--latest cannot be used with --all-ranks-html");
     }
+    if cli.lazy_artifacts.is_some() && !cli.serve {
+        bail!("--lazy-artifacts requires --serve");
+    }
+    // Checked before parsing, so a typo doesn't surface only after a long parse
+    let serve_options = ServeOptions {
+        auth: cli.auth.as_deref().map(BasicAuth::parse).transpose()?,
+        access_log: cli
+            .access_log
+            .as_deref()
+            .map(AccessLog::open)
+            .transpose()?
+            .map(Arc::new),
+    };
 
     // --serve implies --no-browser (we'll serve instead of opening)
-    let open_browser = !cli.no_browser && !cli.serve;
+    // Nothing to open without the HTML
+    let open_browser = !cli.no_browser && !cli.serve && cli.format.contains(&ReportFormat::Html);
 
     let path = if cli.latest {
-        let input_path = cli.path;
         // Path should be a directory
         if !input_path.is_dir() {
             bail!(
@@ -98,10 +649,38 @@ fn main() -> anyhow::Result<()> {
         };
         last_modified_file.path()
     } else {
-        cli.path
+        input_path
+    };
+
+    // A tarball of a trace directory is unpacked first, then parsed like the logs in it
+    let mut all_ranks_html = cli.all_ranks_html;
+    let mut _unpacked = None;
+    let path = if path.is_file() && archive::is_tar(&path)? {
+        if cli.follow {
+            bail!("--follow can't follow the logs inside {}", path.display());
+        }
+        let dir = archive::UnpackDir::create()?;
+        let files = archive::unpack(&path, &dir.path)?;
+        let logs = archive::find_logs(&path, &files)?;
+        println!(
+            "Unpacked {} files from {} into {}",
+            files.len(),
+            path.display(),
+            dir.path.display()
+        );
+        _unpacked = Some(dir);
+        match logs {
+            archive::ArchiveLogs::Ranks(dir) => {
+                all_ranks_html = true;
+                dir
+            }
+            archive::ArchiveLogs::Single(log) => log,
+        }
+    } else {
+        path
     };
 
-    let config = ParseConfig {
+    let mut config = ParseConfig {
         strict: cli.strict,
         strict_compile_id: cli.strict_compile_id,
         custom_parsers: Vec::new(),
@@ -110,10 +689,86 @@ fn main() -> anyhow::Result<()> {
         plain_text: cli.plain_text,
         export: cli.export,
         inductor_provenance: cli.inductor_provenance,
+        derived_metrics: Vec::new(),
+        base_url: cli.base_url.clone(),
+        lazy_artifact_min_bytes: cli.lazy_artifacts,
+        stack_render: StackRenderOptions {
+            collapse_repeated: cli.collapse_repeated_frames,
+            strip_site_packages: cli.strip_site_packages,
+            path_map: cli.path_map,
+        },
+        eager_ops: cli
+            .eager_ops
+            .as_deref()
+            .map(tlparse::op_coverage::load_eager_ops)
+            .transpose()?,
+        progress: None,
+        sampling: (cli.sample_rate.is_some() || cli.max_compiles.is_some()).then_some(
+            tlparse::sampling::SamplingConfig {
+                rate: cli.sample_rate,
+                max_compiles: cli.max_compiles,
+            },
+        ),
+        metadata: cli.metadata.into_iter().collect(),
+        hide_framework_frames: cli.hide_framework_frames,
+        baseline: cli
+            .baseline
+            .as_deref()
+            .map(tlparse::cache_keys::load_baseline)
+            .transpose()?,
+        low_memory: cli.low_memory,
+        max_memory: cli.max_memory,
+        config_docs: match &cli.config_docs {
+            Some(path) => tlparse::config_docs::ConfigDocs::load(path)?,
+            None => tlparse::config_docs::ConfigDocs::bundled(),
+        },
+        html: cli.format.contains(&ReportFormat::Html),
+        report_json: cli.format.contains(&ReportFormat::Json),
+        report_model: cli.report_model,
+        collectives_parity: false,
+        mtime_from_log: cli.mtime_from_log,
+        compile_labels: Default::default(),
     };
+    for (pattern, label) in &cli.compile_label {
+        config.compile_labels.add(pattern, label)?;
+    }
+    if let Some(config_path) = &cli.config {
+        ConfigFile::load(config_path)?.apply(&mut config)?;
+    }
+    if let Some(parser_config) = &cli.parser_config {
+        config.custom_parsers = ParserConfigFile::load(parser_config)?.parsers()?;
+    }
 
-    if cli.all_ranks_html {
-        handle_all_ranks(&config, path, cli.out.clone(), cli.overwrite, open_browser)?;
+    if cli.watch {
+        config.custom_header_html.push_str(&format!(
+            "<p>Watching for changes: see <a href='{}'>what's new</a> since watching started.</p>",
+            tlparse::watch::CHANGES_FILENAME
+        ));
+    }
+    let log_path = path.clone();
+
+    let lock = OutputLock::acquire(&cli.out, cli.break_lock)?;
+    if cli.follow {
+        setup_output_directory(&cli.out, cli.overwrite)?;
+        follow(&config, &log_path, &cli.out, open_browser)?;
+    } else if cli.vllm_merge.is_some() {
+        handle_vllm_merge(
+            &config,
+            &path,
+            &cli.out,
+            cli.overwrite,
+            open_browser,
+            parallel_jobs(cli.jobs),
+        )?;
+    } else if all_ranks_html {
+        handle_all_ranks(
+            &mut config,
+            path,
+            cli.out.clone(),
+            cli.overwrite,
+            open_browser,
+            parallel_jobs(cli.jobs),
+        )?;
     } else {
         handle_one_rank(
             &config,
@@ -125,13 +780,156 @@ fn main() -> anyhow::Result<()> {
         )?;
     }
 
-    if cli.serve {
-        serve_directory(&cli.out, cli.port)?;
+    if cli.single_file {
+        let bundle = tlparse::bundle::write_bundle(&cli.out)?;
+        println!("Wrote the report as one file to {}", bundle.display());
+    }
+
+    #[cfg(feature = "forward")]
+    if !cli.forward.is_empty() {
+        let events = tlparse::forward::events(&cli.out)?;
+        for target in &cli.forward {
+            target.send(&events)?;
+            println!("Forwarded {} events to {target}", events.len());
+        }
+    }
+
+    if cli.template_context_docs {
+        fs::write(
+            cli.out.join("_template_context.html"),
+            tlparse::template_docs::render_template_context_docs()?,
+        )?;
+    }
+
+    // Watch mode keeps regenerating the report, so it keeps the lock until interrupted
+    if cli.watch {
+        watch_and_serve(
+            &config,
+            &log_path,
+            &cli.out,
+            cli.port,
+            serve_options,
+            cli.refresh_interval,
+        )?;
+    } else if cli.serve {
+        drop(lock);
+        serve_directory(&cli.out, cli.port, &serve_options)?;
     }
 
     Ok(())
 }
 
+/// Serve `out_dir` while regenerating it whenever `log_path` changes, recording what each
+/// regeneration added in the changes feed.  Unless `refresh_interval` is 0 the index reloads
+/// itself after a regeneration.
+fn watch_and_serve(
+    config: &ParseConfig,
+    log_path: &PathBuf,
+    out_dir: &PathBuf,
+    port: Option<u16>,
+    serve_options: ServeOptions,
+    refresh_interval: u64,
+) -> anyhow::Result<()> {
+    let add_auto_refresh = || {
+        if refresh_interval > 0 {
+            tlparse::watch::add_auto_refresh(out_dir, refresh_interval)?;
+        }
+        anyhow::Ok(())
+    };
+    add_auto_refresh()?;
+    let mut feed = ChangeFeed::new(log_path);
+    let mut snapshot = Snapshot::load(out_dir)?;
+    feed.write(out_dir)?;
+
+    let serve_dir = out_dir.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = serve_directory(&serve_dir, port, &serve_options) {
+            eprintln!("{e:#}");
+            std::process::exit(1);
+        }
+    });
+
+    let stamp = |path: &PathBuf| {
+        fs::metadata(path)
+            .ok()
+            .map(|m| (m.len(), m.modified().ok()))
+    };
+    let mut last_stamp = stamp(log_path);
+    loop {
+        std::thread::sleep(Duration::from_secs(2));
+        let current = stamp(log_path);
+        if current == last_stamp {
+            continue;
+        }
+        last_stamp = current;
+        // Files are overwritten in place so the server never sees a missing report
+        if let Err(e) = parse_and_write_output(config, log_path, out_dir) {
+            eprintln!("Failed to regenerate report: {e:#}");
+            continue;
+        }
+        add_auto_refresh()?;
+        let after = Snapshot::load(out_dir)?;
+        let added = feed.record(&snapshot, &after);
+        snapshot = after;
+        feed.write(out_dir)?;
+        println!(
+            "{} changed: {added} new artifacts and warnings (see {})",
+            log_path.display(),
+            out_dir.join(tlparse::watch::CHANGES_FILENAME).display()
+        );
+        if let Some(warning) = &feed.recompile_warning {
+            eprintln!("Warning: {warning}");
+        }
+    }
+}
+
+/// Write the report of `log_path` into `out_dir` and keep it up to date as the log grows,
+/// until interrupted
+fn follow(
+    config: &ParseConfig,
+    log_path: &PathBuf,
+    out_dir: &Path,
+    open_browser: bool,
+) -> anyhow::Result<()> {
+    let mut first = true;
+    tlparse::parse_stream(log_path, config, |update| {
+        paths::write_output(out_dir, update.changed_files().cloned().collect())?;
+        for file in &update.removed {
+            let _ = fs::remove_file(out_dir.join(file));
+        }
+        let mut manifest = Manifest::new(log_path, &update.output, update.parse_duration);
+        manifest.metadata = config.metadata.clone();
+        if config.mtime_from_log {
+            manifest.set_mtimes(out_dir)?;
+        }
+        paths::write_atomic(
+            out_dir.join(MANIFEST_FILENAME),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+        if first {
+            println!(
+                "Wrote {} files to {}; following {} for new lines (Ctrl-C to stop)",
+                update.changed.len(),
+                out_dir.display(),
+                log_path.display()
+            );
+            if open_browser {
+                open_in_browser(&out_dir.join("index.html"));
+            }
+            first = false;
+        } else {
+            println!(
+                "{} grew to {} bytes: rewrote {} files, removed {}",
+                log_path.display(),
+                update.log_bytes,
+                update.changed.len(),
+                update.removed.len()
+            );
+        }
+        Ok(true)
+    })
+}
+
 /// Create the output directory
 fn setup_output_directory(out_path: &PathBuf, overwrite: bool) -> anyhow::Result<()> {
     if out_path.exists() {
@@ -153,16 +951,71 @@ fn parse_and_write_output(
     log_path: &PathBuf,
     output_dir: &PathBuf,
 ) -> anyhow::Result<PathBuf> {
-    let output = parse_path(log_path, config)?;
+    let start = Instant::now();
+    // A strict parse that failed still leaves its report, strict_failures.json included
+    let (output, strict_error) = match parse_path(log_path, config) {
+        Ok(output) => (output, None),
+        Err(err) => match err.downcast::<StrictError>() {
+            Ok(StrictError { message, output }) => (output, Some(message)),
+            Err(err) => return Err(err),
+        },
+    };
+    let mut manifest = Manifest::new(log_path, &output, start.elapsed());
+    manifest.metadata = config.metadata.clone();
+    paths::write_output(output_dir, output)?;
+    // With --serve --lazy-artifacts the payloads stay deferred until requested
+    if (config.low_memory || config.max_memory.is_some())
+        && config.lazy_artifact_min_bytes.is_none()
+    {
+        let extracted = tlparse::lazy::materialize_all(output_dir)?;
+        manifest
+            .files
+            .retain(|f| f.path != tlparse::lazy::ARTIFACT_INDEX_FILENAME);
+        let extracted: Vec<ManifestEntry> = extracted
+            .into_iter()
+            .map(|(path, bytes)| ManifestEntry {
+                kind: tlparse::manifest::artifact_kind(Path::new(&path)),
+                log_time: manifest.log_time(&path),
+                path,
+                bytes,
+            })
+            .collect();
+        manifest.files.extend(extracted);
+    }
+    if config.mtime_from_log {
+        manifest.set_mtimes(output_dir)?;
+    }
+    paths::write_atomic(
+        output_dir.join(MANIFEST_FILENAME),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    if let Some(message) = strict_error {
+        bail!(
+            "{message}; see {}",
+            output_dir.join(STRICT_FAILURES_FILENAME).display()
+        );
+    }
+    Ok(output_dir.join("index.html"))
+}
 
-    for (filename, content) in output {
-        let out_path = output_dir.join(&filename);
-        if let Some(dir) = out_path.parent() {
-            fs::create_dir_all(dir)?;
+/// Print the manifest(s) of a previous run, one per rank for multi-rank output
+fn about_run(out_dir: &Path, json: bool) -> anyhow::Result<()> {
+    let reports = tlparse::manifest::load_reports(out_dir)?;
+    if json {
+        let manifests: Vec<&Manifest> = reports.iter().map(|(_, m)| m).collect();
+        println!("{}", serde_json::to_string_pretty(&manifests)?);
+        return Ok(());
+    }
+    for (i, (dir, manifest)) in reports.iter().enumerate() {
+        if i > 0 {
+            println!();
         }
-        fs::write(out_path, content)?;
+        if reports.len() > 1 {
+            println!("== {} ==", dir.display());
+        }
+        print!("{}", manifest.summary());
     }
-    Ok(output_dir.join("index.html"))
+    Ok(())
 }
 
 fn handle_one_rank(
@@ -195,48 +1048,60 @@ fn handle_one_rank(
     let main_output_file = parse_and_write_output(cfg, &log_path, &out_dir)?;
 
     if open_browser {
-        opener::open(&main_output_file)?;
+        open_in_browser(&main_output_file);
     }
     Ok(())
 }
 
 fn handle_all_ranks(
-    cfg: &ParseConfig,
+    cfg: &mut ParseConfig,
     path: PathBuf,
     out_path: PathBuf,
     overwrite: bool,
     open_browser: bool,
+    jobs: usize,
 ) -> anyhow::Result<()> {
     let input_dir = path;
-    if !input_dir.is_dir() {
+    // A single log with the lines of every rank, each prefixed with its rank, is split into
+    // one log per rank first
+    let prefixed_log = input_dir.is_file() && rank_prefix::has_rank_prefixes(&input_dir)?;
+    if !input_dir.is_dir() && !prefixed_log {
         bail!(
-            "Input path {} must be a directory when using --all-ranks-html",
+            "Input path {} must be a directory, or a log with [rankN]: line prefixes, when using --all-ranks-html",
             input_dir.display()
         );
     }
 
     setup_output_directory(&out_path, overwrite)?;
 
+    let input_dir = if prefixed_log {
+        let split_dir = out_path.join(rank_prefix::SPLIT_LOGS_DIR);
+        let logs = rank_prefix::split_by_rank(&input_dir, &split_dir)?;
+        println!(
+            "Split {} into {} rank logs in {}",
+            input_dir.display(),
+            logs.len(),
+            split_dir.display()
+        );
+        split_dir
+    } else {
+        input_dir
+    };
+
     // Discover rank log files
-    let rank_logs: Vec<_> = std::fs::read_dir(&input_dir)?
+    let mut rank_logs: Vec<_> = std::fs::read_dir(&input_dir)?
         .flatten()
         .filter_map(|entry| {
             let path = entry.path();
             if !path.is_file() {
                 return None;
             }
-            let filename = path.file_name()?.to_str()?;
-            filename
-                .strip_prefix("dedicated_log_torch_trace_rank_")?
-                .strip_suffix(".log")?
-                .split('_')
-                .next()?
-                .parse::<u32>()
-                .ok()
-                .map(|rank_num| (path.clone(), rank_num))
+            archive::rank_of_log(&path).map(|rank_num| (path.clone(), rank_num))
         })
         .collect();
 
+    rank_logs.sort_by_key(|(path, rank)| (*rank, path.clone()));
+
     if rank_logs.is_empty() {
         bail!(
             "No rank log files found in directory {}",
@@ -244,16 +1109,24 @@ fn handle_all_ranks(
         );
     }
 
+    // Flag empty, truncated and missing rank logs up front rather than failing half way through
+    let integrity = check_rank_logs(&rank_logs)?;
+    for issue in &integrity {
+        eprintln!("Warning: rank {}: {}", issue.rank, issue.problem);
+    }
+
     // Extract rank numbers, sort numerically, then convert to strings for HTML generation
     let mut rank_nums: Vec<u32> = rank_logs.iter().map(|(_, rank)| *rank).collect();
     rank_nums.sort_unstable();
     let sorted_ranks: Vec<String> = rank_nums.iter().map(|r| r.to_string()).collect();
 
-    for (log_path, rank_num) in rank_logs {
-        let subdir = out_path.join(format!("rank_{rank_num}"));
-        println!("Processing rank {rank_num} → {}", subdir.display());
-        handle_one_rank(cfg, log_path, false, subdir, false, overwrite)?;
-    }
+    cfg.collectives_parity = true;
+    let cfg: &ParseConfig = cfg;
+    let logs: Vec<(PathBuf, String, String)> = rank_logs
+        .iter()
+        .map(|(path, rank)| (path.clone(), format!("rank_{rank}"), format!("rank {rank}")))
+        .collect();
+    parse_logs(cfg, &logs, &out_path, overwrite, jobs)?;
     // Build a minimal context; values other than ranks are recomputed inside the library API
     let ctx = MultiRankContext {
         css: "",
@@ -264,18 +1137,124 @@ fn handle_all_ranks(
         has_chromium_events: false,
         show_desync_warning: false,
         compile_id_divergence: false,
-        diagnostics: Default::default(),
+        diagnostics: Diagnostics {
+            integrity,
+            ..Default::default()
+        },
     };
 
     let landing_page_path = generate_multi_rank_landing(cfg, &ctx, &out_path)?;
 
     if open_browser {
-        opener::open(&landing_page_path)?;
+        open_in_browser(&landing_page_path);
     }
 
     Ok(())
 }
 
+/// Threads to parse several logs with: `--jobs`, else one per CPU
+fn parallel_jobs(jobs: Option<NonZeroUsize>) -> usize {
+    jobs.or_else(|| std::thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get)
+}
+
+/// Parse each of `logs` (log, subdirectory of `out_path`, name) into its subdirectory.  Logs
+/// are handed out to `jobs` threads in order; once one fails no more are started
+fn parse_logs(
+    cfg: &ParseConfig,
+    logs: &[(PathBuf, String, String)],
+    out_path: &Path,
+    overwrite: bool,
+    jobs: usize,
+) -> anyhow::Result<()> {
+    let next_log = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let parse_logs = || -> anyhow::Result<()> {
+        while !failed.load(Ordering::Relaxed) {
+            let Some((log_path, dir, name)) = logs.get(next_log.fetch_add(1, Ordering::Relaxed))
+            else {
+                break;
+            };
+            let subdir = out_path.join(dir);
+            println!("Processing {name} → {}", subdir.display());
+            // Each report lives under its subdirectory, so its links need that prefix too
+            let mut log_cfg = cfg.clone();
+            log_cfg.base_url = cfg
+                .base_url
+                .as_ref()
+                .map(|b| format!("{}/{dir}", b.trim_end_matches('/')));
+            if let Err(e) =
+                handle_one_rank(&log_cfg, log_path.clone(), false, subdir, false, overwrite)
+            {
+                failed.store(true, Ordering::Relaxed);
+                return Err(e.context(format!("Parsing {name}")));
+            }
+        }
+        Ok(())
+    };
+    let results: Vec<anyhow::Result<()>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.clamp(1, logs.len()))
+            .map(|_| scope.spawn(parse_logs))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    });
+    results.into_iter().collect()
+}
+
+/// Parse each vLLM worker log of `dir` into `out_path/worker_N/`, then write the landing page
+/// grouping the workers' subgraphs and cudagraph captures
+fn handle_vllm_merge(
+    cfg: &ParseConfig,
+    dir: &Path,
+    out_path: &PathBuf,
+    overwrite: bool,
+    open_browser: bool,
+    jobs: usize,
+) -> anyhow::Result<()> {
+    let workers = tlparse::vllm::merge::worker_logs(dir)?;
+    setup_output_directory(out_path, overwrite)?;
+    let logs: Vec<(PathBuf, String, String)> = workers
+        .iter()
+        .map(|(worker, path)| {
+            (
+                path.clone(),
+                tlparse::vllm::merge::worker_dir(*worker),
+                format!("worker {worker}"),
+            )
+        })
+        .collect();
+    parse_logs(cfg, &logs, out_path, overwrite, jobs)?;
+    let landing_page_path = tlparse::vllm::merge::write_merged_summary(out_path, &workers, cfg)?;
+    println!(
+        "Merged the vLLM summaries of {} workers: {}",
+        workers.len(),
+        landing_page_path.display()
+    );
+    if open_browser {
+        open_in_browser(&landing_page_path);
+    }
+    Ok(())
+}
+
+/// Open a report in the default browser.  Not finding one (e.g. over SSH or in a Windows
+/// service) is not an error: the report has been written and its path is printed instead.
+fn open_in_browser(path: &Path) {
+    let path = paths::browser_path(path);
+    if let Err(err) = opener::open(&path) {
+        eprintln!(
+            "Could not open a browser ({err}); open {} to view the report",
+            path.display()
+        );
+    }
+}
+
 /// Find an available port in the given range
 fn find_available_port(start: u16, end: u16) -> anyhow::Result<u16> {
     use std::net::TcpListener;
@@ -287,8 +1266,24 @@ fn find_available_port(start: u16, end: u16) -> anyhow::Result<u16> {
     bail!("No available ports in range {}-{}", start, end - 1)
 }
 
-/// Serve a directory over HTTP
-fn serve_directory(dir: &PathBuf, port: Option<u16>) -> anyhow::Result<()> {
+/// `--auth` and `--access-log` of the server
+#[derive(Clone, Default)]
+struct ServeOptions {
+    auth: Option<BasicAuth>,
+    access_log: Option<Arc<AccessLog>>,
+}
+
+type HttpResponse = tiny_http::Response<std::io::Cursor<Vec<u8>>>;
+
+fn not_found() -> HttpResponse {
+    tiny_http::Response::from_string("404 Not Found").with_status_code(404)
+}
+
+fn internal_error() -> HttpResponse {
+    tiny_http::Response::from_string("500 Internal Server Error").with_status_code(500)
+}
+
+fn start_server(port: Option<u16>) -> anyhow::Result<(tiny_http::Server, u16)> {
     let port = match port {
         Some(p) => p,
         None => find_available_port(8000, 8100)?,
@@ -297,68 +1292,255 @@ fn serve_directory(dir: &PathBuf, port: Option<u16>) -> anyhow::Result<()> {
     let addr = format!("0.0.0.0:{}", port);
     let server = tiny_http::Server::http(&addr)
         .map_err(|e| anyhow::anyhow!("Failed to start server on {}: {}", addr, e))?;
+    Ok((server, port))
+}
+
+/// Answer every request with `respond(url)`, behind `--auth` and recorded in `--access-log`
+fn handle_requests(
+    server: tiny_http::Server,
+    options: &ServeOptions,
+    respond: impl Fn(&str, &CacheHeaders) -> HttpResponse,
+) {
+    for request in server.incoming_requests() {
+        let request_header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv(name))
+                .map(|h| h.value.to_string())
+        };
+        let cache = CacheHeaders {
+            if_none_match: request_header("If-None-Match"),
+            if_modified_since: request_header("If-Modified-Since"),
+        };
+        let authorized = options.auth.as_ref().is_none_or(|auth| {
+            auth.check(
+                request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("Authorization"))
+                    .map(|h| h.value.as_str()),
+            )
+        });
+        let response = if authorized {
+            respond(request.url(), &cache)
+        } else {
+            tiny_http::Response::from_string("401 Unauthorized")
+                .with_status_code(401)
+                .with_header(
+                    tiny_http::Header::from_bytes(
+                        &b"WWW-Authenticate"[..],
+                        format!("Basic realm=\"{}\"", tlparse::serve::AUTH_REALM).as_bytes(),
+                    )
+                    .unwrap(),
+                )
+        };
+        if let Some(log) = &options.access_log {
+            log.record(
+                request.remote_addr().copied(),
+                options
+                    .auth
+                    .as_ref()
+                    .filter(|_| authorized)
+                    .map(|a| a.user()),
+                &format!(
+                    "{} {} HTTP/{}",
+                    request.method(),
+                    request.url(),
+                    request.http_version()
+                ),
+                response.status_code().0,
+                response.data_length(),
+            );
+        }
+        let _ = request.respond(response);
+    }
+}
+
+/// Serve a directory over HTTP
+fn serve_directory(dir: &PathBuf, port: Option<u16>, options: &ServeOptions) -> anyhow::Result<()> {
+    let (server, port) = start_server(port)?;
 
     let url = format!("http://localhost:{}/", port);
     println!("Serving {} at {}", dir.display(), url);
+    println!(
+        "Browse every generated file at {url}{}/",
+        tlparse::browse::BROWSE_PREFIX
+    );
+    if options.auth.is_none() {
+        println!("Anyone who can reach port {port} can read the report; pass --auth to require a password");
+    }
     println!("Press Ctrl+C to stop");
 
     let dir = dir.canonicalize()?;
+    handle_requests(server, options, |url, cache| {
+        respond(&dir, &request_path(url), "", cache)
+    });
 
-    for request in server.incoming_requests() {
-        let url_path = request.url().trim_start_matches('/');
-        // URL decode the path
-        let url_path = urlencoding_decode(url_path);
-        let file_path = if url_path.is_empty() {
-            dir.join("index.html")
-        } else {
-            dir.join(&url_path)
-        };
+    Ok(())
+}
+
+/// Serve every report in `workspace` under `/runs/<name>/`, with the list of runs at `/`
+fn serve_workspace(
+    workspace: &Path,
+    port: Option<u16>,
+    options: &ServeOptions,
+) -> anyhow::Result<()> {
+    if !workspace.is_dir() {
+        bail!("Workspace {} is not a directory", workspace.display());
+    }
+    let (server, port) = start_server(port)?;
+
+    println!(
+        "Serving the runs in {} at http://localhost:{port}/",
+        workspace.display()
+    );
+    println!("Add a run by parsing a log into a new subdirectory, remove one by deleting it");
+    if options.auth.is_none() {
+        println!("Anyone who can reach port {port} can read the reports; pass --auth to require a password");
+    }
+    println!("Press Ctrl+C to stop");
 
-        // Security: ensure the path is within the served directory
-        let file_path = match file_path.canonicalize() {
-            Ok(p) if p.starts_with(&dir) => p,
-            _ => {
-                let response =
-                    tiny_http::Response::from_string("404 Not Found").with_status_code(404);
-                let _ = request.respond(response);
-                continue;
+    let workspace = workspace.canonicalize()?;
+    handle_requests(server, options, |url, cache| {
+        use tlparse::workspace::Route;
+        match tlparse::workspace::route(&workspace, &request_path(url)) {
+            Route::Landing => match tlparse::workspace::render_landing(&workspace) {
+                Ok(html) => html_response(html),
+                Err(e) => {
+                    eprintln!("Failed to list the runs of {}: {e:#}", workspace.display());
+                    internal_error()
+                }
+            },
+            Route::Redirect(location) => tiny_http::Response::from_string("")
+                .with_status_code(301)
+                .with_header(
+                    tiny_http::Header::from_bytes(&b"Location"[..], location.as_bytes()).unwrap(),
+                ),
+            Route::Run { name, dir, path } => match dir.canonicalize() {
+                Ok(dir) => respond(
+                    &dir,
+                    &path,
+                    &format!("/{}/{name}", tlparse::workspace::RUNS_PREFIX),
+                    cache,
+                ),
+                Err(_) => not_found(),
+            },
+            Route::NotFound => not_found(),
+        }
+    });
+
+    Ok(())
+}
+
+fn html_response(html: String) -> HttpResponse {
+    tiny_http::Response::from_string(html).with_header(
+        tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..])
+            .unwrap(),
+    )
+}
+
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+/// The response to a GET of the decoded `url_path` (without its leading `/`) from the served
+/// directory `dir`, which is served under `base`.  A directory without an `index.html` gets a
+/// listing, and a file the client has a current copy of `304 Not Modified`
+fn respond(dir: &Path, url_path: &str, base: &str, cache: &CacheHeaders) -> HttpResponse {
+    if let Some(browse_path) = url_path
+        .strip_prefix(tlparse::browse::BROWSE_PREFIX)
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+    {
+        return match tlparse::browse::render_listing(
+            dir,
+            Path::new(browse_path.trim_matches('/')),
+            base,
+        ) {
+            Ok(Some(html)) => html_response(html),
+            Ok(None) => not_found(),
+            Err(e) => {
+                eprintln!("Failed to list {url_path}: {e:#}");
+                internal_error()
             }
         };
+    }
+    let file_path = dir.join(url_path);
 
-        if file_path.is_file() {
-            match fs::File::open(&file_path) {
-                Ok(mut file) => {
-                    let mut content = Vec::new();
-                    if file.read_to_end(&mut content).is_ok() {
-                        let content_type = guess_content_type(&file_path);
-                        let response = tiny_http::Response::from_data(content).with_header(
-                            tiny_http::Header::from_bytes(
-                                &b"Content-Type"[..],
-                                content_type.as_bytes(),
-                            )
-                            .unwrap(),
-                        );
-                        let _ = request.respond(response);
-                    } else {
-                        let response =
-                            tiny_http::Response::from_string("500 Internal Server Error")
-                                .with_status_code(500);
-                        let _ = request.respond(response);
-                    }
-                }
-                Err(_) => {
-                    let response =
-                        tiny_http::Response::from_string("404 Not Found").with_status_code(404);
-                    let _ = request.respond(response);
+    // Artifacts deferred with --lazy-artifacts are extracted from the log on first request
+    if !file_path.exists() {
+        if let Err(e) = tlparse::lazy::materialize_lazy_artifact(dir, Path::new(url_path)) {
+            eprintln!("Failed to extract {url_path}: {e:#}");
+        }
+    }
+
+    // Security: ensure the path is within the served directory
+    let file_path = match file_path.canonicalize() {
+        Ok(p) if p.starts_with(dir) => p,
+        _ => return not_found(),
+    };
+
+    if file_path.is_dir() {
+        // Relative links of the directory's page resolve against its URL only with the `/`
+        if !url_path.is_empty() && !url_path.ends_with('/') {
+            return tiny_http::Response::from_string("")
+                .with_status_code(301)
+                .with_header(header("Location", &format!("{base}/{url_path}/")));
+        }
+        let index = file_path.join("index.html");
+        if !index.is_file() {
+            let rel = file_path.strip_prefix(dir).unwrap();
+            return match tlparse::browse::render_directory(dir, rel, base) {
+                Ok(Some(html)) => html_response(html),
+                Ok(None) => not_found(),
+                Err(e) => {
+                    eprintln!("Failed to list {url_path}: {e:#}");
+                    internal_error()
                 }
+            };
+        }
+        return respond_file(&index, cache);
+    }
+    if !file_path.is_file() {
+        return not_found();
+    }
+    respond_file(&file_path, cache)
+}
+
+fn respond_file(file_path: &PathBuf, cache: &CacheHeaders) -> HttpResponse {
+    let validators = fs::metadata(file_path)
+        .ok()
+        .and_then(|m| Validators::of(&m));
+    let with_validators = |response: HttpResponse| match &validators {
+        Some(v) => response
+            .with_header(header("ETag", &v.etag))
+            .with_header(header("Last-Modified", &v.last_modified)),
+        None => response,
+    };
+    if validators.as_ref().is_some_and(|v| cache.is_fresh(v)) {
+        return with_validators(tiny_http::Response::from_string("").with_status_code(304));
+    }
+    match fs::File::open(file_path) {
+        Ok(mut file) => {
+            let mut content = Vec::new();
+            if file.read_to_end(&mut content).is_ok() {
+                let content_type = guess_content_type(file_path);
+                with_validators(
+                    tiny_http::Response::from_data(content)
+                        .with_header(header("Content-Type", &content_type)),
+                )
+            } else {
+                internal_error()
             }
-        } else {
-            let response = tiny_http::Response::from_string("404 Not Found").with_status_code(404);
-            let _ = request.respond(response);
         }
+        Err(_) => not_found(),
     }
+}
 
-    Ok(())
+/// The decoded path of a request URL, without its leading `/`, query string or fragment
+fn request_path(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    urlencoding_decode(path.trim_start_matches('/'))
 }
 
 /// Simple URL decoding (handles %XX sequences)
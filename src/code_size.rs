@@ -0,0 +1,118 @@
+//! Disk usage of each compile: bytes of Inductor generated code (`inductor_output_code`
+//! payloads) and of all the payloads artifacts were written from.  The pages rendered around
+//! them don't count: how big those come out is up to the templates, not the compile.
+//!
+//! A compile generating far more code than the others usually compiles for longer and bloats
+//! the cache, so compiles with at least `OUTLIER_FACTOR` times the median generated code of the
+//! run are flagged on the index.
+
+use crate::manifest::size_heat;
+use crate::types::{CodeSizeOutlier, CompileDiskUsage, CompileId, FxIndexMap};
+use crate::units;
+
+/// How many times the median generated code makes a compile an outlier
+const OUTLIER_FACTOR: u64 = 4;
+/// Below this much generated code nothing is an outlier, however small the median
+const OUTLIER_MIN_BYTES: u64 = 16 << 10;
+/// Compiles with generated code needed for a meaningful median
+const OUTLIER_MIN_COMPILES: usize = 3;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CompileSize {
+    pub generated_code_bytes: u64,
+    pub artifact_bytes: u64,
+}
+
+/// Compile id (directory key) -> its disk usage
+#[derive(Debug, Default)]
+pub struct CodeSizeIndex {
+    sizes: FxIndexMap<Option<CompileId>, CompileSize>,
+    median_code_bytes: Option<u64>,
+}
+
+impl CodeSizeIndex {
+    /// An `inductor_output_code` payload of `compile_id`
+    pub fn add_code(&mut self, compile_id: &Option<CompileId>, code: &str) {
+        self.sizes
+            .entry(compile_id.clone())
+            .or_default()
+            .generated_code_bytes += code.len() as u64;
+    }
+
+    /// A payload of `compile_id` that artifacts were written from
+    pub fn add_artifact(&mut self, compile_id: &Option<CompileId>, payload: &str) {
+        self.sizes
+            .entry(compile_id.clone())
+            .or_default()
+            .artifact_bytes += payload.len() as u64;
+    }
+
+    /// Take the median generated code, once every payload has been added
+    pub fn finish(&mut self) {
+        let mut code: Vec<u64> = self
+            .sizes
+            .values()
+            .map(|s| s.generated_code_bytes)
+            .filter(|b| *b > 0)
+            .collect();
+        code.sort_unstable();
+        self.median_code_bytes = (code.len() >= OUTLIER_MIN_COMPILES).then(|| code[code.len() / 2]);
+    }
+
+    pub fn get(&self, compile_id: &Option<CompileId>) -> Option<CompileSize> {
+        self.sizes.get(compile_id).copied()
+    }
+
+    pub fn is_outlier(&self, compile_id: &Option<CompileId>) -> bool {
+        let (Some(median), Some(size)) = (self.median_code_bytes, self.sizes.get(compile_id))
+        else {
+            return false;
+        };
+        size.generated_code_bytes >= OUTLIER_MIN_BYTES
+            && size.generated_code_bytes >= median * OUTLIER_FACTOR
+    }
+
+    /// Badge next to the compile id on the index
+    pub fn disk_usage(&self, compile_id: &Option<CompileId>) -> Option<CompileDiskUsage> {
+        let size = self.get(compile_id)?;
+        Some(CompileDiskUsage {
+            generated_code: (size.generated_code_bytes > 0)
+                .then(|| units::bytes(size.generated_code_bytes)),
+            artifacts: units::bytes(size.artifact_bytes),
+            size_heat: if self.is_outlier(compile_id) {
+                "size-hot"
+            } else {
+                size_heat(size.artifact_bytes)
+            },
+            outlier: self.is_outlier(compile_id),
+        })
+    }
+
+    /// The outliers, most generated code first
+    pub fn outliers(&self) -> Vec<CodeSizeOutlier> {
+        let Some(median) = self.median_code_bytes else {
+            return Vec::new();
+        };
+        let mut outliers: Vec<(&Option<CompileId>, &CompileSize)> = self
+            .sizes
+            .iter()
+            .filter(|(cid, _)| self.is_outlier(cid))
+            .collect();
+        outliers.sort_by_key(|(_, size)| std::cmp::Reverse(size.generated_code_bytes));
+        outliers
+            .into_iter()
+            .map(|(cid, size)| CodeSizeOutlier {
+                compile_id: cid
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                generated_code: units::bytes(size.generated_code_bytes),
+                times_median: format!("{:.1}", size.generated_code_bytes as f64 / median as f64),
+            })
+            .collect()
+    }
+
+    /// Median generated code of the compiles that generated any, once there are enough
+    pub fn median(&self) -> Option<String> {
+        self.median_code_bytes.map(units::bytes)
+    }
+}
@@ -0,0 +1,215 @@
+//! The collectives Inductor scheduled in each compiled graph (`collectives.html`).
+//!
+//! With distributed code Inductor logs an `inductor_collective_schedule` artifact per graph:
+//! the collective ops of the graph, in the order the generated code issues them, e.g.
+//!
+//! ```json
+//! ["torch.ops._c10d_functional.all_reduce_.default", "torch.ops._c10d_functional.wait_tensor.default"]
+//! ```
+//!
+//! They are parsed into [`CollectiveOp`]s, listed per compile on `collectives.html` and written
+//! to `collectives.json`, which [`read_report`] reads back, e.g. to compare the schedules of
+//! the ranks of a job: ranks issuing different collectives, or the same ones in another order,
+//! hang.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::types::{CollectiveOpRow, CollectiveScheduleSection, CollectivesContext, CompileId};
+
+pub const SCHEDULE_ARTIFACT: &str = "inductor_collective_schedule";
+pub const COLLECTIVES_FILENAME: &str = "collectives.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectiveKind {
+    AllReduce,
+    AllGather,
+    ReduceScatter,
+    Broadcast,
+    AllToAll,
+    /// `wait_tensor`, waiting for an earlier collective
+    Wait,
+    Other,
+}
+
+impl CollectiveKind {
+    fn of(op: &str) -> Self {
+        // `all_reduce_` is the in-place all_reduce, `all_gather_into_tensor_coalesced` several
+        // all_gathers at once
+        let op = op.trim_end_matches('_');
+        [
+            ("all_reduce", Self::AllReduce),
+            ("all_gather", Self::AllGather),
+            ("reduce_scatter", Self::ReduceScatter),
+            ("broadcast", Self::Broadcast),
+            ("all_to_all", Self::AllToAll),
+            ("wait", Self::Wait),
+        ]
+        .into_iter()
+        .find(|(prefix, _)| op.starts_with(prefix))
+        .map_or(Self::Other, |(_, kind)| kind)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::AllReduce => "all_reduce",
+            Self::AllGather => "all_gather",
+            Self::ReduceScatter => "reduce_scatter",
+            Self::Broadcast => "broadcast",
+            Self::AllToAll => "all_to_all",
+            Self::Wait => "wait",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectiveOp {
+    /// As logged, `torch.ops._c10d_functional.all_reduce_.default`
+    pub target: String,
+    /// `_c10d_functional`
+    pub namespace: String,
+    /// `all_reduce_`
+    pub op: String,
+    pub kind: CollectiveKind,
+}
+
+impl CollectiveOp {
+    pub fn parse(target: &str) -> Self {
+        let path = target.strip_prefix("torch.ops.").unwrap_or(target);
+        let mut parts = path.split('.');
+        let (namespace, op) = match (parts.next(), parts.next()) {
+            (Some(namespace), Some(op)) => (namespace, op),
+            _ => ("", path),
+        };
+        CollectiveOp {
+            target: target.to_string(),
+            namespace: namespace.to_string(),
+            op: op.to_string(),
+            kind: CollectiveKind::of(op),
+        }
+    }
+}
+
+/// The collectives of one graph of a compile, in the order they are issued
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompileCollectives {
+    pub compile_id: Option<CompileId>,
+    pub ops: Vec<CollectiveOp>,
+}
+
+/// The ops of an `inductor_collective_schedule` payload
+pub fn parse_schedule(payload: &str) -> anyhow::Result<Vec<CollectiveOp>> {
+    let targets: Vec<String> = serde_json::from_str(payload)?;
+    Ok(targets.iter().map(|t| CollectiveOp::parse(t)).collect())
+}
+
+/// The schedules of the tlparse report in `report_dir`, in log order; empty if the run logged
+/// none
+pub fn read_report(report_dir: &Path) -> anyhow::Result<Vec<CompileCollectives>> {
+    let path = report_dir.join(COLLECTIVES_FILENAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        std::fs::read_to_string(&path).with_context(|| format!("Reading {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("Parsing {}", path.display()))
+}
+
+/// e.g. "2 all_reduce, 1 all_gather and 3 waits"
+fn counts(ops: &[CollectiveOp]) -> String {
+    let mut kinds: Vec<(CollectiveKind, usize)> = Vec::new();
+    for op in ops {
+        match kinds.iter_mut().find(|(kind, _)| *kind == op.kind) {
+            Some((_, n)) => *n += 1,
+            None => kinds.push((op.kind, 1)),
+        }
+    }
+    // Waits last, they only pair up with the others
+    kinds.sort_by_key(|(kind, _)| *kind == CollectiveKind::Wait);
+    let parts: Vec<String> = kinds
+        .iter()
+        .map(|(kind, n)| match (kind, n) {
+            (CollectiveKind::Wait, 1) => "1 wait".to_string(),
+            (CollectiveKind::Wait, n) => format!("{n} waits"),
+            (kind, n) => format!("{n} {}", kind.name()),
+        })
+        .collect();
+    match parts.len() {
+        0 => "no collectives".to_string(),
+        1 => parts.join(""),
+        n => format!("{} and {}", parts[..n - 1].join(", "), parts[n - 1]),
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct CollectiveIndex {
+    schedules: Vec<CompileCollectives>,
+}
+
+impl CollectiveIndex {
+    pub fn is_empty(&self) -> bool {
+        self.schedules.is_empty()
+    }
+
+    /// An `inductor_collective_schedule` artifact; payloads that aren't a list of ops are
+    /// skipped
+    pub fn add(&mut self, compile_id: &Option<CompileId>, payload: &str) {
+        if let Ok(ops) = parse_schedule(payload) {
+            self.schedules.push(CompileCollectives {
+                compile_id: compile_id.clone(),
+                ops,
+            });
+        }
+    }
+
+    /// For the index page, e.g. "3 collective schedules with 9 collectives"
+    pub fn summary(&self) -> String {
+        let num_ops: usize = self
+            .schedules
+            .iter()
+            .flat_map(|s| &s.ops)
+            .filter(|op| op.kind != CollectiveKind::Wait)
+            .count();
+        format!(
+            "{} collective schedule{} with {num_ops} collective{}",
+            self.schedules.len(),
+            if self.schedules.len() == 1 { "" } else { "s" },
+            if num_ops == 1 { "" } else { "s" },
+        )
+    }
+
+    pub fn json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.schedules)?)
+    }
+
+    pub fn context(&self) -> CollectivesContext {
+        CollectivesContext {
+            css: crate::templates::CSS,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            summary: self.summary(),
+            schedules: self
+                .schedules
+                .iter()
+                .map(|s| CollectiveScheduleSection {
+                    compile_id: s
+                        .compile_id
+                        .as_ref()
+                        .map_or("(unknown)".to_string(), |c| c.to_string()),
+                    counts: counts(&s.ops),
+                    ops: s
+                        .ops
+                        .iter()
+                        .map(|op| CollectiveOpRow {
+                            op: op.op.clone(),
+                            target: op.target.clone(),
+                            kind: op.kind.name(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Outputs that map to the same path.  Outputs written without a unique suffix (dump files,
+//! `GlobalFile`s of custom parsers) used to silently overwrite each other; now the first output
+//! keeps the path and later ones with different contents get the first free
+//! `<stem>_dup<n>.<ext>`.  An output repeating the contents already at its path isn't a
+//! collision.
+//!
+//! The report's own top-level files are taken before any parser runs.  Whatever still ends up
+//! written twice with different contents (the last write wins) is reported as overwritten.
+//! Collisions go to `output_collisions.json` and a warning on the index page.
+
+use fxhash::FxHashMap;
+use md5::{Digest, Md5};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::paths;
+
+pub(crate) const COLLISIONS_FILENAME: &str = "output_collisions.json";
+
+/// Every top-level file of a report, taken before the parsers run so that their outputs can't
+/// take them even though the report writes them last.  A file added to the report's top level
+/// goes here too: `test_report_files_reserved` fails for any top-level file of a test log's
+/// report that isn't.
+const REPORT_FILES: &[&str] = &[
+    "index.html",
+    "tlparse_index.html",
+    "failures_and_restarts.html",
+    "compile_directory.json",
+    "summary.json",
+    "chromium_events.json",
+    "raw.log",
+    "raw.jsonl",
+    "vllm_summary.json",
+    "cache_key_changes.html",
+    "collectives.html",
+    "compare.html",
+    "compile_times.html",
+    "data_dependent.html",
+    "duplicate_graphs.html",
+    "fallback_kernels.html",
+    "graph_breaks.html",
+    "memory_usage.html",
+    "op_coverage.html",
+    "recompiles.html",
+    "sources.html",
+    crate::activity::ACTIVITY_FILENAME,
+    crate::cache_keys::CACHE_KEYS_FILENAME,
+    crate::collectives::COLLECTIVES_FILENAME,
+    COLLISIONS_FILENAME,
+    crate::lazy::ARTIFACT_INDEX_FILENAME,
+    crate::previews::PREVIEWS_FILENAME,
+    crate::report_json::REPORT_JSON_FILENAME,
+    crate::report_model::REPORT_MODEL_FILENAME,
+    crate::report_model::RENDERING_ERRORS_FILENAME,
+    crate::search::SEARCH_INDEX_FILENAME,
+    crate::strict::STRICT_FAILURES_FILENAME,
+    crate::suggestions::SUGGESTED_CONFIG_FILENAME,
+    crate::synthetic_trace::TRACE_FILENAME,
+    // Written next to the report by the CLI
+    crate::manifest::MANIFEST_FILENAME,
+    crate::watch::CHANGES_FILENAME,
+    crate::watch::CHANGES_JSON_FILENAME,
+    crate::bundle::BUNDLE_FILENAME,
+];
+
+/// Top-level pages named after a compile, e.g. `provenance_tracking_-_0_0_0.html`
+const REPORT_FILE_PREFIXES: &[&str] = &["provenance_tracking_"];
+
+/// Whether `path` is one of the report's own top-level files, which parser outputs can't take
+pub fn is_report_file(path: &Path) -> bool {
+    let Some(name) = path
+        .to_str()
+        .filter(|_| path.parent().is_none_or(|p| p.as_os_str().is_empty()))
+    else {
+        return false;
+    };
+    REPORT_FILES.contains(&name)
+        || REPORT_FILE_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix) && name.ends_with(".html"))
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct OutputCollision {
+    pub path: String,
+    /// Where the later output went instead, None if it overwrote the earlier one
+    pub renamed_to: Option<String>,
+    /// The parser of the later output, if a parser wrote it
+    pub parser: Option<&'static str>,
+    pub lineno: Option<usize>,
+}
+
+#[derive(Default)]
+struct Claimed {
+    /// Path -> hash of its contents
+    paths: FxHashMap<PathBuf, [u8; 16]>,
+    collisions: Vec<OutputCollision>,
+}
+
+/// The paths of the outputs without a unique suffix, kept in the log's `ParserState`
+#[derive(Default)]
+pub(crate) struct OutputPaths {
+    claimed: Mutex<Claimed>,
+}
+
+fn dup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = OsString::from(path.file_stem().unwrap_or_default());
+    name.push(format!("_dup{n}"));
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+impl OutputPaths {
+    /// The path to write `contents`, an output of `parser` at `lineno`, to
+    pub fn claim(
+        &self,
+        path: PathBuf,
+        contents: &str,
+        parser: &'static str,
+        lineno: usize,
+    ) -> PathBuf {
+        let mut claimed = self.claimed.lock().unwrap();
+        let hash: [u8; 16] = Md5::digest(contents).into();
+        let reserved = is_report_file(&path);
+        match claimed.paths.get(&path) {
+            Some(existing) if *existing == hash => return path,
+            None if !reserved => {
+                claimed.paths.insert(path.clone(), hash);
+                return path;
+            }
+            _ => {}
+        }
+        let renamed = (2..)
+            .map(|n| dup_path(&path, n))
+            .find(|p| {
+                claimed
+                    .paths
+                    .get(p)
+                    .is_none_or(|existing| *existing == hash)
+            })
+            .unwrap();
+        if claimed.paths.insert(renamed.clone(), hash).is_some() {
+            // Repeats an output already renamed
+            return renamed;
+        }
+        claimed.collisions.push(OutputCollision {
+            path: paths::url(&path),
+            renamed_to: Some(paths::url(&renamed)),
+            parser: Some(parser),
+            lineno: Some(lineno),
+        });
+        renamed
+    }
+
+    /// The collisions, with the paths of `output` written more than once with different
+    /// contents.  Paths `deferred` to `artifact_index.json` are dropped from memory, so
+    /// there's nothing to compare.
+    pub fn finish(
+        &self,
+        output: &[(PathBuf, String)],
+        deferred: impl Fn(&Path) -> bool,
+    ) -> Vec<OutputCollision> {
+        let mut writes: FxHashMap<&Path, Vec<&str>> = FxHashMap::default();
+        for (path, contents) in output.iter().filter(|(path, _)| !deferred(path)) {
+            writes.entry(path).or_default().push(contents);
+        }
+        let mut collisions = std::mem::take(&mut self.claimed.lock().unwrap().collisions);
+        let mut overwritten: Vec<&Path> = writes
+            .into_iter()
+            .filter(|(_, contents)| contents.iter().any(|c| *c != contents[0]))
+            .map(|(path, _)| path)
+            .collect();
+        overwritten.sort();
+        collisions.extend(overwritten.into_iter().map(|path| OutputCollision {
+            path: paths::url(path),
+            renamed_to: None,
+            parser: None,
+            lineno: None,
+        }));
+        collisions
+    }
+}
+
+/// For the index page, e.g. "2 outputs mapped to a path already taken and were renamed."
+pub(crate) fn summary(collisions: &[OutputCollision]) -> String {
+    let renamed = collisions.iter().filter(|c| c.renamed_to.is_some()).count();
+    let overwritten = collisions.len() - renamed;
+    let mut parts = Vec::new();
+    if renamed > 0 {
+        parts.push(match renamed {
+            1 => "1 output mapped to a path already taken and was renamed".to_string(),
+            n => format!("{n} outputs mapped to paths already taken and were renamed"),
+        });
+    }
+    if overwritten > 0 {
+        parts.push(match overwritten {
+            1 => "1 file was written more than once and only its last contents kept".to_string(),
+            n => format!("{n} files were written more than once and only their last contents kept"),
+        });
+    }
+    format!("{}.", parts.join("; "))
+}
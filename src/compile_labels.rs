@@ -0,0 +1,80 @@
+//! Human-friendly labels for compile ids ("decoder layer", "sampler"), from `compile_labels` in
+//! the config file or `--compile-label`, shown next to the ids throughout the report so it can
+//! be shared with people who don't know what `7/0_1` means.
+//!
+//! A rule is a compile id as printed without its brackets (`7/0_1`, `!1/3/0`) or a regex over
+//! it, which must match the whole id: `7/0` labels the first compile of frame 7 only, `7/.*`
+//! every compile of it.  The first matching rule wins.
+
+use anyhow::Context;
+use html_escape::encode_text;
+use regex::Regex;
+use std::cell::RefCell;
+
+use crate::types::CompileId;
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileLabels {
+    rules: Vec<(Regex, String)>,
+}
+
+thread_local! {
+    // Set per parse_path call, like the stack render options, since ids are shown by pages
+    // rendered deep inside parsers and indexes that don't see the config
+    static COMPILE_LABELS: RefCell<CompileLabels> = RefCell::new(CompileLabels::default());
+}
+
+impl CompileLabels {
+    /// Label the compile ids matching `pattern` with `label`, after the rules already added
+    pub fn add(&mut self, pattern: &str, label: &str) -> anyhow::Result<()> {
+        let regex = Regex::new(&format!("^(?:{pattern})$"))
+            .with_context(|| format!("Invalid compile id pattern `{pattern}`"))?;
+        self.rules.push((regex, label.to_string()));
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn label(&self, compile_id: &CompileId) -> Option<&str> {
+        let id = compile_id.to_string();
+        let id = id.trim_start_matches('[').trim_end_matches(']');
+        self.rules
+            .iter()
+            .find(|(regex, _)| regex.is_match(id))
+            .map(|(_, label)| label.as_str())
+    }
+
+    /// Use these labels for the reports parsed on this thread
+    pub(crate) fn install(&self) {
+        COMPILE_LABELS.with(|labels| *labels.borrow_mut() = self.clone());
+    }
+}
+
+/// The label of a compile of the log being parsed
+pub(crate) fn label(compile_id: &Option<CompileId>) -> Option<String> {
+    let compile_id = compile_id.as_ref()?;
+    COMPILE_LABELS.with(|labels| labels.borrow().label(compile_id).map(str::to_string))
+}
+
+/// `[7/0] (decoder layer)`, `[7/0]` without a label, `(unknown)` without an id
+pub(crate) fn display(compile_id: &Option<CompileId>) -> String {
+    let Some(c) = compile_id else {
+        return "(unknown)".to_string();
+    };
+    match label(compile_id) {
+        Some(label) => format!("{c} ({label})"),
+        None => c.to_string(),
+    }
+}
+
+/// The label as a badge to put after a compile id in HTML, empty without one
+pub(crate) fn label_html(compile_id: &Option<CompileId>) -> String {
+    label(compile_id).map_or(String::new(), |label| {
+        format!(
+            " <span class='compile-label'>{}</span>",
+            encode_text(&label)
+        )
+    })
+}
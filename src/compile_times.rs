@@ -0,0 +1,358 @@
+//! Where compile time went, compile by compile (`compile_times.html`): a table of the Dynamo,
+//! AOTAutograd and Inductor wall time of every compile, sortable by any column, with a bar per
+//! compile and the totals and percentiles of the run, so the slowest frame is one click away.
+//!
+//! Times come from `compilation_metrics`: Dynamo is the frame's compile time outside of the
+//! backend, AOTAutograd the backend's time outside of Inductor.  Compiles whose metrics lack a
+//! time take it from their chromium events (`entire_frame_compile`, `backend_compile`,
+//! `inductor_compile`) instead.
+//!
+//! Loggers that also measure CPU time (`entire_frame_compile_cpu_time_s` and friends) get it
+//! next to each wall time, and a Wait column: the wall time a compile spent off the CPU, on lock
+//! contention, compile subprocesses or a remote cache.  Compiles that waited for most of their
+//! time are highlighted, and the index sums up the run's wait.  The derived metrics of the
+//! config file follow the times, one column each.
+
+use fxhash::FxHashMap;
+use std::fmt::Write;
+
+use crate::types::{
+    CompilationMetricsMetadata, CompileId, CompileSummary, CompileTimesContext, CompileTimesRow,
+    CompileTimesStat,
+};
+use crate::units;
+
+/// Colors of the phases, as on the compiler phase charts
+const PHASES: [(&str, &str); 3] = [
+    ("Dynamo", "#1f77b4"),
+    ("AOTAutograd", "#2ca02c"),
+    ("Inductor", "#e6a23c"),
+];
+const BAR_WIDTH: f64 = 240.0;
+/// Compiles that spent at least this share of their wall time, and this many seconds, off the
+/// CPU are highlighted
+const WAIT_FRACTION: f64 = 0.5;
+const MIN_WAIT_S: f64 = 1.0;
+
+struct CompileTimes {
+    compile_id: String,
+    url: String,
+    co_name: Option<String>,
+    failed: bool,
+    /// Dynamo, AOTAutograd and Inductor seconds
+    phases: [Option<f64>; 3],
+    total: Option<f64>,
+    /// CPU seconds of the same, from the metrics only
+    cpu_phases: [Option<f64>; 3],
+    cpu_total: Option<f64>,
+}
+
+impl CompileTimes {
+    /// Wall seconds spent off the CPU
+    fn wait(&self) -> Option<f64> {
+        Some((self.total? - self.cpu_total?).max(0.0))
+    }
+
+    fn is_waiting(&self) -> bool {
+        self.wait().is_some_and(|wait| {
+            wait >= MIN_WAIT_S && wait >= WAIT_FRACTION * self.total.unwrap_or(0.0)
+        })
+    }
+}
+
+/// Dynamo, AOTAutograd and Inductor seconds from the spans that contain each other
+fn split_phases(
+    total: Option<f64>,
+    backend: Option<f64>,
+    inductor: Option<f64>,
+) -> [Option<f64>; 3] {
+    let dynamo = match (total, backend) {
+        (Some(total), Some(backend)) => Some((total - backend).max(0.0)),
+        (total, None) => total,
+        (None, Some(_)) => None,
+    };
+    let aot = backend.map(|backend| (backend - inductor.unwrap_or(0.0)).max(0.0));
+    [dynamo, aot, inductor]
+}
+
+/// Seconds spent in the outermost spans named `name`
+fn span_seconds(events: &[serde_json::Value], name: &str) -> Option<f64> {
+    let mut total = None;
+    let mut open: Vec<f64> = Vec::new();
+    for ev in events.iter().filter(|ev| ev["name"].as_str() == Some(name)) {
+        let Some(ts) = ev["ts"].as_f64() else {
+            continue;
+        };
+        match ev["ph"].as_str() {
+            Some("B") => open.push(ts),
+            Some("E") => {
+                if let Some(start) = open.pop() {
+                    if open.is_empty() {
+                        *total.get_or_insert(0.0) += (ts - start) / 1e6;
+                    }
+                }
+            }
+            Some("X") if open.is_empty() => {
+                *total.get_or_insert(0.0) += ev["dur"].as_f64().unwrap_or(0.0) / 1e6;
+            }
+            _ => {}
+        }
+    }
+    total
+}
+
+fn format_seconds(seconds: Option<f64>) -> String {
+    seconds.map_or(String::new(), |s| units::seconds(s, 3))
+}
+
+/// The value at `fraction` of `values`, by nearest rank
+fn percentile(values: &[f64], fraction: f64) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = ((fraction * sorted.len() as f64).ceil() as usize).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+#[derive(Default)]
+pub(crate) struct CompileTimesIndex {
+    compiles: Vec<CompileTimes>,
+}
+
+impl CompileTimesIndex {
+    pub fn is_empty(&self) -> bool {
+        self.compiles.is_empty()
+    }
+
+    /// The compile whose metrics page is `url`, with its metrics and chromium events
+    pub fn add(
+        &mut self,
+        compile_id: &Option<CompileId>,
+        url: String,
+        metrics: Option<&CompilationMetricsMetadata>,
+        events: &[serde_json::Value],
+    ) {
+        let total = metrics
+            .and_then(|m| m.entire_frame_compile_time_s)
+            .or_else(|| span_seconds(events, "entire_frame_compile"));
+        let backend = metrics
+            .and_then(|m| m.backend_compile_time_s)
+            .or_else(|| span_seconds(events, "backend_compile"));
+        let inductor = metrics
+            .and_then(|m| m.inductor_compile_time_s)
+            .or_else(|| span_seconds(events, "inductor_compile"));
+        let cpu_total = metrics.and_then(|m| m.entire_frame_compile_cpu_time_s);
+        let cpu_phases = split_phases(
+            cpu_total,
+            metrics.and_then(|m| m.backend_compile_cpu_time_s),
+            metrics.and_then(|m| m.inductor_compile_cpu_time_s),
+        );
+        self.compiles.push(CompileTimes {
+            compile_id: crate::compile_labels::display(compile_id),
+            url,
+            co_name: metrics.and_then(|m| m.co_name.clone()),
+            failed: metrics.is_some_and(|m| m.fail_type.is_some()),
+            phases: split_phases(total, backend, inductor),
+            total,
+            cpu_phases,
+            cpu_total,
+        });
+    }
+
+    /// For the index page, e.g. "12 compiles took 30.1s; the slowest, [3/0] (forward), 9.2s"
+    pub fn summary(&self) -> String {
+        let timed: Vec<&CompileTimes> =
+            self.compiles.iter().filter(|c| c.total.is_some()).collect();
+        let total: f64 = timed.iter().filter_map(|c| c.total).sum();
+        let compiles = match self.compiles.len() {
+            1 => "1 compile".to_string(),
+            n => format!("{n} compiles"),
+        };
+        let Some(slowest) = timed
+            .iter()
+            .max_by(|a, b| a.total.unwrap_or(0.0).total_cmp(&b.total.unwrap_or(0.0)))
+        else {
+            return format!("{compiles}, none of them timed");
+        };
+        let name = slowest
+            .co_name
+            .as_ref()
+            .map_or(String::new(), |n| format!(" ({n})"));
+        let mut summary = format!(
+            "{compiles} took {}; the slowest, {}{name}, {}",
+            units::seconds(total, 1),
+            slowest.compile_id,
+            units::seconds(slowest.total.unwrap_or(0.0), 1)
+        );
+        if let Some(wait) = self.wait_summary() {
+            write!(summary, "; {wait}").unwrap();
+        }
+        summary
+    }
+
+    /// E.g. "they waited 6.2s off the CPU, 2 of them for at least 50% of their time", if some
+    /// compile has CPU times
+    fn wait_summary(&self) -> Option<String> {
+        let waits: Vec<f64> = self.compiles.iter().filter_map(|c| c.wait()).collect();
+        if waits.is_empty() {
+            return None;
+        }
+        let mut summary = format!(
+            "they waited {} off the CPU",
+            units::seconds(waits.iter().sum::<f64>(), 1)
+        );
+        let waiting = self.compiles.iter().filter(|c| c.is_waiting()).count();
+        if waiting > 0 {
+            write!(
+                summary,
+                ", {waiting} of them for at least {:.0}% of their time",
+                WAIT_FRACTION * 100.0
+            )
+            .unwrap();
+        }
+        Some(summary)
+    }
+
+    fn bar(phases: &[Option<f64>; 3], max_total: f64) -> String {
+        let mut svg =
+            format!("<svg width='{BAR_WIDTH}' height='12' xmlns='http://www.w3.org/2000/svg'>");
+        let mut x = 0.0;
+        for (seconds, (phase, color)) in phases.iter().zip(PHASES) {
+            let Some(seconds) = seconds.filter(|s| *s > 0.0) else {
+                continue;
+            };
+            let width = seconds / max_total.max(1e-9) * BAR_WIDTH;
+            write!(
+                svg,
+                "<rect x='{x:.1}' y='0' width='{width:.1}' height='12' fill='{color}'><title>{phase}: {}</title></rect>",
+                units::seconds(seconds, 3)
+            )
+            .unwrap();
+            x += width;
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+
+    pub fn context(&self) -> CompileTimesContext {
+        let max_total = self
+            .compiles
+            .iter()
+            .map(|c| c.total.unwrap_or_else(|| c.phases.iter().flatten().sum()))
+            .fold(0.0, f64::max);
+        let rows = self
+            .compiles
+            .iter()
+            .enumerate()
+            .map(|(order, c)| CompileTimesRow {
+                order,
+                compile_id: c.compile_id.clone(),
+                url: c.url.clone(),
+                co_name: c.co_name.clone().unwrap_or_default(),
+                status: if c.failed { "failed" } else { "ok" },
+                dynamo: format_seconds(c.phases[0]),
+                aot: format_seconds(c.phases[1]),
+                inductor: format_seconds(c.phases[2]),
+                total: format_seconds(c.total),
+                dynamo_s: c.phases[0],
+                aot_s: c.phases[1],
+                inductor_s: c.phases[2],
+                total_s: c.total,
+                wait_s: c.wait(),
+                dynamo_cpu: format_seconds(c.cpu_phases[0]),
+                aot_cpu: format_seconds(c.cpu_phases[1]),
+                inductor_cpu: format_seconds(c.cpu_phases[2]),
+                total_cpu: format_seconds(c.cpu_total),
+                wait: format_seconds(c.wait()),
+                waiting: c.is_waiting(),
+                bar_html: Self::bar(&c.phases, max_total),
+                derived_metrics: Vec::new(),
+            })
+            .collect();
+
+        let column = |f: &dyn Fn(&CompileTimes) -> Option<f64>| -> Vec<f64> {
+            self.compiles.iter().filter_map(f).collect()
+        };
+        let columns = [
+            column(&|c| c.phases[0]),
+            column(&|c| c.phases[1]),
+            column(&|c| c.phases[2]),
+            column(&|c| c.total),
+            column(&|c| c.wait()),
+        ];
+        let stat = |name: &'static str, f: &dyn Fn(&[f64]) -> Option<f64>| {
+            let [dynamo, aot, inductor, total, wait] = columns.each_ref().map(|values| {
+                if values.is_empty() {
+                    String::new()
+                } else {
+                    format_seconds(f(values))
+                }
+            });
+            CompileTimesStat {
+                name,
+                dynamo,
+                aot,
+                inductor,
+                total,
+                wait,
+            }
+        };
+        let stats = vec![
+            stat("Total", &|values| Some(values.iter().sum())),
+            stat("p50", &|values| percentile(values, 0.5)),
+            stat("p90", &|values| percentile(values, 0.9)),
+            stat("Max", &|values| percentile(values, 1.0)),
+        ];
+        let legend_html = PHASES
+            .iter()
+            .map(|(phase, color)| {
+                format!("<span style='display:inline-block;width:10px;height:10px;background:{color}'></span> {phase}")
+            })
+            .collect::<Vec<_>>()
+            .join(" &nbsp; ");
+
+        CompileTimesContext {
+            css: crate::templates::CSS,
+            javascript: crate::templates::COMPILE_TIMES_JAVASCRIPT,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            summary: self.summary(),
+            has_cpu: self.compiles.iter().any(|c| c.cpu_total.is_some()),
+            legend_html,
+            stats,
+            rows,
+            derived_metric_columns: Vec::new(),
+        }
+    }
+}
+
+/// A column per derived metric of the config file, after the times: `summaries` has the
+/// values of each compile, found by its metrics page
+pub fn add_derived_metrics(
+    context: &mut CompileTimesContext,
+    names: Vec<String>,
+    summaries: &[CompileSummary],
+) {
+    if names.is_empty() {
+        return;
+    }
+    // Compile id, frame, status, the three phases and the total, and the wait
+    let first = if context.has_cpu { 8 } else { 7 };
+    context.derived_metric_columns = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, first + i))
+        .collect();
+    let by_url: FxHashMap<&str, &CompileSummary> = summaries
+        .iter()
+        .map(|c| (c.metrics_url.as_str(), c))
+        .collect();
+    for row in &mut context.rows {
+        row.derived_metrics = match by_url.get(row.url.as_str()) {
+            Some(c) => c
+                .derived_metrics
+                .values()
+                .map(|v| crate::derived_metrics::format_value(*v))
+                .collect(),
+            None => vec![String::new(); context.derived_metric_columns.len()],
+        };
+    }
+}
@@ -0,0 +1,201 @@
+//! Transparent decompression of compressed logs, so clusters storing their torch trace logs
+//! gzipped don't have to decompress them first.  Compression is detected from the first bytes of
+//! the file rather than its name: gzip is read with flate2, zstd through the `zstd` command,
+//! which must be on PATH.
+//!
+//! Compressed logs can't be seeked into, so their payloads are never left in the log for later
+//! extraction (`--lazy-artifacts`, `--low-memory` warn that they are kept in memory instead),
+//! and they can't be followed as they grow.
+
+use anyhow::{bail, Context};
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// The compression of the file at `path`, None for plain text
+    pub fn detect(path: &Path) -> anyhow::Result<Option<Self>> {
+        let mut file = File::open(path).with_context(|| format!("Opening {}", path.display()))?;
+        let mut magic = [0u8; 4];
+        let mut len = 0;
+        while len < magic.len() {
+            match file.read(&mut magic[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        Ok(match &magic[..len] {
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd] => Some(Compression::Zstd),
+            _ => None,
+        })
+    }
+}
+
+/// `dedicated_log_torch_trace_rank_0.log.gz` -> `dedicated_log_torch_trace_rank_0.log`
+pub fn strip_extension(filename: &str) -> &str {
+    filename
+        .strip_suffix(".gz")
+        .or_else(|| filename.strip_suffix(".zst"))
+        .unwrap_or(filename)
+}
+
+/// Counts the bytes read from the file, for progress on compressed logs
+struct CountingReader {
+    file: File,
+    read: Arc<AtomicU64>,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.file.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// The output of `zstd -dc`, failing at the end if zstd did or if reading the file to feed it
+/// did.  A reader dropped before the end (e.g. after sniffing for a tarball) stops zstd and
+/// reaps it
+struct ZstdReader {
+    child: Child,
+    stdout: ChildStdout,
+    /// The thread copying the file to zstd's stdin
+    feeder: Option<JoinHandle<io::Result<u64>>>,
+}
+
+impl Read for ZstdReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.stdout.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            let status = self.child.wait()?;
+            if !status.success() {
+                return Err(io::Error::other(format!("zstd failed ({status})")));
+            }
+            // A read error closes zstd's stdin as if the file had ended there
+            if let Some(feeder) = self.feeder.take() {
+                feeder
+                    .join()
+                    .map_err(|_| io::Error::other("feeding zstd panicked"))??;
+            }
+        }
+        Ok(n)
+    }
+}
+
+impl Drop for ZstdReader {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A log, decompressed if it is compressed
+pub struct LogReader {
+    inner: Box<dyn BufRead + Send>,
+    read: Arc<AtomicU64>,
+    compression: Option<Compression>,
+}
+
+impl LogReader {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let compression = Compression::detect(path)?;
+        let read = Arc::new(AtomicU64::new(0));
+        let file = CountingReader {
+            file: File::open(path).with_context(|| format!("Opening {}", path.display()))?,
+            read: read.clone(),
+        };
+        let inner: Box<dyn BufRead + Send> = match compression {
+            None => Box::new(BufReader::new(file)),
+            Some(Compression::Gzip) => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+            Some(Compression::Zstd) => {
+                let mut child = Command::new("zstd")
+                    .args(["-dc", "-"])
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .with_context(|| {
+                        format!(
+                            "{} is zstd compressed, which needs the zstd command",
+                            path.display()
+                        )
+                    })?;
+                // Fed from here rather than opened by zstd, so the bytes read are counted
+                let mut stdin = child.stdin.take().unwrap();
+                let mut file = file;
+                let feeder = std::thread::spawn(move || io::copy(&mut file, &mut stdin));
+                let stdout = child.stdout.take().unwrap();
+                Box::new(BufReader::new(ZstdReader {
+                    child,
+                    stdout,
+                    feeder: Some(feeder),
+                }))
+            }
+        };
+        Ok(Self {
+            inner,
+            read,
+            compression,
+        })
+    }
+
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    /// Bytes of the file read so far: compressed bytes for a compressed log
+    pub fn file_bytes_read(&self) -> Arc<AtomicU64> {
+        self.read.clone()
+    }
+}
+
+impl Read for LogReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl BufRead for LogReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+/// The whole log, decompressed
+pub fn read_to_string(path: &Path) -> anyhow::Result<String> {
+    let mut contents = String::new();
+    LogReader::open(path)?
+        .read_to_string(&mut contents)
+        .with_context(|| format!("Reading {}", path.display()))?;
+    Ok(contents)
+}
+
+/// Fail for a compressed log, which `what` needs to seek into
+pub fn require_uncompressed(path: &Path, what: &str) -> anyhow::Result<()> {
+    if let Some(compression) = Compression::detect(path)? {
+        let kind = match compression {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        };
+        bail!(
+            "{} is {kind} compressed; {what} needs an uncompressed log",
+            path.display()
+        );
+    }
+    Ok(())
+}
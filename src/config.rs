@@ -0,0 +1,64 @@
+//! Optional JSON config file, passed on the command line with `--config`.
+//!
+//! ```json
+//! {
+//!   "derived_metrics": {
+//!     "inductor_fraction": "inductor_compile_time_s / entire_frame_compile_time_s"
+//!   },
+//!   "metadata": {
+//!     "experiment": "llama-bf16-sweep"
+//!   },
+//!   "compile_labels": {
+//!     "0/.*": "decoder layer",
+//!     "1/0": "sampler"
+//!   }
+//! }
+//! ```
+
+use anyhow::Context;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::derived_metrics::DerivedMetric;
+use crate::ParseConfig;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ConfigFile {
+    /// Metric name => expression over `compilation_metrics` fields, kept in file order
+    pub derived_metrics: IndexMap<String, String>,
+    /// Report metadata, see `ParseConfig::metadata`; keys already set take precedence
+    pub metadata: IndexMap<String, String>,
+    /// Compile id pattern => label, see `ParseConfig::compile_labels`; tried in file order,
+    /// after the `--compile-label` rules
+    pub compile_labels: IndexMap<String, String>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read config file {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Couldn't parse config file {}", path.display()))
+    }
+
+    /// Merge the file's settings into a ParseConfig, validating expressions up front
+    pub fn apply(&self, config: &mut ParseConfig) -> anyhow::Result<()> {
+        for (name, expression) in &self.derived_metrics {
+            let metric = DerivedMetric::new(name, expression)
+                .with_context(|| format!("Invalid derived metric `{name}`"))?;
+            config.derived_metrics.push(metric);
+        }
+        for (key, value) in &self.metadata {
+            config
+                .metadata
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        for (pattern, label) in &self.compile_labels {
+            config.compile_labels.add(pattern, label)?;
+        }
+        Ok(())
+    }
+}
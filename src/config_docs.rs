@@ -0,0 +1,103 @@
+//! One-line explanations of Dynamo, Inductor and vLLM config options, shown as tooltips on the
+//! option names wherever configs are displayed (the `dynamo_config` and `inductor_config`
+//! metrics of compilation metrics pages, the vLLM summary's config tables).
+//!
+//! The explanations are bundled in `config_docs.json`; `--config-docs` loads a file of the
+//! same shape whose explanations are added to, or replace, the bundled ones:
+//!
+//! ```json
+//! {
+//!   "inductor": {
+//!     "max_autotune": "Off in production: autotuning blows our compile time budget"
+//!   }
+//! }
+//! ```
+
+use anyhow::Context;
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::types::ConfigOption;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigDocs {
+    /// `torch._dynamo.config` option -> explanation
+    pub dynamo: IndexMap<String, String>,
+    /// `torch._inductor.config` option (`triton.cudagraphs` for nested ones) -> explanation
+    pub inductor: IndexMap<String, String>,
+    /// vLLM `CompilationConfig` field -> explanation
+    pub vllm: IndexMap<String, String>,
+}
+
+static BUNDLED: Lazy<ConfigDocs> = Lazy::new(|| {
+    serde_json::from_str(include_str!("config_docs.json")).expect("valid config docs")
+});
+
+impl ConfigDocs {
+    /// The explanations shipped with tlparse
+    pub fn bundled() -> Self {
+        BUNDLED.clone()
+    }
+
+    /// The bundled explanations, with those of the file at `path` added on top
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read config docs {}", path.display()))?;
+        let overrides: ConfigDocs = serde_json::from_str(&contents)
+            .with_context(|| format!("Couldn't parse config docs {}", path.display()))?;
+        let mut docs = Self::bundled();
+        docs.dynamo.extend(overrides.dynamo);
+        docs.inductor.extend(overrides.inductor);
+        docs.vllm.extend(overrides.vllm);
+        Ok(docs)
+    }
+
+    /// The options of a `dynamo_config` or `inductor_config` metric, which are logged as a
+    /// JSON object in a string; `None` for other metrics
+    pub fn options(&self, metric: &str, value: &Value) -> Option<Vec<ConfigOption>> {
+        let docs = match metric {
+            "dynamo_config" => &self.dynamo,
+            "inductor_config" => &self.inductor,
+            _ => return None,
+        };
+        let Ok(Value::Object(options)) = serde_json::from_str(value.as_str()?) else {
+            return None;
+        };
+        Some(
+            options
+                .iter()
+                .map(|(name, value)| ConfigOption {
+                    name: name.clone(),
+                    value: match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    },
+                    doc: docs.get(name).cloned().unwrap_or_default(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Explanation of every vLLM config field, empty for fields without one, so that the vLLM
+    /// summary template can look each up by name
+    pub fn vllm_fields(&self) -> IndexMap<String, String> {
+        let Ok(Value::Object(fields)) =
+            serde_json::to_value(crate::vllm::types::VllmCompilationConfig::default())
+        else {
+            return IndexMap::new();
+        };
+        fields
+            .keys()
+            .map(|field| {
+                (
+                    field.clone(),
+                    self.vllm.get(field).cloned().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,279 @@
+//! Data-dependent operations of the run (`data_dependent.html`).
+//!
+//! Ops whose output depends on tensor data, like `x.item()` or `torch.nonzero(mask)`, give
+//! the compiler sizes and scalars it can't know: each becomes an unbacked symbol (`u0`, `u1`,
+//! ...).  Guards on unbacked symbols can't be checked while compiling, so they are deferred to
+//! runtime asserts; a guard the compiler needs to decide right away fails the compile
+//! (`GuardOnDataDependentSymNode`), and Dynamo graph breaks on data-dependent control flow
+//! it can't trace.  This lists every such operation with its user stack and what it led to.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::parsers::format_stack;
+use crate::types::{
+    CompilationMetricsMetadata, CompileId, DataDependentContext, DataDependentRow, StackSummary,
+    SymbolicShapePropagateRealTensorMetadata, UnbackedSymbolMetadata,
+};
+
+static UNBACKED_SYMBOL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bu\d+\b").unwrap());
+
+/// Graph break reasons caused by data-dependent values
+const DATA_DEPENDENT_BREAKS: [&str; 5] = [
+    "data-dependent",
+    "data dependent",
+    "dynamic shape operator",
+    "unbacked",
+    "item()",
+];
+
+pub const KIND_UNBACKED_SYMBOL: &str = "unbacked symbol";
+pub const KIND_SPECIALIZATION: &str = "specialized expression";
+pub const KIND_GRAPH_BREAK: &str = "graph break";
+pub const KIND_COMPILE_ERROR: &str = "compile error";
+
+struct DataDependentOp {
+    compile_id: Option<CompileId>,
+    kind: &'static str,
+    /// The unbacked symbol, expression or graph break reason
+    operation: String,
+    /// Value range of an unbacked symbol, result of a specialized expression
+    detail: Option<String>,
+    user_stack: Option<StackSummary>,
+    /// `file.py:line` of a graph break, which has no stack
+    location: Option<String>,
+    /// Guards on an unbacked symbol, deferred to runtime
+    runtime_asserts: Vec<String>,
+    /// The compile failed guarding on this unbacked symbol
+    failed: bool,
+}
+
+#[derive(Default)]
+pub struct DataDependentIndex {
+    ops: Vec<DataDependentOp>,
+}
+
+fn is_data_dependent_failure(m: &CompilationMetricsMetadata) -> bool {
+    m.fail_type
+        .as_deref()
+        .is_some_and(|t| t.contains("DataDependent"))
+        || m.fail_reason
+            .as_deref()
+            .is_some_and(|r| r.contains("data-dependent"))
+}
+
+impl DataDependentIndex {
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// A `create_unbacked_symbol` event
+    pub fn add_unbacked_symbol(
+        &mut self,
+        compile_id: &Option<CompileId>,
+        symbol: &UnbackedSymbolMetadata,
+    ) {
+        self.ops.push(DataDependentOp {
+            compile_id: compile_id.clone(),
+            kind: KIND_UNBACKED_SYMBOL,
+            operation: symbol.symbol.clone().unwrap_or_default(),
+            detail: symbol.vr.clone(),
+            user_stack: symbol.user_stack.clone(),
+            location: None,
+            runtime_asserts: Vec::new(),
+            failed: false,
+        });
+    }
+
+    fn unbacked_symbol(
+        &mut self,
+        compile_id: &Option<CompileId>,
+        symbol: &str,
+    ) -> Option<&mut DataDependentOp> {
+        self.ops.iter_mut().rev().find(|op| {
+            op.kind == KIND_UNBACKED_SYMBOL
+                && &op.compile_id == compile_id
+                && op.operation == symbol
+        })
+    }
+
+    /// A `guard_added` event; guards on unbacked symbols of the compile are runtime asserts
+    pub fn add_guard(
+        &mut self,
+        compile_id: &Option<CompileId>,
+        guard: &SymbolicShapePropagateRealTensorMetadata,
+    ) {
+        let Some(expr) = &guard.expr else {
+            return;
+        };
+        for symbol in UNBACKED_SYMBOL.find_iter(expr) {
+            if let Some(op) = self.unbacked_symbol(compile_id, symbol.as_str()) {
+                if !op.runtime_asserts.contains(expr) {
+                    op.runtime_asserts.push(expr.clone());
+                }
+            }
+        }
+    }
+
+    /// A `propagate_real_tensors_provenance` event: export evaluated a data-dependent
+    /// expression with real tensors and specialized on the result
+    pub fn add_specialization(
+        &mut self,
+        compile_id: &Option<CompileId>,
+        guard: &SymbolicShapePropagateRealTensorMetadata,
+    ) {
+        self.ops.push(DataDependentOp {
+            compile_id: compile_id.clone(),
+            kind: KIND_SPECIALIZATION,
+            operation: guard.expr.clone().unwrap_or_default(),
+            detail: guard.result.clone(),
+            user_stack: guard.user_stack.clone(),
+            location: None,
+            runtime_asserts: Vec::new(),
+            failed: false,
+        });
+    }
+
+    /// A `dynamo_graph_break_reason` payload, if the break is data-dependent
+    pub fn add_graph_break(&mut self, compile_id: &Option<CompileId>, payload: &str) {
+        let graph_break = crate::explain::parse_graph_break(payload);
+        let reason = graph_break.reason.to_lowercase();
+        if !DATA_DEPENDENT_BREAKS.iter().any(|r| reason.contains(r)) {
+            return;
+        }
+        self.ops.push(DataDependentOp {
+            compile_id: compile_id.clone(),
+            kind: KIND_GRAPH_BREAK,
+            operation: graph_break.reason,
+            detail: None,
+            user_stack: None,
+            location: graph_break.location,
+            runtime_asserts: Vec::new(),
+            failed: false,
+        });
+    }
+
+    /// The compile's `compilation_metrics`, if it failed guarding on a data-dependent
+    /// expression; the unbacked symbols in the expression are marked as the cause
+    pub fn add_metrics(&mut self, compile_id: &Option<CompileId>, m: &CompilationMetricsMetadata) {
+        if !is_data_dependent_failure(m) {
+            return;
+        }
+        let reason = m.fail_reason.clone().unwrap_or_default();
+        for symbol in UNBACKED_SYMBOL.find_iter(&reason) {
+            if let Some(op) = self.unbacked_symbol(compile_id, symbol.as_str()) {
+                op.failed = true;
+            }
+        }
+        self.ops.push(DataDependentOp {
+            compile_id: compile_id.clone(),
+            kind: KIND_COMPILE_ERROR,
+            operation: reason,
+            detail: m.fail_type.clone(),
+            user_stack: None,
+            location: m
+                .fail_user_frame_filename
+                .as_ref()
+                .zip(m.fail_user_frame_lineno)
+                .map(|(filename, lineno)| {
+                    format!("{}:{lineno}", crate::explain::basename(filename))
+                }),
+            runtime_asserts: Vec::new(),
+            failed: true,
+        });
+    }
+
+    fn outcome(op: &DataDependentOp) -> String {
+        match op.kind {
+            KIND_GRAPH_BREAK => "graph break".to_string(),
+            KIND_COMPILE_ERROR => "compile failed".to_string(),
+            KIND_SPECIALIZATION => "specialized, runtime assert".to_string(),
+            _ if op.failed => "compile failed".to_string(),
+            _ => match op.runtime_asserts.len() {
+                0 => "no guards".to_string(),
+                1 => "1 runtime assert".to_string(),
+                n => format!("{n} runtime asserts"),
+            },
+        }
+    }
+
+    /// For the index page, e.g. "2 unbacked symbols, 1 data-dependent graph break and 1
+    /// data-dependent compile failure"
+    pub fn summary(&self) -> String {
+        let count = |kind| self.ops.iter().filter(|op| op.kind == kind).count();
+        let mut parts = Vec::new();
+        for (kind, one, many) in [
+            (KIND_UNBACKED_SYMBOL, "unbacked symbol", "unbacked symbols"),
+            (
+                KIND_SPECIALIZATION,
+                "specialized data-dependent expression",
+                "specialized data-dependent expressions",
+            ),
+            (
+                KIND_GRAPH_BREAK,
+                "data-dependent graph break",
+                "data-dependent graph breaks",
+            ),
+            (
+                KIND_COMPILE_ERROR,
+                "data-dependent compile failure",
+                "data-dependent compile failures",
+            ),
+        ] {
+            match count(kind) {
+                0 => {}
+                1 => parts.push(format!("1 {one}")),
+                n => parts.push(format!("{n} {many}")),
+            }
+        }
+        match parts.len() {
+            0 | 1 => parts.join(""),
+            n => format!("{} and {}", parts[..n - 1].join(", "), parts[n - 1]),
+        }
+    }
+
+    /// In log order
+    pub fn context(&self) -> DataDependentContext {
+        let rows: Vec<DataDependentRow> = self
+            .ops
+            .iter()
+            .map(|op| DataDependentRow {
+                compile_id: op
+                    .compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                kind: op.kind,
+                operation: op.operation.clone(),
+                detail: op.detail.clone(),
+                location_html: op
+                    .user_stack
+                    .as_ref()
+                    .and_then(|stack| stack.last())
+                    .map(|frame| frame.to_string())
+                    .or_else(|| {
+                        op.location
+                            .as_ref()
+                            .map(|l| html_escape::encode_text(l).into_owned())
+                    })
+                    .unwrap_or_default(),
+                outcome: Self::outcome(op),
+                problem: op.failed || op.kind == KIND_GRAPH_BREAK,
+                runtime_asserts: op.runtime_asserts.clone(),
+                stack_html: op
+                    .user_stack
+                    .as_ref()
+                    .filter(|stack| !stack.is_empty())
+                    .map_or(String::new(), |stack| {
+                        format_stack(stack, "User Stack", false)
+                    }),
+            })
+            .collect();
+        DataDependentContext {
+            css: crate::templates::CSS,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            summary: self.summary(),
+            num_problems: rows.iter().filter(|r| r.problem).count(),
+            rows,
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! User-defined metrics computed per compile from `compilation_metrics` fields.
+//!
+//! A derived metric is an arithmetic expression over numeric metric fields, e.g.
+//! `inductor_compile_time_s / entire_frame_compile_time_s`.  Expressions support
+//! `+ - * /`, unary minus, parentheses, numeric literals and field names.  Field names
+//! must be declared fields of `CompilationMetricsMetadata`; a typo is an error when the
+//! config loads rather than an N/A column.
+
+use anyhow::{anyhow, bail};
+use serde_json::Value;
+
+use crate::template_docs::find_struct;
+use crate::types::CompilationMetricsMetadata;
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f64),
+    Field(String),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+pub struct DerivedMetric {
+    pub name: String,
+    pub expression: String,
+    expr: Expr,
+}
+
+impl DerivedMetric {
+    pub fn new(name: &str, expression: &str) -> anyhow::Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = ExprParser { tokens, pos: 0 };
+        let expr = parser.parse_sum()?;
+        if parser.pos != parser.tokens.len() {
+            bail!("Unexpected trailing input in `{expression}`");
+        }
+        check_fields(&expr)?;
+        Ok(Self {
+            name: name.to_string(),
+            expression: expression.to_string(),
+            expr,
+        })
+    }
+
+    /// Evaluate against a compile's metrics.  Returns None if a referenced field is
+    /// missing/non-numeric or the result isn't finite (e.g. division by zero).
+    pub fn evaluate(&self, m: &CompilationMetricsMetadata) -> Option<f64> {
+        self.evaluate_fields(&serde_json::to_value(m).ok()?)
+    }
+
+    /// Evaluate against a compile's metrics already serialized to a JSON object
+    fn evaluate_fields(&self, fields: &Value) -> Option<f64> {
+        let result = eval(&self.expr, fields)?;
+        result.is_finite().then_some(result)
+    }
+}
+
+/// Fail on the first field name that isn't a (serialized) `CompilationMetricsMetadata` field
+fn check_fields(expr: &Expr) -> anyhow::Result<()> {
+    match expr {
+        Expr::Number(_) => Ok(()),
+        Expr::Field(name) => {
+            let known = find_struct("CompilationMetricsMetadata")
+                .is_some_and(|s| s.fields.iter().any(|f| f.name == name));
+            if !known {
+                bail!("`{name}` isn't a compilation metrics field");
+            }
+            Ok(())
+        }
+        Expr::Neg(e) => check_fields(e),
+        Expr::Binary(l, _, r) => {
+            check_fields(l)?;
+            check_fields(r)
+        }
+    }
+}
+
+/// Evaluate every derived metric for one compile, in definition order.
+pub fn evaluate_all(
+    metrics: &[DerivedMetric],
+    m: &CompilationMetricsMetadata,
+) -> Vec<(String, Option<f64>)> {
+    let fields = serde_json::to_value(m).ok();
+    metrics
+        .iter()
+        .map(|d| {
+            let value = fields.as_ref().and_then(|f| d.evaluate_fields(f));
+            (d.name.clone(), value)
+        })
+        .collect()
+}
+
+/// A derived metric as the index and compile times tables show it
+pub fn format_value(value: Option<f64>) -> String {
+    value.map_or("N/A".to_string(), |v| format!("{v:.3}"))
+}
+
+fn eval(expr: &Expr, fields: &Value) -> Option<f64> {
+    match expr {
+        Expr::Number(n) => Some(*n),
+        Expr::Field(name) => fields.get(name)?.as_f64(),
+        Expr::Neg(e) => eval(e, fields).map(|v| -v),
+        Expr::Binary(l, op, r) => {
+            let (l, r) = (eval(l, fields)?, eval(r, fields)?);
+            match op {
+                '+' => Some(l + r),
+                '-' => Some(l - r),
+                '*' => Some(l * r),
+                '/' => Some(l / r),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(s: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = s.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if "+-*/()".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let n = literal
+                .parse::<f64>()
+                .map_err(|_| anyhow!("Invalid number `{literal}` in `{s}`"))?;
+            tokens.push(Token::Number(n));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            bail!("Unexpected character `{c}` in `{s}`");
+        }
+    }
+    Ok(tokens)
+}
+
+struct ExprParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(c)) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn parse_sum(&mut self) -> anyhow::Result<Expr> {
+        let mut lhs = self.parse_product()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.parse_product()?;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_product(&mut self) -> anyhow::Result<Expr> {
+        let mut lhs = self.parse_unary()?;
+        while let Some(op @ ('*' | '/')) = self.peek_op() {
+            self.pos += 1;
+            let rhs = self.parse_unary()?;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> anyhow::Result<Expr> {
+        if self.peek_op() == Some('-') {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> anyhow::Result<Expr> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unexpected end of expression"))?;
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Ident(name) => Ok(Expr::Field(name)),
+            Token::Op('(') => {
+                let inner = self.parse_sum()?;
+                if self.peek_op() != Some(')') {
+                    bail!("Expected `)`");
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Token::Op(c) => bail!("Unexpected `{c}`"),
+        }
+    }
+}
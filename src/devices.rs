@@ -0,0 +1,222 @@
+//! Device backends a run compiled for (CUDA, ROCm, XPU, MTIA, ...) and the device-specific
+//! artifacts (cudagraphs, Triton kernels, backend logs) of each, so they are listed under the
+//! backend they belong to instead of being assumed to be CUDA.
+//!
+//! The backend is detected from the devices of traced tensors, the toolkit versions in
+//! `compilation_metrics` and environment artifacts (artifacts named like `system_info` or
+//! `env_versions`).  ROCm builds report their tensors as `cuda` and log cudagraph artifacts
+//! under CUDA names, so once a HIP version is seen those count as ROCm.
+
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{
+    CompilationMetricsMetadata, DeviceBackendArtifact, DeviceBackendSection, OutputFile,
+};
+
+/// Namespace and display name of every known backend, in display order
+const BACKENDS: [(&str, &str); 6] = [
+    ("cuda", "CUDA"),
+    ("rocm", "ROCm"),
+    ("xpu", "XPU"),
+    ("mtia", "MTIA"),
+    ("mps", "MPS"),
+    ("cpu", "CPU"),
+];
+
+/// Name tokens of artifacts (and envelope keys) specific to one backend
+const BACKEND_TOKENS: [(&str, &str); 9] = [
+    ("rocm", "rocm"),
+    ("hip", "rocm"),
+    ("hipgraph", "rocm"),
+    ("hipgraphs", "rocm"),
+    ("xpu", "xpu"),
+    ("sycl", "xpu"),
+    ("mtia", "mtia"),
+    ("mps", "mps"),
+    ("metal", "mps"),
+];
+
+/// Tokens of artifacts produced by Inductor's GPU code paths, which belong to whichever GPU
+/// backend the run used
+const GPU_TOKENS: [&str; 5] = ["cuda", "cudagraph", "cudagraphs", "triton", "nvcc"];
+
+/// Tokens marking an artifact as a description of the environment
+const ENVIRONMENT_TOKENS: [&str; 6] = [
+    "env",
+    "environment",
+    "system",
+    "sysinfo",
+    "versions",
+    "platform",
+];
+
+static TENSOR_DEVICE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:device\(type=')?([a-z_]+)").unwrap());
+// `"hip_version": "6.2.41133"`, `torch.version.hip = 6.2`, `ROCm version: 6.2`, ...
+static TOOLKIT_VERSION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)\b(cuda|hip|rocm|xpu|sycl|mtia)[_ .]?(?:runtime[_ ]?)?version["']?\s*[:=]\s*["']?(\d[\w.+-]*)"#,
+    )
+    .unwrap()
+});
+
+fn tokens(name: &str) -> impl Iterator<Item = &str> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|t| !t.is_empty())
+}
+
+fn label(namespace: &str) -> &'static str {
+    BACKENDS
+        .iter()
+        .find(|(ns, _)| *ns == namespace)
+        .map_or("Unknown", |(_, label)| label)
+}
+
+fn toolkit_backend(toolkit: &str) -> &'static str {
+    match toolkit.to_ascii_lowercase().as_str() {
+        "hip" | "rocm" => "rocm",
+        "xpu" | "sycl" => "xpu",
+        "mtia" => "mtia",
+        _ => "cuda",
+    }
+}
+
+/// Collects evidence of the backends a run used while the log is read
+#[derive(Debug, Default)]
+pub struct DeviceDetector {
+    /// Backend namespace -> distinct reasons it was detected, in order seen
+    evidence: IndexMap<&'static str, Vec<String>>,
+}
+
+impl DeviceDetector {
+    fn add(&mut self, namespace: &'static str, reason: String) {
+        let reasons = self.evidence.entry(namespace).or_default();
+        if !reasons.contains(&reason) {
+            reasons.push(reason);
+        }
+    }
+
+    /// `device(type='xpu', index=0)` or `xpu:0` of a traced tensor
+    pub fn tensor_device(&mut self, device: &str) {
+        let Some(caps) = TENSOR_DEVICE.captures(device) else {
+            return;
+        };
+        let device_type = &caps[1];
+        if let Some((namespace, _)) = BACKENDS.iter().find(|(ns, _)| *ns == device_type) {
+            self.add(namespace, format!("tensors on {device_type} devices"));
+        }
+    }
+
+    pub fn compilation_metrics(&mut self, m: &CompilationMetricsMetadata) {
+        if let Some(version) = &m.cuda_version {
+            self.add("cuda", format!("CUDA {version} (compilation_metrics)"));
+        }
+    }
+
+    /// Toolkit versions mentioned by an environment artifact
+    pub fn artifact(&mut self, name: &str, payload: &str) {
+        if !tokens(name).any(|t| ENVIRONMENT_TOKENS.contains(&t.to_ascii_lowercase().as_str())) {
+            return;
+        }
+        for caps in TOOLKIT_VERSION.captures_iter(payload) {
+            let namespace = toolkit_backend(&caps[1]);
+            self.add(
+                namespace,
+                format!("{} {} ({name})", label(namespace), &caps[2]),
+            );
+        }
+    }
+
+    /// An envelope entry tlparse has no parser for, named after a backend
+    pub fn unknown_entry(&mut self, key: &str) {
+        if let Some(namespace) = self.named_backend(key) {
+            self.add(namespace, format!("unrendered `{key}` log entries"));
+        }
+    }
+
+    /// The accelerator Triton kernels and cudagraphs ran on; CUDA if nothing else is known
+    fn gpu(&self) -> &'static str {
+        ["rocm", "mtia", "xpu", "cuda"]
+            .into_iter()
+            .find(|ns| self.evidence.contains_key(ns))
+            .unwrap_or("cuda")
+    }
+
+    /// Backend a name refers to explicitly, without attributing generic GPU names
+    fn named_backend(&self, name: &str) -> Option<&'static str> {
+        let mut gpu = false;
+        for token in tokens(name) {
+            let token = token.to_ascii_lowercase();
+            if let Some((_, namespace)) = BACKEND_TOKENS.iter().find(|(t, _)| *t == token) {
+                return Some(namespace);
+            }
+            gpu |= GPU_TOKENS.contains(&token.as_str());
+        }
+        gpu.then(|| self.gpu())
+    }
+
+    /// Backend an artifact file like `-_0_0_0/cudagraph_skip_reason_3.txt` belongs to, if it is
+    /// device specific
+    pub fn artifact_backend(&self, url: &str) -> Option<&'static str> {
+        let file = url.rsplit('/').next().unwrap_or(url);
+        let stem = file.split_once('.').map_or(file, |(stem, _)| stem);
+        self.named_backend(stem)
+    }
+
+    /// One section per detected backend, with the device-specific artifacts of every compile.
+    /// CUDA evidence is folded into ROCm when both are seen, since HIP poses as CUDA.
+    pub fn sections<'a>(
+        &self,
+        directory: impl IntoIterator<Item = (String, &'a [OutputFile])>,
+    ) -> Vec<DeviceBackendSection> {
+        let mut evidence = self.evidence.clone();
+        if evidence.contains_key("rocm") {
+            if let Some(cuda) = evidence.shift_remove("cuda") {
+                let rocm = evidence.get_mut("rocm").unwrap();
+                rocm.extend(cuda.into_iter().filter(|r| !r.starts_with("CUDA ")));
+            }
+        }
+        let mut sections: Vec<DeviceBackendSection> = BACKENDS
+            .iter()
+            .filter_map(|(namespace, label)| {
+                Some(DeviceBackendSection {
+                    namespace,
+                    label,
+                    evidence: evidence.get(namespace)?.clone(),
+                    num_artifacts: 0,
+                    artifacts: Vec::new(),
+                })
+            })
+            .collect();
+        for (compile_id, files) in directory {
+            for file in files {
+                let Some(namespace) = self.artifact_backend(&file.url) else {
+                    continue;
+                };
+                let index = match sections.iter().position(|s| s.namespace == namespace) {
+                    Some(index) => index,
+                    None => {
+                        sections.push(DeviceBackendSection {
+                            namespace,
+                            label: label(namespace),
+                            evidence: Vec::new(),
+                            num_artifacts: 0,
+                            artifacts: Vec::new(),
+                        });
+                        sections.len() - 1
+                    }
+                };
+                let section = &mut sections[index];
+                section.num_artifacts += 1;
+                section.artifacts.push(DeviceBackendArtifact {
+                    compile_id: compile_id.clone(),
+                    url: file.url.clone(),
+                    name: file.url.rsplit('/').next().unwrap_or(&file.url).to_string(),
+                });
+            }
+        }
+        sections
+    }
+}
@@ -0,0 +1,361 @@
+//! `tlparse diff OLD_LOG NEW_LOG`: compare the artifacts two runs produced, per compile id.
+//!
+//! Artifacts are matched by compile directory, artifact name (without the output counter, which
+//! shifts between runs) and occurrence.  Rendered HTML pages embed timings, so only the
+//! artifacts themselves are compared.
+//!
+//! Above the artifacts, the report leads with what changed in the compiles themselves, read
+//! from both logs: compile ids only one run has, and the guards, recompile reasons and graph
+//! breaks that differ for the others.  The same is written to `diff.json`, to bisect a
+//! regression between two nightlies from a script.
+
+use fxhash::{FxHashMap, FxHashSet};
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use crate::guard_diff::{cpp_guards, json_guards};
+use crate::rank_prefix::strip_rank_prefix;
+use crate::tail::GLOG_LINE;
+use crate::templates::CSS;
+use crate::types::{
+    CompileId, DiffArtifactRow, DiffCompileChanges, DiffCompileRow, DiffContext, DiffDelta,
+    ParseOutput,
+};
+
+pub const DIFF_JSON_FILENAME: &str = "diff.json";
+
+pub const STATUS_IDENTICAL: &str = "identical";
+pub const STATUS_CHANGED: &str = "changed";
+pub const STATUS_ADDED: &str = "added";
+pub const STATUS_REMOVED: &str = "removed";
+
+/// (compile directory, artifact key) -> (path, contents), in output order
+type ArtifactMap<'a> = IndexMap<(String, String), (&'a Path, &'a str)>;
+
+fn artifacts(output: &ParseOutput) -> ArtifactMap<'_> {
+    let mut seen: FxHashMap<(String, String), usize> = FxHashMap::default();
+    let mut map = IndexMap::new();
+    for (path, contents) in output {
+        let mut components = path.components();
+        let (Some(dir), Some(file)) = (components.next(), components.next()) else {
+            continue;
+        };
+        let dir = dir.as_os_str().to_string_lossy().into_owned();
+        if dir == "payloads" || path.extension().is_some_and(|e| e == "html") {
+            continue;
+        }
+        let file = Path::new(file.as_os_str());
+        let name = crate::manifest::artifact_kind(file);
+        let ext = file.extension().unwrap_or_default().to_string_lossy();
+        let occurrence = seen.entry((dir.clone(), name.clone())).or_default();
+        let key = if *occurrence == 0 {
+            format!("{name}.{ext}")
+        } else {
+            format!("{name}.{ext} #{}", *occurrence + 1)
+        };
+        *occurrence += 1;
+        map.insert((dir, key), (path.as_path(), contents.as_str()));
+    }
+    map
+}
+
+fn url(prefix: &str, path: &Path) -> String {
+    format!("{prefix}/{}", path.to_string_lossy().replace('\\', "/"))
+}
+
+/// Compare two parsed runs.  The returned rows link artifacts under `old/` and `new/`.
+pub fn diff_outputs(old: &ParseOutput, new: &ParseOutput) -> Vec<DiffCompileRow> {
+    let (old_artifacts, new_artifacts) = (artifacts(old), artifacts(new));
+    let mut compiles: IndexMap<String, Vec<DiffArtifactRow>> = IndexMap::new();
+    for ((dir, key), (path, contents)) in &old_artifacts {
+        let (status, new_url) = match new_artifacts.get(&(dir.clone(), key.clone())) {
+            Some((new_path, new_contents)) => (
+                if contents == new_contents {
+                    STATUS_IDENTICAL
+                } else {
+                    STATUS_CHANGED
+                },
+                Some(url("new", new_path)),
+            ),
+            None => (STATUS_REMOVED, None),
+        };
+        compiles
+            .entry(dir.clone())
+            .or_default()
+            .push(DiffArtifactRow {
+                name: key.clone(),
+                status,
+                old_url: Some(url("old", path)),
+                new_url,
+            });
+    }
+    for ((dir, key), (path, _)) in &new_artifacts {
+        if !old_artifacts.contains_key(&(dir.clone(), key.clone())) {
+            compiles
+                .entry(dir.clone())
+                .or_default()
+                .push(DiffArtifactRow {
+                    name: key.clone(),
+                    status: STATUS_ADDED,
+                    old_url: None,
+                    new_url: Some(url("new", path)),
+                });
+        }
+    }
+    compiles
+        .into_iter()
+        .map(|(compile_dir, artifacts)| {
+            let status = if artifacts.iter().all(|a| a.status == STATUS_ADDED) {
+                STATUS_ADDED
+            } else if artifacts.iter().all(|a| a.status == STATUS_REMOVED) {
+                STATUS_REMOVED
+            } else if artifacts.iter().all(|a| a.status == STATUS_IDENTICAL) {
+                STATUS_IDENTICAL
+            } else {
+                STATUS_CHANGED
+            };
+            DiffCompileRow {
+                compile_dir,
+                status,
+                artifacts,
+            }
+        })
+        .collect()
+}
+
+/// The guards, recompile reasons and graph breaks of one compile in one log
+#[derive(Default)]
+struct CompileFacts {
+    co_name: Option<String>,
+    guards: IndexSet<String>,
+    recompile_reasons: IndexSet<String>,
+    graph_breaks: IndexSet<String>,
+}
+
+/// The facts of each compile of the log at `path`, by compile directory, in log order
+fn compile_facts(path: &Path) -> anyhow::Result<IndexMap<String, CompileFacts>> {
+    let file = crate::compression::LogReader::open(path)?;
+    let mut lines = file.split(b'\n').peekable();
+    let mut facts: IndexMap<String, CompileFacts> = IndexMap::new();
+    while let Some(line) = lines.next() {
+        let line = String::from_utf8_lossy(&line?).into_owned();
+        let (_, line) = strip_rank_prefix(line.trim_end_matches('\r'));
+        let Some(caps) = GLOG_LINE.captures(line) else {
+            continue;
+        };
+        let Ok(Value::Object(envelope)) = serde_json::from_str::<Value>(&caps["json"]) else {
+            continue;
+        };
+        let compile_id: Option<CompileId> =
+            serde_json::from_value(Value::Object(envelope.clone())).ok();
+        let Some(mut compile_id) = compile_id.filter(|c| c.frame_id.is_some()) else {
+            continue;
+        };
+        // As the report's compile directories, which spell out the first attempt
+        compile_id.attempt = Some(compile_id.attempt.unwrap_or(0));
+        let mut payload = String::new();
+        if envelope.contains_key("has_payload") {
+            let mut payload_lines = Vec::new();
+            while let Some(Ok(next)) = lines.peek() {
+                let next = String::from_utf8_lossy(next).into_owned();
+                let (_, next) = strip_rank_prefix(next.trim_end_matches('\r'));
+                match next.strip_prefix('\t') {
+                    Some(payload_line) => payload_lines.push(payload_line.to_string()),
+                    None if next.is_empty() => {}
+                    None => break,
+                }
+                lines.next();
+            }
+            payload = payload_lines.join("\n");
+        }
+
+        let compile = facts.entry(compile_id.as_directory_name()).or_default();
+        let artifact = envelope
+            .get("artifact")
+            .and_then(|a| a["name"].as_str())
+            .unwrap_or_default();
+        if let Some(co_name) = envelope
+            .get("compilation_metrics")
+            .and_then(|m| m["co_name"].as_str())
+        {
+            compile.co_name = Some(co_name.to_string());
+        } else if envelope.contains_key("dynamo_guards") {
+            compile
+                .guards
+                .extend(json_guards(&payload).unwrap_or_default());
+        } else if envelope.contains_key("dynamo_cpp_guards_str") {
+            compile.guards.extend(cpp_guards(&payload));
+        } else if artifact == "recompile_reasons" {
+            let reasons: Vec<String> = serde_json::from_str(&payload).unwrap_or_default();
+            compile.recompile_reasons.extend(reasons);
+        } else if artifact == "dynamo_graph_break_reason" {
+            let graph_break = crate::explain::parse_graph_break(&payload);
+            compile.graph_breaks.insert(match graph_break.location {
+                Some(location) => format!("{} at {location}", graph_break.reason),
+                None => graph_break.reason,
+            });
+        }
+    }
+    Ok(facts)
+}
+
+fn delta(old: &IndexSet<String>, new: &IndexSet<String>) -> DiffDelta {
+    DiffDelta {
+        added: new.difference(old).cloned().collect(),
+        removed: old.difference(new).cloned().collect(),
+    }
+}
+
+/// The compile ids of the two logs that differ in more than their artifacts, in log order
+pub fn compile_changes(old_log: &Path, new_log: &Path) -> anyhow::Result<Vec<DiffCompileChanges>> {
+    let (old, new) = (compile_facts(old_log)?, compile_facts(new_log)?);
+    let empty = CompileFacts::default();
+    let mut dirs: IndexSet<&String> = old.keys().collect();
+    dirs.extend(new.keys());
+    let changes = dirs
+        .into_iter()
+        .filter_map(|dir| {
+            let (status, before, after) = match (old.get(dir), new.get(dir)) {
+                (Some(before), Some(after)) => (STATUS_CHANGED, before, after),
+                (Some(before), None) => (STATUS_REMOVED, before, &empty),
+                (None, Some(after)) => (STATUS_ADDED, &empty, after),
+                (None, None) => return None,
+            };
+            let guards = if status == STATUS_CHANGED {
+                delta(&before.guards, &after.guards)
+            } else {
+                DiffDelta::default()
+            };
+            let recompile_reasons = delta(&before.recompile_reasons, &after.recompile_reasons);
+            let graph_breaks = delta(&before.graph_breaks, &after.graph_breaks);
+            let differs = [&guards, &recompile_reasons, &graph_breaks]
+                .iter()
+                .any(|d| !d.added.is_empty() || !d.removed.is_empty());
+            if status == STATUS_CHANGED && !differs {
+                return None;
+            }
+            Some(DiffCompileChanges {
+                compile_dir: dir.clone(),
+                co_name: after.co_name.clone().or_else(|| before.co_name.clone()),
+                status,
+                num_guards: before.guards.len().max(after.guards.len()),
+                guards,
+                recompile_reasons,
+                graph_breaks,
+            })
+        })
+        .collect();
+    Ok(changes)
+}
+
+/// e.g. "1 compile id is new and 1 is gone; guards differ in 1 of the others, recompile reasons
+/// in 1 and graph breaks in 0"
+fn changes_summary(changes: &[DiffCompileChanges]) -> String {
+    let count = |status: &str| changes.iter().filter(|c| c.status == status).count();
+    let changed = |f: &dyn Fn(&DiffCompileChanges) -> &DiffDelta| {
+        changes
+            .iter()
+            .filter(|c| c.status == STATUS_CHANGED)
+            .filter(|c| !f(c).added.is_empty() || !f(c).removed.is_empty())
+            .count()
+    };
+    let (added, removed) = (count(STATUS_ADDED), count(STATUS_REMOVED));
+    format!(
+        "{added} compile id{} new and {removed} gone; guards differ in {} of the others, recompile reasons in {} and graph breaks in {}.",
+        if added == 1 { " is" } else { "s are" },
+        changed(&|c| &c.guards),
+        changed(&|c| &c.recompile_reasons),
+        changed(&|c| &c.graph_breaks),
+    )
+}
+
+#[derive(Serialize)]
+struct DiffJson<'a> {
+    old_log: &'a Path,
+    new_log: &'a Path,
+    changes: &'a [DiffCompileChanges],
+    compiles: &'a [DiffCompileRow],
+}
+
+/// Render the diff report.  With `only_changed`, identical compile ids and artifacts are
+/// dropped and summarized in one line, and only the files that differ are kept in the
+/// returned `old/` and `new/` outputs.
+pub fn render_diff(
+    old_log: &Path,
+    new_log: &Path,
+    old: ParseOutput,
+    new: ParseOutput,
+    only_changed: bool,
+) -> anyhow::Result<ParseOutput> {
+    let mut compiles = diff_outputs(&old, &new);
+    let changes = compile_changes(old_log, new_log)?;
+    let mut identical_summary = String::new();
+    if only_changed {
+        let identical_compiles = compiles
+            .iter()
+            .filter(|c| c.status == STATUS_IDENTICAL)
+            .count();
+        let mut identical_artifacts = 0;
+        compiles.retain(|c| c.status != STATUS_IDENTICAL);
+        for c in compiles.iter_mut() {
+            identical_artifacts += c
+                .artifacts
+                .iter()
+                .filter(|a| a.status == STATUS_IDENTICAL)
+                .count();
+            c.artifacts.retain(|a| a.status != STATUS_IDENTICAL);
+        }
+        identical_summary = format!(
+            "{identical_compiles} identical compile ids and {identical_artifacts} identical artifacts in changed compile ids are omitted."
+        );
+    }
+
+    let linked: FxHashSet<String> = compiles
+        .iter()
+        .flat_map(|c| c.artifacts.iter())
+        .flat_map(|a| [a.old_url.clone(), a.new_url.clone()])
+        .flatten()
+        .collect();
+    let mut output: ParseOutput = Vec::new();
+    for (prefix, run) in [("old", old), ("new", new)] {
+        for (path, contents) in run {
+            if !only_changed || linked.contains(&url(prefix, &path)) {
+                output.push((PathBuf::from(prefix).join(path), contents));
+            }
+        }
+    }
+
+    output.push((
+        PathBuf::from(DIFF_JSON_FILENAME),
+        serde_json::to_string_pretty(&DiffJson {
+            old_log,
+            new_log,
+            changes: &changes,
+            compiles: &compiles,
+        })?,
+    ));
+
+    let tt = crate::report_model::templates(false)?;
+    let context = DiffContext {
+        css: CSS,
+        old_log: old_log.display().to_string(),
+        new_log: new_log.display().to_string(),
+        has_full_reports: !only_changed,
+        num_changed: compiles
+            .iter()
+            .filter(|c| c.status != STATUS_IDENTICAL)
+            .count(),
+        compiles,
+        identical_summary,
+        changes_summary: changes_summary(&changes),
+        changes,
+    };
+    output.push((
+        PathBuf::from("index.html"),
+        tt.render("diff.html", &context)?,
+    ));
+    Ok(output)
+}
@@ -0,0 +1,229 @@
+//! An `index.html` in every subdirectory of the report (compile directories, `payloads/`, ...)
+//! that doesn't have one, listing what is in it with a line on what each file is, for whoever
+//! lands in the directory tree instead of on the report's pages.
+
+use fxhash::FxHashMap;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tinytemplate::TinyTemplate;
+
+use crate::manifest::{artifact_name_kind, size_heat};
+use crate::types::{CompileId, DirectoryIndexContext, DirectoryIndexEntry, ParseOutput};
+use crate::units;
+
+/// Artifact kinds and what they are
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    (
+        "compilation_metrics",
+        "Metrics, stack and outputs of the compile",
+    ),
+    (
+        "bwd_compilation_metrics",
+        "Metrics of the compile's backward",
+    ),
+    (
+        "aot_autograd_backward_compilation_metrics",
+        "Metrics of AOTAutograd's backward compile",
+    ),
+    ("dynamo_output_graph", "FX graph Dynamo captured"),
+    (
+        "dynamo_guards",
+        "Guards Dynamo installed on the compiled code",
+    ),
+    (
+        "dynamo_cpp_guards_str",
+        "Guard tree of the C++ guard manager",
+    ),
+    ("dynamo_cpp_guards", "Guard tree of the C++ guard manager"),
+    ("dynamo_graph_break_reason", "Why Dynamo broke the graph"),
+    ("dynamo_error", "Exception the compile failed with"),
+    (
+        "recompile_reasons",
+        "Guards that failed, causing this recompile",
+    ),
+    (
+        "compiled_autograd_graph",
+        "Graph traced by compiled autograd",
+    ),
+    (
+        "optimize_ddp_split_graph",
+        "Graph DDPOptimizer split the Dynamo graph into",
+    ),
+    (
+        "optimize_ddp_split_child",
+        "Subgraph of the DDPOptimizer split",
+    ),
+    (
+        "aot_joint_graph",
+        "Joint forward and backward graph of AOTAutograd",
+    ),
+    ("aot_forward_graph", "Forward graph AOTAutograd partitioned"),
+    (
+        "aot_backward_graph",
+        "Backward graph AOTAutograd partitioned",
+    ),
+    ("aot_inference_graph", "Inference graph of AOTAutograd"),
+    (
+        "inductor_pre_grad_graph",
+        "Graph before Inductor's pre-grad passes",
+    ),
+    (
+        "inductor_post_grad_graph",
+        "Graph after Inductor's post-grad passes",
+    ),
+    (
+        "inductor_output_code",
+        "Code Inductor generated, run at runtime",
+    ),
+    ("inductor_aot_wrapper_code", "AOTInductor wrapper code"),
+    (
+        "fx_graph_runnable",
+        "Standalone script reproducing the graph",
+    ),
+    ("fx_graph_cache_hit", "FX graph cache hit"),
+    ("fx_graph_cache_miss", "FX graph cache miss"),
+    ("fx_graph_cache_bypass", "FX graph cache bypass"),
+    ("fx_graph_cache_hash", "Inputs of the FX graph cache key"),
+    ("aotautograd_cache_hit", "AOTAutograd cache hit"),
+    ("aotautograd_cache_miss", "AOTAutograd cache miss"),
+    ("aotautograd_cache_bypass", "AOTAutograd cache bypass"),
+    (
+        "aotautograd_cache_hash",
+        "Inputs of the AOTAutograd cache key",
+    ),
+    (
+        "inductor_provenance_tracking_node_mappings",
+        "Which generated code came from which graph node",
+    ),
+];
+
+fn description(path: &Path) -> String {
+    if path.starts_with("payloads") {
+        return "Raw payload from the log".to_string();
+    }
+    // Named after the compile id, which the kind pattern would take for an output number
+    if path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with("chromium_events_"))
+    {
+        return "Chromium events of the compile".to_string();
+    }
+    let kind = artifact_name_kind(&path.to_string_lossy());
+    if let Some((_, description)) = DESCRIPTIONS.iter().find(|(k, _)| *k == kind) {
+        return description.to_string();
+    }
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") => "Page".to_string(),
+        Some("json") => format!("JSON artifact {kind}"),
+        _ => format!("Artifact {kind}"),
+    }
+}
+
+/// The compile id of a compile directory, the reverse of `CompileId::as_directory_name`
+fn compile_id(dir_name: &str) -> Option<CompileId> {
+    let ids: Vec<Option<u32>> = dir_name
+        .split('_')
+        .map(|id| match id {
+            "-" => Ok(None),
+            id => id.parse().map(Some),
+        })
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let [compiled_autograd_id, frame_id, frame_compile_id, attempt] = ids[..] else {
+        return None;
+    };
+    Some(CompileId {
+        compiled_autograd_id,
+        frame_id,
+        frame_compile_id,
+        attempt,
+    })
+}
+
+#[derive(Default)]
+struct Listing {
+    files: Vec<(PathBuf, u64)>,
+    /// Subdirectory -> number of files and bytes under it
+    subdirs: BTreeMap<PathBuf, (usize, u64)>,
+}
+
+/// Add the directory pages to `output`; `bytes` has the size of outputs whose contents were
+/// dropped from memory
+pub(crate) fn add_directory_indexes(
+    tt: &TinyTemplate,
+    output: &mut ParseOutput,
+    bytes: &FxHashMap<PathBuf, u64>,
+) {
+    let mut dirs: BTreeMap<PathBuf, Listing> = BTreeMap::new();
+    for (path, contents) in output.iter() {
+        let size = bytes.get(path).copied().unwrap_or(contents.len() as u64);
+        let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) else {
+            continue;
+        };
+        dirs.entry(parent.to_path_buf())
+            .or_default()
+            .files
+            .push((path.clone(), size));
+        for dir in parent.ancestors().skip(1) {
+            let sub = parent
+                .ancestors()
+                .find(|a| a.parent() == Some(dir))
+                .unwrap();
+            let totals = dirs
+                .entry(dir.to_path_buf())
+                .or_default()
+                .subdirs
+                .entry(sub.to_path_buf())
+                .or_default();
+            totals.0 += 1;
+            totals.1 += size;
+        }
+    }
+    dirs.remove(Path::new(""));
+
+    for (dir, Listing { mut files, subdirs }) in dirs {
+        let page = dir.join("index.html");
+        if files.iter().any(|(path, _)| *path == page) {
+            continue;
+        }
+        files.sort();
+        let name = |path: &Path| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        };
+        let depth = dir.components().count();
+        let root = "../".repeat(depth);
+        let context = DirectoryIndexContext {
+            css: crate::templates::CSS,
+            path: crate::paths::url(&dir),
+            root_url: format!("{root}index.html"),
+            compile_id: compile_id(&name(&dir)).map(|c| c.to_string()),
+            dirs: subdirs
+                .into_iter()
+                .map(|(sub, (num_files, bytes))| DirectoryIndexEntry {
+                    url: format!("{}/index.html", name(&sub)),
+                    name: format!("{}/", name(&sub)),
+                    description: format!("{} files", units::count(num_files as u64)),
+                    size: units::bytes(bytes),
+                    size_heat: size_heat(bytes),
+                })
+                .collect(),
+            files: files
+                .into_iter()
+                .map(|(path, bytes)| DirectoryIndexEntry {
+                    url: name(&path),
+                    name: name(&path),
+                    description: description(&path),
+                    size: units::bytes(bytes),
+                    size_heat: size_heat(bytes),
+                })
+                .collect(),
+        };
+        output.push((
+            page,
+            crate::report_model::render(tt, "directory_index.html", &context),
+        ));
+    }
+}
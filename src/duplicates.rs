@@ -0,0 +1,100 @@
+//! Compile ids whose Dynamo graphs are effectively identical (`duplicate_graphs.html`).
+//!
+//! Compiling the same graph again, e.g. once per decoder layer instance, usually means a missed
+//! cache hit or per-instance compilation.  Graphs are compared after dropping source comments
+//! and blank lines, and with the numbers inside identifiers erased, so `l_self_layers_0_attn`
+//! and `l_self_layers_7_attn` are the same input while tensor shapes still have to match.
+
+use fxhash::{FxHashMap, FxHashSet};
+use indexmap::IndexMap;
+use md5::{Digest, Md5};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{DuplicateGraphCluster, DuplicateGraphCompile};
+use crate::units;
+
+static IDENTIFIER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b[A-Za-z_]\w*\b").unwrap());
+static DIGITS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d+").unwrap());
+
+/// Fingerprint of a `dynamo_output_graph` payload
+pub fn graph_fingerprint(graph: &str) -> String {
+    let mut hasher = Md5::new();
+    for line in graph.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = IDENTIFIER.replace_all(line, |caps: &regex::Captures| {
+            DIGITS.replace_all(&caps[0], "N").into_owned()
+        });
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+    let digest = hasher.finalize();
+    digest[..6].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// One compiled graph: compile id, URL of its graph artifact and fingerprint
+#[derive(Debug)]
+pub struct CompiledGraph {
+    pub compile_id: String,
+    pub url: String,
+    pub fingerprint: String,
+}
+
+/// Group graphs with the same fingerprint.  Every compile after the first of a cluster counts
+/// as wasted; clusters are ordered by wasted time, largest first.
+pub fn duplicate_clusters(
+    graphs: &[CompiledGraph],
+    compile_times: &FxHashMap<String, f64>,
+) -> Vec<DuplicateGraphCluster> {
+    let mut by_fingerprint: IndexMap<&str, Vec<&CompiledGraph>> = IndexMap::new();
+    for graph in graphs {
+        by_fingerprint
+            .entry(graph.fingerprint.as_str())
+            .or_default()
+            .push(graph);
+    }
+    let mut clusters: Vec<(f64, DuplicateGraphCluster)> = by_fingerprint
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(fingerprint, members)| {
+            let wasted_s: f64 = members[1..]
+                .iter()
+                .filter_map(|g| compile_times.get(&g.compile_id))
+                .sum();
+            let compiles = members
+                .iter()
+                .map(|g| DuplicateGraphCompile {
+                    compile_id: g.compile_id.clone(),
+                    url: g.url.clone(),
+                    compile_time: compile_times
+                        .get(&g.compile_id)
+                        .map_or("N/A".to_string(), |t| units::seconds(*t, 3)),
+                })
+                .collect();
+            (
+                wasted_s,
+                DuplicateGraphCluster {
+                    fingerprint: fingerprint.to_string(),
+                    num_compiles: members.len(),
+                    wasted: units::seconds(wasted_s, 3),
+                    compiles,
+                },
+            )
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.0.total_cmp(&a.0));
+    clusters.into_iter().map(|(_, cluster)| cluster).collect()
+}
+
+/// Compile time of every graph whose fingerprint was already compiled before it
+pub fn wasted_time(graphs: &[CompiledGraph], compile_times: &FxHashMap<String, f64>) -> f64 {
+    let mut seen: FxHashSet<&str> = FxHashSet::default();
+    graphs
+        .iter()
+        .filter(|g| !seen.insert(g.fingerprint.as_str()))
+        .filter_map(|g| compile_times.get(&g.compile_id))
+        .sum()
+}
@@ -0,0 +1,219 @@
+//! A few plain sentences per compile id assembled from its metrics and artifacts ("Frame
+//! `forward` (model.py:12) compiled in 42.00s; 3 graph breaks ..."), shown at the top of its
+//! compilation metrics page and as `explanation` in summary.json, so readers who don't know the
+//! compiler stack get the story before the raw artifacts.
+
+use html_escape::encode_text;
+use indexmap::IndexMap;
+
+use crate::types::{CompilationMetricsMetadata, GraphBreak, OutputFile};
+use crate::units;
+
+/// Artifact name prefix -> cache it reports on
+const CACHES: [(&str, &str); 3] = [
+    ("fx_graph_cache_", "FX graph cache"),
+    ("aotautograd_cache_", "AOTAutograd cache"),
+    ("autograd_cache_", "AOTAutograd cache"),
+];
+
+pub(crate) fn basename(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+fn plural(n: usize, what: &str) -> String {
+    if n == 1 {
+        format!("1 {what}")
+    } else if what.ends_with('s') {
+        format!("{n} {what}es")
+    } else {
+        format!("{n} {what}s")
+    }
+}
+
+/// `Graph break in user code at /x/model.py:25` / `Reason: Unsupported: ...` payloads
+pub fn parse_graph_break(payload: &str) -> GraphBreak {
+    let mut lines = payload.lines().map(str::trim);
+    let location = payload
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("Graph break in user code at "))
+        .map(|l| basename(l).to_string());
+    let reason = lines
+        .find_map(|l| l.strip_prefix("Reason:"))
+        .map(|r| r.trim().to_string())
+        .or_else(|| payload.lines().next().map(|l| l.trim().to_string()))
+        .unwrap_or_default();
+    GraphBreak { reason, location }
+}
+
+/// `(hits, misses, bypasses)` per cache, from the names of the compile's artifacts
+fn cache_results(files: &[OutputFile]) -> IndexMap<&'static str, (usize, usize, usize)> {
+    let mut results: IndexMap<&'static str, (usize, usize, usize)> = IndexMap::new();
+    for file in files {
+        let name = basename(&file.url);
+        let Some((prefix, cache)) = CACHES.iter().find(|(prefix, _)| name.starts_with(prefix))
+        else {
+            continue;
+        };
+        let counts = results.entry(cache).or_default();
+        match &name[prefix.len()..] {
+            r if r.starts_with("hit") => counts.0 += 1,
+            r if r.starts_with("miss") => counts.1 += 1,
+            r if r.starts_with("bypass") => counts.2 += 1,
+            _ => {}
+        }
+    }
+    results.retain(|_, counts| *counts != (0, 0, 0));
+    results
+}
+
+/// The explanation of one compile, one sentence per fact; code is quoted with backticks
+pub fn explain(
+    m: &CompilationMetricsMetadata,
+    files: &[OutputFile],
+    graph_breaks: &[GraphBreak],
+) -> Vec<String> {
+    let mut sentences = Vec::new();
+
+    let mut frame = match &m.co_name {
+        Some(name) => format!("Frame `{name}`"),
+        None => "This frame".to_string(),
+    };
+    if let (Some(filename), Some(line)) = (&m.co_filename, m.co_firstlineno) {
+        frame.push_str(&format!(" ({}:{line})", basename(filename)));
+    }
+    let time = m.entire_frame_compile_time_s;
+    if let Some(fail_type) = &m.fail_type {
+        let reason = m
+            .fail_reason
+            .as_deref()
+            .and_then(|r| r.lines().next())
+            .unwrap_or_default();
+        sentences.push(match time {
+            Some(t) => {
+                format!(
+                    "{frame} failed to compile after {} with {fail_type}: {reason}.",
+                    units::seconds(t, 2)
+                )
+            }
+            None => format!("{frame} failed to compile with {fail_type}: {reason}."),
+        });
+    } else if let Some(t) = time {
+        let mut s = format!("{frame} compiled in {}", units::seconds(t, 2));
+        if let Some(backend) = m.backend_compile_time_s {
+            s.push_str(&format!(
+                ", {} of it in the backend",
+                units::seconds(backend, 2)
+            ));
+            if let Some(inductor) = m.inductor_compile_time_s {
+                s.push_str(&format!(" (Inductor {})", units::seconds(inductor, 2)));
+            }
+        }
+        s.push('.');
+        sentences.push(s);
+    } else {
+        sentences.push(format!("{frame} was compiled."));
+    }
+
+    if let Some(n) = m.cache_size.filter(|n| *n > 0) {
+        sentences.push(format!(
+            "This is a recompile: the frame already had {} in its cache.",
+            plural(n as usize, "compiled version")
+        ));
+    }
+
+    let mut restarts: Vec<&String> = m.restart_reasons.iter().flatten().collect();
+    restarts.dedup();
+    if !restarts.is_empty() {
+        let reasons: Vec<&str> = restarts.iter().map(|r| r.as_str()).collect();
+        sentences.push(format!(
+            "Dynamo restarted its analysis {}: {}.",
+            plural(restarts.len(), "time"),
+            reasons.join("; ")
+        ));
+    }
+
+    if !graph_breaks.is_empty() {
+        let mut grouped: IndexMap<(&str, Option<&str>), usize> = IndexMap::new();
+        for b in graph_breaks {
+            *grouped
+                .entry((b.reason.as_str(), b.location.as_deref()))
+                .or_default() += 1;
+        }
+        let described: Vec<String> = grouped
+            .into_iter()
+            .map(|((reason, location), count)| {
+                let mut s = reason.to_string();
+                if let Some(location) = location {
+                    s.push_str(&format!(" at `{location}`"));
+                }
+                if count > 1 {
+                    s.push_str(&format!(" ({count}×)"));
+                }
+                s
+            })
+            .collect();
+        sentences.push(format!(
+            "{}: {}.",
+            plural(graph_breaks.len(), "graph break"),
+            described.join("; ")
+        ));
+    }
+
+    if let Some(ops) = m.graph_op_count {
+        let mut s = format!("The captured graph has {}", plural(ops as usize, "op"));
+        if let Some(inputs) = m.graph_input_count {
+            s.push_str(&format!(" on {}", plural(inputs as usize, "input")));
+        }
+        if let Some(guards) = m.guard_count {
+            s.push_str(&format!(
+                " and is protected by {}",
+                plural(guards as usize, "guard")
+            ));
+        }
+        s.push('.');
+        sentences.push(s);
+    }
+
+    if let Some(ops) = m.non_compliant_ops.as_ref().filter(|ops| !ops.is_empty()) {
+        let ops: Vec<String> = ops.iter().map(|op| format!("`{op}`")).collect();
+        sentences.push(format!(
+            "It uses {}: {}.",
+            plural(ops.len(), "non-compliant custom op"),
+            ops.join(", ")
+        ));
+    }
+
+    for (cache, (hits, misses, bypasses)) in cache_results(files) {
+        sentences.push(match (hits, misses, bypasses) {
+            (h, 0, 0) => format!("Re-used the {cache} ({}).", plural(h, "hit")),
+            (0, m, 0) => format!(
+                "Missed the {cache} ({}), so it compiled from scratch.",
+                plural(m, "miss")
+            ),
+            (0, 0, b) => format!("Bypassed the {cache} ({}).", plural(b, "bypass")),
+            (h, m, b) => format!("{cache}: {h} hits, {m} misses, {b} bypasses."),
+        });
+    }
+    sentences
+}
+
+/// Sentences as HTML, with backtick quotes as `<code>`
+pub fn to_html(sentences: &[String]) -> String {
+    sentences
+        .iter()
+        .map(|s| {
+            encode_text(s)
+                .split('`')
+                .enumerate()
+                .map(|(i, part)| {
+                    if i % 2 == 1 {
+                        format!("<code>{part}</code>")
+                    } else {
+                        part.to_string()
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
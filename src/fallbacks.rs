@@ -0,0 +1,162 @@
+//! Ops that ran as eager kernels inside compiled regions (`fallback_kernels.html`).
+//!
+//! Inductor generates a kernel for most ops, but falls back to calling the eager ATen or
+//! custom op implementation for ops it can't lower, and calls library kernels (cuBLAS, cuDNN,
+//! ...) through `extern_kernels` for matmuls and convolutions it doesn't template.  Both show
+//! up as calls in the wrapper of `inductor_output_code`:
+//!
+//! ```text
+//! buf3 = torch.ops.test.baz.default(buf2)            # fallback (Python wrapper)
+//! aoti_torch_cuda__scaled_mm(buf0, ...)              # fallback (C++ wrapper)
+//! extern_kernels.mm(arg0_1, arg1_1, out=buf0)        # extern kernel
+//! ```
+//!
+//! Unexpected fallbacks are the usual reason a compiled model is slower than expected.
+
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{
+    CompileId, FallbackCompile, FallbackKernelsContext, FallbackOpRow, FxIndexMap, OutputFile,
+};
+
+static TORCH_OPS_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\btorch\.ops\.(?P<ns>\w+)\.(?P<op>\w+)\.(?P<overload>\w+)\(").unwrap()
+});
+// `aten = torch.ops.aten` is in every Python wrapper's preamble
+static ATEN_CALL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[^.\w])aten\.(?P<op>\w+)\.(?P<overload>\w+)\(").unwrap());
+static AOTI_FALLBACK_CALL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\baoti_torch_(?:cpu|cuda|xpu|mps)_(?P<op>\w+)\(").unwrap());
+static EXTERN_KERNEL_CALL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bextern_kernels\.(?P<name>\w+)\(").unwrap());
+
+pub const KIND_FALLBACK: &str = "fallback";
+pub const KIND_EXTERN_KERNEL: &str = "extern kernel";
+
+/// Inductor's own helpers called through `torch.ops`, which aren't eager ops
+const INTERNAL_NAMESPACES: [&str; 1] = ["inductor"];
+
+/// (op, kind) -> compile id (directory key) -> number of calls, in order of first appearance
+#[derive(Default)]
+pub struct FallbackIndex {
+    ops: IndexMap<(String, &'static str), IndexMap<Option<CompileId>, usize>>,
+}
+
+/// Eager calls in generated code, as (op, kind)
+fn calls(code: &str) -> Vec<(String, &'static str)> {
+    let mut calls = Vec::new();
+    for line in code.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') || trimmed.starts_with("//") {
+            continue;
+        }
+        for caps in TORCH_OPS_CALL.captures_iter(line) {
+            if !INTERNAL_NAMESPACES.contains(&&caps["ns"]) {
+                calls.push((
+                    format!("{}.{}.{}", &caps["ns"], &caps["op"], &caps["overload"]),
+                    KIND_FALLBACK,
+                ));
+            }
+        }
+        for caps in ATEN_CALL.captures_iter(line) {
+            calls.push((
+                format!("aten.{}.{}", &caps["op"], &caps["overload"]),
+                KIND_FALLBACK,
+            ));
+        }
+        for caps in AOTI_FALLBACK_CALL.captures_iter(line) {
+            calls.push((format!("aten.{}", &caps["op"]), KIND_FALLBACK));
+        }
+        for caps in EXTERN_KERNEL_CALL.captures_iter(line) {
+            calls.push((
+                format!("extern_kernels.{}", &caps["name"]),
+                KIND_EXTERN_KERNEL,
+            ));
+        }
+    }
+    calls
+}
+
+impl FallbackIndex {
+    /// Record the eager calls in one `inductor_output_code` payload
+    pub fn add(&mut self, compile_id: &Option<CompileId>, code: &str) {
+        for call in calls(code) {
+            *self
+                .ops
+                .entry(call)
+                .or_default()
+                .entry(compile_id.clone())
+                .or_default() += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// For the index page, e.g. "2 ops fall back to eager and 1 extern kernel is called"
+    pub fn summary(&self) -> String {
+        let count = |kind| self.ops.keys().filter(|(_, k)| *k == kind).count();
+        let mut parts = Vec::new();
+        match count(KIND_FALLBACK) {
+            0 => {}
+            1 => parts.push("1 op falls back to eager".to_string()),
+            n => parts.push(format!("{n} ops fall back to eager")),
+        }
+        match count(KIND_EXTERN_KERNEL) {
+            0 => {}
+            1 => parts.push("1 extern kernel is called".to_string()),
+            n => parts.push(format!("{n} extern kernels are called")),
+        }
+        parts.join(" and ")
+    }
+
+    /// Fallbacks first, then extern kernels, most called first; compile ids link to their
+    /// output code
+    pub fn context(
+        &self,
+        directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    ) -> FallbackKernelsContext {
+        let mut rows: Vec<FallbackOpRow> = self
+            .ops
+            .iter()
+            .map(|((op, kind), compiles)| FallbackOpRow {
+                op: op.clone(),
+                kind,
+                calls: compiles.values().sum(),
+                num_compiles: compiles.len(),
+                compiles: compiles
+                    .iter()
+                    .map(|(cid, calls)| FallbackCompile {
+                        compile_id: cid
+                            .as_ref()
+                            .map_or("(unknown)".to_string(), |c| c.to_string()),
+                        url: directory
+                            .get(cid)
+                            .and_then(|files| {
+                                files
+                                    .iter()
+                                    .find(|f| f.name.contains("inductor_output_code"))
+                            })
+                            .map(|f| f.url.clone()),
+                        calls: *calls,
+                    })
+                    .collect(),
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            (a.kind != KIND_FALLBACK)
+                .cmp(&(b.kind != KIND_FALLBACK))
+                .then(b.calls.cmp(&a.calls))
+        });
+        FallbackKernelsContext {
+            css: crate::templates::CSS,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            num_fallback_ops: rows.iter().filter(|r| r.kind == KIND_FALLBACK).count(),
+            num_extern_kernels: rows.iter().filter(|r| r.kind == KIND_EXTERN_KERNEL).count(),
+            rows,
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! C API for embedding the parser in non-Rust tools (built with `--features ffi`).
+//!
+//! Every function taking a `char **error_out` sets it to a message on failure (free it with
+//! `tlparse_string_free`), and leaves it untouched on success.  Options are passed as a JSON
+//! object, NULL meaning all defaults:
+//!
+//! ```json
+//! {"strict": false, "strict_compile_id": false, "plain_text": false, "export": false,
+//!  "inductor_provenance": false, "custom_header_html": "", "derived_metrics": {}}
+//! ```
+//!
+//! `derived_metrics` takes the same form as in `--config` files.
+
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::config::ConfigFile;
+use crate::lock::OutputLock;
+use crate::manifest::{Manifest, MANIFEST_FILENAME};
+use crate::{parse_path, ParseConfig};
+
+pub const TLPARSE_OK: i32 = 0;
+pub const TLPARSE_ERR_INVALID_ARGUMENT: i32 = 1;
+pub const TLPARSE_ERR_PARSE: i32 = 2;
+pub const TLPARSE_ERR_PANIC: i32 = 3;
+
+/// Called with (bytes read, log size, user_data) while parsing
+pub type ProgressCallback = Option<extern "C" fn(u64, u64, *mut c_void)>;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Options {
+    strict: bool,
+    strict_compile_id: bool,
+    plain_text: bool,
+    export: bool,
+    inductor_provenance: bool,
+    custom_header_html: String,
+    #[serde(flatten)]
+    config: ConfigFile,
+}
+
+struct Error {
+    code: i32,
+    error: anyhow::Error,
+}
+
+fn invalid_argument(error: anyhow::Error) -> Error {
+    Error {
+        code: TLPARSE_ERR_INVALID_ARGUMENT,
+        error,
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            code: TLPARSE_ERR_PARSE,
+            error,
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Error> {
+    if ptr.is_null() {
+        return Err(invalid_argument(anyhow!("{name} is NULL")));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| invalid_argument(anyhow!("{name} is not valid UTF-8")))
+}
+
+unsafe fn parse_config(options_json: *const c_char) -> Result<ParseConfig, Error> {
+    let options: Options = if options_json.is_null() {
+        Options::default()
+    } else {
+        serde_json::from_str(str_arg(options_json, "options_json")?)
+            .context("Couldn't parse options_json")
+            .map_err(invalid_argument)?
+    };
+    let mut config = ParseConfig {
+        strict: options.strict,
+        strict_compile_id: options.strict_compile_id,
+        plain_text: options.plain_text,
+        export: options.export,
+        inductor_provenance: options.inductor_provenance,
+        custom_header_html: options.custom_header_html,
+        ..Default::default()
+    };
+    options
+        .config
+        .apply(&mut config)
+        .map_err(invalid_argument)?;
+    Ok(config)
+}
+
+fn to_c_string(s: String) -> *mut c_char {
+    // Interior NULs can't cross the boundary; they never occur in JSON or our messages
+    CString::new(s.replace('\0', "")).unwrap().into_raw()
+}
+
+/// Run `f`, turning errors and panics into a code and a message in `error_out`
+unsafe fn guard<T>(
+    error_out: *mut *mut c_char,
+    f: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, i32> {
+    let (code, message) = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return Ok(value),
+        Ok(Err(e)) => (e.code, format!("{:#}", e.error)),
+        Err(panic) => (
+            TLPARSE_ERR_PANIC,
+            panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "tlparse panicked".to_string()),
+        ),
+    };
+    if !error_out.is_null() {
+        *error_out = to_c_string(message);
+    }
+    Err(code)
+}
+
+/// Parse the log at `log_path` and write the report into `out_dir` (created if needed), as
+/// the `tlparse` command does.  Returns `TLPARSE_OK` or one of the `TLPARSE_ERR_*` codes.
+///
+/// # Safety
+///
+/// `log_path` and `out_dir` must be NUL-terminated strings; `options_json` and `error_out`
+/// must be NULL or valid.  `progress` is called on the calling thread with `user_data`.
+#[no_mangle]
+pub unsafe extern "C" fn tlparse_parse_to_dir(
+    log_path: *const c_char,
+    out_dir: *const c_char,
+    options_json: *const c_char,
+    progress: ProgressCallback,
+    user_data: *mut c_void,
+    error_out: *mut *mut c_char,
+) -> i32 {
+    let result = guard(error_out, || {
+        let log_path = PathBuf::from(str_arg(log_path, "log_path")?);
+        let out_dir = PathBuf::from(str_arg(out_dir, "out_dir")?);
+        let mut config = parse_config(options_json)?;
+        if let Some(progress) = progress {
+            let user_data = user_data as usize;
+            config.progress = Some(std::sync::Arc::new(move |read, total| {
+                progress(read, total, user_data as *mut c_void)
+            }));
+        }
+        let _lock = OutputLock::acquire(&out_dir, false)?;
+        let start = Instant::now();
+        let output = parse_path(&log_path, &config)?;
+        let mut manifest = Manifest::new(&log_path, &output, start.elapsed());
+        manifest.metadata = config.metadata.clone();
+        crate::paths::write_output(&out_dir, output)?;
+        crate::paths::write_atomic(
+            out_dir.join(MANIFEST_FILENAME),
+            serde_json::to_string_pretty(&manifest).map_err(anyhow::Error::from)?,
+        )
+        .context("Writing manifest")?;
+        Ok(())
+    });
+    result.err().unwrap_or(TLPARSE_OK)
+}
+
+/// Parse the log at `log_path` without writing anything and return the contents of the
+/// report's summary.json, or NULL on failure.  Free the result with `tlparse_string_free`.
+///
+/// # Safety
+///
+/// As for `tlparse_parse_to_dir`.
+#[no_mangle]
+pub unsafe extern "C" fn tlparse_summary_json(
+    log_path: *const c_char,
+    options_json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = guard(error_out, || {
+        let log_path = PathBuf::from(str_arg(log_path, "log_path")?);
+        let config = parse_config(options_json)?;
+        let output = parse_path(&log_path, &config)?;
+        let summary = output
+            .into_iter()
+            .find(|(path, _)| path.as_os_str() == "summary.json")
+            .ok_or_else(|| anyhow!("Report has no summary.json"))?;
+        Ok(summary.1)
+    });
+    result.map_or(std::ptr::null_mut(), to_c_string)
+}
+
+/// Free a string returned by this library.  NULL is ignored.
+///
+/// # Safety
+///
+/// `s` must come from a tlparse function and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn tlparse_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Version of the library, e.g. "0.3.30"; static, don't free it
+#[no_mangle]
+pub extern "C" fn tlparse_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
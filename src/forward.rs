@@ -0,0 +1,298 @@
+//! `--forward URL` (`forward` feature): publish the parsed results of a run to a message queue,
+//! so the compile telemetry of many hosts, each running tlparse on its own logs, lands in one
+//! place.
+//!
+//! Each report in the output directory (one per rank with `--all-ranks-html`) becomes a `run`
+//! event followed by one `compile` event per entry of its `summary.json`.  Events are JSON
+//! objects carrying the host, log and rank they came from:
+//!
+//! ```text
+//! {"event": "run", "host": "node7", "log": "...", "rank": 0, "num_compiles": 12, ...}
+//! {"event": "compile", "host": "node7", "log": "...", "rank": 0, "compile_id": "[0/0]", ...}
+//! ```
+//!
+//! Supported targets, spoken over plain TCP so the feature adds no dependencies:
+//!
+//! * `nats://HOST[:4222]/SUBJECT`: NATS core publish, confirmed with a round trip
+//! * `kafka://HOST[:8082]/TOPIC`: a Kafka REST Proxy (v2 JSON embedded format), keyed by log,
+//!   confirmed by an error-free offset for every record in the response
+
+use anyhow::{bail, Context};
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::manifest::load_reports;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Fields of a summary.json compile that only make sense next to the report
+const LOCAL_FIELDS: [&str; 1] = ["metrics_url"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForwardTarget {
+    Nats { addr: String, subject: String },
+    KafkaRest { addr: String, topic: String },
+}
+
+impl ForwardTarget {
+    pub fn parse(url: &str) -> anyhow::Result<Self> {
+        let Some((scheme, rest)) = url.split_once("://") else {
+            bail!("Expected a nats:// or kafka:// URL, got `{url}`");
+        };
+        let Some((host, name)) = rest
+            .split_once('/')
+            .filter(|(h, n)| !h.is_empty() && !n.is_empty())
+        else {
+            bail!("Expected {scheme}://HOST[:PORT]/NAME, got `{url}`");
+        };
+        let with_port = |default: u16| match host.contains(':') {
+            true => host.to_string(),
+            false => format!("{host}:{default}"),
+        };
+        match scheme {
+            "nats" => Ok(Self::Nats {
+                addr: with_port(4222),
+                subject: name.to_string(),
+            }),
+            "kafka" => Ok(Self::KafkaRest {
+                addr: with_port(8082),
+                topic: name.to_string(),
+            }),
+            _ => bail!("Unsupported forwarding scheme `{scheme}`, expected nats or kafka"),
+        }
+    }
+
+    /// Deliver `events` in order, failing unless the server accepted all of them
+    pub fn send(&self, events: &[Value]) -> anyhow::Result<()> {
+        match self {
+            Self::Nats { addr, subject } => send_nats(addr, subject, events),
+            Self::KafkaRest { addr, topic } => send_kafka_rest(addr, topic, events),
+        }
+        .with_context(|| format!("Forwarding to {self}"))
+    }
+}
+
+impl std::fmt::Display for ForwardTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nats { addr, subject } => write!(f, "nats://{addr}/{subject}"),
+            Self::KafkaRest { addr, topic } => write!(f, "kafka://{addr}/{topic}"),
+        }
+    }
+}
+
+fn connect(addr: &str) -> anyhow::Result<TcpStream> {
+    let addr = addr
+        .to_socket_addrs()?
+        .next()
+        .with_context(|| format!("Couldn't resolve {addr}"))?;
+    let stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+fn send_nats(addr: &str, subject: &str, events: &[Value]) -> anyhow::Result<()> {
+    let mut stream = connect(addr)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if !line.starts_with("INFO") {
+        bail!("Expected the NATS INFO greeting, got `{}`", line.trim_end());
+    }
+    let mut out = Vec::new();
+    out.extend_from_slice(
+        b"CONNECT {\"verbose\":false,\"pedantic\":false,\"name\":\"tlparse\"}\r\n",
+    );
+    for event in events {
+        let payload = serde_json::to_vec(event)?;
+        out.extend_from_slice(format!("PUB {subject} {}\r\n", payload.len()).as_bytes());
+        out.extend_from_slice(&payload);
+        out.extend_from_slice(b"\r\n");
+    }
+    // The server answers PING only after processing everything before it
+    out.extend_from_slice(b"PING\r\n");
+    stream.write_all(&out)?;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            bail!("NATS server closed the connection");
+        }
+        match line.trim_end() {
+            "PONG" => return Ok(()),
+            "PING" => stream.write_all(b"PONG\r\n")?,
+            err if err.starts_with("-ERR") => bail!("NATS server replied {err}"),
+            _ => {}
+        }
+    }
+}
+
+fn send_kafka_rest(addr: &str, topic: &str, events: &[Value]) -> anyhow::Result<()> {
+    let records: Vec<Value> = events
+        .iter()
+        .map(|event| json!({"key": event["log"], "value": event}))
+        .collect();
+    let body = serde_json::to_vec(&json!({ "records": records }))?;
+    let mut stream = connect(addr)?;
+    let head = format!(
+        "POST /topics/{topic} HTTP/1.1\r\nHost: {addr}\r\n\
+         Content-Type: application/vnd.kafka.json.v2+json\r\n\
+         Accept: application/vnd.kafka.v2+json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&body)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    let status = head.lines().next().unwrap_or_default();
+    if !status
+        .split_whitespace()
+        .nth(1)
+        .is_some_and(|code| code.starts_with('2'))
+    {
+        bail!("Kafka REST Proxy replied `{status}`");
+    }
+    let chunked = head.lines().any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.eq_ignore_ascii_case("transfer-encoding") && value.trim() == "chunked"
+        })
+    });
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body.to_string()
+    };
+    check_offsets(&body, records.len())
+}
+
+/// The body of a `Transfer-Encoding: chunked` response
+fn dechunk(mut body: &str) -> anyhow::Result<String> {
+    let mut out = String::new();
+    loop {
+        let Some((size, rest)) = body.split_once("\r\n") else {
+            bail!("Truncated chunked response");
+        };
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or_default().trim(), 16)
+            .with_context(|| format!("Invalid chunk size `{size}`"))?;
+        if size == 0 {
+            return Ok(out);
+        }
+        let Some(chunk) = rest.get(..size) else {
+            bail!("Truncated chunked response");
+        };
+        out.push_str(chunk);
+        body = rest[size..].strip_prefix("\r\n").unwrap_or(&rest[size..]);
+    }
+}
+
+/// A produce request succeeds per record: the proxy answers 200 with an `error_code` on the
+/// offset of each record it couldn't write
+fn check_offsets(body: &str, num_records: usize) -> anyhow::Result<()> {
+    let response: Value = serde_json::from_str(body)
+        .with_context(|| format!("Kafka REST Proxy replied with invalid JSON `{body}`"))?;
+    let Some(offsets) = response["offsets"].as_array() else {
+        bail!("Kafka REST Proxy reply has no offsets: `{body}`");
+    };
+    if offsets.len() != num_records {
+        bail!(
+            "Kafka REST Proxy returned {} offsets for {num_records} records",
+            offsets.len()
+        );
+    }
+    let failed: Vec<&Value> = offsets
+        .iter()
+        .filter(|offset| !offset["error_code"].is_null())
+        .collect();
+    if let Some(first) = failed.first() {
+        bail!(
+            "Kafka REST Proxy failed to write {} of {num_records} records, first error {}: {}",
+            failed.len(),
+            first["error_code"],
+            first["error"].as_str().unwrap_or("unknown error")
+        );
+    }
+    Ok(())
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The events of every report in `out_dir`, rank by rank
+pub fn events(out_dir: &Path) -> anyhow::Result<Vec<Value>> {
+    let host = hostname();
+    let mut events = Vec::new();
+    for (dir, manifest) in load_reports(out_dir)? {
+        let rank: Option<u32> = dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_prefix("rank_"))
+            .and_then(|r| r.parse().ok())
+            .filter(|_| dir != out_dir);
+        let summary_path = dir.join("summary.json");
+        let summary: Value = serde_json::from_str(
+            &std::fs::read_to_string(&summary_path)
+                .with_context(|| format!("Reading {}", summary_path.display()))?,
+        )?;
+        let compiles = summary["compiles"].as_array().cloned().unwrap_or_default();
+        let source = json!({
+            "host": host,
+            "log": manifest.log,
+            "rank": rank,
+            "metadata": manifest.metadata,
+        });
+        let with_source = |event: &str, fields: Map<String, Value>| {
+            let mut out = Map::new();
+            out.insert("event".to_string(), event.into());
+            out.extend(source.as_object().unwrap().clone());
+            out.extend(fields);
+            Value::Object(out)
+        };
+
+        let mut run = Map::new();
+        run.insert(
+            "tlparse_version".to_string(),
+            manifest.tlparse_version.into(),
+        );
+        run.insert("generated_at".to_string(), manifest.generated_at.into());
+        run.insert(
+            "parse_duration_s".to_string(),
+            manifest.parse_duration_s.into(),
+        );
+        run.insert("num_compiles".to_string(), compiles.len().into());
+        run.insert(
+            "num_failed_compiles".to_string(),
+            compiles
+                .iter()
+                .filter(|c| !c["fail_type"].is_null())
+                .count()
+                .into(),
+        );
+        run.insert(
+            "device_backends".to_string(),
+            summary["device_backends"].clone(),
+        );
+        events.push(with_source("run", run));
+
+        for compile in compiles {
+            let Value::Object(mut fields) = compile else {
+                continue;
+            };
+            for field in LOCAL_FIELDS {
+                fields.remove(field);
+            }
+            events.push(with_source("compile", fields));
+        }
+    }
+    Ok(events)
+}
@@ -0,0 +1,90 @@
+//! Compiles of PyTorch distributed internals rather than model code, e.g. FSDP's parameter
+//! (un)sharding hooks or distributed checkpoint state-dict conversion, which clutter the index
+//! of a model that is wrapped in them.  A compile is attributed to a framework by the frame
+//! Dynamo started compiling, the innermost frame of its `dynamo_start` stack.
+//!
+//! More broadly, `frame_origin` tells compiles rooted in user code apart from those of any
+//! library (PyTorch, vLLM, installed packages), for the "user code only" filter of the index.
+
+use crate::sources::is_framework_file;
+use crate::types::{simplify_filename, unintern_str, StackSummary};
+
+/// Path fragment of the compiled frame's file -> (group key, display name)
+const FRAMEWORK_FILES: [(&str, &str, &str); 5] = [
+    (
+        "torch/distributed/checkpoint/",
+        "dcp",
+        "Distributed checkpoint",
+    ),
+    (
+        "torch/distributed/_state_dict_utils",
+        "dcp",
+        "Distributed checkpoint",
+    ),
+    ("torch/distributed/fsdp/", "fsdp", "FSDP"),
+    ("torch/distributed/_composable/fsdp/", "fsdp", "FSDP"),
+    (
+        "torch/distributed/_composable/",
+        "composable",
+        "Composable distributed APIs",
+    ),
+];
+
+/// Compiled frame names that are state-dict plumbing wherever they are defined
+const STATE_DICT_FRAMES: [&str; 5] = [
+    "state_dict",
+    "load_state_dict",
+    "_load_from_state_dict",
+    "_save_to_state_dict",
+    "_state_dict_hook",
+];
+
+/// Origins of library code -> display name, in the order the index lists them
+pub const FRAME_ORIGINS: [(&str, &str); 3] = [
+    ("torch", "PyTorch"),
+    ("vllm", "vLLM"),
+    ("library", "Other libraries"),
+];
+
+fn compiled_filename(stack: &StackSummary) -> Option<String> {
+    let frame = stack.last()?;
+    let filename = frame
+        .uninterned_filename
+        .clone()
+        .unwrap_or_else(|| unintern_str(frame.filename))
+        .replace('\\', "/");
+    Some(simplify_filename(&filename).to_string())
+}
+
+/// The framework a compile belongs to as (group key, display name), if it is framework
+/// internal
+pub fn classify(stack: &StackSummary) -> Option<(&'static str, &'static str)> {
+    let frame = stack.last()?;
+    let filename = compiled_filename(stack)?;
+    if let Some((_, key, label)) = FRAMEWORK_FILES
+        .iter()
+        .find(|(fragment, _, _)| filename.contains(fragment))
+    {
+        return Some((key, label));
+    }
+    STATE_DICT_FRAMES
+        .contains(&frame.name.as_str())
+        .then_some(("state_dict", "State dict"))
+}
+
+/// Key in `FRAME_ORIGINS` of the library the compiled frame lives in, None for user code
+pub fn frame_origin(stack: &StackSummary) -> Option<&'static str> {
+    let filename = compiled_filename(stack)?;
+    let in_package = |name: &str| {
+        filename.starts_with(&format!("{name}/")) || filename.contains(&format!("/{name}/"))
+    };
+    if in_package("vllm") {
+        Some("vllm")
+    } else if in_package("torch") {
+        Some("torch")
+    } else if is_framework_file(&filename) {
+        Some("library")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,131 @@
+//! Every graph break of the run on one page (`graph_breaks.html`).  The same break is usually
+//! hit by many compiles (each recompile of a frame, each layer calling the same helper), so
+//! breaks with the same reason and user stack are listed once, most frequent first, with how
+//! often they happened and the compile that hit them first.
+
+use fxhash::FxHashMap;
+
+use crate::types::{CompileId, GraphBreaksContext, GraphBreaksRow};
+
+struct UniqueBreak {
+    reason: String,
+    /// `file.py:line` of the break
+    location: Option<String>,
+    /// The `User code traceback` of the payload, as logged
+    traceback: String,
+    count: usize,
+    /// Compile ids hitting the break, first hit first
+    compile_ids: Vec<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct GraphBreaks {
+    breaks: Vec<UniqueBreak>,
+    /// (reason, location, `File ...` lines of the traceback) -> index into `breaks`
+    by_key: FxHashMap<(String, Option<String>, String), usize>,
+}
+
+/// The lines of the payload after `User code traceback:`
+fn traceback(payload: &str) -> String {
+    payload
+        .lines()
+        .skip_while(|l| l.trim() != "User code traceback:")
+        .skip(1)
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl GraphBreaks {
+    pub fn is_empty(&self) -> bool {
+        self.breaks.is_empty()
+    }
+
+    /// A `dynamo_graph_break_reason` payload
+    pub fn add(&mut self, compile_id: &Option<CompileId>, payload: &str) {
+        let graph_break = crate::explain::parse_graph_break(payload);
+        let traceback = traceback(payload);
+        // Frames, without the source lines under them, so edits elsewhere in a file don't split
+        // a break
+        let frames = traceback
+            .lines()
+            .map(str::trim)
+            .filter(|l| l.starts_with("File "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let key = (graph_break.reason, graph_break.location, frames);
+        let compile_id = compile_id
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string());
+        let i = match self.by_key.get(&key) {
+            Some(i) => *i,
+            None => {
+                self.breaks.push(UniqueBreak {
+                    reason: key.0.clone(),
+                    location: key.1.clone(),
+                    traceback,
+                    count: 0,
+                    compile_ids: Vec::new(),
+                });
+                self.by_key.insert(key, self.breaks.len() - 1);
+                self.breaks.len() - 1
+            }
+        };
+        let unique = &mut self.breaks[i];
+        unique.count += 1;
+        if !unique.compile_ids.contains(&compile_id) {
+            unique.compile_ids.push(compile_id);
+        }
+    }
+
+    /// Most frequent first, then in order of first hit
+    fn sorted(&self) -> Vec<&UniqueBreak> {
+        let mut breaks: Vec<&UniqueBreak> = self.breaks.iter().collect();
+        breaks.sort_by_key(|b| std::cmp::Reverse(b.count));
+        breaks
+    }
+
+    /// For the index page, e.g. "7 graph breaks, 3 of them unique; the most frequent, Call to
+    /// `torch._dynamo.graph_break()` (model.py:33), was hit 4 times"
+    pub fn summary(&self) -> String {
+        let total: usize = self.breaks.iter().map(|b| b.count).sum();
+        let mut summary = match (total, self.breaks.len()) {
+            (1, _) => "1 graph break".to_string(),
+            (n, unique) if unique == n => format!("{n} graph breaks, all different"),
+            (n, 1) => format!("{n} graph breaks, all the same"),
+            (n, unique) => format!("{n} graph breaks, {unique} of them unique"),
+        };
+        if let Some(top) = self.sorted().first().filter(|b| b.count > 1) {
+            let location = top
+                .location
+                .as_ref()
+                .map_or(String::new(), |l| format!(" ({l})"));
+            summary.push_str(&format!(
+                "; the most frequent, {}{location}, was hit {} times",
+                top.reason, top.count
+            ));
+        }
+        summary
+    }
+
+    pub fn context(&self) -> GraphBreaksContext {
+        let rows = self
+            .sorted()
+            .into_iter()
+            .map(|b| GraphBreaksRow {
+                count: b.count,
+                reason: b.reason.clone(),
+                location: b.location.clone().unwrap_or_default(),
+                traceback: b.traceback.clone(),
+                first_compile_id: b.compile_ids[0].clone(),
+                other_compile_ids: b.compile_ids[1..].to_vec(),
+            })
+            .collect();
+        GraphBreaksContext {
+            css: crate::templates::CSS,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            summary: self.summary(),
+            rows,
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! What Inductor's FX passes did to a compile's graph (`<compile>/post_grad_passes_diff_N.html`
+//! and friends).
+//!
+//! Inductor logs the graph before and after each group of passes (`before_post_grad_graph` /
+//! `after_post_grad_graph`, the pattern matcher's `before_recompile_pre_grad` /
+//! `after_recompile_pre_grad`, ...).  Passes keep the names of the nodes they don't touch, so
+//! nodes are matched by name: a node only in the graph before was removed, one only in the
+//! graph after was added, and removed and added nodes traced from the same user code line
+//! replaced each other (e.g. `addmm` decomposed into `mm` and `add`).  A node kept under its
+//! name can still have a new target or new inputs.
+
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{CompileId, FxIndexMap, GraphDiffContext, GraphDiffLine, GraphReplacement};
+
+/// Graphs logged before and after a group of passes, the page name and its title
+pub const PASS_GRAPHS: [(&str, &str, &str, &str); 5] = [
+    (
+        "before_pre_grad_graph",
+        "after_pre_grad_graph",
+        "pre_grad_passes",
+        "pre-grad passes",
+    ),
+    (
+        "before_recompile_pre_grad",
+        "after_recompile_pre_grad",
+        "pre_grad_pattern_matcher",
+        "pre-grad pattern matcher",
+    ),
+    (
+        "before_joint_graph",
+        "after_joint_graph",
+        "joint_graph_passes",
+        "joint graph passes",
+    ),
+    (
+        "before_post_grad_graph",
+        "after_post_grad_graph",
+        "post_grad_passes",
+        "post-grad passes",
+    ),
+    (
+        "before_recompile_post_grad",
+        "after_recompile_post_grad",
+        "post_grad_pattern_matcher",
+        "post-grad pattern matcher",
+    ),
+];
+
+// `    mm_default: "f32[8, 16][16, 1]cuda:0" = torch.ops.aten.mm.default(primals_3, permute);  permute = None`
+static NODE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)(?:: "[^"]*")? = (?P<rhs>.+)$"#).unwrap()
+});
+// `     # File: /path/to/model.py:79 in forward, code: x = self.fc1(x)`
+static LOCATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s+# File: (?P<file>.+?):(?P<line>\d+) in (?P<code>.+)$").unwrap());
+
+struct Node {
+    name: String,
+    /// `aten.mm.default`
+    target: String,
+    args: String,
+    /// The line without the `x = None` deallocations
+    text: String,
+    /// User code the node was traced from
+    location: Option<String>,
+}
+
+fn parse_graph(graph: &str) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut location = None;
+    for line in graph.lines() {
+        if line.trim().is_empty() {
+            location = None;
+        } else if let Some(caps) = LOCATION.captures(line) {
+            location = Some(format!(
+                "{}:{} in {}",
+                crate::explain::basename(&caps["file"]),
+                &caps["line"],
+                &caps["code"]
+            ));
+        } else if let Some(caps) = NODE.captures(line) {
+            let rhs = caps["rhs"].split(";  ").next().unwrap_or_default();
+            let (target, args) = match rhs.split_once('(') {
+                Some((target, args)) => (target, args.strip_suffix(')').unwrap_or(args)),
+                None => (rhs, ""),
+            };
+            let text = line.trim();
+            nodes.push(Node {
+                name: caps["name"].to_string(),
+                target: target
+                    .strip_prefix("torch.ops.")
+                    .unwrap_or(target)
+                    .to_string(),
+                args: args.to_string(),
+                text: text.split(";  ").next().unwrap_or(text).to_string(),
+                location: location.clone(),
+            });
+        }
+    }
+    nodes
+}
+
+fn label(node: &Node) -> String {
+    format!("{} ({})", node.name, node.target)
+}
+
+/// The structural diff of the graph before and after a pass group, for its page
+fn diff(before: &[Node], after: &[Node]) -> (Vec<GraphDiffLine>, Vec<GraphReplacement>) {
+    let before_names: FxHashMap<&str, usize> = before
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.name.as_str(), i))
+        .collect();
+    let after_names: FxHashMap<&str, usize> = after
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.name.as_str(), i))
+        .collect();
+    let is_removed = |n: &&Node| !after_names.contains_key(n.name.as_str());
+    let is_added = |n: &&Node| !before_names.contains_key(n.name.as_str());
+
+    // Removed and added nodes of the same user code line replaced each other
+    let mut groups: FxIndexMap<Option<&str>, (Vec<&Node>, Vec<&Node>)> = FxIndexMap::default();
+    for node in before.iter().filter(is_removed) {
+        groups
+            .entry(node.location.as_deref())
+            .or_default()
+            .0
+            .push(node);
+    }
+    for node in after.iter().filter(is_added) {
+        groups
+            .entry(node.location.as_deref())
+            .or_default()
+            .1
+            .push(node);
+    }
+    let mut replaced_by: FxHashMap<&str, String> = FxHashMap::default();
+    let mut replaces: FxHashMap<&str, String> = FxHashMap::default();
+    let mut replacements = Vec::new();
+    for (location, (removed, added)) in &groups {
+        let Some(location) = location.filter(|_| !removed.is_empty() && !added.is_empty()) else {
+            continue;
+        };
+        let names = |nodes: &[&Node]| -> String {
+            nodes
+                .iter()
+                .map(|n| n.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        for node in removed {
+            replaced_by.insert(&node.name, format!("replaced by {}", names(added)));
+        }
+        for node in added {
+            replaces.insert(&node.name, format!("replaces {}", names(removed)));
+        }
+        replacements.push(GraphReplacement {
+            location: location.to_string(),
+            removed: removed.iter().map(|n| label(n)).collect(),
+            added: added.iter().map(|n| label(n)).collect(),
+        });
+    }
+
+    // The graph after, with each removed node where it was relative to the kept ones
+    let mut entries: Vec<(&Node, &'static str, String)> = Vec::new();
+    let removed_note = |node: &Node| replaced_by.get(node.name.as_str()).cloned();
+    let mut next_before = 0;
+    for node in after {
+        let Some(&i) = before_names.get(node.name.as_str()) else {
+            let note = replaces
+                .get(node.name.as_str())
+                .cloned()
+                .unwrap_or_default();
+            entries.push((node, "added", note));
+            continue;
+        };
+        if i >= next_before {
+            for old in before[next_before..i].iter().filter(is_removed) {
+                entries.push((old, "removed", removed_note(old).unwrap_or_default()));
+            }
+            next_before = i + 1;
+        }
+        let old = &before[i];
+        entries.push(if old.target != node.target {
+            (node, "changed", format!("was {}", old.target))
+        } else if old.args != node.args {
+            (node, "rewired", format!("inputs were ({})", old.args))
+        } else {
+            (node, "kept", String::new())
+        });
+    }
+    for old in before[next_before..].iter().filter(is_removed) {
+        entries.push((old, "removed", removed_note(old).unwrap_or_default()));
+    }
+
+    let mut lines = Vec::new();
+    let mut last_location = None;
+    for (node, status, note) in entries {
+        if node.location.is_some() && node.location != last_location {
+            lines.push(GraphDiffLine {
+                status: "location",
+                marker: " ",
+                text: format!("# {}", node.location.as_deref().unwrap_or_default()),
+                note: String::new(),
+            });
+        }
+        last_location = node.location.clone();
+        lines.push(GraphDiffLine {
+            status,
+            marker: match status {
+                "added" => "+",
+                "removed" => "-",
+                "changed" | "rewired" => "~",
+                _ => " ",
+            },
+            text: node.text.clone(),
+            note,
+        });
+    }
+    (lines, replacements)
+}
+
+/// The graphs logged around each pass group, paired up per compile
+#[derive(Default)]
+pub struct GraphDiffIndex {
+    /// Graph logged before a pass group, waiting for the one after it
+    pending: FxHashMap<(Option<CompileId>, &'static str), String>,
+    /// Compile -> page name and context of each pass group run
+    diffs: FxIndexMap<Option<CompileId>, Vec<(&'static str, GraphDiffContext)>>,
+}
+
+impl GraphDiffIndex {
+    /// An artifact of `compile_id`; graphs logged around a pass group are kept until the
+    /// group's graph after arrives
+    pub fn add(&mut self, compile_id: &Option<CompileId>, artifact: &str, payload: &str) {
+        for (before, after, page, title) in PASS_GRAPHS {
+            if artifact == before {
+                self.pending
+                    .insert((compile_id.clone(), page), payload.to_string());
+            } else if artifact == after {
+                let Some(graph_before) = self.pending.remove(&(compile_id.clone(), page)) else {
+                    return;
+                };
+                let context = Self::context(compile_id, title, &graph_before, payload);
+                self.diffs
+                    .entry(compile_id.clone())
+                    .or_default()
+                    .push((page, context));
+            }
+        }
+    }
+
+    fn context(
+        compile_id: &Option<CompileId>,
+        title: &'static str,
+        graph_before: &str,
+        graph_after: &str,
+    ) -> GraphDiffContext {
+        let (before, after) = (parse_graph(graph_before), parse_graph(graph_after));
+        let (lines, replacements) = diff(&before, &after);
+        let count = |status| lines.iter().filter(|l| l.status == status).count();
+        GraphDiffContext {
+            css: crate::templates::CSS,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            compile_id: compile_id
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            title,
+            num_nodes_before: before.len(),
+            num_nodes_after: after.len(),
+            num_added: count("added"),
+            num_removed: count("removed"),
+            num_changed: count("changed"),
+            num_rewired: count("rewired"),
+            unchanged: lines
+                .iter()
+                .all(|l| matches!(l.status, "kept" | "location")),
+            replacements,
+            lines,
+        }
+    }
+
+    /// Compile -> its pass group diffs, in log order
+    pub fn diffs(self) -> FxIndexMap<Option<CompileId>, Vec<(&'static str, GraphDiffContext)>> {
+        self.diffs
+    }
+}
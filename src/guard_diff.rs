@@ -0,0 +1,106 @@
+//! Guards added and removed since the previous compile of the same frame, shown on each
+//! recompile's compilation metrics page.  Full guard dumps run to hundreds of entries, so
+//! comparing two of them side by side is impractical; the diff is what a recompile changed.
+//!
+//! Guards come from `dynamo_guards` (one `code` per guard) or `dynamo_cpp_guards_str` (one
+//! `+- KIND: expr` line per guard in the guard manager tree).  Each source is compared only
+//! against the same source of the previous compile.
+
+use fxhash::{FxHashMap, FxHashSet};
+use indexmap::IndexSet;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{CompileId, DynamoGuard, GuardDiff};
+
+// `| | +- TENSOR_MATCH: check_tensor(L['x'], ...)     # _dynamo/...py:484 in fn`
+static CPP_GUARD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[|\s]*\+- (?P<kind>[A-Z][A-Z0-9_]*): (?P<expr>.*)$").unwrap());
+static TRAILING_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s{2,}# .*$").unwrap());
+
+/// The guard sources a compile's guards can be logged with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GuardSource {
+    Json,
+    CppTree,
+}
+
+pub(crate) fn json_guards(payload: &str) -> Option<IndexSet<String>> {
+    let guards: Vec<DynamoGuard> = serde_json::from_str(payload).ok()?;
+    Some(guards.into_iter().map(|g| g.code).collect())
+}
+
+pub(crate) fn cpp_guards(payload: &str) -> IndexSet<String> {
+    payload
+        .lines()
+        .filter_map(|l| CPP_GUARD.captures(l))
+        .map(|caps| {
+            let expr = TRAILING_COMMENT.replace(&caps["expr"], "");
+            format!("{}: {}", &caps["kind"], expr.trim_end())
+        })
+        .collect()
+}
+
+/// (compiled autograd id, frame id): the compiles of one frame
+type FrameKey = (Option<u32>, Option<u32>);
+
+#[derive(Default)]
+pub struct GuardHistory {
+    /// Last guards seen per frame and source, with the compile they belong to
+    last: FxHashMap<(FrameKey, GuardSource), (CompileId, IndexSet<String>)>,
+    diffs: FxHashMap<Option<CompileId>, GuardDiff>,
+}
+
+impl GuardHistory {
+    /// Record a compile's guards; `compile_id` must already be normalized to an explicit attempt
+    pub fn add(&mut self, compile_id: &Option<CompileId>, source: GuardSource, payload: &str) {
+        let Some(cid) = compile_id else {
+            return;
+        };
+        if cid.frame_id.is_none() {
+            return;
+        }
+        let guards = match source {
+            GuardSource::Json => match json_guards(payload) {
+                Some(guards) => guards,
+                None => return,
+            },
+            GuardSource::CppTree => cpp_guards(payload),
+        };
+        let key = ((cid.compiled_autograd_id, cid.frame_id), source);
+        if let Some((previous, previous_guards)) = self.last.get(&key) {
+            if previous != cid && !self.diffs.contains_key(compile_id) {
+                self.diffs
+                    .insert(compile_id.clone(), diff(previous, previous_guards, &guards));
+            }
+        }
+        self.last.insert(key, (cid.clone(), guards));
+    }
+
+    pub fn get(&self, compile_id: &Option<CompileId>) -> Option<GuardDiff> {
+        self.diffs.get(compile_id).cloned()
+    }
+}
+
+fn diff(previous: &CompileId, before: &IndexSet<String>, after: &IndexSet<String>) -> GuardDiff {
+    let before_set: FxHashSet<&String> = before.iter().collect();
+    let after_set: FxHashSet<&String> = after.iter().collect();
+    let added: Vec<String> = after
+        .iter()
+        .filter(|g| !before_set.contains(g))
+        .cloned()
+        .collect();
+    let removed: Vec<String> = before
+        .iter()
+        .filter(|g| !after_set.contains(g))
+        .cloned()
+        .collect();
+    GuardDiff {
+        previous_compile_id: previous.to_string(),
+        num_added: added.len(),
+        num_removed: removed.len(),
+        num_unchanged: after.len() - added.len(),
+        added,
+        removed,
+    }
+}
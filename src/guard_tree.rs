@@ -0,0 +1,165 @@
+//! A collapsible view of `dynamo_cpp_guards_str` (the C++ guard manager tree), written next to
+//! the raw dump as its readable page.  The dump runs to thousands of lines for real models,
+//! which makes the one artifact most recompile debugging starts from hard to read as text.
+//!
+//! Each guard manager (`RootGuardManager`, `GuardManager: source=L['x'], ...`, ...) becomes a
+//! collapsible node showing how many guards are below it; guards (`+- TENSOR_MATCH: ...`) are its
+//! leaves.  The page has a search box that shows only the guards matching it.
+
+use html_escape::encode_text;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::CppGuardsContext;
+
+// `| | +- GuardManager: source=L['x'], ...` or `| +- TENSOR_MATCH: check_tensor(...)`
+static TREE_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<indent>[|\s]*)\+- (?P<kind>[A-Za-z][A-Za-z0-9_]*)(?:(?P<sep>:\s?|\s)(?P<rest>.*))?$",
+    )
+    .unwrap()
+});
+static TRAILING_COMMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\s{2,}(?P<comment># .*)$").unwrap());
+
+/// Levels of the tree open when the page loads
+const OPEN_DEPTH: usize = 2;
+
+#[derive(Debug, Default)]
+struct Node {
+    kind: String,
+    /// `: ` before the text, or a space (`KeyValueManager pair at index=0`)
+    sep: String,
+    text: String,
+    comment: Option<String>,
+    is_guard: bool,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn num_guards(&self) -> usize {
+        self.children
+            .iter()
+            .map(|c| c.num_guards() + c.is_guard as usize)
+            .sum()
+    }
+
+    fn num_managers(&self) -> usize {
+        self.children
+            .iter()
+            .filter(|c| !c.is_guard)
+            .map(|c| 1 + c.num_managers())
+            .sum()
+    }
+
+    fn last_at(&mut self, depth: usize) -> &mut Node {
+        let mut node = self;
+        for _ in 0..depth {
+            if node.children.is_empty() {
+                break;
+            }
+            node = node.children.last_mut().unwrap();
+        }
+        node
+    }
+}
+
+/// Root holding the top-level nodes, and the lines outside the tree
+fn parse_tree(payload: &str) -> (Node, Vec<String>) {
+    let mut root = Node::default();
+    let mut other = Vec::new();
+    let mut last_depth: Option<usize> = None;
+    for line in payload.lines() {
+        let Some(caps) = TREE_LINE.captures(line) else {
+            // A guard spanning several lines continues in the next ones
+            match last_depth {
+                Some(depth) if !line.trim().is_empty() => {
+                    let node = root.last_at(depth + 1);
+                    node.text.push('\n');
+                    node.text.push_str(line.trim_end());
+                }
+                _ if line.trim().is_empty() || line.trim_end().ends_with("GUARD_MANAGER:") => {}
+                _ => other.push(line.to_string()),
+            }
+            continue;
+        };
+        let depth = caps["indent"].len() / 2;
+        let kind = caps["kind"].to_string();
+        let sep = caps.name("sep").map_or("", |m| m.as_str()).trim_end();
+        let rest = caps.name("rest").map_or("", |m| m.as_str());
+        let (text, comment) = match TRAILING_COMMENT.captures(rest) {
+            Some(c) => (
+                rest[..c.get(0).unwrap().start()].to_string(),
+                Some(c["comment"].to_string()),
+            ),
+            None => (rest.trim_end().to_string(), None),
+        };
+        // Managers are CamelCase (`GuardManager`, `DictGuardManager`, `KeyManager`), guards
+        // UPPER_CASE
+        let is_guard = !kind.chars().any(|c| c.is_ascii_lowercase());
+        let parent = root.last_at(depth);
+        parent.children.push(Node {
+            kind,
+            sep: format!("{sep} "),
+            text,
+            comment,
+            is_guard,
+            children: Vec::new(),
+        });
+        last_depth = Some(depth);
+    }
+    (root, other)
+}
+
+fn render_node(node: &Node, depth: usize, out: &mut String) {
+    let label = if node.text.is_empty() {
+        format!(
+            "<span class=\"guard-kind\">{}</span>",
+            encode_text(&node.kind)
+        )
+    } else {
+        format!(
+            "<span class=\"guard-kind\">{}</span>{}<code>{}</code>",
+            encode_text(&node.kind),
+            node.sep,
+            encode_text(&node.text)
+        )
+    };
+    let comment = node.comment.as_ref().map_or(String::new(), |c| {
+        format!(" <span class=\"guard-comment\">{}</span>", encode_text(c))
+    });
+    if node.is_guard {
+        out.push_str(&format!("<li class=\"guard\">{label}{comment}</li>\n"));
+        return;
+    }
+    let num_guards = node.num_guards();
+    out.push_str(&format!(
+        "<li class=\"guard-manager\"><details{}><summary>{label}{comment} <span class=\"guard-count\">{num_guards} guard{}</span></summary>\n<ul>\n",
+        if depth < OPEN_DEPTH { " open" } else { "" },
+        if num_guards == 1 { "" } else { "s" },
+    ));
+    for child in &node.children {
+        render_node(child, depth + 1, out);
+    }
+    out.push_str("</ul>\n</details></li>\n");
+}
+
+/// Context of the page of one dump; `raw_url` is the raw dump relative to the page
+pub fn cpp_guards_context(payload: &str, compile_id: String, raw_url: String) -> CppGuardsContext {
+    let (root, other) = parse_tree(payload);
+    let mut tree_html = String::from("<ul class=\"guard-tree\">\n");
+    for node in &root.children {
+        render_node(node, 0, &mut tree_html);
+    }
+    tree_html.push_str("</ul>\n");
+    CppGuardsContext {
+        css: crate::templates::CSS,
+        qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+        compile_id,
+        raw_url,
+        num_guards: root.num_guards(),
+        num_managers: root.num_managers(),
+        tree_html,
+        other_lines: other.join("\n"),
+    }
+}
@@ -0,0 +1,200 @@
+//! Where Inductor spent a compile: its chromium spans grouped into passes (pre-grad, joint
+//! graph and post-grad FX passes, pattern matching, lowering, scheduling, codegen, kernel
+//! compilation and autotuning), shown on the compilation metrics page.
+//!
+//! Passes nest (`Scheduler.codegen` runs inside `GraphLowering.codegen`), so each pass is
+//! credited with its self time, i.e. without the passes it ran, and the slowest pass is the one
+//! with the most self time.  Logs without chromium events fall back to the `*_pass_time_us`
+//! fields of `compilation_metrics`, which only have total times.
+
+use fxhash::FxHashMap;
+use html_escape::encode_text;
+use serde_json::Value;
+use std::fmt::Write;
+
+use crate::types::CompilationMetricsMetadata;
+use crate::units;
+
+/// Spans covering the whole Inductor compile, the denominator of each pass' share
+const INDUCTOR_SPANS: [&str; 2] = ["inductor_compile", "compile_fx_inner"];
+
+/// Which pass a span belongs to, if it is one
+fn pass_kind(name: &str) -> Option<&'static str> {
+    let lower = name.to_lowercase();
+    Some(match name {
+        "_recursive_pre_grad_passes" | "pre_grad_passes" => "pre-grad passes",
+        "_recursive_joint_graph_passes" | "joint_graph_passes" => "joint graph passes",
+        "_recursive_post_grad_passes" | "post_grad_passes" => "post-grad passes",
+        _ if lower.contains("pattern") => "pattern matcher",
+        _ if lower.contains("autotune") || lower.contains("benchmark") => "autotuning",
+        _ if name.starts_with("Scheduler.") && !name.ends_with("codegen") => "scheduling",
+        "GraphLowering.run" => "lowering",
+        _ if lower.contains("lowering") && !name.starts_with("GraphLowering.") => "lowering",
+        "code_gen" | "GraphLowering.compile_to_fn" => "codegen",
+        _ if name.ends_with("codegen") || name.ends_with(".generate") => "codegen",
+        _ if name.starts_with("async_compile.") => "kernel compilation",
+        _ => return None,
+    })
+}
+
+struct PassRow {
+    name: String,
+    kind: &'static str,
+    calls: Option<usize>,
+    total_us: f64,
+    self_us: Option<f64>,
+}
+
+#[derive(Default)]
+struct SpanTotal {
+    calls: usize,
+    total_us: f64,
+    /// Excluding the passes nested in it
+    self_us: f64,
+}
+
+/// Count, total and self duration of each span name among `events`
+fn span_totals(events: &[Value]) -> FxHashMap<String, SpanTotal> {
+    let mut totals: FxHashMap<String, SpanTotal> = FxHashMap::default();
+    // Name, start and time spent in nested passes of each open span
+    let mut open: Vec<(&str, f64, f64)> = Vec::new();
+    for ev in events {
+        let (Some(name), Some(ph), Some(ts)) = (
+            ev.get("name").and_then(|v| v.as_str()),
+            ev.get("ph").and_then(|v| v.as_str()),
+            ev.get("ts").and_then(|v| v.as_f64()),
+        ) else {
+            continue;
+        };
+        let (duration, nested) = match ph {
+            "B" => {
+                open.push((name, ts, 0.0));
+                continue;
+            }
+            "E" => match open.iter().rposition(|(n, _, _)| *n == name) {
+                Some(pos) => {
+                    let (_, start, nested) = open.remove(pos);
+                    (ts - start, nested)
+                }
+                None => continue,
+            },
+            "X" => (ev.get("dur").and_then(|v| v.as_f64()).unwrap_or(0.0), 0.0),
+            _ => continue,
+        };
+        // Other spans are part of the pass they ran in
+        let is_pass = |name: &str| pass_kind(name).is_some() || INDUCTOR_SPANS.contains(&name);
+        if is_pass(name) {
+            if let Some((_, _, parent_nested)) = open.iter_mut().rev().find(|(n, _, _)| is_pass(n))
+            {
+                *parent_nested += duration;
+            }
+        }
+        let entry = totals.entry(name.to_string()).or_default();
+        entry.calls += 1;
+        entry.total_us += duration;
+        entry.self_us += (duration - nested).max(0.0);
+    }
+    totals
+}
+
+/// `pre_grad_pass_time_us`-style fields of the compilation metrics
+fn metric_rows(metrics: &CompilationMetricsMetadata) -> Vec<PassRow> {
+    metrics
+        .extra
+        .iter()
+        .filter_map(|(field, value)| {
+            let pass = field.strip_suffix("_pass_time_us")?;
+            Some(PassRow {
+                name: field.clone(),
+                kind: match pass {
+                    "pre_grad" => "pre-grad passes",
+                    "joint_graph" => "joint graph passes",
+                    "post_grad" => "post-grad passes",
+                    _ => "pass",
+                },
+                calls: None,
+                total_us: value.as_f64()?,
+                self_us: None,
+            })
+        })
+        .collect()
+}
+
+/// The "Inductor Passes" section of a compilation metrics page, if the compile logged any
+pub fn render_pass_breakdown(
+    events: &[Value],
+    metrics: Option<&CompilationMetricsMetadata>,
+) -> Option<String> {
+    let totals = span_totals(events);
+    let mut rows: Vec<PassRow> = totals
+        .iter()
+        .filter_map(|(name, span)| {
+            Some(PassRow {
+                name: name.clone(),
+                kind: pass_kind(name)?,
+                calls: Some(span.calls),
+                total_us: span.total_us,
+                self_us: Some(span.self_us),
+            })
+        })
+        .collect();
+    let from_metrics = rows.is_empty();
+    if from_metrics {
+        rows = metrics.map(metric_rows).unwrap_or_default();
+    }
+    if rows.is_empty() {
+        return None;
+    }
+    let ranked = |row: &PassRow| row.self_us.unwrap_or(row.total_us);
+    rows.sort_by(|a, b| ranked(b).total_cmp(&ranked(a)).then(a.name.cmp(&b.name)));
+
+    let inductor_us: f64 = INDUCTOR_SPANS
+        .iter()
+        .find_map(|name| totals.get(*name).map(|span| span.total_us))
+        .or_else(|| {
+            metrics
+                .and_then(|m| m.inductor_compile_time_s)
+                .map(|s| s * 1e6)
+        })
+        .unwrap_or_default()
+        .max(ranked(&rows[0]))
+        .max(1.0);
+    let slowest = &rows[0];
+
+    let mut html = String::from("<h2>Inductor Passes</h2>\n");
+    writeln!(
+        html,
+        "<p>The slowest pass, <code>{}</code> ({}), took {:.1}% of the {} Inductor compile.{}</p>",
+        encode_text(&slowest.name),
+        slowest.kind,
+        ranked(slowest) / inductor_us * 100.0,
+        units::duration(inductor_us / 1e6),
+        if from_metrics {
+            " Timings are from <code>compilation_metrics</code>; log chromium events for a \
+             finer breakdown."
+        } else {
+            " Self time excludes the passes nested in a pass."
+        }
+    )
+    .unwrap();
+    html.push_str(
+        "<table class='inductor-passes'>\n<tr><th>Pass</th><th>Kind</th><th>Calls</th><th>Total</th><th>Self</th><th>Share of Inductor compile</th></tr>\n",
+    );
+    for row in &rows {
+        let share = ranked(row) / inductor_us * 100.0;
+        writeln!(
+            html,
+            "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td><div style='display:inline-block;width:{:.0}px;height:10px;background:#9467bd'></div> {share:.1}%</td></tr>",
+            encode_text(&row.name),
+            row.kind,
+            row.calls.map_or(String::new(), |c| c.to_string()),
+            units::duration(row.total_us / 1e6),
+            row.self_us
+                .map_or(String::new(), |us| units::duration(us / 1e6)),
+            share * 2.0,
+        )
+        .unwrap();
+    }
+    html.push_str("</table>\n");
+    Some(html)
+}
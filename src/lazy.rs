@@ -0,0 +1,212 @@
+//! Lazy materialization of large artifacts (`--serve --lazy-artifacts`).
+//!
+//! Instead of writing every large payload to disk up front, the parse records where each
+//! payload lives in the original log (`artifact_index.json`).  The server extracts a file
+//! from the log the first time it is requested and caches it in the output directory.
+//!
+//! `--low-memory` uses the same index to keep large payloads out of memory during the parse:
+//! they are dropped as soon as they are read and copied out of the log one at a time once the
+//! rest of the report is written.
+
+use anyhow::{bail, Context};
+use fxhash::FxHashMap;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+use crate::types::ParseOutput;
+
+pub const ARTIFACT_INDEX_FILENAME: &str = "artifact_index.json";
+
+/// Location of one payload in the log: the byte offset of its first payload line and the
+/// number of (non-empty) payload lines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LazyArtifact {
+    pub offset: u64,
+    pub lines: usize,
+    pub md5: String,
+    /// Set for outputs spilled to disk with `--max-memory`: the contents are the `bytes` bytes
+    /// at `offset` of this file rather than payload lines of the log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spill_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LazyArtifactIndex {
+    /// Absolute path of the (uncompressed) log the offsets refer to
+    pub log: PathBuf,
+    /// By path, sorted so the index is the same on every run
+    pub artifacts: BTreeMap<String, LazyArtifact>,
+}
+
+/// Drop deferred artifacts from the output and record them in `artifact_index.json` instead
+pub(crate) fn defer_artifacts(
+    output: &mut ParseOutput,
+    log: &Path,
+    artifacts: FxHashMap<String, LazyArtifact>,
+) -> anyhow::Result<()> {
+    if artifacts.is_empty() {
+        return Ok(());
+    }
+    output.retain(|(path, _)| !artifacts.contains_key(&path.to_string_lossy().replace('\\', "/")));
+    let index = LazyArtifactIndex {
+        log: log.canonicalize()?,
+        artifacts: artifacts.into_iter().collect(),
+    };
+    output.push((
+        PathBuf::from(ARTIFACT_INDEX_FILENAME),
+        serde_json::to_string_pretty(&index)?,
+    ));
+    Ok(())
+}
+
+/// Contents of an output file: `contents` itself, or, when it was dropped from memory
+/// (`--low-memory`), its payload re-read from the log
+pub(crate) fn contents<'a>(
+    log: &Path,
+    artifacts: &FxHashMap<String, LazyArtifact>,
+    path: &Path,
+    contents: &'a str,
+) -> anyhow::Result<Cow<'a, str>> {
+    match artifacts.get(path.to_string_lossy().replace('\\', "/").as_str()) {
+        Some(artifact) if contents.is_empty() => Ok(Cow::Owned(read_lazy_artifact(log, artifact)?)),
+        _ => Ok(Cow::Borrowed(contents)),
+    }
+}
+
+fn check_md5(
+    payload: &str,
+    artifact: &LazyArtifact,
+    file: &Path,
+    what: &str,
+) -> anyhow::Result<()> {
+    let digest = Md5::digest(payload.as_bytes());
+    let mut buf = [0u8; 32];
+    let hash = base16ct::lower::encode_str(&digest, &mut buf)
+        .map_err(|e| anyhow::anyhow!("Encoding md5: {e}"))?;
+    if hash != artifact.md5 {
+        bail!(
+            "Payload at offset {} of {} does not match its md5; was the {what} modified?",
+            artifact.offset,
+            file.display()
+        );
+    }
+    Ok(())
+}
+
+/// Re-read a payload from the log, undoing the tab (and any rank) prefix, and check it against its md5
+pub fn read_lazy_artifact(log: &Path, artifact: &LazyArtifact) -> anyhow::Result<String> {
+    if let (Some(spill_file), Some(bytes)) = (&artifact.spill_file, artifact.bytes) {
+        let mut file = fs::File::open(spill_file)
+            .with_context(|| format!("Opening {}", spill_file.display()))?;
+        file.seek(SeekFrom::Start(artifact.offset))?;
+        let mut payload = String::new();
+        file.take(bytes).read_to_string(&mut payload)?;
+        check_md5(&payload, artifact, spill_file, "spill file")?;
+        return Ok(payload);
+    }
+    let mut file = fs::File::open(log).with_context(|| format!("Opening {}", log.display()))?;
+    file.seek(SeekFrom::Start(artifact.offset))?;
+    let mut payload = String::new();
+    let mut remaining = artifact.lines;
+    for line in BufReader::new(file).lines() {
+        if remaining == 0 {
+            break;
+        }
+        let line = line?;
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        let (_, line) = crate::rank_prefix::strip_rank_prefix(line);
+        // The parser skips blank lines, so they don't count towards the payload either
+        if line.is_empty() {
+            continue;
+        }
+        if remaining != artifact.lines {
+            payload.push('\n');
+        }
+        payload.push_str(line.strip_prefix('\t').unwrap_or(line));
+        remaining -= 1;
+    }
+    check_md5(&payload, artifact, log, "log")?;
+    Ok(payload)
+}
+
+/// Materialize `rel_path` (relative to `root`) if some report under `root` deferred it.
+/// Returns the path of the written file, or None if the file isn't a lazy artifact.
+pub fn materialize_lazy_artifact(root: &Path, rel_path: &Path) -> anyhow::Result<Option<PathBuf>> {
+    if rel_path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Ok(None);
+    }
+    // Multi-rank output keeps one index per rank directory, so check every ancestor
+    for report_dir in rel_path.ancestors().skip(1) {
+        let index_path = root.join(report_dir).join(ARTIFACT_INDEX_FILENAME);
+        if !index_path.is_file() {
+            continue;
+        }
+        let index: LazyArtifactIndex = serde_json::from_str(&fs::read_to_string(&index_path)?)
+            .with_context(|| format!("Parsing {}", index_path.display()))?;
+        let key = rel_path
+            .strip_prefix(report_dir)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        if let Some(artifact) = index.artifacts.get(&key) {
+            let payload = read_lazy_artifact(&index.log, artifact)?;
+            let out_path = root.join(rel_path);
+            if let Some(parent) = out_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&out_path, payload)?;
+            return Ok(Some(out_path));
+        }
+    }
+    Ok(None)
+}
+
+/// Extract every artifact deferred in `report_dir`'s `artifact_index.json` and remove the
+/// index, returning the (relative path, bytes) of each file written
+pub fn materialize_all(report_dir: &Path) -> anyhow::Result<Vec<(String, u64)>> {
+    let index_path = report_dir.join(ARTIFACT_INDEX_FILENAME);
+    if !index_path.is_file() {
+        return Ok(Vec::new());
+    }
+    let index: LazyArtifactIndex = serde_json::from_str(&fs::read_to_string(&index_path)?)
+        .with_context(|| format!("Parsing {}", index_path.display()))?;
+    let mut written = Vec::new();
+    for (key, artifact) in &index.artifacts {
+        if Path::new(key)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            bail!(
+                "Refusing to extract {key} outside of {}",
+                report_dir.display()
+            );
+        }
+        let payload = read_lazy_artifact(&index.log, artifact)?;
+        let out_path = report_dir.join(key);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&out_path, &payload)?;
+        written.push((key.clone(), payload.len() as u64));
+    }
+    fs::remove_file(&index_path)?;
+    let spill_files: BTreeSet<&PathBuf> = index
+        .artifacts
+        .values()
+        .filter_map(|a| a.spill_file.as_ref())
+        .collect();
+    for spill_file in spill_files {
+        fs::remove_file(spill_file)
+            .with_context(|| format!("Removing spill file {}", spill_file.display()))?;
+    }
+    Ok(written)
+}
@@ -1,35 +1,110 @@
-use anyhow::{anyhow, bail};
+use anyhow::bail;
 use chrono::Datelike;
 use fxhash::{FxHashMap, FxHashSet};
 use md5::{Digest, Md5};
 use std::ffi::{OsStr, OsString};
 
 use html_escape::encode_text;
+use indexmap::IndexMap;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use once_cell::sync::Lazy;
 use regex::Regex;
 use serde_json::Value;
 use std::cell::RefCell;
 use std::fmt::Write as FmtWrite;
 use std::fs::{self, File};
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 use tinytemplate::TinyTemplate;
 
+use crate::parser_state::ParserState;
 use crate::parsers::default_parsers;
 use crate::parsers::ParserOutput;
 use crate::parsers::StructuredLogParser;
+use crate::strict::{StrictError, StrictFailure, StrictFailures};
 use crate::templates::*;
 use crate::types::*;
+mod abnormal_end;
+mod activity;
+pub mod archive;
+mod attempts;
+pub mod browse;
+pub mod bundle;
+pub mod cache_keys;
+mod cancellation;
+pub mod cat;
+mod code_size;
+pub mod collectives;
+pub mod collisions;
+pub mod compile_labels;
+mod compile_times;
+pub mod compression;
+pub mod config;
+pub mod config_docs;
+mod data_dependent;
+pub mod derived_metrics;
+mod devices;
+pub mod diff;
+mod dir_index;
+mod duplicates;
+mod explain;
+mod fallbacks;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "forward")]
+pub mod forward;
+mod framework_compiles;
+mod graph_breaks;
+mod graph_diff;
+mod guard_diff;
+mod guard_tree;
+mod inductor_passes;
+pub mod lazy;
+pub mod links;
+pub mod lock;
+pub mod manifest;
+pub mod merge;
+mod metrics_catalog;
+pub mod op_coverage;
+pub mod parser_config;
+pub mod parser_state;
 pub mod parsers;
+pub mod paths;
+mod previews;
+mod rank_artifacts;
+mod rank_divergence;
+pub mod rank_prefix;
+mod recompiles;
+pub mod report_json;
+pub mod report_model;
+pub mod rpc;
+pub mod sampling;
+mod search;
+pub mod selftest;
+pub mod serve;
+mod sources;
+mod spool;
+pub mod strict;
+mod suggestions;
+mod synthetic_trace;
+pub mod tail;
+pub mod template_docs;
 mod templates;
+mod torch_logs;
 mod types;
+pub mod units;
 pub mod vllm;
+mod warnings;
+pub mod watch;
+pub mod workspace;
 
 pub use types::{
-    ArtifactFlags, CollectiveSchedule, CollectivesParityReport, Diagnostics, DivergenceFlags,
-    DivergenceGroup, ExecOrderSummary, GraphAnalysis, GraphCollectivesParity, GraphRuntime,
-    MultiRankContext, RankMetaData, RuntimeAnalysis, RuntimeRankDetail,
+    ArtifactFlags, CollectiveSchedule, CollectivesParityReport, CompileId, Diagnostics,
+    DivergenceFlags, DivergenceGroup, ExecOrderSummary, GraphAnalysis, GraphCollectivesParity,
+    GraphRuntime, MultiRankContext, RankLogIssue, RankMetaData, RuntimeAnalysis, RuntimeRankDetail,
+    StackRenderOptions,
 };
 
 pub use execution_order::{
@@ -43,17 +118,66 @@ enum ParserResult {
     PayloadFilename(String),
 }
 
+/// Cheap to clone and shareable between threads, so `--all-ranks-html` can parse ranks in
+/// parallel with a config per rank
+#[derive(Clone)]
 pub struct ParseConfig {
     pub strict: bool,
     pub strict_compile_id: bool,
-    pub custom_parsers: Vec<Box<dyn crate::parsers::StructuredLogParser>>,
+    pub custom_parsers: Vec<Arc<dyn crate::parsers::StructuredLogParser + Send + Sync>>,
     pub custom_header_html: String,
     pub verbose: bool,
     pub plain_text: bool,
     pub export: bool,
     pub inductor_provenance: bool,
+    pub derived_metrics: Vec<crate::derived_metrics::DerivedMetric>,
+    /// URL prefix the report will be hosted under; intra-report links are made absolute
+    pub base_url: Option<String>,
+    /// Leave raw payloads of at least this many bytes in the log, to be extracted on demand
+    pub lazy_artifact_min_bytes: Option<usize>,
+    pub stack_render: StackRenderOptions,
+    /// Eager op trace to compare the compiled graphs against (`op_coverage.html`)
+    pub eager_ops: Option<op_coverage::EagerOps>,
+    /// Called with (bytes read, log size) every percent of the log, and once when done
+    pub progress: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+    /// Parse only a subset of the compiles (`--sample-rate`, `--max-compiles`)
+    pub sampling: Option<sampling::SamplingConfig>,
+    /// Key-value metadata about the run (experiment id, git SHA, hyperparameters, ...), shown
+    /// on the index page and recorded in summary.json and manifest.json
+    pub metadata: IndexMap<String, String>,
+    /// Start the index with only the compiles rooted in user code shown
+    pub hide_framework_frames: bool,
+    /// Prior run to explain cache misses against (`cache_key_changes.html`)
+    pub baseline: Option<cache_keys::Baseline>,
+    /// Drop raw payloads of at least `LOW_MEMORY_PAYLOAD_BYTES` from memory as soon as they are
+    /// parsed and defer them to `artifact_index.json`, to be copied out of the log once the
+    /// report is written (`--low-memory`)
+    pub low_memory: bool,
+    /// Spill outputs to a file in the temp directory once they hold more than this many bytes
+    /// of memory, deferring them to `artifact_index.json` like `low_memory` (`--max-memory`)
+    pub max_memory: Option<u64>,
+    /// Explanations of config options, shown as tooltips (`--config-docs`)
+    pub config_docs: config_docs::ConfigDocs,
+    /// Write the HTML pages (`--format html`, the default)
+    pub html: bool,
+    /// Write `report.json` (`--format json`)
+    pub report_json: bool,
+    /// Write `report_model.json`, the context of every page, for `tlparse rerender`
+    /// (`--report-model`).  The contexts are held in memory until the end of the parse.
+    pub report_model: bool,
+    /// The log is a rank of `--all-ranks-html`, whose `collectives_parity.json` is written
+    /// next to its index once every rank is parsed
+    pub collectives_parity: bool,
+    /// Set the modification time of each written artifact to the log timestamp of the
+    /// envelope it came from (`--mtime-from-log`)
+    pub mtime_from_log: bool,
+    /// Labels shown next to compile ids (`compile_labels` in the config file, `--compile-label`)
+    pub compile_labels: compile_labels::CompileLabels,
 }
 
+/// Raw payloads at least this large are kept out of memory with `ParseConfig::low_memory`
+pub const LOW_MEMORY_PAYLOAD_BYTES: usize = 16 << 10;
+
 impl Default for ParseConfig {
     fn default() -> Self {
         Self {
@@ -65,6 +189,25 @@ impl Default for ParseConfig {
             plain_text: false,
             export: false,
             inductor_provenance: false,
+            derived_metrics: Vec::default(),
+            base_url: None,
+            lazy_artifact_min_bytes: None,
+            stack_render: StackRenderOptions::default(),
+            eager_ops: None,
+            progress: None,
+            sampling: None,
+            metadata: IndexMap::default(),
+            hide_framework_frames: false,
+            baseline: None,
+            low_memory: false,
+            max_memory: None,
+            config_docs: config_docs::ConfigDocs::bundled(),
+            html: true,
+            report_json: false,
+            report_model: false,
+            collectives_parity: false,
+            mtime_from_log: false,
+            compile_labels: compile_labels::CompileLabels::default(),
         }
     }
 }
@@ -102,6 +245,7 @@ fn maybe_remove_convert_frame_suffixes(frames: &mut Vec<FrameSummary>) {
 }
 
 fn add_unique_suffix(raw_filename: PathBuf, output_count: i32) -> PathBuf {
+    let raw_filename = paths::sanitize_path(&raw_filename);
     if let Some(stem) = raw_filename.file_stem() {
         let mut r = OsString::new();
         r.push(stem);
@@ -124,6 +268,7 @@ fn add_file_output(
     compile_directory: &mut Vec<OutputFile>,
     output_count: &mut i32,
     vllm_state: &vllm::VllmState,
+    payload: &str,
 ) {
     let is_stack_traces = is_stack_traces_file(&filename);
     let maybe_content = if is_stack_traces {
@@ -131,8 +276,7 @@ fn add_file_output(
     } else {
         None
     };
-    output.push((filename.clone(), content));
-    let filename_str = filename.to_string_lossy().to_string();
+    let filename_str = paths::url(&filename);
 
     let suffix = if filename_str.contains("cache_miss") {
         "❌".to_string()
@@ -144,20 +288,30 @@ fn add_file_output(
         "".to_string()
     };
 
+    let bytes = content.len() as u64;
     // Track artifact for vLLM summary
-    vllm_state.add_artifact(&filename, suffix.clone());
+    vllm_state.add_artifact(&filename, suffix.clone(), payload, bytes);
+    output.push((filename.clone(), content));
 
     let readable_url = if let Some(c) = maybe_content {
         Some(add_stack_traces_html(&filename, &c, output, output_count))
     } else {
         None
     };
+    let compare_url = if filename_str.ends_with(".html") {
+        None
+    } else {
+        Some(format!("compare.html?left={filename_str}"))
+    };
     compile_directory.push(OutputFile {
         url: filename_str.clone(),
         name: filename_str,
         number: *output_count,
         suffix: suffix,
         readable_url,
+        compare_url,
+        size: units::bytes(bytes),
+        size_heat: manifest::size_heat(bytes),
     });
     *output_count += 1;
 }
@@ -214,7 +368,7 @@ fn add_stack_traces_html(
 
 fn run_parser<'t>(
     lineno: usize,
-    parser: &Box<dyn StructuredLogParser + 't>,
+    parser: &(dyn StructuredLogParser + 't),
     e: &Envelope,
     payload: &str,
     output_count: &mut i32,
@@ -222,11 +376,12 @@ fn run_parser<'t>(
     compile_directory: &mut Vec<OutputFile>,
     multi: &MultiProgress,
     stats: &mut Stats,
-    vllm_state: &vllm::VllmState,
+    state: &ParserState,
 ) -> ParserResult {
     let mut payload_filename = ParserResult::NoPayload;
     if let Some(md) = parser.get_metadata(&e) {
-        let results = parser.parse(lineno, md, e.rank, &e.compile_id, &payload);
+        let vllm_state: &vllm::VllmState = &state.get::<vllm::VllmState>();
+        let results = parser.parse_with_state(state, lineno, md, e.rank, &e.compile_id, &payload);
         match results {
             Ok(results) => {
                 for parser_result in results {
@@ -240,9 +395,16 @@ fn run_parser<'t>(
                                 compile_directory,
                                 output_count,
                                 vllm_state,
+                                payload,
                             );
                         }
                         ParserOutput::GlobalFile(filename, out) => {
+                            let filename = state.get::<collisions::OutputPaths>().claim(
+                                paths::sanitize_path(&filename),
+                                &out,
+                                parser.name(),
+                                lineno,
+                            );
                             add_file_output(
                                 filename,
                                 out,
@@ -250,13 +412,12 @@ fn run_parser<'t>(
                                 compile_directory,
                                 output_count,
                                 vllm_state,
+                                payload,
                             );
                         }
                         ParserOutput::PayloadFile(raw_filename) => {
                             let filename = add_unique_suffix(raw_filename, *output_count);
-                            payload_filename = ParserResult::PayloadFilename(
-                                filename.to_string_lossy().to_string(),
-                            );
+                            payload_filename = ParserResult::PayloadFilename(paths::url(&filename));
                             add_file_output(
                                 filename,
                                 payload.to_string(),
@@ -264,15 +425,15 @@ fn run_parser<'t>(
                                 compile_directory,
                                 output_count,
                                 vllm_state,
+                                payload,
                             );
                         }
                         ParserOutput::PayloadReformatFile(raw_filename, formatter) => {
                             let filename = add_unique_suffix(raw_filename, *output_count);
                             match formatter(payload) {
                                 Ok(formatted_content) => {
-                                    payload_filename = ParserResult::PayloadFilename(
-                                        filename.to_string_lossy().to_string(),
-                                    );
+                                    payload_filename =
+                                        ParserResult::PayloadFilename(paths::url(&filename));
                                     add_file_output(
                                         filename,
                                         formatted_content,
@@ -280,6 +441,7 @@ fn run_parser<'t>(
                                         compile_directory,
                                         output_count,
                                         vllm_state,
+                                        payload,
                                     );
                                 }
                                 Err(err) => {
@@ -291,6 +453,13 @@ fn run_parser<'t>(
                                         )
                                     });
                                     stats.fail_parser += 1;
+                                    state.get::<StrictFailures>().record(StrictFailure {
+                                        kind: "fail_parser",
+                                        line: Some(lineno),
+                                        compile_id: strict::compile_id(&e.compile_id),
+                                        artifact: Some(filename.to_string_lossy().to_string()),
+                                        message: format!("Failed to format payload: {err}"),
+                                    });
                                 }
                             }
                         }
@@ -301,22 +470,36 @@ fn run_parser<'t>(
                                 number: *output_count,
                                 suffix: "".to_string(),
                                 readable_url: None,
+                                compare_url: None,
+                                size: String::new(),
+                                size_heat: "",
                             });
                             *output_count += 1;
                         }
                     }
                 }
             }
-            Err(err) => match parser.name() {
-                "dynamo_guards" => {
-                    multi.suspend(|| eprintln!("Failed to parse guards json: {}", err));
-                    stats.fail_dynamo_guards_json += 1;
-                }
-                name => {
-                    multi.suspend(|| eprintln!("Parser {name} failed: {err}"));
-                    stats.fail_parser += 1;
-                }
-            },
+            Err(err) => {
+                let kind = match parser.name() {
+                    "dynamo_guards" => {
+                        multi.suspend(|| eprintln!("Failed to parse guards json: {}", err));
+                        stats.fail_dynamo_guards_json += 1;
+                        "fail_dynamo_guards_json"
+                    }
+                    name => {
+                        multi.suspend(|| eprintln!("Parser {name} failed: {err}"));
+                        stats.fail_parser += 1;
+                        "fail_parser"
+                    }
+                };
+                state.get::<StrictFailures>().record(StrictFailure {
+                    kind,
+                    line: Some(lineno),
+                    compile_id: strict::compile_id(&e.compile_id),
+                    artifact: Some(parser.name().to_string()),
+                    message: err.to_string(),
+                });
+            }
         }
     }
     payload_filename
@@ -324,6 +507,7 @@ fn run_parser<'t>(
 
 fn directory_to_json(
     directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>,
+    log_times: &FxHashMap<String, String>,
 ) -> serde_json::Value {
     let mut json_map = serde_json::Map::new();
 
@@ -342,6 +526,7 @@ fn directory_to_json(
                     "number": file.number,
                     "suffix": file.suffix,
                     "readable_url": file.readable_url,
+                    "log_time": log_times.get(&file.url),
                 })
             })
             .collect();
@@ -351,6 +536,335 @@ fn directory_to_json(
     serde_json::Value::Object(json_map)
 }
 
+/// Build the memory row for a compile, diffing against the previous compile that reported memory.
+/// The first compile is diffed against zero so the table shows the baseline footprint.
+fn memory_usage_row(
+    compile_id: &Option<CompileId>,
+    rss_bytes: u64,
+    pss_bytes: Option<u64>,
+    prev: Option<&MemoryUsageRow>,
+) -> MemoryUsageRow {
+    let rss_delta_bytes = rss_bytes as i64 - prev.map_or(0, |p| p.rss_bytes as i64);
+    let pss_delta_bytes =
+        pss_bytes.map(|pss| pss as i64 - prev.and_then(|p| p.pss_bytes).unwrap_or(0) as i64);
+    MemoryUsageRow {
+        compile_id: compile_id
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string()),
+        rss_bytes,
+        pss_bytes,
+        rss_delta_bytes,
+        pss_delta_bytes,
+        rss: units::mib(rss_bytes as i64),
+        pss: pss_bytes.map_or("".to_string(), |b| units::mib(b as i64)),
+        rss_delta: units::mib_delta(rss_delta_bytes),
+        pss_delta: pss_delta_bytes.map_or("".to_string(), units::mib_delta),
+    }
+}
+
+/// Render RSS (and PSS, when present) across compiles as an inline SVG line chart.
+fn memory_usage_svg(rows: &[MemoryUsageRow]) -> String {
+    let (width, height, pad) = (800.0, 300.0, 40.0);
+    let max_bytes = rows
+        .iter()
+        .map(|r| r.rss_bytes.max(r.pss_bytes.unwrap_or(0)))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let step = if rows.len() > 1 {
+        (width - 2.0 * pad) / (rows.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let point = |i: usize, bytes: u64| -> (f64, f64) {
+        (
+            pad + step * i as f64,
+            height - pad - (bytes as f64 / max_bytes) * (height - 2.0 * pad),
+        )
+    };
+    let polyline = |values: Vec<(usize, u64)>, color: &str| -> String {
+        let points: Vec<String> = values
+            .iter()
+            .map(|(i, b)| {
+                let (x, y) = point(*i, *b);
+                format!("{x:.1},{y:.1}")
+            })
+            .collect();
+        format!(
+            "<polyline fill='none' stroke='{color}' stroke-width='2' points='{}'/>",
+            points.join(" ")
+        )
+    };
+
+    let mut svg =
+        format!("<svg width='{width}' height='{height}' xmlns='http://www.w3.org/2000/svg'>\n");
+    writeln!(
+        svg,
+        "<line x1='{pad}' y1='{y}' x2='{x}' y2='{y}' stroke='#888'/>\n<line x1='{pad}' y1='{pad}' x2='{pad}' y2='{y}' stroke='#888'/>",
+        x = width - pad,
+        y = height - pad,
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<text x='{pad}' y='{}' font-size='12'>{}</text>",
+        pad - 10.0,
+        units::mib(max_bytes as i64)
+    )
+    .unwrap();
+    svg.push_str(&polyline(
+        rows.iter()
+            .enumerate()
+            .map(|(i, r)| (i, r.rss_bytes))
+            .collect(),
+        "#1f77b4",
+    ));
+    let pss: Vec<(usize, u64)> = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(i, r)| r.pss_bytes.map(|b| (i, b)))
+        .collect();
+    if !pss.is_empty() {
+        svg.push_str(&polyline(pss, "#ff7f0e"));
+    }
+    for (i, r) in rows.iter().enumerate() {
+        let (x, y) = point(i, r.rss_bytes);
+        writeln!(
+            svg,
+            "<circle cx='{x:.1}' cy='{y:.1}' r='3' fill='#1f77b4'><title>{} RSS {} ({})</title></circle>",
+            encode_text(&r.compile_id),
+            r.rss,
+            r.rss_delta
+        )
+        .unwrap();
+    }
+    writeln!(
+        svg,
+        "<text x='{}' y='{pad}' font-size='12' fill='#1f77b4'>RSS</text>",
+        width - pad - 60.0
+    )
+    .unwrap();
+    if rows.iter().any(|r| r.pss_bytes.is_some()) {
+        writeln!(
+            svg,
+            "<text x='{}' y='{}' font-size='12' fill='#ff7f0e'>PSS</text>",
+            width - pad - 60.0,
+            pad + 15.0
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Bucket a chromium event name into the compiler phase used to color the Gantt chart
+fn compile_phase_of(name: &str) -> (&'static str, &'static str) {
+    let lower = name.to_lowercase();
+    if lower.contains("autotune") || lower.contains("benchmark") {
+        ("autotune", "#8c564b")
+    } else if lower.starts_with("scheduler") {
+        ("scheduling", "#9467bd")
+    } else if lower.contains("codegen")
+        || lower.contains("code_gen")
+        || lower.contains("generate")
+        || lower.contains("async_compile")
+        || lower.contains("codecache")
+    {
+        ("codegen", "#d62728")
+    } else if lower.contains("lowering") {
+        ("inductor lowering", "#ff7f0e")
+    } else if lower.contains("inductor") || lower.contains("post_grad") {
+        ("inductor", "#e6a23c")
+    } else if lower.contains("aot")
+        || lower.contains("joint_graph")
+        || lower.contains("fw_compiler")
+        || lower.contains("bw_compiler")
+    {
+        ("aot", "#2ca02c")
+    } else if lower.contains("dynamo")
+        || lower.contains("entire_frame")
+        || lower.contains("pre_grad")
+        || lower.contains("bytecode")
+    {
+        ("dynamo tracing", "#1f77b4")
+    } else if lower.contains("backend") {
+        ("backend", "#17becf")
+    } else {
+        ("other", "#7f7f7f")
+    }
+}
+
+/// Render the chromium events of a single compile as an inline SVG Gantt chart of its phases.
+/// Begin/end pairs and complete events become bars, instant events become markers.
+fn render_phase_gantt(events: &[serde_json::Value]) -> Option<String> {
+    struct Span {
+        name: String,
+        start: f64,
+        end: f64,
+        depth: usize,
+    }
+    let mut spans: Vec<Span> = Vec::new();
+    let mut instants: Vec<(String, f64)> = Vec::new();
+    let mut open: Vec<(String, f64)> = Vec::new();
+    for ev in events {
+        let (Some(name), Some(ph), Some(ts)) = (
+            ev.get("name").and_then(|v| v.as_str()),
+            ev.get("ph").and_then(|v| v.as_str()),
+            ev.get("ts").and_then(|v| v.as_f64()),
+        ) else {
+            continue;
+        };
+        match ph {
+            "B" => open.push((name.to_string(), ts)),
+            "E" => {
+                // Close the innermost matching begin; unmatched ends are dropped
+                if let Some(pos) = open.iter().rposition(|(n, _)| n == name) {
+                    let (name, start) = open.remove(pos);
+                    spans.push(Span {
+                        name,
+                        start,
+                        end: ts,
+                        depth: pos,
+                    });
+                }
+            }
+            "X" => {
+                let dur = ev.get("dur").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                spans.push(Span {
+                    name: name.to_string(),
+                    start: ts,
+                    end: ts + dur,
+                    depth: open.len(),
+                });
+            }
+            "i" | "I" => instants.push((name.to_string(), ts)),
+            _ => {}
+        }
+    }
+    if spans.is_empty() {
+        return None;
+    }
+    spans.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let origin = spans.iter().map(|s| s.start).fold(f64::INFINITY, f64::min);
+    let finish = spans
+        .iter()
+        .map(|s| s.end)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let total = (finish - origin).max(1.0);
+    let (label_width, chart_width, row_height) = (300.0, 600.0, 18.0);
+    let height = row_height * (spans.len() + 2) as f64;
+    let x_of = |ts: f64| label_width + (ts - origin) / total * chart_width;
+
+    let mut svg = format!(
+        "<svg width='{}' height='{height}' xmlns='http://www.w3.org/2000/svg' font-size='11' font-family='monospace'>\n",
+        label_width + chart_width + 80.0
+    );
+    for (row, span) in spans.iter().enumerate() {
+        let (phase, color) = compile_phase_of(&span.name);
+        let y = row_height * row as f64;
+        let x = x_of(span.start);
+        let width = (x_of(span.end) - x).max(1.0);
+        let duration = units::duration((span.end - span.start) / 1e6);
+        let name = encode_text(&span.name);
+        writeln!(
+            svg,
+            "<text x='{}' y='{:.1}'>{name}</text>",
+            4.0 + 8.0 * span.depth as f64,
+            y + 13.0
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<rect x='{x:.1}' y='{:.1}' width='{width:.1}' height='{:.1}' fill='{color}'><title>{name} ({phase}): {duration}</title></rect>",
+            y + 2.0,
+            row_height - 4.0
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<text x='{:.1}' y='{:.1}' fill='#555'>{duration}</text>",
+            x + width + 4.0,
+            y + 13.0
+        )
+        .unwrap();
+    }
+    let axis_y = row_height * spans.len() as f64 + 4.0;
+    for (name, ts) in &instants {
+        let x = x_of(*ts);
+        writeln!(
+            svg,
+            "<line x1='{x:.1}' y1='0' x2='{x:.1}' y2='{axis_y:.1}' stroke='#aaa' stroke-dasharray='2,2'><title>{}</title></line>",
+            encode_text(name)
+        )
+        .unwrap();
+    }
+    writeln!(
+        svg,
+        "<text x='{label_width}' y='{:.1}' fill='#555'>0</text><text x='{:.1}' y='{:.1}' fill='#555' text-anchor='end'>{}</text>",
+        axis_y + 12.0,
+        label_width + chart_width,
+        axis_y + 12.0,
+        units::duration(total / 1e6)
+    )
+    .unwrap();
+    svg.push_str("</svg>");
+
+    let mut legend: Vec<(&str, &str)> = spans.iter().map(|s| compile_phase_of(&s.name)).collect();
+    legend.dedup();
+    let mut seen = FxHashSet::default();
+    legend.retain(|(phase, _)| seen.insert(*phase));
+    let legend_html: Vec<String> = legend
+        .iter()
+        .map(|(phase, color)| {
+            format!("<span style='display:inline-block;width:10px;height:10px;background:{color}'></span> {phase}")
+        })
+        .collect();
+    Some(format!(
+        "<h2>Compiler Phases</h2>\n<p>{}</p>\n{svg}",
+        legend_html.join(" &nbsp; ")
+    ))
+}
+
+/// Links from a metrics page to the kernels of the output code pages of its compile, given the
+/// kernels of each output code payload of the compile
+fn render_output_code_kernels(files: &[OutputFile], kernels: &[Vec<String>]) -> String {
+    let pages = files.iter().filter_map(|file| {
+        let name = file.url.rsplit('/').next().unwrap_or_default();
+        (name.starts_with("inductor_output_code") && name.ends_with(".html"))
+            .then_some((file, name))
+    });
+    let mut html = String::new();
+    for ((file, name), kernels) in pages.zip(kernels) {
+        if kernels.is_empty() {
+            continue;
+        }
+        let links: Vec<String> = kernels
+            .iter()
+            .map(|kernel| format!("<a href='{}#kernel-{kernel}'>{kernel}</a>", file.url))
+            .collect();
+        html.push_str(&format!(
+            "<p><a href='{}'>{}</a>: {}</p>\n",
+            file.url,
+            encode_text(name),
+            links.join(", ")
+        ));
+    }
+    if html.is_empty() {
+        return html;
+    }
+    format!("<h2>Kernels</h2>\n{html}")
+}
+
+/// Placeholder in compilation metrics pages, replaced once all chromium events are read
+const PHASE_GANTT_MARKER: &str = "<!-- tlparse:phase_gantt -->";
+/// Likewise for the Inductor pass breakdown
+const INDUCTOR_PASSES_MARKER: &str = "<!-- tlparse:inductor_passes -->";
+/// Likewise for the link to the compile's own chromium events
+const COMPILE_CHROMIUM_EVENTS_MARKER: &str = "<!-- tlparse:compile_chromium_events -->";
+/// Likewise for the links to the kernels of the compile's output code
+const OUTPUT_CODE_KERNELS_MARKER: &str = "<!-- tlparse:output_code_kernels -->";
+
 fn handle_guard(
     failure_type: &str,
     reason: &str,
@@ -365,7 +879,7 @@ fn handle_guard(
     tt: &TinyTemplate,
     sym_expr_info_index: &RefCell<SymExprInfoIndex>,
     export_failures: &mut Vec<ExportFailure>,
-    vllm_state: &vllm::VllmState,
+    state: &ParserState,
 ) {
     let sym_expr_info_index_borrowed = sym_expr_info_index.borrow();
     let parser: Box<dyn StructuredLogParser> =
@@ -375,7 +889,7 @@ fn handle_guard(
         });
     let _ = run_parser(
         lineno,
-        &parser,
+        parser.as_ref(),
         e,
         payload,
         output_count,
@@ -383,7 +897,7 @@ fn handle_guard(
         compile_directory,
         multi,
         stats,
-        vllm_state,
+        state,
     );
 
     let filename = format!(
@@ -408,14 +922,23 @@ fn handle_guard(
     });
 }
 
+/// `ParseConfig::metadata` as rows of the metadata table on index pages
+fn metadata_rows(metadata: &IndexMap<String, String>) -> Vec<(String, String)> {
+    metadata
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
 pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseOutput> {
     let strict = config.strict;
+    STACK_RENDER_OPTIONS.with(|options| *options.borrow_mut() = config.stack_render.clone());
+    INTERN_TABLE.with(|intern_table| intern_table.borrow_mut().clear());
+    config.compile_labels.install();
     if !path.is_file() {
         bail!("{} is not a file", path.display())
     }
-    let file = File::open(path)?;
-    let metadata = file.metadata()?;
-    let file_size = metadata.len();
+    let file_size = fs::metadata(path)?.len();
 
     // TODO: abstract out this spinner to not be part of the library
     // Instead, add a callback trait for CLIs to implement
@@ -426,7 +949,24 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         .progress_chars("#>-"));
     let spinner = multi.add(ProgressBar::new_spinner());
 
-    let reader = io::BufReader::new(file);
+    let reader = compression::LogReader::open(path)?;
+    // Progress is in bytes of the file, which for compressed logs aren't the lines' bytes
+    let compressed = reader.compression().is_some();
+    if compressed && (config.lazy_artifact_min_bytes.is_some() || config.low_memory) {
+        eprintln!(
+            "{} is compressed, so its payloads can't be left in it for later: \
+             --lazy-artifacts and --low-memory keep them in memory; \
+             --max-memory bounds the memory a compressed log takes",
+            path.display()
+        );
+    }
+    let file_bytes_read = reader.file_bytes_read();
+    let sampling = config
+        .sampling
+        .as_ref()
+        .map(|s| sampling::select_compiles(path, s))
+        .transpose()?;
+    let sampling_summary = sampling.as_ref().map(|s| s.summary());
 
     let re_glog = Regex::new(concat!(
         r"(?<level>[VIWEC])(?<month>\d{2})(?<day>\d{2}) ",
@@ -493,6 +1033,32 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
     let sym_expr_info_index: RefCell<SymExprInfoIndex> = RefCell::new(FxHashMap::default());
     let create_symbol_index: RefCell<CreateSymbolIndex> = RefCell::new(FxHashMap::default());
     let unbacked_symbol_index: RefCell<UnbackedSymbolIndex> = RefCell::new(FxHashMap::default());
+    let compile_warning_index: RefCell<CompileWarningIndex> = RefCell::new(FxHashMap::default());
+    let mut graph_break_index: GraphBreakIndex = FxHashMap::default();
+    let mut guard_history = guard_diff::GuardHistory::default();
+    let mut cancellation_index: CompileCancellationIndex = FxIndexMap::default();
+    // TORCH_LOGS text lines per compile; the compile the trace was last in owns lines without
+    // a compile id in their prefix, and unprefixed lines continue the text line before them
+    let mut compiler_logs: FxIndexMap<Option<CompileId>, Vec<CompilerLogLine>> =
+        FxIndexMap::default();
+    let mut last_compile_id: Option<CompileId> = None;
+    // Compile the last line was a text line of, if it was one
+    let mut last_text_log: Option<Option<CompileId>> = None;
+    // Chromium events per compile id, split into one segment per compilation metrics page
+    // (a compile id can be compiled again, e.g. on a later run in the same process)
+    let mut chromium_event_index: ChromiumEventIndex = FxHashMap::default();
+    let mut chromium_event_depth: FxHashMap<Option<CompileId>, (usize, usize)> =
+        FxHashMap::default();
+    let mut metrics_pages: Vec<(PathBuf, Option<CompileId>)> = Vec::new();
+    let mut metrics_page_counts: FxHashMap<Option<CompileId>, usize> = FxHashMap::default();
+    let mut attempt_index = attempts::AttemptIndex::default();
+
+    // Process memory reported by compilation metrics, in log order
+    let mut memory_usage: Vec<MemoryUsageRow> = Vec::new();
+
+    let mut compile_summaries: Vec<CompileSummary> = Vec::new();
+    // Directory key of each compile summary
+    let mut summary_cids: Vec<Option<CompileId>> = Vec::new();
 
     // Store results in an output ParseOutput
     let mut output: ParseOutput = Vec::new();
@@ -500,42 +1066,42 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
     // Store raw.jsonl content (without payloads)
     let mut shortraw_content = String::new();
 
-    let mut tt: TinyTemplate = TinyTemplate::new();
-    tt.add_formatter("format_unescaped", tinytemplate::format_unescaped);
-    tt.add_formatter("format_float", |value, output| {
-        if let serde_json::Value::Number(n) = value {
-            if let Some(f) = n.as_f64() {
-                write!(output, "{:.3}", f)?;
-                return Ok(());
-            }
-        }
-        write!(output, "{}", value)?;
-        Ok(())
-    });
-    if config.export {
-        tt.add_template("index.html", TEMPLATE_EXPORT_INDEX)?;
-        tt.add_template(
-            "symbolic_guard_information.html",
-            TEMPLATE_SYMBOLIC_GUARD_INFO,
-        )?;
-    } else {
-        tt.add_template("index.html", TEMPLATE_INDEX)?;
-        tt.add_template("failures_and_restarts.html", TEMPLATE_FAILURES_AND_RESTARTS)?;
-        tt.add_template("dynamo_guards.html", TEMPLATE_DYNAMO_GUARDS)?;
-        tt.add_template("compilation_metrics.html", TEMPLATE_COMPILATION_METRICS)?;
-        tt.add_template(
-            "bwd_compilation_metrics.html",
-            TEMPLATE_BWD_COMPILATION_METRICS,
-        )?;
-        tt.add_template(
-            "aot_autograd_backward_compilation_metrics.html",
-            TEMPLATE_AOT_AUTOGRAD_BACKWARD_COMPILATION_METRICS,
-        )?;
+    let tt = report_model::templates(config.export)?;
+    report_model::start_recording(config.report_model);
+    if config.report_model && (config.low_memory || config.max_memory.is_some()) {
+        eprintln!(
+            "--report-model keeps the context of every page in memory until the end of the \
+             parse, on top of what --low-memory and --max-memory bound"
+        );
     }
-    tt.add_template("provenance_tracking.html", TEMPLATE_PROVENANCE_TRACKING)?;
-    tt.add_template("vllm_summary.html", vllm::templates::VLLM_SUMMARY_TEMPLATE)?;
 
     let mut unknown_fields: FxHashSet<String> = FxHashSet::default();
+    let mut device_detector = devices::DeviceDetector::default();
+    let mut source_index = sources::SourceIndex::default();
+    let mut run_end = abnormal_end::RunEndTracker::default();
+    // Compile id (directory key) -> (framework key, label) of framework-internal compiles
+    let mut framework_index: FxIndexMap<Option<CompileId>, (&'static str, &'static str)> =
+        FxIndexMap::default();
+    // Compile id (directory key) -> library its compiled frame lives in, None for user code
+    let mut frame_origin_index: FxIndexMap<Option<CompileId>, Option<&'static str>> =
+        FxIndexMap::default();
+    // Compile id (directory key) -> fingerprint of its Dynamo graph
+    let mut graph_fingerprints: FxIndexMap<Option<CompileId>, String> = FxIndexMap::default();
+    let mut fallback_index = fallbacks::FallbackIndex::default();
+    let mut data_dependent_index = data_dependent::DataDependentIndex::default();
+    let mut graph_breaks = graph_breaks::GraphBreaks::default();
+    let mut code_size_index = code_size::CodeSizeIndex::default();
+    // Kernels of each inductor_output_code payload, for the metrics pages to link to
+    let mut output_code_kernels: FxHashMap<Option<CompileId>, Vec<Vec<String>>> =
+        FxHashMap::default();
+    let mut graph_diff_index = graph_diff::GraphDiffIndex::default();
+    let mut collective_index = collectives::CollectiveIndex::default();
+    let mut cache_keys = cache_keys::CacheKeys::default();
+    let mut suggestion_index = suggestions::SuggestionIndex::default();
+    let mut report_index = report_json::ReportIndex::default();
+    let mut compile_times_index = compile_times::CompileTimesIndex::default();
+    let mut search_index = search::SearchIndex::default();
+    let mut preview_index = previews::PreviewIndex::default();
 
     let mut output_count = 0;
 
@@ -549,34 +1115,94 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
 
     // NB: Sometimes, the log output we get from Logarithm stutters with a blank line.
     // Filter them out, they're never valid (a blank line in payload will still be \t)
+    // Byte offsets are tracked so payloads can be located in the log again (--lazy-artifacts)
+    let mut line_offset: u64 = 0;
     let mut iter = reader
-        .lines()
+        .split(b'\n')
         .enumerate()
-        .filter_map(|(i, l)| match l {
+        .filter_map(|(i, l)| {
+            let l = l.ok()?;
+            let offset = line_offset;
+            line_offset += l.len() as u64 + 1;
+            let mut l = String::from_utf8(l).ok()?;
+            if l.ends_with('\r') {
+                l.pop();
+            }
+            // Launchers gathering the logs of all ranks prefix each line with its rank
+            let (prefix_rank, stripped) = rank_prefix::strip_rank_prefix(&l);
+            if prefix_rank.is_some() {
+                l = stripped.to_string();
+            }
             // 1-indexed line numbers please
-            Ok(l) if !l.is_empty() => Some((i + 1, l)),
-            _ => None,
+            (!l.is_empty()).then_some((i + 1, offset, prefix_rank, l))
         })
         .peekable();
+    let mut lazy_artifacts: FxHashMap<String, lazy::LazyArtifact> = FxHashMap::default();
+    // Log timestamp of the envelope each output file was generated from, by url
+    let mut log_times: FxHashMap<String, String> = FxHashMap::default();
+    // Sizes of the outputs dropped from memory with --low-memory
+    let mut dropped_bytes: FxHashMap<PathBuf, u64> = FxHashMap::default();
+    let mut spool = config
+        .max_memory
+        .map(|budget| spool::Spool::new(budget, config.base_url.clone()));
 
     let default_parsers = default_parsers(&tt, config);
-    let vllm_state = vllm::VllmState::new();
-    let vllm_parsers = vllm::vllm_parsers_with_state(vllm_state.clone());
-    let mut all_parsers: Vec<&Box<dyn StructuredLogParser>> = default_parsers.iter().collect();
-    all_parsers.extend(vllm_parsers.iter());
+    let parser_state = ParserState::default();
+    let vllm_state = parser_state.get::<vllm::VllmState>();
+    let recompiles = parser_state.get::<recompiles::Recompiles>();
+    let strict_failures = parser_state.get::<StrictFailures>();
+    let vllm_parsers = vllm::vllm_parsers();
+    let mut all_parsers: Vec<&dyn StructuredLogParser> =
+        default_parsers.iter().map(|p| p.as_ref()).collect();
+    all_parsers.extend(vllm_parsers.iter().map(|p| p.as_ref()));
     let mut chromium_events: Vec<serde_json::Value> = Vec::new();
-    all_parsers.extend(config.custom_parsers.iter());
+    all_parsers.extend(
+        config
+            .custom_parsers
+            .iter()
+            .map(|p| p.as_ref() as &dyn StructuredLogParser),
+    );
 
-    while let Some((lineno, line)) = iter.next() {
+    let mut next_progress: u64 = 0;
+    let mut saw_rank_prefix = false;
+    let mut compile_activity = activity::CompileActivity::default();
+    let mut synthetic_trace = synthetic_trace::SyntheticTrace::default();
+    while let Some((lineno, _, prefix_rank, line)) = iter.next() {
+        saw_rank_prefix |= prefix_rank.is_some();
         bytes_read += line.len() as u64;
+        if compressed {
+            bytes_read = file_bytes_read.load(std::sync::atomic::Ordering::Relaxed);
+        }
         pb.set_position(bytes_read);
+        if let Some(progress) = &config.progress {
+            if bytes_read >= next_progress {
+                progress(bytes_read, file_size);
+                next_progress = bytes_read + (file_size / 100).max(1);
+            }
+        }
         spinner.set_message(format!("{}", stats));
         //spinner.set_message(format!("{:?} {:?}", slowest_time, fastest_time));
         let start = Instant::now();
 
         let Some(caps) = re_glog.captures(&line) else {
+            if let Some(cid) = &last_text_log {
+                let last = compiler_logs
+                    .get_mut(cid)
+                    .and_then(|l| l.last_mut())
+                    .unwrap();
+                last.message.push('\n');
+                last.message.push_str(&line);
+                continue;
+            }
             multi.suspend(|| eprintln!("Failed to parse glog prefix on line {}", lineno));
             stats.fail_glog += 1;
+            strict_failures.record(StrictFailure {
+                kind: "fail_glog",
+                line: Some(lineno),
+                compile_id: None,
+                artifact: None,
+                message: "Failed to parse glog prefix".to_string(),
+            });
             continue;
         };
 
@@ -589,6 +1215,41 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         }
         let payload = &line[caps.name("payload").unwrap().start()..];
         let original_json_envelope = payload; // Store the original JSON envelope
+        let line_time = activity::glog_time(
+            caps["month"].parse().unwrap(),
+            caps["day"].parse().unwrap(),
+            caps["hour"].parse().unwrap(),
+            caps["minute"].parse().unwrap(),
+            caps["second"].parse().unwrap(),
+            caps["millisecond"].parse().unwrap(),
+        );
+        if let Some(time) = line_time {
+            compile_activity.add_line(time);
+        }
+        vllm_state.set_line_time(
+            ["hour", "minute", "second"].iter().fold(0.0, |acc, name| {
+                acc * 60.0 + caps[*name].parse::<f64>().unwrap()
+            }) + caps["millisecond"].parse::<f64>().unwrap() / 1e6,
+        );
+
+        last_text_log = None;
+        if !payload.starts_with('{') {
+            let (cid, log_line) = torch_logs::parse_line(
+                &caps["level"],
+                format!(
+                    "{}:{}:{}.{}",
+                    &caps["hour"], &caps["minute"], &caps["second"], &caps["millisecond"]
+                ),
+                caps["pathname"].trim(),
+                &caps["line"],
+                payload,
+            );
+            let cid = cid.or_else(|| last_compile_id.clone());
+            compiler_logs.entry(cid.clone()).or_default().push(log_line);
+            last_text_log = Some(cid);
+            stats.text_logs += 1;
+            continue;
+        }
 
         // Helper function to safely insert keys and detect conflicts
         let try_insert = |obj: &mut serde_json::Map<String, serde_json::Value>,
@@ -697,7 +1358,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
             }
         };
 
-        let e = match serde_json::from_str::<Envelope>(payload) {
+        let mut e = match serde_json::from_str::<Envelope>(payload) {
             Ok(r) => r,
             Err(err) => {
                 multi.suspend(|| {
@@ -707,31 +1368,48 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     );
                 });
                 stats.fail_json += 1;
+                strict_failures.record(StrictFailure {
+                    kind: "fail_json",
+                    line: Some(lineno),
+                    compile_id: None,
+                    artifact: None,
+                    message: format!("Failed to parse metadata JSON: {err}"),
+                });
                 write_to_shortraw(&mut shortraw_content, None, &multi, &mut stats);
                 continue;
             }
         };
 
+        if e.rank.is_none() {
+            e.rank = prefix_rank;
+        }
+
         stats.unknown += e._other.len() as u64;
 
         for k in e._other.keys() {
+            device_detector.unknown_entry(k);
             unknown_fields.insert(k.clone());
             if config.verbose {
                 multi.suspend(|| eprintln!("Unknown field {}", k))
             }
         }
 
+        vllm_state.observe(&e);
+
         if let Some((s, i)) = e.str {
-            let mut intern_table = INTERN_TABLE.lock().unwrap();
-            intern_table.insert(i, s);
+            INTERN_TABLE.with(|intern_table| intern_table.borrow_mut().insert(i, s));
             continue;
         };
 
         let mut payload = String::new();
+        // Where the payload starts in the log and how many lines it spans, if its md5 checks out
+        let mut payload_location: Option<(u64, usize)> = None;
         if let Some(ref expect) = e.has_payload {
             let mut first = true;
-            while let Some((_payload_lineno, payload_line)) =
-                iter.next_if(|(_, l)| l.starts_with('\t'))
+            let mut payload_offset = None;
+            let mut payload_lines = 0;
+            while let Some((_payload_lineno, offset, _, payload_line)) =
+                iter.next_if(|(_, _, _, l)| l.starts_with('\t'))
             {
                 // Careful! Distinguish between missing EOL and not
                 if !first {
@@ -739,18 +1417,46 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                 }
                 first = false;
                 payload.push_str(&payload_line[1..]);
+                payload_offset.get_or_insert(offset);
+                payload_lines += 1;
             }
             let mut hasher = Md5::new();
             hasher.update(&payload);
             let hash = hasher.finalize();
             let mut expect_buf = [0u8; 16];
-            if base16ct::lower::decode(expect, &mut expect_buf).is_ok() {
+            let md5_failure = if base16ct::lower::decode(expect, &mut expect_buf).is_ok() {
                 if expect_buf != hash[..] {
                     // TODO: error log
-                    stats.fail_payload_md5 += 1;
+                    Some(format!("Payload md5 doesn't match {expect}"))
+                } else {
+                    payload_location = payload_offset.map(|o| (o, payload_lines));
+                    None
                 }
             } else {
+                Some(format!("Payload md5 {expect} isn't hex"))
+            };
+            if let Some(message) = md5_failure {
                 stats.fail_payload_md5 += 1;
+                strict_failures.record(StrictFailure {
+                    kind: "fail_payload_md5",
+                    line: Some(lineno),
+                    compile_id: strict::compile_id(&e.compile_id),
+                    artifact: None,
+                    message,
+                });
+            }
+        }
+        // Source paths embedded in payloads are rewritten like those of stack frames; the
+        // rewritten payload can no longer be read back from the log lazily
+        if let Some(mapped) = map_paths_in_text(&payload) {
+            payload = mapped;
+            payload_location = None;
+        }
+        if let Some(m) = e.compilation_metrics.as_mut() {
+            for filename in [&mut m.co_filename, &mut m.fail_user_frame_filename] {
+                if let Some(mapped) = filename.as_deref().and_then(map_paths_in_text) {
+                    *filename = Some(mapped);
+                }
             }
         }
 
@@ -758,6 +1464,13 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
             Some(rank) => {
                 if rank != e.rank {
                     stats.other_rank += 1;
+                    strict_failures.record(StrictFailure {
+                        kind: "other_rank",
+                        line: Some(lineno),
+                        compile_id: strict::compile_id(&e.compile_id),
+                        artifact: None,
+                        message: format!("Line of rank {:?} in a log of rank {rank:?}", e.rank),
+                    });
                     write_to_shortraw(&mut shortraw_content, None, &multi, &mut stats);
                     continue;
                 }
@@ -774,6 +1487,11 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
             }
         };
 
+        if sampling.as_ref().is_some_and(|s| !s.keeps(&e.compile_id)) {
+            stats.sampled_out += 1;
+            continue;
+        }
+
         stats.ok += 1;
 
         // Some runtime compile ids don't have attempts. Collapse these entries into
@@ -785,14 +1503,60 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
             }
         }
 
+        if compile_id_entry.is_some() {
+            last_compile_id = compile_id_entry.clone();
+        }
+
+        run_end.envelope(
+            &e,
+            &compile_id_entry,
+            caps.name("level").unwrap().as_str(),
+            &payload,
+        );
+
+        if let Some(stack) = e.dynamo_start.as_ref().and_then(|m| m.stack.as_ref()) {
+            if let Some(framework) = framework_compiles::classify(stack) {
+                framework_index.insert(compile_id_entry.clone(), framework);
+            }
+            frame_origin_index.insert(
+                compile_id_entry.clone(),
+                framework_compiles::frame_origin(stack),
+            );
+        }
+
+        if e.dynamo_output_graph.is_some() {
+            graph_fingerprints
+                .entry(compile_id_entry.clone())
+                .or_insert_with(|| duplicates::graph_fingerprint(&payload));
+        }
+
+        if e.inductor_output_code.is_some() {
+            fallback_index.add(&compile_id_entry, &payload);
+            code_size_index.add_code(&compile_id_entry, &payload);
+            if !config.plain_text {
+                output_code_kernels
+                    .entry(compile_id_entry.clone())
+                    .or_default()
+                    .push(parsers::output_code_kernels(&payload));
+            }
+        }
+        if let Some(artifact) = &e.artifact {
+            graph_diff_index.add(&compile_id_entry, &artifact.name, &payload);
+            if artifact.name == collectives::SCHEDULE_ARTIFACT {
+                collective_index.add(&compile_id_entry, &payload);
+            }
+        }
+
         // TODO: output should be able to generate this without explicitly creating
-        let compile_directory = directory.entry(compile_id_entry).or_default();
+        let compile_directory = directory.entry(compile_id_entry.clone()).or_default();
 
+        let outputs_before = output.len();
+        let files_before = compile_directory.len();
         let mut parser_payload_filename = ParserResult::NoPayload;
         for parser in &all_parsers {
             let result = run_parser(
                 lineno,
-                parser,
+                *parser,
                 &e,
                 &payload,
                 &mut output_count,
@@ -800,21 +1564,147 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                 compile_directory,
                 &multi,
                 &mut stats,
-                &vllm_state,
+                &parser_state,
             );
             // Take the last PayloadFilename entry as per the requirement
             if matches!(result, ParserResult::PayloadFilename(_)) {
                 parser_payload_filename = result;
             }
         }
+        search_index.add(&e.compile_id, &compile_directory[files_before..], &payload);
+        preview_index.add(&compile_directory[files_before..], &payload);
+        if compile_directory.len() > files_before && !payload.is_empty() {
+            code_size_index.add_artifact(&compile_id_entry, &payload);
+        }
+
+        if let Some(stack) = e.dynamo_start.as_ref().and_then(|m| m.stack.as_ref()) {
+            source_index.add_stack(sources::KIND_COMPILE, &e.compile_id, stack);
+        }
+        if let Some(stack) = &e.stack {
+            source_index.add_stack(sources::KIND_OTHER, &e.compile_id, stack);
+        }
+        if let Some(stack) = e.guard_added.as_ref().and_then(|m| m.user_stack.as_ref()) {
+            source_index.add_stack(sources::KIND_GUARD, &e.compile_id, stack);
+        }
+        if let Some(symbol) = &e.create_unbacked_symbol {
+            data_dependent_index.add_unbacked_symbol(&e.compile_id, symbol);
+        }
+        if let Some(guard) = &e.guard_added {
+            data_dependent_index.add_guard(&e.compile_id, guard);
+        }
+        if let Some(guard) = &e.propagate_real_tensors_provenance {
+            data_dependent_index.add_specialization(&e.compile_id, guard);
+        }
+        if let Some(stack) = e
+            .symbolic_shape_specialization
+            .as_ref()
+            .and_then(|m| m.user_stack.as_ref())
+        {
+            source_index.add_stack(sources::KIND_SPECIALIZATION, &e.compile_id, stack);
+        }
+        if let Some(time) = line_time {
+            synthetic_trace.add(&e, time);
+        }
+        if let Some(m) = &e.compilation_metrics {
+            data_dependent_index.add_metrics(&e.compile_id, m);
+            recompiles.add_metrics(&e.compile_id, m);
+            if let (Some(filename), Some(lineno)) =
+                (&m.fail_user_frame_filename, m.fail_user_frame_lineno)
+            {
+                source_index.add_frame(
+                    sources::KIND_FAILURE,
+                    &e.compile_id,
+                    filename,
+                    lineno as i32,
+                    "",
+                );
+            }
+        }
+        if let Some(artifact) = &e.artifact {
+            cache_keys.add(&e.compile_id, &artifact.name, &payload);
+            if artifact.name == "dynamo_graph_break_reason" {
+                source_index.add_graph_break(&e.compile_id, &payload);
+                data_dependent_index.add_graph_break(&e.compile_id, &payload);
+                graph_breaks.add(&e.compile_id, &payload);
+                let mut cid = e.compile_id.clone();
+                if let Some(c) = cid.as_mut() {
+                    if c.frame_compile_id.is_some() {
+                        c.attempt = Some(c.attempt.unwrap_or(0));
+                    }
+                }
+                graph_break_index
+                    .entry(cid)
+                    .or_default()
+                    .push(explain::parse_graph_break(&payload));
+            }
+        }
+
+        let guard_source = if e.dynamo_guards.is_some() {
+            Some(guard_diff::GuardSource::Json)
+        } else if e.dynamo_cpp_guards_str.is_some() {
+            Some(guard_diff::GuardSource::CppTree)
+        } else {
+            None
+        };
+        if let Some(source) = guard_source {
+            let mut cid = e.compile_id.clone();
+            if let Some(c) = cid.as_mut() {
+                if c.frame_compile_id.is_some() {
+                    c.attempt = Some(c.attempt.unwrap_or(0));
+                }
+            }
+            guard_history.add(&cid, source, &payload);
+            if let Some(diff) = guard_history.get(&cid) {
+                recompiles.add_guard_diff(&cid, diff);
+            }
+            suggestion_index.add_guards(&cid, &payload);
+        }
+        if e.artifact
+            .as_ref()
+            .is_some_and(|a| a.name == "recompile_reasons")
+        {
+            suggestion_index.add_recompile_reasons(&e.compile_id, &payload);
+            recompiles.add_reasons(&e.compile_id, &payload);
+        }
+
+        if let Some(tensor) = &e.describe_tensor {
+            device_detector.tensor_device(&tensor.device);
+        }
 
         if let Some(ref m) = e.compilation_metrics {
+            device_detector.compilation_metrics(m);
+            suggestion_index.add_metrics(&e.compile_id, m);
             let copied_directory = compile_directory.clone();
             let compile_id_dir: PathBuf = e
                 .compile_id
                 .as_ref()
                 .map_or(format!("unknown_{lineno}"), |cid| cid.as_directory_name())
                 .into();
+            let memory = m.process_rss_bytes.map(|rss| {
+                memory_usage_row(&e.compile_id, rss, m.process_pss_bytes, memory_usage.last())
+            });
+            if let Some(row) = memory.as_ref() {
+                memory_usage.push(row.clone());
+            }
+            // The metrics parser takes this compile's warnings, keep a copy for summary.json
+            let mut warnings_cid = e.compile_id.clone();
+            if let Some(c) = warnings_cid.as_mut() {
+                if c.frame_compile_id.is_some() {
+                    c.attempt = Some(c.attempt.unwrap_or(0));
+                }
+            }
+            let compile_warnings = compile_warning_index
+                .borrow()
+                .get(&warnings_cid)
+                .cloned()
+                .unwrap_or_default();
+            let explanation = explain::explain(
+                m,
+                &copied_directory,
+                graph_break_index
+                    .get(&warnings_cid)
+                    .map_or(&[], |breaks| breaks.as_slice()),
+            );
             let parser: Box<dyn StructuredLogParser> =
                 Box::new(crate::parsers::CompilationMetricsParser {
                     tt: &tt,
@@ -823,12 +1713,17 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     guard_added_fast_index: &guard_added_fast_index,
                     create_symbol_index: &create_symbol_index,
                     unbacked_symbol_index: &unbacked_symbol_index,
+                    compile_warning_index: &compile_warning_index,
                     output_files: &copied_directory,
                     compile_id_dir: &compile_id_dir,
+                    memory,
+                    explanation: explain::to_html(&explanation),
+                    guard_diff: guard_history.get(&warnings_cid),
+                    config_docs: &config.config_docs,
                 });
             let result = run_parser(
                 lineno,
-                &parser,
+                parser.as_ref(),
                 &e,
                 &payload,
                 &mut output_count,
@@ -836,7 +1731,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                 compile_directory,
                 &multi,
                 &mut stats,
-                &vllm_state,
+                &parser_state,
             );
             // Take the last PayloadFilename entry as per the requirement
             if matches!(result, ParserResult::PayloadFilename(_)) {
@@ -851,12 +1746,41 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
 
             let id = e.compile_id.clone().map_or("(unknown) ".to_string(), |c| {
                 format!(
-                    "<a href='{}/{}'>{cid}</a> ",
+                    "<a href='{}/{}'>{cid}</a>{label} ",
                     compile_id_dir.display(),
                     metrics_filename,
                     cid = c,
+                    label = compile_labels::label_html(&e.compile_id),
                 )
             });
+            summary_cids.push(warnings_cid.clone());
+            compile_summaries.push(CompileSummary {
+                compile_id: e
+                    .compile_id
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                label: compile_labels::label(&e.compile_id),
+                co_name: m.co_name.clone(),
+                co_filename: m.co_filename.clone(),
+                co_firstlineno: m.co_firstlineno,
+                entire_frame_compile_time_s: m.entire_frame_compile_time_s,
+                backend_compile_time_s: m.backend_compile_time_s,
+                inductor_compile_time_s: m.inductor_compile_time_s,
+                fail_type: m.fail_type.clone(),
+                metrics_url: format!("{}/{}", compile_id_dir.display(), metrics_filename),
+                framework: framework_index.get(&warnings_cid).map(|(key, _)| *key),
+                frame_origin: frame_origin_index.get(&warnings_cid).copied().flatten(),
+                // Filled in once every artifact of the compile has been written
+                generated_code_bytes: 0,
+                artifact_bytes: 0,
+                code_size_outlier: false,
+                derived_metrics: derived_metrics::evaluate_all(&config.derived_metrics, m)
+                    .into_iter()
+                    .collect(),
+                warnings: compile_warnings,
+                explanation: explanation.join(" "),
+            });
+            report_index.add_metrics(&e.compile_id, m);
             if let Some(rr) = m.restart_reasons.as_ref() {
                 for restart in rr {
                     breaks.failures.push((
@@ -892,6 +1816,15 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     c.attempt = Some(0);
                 }
             }
+            if let Some(cancellation) = cancellation::from_metrics(m) {
+                cancellation_index
+                    .entry(cid.clone())
+                    .or_default()
+                    .push(cancellation);
+            }
+            attempt_index.add_page(&e.compile_id, compile_id_dir.join(&metrics_filename));
+            metrics_pages.push((compile_id_dir.join(&metrics_filename), cid.clone()));
+            *metrics_page_counts.entry(cid.clone()).or_default() += 1;
             metrics_index.entry(cid).or_default().push(m.clone());
         }
 
@@ -923,7 +1856,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     &tt,
                     &sym_expr_info_index,
                     &mut export_failures,
-                    &vllm_state,
+                    &parser_state,
                 );
             }
 
@@ -953,7 +1886,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                     &tt,
                     &sym_expr_info_index,
                     &mut export_failures,
-                    &vllm_state,
+                    &parser_state,
                 );
             }
 
@@ -1046,14 +1979,80 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                 .push(symbol);
         }
 
+        if let Some(ref artifact) = e.artifact {
+            device_detector.artifact(&artifact.name, &payload);
+            let found = warnings::extract_warnings(
+                &artifact.name,
+                &artifact.encoding,
+                caps.name("level").unwrap().as_str(),
+                &payload,
+            );
+            if !found.is_empty() {
+                // Apply same data migration as in CompilationMetricsParser for consistent HashMap keys
+                let mut cid = e.compile_id.clone();
+                if let Some(c) = cid.as_mut() {
+                    if c.frame_compile_id.is_some() {
+                        c.attempt = Some(c.attempt.unwrap_or(0));
+                    }
+                }
+                compile_warning_index
+                    .borrow_mut()
+                    .entry(cid)
+                    .or_default()
+                    .extend(found);
+            }
+            if let Some(cancellation) = cancellation::from_artifact(&artifact.name, &payload) {
+                // Apply same data migration as in CompilationMetricsParser for consistent HashMap keys
+                let mut cid = e.compile_id.clone();
+                if let Some(c) = cid.as_mut() {
+                    if c.frame_compile_id.is_some() {
+                        c.attempt = Some(c.attempt.unwrap_or(0));
+                    }
+                }
+                cancellation_index
+                    .entry(cid)
+                    .or_default()
+                    .push(cancellation);
+            }
+        }
+
         if let Some(stack) = e.stack {
             unknown_stack_trie.insert(stack.clone(), None);
         }
 
         if let Some(_) = e.chromium_event {
             // Skip bad json in chromium event. This can happen if log lines are dropped.
-            match serde_json::from_str(&payload) {
-                Ok(event) => chromium_events.push(event),
+            match serde_json::from_str::<serde_json::Value>(&payload) {
+                Ok(event) => {
+                    // Apply same data migration as in CompilationMetricsParser for consistent HashMap keys
+                    let mut cid = e.compile_id.clone();
+                    if let Some(c) = cid.as_mut() {
+                        if c.frame_compile_id.is_some() {
+                            c.attempt = Some(c.attempt.unwrap_or(0));
+                        }
+                    }
+                    if cid.is_some() {
+                        // A top level span belongs to the next metrics page for this compile id;
+                        // everything nested in it follows, even if it ends after that page
+                        let pages_so_far = metrics_page_counts.get(&cid).copied().unwrap_or(0);
+                        let (depth, segment) =
+                            chromium_event_depth.entry(cid.clone()).or_insert((0, 0));
+                        if *depth == 0 {
+                            *segment = pages_so_far;
+                        }
+                        match event.get("ph").and_then(|v| v.as_str()) {
+                            Some("B") => *depth += 1,
+                            Some("E") => *depth = depth.saturating_sub(1),
+                            _ => {}
+                        }
+                        let segments = chromium_event_index.entry(cid).or_default();
+                        if segments.len() <= *segment {
+                            segments.resize(*segment + 1, Vec::new());
+                        }
+                        segments[*segment].push(chromium_events.len());
+                    }
+                    chromium_events.push(event)
+                }
                 Err(_) => {
                     // Continue processing instead of crashing
                     // If json line is dropped, we should see fail_payload_md5 in result because the
@@ -1078,6 +2077,14 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         }
 
         if let Some(m) = e.dynamo_start {
+            if let Some(time) = line_time {
+                compile_activity.add_compile(
+                    e.compile_id
+                        .as_ref()
+                        .map_or("(unknown)".to_string(), |c| c.to_string()),
+                    time,
+                );
+            }
             if let Some(mut stack) = m.stack {
                 maybe_remove_convert_frame_suffixes(&mut stack);
                 stack_index
@@ -1107,6 +2114,49 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
             }
         };
 
+        // Outputs that are just the raw payload can be extracted from the log on demand instead
+        let min_bytes = config
+            .lazy_artifact_min_bytes
+            .or(config.low_memory.then_some(LOW_MEMORY_PAYLOAD_BYTES))
+            .filter(|_| !compressed);
+        if let (Some(min_bytes), Some((offset, lines)), Some(md5)) =
+            (min_bytes, payload_location, e.has_payload.as_ref())
+        {
+            if payload.len() >= min_bytes {
+                for (path, contents) in &mut output[outputs_before..] {
+                    if *contents == payload {
+                        lazy_artifacts.insert(
+                            path.to_string_lossy().replace('\\', "/"),
+                            lazy::LazyArtifact {
+                                offset,
+                                lines,
+                                md5: md5.clone(),
+                                spill_file: None,
+                                bytes: None,
+                            },
+                        );
+                        if config.low_memory {
+                            dropped_bytes.insert(path.clone(), contents.len() as u64);
+                            *contents = String::new();
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(spool) = &mut spool {
+            spool.track(&mut output, &mut lazy_artifacts, &mut dropped_bytes)?;
+        }
+
+        if output.len() > outputs_before {
+            let log_time = format_timestamp(&caps);
+            for (path, _) in &output[outputs_before..] {
+                log_times
+                    .entry(paths::url(path))
+                    .or_insert_with(|| log_time.clone());
+            }
+        }
+
         // Write to raw.jsonl with optional payload filename, but skip chromium events
         if e.chromium_event.is_none() {
             write_to_shortraw(
@@ -1144,26 +2194,567 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
 
         output.push((
             PathBuf::from("index.html"),
-            tt.render("index.html", &index_context)?,
+            report_model::render(&tt, "index.html", &index_context),
         ));
 
+        report_model::attach(&mut output, config.export, config.base_url.as_deref())?;
+        lazy::defer_artifacts(&mut output, path, lazy_artifacts)?;
+        if let Some(base_url) = &config.base_url {
+            links::rewrite_report_links(&mut output, base_url);
+        }
+        if let Some(spool) = spool {
+            spool.hand_off();
+        }
         return Ok(output);
     }
 
     output.push((
         PathBuf::from("failures_and_restarts.html"),
-        tt.render("failures_and_restarts.html", &breaks)?,
+        report_model::render(&tt, "failures_and_restarts.html", &breaks),
     ));
-    pb.finish_with_message("done");
-    spinner.finish();
 
-    output.push((
-        PathBuf::from("chromium_events.json"),
-        serde_json::to_string_pretty(&chromium_events).unwrap(),
-    ));
+    // Explain what timed out / cancelled compiles never got to produce
+    for (cid, cancellations) in &cancellation_index {
+        let compile_id_dir: PathBuf = cid
+            .as_ref()
+            .map_or("unknown".to_string(), |c| c.as_directory_name())
+            .into();
+        let compile_directory = directory.entry(cid.clone()).or_default();
+        let filename = compile_id_dir.join("compile_cancellation.html");
+        let context = CompileCancellationContext {
+            css: CSS,
+            compile_id: cid
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            kind: cancellations[0].kind,
+            cancellations: cancellations.clone(),
+            produced: compile_directory.clone(),
+            missing: cancellation::missing_phases(compile_directory),
+            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+        };
+        let html = report_model::render(&tt, "compile_cancellation.html", &context);
+        let bytes = html.len() as u64;
+        output.push((filename.clone(), html));
+        let filename_str = paths::url(&filename);
+        compile_directory.push(OutputFile {
+            url: filename_str.clone(),
+            name: filename_str,
+            number: output_count,
+            suffix: "⏱️".to_string(),
+            readable_url: None,
+            compare_url: None,
+            size: units::bytes(bytes),
+            size_heat: manifest::size_heat(bytes),
+        });
+        output_count += 1;
+    }
 
-    eprintln!("{}", stats);
+    for (cid, lines) in &compiler_logs {
+        let compile_id_dir: PathBuf = cid
+            .as_ref()
+            .map_or("unknown".to_string(), |c| c.as_directory_name())
+            .into();
+        let filename = compile_id_dir.join("compiler_logs.html");
+        let context = CompilerLogsContext {
+            css: CSS,
+            compile_id: cid
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            num_lines: lines.len(),
+            channels: torch_logs::group_by_channel(lines),
+            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+        };
+        let html = report_model::render(&tt, "compiler_logs.html", &context);
+        let bytes = html.len() as u64;
+        output.push((filename.clone(), html));
+        let filename_str = paths::url(&filename);
+        directory.entry(cid.clone()).or_default().push(OutputFile {
+            url: filename_str.clone(),
+            name: filename_str,
+            number: output_count,
+            suffix: "📝".to_string(),
+            readable_url: None,
+            compare_url: None,
+            size: units::bytes(bytes),
+            size_heat: manifest::size_heat(bytes),
+        });
+        output_count += 1;
+    }
+    // What each group of Inductor passes did to the graph
+    for (cid, diffs) in graph_diff_index.diffs() {
+        let compile_id_dir: PathBuf = cid
+            .as_ref()
+            .map_or("unknown".to_string(), |c| c.as_directory_name())
+            .into();
+        for (page, context) in diffs {
+            let filename = compile_id_dir.join(format!("{page}_diff_{output_count}.html"));
+            let html = report_model::render(&tt, "graph_diff.html", &context);
+            let bytes = html.len() as u64;
+            output.push((filename.clone(), html));
+            let filename_str = paths::url(&filename);
+            directory.entry(cid.clone()).or_default().push(OutputFile {
+                url: filename_str.clone(),
+                name: filename_str,
+                number: output_count,
+                suffix: String::new(),
+                readable_url: None,
+                compare_url: None,
+                size: units::bytes(bytes),
+                size_heat: manifest::size_heat(bytes),
+            });
+            output_count += 1;
+        }
+    }
+    pb.finish_with_message("done");
+    if let Some(progress) = &config.progress {
+        progress(file_size, file_size);
+    }
+    spinner.finish();
+    if saw_rank_prefix && stats.other_rank > 0 {
+        eprintln!(
+            "The log holds the lines of several ranks; only rank {} was parsed. \
+             Use --all-ranks-html to report on every rank",
+            expected_rank
+                .flatten()
+                .map_or("(none)".to_string(), |r| r.to_string())
+        );
+    }
+
+    // Fill in the per-compile phase charts now that every chromium event has been seen, and
+    // the attempt switchers now that every attempt has
+    attempt_index.add_compiles(directory.keys());
+    // Each compile's chromium events on their own, a small trace to open instead of the run's
+    let mut compile_traces: Vec<(PathBuf, String)> = Vec::new();
+    let mut compile_trace_paths: FxHashSet<PathBuf> = FxHashSet::default();
+    // Where each page is in the output, the first one if a path is output twice
+    let output_positions: FxHashMap<PathBuf, usize> = output
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, (path, _))| (path.clone(), i))
+        .collect();
+    let mut pages_seen: FxHashMap<&Option<CompileId>, usize> = FxHashMap::default();
+    for (page, cid) in &metrics_pages {
+        let seen = pages_seen.entry(cid).or_default();
+        let nth = *seen;
+        *seen += 1;
+        let is_last = *seen == metrics_page_counts[cid];
+        let events: Vec<serde_json::Value> = chromium_event_index
+            .get(cid)
+            .map(|segments| {
+                // The last page also picks up spans logged after it, e.g. a lazy backward compile
+                let end = if is_last { segments.len() } else { nth + 1 };
+                segments
+                    .iter()
+                    .skip(nth)
+                    .take(end.saturating_sub(nth))
+                    .flatten()
+                    .map(|&i| chromium_events[i].clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let gantt = render_phase_gantt(&events).unwrap_or_default();
+        let compile_trace = match (cid, chromium_event_index.get(cid)) {
+            (Some(c), Some(segments)) => {
+                let path =
+                    page.with_file_name(format!("chromium_events_{}.json", c.as_directory_name()));
+                let url = paths::url(&path);
+                let num_events: usize = segments.iter().map(Vec::len).sum();
+                if compile_trace_paths.insert(path.clone()) {
+                    let events: Vec<&serde_json::Value> = segments
+                        .iter()
+                        .flatten()
+                        .map(|&i| &chromium_events[i])
+                        .collect();
+                    compile_traces.push((path, serde_json::to_string_pretty(&events).unwrap()));
+                }
+                // Links of metrics pages resolve against the report root (their <base>)
+                format!(
+                    "<p>The {num_events} <a href='{url}'>chromium events</a> of this compile, \
+                     a trace of it alone to open in <a href='https://ui.perfetto.dev/'>Perfetto</a> \
+                     instead of the whole run's <a href='chromium_events.json'>chromium_events.json</a>.</p>"
+                )
+            }
+            _ => String::new(),
+        };
+        let metrics = metrics_index.get(cid).and_then(|m| m.get(nth));
+        let passes = inductor_passes::render_pass_breakdown(&events, metrics).unwrap_or_default();
+        compile_times_index.add(cid, paths::url(page), metrics, &events);
+        let kernels = match (directory.get(cid), output_code_kernels.get(cid)) {
+            (Some(files), Some(kernels)) => render_output_code_kernels(files, kernels),
+            _ => String::new(),
+        };
+        if let Some((_, html)) = output_positions.get(page).map(|&i| &mut output[i]) {
+            report_model::substitute(html, PHASE_GANTT_MARKER, &gantt);
+            report_model::substitute(html, COMPILE_CHROMIUM_EVENTS_MARKER, &compile_trace);
+            report_model::substitute(html, OUTPUT_CODE_KERNELS_MARKER, &kernels);
+            report_model::substitute(html, INDUCTOR_PASSES_MARKER, &passes);
+            report_model::substitute(
+                html,
+                attempts::ATTEMPTS_MARKER,
+                &attempt_index.switcher_html(page),
+            );
+        }
+    }
+
+    output.extend(compile_traces);
+    output.push((
+        PathBuf::from("chromium_events.json"),
+        serde_json::to_string_pretty(&chromium_events).unwrap(),
+    ));
+
+    let derived_metric_rows: Vec<DerivedMetricRow> = if config.derived_metrics.is_empty() {
+        Vec::new()
+    } else {
+        compile_summaries
+            .iter()
+            .map(|c| DerivedMetricRow {
+                compile_id: c.compile_id.clone(),
+                url: c.metrics_url.clone(),
+                values: c
+                    .derived_metrics
+                    .values()
+                    .map(|v| derived_metrics::format_value(*v))
+                    .collect(),
+            })
+            .collect()
+    };
+    let vllm_timing: Vec<vllm::VllmCompileTiming> = compile_summaries
+        .iter()
+        .map(|c| vllm::VllmCompileTiming {
+            compile_id: c.compile_id.clone(),
+            entire_frame_compile_time_s: c.entire_frame_compile_time_s,
+            backend_compile_time_s: c.backend_compile_time_s,
+            inductor_compile_time_s: c.inductor_compile_time_s,
+        })
+        .collect();
+    let compile_times: FxHashMap<String, f64> = compile_summaries
+        .iter()
+        .filter_map(|c| Some((c.compile_id.clone(), c.entire_frame_compile_time_s?)))
+        .collect();
+    let device_backends = device_detector.sections(directory.iter().map(|(cid, files)| {
+        (
+            cid.as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            files.as_slice(),
+        )
+    }));
+    // Readable pages of the C++ guard dumps, next to the raw ones
+    if !config.export {
+        for (cid, files) in directory.iter_mut() {
+            for file in files
+                .iter_mut()
+                .filter(|f| f.url.ends_with(".txt") && f.url.contains("dynamo_cpp_guards_str"))
+            {
+                let raw_path = PathBuf::from(&file.url);
+                let Some((_, raw)) = output.iter().find(|(path, _)| *path == raw_path) else {
+                    continue;
+                };
+                let raw = lazy::contents(path, &lazy_artifacts, &raw_path, raw)?;
+                let context = guard_tree::cpp_guards_context(
+                    &raw,
+                    cid.as_ref()
+                        .map_or("(unknown)".to_string(), |c| c.to_string()),
+                    raw_path
+                        .file_name()
+                        .map_or(String::new(), |n| n.to_string_lossy().into_owned()),
+                );
+                let page = raw_path.with_extension("html");
+                output.push((
+                    page.clone(),
+                    report_model::render(&tt, "dynamo_cpp_guards.html", &context),
+                ));
+                file.readable_url = Some(paths::url(&page));
+            }
+        }
+    }
+    code_size_index.finish();
+    for (cid, summary) in summary_cids.iter().zip(compile_summaries.iter_mut()) {
+        if let Some(size) = code_size_index.get(cid) {
+            summary.generated_code_bytes = size.generated_code_bytes;
+            summary.artifact_bytes = size.artifact_bytes;
+        }
+        summary.code_size_outlier = code_size_index.is_outlier(cid);
+    }
+    let mut compile_times_summary = None;
+    if !compile_times_index.is_empty() {
+        compile_times_summary = Some(compile_times_index.summary());
+        let mut context = compile_times_index.context();
+        compile_times::add_derived_metrics(
+            &mut context,
+            config
+                .derived_metrics
+                .iter()
+                .map(|d| d.name.clone())
+                .collect(),
+            &compile_summaries,
+        );
+        output.push((
+            PathBuf::from("compile_times.html"),
+            report_model::render(&tt, "compile_times.html", &context),
+        ));
+    }
+
+    output.push((
+        PathBuf::from("summary.json"),
+        serde_json::to_string_pretty(&RunSummary {
+            device_backends: device_backends.iter().map(|s| s.namespace).collect(),
+            metadata: config.metadata.clone(),
+            sampling,
+            compiles: compile_summaries,
+        })?,
+    ));
+
+    let has_memory_usage = !memory_usage.is_empty();
+    if has_memory_usage {
+        let first_rss = memory_usage.first().map_or(0, |r| r.rss_bytes as i64);
+        let last_rss = memory_usage.last().map_or(0, |r| r.rss_bytes as i64);
+        let memory_context = MemoryUsageContext {
+            css: CSS,
+            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+            chart_svg: memory_usage_svg(&memory_usage),
+            total_rss_growth: units::mib_delta(last_rss - first_rss),
+            rows: memory_usage,
+        };
+        output.push((
+            PathBuf::from("memory_usage.html"),
+            report_model::render(&tt, "memory_usage.html", &memory_context),
+        ));
+    }
+
+    let mut op_coverage = None;
+    if let Some(eager_ops) = &config.eager_ops {
+        let coverage_context = op_coverage::op_coverage(eager_ops, &output);
+        op_coverage = Some(coverage_context.coverage.clone());
+        output.push((
+            PathBuf::from("op_coverage.html"),
+            report_model::render(&tt, "op_coverage.html", &coverage_context),
+        ));
+    }
+
+    let compiled_graphs: Vec<duplicates::CompiledGraph> = graph_fingerprints
+        .iter()
+        .filter_map(|(cid, fingerprint)| {
+            let url = directory
+                .get(cid)?
+                .iter()
+                .find(|f| f.name.contains("dynamo_output_graph"))?
+                .url
+                .clone();
+            Some(duplicates::CompiledGraph {
+                compile_id: cid
+                    .as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                url,
+                fingerprint: fingerprint.clone(),
+            })
+        })
+        .collect();
+    let duplicate_clusters = duplicates::duplicate_clusters(&compiled_graphs, &compile_times);
+    let num_duplicate_compiles: usize = duplicate_clusters.iter().map(|c| c.num_compiles - 1).sum();
+    let mut duplicate_graphs_wasted = None;
+    if !duplicate_clusters.is_empty() {
+        let total_wasted = duplicates::wasted_time(&compiled_graphs, &compile_times);
+        let context = DuplicateGraphsContext {
+            css: CSS,
+            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+            num_duplicate_compiles,
+            total_wasted: units::seconds(total_wasted, 3),
+            clusters: duplicate_clusters,
+        };
+        duplicate_graphs_wasted = Some(context.total_wasted.clone());
+        output.push((
+            PathBuf::from("duplicate_graphs.html"),
+            report_model::render(&tt, "duplicate_graphs.html", &context),
+        ));
+    }
+
+    let mut fallback_kernels = None;
+    if !fallback_index.is_empty() {
+        fallback_kernels = Some(fallback_index.summary());
+        output.push((
+            PathBuf::from("fallback_kernels.html"),
+            report_model::render(
+                &tt,
+                "fallback_kernels.html",
+                &fallback_index.context(&directory),
+            ),
+        ));
+    }
+
+    let mut recompiles_summary = None;
+    if recompiles.any() {
+        recompiles_summary = Some(recompiles.summary());
+        output.push((
+            PathBuf::from("recompiles.html"),
+            report_model::render(&tt, "recompiles.html", &recompiles.context()),
+        ));
+    }
+
+    let mut compile_trace = None;
+    if !synthetic_trace.is_empty() {
+        compile_trace = Some(synthetic_trace.summary());
+        output.push((
+            PathBuf::from(synthetic_trace::TRACE_FILENAME),
+            serde_json::to_string_pretty(&synthetic_trace.to_json())?,
+        ));
+    }
+
+    let mut graph_breaks_summary = None;
+    if !graph_breaks.is_empty() {
+        graph_breaks_summary = Some(graph_breaks.summary());
+        output.push((
+            PathBuf::from("graph_breaks.html"),
+            report_model::render(&tt, "graph_breaks.html", &graph_breaks.context()),
+        ));
+    }
+
+    let mut data_dependent = None;
+    if !data_dependent_index.is_empty() {
+        data_dependent = Some(data_dependent_index.summary());
+        output.push((
+            PathBuf::from("data_dependent.html"),
+            report_model::render(&tt, "data_dependent.html", &data_dependent_index.context()),
+        ));
+    }
+
+    let mut search = None;
+    if !search_index.is_empty() {
+        search = Some(search_index.summary());
+        output.push((
+            PathBuf::from(search::SEARCH_INDEX_FILENAME),
+            search_index.json()?,
+        ));
+    }
+
+    let mut collectives = None;
+    if !collective_index.is_empty() {
+        collectives = Some(collective_index.summary());
+        output.push((
+            PathBuf::from("collectives.html"),
+            report_model::render(&tt, "collectives.html", &collective_index.context()),
+        ));
+        output.push((
+            PathBuf::from(collectives::COLLECTIVES_FILENAME),
+            collective_index.json()?,
+        ));
+    }
+
+    let mut cache_key_changes = None;
+    if !cache_keys.is_empty() {
+        if let Some(baseline) = &config.baseline {
+            let changes_context = cache_keys.explain(baseline);
+            if changes_context.num_misses > 0 {
+                cache_key_changes = Some(format!(
+                    "{} of {} cache misses changed key",
+                    changes_context.num_changed, changes_context.num_misses
+                ));
+                output.push((
+                    PathBuf::from("cache_key_changes.html"),
+                    report_model::render(&tt, "cache_key_changes.html", &changes_context),
+                ));
+            }
+        }
+        output.push((
+            PathBuf::from(cache_keys::CACHE_KEYS_FILENAME),
+            serde_json::to_string_pretty(&cache_keys)?,
+        ));
+    }
+
+    let suggested_config = suggestion_index.summary();
+    if let Some(contents) = suggestion_index.render() {
+        output.push((
+            PathBuf::from(suggestions::SUGGESTED_CONFIG_FILENAME),
+            contents,
+        ));
+    }
+
+    let mut framework_compiles: Vec<FrameworkCompileGroup> = Vec::new();
+    for (cid, (framework, label)) in &framework_index {
+        let compile_id = cid
+            .as_ref()
+            .map_or("(unknown)".to_string(), |c| c.to_string());
+        match framework_compiles
+            .iter_mut()
+            .find(|g| g.framework == *framework)
+        {
+            Some(group) => {
+                group.num_compiles += 1;
+                group.compile_ids.push(compile_id);
+            }
+            None => framework_compiles.push(FrameworkCompileGroup {
+                framework,
+                label,
+                num_compiles: 1,
+                compile_ids: vec![compile_id],
+            }),
+        }
+    }
+    let framework_compile_ids: Vec<&String> = framework_compiles
+        .iter()
+        .flat_map(|g| g.compile_ids.iter())
+        .collect();
+    let framework_compile_ids_json =
+        serde_json::to_string(&framework_compile_ids)?.replace("</", "<\\/");
+
+    let mut frame_presets = Vec::new();
+    let frame_origins: serde_json::Map<String, serde_json::Value> = frame_origin_index
+        .iter()
+        .filter_map(|(cid, origin)| {
+            Some((
+                cid.as_ref()
+                    .map_or("(unknown)".to_string(), |c| c.to_string()),
+                serde_json::Value::from((*origin)?),
+            ))
+        })
+        .collect();
+    if !frame_origins.is_empty() {
+        frame_presets.push(FramePreset {
+            key: "all",
+            label: "All compiles",
+            num_compiles: frame_origin_index.len(),
+            selectable: true,
+            checked: !config.hide_framework_frames,
+        });
+        frame_presets.push(FramePreset {
+            key: "user",
+            label: "User code only",
+            num_compiles: frame_origin_index.len() - frame_origins.len(),
+            selectable: true,
+            checked: config.hide_framework_frames,
+        });
+        for (key, label) in framework_compiles::FRAME_ORIGINS {
+            let num_compiles = frame_origin_index
+                .values()
+                .filter(|o| **o == Some(key))
+                .count();
+            if num_compiles > 0 {
+                frame_presets.push(FramePreset {
+                    key,
+                    label,
+                    num_compiles,
+                    selectable: false,
+                    checked: false,
+                });
+            }
+        }
+    }
+    let frame_origins_json = serde_json::to_string(&frame_origins)?.replace("</", "<\\/");
+
+    let abnormal_end = run_end.finish(&directory);
+
+    let num_source_files = source_index.len();
+    if !source_index.is_empty() {
+        output.push((
+            PathBuf::from("sources.html"),
+            report_model::render(&tt, "sources.html", &source_index.context()),
+        ));
+    }
+
+    eprintln!("{}", stats);
     if unknown_fields.len() > 0 {
+        let mut unknown_fields: Vec<_> = unknown_fields.iter().collect();
+        unknown_fields.sort();
         eprintln!(
             "Unknown fields: {:?} (consider updating tlparse to render these)",
             unknown_fields
@@ -1172,6 +2763,40 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
 
     let has_unknown_compile_id = directory.contains_key(&None);
 
+    // Every text artifact can be picked on either side of compare.html
+    let comparable_artifacts: Vec<serde_json::Value> = directory
+        .iter()
+        .flat_map(|(cid, files)| {
+            let cid = cid
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string());
+            files
+                .iter()
+                .filter(|f| f.compare_url.is_some())
+                .map(move |f| {
+                    serde_json::json!({
+                        "url": f.url,
+                        "label": format!("{} {}", cid, f.name.split('/').next_back().unwrap_or(&f.name)),
+                    })
+                })
+        })
+        .collect();
+    let compare_context = CompareContext {
+        css: CSS,
+        javascript: COMPARE_JAVASCRIPT,
+        base_url: config
+            .base_url
+            .as_deref()
+            .map_or(String::new(), |b| b.trim_end_matches('/').to_string()),
+        custom_header_html: config.custom_header_html.clone(),
+        artifacts_json: serde_json::to_string(&comparable_artifacts)?.replace("</", "<\\/"),
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+    };
+    output.push((
+        PathBuf::from("compare.html"),
+        report_model::render(&tt, "compare.html", &compare_context),
+    ));
+
     let directory_names: Vec<String> = directory
         .iter()
         .map(|(x, _)| {
@@ -1181,7 +2806,12 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         .collect();
     output.push((
         PathBuf::from("compile_directory.json"),
-        serde_json::to_string_pretty(&directory_to_json(&directory))?,
+        serde_json::to_string_pretty(&directory_to_json(&directory, &log_times))?,
+    ));
+    report_index.add_directory(&directory);
+    output.push((
+        PathBuf::from(activity::ACTIVITY_FILENAME),
+        serde_json::to_string_pretty(&compile_activity)?,
     ));
 
     // Generate traditional tlparse index
@@ -1209,19 +2839,63 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         })
         .collect();
 
+    let output_collisions = parser_state
+        .get::<collisions::OutputPaths>()
+        .finish(&output, |path| {
+            lazy_artifacts.contains_key(&paths::url(path))
+        });
+    for collision in &output_collisions {
+        match &collision.renamed_to {
+            Some(renamed) => eprintln!(
+                "Output {} of {} (line {}) collides with an earlier one, written to {renamed}",
+                collision.path,
+                collision.parser.unwrap_or("?"),
+                collision.lineno.unwrap_or_default(),
+            ),
+            None => eprintln!("Output {} was written more than once", collision.path),
+        }
+    }
+    if !output_collisions.is_empty() {
+        output.push((
+            PathBuf::from(collisions::COLLISIONS_FILENAME),
+            serde_json::to_string_pretty(&output_collisions)?,
+        ));
+    }
+
     let index_context = IndexContext {
         css: CSS,
         javascript: JAVASCRIPT,
         custom_header_html: config.custom_header_html.clone(),
         directory: directory
             .drain(..)
-            .map(|(x, y)| (x.map_or("(unknown)".to_string(), |e| e.to_string()), y))
+            .map(|(x, y)| {
+                let disk_usage = code_size_index.disk_usage(&x);
+                let attempts = attempt_index.rollup(&x, &metrics_index);
+                let label = compile_labels::label(&x);
+                (
+                    x.map_or("(unknown)".to_string(), |e| e.to_string()),
+                    y,
+                    disk_usage,
+                    attempts,
+                    label,
+                )
+            })
             .collect(),
         stack_trie_html: stack_trie
-            .fmt(Some(&metrics_index), "Stack", false)
+            .fmt(
+                Some(&metrics_index),
+                Some(&cancellation_index),
+                "Stack",
+                false,
+            )
             .unwrap(),
         unknown_stack_trie_html: unknown_stack_trie
-            .fmt(Some(&metrics_index), "Stack", false)
+            .fmt(
+                Some(&metrics_index),
+                Some(&cancellation_index),
+                "Stack",
+                false,
+            )
             .unwrap(),
         has_unknown_stack_trie: !unknown_stack_trie.is_empty(),
         num_breaks: breaks.failures.len(),
@@ -1230,33 +2904,89 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         has_inductor_provenance: config.inductor_provenance,
         directory_names: directory_names.clone(),
         milestones,
+        has_memory_usage,
+        op_coverage,
+        derived_metric_names: config
+            .derived_metrics
+            .iter()
+            .map(|d| d.name.clone())
+            .collect(),
+        derived_metric_rows,
+        device_backends,
+        abnormal_end,
+        sampling: sampling_summary,
+        num_source_files,
+        framework_compiles,
+        framework_compile_ids_json,
+        frame_presets,
+        frame_origins_json,
+        hide_framework_frames: config.hide_framework_frames,
+        duplicate_graphs_wasted,
+        num_duplicate_compiles,
+        fallback_kernels,
+        data_dependent,
+        recompiles: recompiles_summary,
+        graph_breaks: graph_breaks_summary,
+        compile_trace,
+        collectives,
+        collectives_parity: config.collectives_parity,
+        compile_times: compile_times_summary,
+        search,
+        output_collisions: (!output_collisions.is_empty())
+            .then(|| collisions::summary(&output_collisions)),
+        suggested_config,
+        cache_key_changes,
+        code_size_outliers: code_size_index.outliers(),
+        median_generated_code: code_size_index.median(),
+        metadata: metadata_rows(&config.metadata),
+        vllm_view: vllm_state.has_artifacts(),
+        compile_activity: compile_activity.summary(),
+        compile_activity_html: compile_activity
+            .start
+            .zip(compile_activity.end)
+            .and_then(|(start, end)| compile_activity.sparkline(start, end))
+            .unwrap_or_default(),
     };
-    let tlparse_index_html = tt.render("index.html", &index_context)?;
+    let tlparse_index_html = report_model::render(&tt, "index.html", &index_context);
 
     if vllm_state.has_artifacts() {
         // If vLLM artifacts are present, use vLLM summary as index.html and
         // save traditional tlparse index as tlparse_index.html for reference.
-        // `has_vllm_artifacts` gets set to true when the vLLM parsers are
-        // triggered. This happens when we see the following events:
-        // `vllm_subgraph_*`, `vllm_compilation_config`,
-        // `vllm_piecewise_split_graph`.
-        let vllm_html = vllm::generate_vllm_summary(&vllm_state, &tt, &config.custom_header_html)?;
+        // `has_vllm_artifacts` gets set by `VllmState::observe` as soon as any
+        // `vllm_*` artifact or graph dump is seen, so no caller has to know
+        // whether the log came from vLLM.
+        let vllm_html = vllm::generate_vllm_summary(
+            &vllm_state,
+            &tt,
+            &config.custom_header_html,
+            metadata_rows(&config.metadata),
+            &config.config_docs,
+        )?;
         output.push((PathBuf::from("index.html"), vllm_html));
+        output.push((
+            PathBuf::from("vllm_summary.json"),
+            vllm::generate_vllm_summary_json(&vllm_state, vllm_timing)?,
+        ));
         output.push((PathBuf::from("tlparse_index.html"), tlparse_index_html));
     } else {
         output.push((PathBuf::from("index.html"), tlparse_index_html));
     }
 
-    output.push((PathBuf::from("raw.log"), fs::read_to_string(path)?));
+    match &mut spool {
+        Some(spool) => spool.spill_log(path, &mut output, &mut lazy_artifacts)?,
+        None => output.push((PathBuf::from("raw.log"), compression::read_to_string(path)?)),
+    }
 
     // Create string table from INTERN_TABLE as an array with nulls for missing indices
-    let intern_table = INTERN_TABLE.lock().unwrap();
-    let max_index = intern_table.keys().max().copied().unwrap_or(0) as usize;
-    let mut string_table: Vec<Option<String>> = vec![None; max_index + 1];
-    for (&index, value) in intern_table.iter() {
-        string_table[index as usize] = Some(value.clone());
-    }
-    drop(intern_table); // Release the lock early
+    let string_table = INTERN_TABLE.with(|intern_table| {
+        let intern_table = intern_table.borrow();
+        let max_index = intern_table.keys().max().copied().unwrap_or(0) as usize;
+        let mut string_table: Vec<Option<String>> = vec![None; max_index + 1];
+        for (&index, value) in intern_table.iter() {
+            string_table[index as usize] = Some(value.clone());
+        }
+        string_table
+    });
 
     // Serialize string table as JSON object
     let string_table_json = serde_json::json!({
@@ -1275,6 +3005,7 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
 
     // other_rank is included here because you should only have logs from one rank when
     // configured properly
+    let mut strict_reasons = Vec::new();
     if strict
         && (stats.fail_glog
             + stats.fail_json
@@ -1285,27 +3016,39 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
             > 0)
     {
         // Report something went wrong
-        return Err(anyhow!("Something went wrong"));
+        strict_reasons.push("Something went wrong".to_string());
     }
-
     if config.strict_compile_id && has_unknown_compile_id {
-        return Err(anyhow!("Some log entries did not have compile id"));
+        strict_reasons.push("Some log entries did not have compile id".to_string());
+    }
+    if !strict_reasons.is_empty() {
+        let without_compile_id = match directory.get(&None) {
+            Some(files) if config.strict_compile_id => &files[..],
+            _ => &[],
+        };
+        output.push((
+            PathBuf::from(strict::STRICT_FAILURES_FILENAME),
+            strict_failures.report(&strict_reasons, &stats, without_compile_id)?,
+        ));
     }
 
     if config.inductor_provenance {
         // Helper function to get file content for a specific directory name
         fn get_file_content(
             output: &[(PathBuf, String)],
+            (log, lazy_artifacts): (&Path, &FxHashMap<String, lazy::LazyArtifact>),
             filename_patterns: &[&str],
             directory_name: &str,
         ) -> String {
             // Try each pattern in order and return the first match found
             for pattern in filename_patterns {
-                if let Some((_, content)) = output.iter().rev().find(|(path, _)| {
+                if let Some((path, content)) = output.iter().rev().find(|(path, _)| {
                     path.to_string_lossy()
                         .contains(&format!("{}/{}", directory_name, pattern))
                 }) {
-                    return content.clone();
+                    return lazy::contents(log, lazy_artifacts, path, content)
+                        .map(|c| c.into_owned())
+                        .unwrap_or_default();
                 }
             }
             String::default()
@@ -1315,20 +3058,31 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
         for directory_name in &directory_names {
             let pre_grad_graph_content = get_file_content(
                 &output,
+                (path, &lazy_artifacts),
                 &["before_pre_grad_graph", "inductor_pre_grad_graph"],
                 directory_name,
             );
             let post_grad_graph_content = get_file_content(
                 &output,
+                (path, &lazy_artifacts),
                 &["after_post_grad_graph", "inductor_post_grad_graph"],
                 directory_name,
             );
-            let output_code_content =
-                get_file_content(&output, &["inductor_output_code"], directory_name);
-            let aot_code_content =
-                get_file_content(&output, &["inductor_aot_wrapper_code"], directory_name);
+            let output_code_content = get_file_content(
+                &output,
+                (path, &lazy_artifacts),
+                &["inductor_output_code"],
+                directory_name,
+            );
+            let aot_code_content = get_file_content(
+                &output,
+                (path, &lazy_artifacts),
+                &["inductor_aot_wrapper_code"],
+                directory_name,
+            );
             let node_mappings_content = get_file_content(
                 &output,
+                (path, &lazy_artifacts),
                 &["inductor_provenance_tracking_node_mappings"],
                 directory_name,
             );
@@ -1346,7 +3100,8 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
 
             output.push((
                 PathBuf::from(format!("provenance_tracking_{}.html", directory_name)),
-                tt.render(
+                report_model::render(
+                    &tt,
                     "provenance_tracking.html",
                     &ProvenanceContext {
                         css: PROVENANCE_CSS,
@@ -1357,14 +3112,149 @@ pub fn parse_path(path: &PathBuf, config: &ParseConfig) -> anyhow::Result<ParseO
                         aot_code_content,
                         line_mappings_content: line_mappings_content_str,
                     },
-                )?,
+                ),
             ));
         }
     }
 
+    dir_index::add_directory_indexes(&tt, &mut output, &dropped_bytes);
+    previews::add_preview_scripts(&mut output, &preview_index);
+    if !preview_index.is_empty() {
+        output.push((
+            PathBuf::from(previews::PREVIEWS_FILENAME),
+            preview_index.js()?,
+        ));
+    }
+
+    report_model::attach(&mut output, config.export, config.base_url.as_deref())?;
+    // With --report-model the pages stay in report_model.json, for `tlparse rerender`
+    if !config.html {
+        output.retain(|(path, _)| path.extension().is_none_or(|e| e != "html"));
+    }
+    if config.report_json {
+        let report = report_index.report(path, &output)?;
+        output.push((PathBuf::from(report_json::REPORT_JSON_FILENAME), report));
+    }
+    lazy::defer_artifacts(&mut output, path, lazy_artifacts)?;
+    if let Some(base_url) = &config.base_url {
+        links::rewrite_report_links(&mut output, base_url);
+    }
+    if let Some(spool) = spool {
+        spool.hand_off();
+    }
+    if !strict_reasons.is_empty() {
+        return Err(StrictError {
+            message: strict_reasons.join("; "),
+            output,
+        }
+        .into());
+    }
     Ok(output)
 }
 
+/// How often [`parse_stream`] checks whether the log grew
+const STREAM_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The report of a growing log after it grew, see [`parse_stream`]
+#[derive(Debug)]
+pub struct StreamUpdate {
+    /// Bytes of the log the report covers
+    pub log_bytes: u64,
+    pub parse_duration: std::time::Duration,
+    /// The whole report
+    pub output: ParseOutput,
+    /// Files of `output` that are new or whose contents changed since the previous update;
+    /// every file on the first one
+    pub changed: Vec<PathBuf>,
+    /// Files of the previous update that aren't in the report anymore
+    pub removed: Vec<PathBuf>,
+}
+
+impl StreamUpdate {
+    /// The files to (re)write
+    pub fn changed_files(&self) -> impl Iterator<Item = &(PathBuf, String)> {
+        let changed: FxHashSet<&PathBuf> = self.changed.iter().collect();
+        self.output
+            .iter()
+            .filter(move |(path, _)| changed.contains(path))
+    }
+}
+
+/// Parse the still-growing log at `path` (`--follow`): call `on_update` with the report of the
+/// log as it is now, then again each time complete lines are appended to it, until `on_update`
+/// returns false.  A log that shrinks (rotated or truncated) is parsed again from the start.
+///
+/// Compiles refer to each other (the string table, recompile reasons, the index), so the log is
+/// parsed again as a whole each time, which gets slower as it grows; each update lists the files
+/// that actually changed: new envelopes usually affect the directory of their compile and the
+/// index pages, and a caller writing only `changed` leaves the rest of the report untouched.
+/// So that a large log doesn't keep a core busy reparsing, the next parse waits at least as
+/// long as the last one took: at most half of the time goes to parsing, and on a log appended
+/// to faster than that, an update covers several appends.
+pub fn parse_stream(
+    path: &PathBuf,
+    config: &ParseConfig,
+    mut on_update: impl FnMut(StreamUpdate) -> anyhow::Result<bool>,
+) -> anyhow::Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    compression::require_uncompressed(path, "following a log")?;
+    // Hashes of the files of the previous update
+    let mut previous: FxHashMap<PathBuf, u64> = FxHashMap::default();
+    let mut parsed_len = None;
+    let mut next_parse = Instant::now();
+    loop {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        // Wait for a line being written to be complete, the next poll picks it up
+        let mut last_byte = [0u8];
+        if len > 0 {
+            file.seek(SeekFrom::Start(len - 1))?;
+            file.read_exact(&mut last_byte)?;
+        }
+        let complete = len == 0 || last_byte[0] == b'\n';
+        if parsed_len == Some(len) || !complete || Instant::now() < next_parse {
+            std::thread::sleep(STREAM_POLL_INTERVAL);
+            continue;
+        }
+        if parsed_len.is_some_and(|parsed| len < parsed) {
+            previous.clear();
+        }
+
+        let start = Instant::now();
+        let output = parse_path(path, config)?;
+        let hashes: FxHashMap<PathBuf, u64> = output
+            .iter()
+            .map(|(file, contents)| (file.clone(), fxhash::hash64(contents)))
+            .collect();
+        let changed = output
+            .iter()
+            .filter(|(file, _)| previous.get(file) != hashes.get(file))
+            .map(|(file, _)| file.clone())
+            .collect();
+        let mut removed: Vec<PathBuf> = previous
+            .keys()
+            .filter(|file| !hashes.contains_key(*file))
+            .cloned()
+            .collect();
+        removed.sort();
+        previous = hashes;
+        parsed_len = Some(len);
+        let parse_duration = start.elapsed();
+        next_parse = Instant::now() + parse_duration;
+        let update = StreamUpdate {
+            log_bytes: len,
+            parse_duration,
+            output,
+            changed,
+            removed,
+        };
+        if !on_update(update)? {
+            return Ok(());
+        }
+    }
+}
+
 pub fn read_chromium_events_with_pid(
     path: &std::path::Path,
     rank_num: u32,
@@ -1399,10 +3289,7 @@ pub fn generate_multi_rank_html(
     compile_id_divergence: bool,
     diagnostics: Diagnostics,
 ) -> anyhow::Result<(PathBuf, String)> {
-    // Create the TinyTemplate instance for rendering the landing page.
-    let mut tt = TinyTemplate::new();
-    tt.add_formatter("format_unescaped", tinytemplate::format_unescaped);
-    tt.add_template("multi_rank_index.html", TEMPLATE_MULTI_RANK_INDEX)?;
+    let tt = report_model::templates(false)?;
 
     let ctx = MultiRankContext {
         css: CSS,
@@ -1415,12 +3302,145 @@ pub fn generate_multi_rank_html(
         compile_id_divergence,
         diagnostics,
     };
-    let html = tt.render("multi_rank_index.html", &ctx)?;
+    let html = report_model::render(&tt, "multi_rank_index.html", &ctx);
     let landing_page_path = out_path.join("index.html");
 
     Ok((landing_page_path, html))
 }
 
+/// Scan rank logs before parsing them for empty or truncated logs and for ranks without a log.
+///
+/// The expected ranks are `0..world_size` when a `world_size` shows up in an envelope or a
+/// process group artifact of the logs, otherwise every rank up to the highest one found.  Returns the issues sorted by rank.
+pub fn check_rank_logs(rank_logs: &[(PathBuf, u32)]) -> anyhow::Result<Vec<RankLogIssue>> {
+    static GLOG_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[VIWEC]\d{4} ").unwrap());
+    // Artifacts torch.distributed logs its process group, and with it the world size, in
+    const PROCESS_GROUP_ARTIFACTS: [&str; 2] = ["pg_config", "pg_info"];
+
+    let mut issues = Vec::new();
+    let mut world_size: Option<u32> = None;
+    for (log_path, rank) in rank_logs {
+        let mut problem = |problem: &str| {
+            issues.push(RankLogIssue {
+                rank: *rank,
+                problem: problem.to_string(),
+            })
+        };
+        if fs::metadata(log_path)?.len() == 0 {
+            problem("log is empty");
+            continue;
+        }
+        let mut structured_lines = 0;
+        // md5 the last entry's payload should have, and a hash of what was actually read
+        let mut expected_md5: Option<String> = None;
+        let mut hasher = Md5::new();
+        let mut first_payload_line = true;
+        let mut process_group_payload = false;
+        // What the last line was: whether it ended in a newline, and whether it was an envelope
+        // that parsed, one that didn't or a payload line
+        let mut ends_with_newline = true;
+        let mut last_envelope: Option<bool> = None;
+        let mut last_payload_line = false;
+        let mut reader = compression::LogReader::open(log_path)?;
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                break;
+            }
+            // Line endings are stripped the way the parser strips them, CRLF included
+            ends_with_newline = buf.last() == Some(&b'\n');
+            if ends_with_newline {
+                buf.pop();
+            }
+            if buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+            let line = String::from_utf8_lossy(&buf);
+            last_envelope = None;
+            last_payload_line = line.starts_with('\t');
+            if let Some(payload_line) = line.strip_prefix('\t') {
+                if !first_payload_line {
+                    hasher.update(b"\n");
+                }
+                first_payload_line = false;
+                hasher.update(payload_line);
+                if process_group_payload {
+                    world_size = max_world_size(world_size, payload_line);
+                }
+            } else if GLOG_LINE.is_match(&line) {
+                structured_lines += 1;
+                // TORCH_LOGS lines in the log aren't envelopes
+                let json = line
+                    .split_once("] ")
+                    .map(|(_, json)| json)
+                    .filter(|json| json.starts_with('{'));
+                let envelope = json.and_then(|json| serde_json::from_str::<Value>(json).ok());
+                if json.is_some() {
+                    last_envelope = Some(envelope.is_some());
+                }
+                if let Some(envelope) = &envelope {
+                    world_size = max_world_size(world_size, &line);
+                    process_group_payload = envelope
+                        .pointer("/artifact/name")
+                        .and_then(Value::as_str)
+                        .is_some_and(|name| PROCESS_GROUP_ARTIFACTS.contains(&name));
+                }
+                expected_md5 = envelope
+                    .as_ref()
+                    .and_then(|e| e.get("has_payload")?.as_str().map(str::to_string));
+                hasher = Md5::new();
+                first_payload_line = true;
+            }
+        }
+        let payload_complete = expected_md5.map(|md5| {
+            let mut expect_buf = [0u8; 16];
+            base16ct::lower::decode(md5, &mut expect_buf).is_ok()
+                && expect_buf == hasher.finalize()[..]
+        });
+        // A log may stop right after a complete line without its newline: only a line that can't
+        // be shown to be complete counts as cut off
+        let last_line_verified =
+            last_envelope == Some(true) || (last_payload_line && payload_complete == Some(true));
+        if structured_lines == 0 {
+            problem("no structured trace lines found");
+        } else if payload_complete == Some(false) {
+            problem("payload of the last entry is incomplete (truncated?)");
+        } else if last_envelope == Some(false) || (!ends_with_newline && !last_line_verified) {
+            problem("last line is incomplete (truncated?)");
+        }
+    }
+
+    let present: FxHashSet<u32> = rank_logs.iter().map(|(_, rank)| *rank).collect();
+    let expected = match world_size {
+        Some(w) => w,
+        None => present.iter().max().map_or(0, |max| max + 1),
+    };
+    for rank in (0..expected).filter(|r| !present.contains(r)) {
+        issues.push(RankLogIssue {
+            rank,
+            problem: match world_size {
+                Some(w) => format!("no log found (world size is {w})"),
+                None => "no log found (gap in rank numbering)".to_string(),
+            },
+        });
+    }
+    issues.sort_by_key(|issue| issue.rank);
+    Ok(issues)
+}
+
+/// The larger of `world_size` and any world size reported in `line`
+fn max_world_size(world_size: Option<u32>, line: &str) -> Option<u32> {
+    static WORLD_SIZE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"\bworld_size["']?\s*[:=]\s*(\d+)"#).unwrap());
+    WORLD_SIZE
+        .captures_iter(line)
+        .filter_map(|caps| caps[1].parse::<u32>().ok())
+        .fold(world_size, |max, size| {
+            Some(max.map_or(size, |m| m.max(size)))
+        })
+}
+
 /// Generate multi-rank landing and aggregated artifacts from pre-parsed rank outputs.
 ///
 /// This mirrors the diagnostics and file emission performed by the CLI `--all-ranks-html` path.
@@ -1522,38 +3542,20 @@ pub fn generate_multi_rank_landing(
             });
 
     // Build groups describing cache hit/miss patterns per rank
-    let cache_divergence_groups: Vec<DivergenceGroup> = if cache_seq_groups.len() > 1 {
-        cache_seq_groups
-            .iter()
-            .map(|(seq, ranks_vec)| {
-                let mut sorted = ranks_vec.clone();
-                sorted.sort_unstable();
-                DivergenceGroup {
-                    sequence: seq.clone(),
-                    ranks: sorted
-                        .iter()
-                        .map(|r| r.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                }
-            })
-            .collect()
-    } else {
-        Vec::new()
-    };
+    let cache_divergence_groups = divergence_groups(&cache_seq_groups);
 
     // combine chromium events from all ranks
     if !all_chromium_events.is_empty() {
         let combined_chromium_path = out_path.join("chromium_events.json");
         let combined_events_json = serde_json::to_string_pretty(&all_chromium_events)?;
-        fs::write(combined_chromium_path, combined_events_json)?;
+        paths::write_atomic(combined_chromium_path, combined_events_json)?;
     }
 
     // Process runtime estimations from all ranks
     let runtime_estimations = crate::parsers::read_runtime_estimations(&out_path, &rank_nums)?;
     if !runtime_estimations.is_empty() {
         let runtime_path = out_path.join("runtime_estimations.json");
-        fs::write(
+        paths::write_atomic(
             &runtime_path,
             serde_json::to_string_pretty(&runtime_estimations)?,
         )?;
@@ -1633,7 +3635,8 @@ pub fn generate_multi_rank_landing(
         let mut pids_for_threads: Vec<u32> = threads_by_pid.keys().copied().collect();
         pids_for_threads.sort_unstable();
         for pid in pids_for_threads {
-            let entries = threads_by_pid.remove(&pid).unwrap_or_default();
+            let mut entries = threads_by_pid.remove(&pid).unwrap_or_default();
+            entries.sort_by(|a, b| a.1.cmp(&b.1));
             for (idx, (tid, graph_name)) in entries.into_iter().enumerate() {
                 all_events.extend([
                     serde_json::json!({
@@ -1654,7 +3657,7 @@ pub fn generate_multi_rank_landing(
             }
         }
 
-        fs::write(
+        paths::write_atomic(
             out_path.join("chromium_trace_with_runtime.json"),
             serde_json::to_string_pretty(&all_events)?,
         )?;
@@ -1671,7 +3674,7 @@ pub fn generate_multi_rank_landing(
     let collective_schedules = crate::parsers::read_collective_schedules(&out_path, &rank_nums)?;
     if !collective_schedules.is_empty() {
         let schedules_path = out_path.join("collective_schedules.json");
-        fs::write(
+        paths::write_atomic(
             &schedules_path,
             serde_json::to_string_pretty(&collective_schedules)?,
         )?;
@@ -1706,25 +3709,7 @@ pub fn generate_multi_rank_landing(
         }
     }
 
-    let tensor_meta_divergence_groups: Vec<DivergenceGroup> = if tensor_meta_groups.len() > 1 {
-        tensor_meta_groups
-            .iter()
-            .map(|(seq, ranks_vec)| {
-                let mut sorted = ranks_vec.clone();
-                sorted.sort_unstable();
-                DivergenceGroup {
-                    sequence: seq.clone(),
-                    ranks: sorted
-                        .iter()
-                        .map(|r| r.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                }
-            })
-            .collect()
-    } else {
-        Vec::new()
-    };
+    let tensor_meta_divergence_groups = divergence_groups(&tensor_meta_groups);
 
     // Group ranks by their collective op sequence
     let mut collective_seq_groups: FxHashMap<String, Vec<u32>> = FxHashMap::default();
@@ -1743,25 +3728,7 @@ pub fn generate_multi_rank_landing(
         }
     }
 
-    let collective_divergence_groups: Vec<DivergenceGroup> = if collective_seq_groups.len() > 1 {
-        collective_seq_groups
-            .iter()
-            .map(|(seq, ranks_vec)| {
-                let mut sorted = ranks_vec.clone();
-                sorted.sort_unstable();
-                DivergenceGroup {
-                    sequence: seq.clone(),
-                    ranks: sorted
-                        .iter()
-                        .map(|r| r.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                }
-            })
-            .collect()
-    } else {
-        Vec::new()
-    };
+    let collective_divergence_groups = divergence_groups(&collective_seq_groups);
 
     println!(
         "Multi-rank report generated under {}\nIndividual pages: rank_*/index.html",
@@ -1769,12 +3736,21 @@ pub fn generate_multi_rank_landing(
     );
 
     let exec_order_summary = build_exec_order_summary(&out_path, &rank_nums, &collective_schedules);
+    let activity = rank_activity(&out_path, &rank_nums);
+    let rank_artifacts =
+        rank_artifacts::write_rank_artifact_pages(&out_path, &rank_nums, cfg.base_url.as_deref())?;
+
+    let compile_divergence = rank_divergence::compile_divergence(&out_path, &rank_nums)?;
 
     let diagnostics = Diagnostics {
+        integrity: ctx.diagnostics.integrity.clone(),
         divergence: DivergenceFlags {
             cache: cache_seq_groups.len() > 1,
             collective: collective_seq_groups.len() > 1,
             tensor_meta: tensor_meta_groups.len() > 1,
+            graphs: compile_divergence
+                .as_ref()
+                .is_some_and(|d| !d.divergent_ranks.is_empty()),
         },
         artifacts: ArtifactFlags {
             runtime_trace: !runtime_estimations.is_empty(),
@@ -1784,15 +3760,21 @@ pub fn generate_multi_rank_landing(
         collective_groups: collective_divergence_groups.clone(),
         tensor_meta_groups: tensor_meta_divergence_groups.clone(),
         exec_order: exec_order_summary,
+        rank_artifacts,
+        activity,
+        compile_divergence,
     };
 
     // Emit landing page identical to CLI
     let has_chromium_events = !all_chromium_events.is_empty();
     let show_desync_warning = compile_id_divergence
+        || diagnostics.divergence.graphs
         || diagnostics.divergence.cache
         || diagnostics.divergence.collective
         || diagnostics.divergence.tensor_meta;
 
+    // Each rank's report has its own report model; the landing page's is at the root
+    report_model::start_recording(cfg.report_model);
     let (landing_page_path, landing_html) = generate_multi_rank_html(
         &out_path,
         sorted_ranks,
@@ -1802,11 +3784,76 @@ pub fn generate_multi_rank_landing(
         compile_id_divergence,
         diagnostics,
     )?;
-    fs::write(&landing_page_path, landing_html)?;
+    let mut output: ParseOutput = vec![(PathBuf::from("index.html"), landing_html)];
+    report_model::attach(&mut output, false, cfg.base_url.as_deref())?;
+    if let Some(base_url) = &cfg.base_url {
+        links::rewrite_report_links(&mut output, base_url);
+    }
+    paths::write_output(&out_path, output)?;
 
     Ok(landing_page_path)
 }
 
+/// The compile activity strip of each rank that compiled, all over the span of the whole run
+fn rank_activity(out_path: &Path, rank_nums: &[u32]) -> Vec<RankActivity> {
+    let activities: Vec<(u32, activity::CompileActivity)> = rank_nums
+        .iter()
+        .filter_map(|&rank| {
+            let path = out_path
+                .join(format!("rank_{rank}"))
+                .join(activity::ACTIVITY_FILENAME);
+            let content = fs::read_to_string(path).ok()?;
+            Some((rank, serde_json::from_str(&content).ok()?))
+        })
+        .collect();
+    let start = activities
+        .iter()
+        .filter_map(|(_, a)| a.start)
+        .fold(f64::INFINITY, f64::min);
+    let end = activities
+        .iter()
+        .filter_map(|(_, a)| a.end)
+        .fold(f64::NEG_INFINITY, f64::max);
+    activities
+        .iter()
+        .filter_map(|(rank, a)| {
+            Some(RankActivity {
+                rank: *rank,
+                summary: a.summary()?,
+                sparkline_html: a.sparkline(start, end)?,
+            })
+        })
+        .collect()
+}
+
+/// Ranks grouped by a per-rank sequence, when they don't all agree; groups are ordered by
+/// their lowest rank so the landing page reads the same on every run
+fn divergence_groups(groups: &FxHashMap<String, Vec<u32>>) -> Vec<DivergenceGroup> {
+    if groups.len() <= 1 {
+        return Vec::new();
+    }
+    let mut groups: Vec<(&String, Vec<u32>)> = groups
+        .iter()
+        .map(|(seq, ranks)| {
+            let mut ranks = ranks.clone();
+            ranks.sort_unstable();
+            (seq, ranks)
+        })
+        .collect();
+    groups.sort_by(|a, b| a.1.cmp(&b.1));
+    groups
+        .into_iter()
+        .map(|(seq, ranks)| DivergenceGroup {
+            sequence: seq.clone(),
+            ranks: ranks
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        })
+        .collect()
+}
+
 /// Build ExecOrderSummary from artifacts under out_path for the given ranks
 pub fn build_exec_order_summary(
     out_path: &PathBuf,
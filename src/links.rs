@@ -0,0 +1,103 @@
+//! Rewriting of intra-report links for reports hosted under a URL prefix (`--base-url`).
+//!
+//! Reports normally use relative links, which break when a hosting proxy mounts the output
+//! directory under a nested prefix.  With a base URL, every relative `href`/`src` is resolved
+//! against the page it appears on and made absolute under the prefix.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Component, Path, PathBuf};
+
+use crate::types::ParseOutput;
+
+static LINK_ATTR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?P<attr>\b(?:href|src))=(?P<q>["'])(?P<url>[^"']*)["']"#).unwrap());
+static BASE_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<base href=["']([^"']*)["']\s*/?>"#).unwrap());
+
+fn is_relative(url: &str) -> bool {
+    !(url.is_empty()
+        || url.starts_with('#')
+        || url.starts_with('/')
+        || url.starts_with('?')
+        || url.contains("://")
+        || url.starts_with("mailto:")
+        || url.starts_with("javascript:")
+        || url.starts_with("data:"))
+}
+
+/// Lexically normalize `a/./b/../c` to `a/c`; `..` past the report root is dropped
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::Normal(c) => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// The directory, relative to the report root, the links of the page at `page` resolve against
+pub(crate) fn link_dir(html: &str, page: &Path) -> PathBuf {
+    let dir = page.parent().map(Path::to_path_buf).unwrap_or_default();
+    // Pages like compilation_metrics.html resolve their links against a <base> tag
+    match BASE_TAG.captures(html) {
+        Some(caps) if is_relative(&caps[1]) => normalize(&dir.join(&caps[1])),
+        _ => dir,
+    }
+}
+
+/// The relative links of the page at `page`, as written and resolved against the report root,
+/// without their query strings and fragments
+pub(crate) fn relative_links(html: &str, page: &Path) -> Vec<(String, PathBuf)> {
+    let dir = link_dir(html, page);
+    let html = BASE_TAG.replace(html, "");
+    LINK_ATTR
+        .captures_iter(&html)
+        .map(|caps| caps["url"].to_string())
+        .filter(|url| is_relative(url))
+        .map(|url| {
+            let path = &url[..url.find(['?', '#']).unwrap_or(url.len())];
+            let resolved = normalize(&dir.join(path));
+            (url, resolved)
+        })
+        .collect()
+}
+
+/// Rewrite the relative links of one HTML page located at `page` (relative to the report root)
+pub fn rewrite_links(html: &str, page: &Path, base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    let dir = link_dir(html, page);
+    let html = BASE_TAG.replace(html, "");
+    LINK_ATTR
+        .replace_all(&html, |caps: &regex::Captures| {
+            let url = &caps["url"];
+            if !is_relative(url) {
+                return caps[0].to_string();
+            }
+            // Keep query strings and fragments as-is, only resolve the path part
+            let split = url.find(['?', '#']).unwrap_or(url.len());
+            let (path, rest) = url.split_at(split);
+            let resolved = normalize(&dir.join(path));
+            let resolved = resolved.to_string_lossy().replace('\\', "/");
+            format!(
+                "{}={q}{base_url}/{resolved}{rest}{q}",
+                &caps["attr"],
+                q = &caps["q"]
+            )
+        })
+        .into_owned()
+}
+
+/// Rewrite links in every HTML page of a parsed report
+pub fn rewrite_report_links(output: &mut ParseOutput, base_url: &str) {
+    for (path, contents) in output.iter_mut() {
+        if path.extension().is_some_and(|e| e == "html") {
+            *contents = rewrite_links(contents, path, base_url);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Advisory lock of an output directory, so two tlparse runs writing the same report at once
+//! (e.g. overlapping cron jobs) fail fast instead of interleaving files.  The lock is a file
+//! next to the directory rather than in it, since `--overwrite` deletes the directory:
+//! `tl_out/` is locked by `tl_out.tlparse.lock`, which the run holds an OS lock on
+//! (`flock`/`LockFileEx`), so the lock goes with the process however it ends, Ctrl-C and
+//! `process::exit` included.  The file holds the pid, host and start time of the run, only to
+//! say who holds the lock; a file left behind by a run that's gone is simply locked again.
+//! `--break-lock` takes over a lock that is held.
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+const LOCK_SUFFIX: &str = ".tlparse.lock";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LockInfo {
+    pid: u32,
+    host: String,
+    started_at: String,
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// `tl_out` -> `tl_out.tlparse.lock`
+pub fn lock_path(out_dir: &Path) -> PathBuf {
+    let name = out_dir
+        .file_name()
+        .map_or("tl_out".into(), |n| n.to_string_lossy().into_owned());
+    out_dir.with_file_name(format!("{name}{LOCK_SUFFIX}"))
+}
+
+/// Whether `file` is still the one at `path`, rather than one a releasing run removed after
+/// it was opened
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_current(_file: &File, path: &Path) -> bool {
+    path.exists()
+}
+
+/// Held while a run writes its output directory; released when dropped or when the process
+/// ends
+#[derive(Debug)]
+pub struct OutputLock {
+    path: PathBuf,
+    /// Holds the OS lock
+    file: File,
+}
+
+impl OutputLock {
+    /// Lock `out_dir`, failing if another run holds the lock unless `break_lock` is set
+    pub fn acquire(out_dir: &Path, break_lock: bool) -> anyhow::Result<Self> {
+        let path = lock_path(out_dir);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).with_context(|| format!("Creating {}", parent.display()))?;
+        }
+        let info = LockInfo {
+            pid: std::process::id(),
+            host: hostname(),
+            started_at: chrono::Local::now().to_rfc3339(),
+        };
+        loop {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .with_context(|| format!("Opening {}", path.display()))?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) => {
+                    if !break_lock {
+                        bail!("{}", held_message(out_dir, &path));
+                    }
+                    eprintln!("Breaking the lock {}", path.display());
+                    match fs::remove_file(&path) {
+                        Ok(()) => {}
+                        Err(e) if e.kind() == ErrorKind::NotFound => {}
+                        Err(e) => {
+                            return Err(e).with_context(|| format!("Removing {}", path.display()))
+                        }
+                    }
+                    continue;
+                }
+                Err(TryLockError::Error(e)) => {
+                    return Err(e).with_context(|| format!("Locking {}", path.display()))
+                }
+            }
+            if !is_current(&file, &path) {
+                continue;
+            }
+            file.set_len(0)
+                .and_then(|()| file.write_all(serde_json::to_string(&info)?.as_bytes()))
+                .with_context(|| format!("Writing {}", path.display()))?;
+            return Ok(Self { path, file });
+        }
+    }
+}
+
+impl Drop for OutputLock {
+    fn drop(&mut self) {
+        // Removed while still locked, so that no other run locks the file on its way out, and
+        // only if --break-lock didn't replace it
+        if is_current(&self.file, &self.path) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Why `out_dir` can't be locked
+fn held_message(out_dir: &Path, path: &Path) -> String {
+    let holder = fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<LockInfo>(&s).ok());
+    match holder {
+        Some(holder) => format!(
+            "{} is being written by tlparse (pid {} on {}, started {}); wait for it to finish or use another -o OUTDIR",
+            out_dir.display(),
+            holder.pid,
+            holder.host,
+            holder.started_at
+        ),
+        None => format!(
+            "{} is locked by {}; wait for the run holding it to finish or pass --break-lock",
+            out_dir.display(),
+            path.display()
+        ),
+    }
+}
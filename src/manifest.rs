@@ -0,0 +1,258 @@
+//! `manifest.json`: a record of what one tlparse invocation generated, read back by
+//! `tlparse about-run`.
+
+use anyhow::Context;
+use fxhash::FxHashMap;
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::types::ParseOutput;
+
+pub const MANIFEST_FILENAME: &str = "manifest.json";
+
+// `dynamo_output_graph_0.txt` -> `dynamo_output_graph`; inductor output code also carries a hash
+static ARTIFACT_KIND: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<kind>.+?)(?:_[0-9a-z]{20,})?_\d+$").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub bytes: u64,
+    pub kind: String,
+    /// Log timestamp of the envelope the file was generated from, for compile artifacts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub tlparse_version: String,
+    pub log: PathBuf,
+    pub generated_at: String,
+    pub parse_duration_s: f64,
+    /// `ParseConfig::metadata` of the run
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub metadata: IndexMap<String, String>,
+    pub files: Vec<ManifestEntry>,
+}
+
+/// Classify an output file: `page` for HTML, `payload` for raw payloads, otherwise the
+/// artifact name without its output counter
+pub fn artifact_kind(path: &Path) -> String {
+    if path.extension().is_some_and(|e| e == "html") {
+        return "page".to_string();
+    }
+    if path.starts_with("payloads") {
+        return "payload".to_string();
+    }
+    artifact_name_kind(&path.to_string_lossy())
+}
+
+/// The artifact a file name is an instance of, regardless of its extension:
+/// `-_0_0_0/dynamo_output_graph_0.txt` -> `dynamo_output_graph`
+pub fn artifact_name_kind(name: &str) -> String {
+    let stem = Path::new(name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    ARTIFACT_KIND
+        .captures(&stem)
+        .map_or(stem.clone(), |caps| caps["kind"].to_string())
+}
+
+impl Manifest {
+    pub fn new(log: &Path, output: &ParseOutput, parse_duration: Duration) -> Self {
+        let log_times = log_times(output);
+        Self {
+            tlparse_version: env!("CARGO_PKG_VERSION").to_string(),
+            log: log.canonicalize().unwrap_or_else(|_| log.to_path_buf()),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            parse_duration_s: parse_duration.as_secs_f64(),
+            metadata: IndexMap::default(),
+            files: output
+                .iter()
+                .map(|(path, contents)| {
+                    let path = path.to_string_lossy().replace('\\', "/");
+                    ManifestEntry {
+                        log_time: log_times.get(&path).cloned(),
+                        bytes: contents.len() as u64,
+                        kind: artifact_kind(Path::new(&path)),
+                        path,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load(out_dir: &Path) -> anyhow::Result<Self> {
+        let path = out_dir.join(MANIFEST_FILENAME);
+        let contents =
+            fs::read_to_string(&path).with_context(|| format!("Reading {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Parsing {}", path.display()))
+    }
+
+    /// The `log_time` of the file at `path`, if the manifest records one
+    pub fn log_time(&self, path: &str) -> Option<String> {
+        self.files
+            .iter()
+            .find(|f| f.path == path)
+            .and_then(|f| f.log_time.clone())
+    }
+
+    /// Set the modification time of each file under `out_dir` with a `log_time` to it;
+    /// returns how many were set
+    pub fn set_mtimes(&self, out_dir: &Path) -> anyhow::Result<usize> {
+        let mut set = 0;
+        for entry in &self.files {
+            let Some(log_time) = &entry.log_time else {
+                continue;
+            };
+            let Ok(time) = chrono::DateTime::parse_from_rfc3339(log_time) else {
+                continue;
+            };
+            let path = out_dir.join(&entry.path);
+            // Files of a lazy artifact index aren't written until they're requested
+            let Ok(file) = fs::File::options().write(true).open(&path) else {
+                continue;
+            };
+            file.set_modified(time.into())
+                .with_context(|| format!("Setting the modification time of {}", path.display()))?;
+            set += 1;
+        }
+        Ok(set)
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.files.iter().map(|f| f.bytes).sum()
+    }
+
+    /// (files, bytes) per artifact kind, largest first
+    pub fn counts_by_kind(&self) -> IndexMap<String, (usize, u64)> {
+        let mut counts: IndexMap<String, (usize, u64)> = IndexMap::new();
+        for f in &self.files {
+            let entry = counts.entry(f.kind.clone()).or_default();
+            entry.0 += 1;
+            entry.1 += f.bytes;
+        }
+        counts.sort_by(|ka, a, kb, b| b.1.cmp(&a.1).then_with(|| ka.cmp(kb)));
+        counts
+    }
+
+    /// Human readable postmortem of the run
+    pub fn summary(&self) -> String {
+        let mut s = String::new();
+        let pages = self.files.iter().filter(|f| f.kind == "page").count();
+        writeln!(s, "Log:          {}", self.log.display()).unwrap();
+        writeln!(
+            s,
+            "Generated:    {} (tlparse {})",
+            self.generated_at, self.tlparse_version
+        )
+        .unwrap();
+        writeln!(
+            s,
+            "Parse time:   {}",
+            crate::units::seconds(self.parse_duration_s, 2)
+        )
+        .unwrap();
+        for (key, value) in &self.metadata {
+            writeln!(s, "{:<14}{value}", format!("{key}:")).unwrap();
+        }
+        writeln!(
+            s,
+            "Output:       {} files ({} pages), {}",
+            self.files.len(),
+            pages,
+            crate::units::bytes(self.total_bytes())
+        )
+        .unwrap();
+        writeln!(s, "By kind:").unwrap();
+        for (kind, (count, bytes)) in self.counts_by_kind() {
+            writeln!(
+                s,
+                "  {count:>6}  {:>10}  {kind}",
+                crate::units::bytes(bytes)
+            )
+            .unwrap();
+        }
+        s
+    }
+}
+
+/// `log_time` of each compile artifact, by path, from the `compile_directory.json` of `output`
+fn log_times(output: &ParseOutput) -> FxHashMap<String, String> {
+    let Some((_, directory)) = output
+        .iter()
+        .find(|(path, _)| path == Path::new("compile_directory.json"))
+    else {
+        return FxHashMap::default();
+    };
+    let Ok(serde_json::Value::Object(directory)) = serde_json::from_str(directory) else {
+        return FxHashMap::default();
+    };
+    directory
+        .values()
+        .filter_map(|compile| compile["artifacts"].as_array())
+        .flatten()
+        .filter_map(|artifact| {
+            Some((
+                artifact["url"].as_str()?.to_string(),
+                artifact["log_time"].as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Size badge classes, largest threshold first
+const SIZE_HEAT: [(u64, &str); 3] = [
+    (256 << 20, "size-huge"),
+    (16 << 20, "size-hot"),
+    (1 << 20, "size-warm"),
+];
+
+/// CSS class color-grading the size badge of an artifact; empty for ordinary sizes
+pub(crate) fn size_heat(bytes: u64) -> &'static str {
+    SIZE_HEAT
+        .iter()
+        .find(|(threshold, _)| bytes >= *threshold)
+        .map_or("", |(_, class)| class)
+}
+
+/// Load the manifest of `out_dir`, or of each `rank_N/` report for multi-rank output
+pub fn load_reports(out_dir: &Path) -> anyhow::Result<Vec<(PathBuf, Manifest)>> {
+    if out_dir.join(MANIFEST_FILENAME).is_file() {
+        return Ok(vec![(out_dir.to_path_buf(), Manifest::load(out_dir)?)]);
+    }
+    let mut rank_dirs: Vec<(u32, PathBuf)> = fs::read_dir(out_dir)
+        .with_context(|| format!("Couldn't access directory {}", out_dir.display()))?
+        .flatten()
+        .filter_map(|entry| {
+            let rank = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("rank_")?
+                .parse()
+                .ok()?;
+            Some((rank, entry.path()))
+        })
+        .filter(|(_, dir)| dir.join(MANIFEST_FILENAME).is_file())
+        .collect();
+    if rank_dirs.is_empty() {
+        anyhow::bail!(
+            "No {} found in {}; was it generated by tlparse?",
+            MANIFEST_FILENAME,
+            out_dir.display()
+        );
+    }
+    rank_dirs.sort();
+    rank_dirs
+        .into_iter()
+        .map(|(_, dir)| Manifest::load(&dir).map(|m| (dir, m)))
+        .collect()
+}
@@ -0,0 +1,116 @@
+//! `tlparse merge out_a/ out_b/ -o merged/`: combine reports generated separately (e.g. by each
+//! node of a job, on its own machine) into one multi-rank report, without the original logs.
+//!
+//! Every report of the inputs (a single-log report, or each `rank_N/` of a multi-rank one) is
+//! copied to a `rank_N/` of the output using the file list of its manifest, and the multi-rank
+//! landing page is generated over all of them.  Ranks keep their number (from the `rank_N/`
+//! directory or the log name) unless an earlier input already has it; those, and reports
+//! without a rank, are numbered after the highest rank.  The manifest of each copy records
+//! where it came from in its metadata.
+
+use anyhow::{bail, Context};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::manifest::{load_reports, Manifest, MANIFEST_FILENAME};
+
+static LOG_RANK: Lazy<Regex> = Lazy::new(|| Regex::new(r"rank_(?P<rank>\d+)").unwrap());
+
+/// Where a rank of the merged report came from
+#[derive(Debug, Clone)]
+pub struct MergedRank {
+    pub rank: u32,
+    pub source: PathBuf,
+    /// The rank the report had before, if it had to be renumbered
+    pub original_rank: Option<u32>,
+}
+
+/// The rank of a report: its `rank_N/` directory within a multi-rank report, or its log name
+fn rank_hint(input: &Path, dir: &Path, manifest: &Manifest) -> Option<u32> {
+    let from_dir = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_prefix("rank_"))
+        .and_then(|r| r.parse().ok())
+        .filter(|_| dir != input);
+    from_dir.or_else(|| {
+        let name = manifest.log.file_name()?.to_string_lossy().into_owned();
+        LOG_RANK.captures(&name)?["rank"].parse().ok()
+    })
+}
+
+fn copy_report(dir: &Path, manifest: &Manifest, to: &Path) -> anyhow::Result<()> {
+    for entry in &manifest.files {
+        let path = Path::new(&entry.path);
+        if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            bail!(
+                "{} lists a file outside the report: {}",
+                dir.display(),
+                entry.path
+            );
+        }
+        let target = to.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Creating {}", parent.display()))?;
+        }
+        fs::copy(dir.join(path), &target)
+            .with_context(|| format!("Copying {}", dir.join(path).display()))?;
+    }
+    Ok(())
+}
+
+/// Copy the reports of `inputs` to `rank_N/` directories of `out_dir`; the caller generates the
+/// landing page over the returned ranks
+pub fn merge_reports(inputs: &[PathBuf], out_dir: &Path) -> anyhow::Result<Vec<MergedRank>> {
+    let mut reports: Vec<(PathBuf, Manifest, Option<u32>)> = Vec::new();
+    for input in inputs {
+        for (dir, manifest) in load_reports(input)? {
+            let hint = rank_hint(input, &dir, &manifest);
+            reports.push((dir, manifest, hint));
+        }
+    }
+    if reports.is_empty() {
+        bail!("No reports to merge");
+    }
+
+    let mut taken: BTreeSet<u32> = BTreeSet::new();
+    let mut ranks: Vec<Option<u32>> = reports
+        .iter()
+        .map(|(_, _, hint)| hint.filter(|r| taken.insert(*r)))
+        .collect();
+    let first_free = taken.last().map_or(0, |r| r + 1);
+    for (next, rank) in (first_free..).zip(ranks.iter_mut().filter(|r| r.is_none())) {
+        *rank = Some(next);
+    }
+
+    let mut merged = Vec::new();
+    for ((dir, mut manifest, hint), rank) in reports.into_iter().zip(ranks) {
+        let rank = rank.unwrap();
+        let to = out_dir.join(format!("rank_{rank}"));
+        copy_report(&dir, &manifest, &to)?;
+        let source = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+        manifest
+            .metadata
+            .insert("merged_from".to_string(), source.display().to_string());
+        let original_rank = hint.filter(|h| *h != rank);
+        if let Some(original) = original_rank {
+            manifest
+                .metadata
+                .insert("source_rank".to_string(), original.to_string());
+        }
+        crate::paths::write_atomic(
+            to.join(MANIFEST_FILENAME),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+        merged.push(MergedRank {
+            rank,
+            source,
+            original_rank,
+        });
+    }
+    merged.sort_by_key(|m| m.rank);
+    Ok(merged)
+}
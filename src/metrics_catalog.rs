@@ -0,0 +1,161 @@
+//! The Metrics table of compilation metrics pages: every field of a `compilation_metrics`
+//! envelope grouped by phase, with durations, timestamps and sizes formatted, and explained
+//! where the bundled field catalog (`metrics_catalog.json`) knows the field.  New fields PyTorch
+//! starts logging are listed under Other until they're added to the catalog.
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config_docs::ConfigDocs;
+use crate::types::{CompilationMetricsMetadata, MetricsField, MetricsGroup};
+use crate::units;
+
+#[derive(Deserialize)]
+struct Catalog {
+    phases: Vec<String>,
+    fields: Vec<CatalogField>,
+}
+
+#[derive(Deserialize)]
+struct CatalogField {
+    field: String,
+    phase: String,
+    /// `timestamp_s` or `timestamp_us` for times that aren't durations; otherwise the unit
+    /// follows from the field's suffix
+    unit: Option<String>,
+    doc: String,
+}
+
+static CATALOG: Lazy<Catalog> = Lazy::new(|| {
+    serde_json::from_str(include_str!("metrics_catalog.json")).expect("valid metrics catalog")
+});
+
+const OTHER_PHASE: &str = "Other";
+
+fn format_timestamp(seconds: f64) -> String {
+    chrono::DateTime::from_timestamp_micros((seconds * 1e6) as i64).map_or_else(
+        || seconds.to_string(),
+        |t| t.format("%Y-%m-%d %H:%M:%S%.3f UTC").to_string(),
+    )
+}
+
+/// How a field's value is shown, or `None` for null and empty values
+fn format_value(field: &str, unit: Option<&str>, value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(if *b { "yes" } else { "no" }.to_string()),
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => {
+            let Some(x) = n.as_f64() else {
+                return Some(n.to_string());
+            };
+            let seconds = match unit {
+                Some("timestamp_s") => return Some(format_timestamp(x)),
+                Some("timestamp_us") => return Some(format_timestamp(x / 1e6)),
+                _ if field.ends_with("_s") => x,
+                _ if field.ends_with("_ms") => x / 1e3,
+                _ if field.ends_with("_us") => x / 1e6,
+                _ if field.ends_with("_ns") => x / 1e9,
+                _ if field.ends_with("_bytes") => return Some(units::bytes(x as u64)),
+                // Counts, but not ids or line numbers
+                _ if field.ends_with("_count") || field.starts_with("num_") => {
+                    return Some(n.as_u64().map_or_else(|| n.to_string(), units::count))
+                }
+                _ => return Some(n.to_string()),
+            };
+            Some(units::duration(seconds))
+        }
+        Value::Array(items) if items.is_empty() => None,
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        Value::Object(map) if map.is_empty() => None,
+        Value::Object(_) => Some(value.to_string()),
+    }
+}
+
+/// The fields of `m` with a value, by phase in catalog order
+pub fn metric_groups(
+    m: &CompilationMetricsMetadata,
+    config_docs: &ConfigDocs,
+) -> Vec<MetricsGroup> {
+    let Ok(Value::Object(fields)) = serde_json::to_value(m) else {
+        return Vec::new();
+    };
+    let mut groups: Vec<MetricsGroup> = CATALOG
+        .phases
+        .iter()
+        .map(|phase| MetricsGroup {
+            phase: phase.clone(),
+            fields: Vec::new(),
+        })
+        .collect();
+    let mut add = |phase: &str, field: MetricsField| {
+        let i = groups
+            .iter()
+            .position(|g| g.phase == phase)
+            .unwrap_or(groups.len() - 1);
+        groups[i].fields.push(field);
+    };
+    for entry in &CATALOG.fields {
+        let Some(value) = fields.get(&entry.field) else {
+            continue;
+        };
+        if let Some(formatted) = format_value(&entry.field, entry.unit.as_deref(), value) {
+            let options = config_docs.options(&entry.field, value);
+            add(
+                &entry.phase,
+                MetricsField {
+                    raw: raw_if_formatted(value, &formatted),
+                    name: entry.field.clone(),
+                    value: match &options {
+                        Some(options) => format!("{} options", options.len()),
+                        None => formatted,
+                    },
+                    doc: entry.doc.clone(),
+                    options,
+                },
+            );
+        }
+    }
+    for (name, value) in &fields {
+        if CATALOG.fields.iter().any(|f| &f.field == name) {
+            continue;
+        }
+        if let Some(formatted) = format_value(name, None, value) {
+            add(
+                OTHER_PHASE,
+                MetricsField {
+                    raw: raw_if_formatted(value, &formatted),
+                    name: name.clone(),
+                    value: formatted,
+                    doc: String::new(),
+                    options: None,
+                },
+            );
+        }
+    }
+    groups.retain(|g| !g.fields.is_empty());
+    groups
+}
+
+/// The logged value, when it's shown formatted
+fn raw_if_formatted(value: &Value, formatted: &str) -> String {
+    match value {
+        Value::Number(n) if n.to_string() != formatted => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// The metrics as pretty printed JSON, for the raw JSON toggle
+pub fn raw_json(m: &CompilationMetricsMetadata) -> String {
+    serde_json::to_string_pretty(m).unwrap_or_default()
+}
@@ -0,0 +1,171 @@
+//! Operator coverage of a run against an eager baseline (`op_coverage.html`).
+//!
+//! An eager op trace lists the operators a model runs without torch.compile.  Operators that
+//! never show up in a compiled (AOT or post-grad) graph ran outside compiled regions, because
+//! of graph breaks or skipped frames.  The trace can be
+//!
+//! - a text file with one `op [count]` per line (`#` starts a comment),
+//! - a JSON object mapping op names to call counts, or
+//! - a Chrome trace from `torch.profiler`, whose `aten::` events are counted.
+//!
+//! Op names are compared without namespace separators or overloads, so `aten::mm`,
+//! `aten.mm.default` and `torch.ops.aten.mm.default` are all `aten.mm`.
+
+use anyhow::{bail, Context};
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::types::{OpCoverageContext, OpCoverageRow, ParseOutput};
+
+static COMPILED_OP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"torch\.ops\.([A-Za-z_]\w*)\.([A-Za-z_]\w*)").unwrap());
+
+/// Graph artifacts whose ops count as compiled
+const COMPILED_GRAPHS: [&str; 5] = [
+    "aot_forward_graph",
+    "aot_backward_graph",
+    "aot_inference_graph",
+    "aot_joint_graph",
+    "inductor_post_grad_graph",
+];
+
+/// Eager op name -> number of calls
+pub type EagerOps = IndexMap<String, u64>;
+
+/// `aten::add_.Tensor`, `torch.ops.aten.add_.Tensor` -> `aten.add_`
+pub fn normalize_op(name: &str) -> String {
+    let name = name.trim();
+    let name = name.strip_prefix("torch.ops.").unwrap_or(name);
+    let name = name.replace("::", ".");
+    let mut parts = name.split('.');
+    match (parts.next(), parts.next()) {
+        (Some(ns), Some(op)) => format!("{ns}.{op}"),
+        _ => name.to_string(),
+    }
+}
+
+fn add_op(ops: &mut EagerOps, name: &str, count: u64) {
+    *ops.entry(normalize_op(name)).or_default() += count;
+}
+
+pub fn load_eager_ops(path: &Path) -> anyhow::Result<EagerOps> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Reading eager op trace {}", path.display()))?;
+    let mut ops = EagerOps::new();
+    match serde_json::from_str::<Value>(&contents) {
+        Ok(json) => {
+            let events = match &json {
+                Value::Array(events) => Some(events),
+                Value::Object(o) => o.get("traceEvents").and_then(Value::as_array),
+                _ => None,
+            };
+            if let Some(events) = events {
+                for name in events
+                    .iter()
+                    .filter_map(|e| e.get("name").and_then(Value::as_str))
+                    .filter(|name| name.contains("::"))
+                {
+                    add_op(&mut ops, name, 1);
+                }
+            } else if let Value::Object(counts) = &json {
+                for (name, count) in counts {
+                    let Some(count) = count.as_u64() else {
+                        bail!("{}: count of `{name}` is not an integer", path.display());
+                    };
+                    add_op(&mut ops, name, count);
+                }
+            }
+        }
+        Err(_) => {
+            for (lineno, line) in contents.lines().enumerate() {
+                let line = line.split('#').next().unwrap_or_default();
+                let mut fields = line.split_whitespace();
+                let Some(name) = fields.next() else {
+                    continue;
+                };
+                let count = match fields.next() {
+                    Some(count) => count.parse().with_context(|| {
+                        format!("{}:{}: bad call count", path.display(), lineno + 1)
+                    })?,
+                    None => 1,
+                };
+                add_op(&mut ops, name, count);
+            }
+        }
+    }
+    if ops.is_empty() {
+        bail!("No operators found in eager op trace {}", path.display());
+    }
+    Ok(ops)
+}
+
+/// Op -> number of compiled graph artifacts it appears in
+fn compiled_ops(output: &ParseOutput) -> IndexMap<String, usize> {
+    let mut ops: IndexMap<String, usize> = IndexMap::new();
+    for (path, contents) in output {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if !COMPILED_GRAPHS.iter().any(|g| name.starts_with(g)) {
+            continue;
+        }
+        let mut in_graph: Vec<String> = COMPILED_OP
+            .captures_iter(contents)
+            .map(|caps| format!("{}.{}", &caps[1], &caps[2]))
+            .collect();
+        in_graph.sort();
+        in_graph.dedup();
+        for op in in_graph {
+            *ops.entry(op).or_default() += 1;
+        }
+    }
+    ops
+}
+
+pub fn op_coverage(eager: &EagerOps, output: &ParseOutput) -> OpCoverageContext {
+    let compiled = compiled_ops(output);
+    let mut rows: Vec<OpCoverageRow> = eager
+        .iter()
+        .map(|(op, calls)| OpCoverageRow {
+            op: op.clone(),
+            eager_calls: *calls,
+            compiled_graphs: compiled.get(op).copied().unwrap_or(0),
+        })
+        .collect();
+    // Uncovered ops first, most frequent first
+    rows.sort_by(|a, b| {
+        (a.compiled_graphs > 0)
+            .cmp(&(b.compiled_graphs > 0))
+            .then(b.eager_calls.cmp(&a.eager_calls))
+            .then_with(|| a.op.cmp(&b.op))
+    });
+    let total_calls: u64 = rows.iter().map(|r| r.eager_calls).sum();
+    let covered_calls: u64 = rows
+        .iter()
+        .filter(|r| r.compiled_graphs > 0)
+        .map(|r| r.eager_calls)
+        .sum();
+    let covered_ops = rows.iter().filter(|r| r.compiled_graphs > 0).count();
+    let mut compiled_only: Vec<String> = compiled
+        .keys()
+        .filter(|op| !eager.contains_key(*op))
+        .cloned()
+        .collect();
+    compiled_only.sort();
+    OpCoverageContext {
+        css: crate::templates::CSS,
+        qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+        coverage: format!(
+            "{:.1}%",
+            100.0 * covered_calls as f64 / total_calls.max(1) as f64
+        ),
+        covered_calls,
+        total_calls,
+        covered_ops,
+        total_ops: rows.len(),
+        rows,
+        compiled_only,
+    }
+}
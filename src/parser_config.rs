@@ -0,0 +1,173 @@
+//! Declarative parsers, loaded with `--parser-config` from a JSON file, so teams can surface
+//! their own structured-log artifacts without writing a `StructuredLogParser`:
+//!
+//! ```json
+//! {
+//!   "parsers": [
+//!     {"artifact": "my_team_.*", "filename": "{name}.json", "format": "json"},
+//!     {"graph_dump": "fused_region_\\d+", "format": "html"}
+//!   ]
+//! }
+//! ```
+//!
+//! `artifact` and `graph_dump` are regexes over the names of `artifact` and `graph_dump`
+//! envelopes, which must match the whole name.  Each matching payload is written to the
+//! compile's directory as `filename` (`{name}` is the artifact name; by default
+//! `{name}.<format>`), as is (`text`), pretty-printed (`json`) or as a highlighted code page
+//! with line anchors (`html`).
+
+use anyhow::{bail, Context};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::parsers::{
+    build_file_path, format_json_pretty, generate_html_output, ParserOutput, ParserResults,
+    StructuredLogParser,
+};
+use crate::types::{CompileId, Envelope, Metadata};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Html,
+}
+
+impl OutputFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Json => "json",
+            OutputFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParserDefinition {
+    /// For error messages; by default the pattern
+    pub name: Option<String>,
+    pub artifact: Option<String>,
+    pub graph_dump: Option<String>,
+    pub filename: Option<String>,
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParserConfigFile {
+    pub parsers: Vec<ParserDefinition>,
+}
+
+/// A parser made from a `ParserDefinition`
+pub struct DeclarativeParser {
+    name: &'static str,
+    artifact: Option<Regex>,
+    graph_dump: Option<Regex>,
+    filename: String,
+    format: OutputFormat,
+}
+
+fn whole_name(pattern: &Option<String>, name: &str) -> anyhow::Result<Option<Regex>> {
+    pattern
+        .as_ref()
+        .map(|pattern| {
+            Regex::new(&format!("^(?:{pattern})$"))
+                .with_context(|| format!("Invalid name pattern `{pattern}` in parser `{name}`"))
+        })
+        .transpose()
+}
+
+impl DeclarativeParser {
+    pub fn new(definition: &ParserDefinition) -> anyhow::Result<Self> {
+        let Some(pattern) = definition
+            .artifact
+            .as_ref()
+            .or(definition.graph_dump.as_ref())
+        else {
+            bail!("A parser needs an `artifact` or `graph_dump` name pattern");
+        };
+        let name = definition.name.clone().unwrap_or_else(|| pattern.clone());
+        let filename = definition
+            .filename
+            .clone()
+            .unwrap_or_else(|| format!("{{name}}.{}", definition.format.extension()));
+        Ok(Self {
+            artifact: whole_name(&definition.artifact, &name)?,
+            graph_dump: whole_name(&definition.graph_dump, &name)?,
+            filename,
+            format: definition.format,
+            // Parser names are static; these are made once per run
+            name: Box::leak(name.into_boxed_str()),
+        })
+    }
+}
+
+impl StructuredLogParser for DeclarativeParser {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        let matches =
+            |regex: &Option<Regex>, name: &str| regex.as_ref().is_some_and(|r| r.is_match(name));
+        if let Some(artifact) = e
+            .artifact
+            .as_ref()
+            .filter(|a| matches(&self.artifact, &a.name))
+        {
+            return Some(Metadata::Artifact(artifact));
+        }
+        e.graph_dump
+            .as_ref()
+            .filter(|g| matches(&self.graph_dump, &g.name))
+            .map(Metadata::GraphDump)
+    }
+
+    fn parse<'e>(
+        &self,
+        lineno: usize,
+        metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        _payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let name = match metadata {
+            Metadata::Artifact(artifact) => &artifact.name,
+            Metadata::GraphDump(graph_dump) => &graph_dump.name,
+            _ => bail!("Expected Artifact or GraphDump metadata"),
+        };
+        let f = build_file_path(&self.filename.replace("{name}", name), lineno, compile_id);
+        Ok(vec![match self.format {
+            OutputFormat::Text => ParserOutput::PayloadFile(f),
+            OutputFormat::Json => ParserOutput::PayloadReformatFile(f, format_json_pretty),
+            OutputFormat::Html => ParserOutput::PayloadReformatFile(f, generate_html_output),
+        }])
+    }
+}
+
+impl ParserConfigFile {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read parser config {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Couldn't parse parser config {}", path.display()))
+    }
+
+    /// The parsers, for `ParseConfig::custom_parsers`
+    pub fn parsers(&self) -> anyhow::Result<Vec<Arc<dyn StructuredLogParser + Send + Sync>>> {
+        self.parsers
+            .iter()
+            .map(|definition| {
+                Ok(Arc::new(DeclarativeParser::new(definition)?)
+                    as Arc<dyn StructuredLogParser + Send + Sync>)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,46 @@
+//! State shared by the parsers of one log, one value per type.  A parser that records something
+//! for another to use (the vLLM parsers building up their engines, a guards parser telling a
+//! recompile analysis what it saw) asks for the state by type and gets the same value as every
+//! other parser of the log, created on first use.
+//!
+//! Every `parse_path` call has a registry of its own, so parsers shared by the threads parsing
+//! ranks in parallel keep each rank's state apart.  Values must be `Send + Sync`: use a `Mutex`
+//! or atomics for what changes.
+
+use fxhash::FxHashMap;
+use std::any::{Any, TypeId};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+pub struct ParserState {
+    values: Mutex<FxHashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl ParserState {
+    /// The state of type `T`, created with its `Default` by the first parser to ask
+    pub fn get<T: Default + Send + Sync + 'static>(&self) -> Arc<T> {
+        let mut values = self.values.lock().unwrap();
+        let value = values
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(T::default()));
+        value.clone().downcast().unwrap()
+    }
+
+    /// The state of type `T` if some parser created it
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let values = self.values.lock().unwrap();
+        values
+            .get(&TypeId::of::<T>())
+            .map(|value| value.clone().downcast().unwrap())
+    }
+
+    /// Replace the state of type `T`, e.g. to start from something other than its `Default`
+    pub fn insert<T: Send + Sync + 'static>(&self, value: T) -> Arc<T> {
+        let value = Arc::new(value);
+        self.values
+            .lock()
+            .unwrap()
+            .insert(TypeId::of::<T>(), value.clone());
+        value
+    }
+}
@@ -1,6 +1,11 @@
-use crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT;
+use crate::parser_state::ParserState;
+use crate::templates::{
+    LINE_PERMALINK_CSS, LINE_PERMALINK_JAVASCRIPT, TEMPLATE_QUERY_PARAM_SCRIPT,
+};
 use crate::{types::*, ParseConfig};
 use html_escape::encode_text;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::ffi::{OsStr, OsString};
@@ -10,7 +15,7 @@ use tinytemplate::TinyTemplate;
 
 use serde_json::Value;
 
-fn format_json_pretty(payload: &str) -> Result<String, anyhow::Error> {
+pub(crate) fn format_json_pretty(payload: &str) -> Result<String, anyhow::Error> {
     match serde_json::from_str::<Value>(payload) {
         Ok(value) => Ok(serde_json::to_string_pretty(&value)?),
         Err(_) => {
@@ -20,8 +25,11 @@ fn format_json_pretty(payload: &str) -> Result<String, anyhow::Error> {
     }
 }
 
-use syntect::highlighting::ThemeSet;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, ThemeSet};
+use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
 use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
 
 // Re-export types from types.rs for external use
 pub use crate::types::{CompileId, EmptyMetadata, Envelope, GraphRuntime, Metadata, OpRuntime};
@@ -52,12 +60,28 @@ pub trait StructuredLogParser {
     // Take a log input and the metadata you asked for, return a set of files to write
     fn parse<'e>(
         &self,
-        lineno: usize,                  // Line number from log
-        metadata: Metadata<'e>,         // Metadata from get_metadata
-        rank: Option<u32>,              // Rank of the log
-        compile_id: &Option<CompileId>, // Compile ID of the envelope
-        payload: &str,                  // Payload from the log (empty string when None)
-    ) -> anyhow::Result<ParserResults>;
+        _lineno: usize,                  // Line number from log
+        _metadata: Metadata<'e>,         // Metadata from get_metadata
+        _rank: Option<u32>,              // Rank of the log
+        _compile_id: &Option<CompileId>, // Compile ID of the envelope
+        _payload: &str,                  // Payload from the log (empty string when None)
+    ) -> anyhow::Result<ParserResults> {
+        anyhow::bail!("{} only parses with the state of a log", self.name())
+    }
+
+    // Like parse, with the state shared by all parsers of the log (see `ParserState`).  Implement
+    // this instead of parse to record state for other parsers or read theirs; tlparse calls it.
+    fn parse_with_state<'e>(
+        &self,
+        _state: &ParserState,
+        lineno: usize,
+        metadata: Metadata<'e>,
+        rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        self.parse(lineno, metadata, rank, compile_id, payload)
+    }
 
     // Name of the parser, for error logging
     fn name(&self) -> &'static str;
@@ -70,7 +94,7 @@ pub fn build_file_path(filename: &str, lineno: usize, compile_id: &Option<Compil
         .map_or(format!("unknown_{lineno}"), |cid| cid.as_directory_name())
         .into();
     let subdir = PathBuf::from(compile_id_dir);
-    subdir.join(filename)
+    subdir.join(crate::paths::sanitize_filename(filename))
 }
 
 // Takes a filename and a payload and writes that payload into a the file
@@ -227,7 +251,7 @@ impl StructuredLogParser for DynamoGuardParser<'_> {
             guards,
             qps: TEMPLATE_QUERY_PARAM_SCRIPT,
         };
-        let output = self.tt.render(&filename, &guards_context)?;
+        let output = crate::report_model::render(self.tt, &filename, &guards_context);
         simple_file_output(&filename, lineno, compile_id, &output)
     }
 }
@@ -310,17 +334,142 @@ impl StructuredLogParser for InductorOutputCodeParser {
     }
 }
 
-fn generate_html_output(payload: &str) -> Result<String, anyhow::Error> {
+/// `name = async_compile.triton('name', '''`: the definition of a kernel, whose source
+/// follows up to the line starting with `'''`
+static KERNEL_DEFINITION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\w+) = async_compile\.(\w+)\(.*'''\s*$").unwrap());
+/// `name.run(` (Triton) or `name(` (C++): a possible launch of kernel `name`
+static KERNEL_CALL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(\w+)(?:\.run)?\(").unwrap());
+
+/// The kernels an output code payload defines, anchored by `generate_html_output`, in order
+pub(crate) fn output_code_kernels(code: &str) -> Vec<String> {
+    code.lines()
+        .filter_map(|line| KERNEL_DEFINITION.captures(line))
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+/// A kernel of an output code page: its name, the line defining it and the lines launching it
+struct OutputCodeKernel {
+    name: String,
+    line: usize,
+    launches: Vec<usize>,
+}
+
+/// Python code highlighted as HTML with numbered lines.  The sources of the kernels Inductor
+/// generated are highlighted as code (Triton as Python, C++ as C++) rather than as the
+/// strings they are, each kernel definition gets a `kernel-<name>` anchor, and an index at the
+/// top links to each kernel and the lines launching it.
+pub(crate) fn generate_html_output(payload: &str) -> Result<String, anyhow::Error> {
     let syntax_set = SyntaxSet::load_defaults_newlines();
     let theme_set = ThemeSet::load_defaults();
     let syntax = syntax_set.find_syntax_by_extension("py").unwrap();
-    let html = syntect::html::highlighted_html_for_string(
-        &payload,
-        &syntax_set,
-        &syntax,
-        &theme_set.themes["InspiredGitHub"],
+    let cpp_syntax = syntax_set.find_syntax_by_extension("cpp").unwrap_or(syntax);
+    let theme = &theme_set.themes["InspiredGitHub"];
+    let background = theme.settings.background.unwrap_or(Color::WHITE);
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    // The highlighter of the source of the kernel being defined
+    let mut kernel_highlighter: Option<HighlightLines> = None;
+    let mut kernels: Vec<OutputCodeKernel> = Vec::new();
+    let mut lines = Vec::new();
+    for (i, line) in LinesWithEndings::from(payload).enumerate() {
+        if kernel_highlighter.is_some() && line.starts_with("'''") {
+            kernel_highlighter = None;
+        }
+        let regions = match &mut kernel_highlighter {
+            Some(kernel_highlighter) => kernel_highlighter.highlight_line(line, &syntax_set)?,
+            None => highlighter.highlight_line(line, &syntax_set)?,
+        };
+        let mut html =
+            styled_line_to_highlighted_html(&regions, IncludeBackground::IfDifferent(background))?;
+        if kernel_highlighter.is_none() {
+            if let Some(caps) = KERNEL_DEFINITION.captures(line) {
+                let kernel_syntax = match &caps[2] {
+                    "cpp" | "cpp_pybinding" => cpp_syntax,
+                    _ => syntax,
+                };
+                kernel_highlighter = Some(HighlightLines::new(kernel_syntax, theme));
+                html.insert_str(
+                    0,
+                    &format!(r#"<a class="kernel-anchor" id="kernel-{}"></a>"#, &caps[1]),
+                );
+                kernels.push(OutputCodeKernel {
+                    name: caps[1].to_string(),
+                    line: i + 1,
+                    launches: Vec::new(),
+                });
+            } else {
+                for caps in KERNEL_CALL.captures_iter(line) {
+                    if let Some(kernel) = kernels.iter_mut().find(|k| k.name == caps[1]) {
+                        kernel.launches.push(i + 1);
+                    }
+                }
+            }
+        }
+        lines.push(html);
+    }
+    Ok(kernel_index(&kernels)
+        + &anchored_lines(
+            &format!(
+                "background-color:#{:02x}{:02x}{:02x};",
+                background.r, background.g, background.b
+            ),
+            lines,
+        ))
+}
+
+/// The kernel links at the top of an output code page, without line breaks so that the
+/// lines of the page stay those of the code
+fn kernel_index(kernels: &[OutputCodeKernel]) -> String {
+    if kernels.is_empty() {
+        return String::new();
+    }
+    let mut html = format!(
+        r#"<details class="kernel-index" open><summary>{} {}</summary><ul>"#,
+        kernels.len(),
+        if kernels.len() == 1 {
+            "kernel"
+        } else {
+            "kernels"
+        }
+    );
+    for kernel in kernels {
+        let launches: Vec<String> = kernel
+            .launches
+            .iter()
+            .map(|n| format!("<a href='#L{n}'>{n}</a>"))
+            .collect();
+        let launched = if launches.is_empty() {
+            String::new()
+        } else {
+            format!(", launched on line {}", launches.join(", "))
+        };
+        html.push_str(&format!(
+            "<li><a href='#kernel-{name}'>{name}</a> (line <a href='#L{line}'>{line}</a>{launched})</li>",
+            name = kernel.name,
+            line = kernel.line,
+        ));
+    }
+    html.push_str("</ul></details>");
+    html
+}
+
+/// A `<pre>` of already escaped lines, each with an `L<n>` anchor and a line number linking
+/// to it; clicking the number copies a permalink to the line
+fn anchored_lines(style: &str, lines: impl IntoIterator<Item = String>) -> String {
+    let mut html = format!(
+        "<style>{LINE_PERMALINK_CSS}</style>\n<pre class=\"anchored-lines\" style=\"{style}\">"
     );
-    Ok(html?)
+    for (i, line) in lines.into_iter().enumerate() {
+        let n = i + 1;
+        html.push_str(&format!(
+            r#"<span class="line" id="L{n}"><a class="lineno" href='#L{n}' data-line="{n}"></a>{line}</span>"#
+        ));
+    }
+    html.push_str(&format!(
+        "</pre>\n<script>{LINE_PERMALINK_JAVASCRIPT}</script>\n"
+    ));
+    html
 }
 
 pub struct OptimizeDdpSplitChildParser;
@@ -379,10 +528,10 @@ impl StructuredLogParser for LinkParser {
     }
 }
 
-fn format_stack(stack: &StackSummary, caption: &str, open: bool) -> String {
+pub(crate) fn format_stack(stack: &StackSummary, caption: &str, open: bool) -> String {
     let mut trie = StackTrieNode::default();
     trie.insert_no_terminal(stack.to_vec());
-    trie.fmt(None, caption, open).unwrap()
+    trie.fmt(None, None, caption, open).unwrap()
 }
 
 pub struct CompilationMetricsParser<'t> {
@@ -392,8 +541,13 @@ pub struct CompilationMetricsParser<'t> {
     pub guard_added_fast_index: &'t RefCell<GuardAddedFastIndex>,
     pub create_symbol_index: &'t RefCell<CreateSymbolIndex>,
     pub unbacked_symbol_index: &'t RefCell<UnbackedSymbolIndex>,
+    pub compile_warning_index: &'t RefCell<CompileWarningIndex>,
     pub output_files: &'t Vec<OutputFile>,
     pub compile_id_dir: &'t PathBuf,
+    pub memory: Option<MemoryUsageRow>,
+    pub explanation: String,
+    pub guard_diff: Option<GuardDiff>,
+    pub config_docs: &'t crate::config_docs::ConfigDocs,
 }
 impl StructuredLogParser for CompilationMetricsParser<'_> {
     fn name(&self) -> &'static str {
@@ -414,9 +568,7 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
     ) -> anyhow::Result<ParserResults> {
         let filename = format!("{}.html", self.name());
         if let Metadata::CompilationMetrics(m) = metrics {
-            let id = compile_id
-                .clone()
-                .map_or("(unknown) ".to_string(), |c| format!("{cid} ", cid = c));
+            let id = format!("{} ", crate::compile_labels::display(compile_id));
             let mut cid = compile_id.clone();
             if let Some(c) = cid.as_mut() {
                 if let Some(_frame_id) = c.frame_compile_id {
@@ -532,6 +684,11 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
                     ),
                 })
                 .collect();
+            let warnings = self
+                .compile_warning_index
+                .borrow_mut()
+                .remove(&cid)
+                .unwrap_or_default();
             let remove_prefix = |x: &String| -> String {
                 // url is X_Y_Z/<rest>. Get the rest of the string for the link
                 // on compilation metrics page
@@ -548,6 +705,9 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
                     number: o.number.clone(),
                     suffix: o.suffix.clone(),
                     readable_url: o.readable_url.as_ref().map(|u| remove_prefix(u)),
+                    compare_url: None,
+                    size: o.size.clone(),
+                    size_heat: o.size_heat,
                 })
                 .collect();
             let context = CompilationMetricsContext {
@@ -562,9 +722,15 @@ impl StructuredLogParser for CompilationMetricsParser<'_> {
                 unbacked_symbols: unbacked_symbols,
                 output_files: &output_files,
                 compile_id_dir: &self.compile_id_dir,
+                memory: self.memory.clone(),
+                warnings,
+                explanation: self.explanation.clone(),
+                guard_diff: self.guard_diff.clone(),
+                metric_groups: crate::metrics_catalog::metric_groups(m, self.config_docs),
+                raw_metrics: crate::metrics_catalog::raw_json(m),
                 qps: TEMPLATE_QUERY_PARAM_SCRIPT,
             };
-            let output = self.tt.render(&filename, &context)?;
+            let output = crate::report_model::render(self.tt, &filename, &context);
             simple_file_output(&filename, lineno, compile_id, &output)
         } else {
             Err(anyhow::anyhow!("Expected CompilationMetrics metadata"))
@@ -594,16 +760,14 @@ impl StructuredLogParser for AOTAutogradBackwardCompilationMetricsParser<'_> {
     ) -> anyhow::Result<ParserResults> {
         let filename = format!("{}.html", self.name());
         if let Metadata::AOTAutogradBackwardCompilationMetrics(m) = metrics {
-            let id = compile_id
-                .clone()
-                .map_or("(unknown) ".to_string(), |c| format!("{cid} ", cid = c));
+            let id = format!("{} ", crate::compile_labels::display(compile_id));
             let context = AOTAutogradBackwardCompilationMetricsContext {
                 css: crate::CSS,
                 m: &m,
                 compile_id: id,
                 qps: TEMPLATE_QUERY_PARAM_SCRIPT,
             };
-            let output = self.tt.render(&filename, &context)?;
+            let output = crate::report_model::render(self.tt, &filename, &context);
             simple_file_output(&filename, lineno, compile_id, &output)
         } else {
             Err(anyhow::anyhow!(
@@ -635,16 +799,14 @@ impl StructuredLogParser for BwdCompilationMetricsParser<'_> {
     ) -> anyhow::Result<ParserResults> {
         let filename = format!("{}.html", self.name());
         if let Metadata::BwdCompilationMetrics(m) = metrics {
-            let id = compile_id
-                .clone()
-                .map_or("(unknown) ".to_string(), |c| format!("{cid} ", cid = c));
+            let id = format!("{} ", crate::compile_labels::display(compile_id));
             let context = BwdCompilationMetricsContext {
                 css: crate::CSS,
                 m: &m,
                 compile_id: id,
                 qps: TEMPLATE_QUERY_PARAM_SCRIPT,
             };
-            let output = self.tt.render(&filename, &context)?;
+            let output = crate::report_model::render(self.tt, &filename, &context);
             simple_file_output(&filename, lineno, compile_id, &output)
         } else {
             Err(anyhow::anyhow!("Expected BwdCompilationMetrics metadata"))
@@ -676,7 +838,7 @@ impl StructuredLogParser for DumpFileParser {
                 format!("{}.html", metadata.name)
             };
             let subdir = PathBuf::from("dump_file");
-            let f = subdir.join(filename);
+            let f = subdir.join(crate::paths::sanitize_filename(&filename));
             Ok(Vec::from([ParserOutput::GlobalFile(
                 f,
                 anchor_source(payload),
@@ -688,51 +850,18 @@ impl StructuredLogParser for DumpFileParser {
 }
 
 pub fn anchor_source(text: &str) -> String {
-    let lines: Vec<&str> = text.lines().collect();
-    let mut html = String::from(
+    let lines = anchored_lines("", text.lines().map(|line| encode_text(line).into_owned()));
+    format!(
         r#"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>Source Code</title>
-    <style>
-        pre {
-            counter-reset: line;
-        }
-        pre span {
-            display: block;
-        }
-        pre span:before {
-            counter-increment: line;
-            content: counter(line);
-            display: inline-block;
-            padding: 0 .5em;
-            margin-right: .5em;
-            color: #888;
-        }
-        pre span:target {
-            background-color: #ffff00;
-        }
-    </style>
 </head>
 <body>
-    <pre>"#,
-    );
-
-    for (i, line) in lines.iter().enumerate() {
-        let line_number = i + 1;
-        html.push_str(&format!(
-            r#"<span id="L{}">{}</span>"#,
-            line_number,
-            encode_text(line)
-        ));
-    }
-
-    html.push_str(&format!(
-        "</pre>{TEMPLATE_QUERY_PARAM_SCRIPT}</body></html>"
-    ));
-    html
+{lines}{TEMPLATE_QUERY_PARAM_SCRIPT}</body></html>"#
+    )
 }
 
 pub fn read_runtime_estimations(
@@ -793,7 +922,10 @@ pub fn read_collective_schedules(
         rank_nums,
         "inductor_collective_schedule",
         |content, rank, graph| {
-            let ops: Vec<String> = serde_json::from_str(content)?;
+            let ops: Vec<String> = crate::collectives::parse_schedule(content)?
+                .into_iter()
+                .map(|op| op.target)
+                .collect();
             Ok((!ops.is_empty()).then(|| CollectiveSchedule { rank, graph, ops }))
         },
     )
@@ -846,13 +978,12 @@ pub fn check_collectives_parity(out_path: &PathBuf, rank_nums: &[u32]) -> anyhow
             graphs: Vec::new(),
         };
 
-        for compile_dir in fs::read_dir(&rank_dir)?
-            .flatten()
-            .map(|e| e.path())
+        for compile_dir in crate::paths::sorted_dir(&rank_dir)?
+            .into_iter()
             .filter(|p| p.is_dir())
         {
             let (mut schedule_path, mut code_path) = (None, None);
-            for p in fs::read_dir(&compile_dir)?.flatten().map(|e| e.path()) {
+            for p in crate::paths::sorted_dir(&compile_dir)? {
                 let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("");
                 if p.extension() == Some(OsStr::new("json"))
                     && stem.starts_with("inductor_collective_schedule")
@@ -979,23 +1110,22 @@ fn read_artifacts<T>(
             continue;
         }
 
-        for entry in fs::read_dir(&rank_dir)?
-            .flatten()
-            .filter(|e| e.path().is_dir())
+        for compile_dir in crate::paths::sorted_dir(&rank_dir)?
+            .into_iter()
+            .filter(|p| p.is_dir())
         {
-            let compile_dir = entry.path();
-
-            let file = fs::read_dir(&compile_dir)?.flatten().find(|e| {
-                let path = e.path();
-                path.extension() == Some(OsStr::new("json"))
-                    && path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .map_or(false, |s| s.starts_with(file_prefix))
-            });
+            let file = crate::paths::sorted_dir(&compile_dir)?
+                .into_iter()
+                .find(|path| {
+                    path.extension() == Some(OsStr::new("json"))
+                        && path
+                            .file_stem()
+                            .and_then(|s| s.to_str())
+                            .map_or(false, |s| s.starts_with(file_prefix))
+                });
 
             if let Some(file) = file {
-                let content = fs::read_to_string(file.path())
+                let content = fs::read_to_string(&file)
                     .with_context(|| format!("Reading {file_prefix} for rank {rank}"))?;
 
                 let graph = compile_dir
@@ -1204,7 +1334,7 @@ impl StructuredLogParser for PropagateRealTensorsParser<'_> {
                 sym_expr_trie_html: sym_expr_trie_html,
                 locals_html: locals_html,
             };
-            let output = self.tt.render(&filename, &context)?;
+            let output = crate::report_model::render(self.tt, &filename, &context);
             simple_file_output(&filename, lineno, compile_id, &output)
         } else {
             Err(anyhow::anyhow!(
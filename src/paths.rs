@@ -0,0 +1,143 @@
+//! Output paths that work on every platform.  Artifact names come straight from the log and
+//! can contain characters Windows rejects in filenames (`<>:"/\|?*`), reserved device names
+//! (`CON`, `NUL`, `COM1`, ...) or trailing dots, so every generated name is sanitized the same
+//! way on all platforms; reports stay portable between machines.
+
+use anyhow::Context;
+use md5::{Digest, Md5};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::types::ParseOutput;
+
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Longest filename we generate, in bytes; most filesystems allow 255
+const MAX_FILENAME_BYTES: usize = 200;
+
+/// Make `name` a valid filename on Windows and Unix: invalid characters become `_`, reserved
+/// device names get a `_` prefix and overlong names are shortened with a hash of the original
+pub fn sanitize_filename(name: &str) -> String {
+    let mut s: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows drops trailing dots and spaces, which would merge distinct names
+    if s.ends_with(['.', ' ']) {
+        s = format!("{}_", s.trim_end_matches(['.', ' ']));
+    }
+    let stem = s.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        s.insert(0, '_');
+    }
+    if s.len() > MAX_FILENAME_BYTES {
+        let hash = format!("{:x}", Md5::digest(name.as_bytes()));
+        let ext = Path::new(&s)
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .filter(|e| e.len() < 16)
+            .unwrap_or_default();
+        let mut end = MAX_FILENAME_BYTES - ext.len() - 9;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s = format!("{}_{}{ext}", &s[..end], &hash[..8]);
+    }
+    if s.is_empty() {
+        s.push('_');
+    }
+    s
+}
+
+/// Sanitize every component of a relative output path; roots and `..` are dropped so the
+/// path stays inside the output directory
+pub fn sanitize_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(sanitize_filename(&name.to_string_lossy())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A relative output path as a link target, `/` separated on every platform
+pub fn url(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// `path` in a form not limited to MAX_PATH (260 characters) on Windows
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    let s = absolute.to_string_lossy();
+    if s.len() < 260 || s.starts_with(r"\\?\") {
+        return absolute;
+    }
+    match s.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!(r"\\?\UNC\{unc}")),
+        None => PathBuf::from(format!(r"\\?\{s}")),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// An absolute path a browser can open; Windows verbatim paths (`\\?\C:\...`, as returned by
+/// `canonicalize`) are turned back into plain ones
+pub fn browser_path(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let s = absolute.to_string_lossy();
+    if let Some(unc) = s.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{unc}"))
+    } else if let Some(plain) = s.strip_prefix(r"\\?\") {
+        PathBuf::from(plain)
+    } else {
+        absolute
+    }
+}
+
+/// Write `contents` to a temporary file next to `path` and rename it into place, so readers
+/// (a browser, `--serve`, a concurrent run) never see a partially written file
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{name}.{}.tmp", std::process::id()));
+    fs::write(&tmp, contents).with_context(|| format!("Writing {}", tmp.display()))?;
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("Writing {}", path.display()));
+    }
+    Ok(())
+}
+
+/// The entries of `dir` sorted by name.  `read_dir` order depends on the filesystem, and
+/// anything built from it (page rows, JSON arrays, which of several matching files is picked)
+/// has to come out the same on every run for reports to diff cleanly.
+pub fn sorted_dir(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?.flatten().map(|e| e.path()).collect();
+    entries.sort();
+    Ok(entries)
+}
+
+/// Write parse output under `out_dir`, creating directories as needed
+pub fn write_output(out_dir: &Path, output: ParseOutput) -> anyhow::Result<()> {
+    for (filename, content) in output {
+        let path = long_path(&out_dir.join(&filename));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Creating {}", dir.display()))?;
+        }
+        write_atomic(&path, content)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Hover cards on artifact links: the first lines of each artifact written from a payload go to
+//! `previews.js`, which the index, directory, compilation metrics and vLLM summary pages load
+//! the first time a link is hovered, so that an artifact can be peeked at without opening it.
+//!
+//! The previews are a script rather than JSON so that they load from `file://` reports too.
+//! They hold at most `MAX_PREVIEW_BYTES` of text; artifacts beyond that get no card.
+
+use indexmap::IndexMap;
+use std::path::Path;
+
+use crate::types::{OutputFile, ParseOutput};
+
+pub(crate) const PREVIEWS_FILENAME: &str = "previews.js";
+/// Placeholder in the pages with artifact links, replaced with the hover script
+pub(crate) const PREVIEWS_MARKER: &str = "<!-- tlparse:previews -->";
+const PREVIEW_LINES: usize = 20;
+/// Longer lines (serialized tensors, giant lists) are cut to this many characters
+const MAX_LINE_CHARS: usize = 200;
+const MAX_PREVIEW_BYTES: usize = 16 << 20;
+/// Larger JSON payloads are previewed as they are rather than pretty-printed
+const MAX_PRETTY_BYTES: usize = 1 << 20;
+
+#[derive(Default)]
+pub(crate) struct PreviewIndex {
+    /// URL of the artifact, relative to the report root -> its first lines and its line count
+    previews: IndexMap<String, (String, usize)>,
+    bytes: usize,
+}
+
+impl PreviewIndex {
+    pub fn is_empty(&self) -> bool {
+        self.previews.is_empty()
+    }
+
+    /// The payload `files` were just written from, previewed on the first of them
+    pub fn add(&mut self, files: &[OutputFile], payload: &str) {
+        let Some(file) = files.first() else {
+            return;
+        };
+        if payload.trim().is_empty() || self.bytes >= MAX_PREVIEW_BYTES {
+            return;
+        }
+        // JSON payloads often come on one line
+        let is_json = payload.trim_start().starts_with(['[', '{']);
+        let pretty = (is_json && payload.len() <= MAX_PRETTY_BYTES)
+            .then(|| serde_json::from_str::<serde_json::Value>(payload).ok())
+            .flatten()
+            .and_then(|value| serde_json::to_string_pretty(&value).ok());
+        let text = pretty.as_deref().unwrap_or(payload);
+        let lines: Vec<String> = text
+            .lines()
+            .take(PREVIEW_LINES)
+            .map(|line| match line.char_indices().nth(MAX_LINE_CHARS) {
+                Some((end, _)) => format!("{}…", &line[..end]),
+                None => line.to_string(),
+            })
+            .collect();
+        let preview = lines.join("\n");
+        self.bytes += preview.len();
+        self.previews
+            .insert(file.url.clone(), (preview, text.lines().count()));
+    }
+
+    /// `previews.js`
+    pub fn js(&self) -> anyhow::Result<String> {
+        Ok(format!(
+            "tlparsePreviewsLoaded({});\n",
+            serde_json::to_string(&self.previews)?
+        ))
+    }
+}
+
+/// The hover script for the page at `page` (relative to the report root): the previews are
+/// loaded relative to where its links resolve
+fn preview_script(html: &str, page: &Path) -> String {
+    let depth = crate::links::link_dir(html, page).components().count();
+    format!(
+        "<script data-previews=\"{}{PREVIEWS_FILENAME}\">{}</script>",
+        "../".repeat(depth),
+        crate::templates::PREVIEW_JAVASCRIPT
+    )
+}
+
+/// Fill in the previews placeholder of every page: the hover script if there are previews,
+/// otherwise nothing
+pub(crate) fn add_preview_scripts(output: &mut ParseOutput, previews: &PreviewIndex) {
+    for (path, html) in output.iter_mut() {
+        if !html.contains(PREVIEWS_MARKER) {
+            continue;
+        }
+        let script = if previews.is_empty() {
+            String::new()
+        } else {
+            preview_script(html, path)
+        };
+        crate::report_model::substitute(html, PREVIEWS_MARKER, &script);
+    }
+}
@@ -0,0 +1,235 @@
+//! Pages of the multi-rank report showing one artifact of one compile from every rank side by
+//! side (`across_ranks/-_3_0_0/dynamo_cpp_guards_str.html`), with ranks grouped by identical
+//! content, so finding the rank that diverged doesn't mean opening every rank's report.
+
+use anyhow::Context;
+use indexmap::IndexMap;
+use md5::{Digest, Md5};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::manifest::artifact_name_kind;
+use crate::templates::{CSS, TEMPLATE_QUERY_PARAM_SCRIPT};
+use crate::types::{RankArtifactContext, RankArtifactLink, RankArtifactRow, RankArtifactVariant};
+
+/// Directory of the pages, relative to the multi-rank output directory
+pub const RANK_ARTIFACTS_DIR: &str = "across_ranks";
+
+/// Larger text artifacts are linked rather than shown inline
+const MAX_INLINE_BYTES: usize = 256 * 1024;
+
+/// An entry of a rank's `compile_directory.json`
+#[derive(Deserialize)]
+pub(crate) struct CompileDirEntry {
+    pub artifacts: Vec<DirArtifact>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DirArtifact {
+    pub name: String,
+    pub url: String,
+    pub number: u64,
+}
+
+/// `0, 1, 2, 3, 5` -> `0-3, 5`
+pub(crate) fn rank_ranges(ranks: &[u32]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < ranks.len() {
+        let mut j = i;
+        while j + 1 < ranks.len() && ranks[j + 1] == ranks[j] + 1 {
+            j += 1;
+        }
+        ranges.push(if j > i + 1 {
+            format!("{}-{}", ranks[i], ranks[j])
+        } else if j == i + 1 {
+            format!("{}, {}", ranks[i], ranks[j])
+        } else {
+            ranks[i].to_string()
+        });
+        i = j + 1;
+    }
+    ranges.join(", ")
+}
+
+/// Numbers in a compile id, so `[0/10]` sorts after `[0/2]`
+pub(crate) fn compile_sort_key(compile_id: &str) -> Vec<u64> {
+    compile_id
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|n| n.parse().ok())
+        .collect()
+}
+
+/// Ranks that logged the same content, with the first one's files
+struct Variant<'a> {
+    ranks: Vec<u32>,
+    files: &'a [String],
+    content: String,
+}
+
+/// The artifacts of one (compile, kind) on one rank
+struct RankFiles {
+    rank: u32,
+    /// Relative to the multi-rank output directory, ordered as logged
+    files: Vec<String>,
+}
+
+/// Write a page per (compile, artifact kind) logged by more than one rank and return the
+/// landing page's table of them
+pub fn write_rank_artifact_pages(
+    out_path: &Path,
+    rank_nums: &[u32],
+    base_url: Option<&str>,
+) -> anyhow::Result<Vec<RankArtifactRow>> {
+    if rank_nums.len() < 2 {
+        return Ok(Vec::new());
+    }
+    // (compile id, compile directory) -> kind -> files per rank
+    let mut compiles: IndexMap<(String, String), IndexMap<String, Vec<RankFiles>>> =
+        IndexMap::new();
+    for &rank in rank_nums {
+        let path = out_path.join(format!("rank_{rank}/compile_directory.json"));
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let directory: IndexMap<String, CompileDirEntry> = serde_json::from_str(&contents)
+            .with_context(|| format!("Reading {}", path.display()))?;
+        for (compile_id, entry) in directory {
+            if compile_id == "unknown" || compile_id.starts_with("unknown_") {
+                continue;
+            }
+            let mut artifacts = entry.artifacts;
+            artifacts.sort_by_key(|a| a.number);
+            for artifact in artifacts {
+                let Some((dir, _)) = artifact.url.split_once('/') else {
+                    continue;
+                };
+                let per_rank = compiles
+                    .entry((compile_id.clone(), dir.to_string()))
+                    .or_default()
+                    .entry(artifact_name_kind(&artifact.name))
+                    .or_default();
+                let url = format!("rank_{rank}/{}", artifact.url);
+                match per_rank.last_mut() {
+                    Some(last) if last.rank == rank => last.files.push(url),
+                    _ => per_rank.push(RankFiles {
+                        rank,
+                        files: vec![url],
+                    }),
+                }
+            }
+        }
+    }
+    compiles.sort_by(|(a, _), _, (b, _), _| compile_sort_key(a).cmp(&compile_sort_key(b)));
+
+    let tt = crate::report_model::templates(false)?;
+    let mut rows = Vec::new();
+    for ((compile_id, dir), kinds) in compiles {
+        let mut artifacts = Vec::new();
+        for (kind, per_rank) in kinds {
+            if per_rank.len() < 2 {
+                continue;
+            }
+            let page = format!("{RANK_ARTIFACTS_DIR}/{dir}/{kind}.html");
+            let context = rank_artifact_context(out_path, rank_nums, &compile_id, &kind, &per_rank);
+            let mut notes = Vec::new();
+            if context.num_variants > 1 {
+                notes.push(format!("{} variants", context.num_variants));
+            }
+            if per_rank.len() < rank_nums.len() {
+                notes.push(format!(
+                    "on {} of {} ranks",
+                    per_rank.len(),
+                    rank_nums.len()
+                ));
+            }
+            let html = tt.render("rank_artifact.html", &context)?;
+            let html = match base_url {
+                Some(base_url) => crate::links::rewrite_links(&html, Path::new(&page), base_url),
+                None => html,
+            };
+            let path = out_path.join(&page);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Creating {}", parent.display()))?;
+            }
+            crate::paths::write_atomic(&path, html)?;
+            artifacts.push(RankArtifactLink {
+                kind,
+                url: page,
+                differs: !notes.is_empty(),
+                note: notes.join(", "),
+            });
+        }
+        if !artifacts.is_empty() {
+            rows.push(RankArtifactRow {
+                compile_id,
+                artifacts,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+fn rank_artifact_context(
+    out_path: &Path,
+    rank_nums: &[u32],
+    compile_id: &str,
+    kind: &str,
+    per_rank: &[RankFiles],
+) -> RankArtifactContext {
+    // By content hash
+    let mut variants: IndexMap<Vec<u8>, Variant> = IndexMap::new();
+    for rank_files in per_rank {
+        let content: String = rank_files
+            .files
+            .iter()
+            .map(|f| fs::read_to_string(out_path.join(f)).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let hash = Md5::digest(content.as_bytes()).to_vec();
+        variants
+            .entry(hash)
+            .or_insert_with(|| Variant {
+                ranks: Vec::new(),
+                files: &rank_files.files,
+                content,
+            })
+            .ranks
+            .push(rank_files.rank);
+    }
+    // The most common version first
+    variants.sort_by(|_, a, _, b| b.ranks.len().cmp(&a.ranks.len()));
+    let present: Vec<u32> = per_rank.iter().map(|r| r.rank).collect();
+    let missing: Vec<u32> = rank_nums
+        .iter()
+        .copied()
+        .filter(|r| !present.contains(r))
+        .collect();
+    // Pages are two directories below the output directory
+    let link = |file: &String| (format!("../../{file}"), file.clone());
+    RankArtifactContext {
+        css: CSS,
+        qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+        compile_id: compile_id.to_string(),
+        kind: kind.to_string(),
+        num_ranks: present.len(),
+        num_variants: variants.len(),
+        missing_ranks: rank_ranges(&missing),
+        variants: variants
+            .into_values()
+            .map(|variant| {
+                let is_page = variant.files[0].ends_with(".html");
+                RankArtifactVariant {
+                    ranks: rank_ranges(&variant.ranks),
+                    num_ranks: variant.ranks.len(),
+                    files: variant.files.iter().map(link).collect(),
+                    is_page,
+                    content: (!is_page && variant.content.len() <= MAX_INLINE_BYTES)
+                        .then_some(variant.content),
+                }
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,222 @@
+//! The compile divergence matrix of the multi-rank landing page: for every compile id, whether
+//! each rank compiled it at all, and with the same Dynamo graph (`dynamo_output_graph`) and
+//! guards (`dynamo_cpp_guards_str`, or `dynamo_guards` for older logs) as the other ranks.
+//!
+//! Ranks that compiled the same graph and guards share a variant: `A` for the most common one of
+//! the compile, `B` for the next and so on.  Only compiles that differ somewhere get a row.
+
+use anyhow::Context;
+use indexmap::IndexMap;
+use md5::{Digest, Md5};
+use std::fs;
+use std::path::Path;
+
+use crate::manifest::artifact_name_kind;
+use crate::rank_artifacts::{compile_sort_key, rank_ranges, CompileDirEntry, RANK_ARTIFACTS_DIR};
+use crate::types::{CompileDivergence, CompileDivergenceCell, CompileDivergenceRow};
+
+const GRAPH_KIND: &str = "dynamo_output_graph";
+/// In order of preference
+const GUARD_KINDS: [&str; 2] = ["dynamo_cpp_guards_str", "dynamo_guards"];
+
+/// Hashes of what one rank compiled for one compile id
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Compiled {
+    graph: Option<Vec<u8>>,
+    guards: Option<Vec<u8>>,
+}
+
+/// The compile directory and files of each kind of one compile on one rank
+#[derive(Default)]
+struct RankCompile {
+    dir: String,
+    files: IndexMap<String, Vec<String>>,
+}
+
+/// Where the side by side pages of a compile's graph and guards are, if there are any
+struct ComparePages {
+    dir: String,
+    guard_kind: Option<&'static str>,
+    /// Ranks that logged a graph and guards; `rank_artifacts` writes pages for two or more
+    graph_ranks: usize,
+    guard_ranks: usize,
+}
+
+fn hash_files(rank_dir: &Path, files: &[String]) -> Vec<u8> {
+    let mut hasher = Md5::new();
+    for file in files {
+        hasher.update(fs::read(rank_dir.join(file)).unwrap_or_default());
+    }
+    hasher.finalize().to_vec()
+}
+
+fn variant_name(index: usize) -> String {
+    match u8::try_from(index).ok().filter(|i| *i < 26) {
+        Some(i) => char::from(b'A' + i).to_string(),
+        None => (index + 1).to_string(),
+    }
+}
+
+/// None for single-rank runs and runs without compiles
+pub(crate) fn compile_divergence(
+    out_path: &Path,
+    rank_nums: &[u32],
+) -> anyhow::Result<Option<CompileDivergence>> {
+    if rank_nums.len() < 2 {
+        return Ok(None);
+    }
+    // compile id -> rank -> what it compiled
+    let mut compiles: IndexMap<String, IndexMap<u32, Compiled>> = IndexMap::new();
+    let mut pages: IndexMap<String, ComparePages> = IndexMap::new();
+    for &rank in rank_nums {
+        let rank_dir = out_path.join(format!("rank_{rank}"));
+        let path = rank_dir.join("compile_directory.json");
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let directory: IndexMap<String, CompileDirEntry> = serde_json::from_str(&contents)
+            .with_context(|| format!("Reading {}", path.display()))?;
+        for (compile_id, entry) in directory {
+            if compile_id == "unknown" || compile_id.starts_with("unknown_") {
+                continue;
+            }
+            let mut compile = RankCompile::default();
+            let mut artifacts = entry.artifacts;
+            artifacts.sort_by_key(|a| a.number);
+            for artifact in artifacts {
+                if let Some((dir, _)) = artifact.url.split_once('/') {
+                    compile.dir = dir.to_string();
+                }
+                compile
+                    .files
+                    .entry(artifact_name_kind(&artifact.name))
+                    .or_default()
+                    .push(artifact.url);
+            }
+            let guard_kind = GUARD_KINDS
+                .into_iter()
+                .find(|kind| compile.files.contains_key(*kind));
+            let compiled = Compiled {
+                graph: compile
+                    .files
+                    .get(GRAPH_KIND)
+                    .map(|files| hash_files(&rank_dir, files)),
+                guards: guard_kind.map(|kind| hash_files(&rank_dir, &compile.files[kind])),
+            };
+            let compile_pages = pages
+                .entry(compile_id.clone())
+                .or_insert_with(|| ComparePages {
+                    dir: compile.dir.clone(),
+                    guard_kind: None,
+                    graph_ranks: 0,
+                    guard_ranks: 0,
+                });
+            compile_pages.guard_kind = compile_pages.guard_kind.or(guard_kind);
+            compile_pages.graph_ranks += usize::from(compiled.graph.is_some());
+            compile_pages.guard_ranks += usize::from(compiled.guards.is_some());
+            compiles
+                .entry(compile_id)
+                .or_default()
+                .insert(rank, compiled);
+        }
+    }
+    if compiles.is_empty() {
+        return Ok(None);
+    }
+    compiles.sort_by(|a, _, b, _| compile_sort_key(a).cmp(&compile_sort_key(b)));
+
+    let mut rows = Vec::new();
+    let mut divergent_ranks: Vec<u32> = Vec::new();
+    let num_compiles = compiles.len();
+    for (compile_id, per_rank) in compiles {
+        // Most common first, ties in rank order
+        let mut variants: IndexMap<&Compiled, usize> = IndexMap::new();
+        for compiled in per_rank.values() {
+            *variants.entry(compiled).or_default() += 1;
+        }
+        variants.sort_by(|_, a, _, b| b.cmp(a));
+        let missing = rank_nums.len() - per_rank.len();
+        if variants.len() < 2 && missing == 0 {
+            continue;
+        }
+
+        let distinct = |field: fn(&Compiled) -> &Option<Vec<u8>>| {
+            let mut seen: Vec<&Option<Vec<u8>>> = Vec::new();
+            for compiled in per_rank.values() {
+                if !seen.contains(&field(compiled)) {
+                    seen.push(field(compiled));
+                }
+            }
+            seen.len() > 1
+        };
+        let mut differs_in = Vec::new();
+        if distinct(|c| &c.graph) {
+            differs_in.push("graph".to_string());
+        }
+        if distinct(|c| &c.guards) {
+            differs_in.push("guards".to_string());
+        }
+        if missing > 0 {
+            differs_in.push(if missing == 1 {
+                "missing on 1 rank".to_string()
+            } else {
+                format!("missing on {missing} ranks")
+            });
+        }
+
+        let cells = rank_nums
+            .iter()
+            .map(|rank| match per_rank.get(rank) {
+                None => CompileDivergenceCell {
+                    variant: String::new(),
+                    class: "divergence-missing".to_string(),
+                },
+                Some(compiled) => {
+                    let index = variants.get_index_of(compiled).unwrap_or(0);
+                    if index > 0 && !divergent_ranks.contains(rank) {
+                        divergent_ranks.push(*rank);
+                    }
+                    CompileDivergenceCell {
+                        variant: variant_name(index),
+                        class: if index == 0 {
+                            "divergence-same"
+                        } else {
+                            "divergence-differs"
+                        }
+                        .to_string(),
+                    }
+                }
+            })
+            .collect();
+
+        let compile_pages = &pages[&compile_id];
+        let page = |kind: &str| format!("{RANK_ARTIFACTS_DIR}/{}/{kind}.html", compile_pages.dir);
+        rows.push(CompileDivergenceRow {
+            compile_id,
+            cells,
+            differs_in: differs_in.join(", "),
+            graph_url: (compile_pages.graph_ranks > 1).then(|| page(GRAPH_KIND)),
+            guards_url: compile_pages
+                .guard_kind
+                .filter(|_| compile_pages.guard_ranks > 1)
+                .map(page),
+        });
+    }
+    divergent_ranks.sort_unstable();
+
+    let summary = match rows.len() {
+        0 => format!("All {num_compiles} compiles have the same graph and guards on every rank."),
+        1 => format!("1 of {num_compiles} compiles differs between ranks."),
+        n => format!("{n} of {num_compiles} compiles differ between ranks."),
+    };
+    Ok(Some(CompileDivergence {
+        summary,
+        ranks: rank_nums.to_vec(),
+        rows,
+        divergent_ranks: match divergent_ranks.len() {
+            0 => String::new(),
+            1 => format!("Rank {}", divergent_ranks[0]),
+            _ => format!("Ranks {}", rank_ranges(&divergent_ranks)),
+        },
+    }))
+}
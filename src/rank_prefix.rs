@@ -0,0 +1,90 @@
+//! Logs with a rank prefix on every line (`[rank3]:[rank3]:V1014 ...`), as written by launchers
+//! that gather the output of all ranks into one file (torchrun's `--tee`, SLURM's `--label`
+//! with a wrapper, ...).
+//!
+//! The parser strips the prefix of each line and takes the rank of an envelope without one
+//! from it, so a prefixed single-rank log parses as if it wasn't prefixed.  A log holding
+//! several ranks is split into one log per rank for `--all-ranks-html`, which then reports on
+//! them like a directory of `dedicated_log_torch_trace_rank_N.log` files.
+
+use anyhow::Context;
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fs::{self, File};
+use std::io::{BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// `[rank3]:`, the space some launchers add after it is part of the prefix
+static RANK_PREFIX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[rank(\d+)\]: ?").unwrap());
+
+/// Subdirectory of an `--all-ranks-html` report the logs split from a prefixed log go to
+pub const SPLIT_LOGS_DIR: &str = "split_logs";
+
+/// Lines looked at to decide whether a log is prefixed
+const DETECTION_LINES: usize = 100;
+
+/// The rank of a prefixed line and the line without the prefix; `(None, line)` for other lines
+pub fn strip_rank_prefix(line: &str) -> (Option<u32>, &str) {
+    match RANK_PREFIX.captures(line) {
+        Some(caps) => match caps[1].parse() {
+            Ok(rank) => (Some(rank), &line[caps.get(0).unwrap().end()..]),
+            Err(_) => (None, line),
+        },
+        None => (None, line),
+    }
+}
+
+/// Whether the lines at the start of the log at `path` have rank prefixes
+pub fn has_rank_prefixes(path: &Path) -> anyhow::Result<bool> {
+    let file = crate::compression::LogReader::open(path)?;
+    for line in file.split(b'\n').take(DETECTION_LINES) {
+        if RANK_PREFIX.is_match(&String::from_utf8_lossy(&line?)) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Split the prefixed log at `path` into `dedicated_log_torch_trace_rank_N.log` files in `dir`,
+/// one per rank and without the prefixes.  A line without a prefix continues the line before
+/// it, so it goes to that line's rank; lines before the first prefix aren't from any rank and
+/// are dropped.  Returns the logs written with their ranks, by rank.
+pub fn split_by_rank(path: &Path, dir: &Path) -> anyhow::Result<Vec<(PathBuf, u32)>> {
+    fs::create_dir_all(dir)?;
+    let file = crate::compression::LogReader::open(path)?;
+    let mut writers: FxHashMap<u32, (PathBuf, BufWriter<File>)> = FxHashMap::default();
+    let mut rank = None;
+    for line in file.split(b'\n') {
+        let line = line?;
+        let text = String::from_utf8_lossy(&line);
+        let (line_rank, stripped) = strip_rank_prefix(&text);
+        rank = line_rank.or(rank);
+        let Some(rank) = rank else {
+            continue;
+        };
+        let (_, writer) = match writers.entry(rank) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let log = dir.join(format!("dedicated_log_torch_trace_rank_{rank}.log"));
+                let file =
+                    File::create(&log).with_context(|| format!("Creating {}", log.display()))?;
+                entry.insert((log, BufWriter::new(file)))
+            }
+        };
+        // Lines without a prefix are copied as is, so they aren't re-encoded
+        if line_rank.is_some() {
+            writer.write_all(stripped.as_bytes())?;
+        } else {
+            writer.write_all(&line)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    let mut logs = Vec::new();
+    for (rank, (log, mut writer)) in writers {
+        writer.flush()?;
+        logs.push((log, rank));
+    }
+    logs.sort_by_key(|(_, rank)| *rank);
+    Ok(logs)
+}
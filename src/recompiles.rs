@@ -0,0 +1,238 @@
+//! Why each frame recompiled (`recompiles.html`): a tree per frame with the original compile at
+//! the root and every recompile under it, each with the guards that failed and triggered it.
+//!
+//! The reasons come from the `recompile_reasons` artifact (or the `recompile_reason` metric of
+//! newer logs).  A recompile without either is explained by its guards instead: the guards of
+//! the previous compile of the frame it doesn't have, one of which must have failed.  Compiles
+//! of all compile ids are gathered as the log is parsed, in the log's `ParserState`.
+
+use indexmap::IndexMap;
+use std::sync::Mutex;
+
+use crate::types::{
+    CompilationMetricsMetadata, CompileId, GuardDiff, RecompileFrame, RecompileNode,
+    RecompileReasonCount, RecompilesContext,
+};
+
+/// (compiled autograd id, frame id): the compiles of one frame
+type FrameKey = (Option<u32>, Option<u32>);
+
+/// Most frequent reasons shown per frame
+const TOP_REASONS: usize = 5;
+
+#[derive(Default)]
+struct Compile {
+    /// Of the last attempt seen
+    compile_id: Option<CompileId>,
+    /// Compiles with metrics; others were only seen through their artifacts
+    has_metrics: bool,
+    failed: bool,
+    restarts: usize,
+    reasons: Vec<String>,
+    /// What the guards of the compile lost since the previous one, if it has no reasons
+    guard_diff: Option<GuardDiff>,
+}
+
+#[derive(Default)]
+struct Frame {
+    co_name: Option<String>,
+    location: Option<String>,
+    /// Frame compile id -> compile
+    compiles: IndexMap<Option<u32>, Compile>,
+}
+
+#[derive(Default)]
+struct Frames {
+    frames: IndexMap<FrameKey, Frame>,
+}
+
+/// The compiles of every frame, kept in the log's `ParserState`
+#[derive(Default)]
+pub(crate) struct Recompiles {
+    frames: Mutex<Frames>,
+}
+
+impl Frames {
+    /// The frame of the compile, with the compile's frame compile id
+    fn frame(&mut self, compile_id: &Option<CompileId>) -> Option<(&mut Frame, Option<u32>)> {
+        let cid = compile_id.as_ref()?;
+        cid.frame_id?;
+        let frame = self
+            .frames
+            .entry((cid.compiled_autograd_id, cid.frame_id))
+            .or_default();
+        Some((frame, cid.frame_compile_id))
+    }
+}
+
+impl Recompiles {
+    /// Frames have recompiled
+    pub fn any(&self) -> bool {
+        let frames = self.frames.lock().unwrap();
+        frames.frames.values().any(|f| f.compiles.len() > 1)
+    }
+
+    /// A compile's `compilation_metrics`; each attempt of a compile logs its own
+    pub fn add_metrics(&self, compile_id: &Option<CompileId>, m: &CompilationMetricsMetadata) {
+        let mut frames = self.frames.lock().unwrap();
+        let Some((frame, frame_compile_id)) = frames.frame(compile_id) else {
+            return;
+        };
+        if frame.co_name.is_none() {
+            frame.co_name = m.co_name.clone();
+            frame.location = m.co_filename.as_ref().map(|f| {
+                let file = crate::explain::basename(f);
+                match m.co_firstlineno {
+                    Some(line) => format!("{file}:{line}"),
+                    None => file.to_string(),
+                }
+            });
+        }
+        let compile = frame.compiles.entry(frame_compile_id).or_default();
+        if compile.has_metrics {
+            compile.restarts += 1;
+        }
+        compile.has_metrics = true;
+        compile.compile_id = compile_id.clone();
+        compile.failed = m.fail_type.is_some();
+        if let Some(serde_json::Value::String(reason)) = m.extra.get("recompile_reason") {
+            if compile.reasons.is_empty() {
+                compile.reasons.push(reason.clone());
+            }
+        }
+    }
+
+    /// A `recompile_reasons` artifact payload, a JSON list of the failed guards
+    pub fn add_reasons(&self, compile_id: &Option<CompileId>, payload: &str) {
+        let mut frames = self.frames.lock().unwrap();
+        let Some((frame, frame_compile_id)) = frames.frame(compile_id) else {
+            return;
+        };
+        let compile = frame.compiles.entry(frame_compile_id).or_default();
+        let reasons: Vec<String> = serde_json::from_str(payload)
+            .unwrap_or_else(|_| payload.lines().map(|l| l.trim().to_string()).collect());
+        for reason in reasons.into_iter().filter(|r| !r.is_empty()) {
+            if !compile.reasons.contains(&reason) {
+                compile.reasons.push(reason);
+            }
+        }
+        compile
+            .compile_id
+            .get_or_insert_with(|| compile_id.clone().unwrap());
+    }
+
+    /// The compile's guards compared with those of the previous compile of the frame
+    pub fn add_guard_diff(&self, compile_id: &Option<CompileId>, diff: GuardDiff) {
+        let mut frames = self.frames.lock().unwrap();
+        let Some((frame, frame_compile_id)) = frames.frame(compile_id) else {
+            return;
+        };
+        let compile = frame.compiles.entry(frame_compile_id).or_default();
+        compile.guard_diff.get_or_insert(diff);
+        compile
+            .compile_id
+            .get_or_insert_with(|| compile_id.clone().unwrap());
+    }
+
+    /// Frames with the most compiles first, frames compiled once left out
+    fn recompiled(frames: &Frames) -> Vec<(&FrameKey, &Frame)> {
+        let mut recompiled: Vec<(&FrameKey, &Frame)> = frames
+            .frames
+            .iter()
+            .filter(|(_, f)| f.compiles.len() > 1)
+            .collect();
+        recompiled.sort_by_key(|(_, f)| std::cmp::Reverse(f.compiles.len()));
+        recompiled
+    }
+
+    fn label((compiled_autograd_id, frame_id): &FrameKey) -> String {
+        let prefix = compiled_autograd_id.map_or(String::new(), |id| format!("!{id}/"));
+        let frame_id = frame_id.map_or("-".to_string(), |id| id.to_string());
+        format!("[{prefix}{frame_id}/*]")
+    }
+
+    /// For the index page, e.g. "2 of 3 frames recompiled, 9 times in all; [1/*] (step) compiled
+    /// 8 times"
+    pub fn summary(&self) -> String {
+        let frames = self.frames.lock().unwrap();
+        let recompiled = Self::recompiled(&frames);
+        let recompiles: usize = recompiled.iter().map(|(_, f)| f.compiles.len() - 1).sum();
+        let mut summary = format!(
+            "{} of {} frames recompiled, {recompiles} {} in all",
+            recompiled.len(),
+            frames.frames.len(),
+            if recompiles == 1 { "time" } else { "times" }
+        );
+        if let Some((key, frame)) = recompiled.first() {
+            let name = frame
+                .co_name
+                .as_ref()
+                .map_or(String::new(), |n| format!(" ({n})"));
+            summary.push_str(&format!(
+                "; {}{name} compiled {} times",
+                Self::label(key),
+                frame.compiles.len()
+            ));
+        }
+        summary
+    }
+
+    fn node(compile: &Compile) -> RecompileNode {
+        let guard_diff = compile
+            .guard_diff
+            .as_ref()
+            .filter(|_| compile.reasons.is_empty());
+        RecompileNode {
+            compile_id: compile
+                .compile_id
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            failed: compile.failed,
+            restarts: compile.restarts,
+            reasons: compile.reasons.clone(),
+            previous_compile_id: guard_diff.map(|d| d.previous_compile_id.clone()),
+            lost_guards: guard_diff.map_or(Vec::new(), |d| d.removed.clone()),
+        }
+    }
+
+    pub fn context(&self) -> RecompilesContext {
+        let frames = self.frames.lock().unwrap();
+        let frames_context = Self::recompiled(&frames)
+            .into_iter()
+            .map(|(key, frame)| {
+                let mut compiles: Vec<(&Option<u32>, &Compile)> = frame.compiles.iter().collect();
+                compiles.sort_by_key(|(frame_compile_id, _)| **frame_compile_id);
+                let mut counts: IndexMap<&str, usize> = IndexMap::new();
+                for (_, compile) in &compiles[1..] {
+                    for reason in &compile.reasons {
+                        *counts.entry(reason).or_default() += 1;
+                    }
+                }
+                counts.sort_by(|_, a, _, b| b.cmp(a));
+                RecompileFrame {
+                    label: Self::label(key),
+                    co_name: frame.co_name.clone().unwrap_or_default(),
+                    location: frame.location.clone().unwrap_or_default(),
+                    num_compiles: compiles.len(),
+                    original: Self::node(compiles[0].1),
+                    recompiles: compiles[1..].iter().map(|(_, c)| Self::node(c)).collect(),
+                    top_reasons: counts
+                        .into_iter()
+                        .take(TOP_REASONS)
+                        .map(|(reason, count)| RecompileReasonCount {
+                            reason: reason.to_string(),
+                            count,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        drop(frames);
+        RecompilesContext {
+            css: crate::templates::CSS,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            summary: self.summary(),
+            frames: frames_context,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! `--format json`: `report.json`, the report as one JSON document for CI systems and other
+//! programs that would otherwise scrape the HTML.
+//!
+//! It holds, per compile in log order, the artifacts written for it (paths relative to the
+//! report directory), its compilation metrics as logged, its failure and its restart reasons.
+//! With `--format json` alone the HTML pages are left out; `report_model.json` still records
+//! them, so `tlparse rerender` can produce them later.
+
+use fxhash::FxHashSet;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::types::{CompilationMetricsMetadata, CompileId, FxIndexMap, OutputFile, ParseOutput};
+
+pub const REPORT_JSON_FILENAME: &str = "report.json";
+
+/// Bumped when fields are renamed or removed, not when some are added
+const REPORT_JSON_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct CompileFailure {
+    pub fail_type: String,
+    pub fail_reason: Option<String>,
+    pub user_frame_filename: Option<String>,
+    pub user_frame_lineno: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportCompile {
+    /// `[0/0]`, `(unknown)` for artifacts logged outside of a compile
+    pub compile_id: String,
+    /// `ParseConfig::compile_labels` label of the compile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Directory of the compile's artifacts in the report
+    pub directory: Option<String>,
+    pub artifacts: Vec<String>,
+    /// The compile's `compilation_metrics`, every logged field
+    pub metrics: Option<Value>,
+    pub failure: Option<CompileFailure>,
+    pub restart_reasons: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub version: u32,
+    pub log: PathBuf,
+    pub num_compiles: usize,
+    pub num_failures: usize,
+    pub num_restarts: usize,
+    pub compiles: Vec<ReportCompile>,
+}
+
+/// The compilation metrics and artifacts of each compile, as the log is parsed
+#[derive(Default)]
+pub(crate) struct ReportIndex {
+    metrics: FxIndexMap<Option<CompileId>, CompilationMetricsMetadata>,
+    artifacts: FxIndexMap<Option<CompileId>, Vec<String>>,
+}
+
+impl ReportIndex {
+    pub fn add_metrics(
+        &mut self,
+        compile_id: &Option<CompileId>,
+        metrics: &CompilationMetricsMetadata,
+    ) {
+        self.metrics.insert(compile_id.clone(), metrics.clone());
+    }
+
+    /// The files written for each compile, once they all are
+    pub fn add_directory(&mut self, directory: &FxIndexMap<Option<CompileId>, Vec<OutputFile>>) {
+        self.artifacts = directory
+            .iter()
+            .map(|(compile_id, files)| {
+                (
+                    compile_id.clone(),
+                    files.iter().map(|f| f.url.clone()).collect(),
+                )
+            })
+            .collect();
+    }
+
+    /// `report.json`; artifacts that aren't in `output` (HTML pages with `--format json`) are
+    /// left out
+    pub fn report(&self, log: &Path, output: &ParseOutput) -> anyhow::Result<String> {
+        let written: FxHashSet<String> = output
+            .iter()
+            .map(|(path, _)| crate::paths::url(path))
+            .collect();
+        let mut compile_ids: Vec<&Option<CompileId>> = self.artifacts.keys().collect();
+        compile_ids.extend(
+            self.metrics
+                .keys()
+                .filter(|c| !self.artifacts.contains_key(*c)),
+        );
+        let compiles: Vec<ReportCompile> = compile_ids
+            .into_iter()
+            .map(|compile_id| {
+                let metrics = self.metrics.get(compile_id);
+                ReportCompile {
+                    compile_id: compile_id
+                        .as_ref()
+                        .map_or("(unknown)".to_string(), |c| c.to_string()),
+                    label: crate::compile_labels::label(compile_id),
+                    directory: compile_id.as_ref().map(|c| c.as_directory_name()),
+                    artifacts: self
+                        .artifacts
+                        .get(compile_id)
+                        .into_iter()
+                        .flatten()
+                        .filter(|url| written.contains(*url))
+                        .cloned()
+                        .collect(),
+                    metrics: metrics.and_then(|m| serde_json::to_value(m).ok()),
+                    failure: metrics.and_then(|m| {
+                        Some(CompileFailure {
+                            fail_type: m.fail_type.clone()?,
+                            fail_reason: m.fail_reason.clone(),
+                            user_frame_filename: m.fail_user_frame_filename.clone(),
+                            user_frame_lineno: m.fail_user_frame_lineno,
+                        })
+                    }),
+                    restart_reasons: metrics
+                        .and_then(|m| m.restart_reasons.clone())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        let report = Report {
+            version: REPORT_JSON_VERSION,
+            log: log.to_path_buf(),
+            num_compiles: compiles.iter().filter(|c| c.metrics.is_some()).count(),
+            num_failures: compiles.iter().filter(|c| c.failure.is_some()).count(),
+            num_restarts: compiles.iter().map(|c| c.restart_reasons.len()).sum(),
+            compiles,
+        };
+        Ok(serde_json::to_string_pretty(&report)?)
+    }
+}
@@ -0,0 +1,579 @@
+//! The rendered pages of a report with the contexts they were rendered from
+//! (`report_model.json`), so `tlparse rerender` can regenerate the HTML with other templates
+//! or another custom header without re-reading the log.
+//!
+//! Recording is opt-in (`--report-model`): the context of every page is kept in memory until
+//! the end of the parse, which `--low-memory` and `--max-memory` runs can't afford.  Without it
+//! only the md5 of each rendered page is kept.
+//!
+//! Built-in stylesheets and scripts are stored by name rather than by value, so a re-render
+//! also picks up the current ones.
+//!
+//! A page whose template fails to render (a field missing after a type change, a custom
+//! template with a typo) doesn't abort the run: it is replaced with a page showing the error
+//! and the context it was rendered with, and the failures are listed in `rendering_errors.html`,
+//! linked from the top of the index.
+//!
+//! Every built-in template is compiled into one registry per thread, once, and reused by each
+//! page rendered on it; TinyTemplate's formatters aren't `Send`, so threads can't share one.
+//! `validate_templates` compiles them all up front, so a template syntax error fails the run at
+//! startup instead of partway through a log.
+
+use anyhow::{bail, Context};
+use fxhash::FxHashMap;
+use html_escape::encode_text;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::{Cell, RefCell};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::OnceLock;
+use tinytemplate::TinyTemplate;
+
+use crate::templates::*;
+use crate::types::ParseOutput;
+
+pub const REPORT_MODEL_FILENAME: &str = "report_model.json";
+pub const RENDERING_ERRORS_FILENAME: &str = "rendering_errors.html";
+/// Placeholder at the top of the index, replaced with a link to the rendering errors if any
+pub const RENDERING_ERRORS_MARKER: &str = "<!-- tlparse:rendering-errors -->";
+const REPORT_MODEL_VERSION: u32 = 1;
+
+/// Static context values stored as `{"$static": name}`
+const STATICS: [(&str, &str); 10] = [
+    ("CSS", CSS),
+    ("EXPORT_CSS", EXPORT_CSS),
+    ("JAVASCRIPT", JAVASCRIPT),
+    ("TEMPLATE_FAILURES_CSS", TEMPLATE_FAILURES_CSS),
+    ("COMPARE_JAVASCRIPT", COMPARE_JAVASCRIPT),
+    ("COMPILE_TIMES_JAVASCRIPT", COMPILE_TIMES_JAVASCRIPT),
+    ("TEMPLATE_QUERY_PARAM_SCRIPT", TEMPLATE_QUERY_PARAM_SCRIPT),
+    ("PROVENANCE_CSS", PROVENANCE_CSS),
+    ("PROVENANCE_JS", PROVENANCE_JS),
+    ("VLLM_CSS", crate::vllm::templates::VLLM_CSS),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelPage {
+    pub path: PathBuf,
+    pub template: String,
+    pub context: Value,
+    /// (marker, replacement) pairs applied to the rendered page, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub substitutions: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportModel {
+    pub version: u32,
+    /// Rendered with the --export templates
+    pub export: bool,
+    pub base_url: Option<String>,
+    pub pages: Vec<ModelPage>,
+}
+
+struct RenderedPage {
+    template: String,
+    context: Value,
+    substitutions: Vec<(String, String)>,
+    html_md5: Vec<u8>,
+}
+
+/// The rendered pages, by the md5 of their html as well, since every output is looked up
+#[derive(Default)]
+struct RenderedPages {
+    pages: Vec<RenderedPage>,
+    /// Pages rendered identically share an md5
+    by_md5: FxHashMap<Vec<u8>, Vec<usize>>,
+}
+
+impl RenderedPages {
+    fn push(&mut self, page: RenderedPage) {
+        self.by_md5
+            .entry(page.html_md5.clone())
+            .or_default()
+            .push(self.pages.len());
+        self.pages.push(page);
+    }
+
+    fn contains(&self, html_md5: &[u8]) -> bool {
+        self.by_md5.contains_key(html_md5)
+    }
+
+    /// Rehash the first page whose html has `old_md5`, now that its html has `new_md5`,
+    /// returning where it is
+    fn update(&mut self, old_md5: &[u8], new_md5: Vec<u8>) -> Option<usize> {
+        let indexes = self.by_md5.get_mut(old_md5)?;
+        let i = indexes.remove(0);
+        if indexes.is_empty() {
+            self.by_md5.remove(old_md5);
+        }
+        let at = self.by_md5.entry(new_md5.clone()).or_default();
+        at.push(i);
+        at.sort_unstable();
+        self.pages[i].html_md5 = new_md5;
+        Some(i)
+    }
+}
+
+/// A page whose template failed to render
+struct RenderFailure {
+    template: String,
+    error: String,
+    /// Of the error page standing in for it, to find where it was written
+    html_md5: Vec<u8>,
+}
+
+thread_local! {
+    // Pages rendered by the current parse_path call; parsers render through a plain
+    // TinyTemplate reference, so the recording can't live in the config
+    static RENDERED: RefCell<RenderedPages> = RefCell::new(RenderedPages::default());
+    // Whether that parse writes a report model
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+    static RENDER_FAILURES: RefCell<Vec<RenderFailure>> = const { RefCell::new(Vec::new()) };
+    // The template registries of this thread, without and with --export
+    static REGISTRIES: RefCell<[Option<Rc<TinyTemplate<'static>>>; 2]> =
+        const { RefCell::new([None, None]) };
+}
+
+/// Templates of the pages rendered outside of a log's report
+const PAGE_TEMPLATES: [(&str, &str); 8] = [
+    ("multi_rank_index.html", TEMPLATE_MULTI_RANK_INDEX),
+    (
+        "vllm_workers.html",
+        crate::vllm::templates::VLLM_WORKERS_TEMPLATE,
+    ),
+    ("rank_artifact.html", TEMPLATE_RANK_ARTIFACT),
+    ("diff.html", TEMPLATE_DIFF_INDEX),
+    ("browse.html", TEMPLATE_BROWSE),
+    ("workspace.html", TEMPLATE_WORKSPACE),
+    (crate::watch::CHANGES_FILENAME, TEMPLATE_CHANGES),
+    ("_template_context.html", TEMPLATE_CONTEXT_DOCS),
+];
+
+fn md5(s: &str) -> Vec<u8> {
+    Md5::digest(s.as_bytes()).to_vec()
+}
+
+/// Built-in templates by name; `index.html` and the symbolic guard page differ for --export
+pub fn builtin_templates(export: bool) -> Vec<(&'static str, &'static str)> {
+    let mut templates = if export {
+        vec![
+            ("index.html", TEMPLATE_EXPORT_INDEX),
+            (
+                "symbolic_guard_information.html",
+                TEMPLATE_SYMBOLIC_GUARD_INFO,
+            ),
+        ]
+    } else {
+        vec![
+            ("index.html", TEMPLATE_INDEX),
+            ("failures_and_restarts.html", TEMPLATE_FAILURES_AND_RESTARTS),
+            ("dynamo_guards.html", TEMPLATE_DYNAMO_GUARDS),
+            ("dynamo_cpp_guards.html", TEMPLATE_CPP_GUARDS),
+            ("compilation_metrics.html", TEMPLATE_COMPILATION_METRICS),
+            ("memory_usage.html", TEMPLATE_MEMORY_USAGE),
+            ("op_coverage.html", TEMPLATE_OP_COVERAGE),
+            ("duplicate_graphs.html", TEMPLATE_DUPLICATE_GRAPHS),
+            ("fallback_kernels.html", TEMPLATE_FALLBACK_KERNELS),
+            ("data_dependent.html", TEMPLATE_DATA_DEPENDENT),
+            ("graph_breaks.html", TEMPLATE_GRAPH_BREAKS),
+            ("recompiles.html", TEMPLATE_RECOMPILES),
+            ("collectives.html", TEMPLATE_COLLECTIVES),
+            ("compile_times.html", TEMPLATE_COMPILE_TIMES),
+            ("graph_diff.html", TEMPLATE_GRAPH_DIFF),
+            ("cache_key_changes.html", TEMPLATE_CACHE_KEY_CHANGES),
+            ("sources.html", TEMPLATE_SOURCES),
+            ("compile_cancellation.html", TEMPLATE_COMPILE_CANCELLATION),
+            ("compiler_logs.html", TEMPLATE_COMPILER_LOGS),
+            ("compare.html", TEMPLATE_COMPARE),
+            ("directory_index.html", TEMPLATE_DIRECTORY_INDEX),
+            (
+                "bwd_compilation_metrics.html",
+                TEMPLATE_BWD_COMPILATION_METRICS,
+            ),
+            (
+                "aot_autograd_backward_compilation_metrics.html",
+                TEMPLATE_AOT_AUTOGRAD_BACKWARD_COMPILATION_METRICS,
+            ),
+        ]
+    };
+    templates.push(("provenance_tracking.html", TEMPLATE_PROVENANCE_TRACKING));
+    templates.push((
+        "vllm_summary.html",
+        crate::vllm::templates::VLLM_SUMMARY_TEMPLATE,
+    ));
+    templates
+}
+
+fn compile_templates(export: bool) -> anyhow::Result<TinyTemplate<'static>> {
+    let mut tt = TinyTemplate::new();
+    add_formatters(&mut tt);
+    for (name, template) in builtin_templates(export).into_iter().chain(PAGE_TEMPLATES) {
+        tt.add_template(name, template)
+            .with_context(|| format!("Compiling template {name}"))?;
+    }
+    Ok(tt)
+}
+
+/// Compile every built-in template, once per process, failing on the first that doesn't
+pub fn validate_templates() -> anyhow::Result<()> {
+    static VALID: OnceLock<Result<(), String>> = OnceLock::new();
+    VALID
+        .get_or_init(|| {
+            for export in [false, true] {
+                compile_templates(export).map_err(|e| format!("{e:#}"))?;
+            }
+            Ok(())
+        })
+        .clone()
+        .map_err(anyhow::Error::msg)
+}
+
+/// The registry of every built-in template, compiled the first time the thread asks for it
+pub(crate) fn templates(export: bool) -> anyhow::Result<Rc<TinyTemplate<'static>>> {
+    validate_templates()?;
+    REGISTRIES.with(|registries| {
+        let slot = &mut registries.borrow_mut()[usize::from(export)];
+        if let Some(tt) = slot {
+            return Ok(Rc::clone(tt));
+        }
+        let tt = Rc::new(compile_templates(export)?);
+        *slot = Some(Rc::clone(&tt));
+        Ok(tt)
+    })
+}
+
+/// The formatters report templates use
+pub fn add_formatters(tt: &mut TinyTemplate) {
+    tt.add_formatter("format_unescaped", tinytemplate::format_unescaped);
+    tt.add_formatter("format_float", |value, output| {
+        if let Value::Number(n) = value {
+            if let Some(f) = n.as_f64() {
+                write!(output, "{:.3}", f)?;
+                return Ok(());
+            }
+        }
+        write!(output, "{}", value)?;
+        Ok(())
+    });
+    crate::units::add_formatters(tt);
+}
+
+fn stash_statics(value: &mut Value) {
+    if let Value::Object(fields) = value {
+        for field in fields.values_mut() {
+            if let Some((name, _)) = STATICS.iter().find(|(_, s)| field.as_str() == Some(s)) {
+                *field = serde_json::json!({ "$static": name });
+            }
+        }
+    }
+}
+
+/// The page standing in for one whose template failed to render
+fn error_page(template: &str, error: &str, context: &Value) -> String {
+    format!(
+        "<html>\n<head>\n<style>\n{CSS}\n</style>\n<title>Couldn't render {template}</title>\n</head>\n\
+         <body>\n<h1>Couldn't render this page</h1>\n\
+         <p>The <code>{template}</code> template failed; the rest of the report was generated.</p>\n\
+         <pre class=\"status-error\">{}</pre>\n\
+         <h2>Context</h2>\n<p>What the page was to be rendered from:</p>\n<pre>{}</pre>\n</body>\n</html>\n",
+        encode_text(error),
+        encode_text(&serde_json::to_string_pretty(context).unwrap_or_default()),
+        template = encode_text(template),
+    )
+}
+
+fn context_value<C: Serialize>(context: &C) -> Value {
+    let mut value = serde_json::to_value(context).unwrap_or_default();
+    stash_statics(&mut value);
+    value
+}
+
+/// Render a report page, recording its context if there is a report model; if the template
+/// fails, the page shows the error and the context instead
+pub(crate) fn render<C: Serialize>(tt: &TinyTemplate, template: &str, context: &C) -> String {
+    let html = match tt.render(template, context) {
+        Ok(html) => html,
+        Err(e) => {
+            let error = format!("{e:#}");
+            let html = error_page(template, &error, &context_value(context));
+            RENDER_FAILURES.with(|failures| {
+                failures.borrow_mut().push(RenderFailure {
+                    template: template.to_string(),
+                    error,
+                    html_md5: md5(&html),
+                })
+            });
+            html
+        }
+    };
+    // The md5 is kept either way, so the page stays in memory for its markers to be filled in
+    let context = if RECORDING.get() {
+        context_value(context)
+    } else {
+        Value::Null
+    };
+    RENDERED.with(|rendered| {
+        rendered.borrow_mut().push(RenderedPage {
+            template: template.to_string(),
+            context,
+            substitutions: Vec::new(),
+            html_md5: md5(&html),
+        })
+    });
+    html
+}
+
+fn pages(n: usize) -> String {
+    match n {
+        1 => "1 page".to_string(),
+        n => format!("{n} pages"),
+    }
+}
+
+/// `rendering_errors.html`, listing each page that failed with its template and error
+fn rendering_errors_page(failures: &[(String, String, String)]) -> String {
+    let mut rows = String::new();
+    for (path, template, error) in failures {
+        writeln!(
+            rows,
+            "<tr> <td> <a href=\"{}\">{}</a> </td> <td> <code>{}</code> </td> <td> <pre>{}</pre> </td> </tr>",
+            encode_text(path),
+            encode_text(path),
+            encode_text(template),
+            encode_text(error)
+        )
+        .unwrap();
+    }
+    format!(
+        "<html>\n<head>\n<style>\n{CSS}\n</style>\n<title>Rendering errors</title>\n</head>\n\
+         <body>\n<h1>Rendering errors</h1>\n\
+         <p>{} failed to render and show the error and their context instead.  \
+         Back to the <a href=\"index.html\">index</a>.</p>\n\
+         <table>\n<tr> <th> Page </th> <th> Template </th> <th> Error </th> </tr>\n{rows}</table>\n</body>\n</html>\n",
+        pages(failures.len())
+    )
+}
+
+/// Add `rendering_errors.html` for the pages in `output` that failed to render (by the md5 of
+/// their error pages), and link it from the index; returns how many failed
+fn report_failures(output: &mut ParseOutput, failed: &[(Vec<u8>, String, String)]) -> usize {
+    let failures: Vec<(String, String, String)> = output
+        .iter()
+        .filter_map(|(path, html)| {
+            let md5 = md5(html);
+            let (_, template, error) = failed.iter().find(|(m, _, _)| *m == md5)?;
+            Some((crate::paths::url(path), template.clone(), error.clone()))
+        })
+        .collect();
+    if failures.is_empty() {
+        return 0;
+    }
+    let banner = format!(
+        "<p class=\"status-error\">{} failed to render; see <a href=\"{RENDERING_ERRORS_FILENAME}\">rendering errors</a>.</p>",
+        pages(failures.len())
+    );
+    if let Some((_, index)) = output
+        .iter_mut()
+        .find(|(path, _)| path.as_os_str() == "index.html")
+    {
+        // Not recorded as a substitution, so a re-render only shows its own failures
+        let old_md5 = md5(index);
+        *index = index.replace(RENDERING_ERRORS_MARKER, &banner);
+        let new_md5 = md5(index);
+        RENDERED.with(|rendered| rendered.borrow_mut().update(&old_md5, new_md5));
+    }
+    output.push((
+        PathBuf::from(RENDERING_ERRORS_FILENAME),
+        rendering_errors_page(&failures),
+    ));
+    failures.len()
+}
+
+/// Whether `html` is a page rendered from a template by this parse, which later passes and the
+/// report model still need
+pub(crate) fn is_rendered(html: &str) -> bool {
+    RENDERED.with(|rendered| rendered.borrow().contains(&md5(html)))
+}
+
+/// Replace `marker` in a rendered page with content rendered separately, recording the
+/// replacement so a re-render keeps it
+pub(crate) fn substitute(html: &mut String, marker: &str, replacement: &str) {
+    let old_md5 = md5(html);
+    *html = html.replace(marker, replacement);
+    RENDERED.with(|rendered| {
+        let mut rendered = rendered.borrow_mut();
+        let i = rendered.update(&old_md5, md5(html));
+        if let Some(i) = i.filter(|_| RECORDING.get()) {
+            rendered.pages[i]
+                .substitutions
+                .push((marker.to_string(), replacement.to_string()));
+        }
+    });
+}
+
+/// Forget pages recorded by an earlier parse on this thread, and record the pages rendered from
+/// now on if `record` (`--report-model`)
+pub(crate) fn start_recording(record: bool) {
+    RECORDING.set(record);
+    RENDERED.with(|rendered| *rendered.borrow_mut() = RenderedPages::default());
+    RENDER_FAILURES.with(|failures| failures.borrow_mut().clear());
+}
+
+/// List the pages that failed to render, and if recording, match the recorded pages to the
+/// output files they ended up in and add `report_model.json`
+pub(crate) fn attach(
+    output: &mut ParseOutput,
+    export: bool,
+    base_url: Option<&str>,
+) -> anyhow::Result<()> {
+    let failed: Vec<(Vec<u8>, String, String)> = RENDER_FAILURES.with(|failures| {
+        std::mem::take(&mut *failures.borrow_mut())
+            .into_iter()
+            .map(|f| (f.html_md5, f.template, f.error))
+            .collect()
+    });
+    report_failures(output, &failed);
+    let rendered = RENDERED.with(|rendered| std::mem::take(&mut *rendered.borrow_mut()).pages);
+    if !RECORDING.replace(false) {
+        return Ok(());
+    }
+    let by_md5: FxHashMap<&[u8], &RenderedPage> = rendered
+        .iter()
+        .map(|page| (page.html_md5.as_slice(), page))
+        .collect();
+    let pages = output
+        .iter()
+        .filter(|(path, _)| path.extension().is_some_and(|e| e == "html"))
+        .filter_map(|(path, html)| {
+            let page = by_md5.get(md5(html).as_slice())?;
+            Some(ModelPage {
+                path: path.clone(),
+                template: page.template.clone(),
+                context: page.context.clone(),
+                substitutions: page.substitutions.clone(),
+            })
+        })
+        .collect();
+    let model = ReportModel {
+        version: REPORT_MODEL_VERSION,
+        export,
+        base_url: base_url.map(str::to_string),
+        pages,
+    };
+    output.push((
+        PathBuf::from(REPORT_MODEL_FILENAME),
+        serde_json::to_string(&model)?,
+    ));
+    Ok(())
+}
+
+fn restore_statics(value: &mut Value) {
+    if let Value::Object(fields) = value {
+        for field in fields.values_mut() {
+            if let Some(name) = field.get("$static").and_then(Value::as_str) {
+                if let Some((_, s)) = STATICS.iter().find(|(n, _)| *n == name) {
+                    *field = Value::String(s.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Regenerate the pages of the report in `out_dir` from its report model.  Templates in
+/// `template_dir` named like the built-in ones (`index.html`, `compilation_metrics.html`,
+/// `multi_rank_index.html`, ...) replace them; `custom_header_html` replaces the header of every
+/// page that has one.  Returns the number of pages written.
+pub fn rerender(
+    out_dir: &Path,
+    template_dir: Option<&Path>,
+    custom_header_html: Option<&str>,
+) -> anyhow::Result<usize> {
+    let model_path = out_dir.join(REPORT_MODEL_FILENAME);
+    let model: ReportModel =
+        serde_json::from_str(&fs::read_to_string(&model_path).with_context(|| {
+            format!(
+                "Reading {} (parse the log with --report-model to write it)",
+                model_path.display()
+            )
+        })?)
+        .with_context(|| format!("Parsing {}", model_path.display()))?;
+    if model.version != REPORT_MODEL_VERSION {
+        bail!(
+            "{} has version {}, this tlparse reads version {REPORT_MODEL_VERSION}; parse the log again",
+            model_path.display(),
+            model.version
+        );
+    }
+
+    let templates: Vec<(&'static str, &'static str)> = builtin_templates(model.export)
+        .into_iter()
+        .chain(PAGE_TEMPLATES)
+        .collect();
+    let mut overrides: Vec<(&'static str, String)> = Vec::new();
+    if let Some(dir) = template_dir {
+        for &(name, _) in &templates {
+            let path = dir.join(name);
+            if path.exists() {
+                let template = fs::read_to_string(&path)
+                    .with_context(|| format!("Reading template {}", path.display()))?;
+                overrides.push((name, template));
+            }
+        }
+        if overrides.is_empty() {
+            bail!(
+                "No templates named like the built-in ones in {}",
+                dir.display()
+            );
+        }
+    }
+    let mut tt = TinyTemplate::new();
+    add_formatters(&mut tt);
+    for (name, builtin) in templates {
+        let template = overrides
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map_or(builtin, |(_, t)| t.as_str());
+        tt.add_template(name, template)
+            .with_context(|| format!("Compiling template {name}"))?;
+    }
+
+    let mut output: ParseOutput = Vec::new();
+    let mut failed = Vec::new();
+    for mut page in model.pages {
+        restore_statics(&mut page.context);
+        if let (Some(header), Some(field)) = (
+            custom_header_html,
+            page.context.get_mut("custom_header_html"),
+        ) {
+            *field = Value::String(header.to_string());
+        }
+        let mut html = match tt.render(&page.template, &page.context) {
+            Ok(html) => html,
+            Err(e) => {
+                let error = format!("{e:#}");
+                eprintln!("Failed to render {}: {error}", page.path.display());
+                let mut context = page.context.clone();
+                stash_statics(&mut context);
+                let html = error_page(&page.template, &error, &context);
+                failed.push((md5(&html), page.template.clone(), error));
+                html
+            }
+        };
+        for (marker, replacement) in &page.substitutions {
+            html = html.replace(marker, replacement);
+        }
+        output.push((page.path, html));
+    }
+    if let Some(base_url) = &model.base_url {
+        crate::links::rewrite_report_links(&mut output, base_url);
+    }
+    let pages = output.len();
+    report_failures(&mut output, &failed);
+    crate::paths::write_output(out_dir, output)?;
+    Ok(pages)
+}
@@ -0,0 +1,302 @@
+//! `tlparse lsp-ish --stdio REPORT`: answer editor plugins over JSON-RPC 2.0 on stdio, so they
+//! can annotate user code with the compiles and guards of a parsed report.
+//!
+//! REPORT is a tlparse output directory or a log, which is parsed in memory first.  Messages
+//! are framed like LSP (`Content-Length` headers) or, for scripting, one JSON object per line;
+//! replies use the framing of the request.  Methods:
+//!
+//! * `getCompileForSource(file, line)`: the compiles of the frame enclosing `line` of `file`,
+//!   i.e. the compiled function with the nearest first line at or before it
+//! * `getGuardsForSource(file, line)`: the guards of those compiles
+//! * `initialize`, `shutdown` and the `exit` notification, as in LSP
+//!
+//! Params are `{"file": ..., "line": ...}` or `[file, line]`, with 1-based lines.  Logged
+//! filenames are often absolute paths on another machine, so `file` also matches a logged
+//! filename ending in it (or the other way round) at a path separator.
+
+use anyhow::Context;
+use fxhash::FxHashMap;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::guard_diff::{cpp_guards, json_guards};
+use crate::report_model::{ReportModel, REPORT_MODEL_FILENAME};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+const METHODS: [&str; 2] = ["getCompileForSource", "getGuardsForSource"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceCompile {
+    pub compile_id: String,
+    pub co_name: Option<String>,
+    pub co_filename: Option<String>,
+    pub co_firstlineno: Option<i64>,
+    pub fail_type: Option<String>,
+    pub entire_frame_compile_time_s: Option<f64>,
+    pub explanation: Option<String>,
+    pub num_guards: usize,
+    /// Compilation metrics page, absolute when the report is on disk
+    pub metrics_url: Option<String>,
+    #[serde(skip)]
+    guards: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceGuards {
+    pub compile_id: String,
+    pub guards: Vec<String>,
+}
+
+/// The compiles of a report, looked up by source location
+#[derive(Debug, Default)]
+pub struct ReportIndex {
+    compiles: Vec<SourceCompile>,
+}
+
+fn same_file(a: &str, b: &str) -> bool {
+    let a = a.replace('\\', "/");
+    let b = b.replace('\\', "/");
+    let (short, long) = if a.len() <= b.len() {
+        (&a, &b)
+    } else {
+        (&b, &a)
+    };
+    long == short
+        || (long.ends_with(short.as_str())
+            && (short.starts_with('/') || long[..long.len() - short.len()].ends_with('/')))
+}
+
+impl ReportIndex {
+    /// Index a tlparse output directory, or parse a log and index its report
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if path.is_dir() {
+            let read = |name: &str| fs::read_to_string(path.join(name)).ok();
+            Self::from_report(read, Some(path))
+        } else {
+            let output = crate::parse_path(&path.to_path_buf(), &crate::ParseConfig::default())
+                .with_context(|| format!("Parsing {}", path.display()))?;
+            let files: FxHashMap<PathBuf, String> = output.into_iter().collect();
+            Self::from_report(|name: &str| files.get(Path::new(name)).cloned(), None)
+        }
+    }
+
+    /// From the files of a report: summary.json, compile_directory.json, the guard dumps and
+    /// report_model.json for the `dynamo_guards` pages
+    fn from_report(
+        read: impl Fn(&str) -> Option<String>,
+        dir: Option<&Path>,
+    ) -> anyhow::Result<Self> {
+        let summary: Value =
+            serde_json::from_str(&read("summary.json").context("The report has no summary.json")?)?;
+        let directory: Value = match read("compile_directory.json") {
+            Some(contents) => serde_json::from_str(&contents)?,
+            None => Value::Null,
+        };
+        let json_guard_pages: FxHashMap<String, Vec<String>> = match read(REPORT_MODEL_FILENAME) {
+            Some(contents) => serde_json::from_str::<ReportModel>(&contents)?
+                .pages
+                .into_iter()
+                .filter(|page| page.template == "dynamo_guards.html")
+                .filter_map(|page| {
+                    let guards = json_guards(&page.context["guards"].to_string())?;
+                    Some((
+                        page.path.to_string_lossy().into_owned(),
+                        guards.into_iter().collect(),
+                    ))
+                })
+                .collect(),
+            None => FxHashMap::default(),
+        };
+        let compiles = summary["compiles"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|c| {
+                let compile_id = c["compile_id"].as_str().unwrap_or_default().to_string();
+                let mut guards = Vec::new();
+                for artifact in directory[&compile_id]["artifacts"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                {
+                    let (Some(name), Some(url)) =
+                        (artifact["name"].as_str(), artifact["url"].as_str())
+                    else {
+                        continue;
+                    };
+                    if name.starts_with("dynamo_cpp_guards_str") {
+                        if let Some(payload) = read(url) {
+                            guards.extend(cpp_guards(&payload));
+                        }
+                    } else if let Some(json) = json_guard_pages.get(url) {
+                        guards.extend(json.iter().cloned());
+                    }
+                }
+                let text = |field: &str| c[field].as_str().map(str::to_string);
+                SourceCompile {
+                    co_name: text("co_name"),
+                    co_filename: text("co_filename"),
+                    co_firstlineno: c["co_firstlineno"].as_i64(),
+                    fail_type: text("fail_type"),
+                    entire_frame_compile_time_s: c["entire_frame_compile_time_s"].as_f64(),
+                    explanation: text("explanation").filter(|e| !e.is_empty()),
+                    num_guards: guards.len(),
+                    metrics_url: text("metrics_url").map(|url| match dir {
+                        Some(dir) => dir.join(url).to_string_lossy().into_owned(),
+                        None => url,
+                    }),
+                    compile_id,
+                    guards,
+                }
+            })
+            .collect();
+        Ok(Self { compiles })
+    }
+
+    pub fn len(&self) -> usize {
+        self.compiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.compiles.is_empty()
+    }
+
+    /// The compiles of the frame enclosing `line` of `file`
+    pub fn compiles_for_source(&self, file: &str, line: i64) -> Vec<&SourceCompile> {
+        let in_file: Vec<&SourceCompile> = self
+            .compiles
+            .iter()
+            .filter(|c| c.co_filename.as_deref().is_some_and(|f| same_file(f, file)))
+            .collect();
+        let Some(first_line) = in_file
+            .iter()
+            .filter_map(|c| c.co_firstlineno)
+            .filter(|first| *first <= line)
+            .max()
+        else {
+            return Vec::new();
+        };
+        in_file
+            .into_iter()
+            .filter(|c| c.co_firstlineno == Some(first_line))
+            .collect()
+    }
+
+    pub fn guards_for_source(&self, file: &str, line: i64) -> Vec<SourceGuards> {
+        self.compiles_for_source(file, line)
+            .into_iter()
+            .map(|c| SourceGuards {
+                compile_id: c.compile_id.clone(),
+                guards: c.guards.clone(),
+            })
+            .collect()
+    }
+
+    fn call(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "serverInfo": {"name": "tlparse", "version": env!("CARGO_PKG_VERSION")},
+                "capabilities": {"methods": METHODS},
+                "numCompiles": self.len(),
+            })),
+            "shutdown" => Ok(Value::Null),
+            "getCompileForSource" | "getGuardsForSource" => {
+                let (file, line) = match params {
+                    Value::Array(args) => (args.first(), args.get(1)),
+                    _ => (params.get("file"), params.get("line")),
+                };
+                let (Some(file), Some(line)) =
+                    (file.and_then(Value::as_str), line.and_then(Value::as_i64))
+                else {
+                    return Err((INVALID_PARAMS, "Expected params file and line".to_string()));
+                };
+                Ok(if method == "getCompileForSource" {
+                    json!(self.compiles_for_source(file, line))
+                } else {
+                    json!(self.guards_for_source(file, line))
+                })
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("No method {method}"))),
+        }
+    }
+
+    /// The reply to one message, or `None` for a notification
+    pub fn handle(&self, message: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => return Some(error_reply(Value::Null, PARSE_ERROR, e.to_string())),
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request["method"].as_str() else {
+            return Some(error_reply(
+                id.unwrap_or(Value::Null),
+                INVALID_REQUEST,
+                "Expected a method".to_string(),
+            ));
+        };
+        let result = self.call(method, &request["params"]);
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => error_reply(id, code, message),
+        })
+    }
+
+    /// Answer messages from `input` on `output` until `exit` or the end of the input
+    pub fn serve(&self, mut input: impl BufRead, mut output: impl Write) -> anyhow::Result<()> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let header = line.trim();
+            if header.is_empty() {
+                continue;
+            }
+            let (message, framed) = match header
+                .split_once(':')
+                .filter(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            {
+                Some((_, length)) => {
+                    let length: usize = length.trim().parse().context("Bad Content-Length")?;
+                    // Skip the other headers up to the blank line
+                    loop {
+                        line.clear();
+                        if input.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                            break;
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    input.read_exact(&mut body)?;
+                    (String::from_utf8_lossy(&body).into_owned(), true)
+                }
+                None => (header.to_string(), false),
+            };
+            if serde_json::from_str::<Value>(&message).is_ok_and(|m| m["method"] == "exit") {
+                return Ok(());
+            }
+            let Some(reply) = self.handle(&message) else {
+                continue;
+            };
+            let reply = reply.to_string();
+            if framed {
+                write!(output, "Content-Length: {}\r\n\r\n{reply}", reply.len())?;
+            } else {
+                writeln!(output, "{reply}")?;
+            }
+            output.flush()?;
+        }
+    }
+}
+
+fn error_reply(id: Value, code: i64, message: String) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
@@ -0,0 +1,172 @@
+//! `--sample-rate` / `--max-compiles`: parse only a deterministic subset of the compile ids of a
+//! log too large to parse in full.
+//!
+//! A cheap first pass reads just the `compilation_metrics` envelopes.  Failed compiles are
+//! always kept, then the slowest ones, then compiles whose id hashes below the sample rate, up
+//! to `--max-compiles`.  The same log and options always select the same compiles.  Envelopes
+//! outside any compile (string table, chromium events, ...) are always parsed; envelopes of
+//! compiles that weren't selected are skipped.
+
+use anyhow::bail;
+use fxhash::{FxHashMap, FxHashSet};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::path::Path;
+
+use crate::types::CompileId;
+
+/// How many of the slowest compiles are kept regardless of the sample rate
+pub const SLOWEST_KEPT: usize = 10;
+
+#[derive(Debug, Clone, Default)]
+pub struct SamplingConfig {
+    /// Fraction of the compiles to keep, in (0, 1]
+    pub rate: Option<f64>,
+    pub max_compiles: Option<usize>,
+}
+
+/// (compiled autograd id, frame id, frame compile id): every attempt of a compile is kept or
+/// skipped together
+type CompileKey = (Option<u32>, Option<u32>, Option<u32>);
+
+fn key(cid: &CompileId) -> CompileKey {
+    (cid.compiled_autograd_id, cid.frame_id, cid.frame_compile_id)
+}
+
+#[derive(Deserialize)]
+struct MetricsFields {
+    entire_frame_compile_time_s: Option<f64>,
+    fail_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MetricsEnvelope {
+    #[serde(flatten)]
+    compile_id: Option<CompileId>,
+    compilation_metrics: MetricsFields,
+}
+
+/// Position of a compile in [0, 1), stable across runs
+fn hash_position(key: &CompileKey) -> f64 {
+    let mut hasher = Md5::new();
+    hasher.update(format!("{key:?}"));
+    let hash = hasher.finalize();
+    u64::from_be_bytes(hash[..8].try_into().unwrap()) as f64 / (u64::MAX as f64 + 1.0)
+}
+
+#[derive(Debug, Serialize)]
+pub struct Sampling {
+    pub total_compiles: usize,
+    pub kept_compiles: usize,
+    pub kept_failures: usize,
+    #[serde(skip)]
+    kept: FxHashSet<CompileKey>,
+    /// Compile ids seen in the first pass, to tell apart compiles that logged no metrics
+    #[serde(skip)]
+    seen: FxHashSet<CompileKey>,
+}
+
+impl Sampling {
+    /// Envelopes of compiles without metrics (e.g. cut off by a crash) are kept
+    pub fn keeps(&self, compile_id: &Option<CompileId>) -> bool {
+        let Some(cid) = compile_id else {
+            return true;
+        };
+        let key = key(cid);
+        self.kept.contains(&key) || !self.seen.contains(&key)
+    }
+
+    /// One line describing what was sampled, for the index page
+    pub fn summary(&self) -> String {
+        format!(
+            "Sampled report: {} of {} compiles were parsed, including all {} failed compiles and the slowest ones.  Compiles outside the sample are missing from every page.",
+            self.kept_compiles, self.total_compiles, self.kept_failures
+        )
+    }
+}
+
+pub fn select_compiles(path: &Path, config: &SamplingConfig) -> anyhow::Result<Sampling> {
+    let rate = config.rate.unwrap_or(1.0);
+    if !(rate > 0.0 && rate <= 1.0) {
+        bail!("Sample rate must be in (0, 1], got {rate}");
+    }
+    let file = crate::compression::LogReader::open(path)?;
+    // (compile id, compile time, failed), first metrics of each compile
+    let mut compiles: Vec<(CompileId, f64, bool)> = Vec::new();
+    let mut positions: FxHashMap<CompileKey, usize> = FxHashMap::default();
+    for line in file.split(b'\n') {
+        let line = line?;
+        if line.first() == Some(&b'\t') {
+            continue;
+        }
+        let Ok(line) = std::str::from_utf8(&line) else {
+            continue;
+        };
+        if !line.contains("\"compilation_metrics\"") {
+            continue;
+        }
+        let Some(start) = line.find("] {") else {
+            continue;
+        };
+        let Ok(e) = serde_json::from_str::<MetricsEnvelope>(&line[start + 2..]) else {
+            continue;
+        };
+        let Some(cid) = e.compile_id else {
+            continue;
+        };
+        let failed = e.compilation_metrics.fail_type.is_some();
+        let time = e
+            .compilation_metrics
+            .entire_frame_compile_time_s
+            .unwrap_or(0.0);
+        match positions.get(&key(&cid)) {
+            Some(&i) => {
+                // Restarted attempts: the compile failed if any attempt did, and took all of them
+                compiles[i].1 += time;
+                compiles[i].2 |= failed;
+            }
+            None => {
+                positions.insert(key(&cid), compiles.len());
+                compiles.push((cid, time, failed));
+            }
+        }
+    }
+
+    let total_compiles = compiles.len();
+    let max = config.max_compiles.unwrap_or(usize::MAX);
+    let mut kept = FxHashSet::default();
+    let mut kept_failures = 0;
+    for (cid, _, _) in compiles.iter().filter(|(_, _, failed)| *failed) {
+        kept.insert(key(cid));
+        kept_failures += 1;
+    }
+    let mut by_time: Vec<&(CompileId, f64, bool)> = compiles.iter().collect();
+    by_time.sort_by(|a, b| b.1.total_cmp(&a.1));
+    for (cid, _, _) in by_time.into_iter().take(SLOWEST_KEPT) {
+        if kept.len() >= max {
+            break;
+        }
+        kept.insert(key(cid));
+    }
+    let mut sampled: Vec<(f64, &CompileId)> = compiles
+        .iter()
+        .map(|(cid, _, _)| (hash_position(&key(cid)), cid))
+        .filter(|(position, _)| *position < rate)
+        .collect();
+    sampled.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for (_, cid) in sampled {
+        if kept.len() >= max {
+            break;
+        }
+        kept.insert(key(cid));
+    }
+
+    Ok(Sampling {
+        total_compiles,
+        kept_compiles: kept.len(),
+        kept_failures,
+        kept,
+        seen: positions.into_keys().collect(),
+    })
+}
@@ -0,0 +1,123 @@
+//! Search across the graphs, guards and generated code of a run from the index page.  Their
+//! text goes to `search_index.json`, which the page loads on the first search and matches line
+//! by line, linking each hit to its line on pages with line anchors.
+//!
+//! The index holds at most `MAX_INDEX_BYTES` of text, which also bounds what it keeps in memory
+//! with `--low-memory`; artifacts beyond that are counted but not searchable.
+
+use serde::Serialize;
+
+use crate::manifest::artifact_name_kind;
+use crate::types::{CompileId, OutputFile};
+
+pub(crate) const SEARCH_INDEX_FILENAME: &str = "search_index.json";
+const MAX_INDEX_BYTES: usize = 64 << 20;
+/// Longer lines (serialized tensors, giant lists) are cut to this many characters
+const MAX_LINE_CHARS: usize = 500;
+
+#[derive(Serialize)]
+struct SearchFile {
+    url: String,
+    compile_id: String,
+    /// The page has an `L<n>` anchor per line of the payload
+    anchored: bool,
+    lines: Vec<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct SearchIndex {
+    files: Vec<SearchFile>,
+    bytes: usize,
+    skipped: usize,
+}
+
+#[derive(Serialize)]
+struct SearchIndexJson<'a> {
+    files: &'a [SearchFile],
+    skipped: usize,
+}
+
+/// Graphs, guards and code, but not the cache and metadata artifacts named after graphs
+fn is_searchable(url: &str) -> bool {
+    if url.starts_with("dump_file/") {
+        return true;
+    }
+    let kind = artifact_name_kind(url);
+    !kind.contains("cache")
+        && !kind.contains("metadata")
+        && ["graph", "guards", "code"].iter().any(|k| kind.contains(k))
+}
+
+impl SearchIndex {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The payload `files` were just written from, indexed under the first of them
+    pub fn add(&mut self, compile_id: &Option<CompileId>, files: &[OutputFile], payload: &str) {
+        let Some(file) = files.first().filter(|f| is_searchable(&f.url)) else {
+            return;
+        };
+        if payload.is_empty() {
+            return;
+        }
+        if self.bytes + payload.len() > MAX_INDEX_BYTES {
+            self.skipped += 1;
+            return;
+        }
+        // JSON guards come on one line, one guard per line is what there is to search
+        let pretty = payload
+            .trim_start()
+            .starts_with(['[', '{'])
+            .then(|| serde_json::from_str::<serde_json::Value>(payload).ok())
+            .flatten()
+            .and_then(|value| serde_json::to_string_pretty(&value).ok());
+        let lines: Vec<String> = pretty
+            .as_deref()
+            .unwrap_or(payload)
+            .lines()
+            .map(|line| match line.char_indices().nth(MAX_LINE_CHARS) {
+                Some((end, _)) => format!("{}…", &line[..end]),
+                None => line.to_string(),
+            })
+            .collect();
+        self.bytes += lines.iter().map(String::len).sum::<usize>();
+        let anchored = pretty.is_none()
+            && file.url.ends_with(".html")
+            && (file.url.starts_with("dump_file/")
+                || artifact_name_kind(&file.url) == "inductor_output_code");
+        self.files.push(SearchFile {
+            url: file.url.clone(),
+            compile_id: compile_id
+                .as_ref()
+                .map_or("(unknown)".to_string(), |c| c.to_string()),
+            anchored,
+            lines,
+        });
+    }
+
+    /// For the index page, e.g. "Search the 120 graphs, guards and code files of this run"
+    pub fn summary(&self) -> String {
+        let files = match self.files.len() {
+            1 => "the graph, guards or code file".to_string(),
+            n => format!("the {n} graphs, guards and code files"),
+        };
+        let mut summary =
+            format!("Search {files} of this run for an op name, symbol or guard expression.");
+        if self.skipped > 0 {
+            summary.push_str(&format!(
+                "  {} more were left out to keep the search index under {} MiB.",
+                self.skipped,
+                MAX_INDEX_BYTES >> 20
+            ));
+        }
+        summary
+    }
+
+    pub fn json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(&SearchIndexJson {
+            files: &self.files,
+            skipped: self.skipped,
+        })?)
+    }
+}
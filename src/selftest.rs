@@ -0,0 +1,154 @@
+//! `tlparse selftest OUT_DIR`: check a generated report end to end, e.g. after a tlparse
+//! upgrade, without a browser.
+//!
+//! Every HTML page is read, and each of its relative links must point at a file of the report
+//! (a directory link at its `index.html`).  Every `.json` file must parse, and each line of a
+//! `.jsonl` file.  Each file `manifest.json` lists must exist, with the size it records;
+//! pages are exempt from the size check, as `tlparse rerender` rewrites them in place.
+
+use anyhow::{bail, Context};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::load_reports;
+
+#[derive(Debug, Default)]
+pub struct SelftestReport {
+    pub pages: usize,
+    pub links: usize,
+    pub json_files: usize,
+    pub manifest_files: usize,
+    /// `path: what is wrong`, paths relative to the output directory
+    pub problems: Vec<String>,
+}
+
+impl SelftestReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// e.g. "12 pages (340 links), 5 JSON files and 48 manifest entries checked: 2 problems"
+    pub fn summary(&self) -> String {
+        let result = match self.problems.len() {
+            0 => "ok".to_string(),
+            1 => "1 problem".to_string(),
+            n => format!("{n} problems"),
+        };
+        format!(
+            "{} pages ({} links), {} JSON files and {} manifest entries checked: {result}",
+            self.pages, self.links, self.json_files, self.manifest_files
+        )
+    }
+}
+
+/// Every file under `dir`, relative to `root`
+fn list_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Listing {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(root, &path, files)?;
+        } else {
+            files.push(path.strip_prefix(root)?.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn check_page(out_dir: &Path, page: &Path, report: &mut SelftestReport) {
+    let html = match fs::read_to_string(out_dir.join(page)) {
+        Ok(html) => html,
+        Err(e) => {
+            report
+                .problems
+                .push(format!("{}: unreadable: {e}", display(page)));
+            return;
+        }
+    };
+    report.pages += 1;
+    for (url, target) in crate::links::relative_links(&html, page) {
+        report.links += 1;
+        let target = out_dir.join(target);
+        let exists = if target.is_dir() {
+            target.join("index.html").is_file()
+        } else {
+            target.is_file()
+        };
+        if !exists {
+            report
+                .problems
+                .push(format!("{}: broken link {url}", display(page)));
+        }
+    }
+}
+
+fn check_json(out_dir: &Path, file: &Path, report: &mut SelftestReport) {
+    report.json_files += 1;
+    let contents = match fs::read_to_string(out_dir.join(file)) {
+        Ok(contents) => contents,
+        Err(e) => {
+            report
+                .problems
+                .push(format!("{}: unreadable: {e}", display(file)));
+            return;
+        }
+    };
+    if file.extension().is_some_and(|e| e == "jsonl") {
+        for (i, line) in contents.lines().enumerate() {
+            if let Err(e) = serde_json::from_str::<serde_json::Value>(line) {
+                report
+                    .problems
+                    .push(format!("{}:{}: invalid JSON: {e}", display(file), i + 1));
+            }
+        }
+    } else if let Err(e) = serde_json::from_str::<serde_json::Value>(&contents) {
+        report
+            .problems
+            .push(format!("{}: invalid JSON: {e}", display(file)));
+    }
+}
+
+pub fn selftest(out_dir: &Path) -> anyhow::Result<SelftestReport> {
+    if !out_dir.is_dir() {
+        bail!("{} is not a directory", out_dir.display());
+    }
+    let mut report = SelftestReport::default();
+    let mut files = Vec::new();
+    list_files(out_dir, out_dir, &mut files)?;
+    files.sort();
+    for file in &files {
+        match file.extension().and_then(|e| e.to_str()) {
+            Some("html") => check_page(out_dir, file, &mut report),
+            Some("json" | "jsonl") => check_json(out_dir, file, &mut report),
+            _ => {}
+        }
+    }
+
+    // One manifest, or one per rank_N/ report
+    for (dir, manifest) in load_reports(out_dir)? {
+        let prefix = dir.strip_prefix(out_dir).unwrap_or(Path::new(""));
+        for entry in &manifest.files {
+            report.manifest_files += 1;
+            let path = prefix.join(&entry.path);
+            match fs::metadata(out_dir.join(&path)) {
+                Err(_) => report.problems.push(format!(
+                    "{}: listed in the manifest but missing",
+                    display(&path)
+                )),
+                Ok(metadata) if entry.kind != "page" && metadata.len() != entry.bytes => {
+                    report.problems.push(format!(
+                        "{}: {} bytes, the manifest says {}",
+                        display(&path),
+                        metadata.len(),
+                        entry.bytes
+                    ))
+                }
+                Ok(_) => {}
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,163 @@
+//! Access control and logging for `--serve`.  Reports often contain proprietary model details
+//! and the server listens on every interface, so `--auth user:pass` puts it behind HTTP basic
+//! auth, and `--access-log` records who fetched what.
+//!
+//! Served files carry an `ETag` and `Last-Modified` from their size and modification time, so
+//! a browser reloading a page with a big graph dump gets `304 Not Modified` instead of the
+//! file again.
+
+use anyhow::{bail, Context};
+use base64::Engine;
+use std::fs::{File, Metadata, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// Realm sent with `401 Unauthorized`, shown by browsers in the login prompt
+pub const AUTH_REALM: &str = "tlparse";
+
+/// `--auth user:pass`: the `Authorization` header value a client must send
+#[derive(Debug, Clone)]
+pub struct BasicAuth {
+    user: String,
+    expected: String,
+}
+
+impl BasicAuth {
+    pub fn parse(credentials: &str) -> anyhow::Result<Self> {
+        let Some((user, password)) = credentials.split_once(':') else {
+            bail!("Expected --auth USER:PASSWORD, got `{credentials}`");
+        };
+        if user.is_empty() || password.is_empty() {
+            bail!("--auth needs both a user and a password");
+        }
+        Ok(Self {
+            user: user.to_string(),
+            expected: format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            ),
+        })
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// Whether an `Authorization` header value grants access
+    pub fn check(&self, header: Option<&str>) -> bool {
+        let Some(header) = header else {
+            return false;
+        };
+        // Compare every byte so the time taken doesn't tell how much of a guess was right
+        let (a, b) = (header.trim().as_bytes(), self.expected.as_bytes());
+        a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+}
+
+/// `--access-log [FILE]`: one line per request in the Common Log Format, to FILE or, with `-`,
+/// to stdout
+pub struct AccessLog {
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl AccessLog {
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let out: Box<dyn Write + Send> = if path == "-" {
+            Box::new(std::io::stdout())
+        } else {
+            let file: File = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(Path::new(path))
+                .with_context(|| format!("Opening access log {path}"))?;
+            Box::new(file)
+        };
+        Ok(Self::new(out))
+    }
+
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out: Mutex::new(out),
+        }
+    }
+
+    /// `10.0.0.5 - alice [14/Oct/2026:15:00:03 +0000] "GET /index.html HTTP/1.1" 200 5120`
+    pub fn record(
+        &self,
+        remote: Option<SocketAddr>,
+        user: Option<&str>,
+        request_line: &str,
+        status: u16,
+        bytes: Option<usize>,
+    ) {
+        let line = format!(
+            "{} - {} [{}] \"{}\" {} {}\n",
+            remote.map_or("-".to_string(), |r| r.ip().to_string()),
+            user.unwrap_or("-"),
+            chrono::Utc::now().format("%d/%b/%Y:%H:%M:%S %z"),
+            request_line.replace('"', "\\\""),
+            status,
+            bytes.map_or("-".to_string(), |b| b.to_string()),
+        );
+        let mut out = self.out.lock().unwrap();
+        // A full disk shouldn't stop the server
+        let _ = out.write_all(line.as_bytes());
+        let _ = out.flush();
+    }
+}
+
+/// The `ETag` and `Last-Modified` of a served file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: String,
+    pub last_modified: String,
+    /// Modification time in whole seconds since the epoch, the precision of `Last-Modified`
+    modified_secs: i64,
+}
+
+impl Validators {
+    /// From the file's size and modification time; None if the platform has no mtime
+    pub fn of(metadata: &Metadata) -> Option<Self> {
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        let modified_secs = i64::try_from(modified.as_secs()).ok()?;
+        let last_modified = chrono::DateTime::from_timestamp(modified_secs, 0)?
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        Some(Self {
+            etag: format!(
+                "\"{:x}-{:x}\"",
+                metadata.len(),
+                modified.as_nanos() & u128::from(u64::MAX)
+            ),
+            last_modified,
+            modified_secs,
+        })
+    }
+}
+
+/// The conditional headers of a request
+#[derive(Debug, Default)]
+pub struct CacheHeaders {
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<String>,
+}
+
+impl CacheHeaders {
+    /// Whether the copy the client has of a file with `validators` is current.  `If-None-Match`
+    /// wins over `If-Modified-Since` when both are sent, as RFC 9110 says
+    pub fn is_fresh(&self, validators: &Validators) -> bool {
+        if let Some(tags) = &self.if_none_match {
+            return tags
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == validators.etag);
+        }
+        self.if_modified_since
+            .as_deref()
+            .and_then(|since| chrono::DateTime::parse_from_rfc2822(since).ok())
+            .is_some_and(|since| since.timestamp() >= validators.modified_secs)
+    }
+}
@@ -0,0 +1,159 @@
+//! File-centric index of the report (`sources.html`): every user source file that shows up in
+//! a compile stack, guard, specialization or graph break, with links to where it does.
+
+use fxhash::FxHashSet;
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{
+    simplify_filename, symbolicate_filename, unintern_str, CompileId, SourceFileRow,
+    SourceOccurrence, SourcesContext, StackSummary,
+};
+
+// `  File "/home/user/model.py", line 12, in forward` in Python tracebacks
+static TRACEBACK_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"File "(?P<file>[^"]+)", line (?P<line>\d+), in (?P<name>\S+)"#).unwrap()
+});
+
+pub const KIND_COMPILE: &str = "compile stack";
+pub const KIND_GRAPH_BREAK: &str = "graph break";
+pub const KIND_FAILURE: &str = "failure";
+pub const KIND_GUARD: &str = "guard";
+pub const KIND_SPECIALIZATION: &str = "specialization";
+pub const KIND_OTHER: &str = "other event";
+
+/// Files of PyTorch itself, the standard library, installed packages and synthetic modules,
+/// which aren't user code
+pub fn is_framework_file(filename: &str) -> bool {
+    filename.starts_with('<')
+        || filename.contains("/lib/python")
+        || filename.starts_with("torch/")
+        || filename.contains("/torch/")
+        || filename.contains("site-packages/")
+        || filename.contains("dist-packages/")
+}
+
+#[derive(Debug, Default)]
+pub struct SourceIndex {
+    /// File -> occurrences in log order
+    files: IndexMap<String, Vec<SourceOccurrence>>,
+    seen: FxHashSet<(String, &'static str, String, i32)>,
+}
+
+fn compile_id_str(compile_id: &Option<CompileId>) -> String {
+    compile_id
+        .as_ref()
+        .map_or("(unknown)".to_string(), |c| c.to_string())
+}
+
+impl SourceIndex {
+    pub fn add_frame(
+        &mut self,
+        kind: &'static str,
+        compile_id: &Option<CompileId>,
+        filename: &str,
+        line: i32,
+        name: &str,
+    ) {
+        let filename = symbolicate_filename(filename);
+        let filename = simplify_filename(&filename);
+        if is_framework_file(filename) {
+            return;
+        }
+        let compile_id = compile_id_str(compile_id);
+        if !self
+            .seen
+            .insert((filename.to_string(), kind, compile_id.clone(), line))
+        {
+            return;
+        }
+        self.files
+            .entry(filename.to_string())
+            .or_default()
+            .push(SourceOccurrence {
+                kind,
+                url: format!("index.html#{compile_id}"),
+                compile_id,
+                line,
+                name: name.to_string(),
+            });
+    }
+
+    pub fn add_stack(
+        &mut self,
+        kind: &'static str,
+        compile_id: &Option<CompileId>,
+        stack: &StackSummary,
+    ) {
+        for frame in stack {
+            let filename = frame
+                .uninterned_filename
+                .clone()
+                .unwrap_or_else(|| unintern_str(frame.filename));
+            self.add_frame(kind, compile_id, &filename, frame.line, &frame.name);
+        }
+    }
+
+    /// User frames of the traceback in a `dynamo_graph_break_reason` artifact
+    pub fn add_graph_break(&mut self, compile_id: &Option<CompileId>, payload: &str) {
+        for caps in TRACEBACK_FRAME.captures_iter(payload) {
+            let line = caps["line"].parse().unwrap_or(0);
+            self.add_frame(
+                KIND_GRAPH_BREAK,
+                compile_id,
+                &caps["file"],
+                line,
+                &caps["name"],
+            );
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Files with the most occurrences first
+    pub fn context(self) -> SourcesContext {
+        let mut files: Vec<SourceFileRow> = self
+            .files
+            .into_iter()
+            .map(|(filename, mut occurrences)| {
+                let mut counts: IndexMap<&str, usize> = IndexMap::new();
+                for o in &occurrences {
+                    *counts.entry(o.kind).or_default() += 1;
+                }
+                occurrences.sort_by_key(|o| o.line);
+                SourceFileRow {
+                    anchor: filename.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+                    filename,
+                    num_occurrences: occurrences.len(),
+                    counts: counts
+                        .iter()
+                        .map(|(kind, n)| match n {
+                            1 => format!("1 {kind}"),
+                            n => format!("{n} {kind}s"),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    occurrences,
+                }
+            })
+            .collect();
+        files.sort_by(|a, b| {
+            b.num_occurrences
+                .cmp(&a.num_occurrences)
+                .then_with(|| a.filename.cmp(&b.filename))
+        });
+        SourcesContext {
+            css: crate::templates::CSS,
+            qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT,
+            num_files: files.len(),
+            files,
+        }
+    }
+}
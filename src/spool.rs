@@ -0,0 +1,179 @@
+//! Spilling outputs to disk during the parse once they hold more than `--max-memory`, for rank
+//! logs of tens of gigabytes whose artifacts don't fit in memory.
+//!
+//! The outputs are appended to a spill file in the temp directory, oldest first, until half the
+//! budget is left, and recorded in `artifact_index.json` like the payloads `--low-memory` leaves
+//! in the log; once the rest of the report is written they are copied out one at a time and the
+//! spill file is removed.  A parse that fails, or whose output is dropped without being written,
+//! removes the spill file when its `Spool` goes.  The copy of the log as `raw.log` goes through the spill file too, so
+//! the log is never read into memory whole.
+//!
+//! Pages rendered from templates stay in memory: their markers are filled in after the parse,
+//! and with `--report-model` the report model finds them by their contents.
+
+use anyhow::Context;
+use fxhash::FxHashMap;
+use md5::{Digest, Md5};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::lazy::LazyArtifact;
+use crate::types::ParseOutput;
+
+/// Smaller outputs aren't worth an entry in the artifact index
+const MIN_SPILL_BYTES: usize = 4 << 10;
+
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) struct Spool {
+    budget: u64,
+    base_url: Option<String>,
+    file: Option<(PathBuf, BufWriter<File>)>,
+    offset: u64,
+    /// Bytes of output contents in memory
+    retained: u64,
+    /// Outputs counted towards `retained` so far
+    counted: usize,
+    /// Outputs before this one were already considered for spilling
+    next: usize,
+}
+
+/// Md5 of everything written through it, as the artifact index wants it
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Md5,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Spool {
+    pub fn new(budget: u64, base_url: Option<String>) -> Self {
+        Self {
+            budget,
+            base_url,
+            file: None,
+            offset: 0,
+            retained: 0,
+            counted: 0,
+            next: 0,
+        }
+    }
+
+    /// Append `contents` to the spill file, returning its location in the artifact index
+    fn write(&mut self, contents: &mut dyn Read) -> anyhow::Result<LazyArtifact> {
+        if self.file.is_none() {
+            let path = std::env::temp_dir().join(format!(
+                "tlparse-spill-{}-{}",
+                std::process::id(),
+                SPILL_FILES.fetch_add(1, Ordering::Relaxed)
+            ));
+            let file = File::create(&path)
+                .with_context(|| format!("Creating spill file {}", path.display()))?;
+            self.file = Some((path, BufWriter::new(file)));
+        }
+        let (path, file) = self.file.as_mut().unwrap();
+        let mut writer = HashingWriter {
+            inner: file,
+            hasher: Md5::new(),
+        };
+        let bytes = io::copy(contents, &mut writer)
+            .with_context(|| format!("Writing spill file {}", path.display()))?;
+        let artifact = LazyArtifact {
+            offset: self.offset,
+            lines: 0,
+            md5: format!("{:x}", writer.hasher.finalize()),
+            spill_file: Some(path.clone()),
+            bytes: Some(bytes),
+        };
+        self.offset += bytes;
+        Ok(artifact)
+    }
+
+    /// Count the outputs added since the last call and spill the oldest ones if they went over
+    /// the budget
+    pub fn track(
+        &mut self,
+        output: &mut ParseOutput,
+        lazy_artifacts: &mut FxHashMap<String, LazyArtifact>,
+        dropped_bytes: &mut FxHashMap<PathBuf, u64>,
+    ) -> anyhow::Result<()> {
+        for (_, contents) in &output[self.counted..] {
+            self.retained += contents.len() as u64;
+        }
+        self.counted = output.len();
+        if self.retained <= self.budget {
+            return Ok(());
+        }
+        while self.next < output.len() && self.retained > self.budget / 2 {
+            let (path, contents) = &mut output[self.next];
+            self.next += 1;
+            let is_html = path.extension().is_some_and(|e| e == "html");
+            if contents.len() < MIN_SPILL_BYTES
+                || lazy_artifacts.contains_key(&crate::paths::url(path))
+                || (is_html && crate::report_model::is_rendered(contents))
+            {
+                continue;
+            }
+            let bytes = contents.len() as u64;
+            // Spilled outputs are gone by the time the report's links are rewritten
+            if let (Some(base_url), true) = (&self.base_url, is_html) {
+                *contents = crate::links::rewrite_links(contents, path, base_url);
+            }
+            let (path, contents) = (path.clone(), std::mem::take(contents));
+            let artifact = self.write(&mut contents.as_bytes())?;
+            self.retained -= bytes;
+            dropped_bytes.insert(path.clone(), bytes);
+            lazy_artifacts.insert(crate::paths::url(&path), artifact);
+        }
+        if let Some((_, file)) = &mut self.file {
+            // Pages rendered from the spilled outputs read them back
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// `raw.log`, the (decompressed) log copied to the spill file without reading it into
+    /// memory
+    pub fn spill_log(
+        &mut self,
+        log: &Path,
+        output: &mut ParseOutput,
+        lazy_artifacts: &mut FxHashMap<String, LazyArtifact>,
+    ) -> anyhow::Result<()> {
+        let mut reader = crate::compression::LogReader::open(log)?;
+        let artifact = self.write(&mut reader)?;
+        if let Some((_, file)) = &mut self.file {
+            file.flush()?;
+        }
+        let path = PathBuf::from("raw.log");
+        lazy_artifacts.insert(crate::paths::url(&path), artifact);
+        output.push((path, String::new()));
+        Ok(())
+    }
+
+    /// Leave the spill file to the parse output, whose artifact index points into it
+    pub fn hand_off(mut self) {
+        self.file = None;
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        if let Some((path, file)) = self.file.take() {
+            drop(file);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! What `--strict` and `--strict-compile-id` failed on.  A strict parse that finds problems
+//! still renders the whole report, with `strict_failures.json` listing each line or artifact
+//! at fault, and hands it back in a [`StrictError`] so that callers can write it before
+//! failing: a CI job gating on strictness then leaves an artifact to look at, not just a
+//! non-zero exit.
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+use crate::types::{CompileId, OutputFile, ParseOutput, Stats};
+
+pub const STRICT_FAILURES_FILENAME: &str = "strict_failures.json";
+
+/// Failures listed one by one; the counts in `stats` cover the rest
+const MAX_FAILURES: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrictFailure {
+    /// The `Stats` counter the failure counts towards, or `missing_compile_id`
+    pub kind: &'static str,
+    /// Line of the log, from 1
+    pub line: Option<usize>,
+    pub compile_id: Option<String>,
+    /// The parser or output file at fault
+    pub artifact: Option<String>,
+    pub message: String,
+}
+
+/// `strict_failures.json`
+#[derive(Serialize)]
+struct StrictReport<'a> {
+    reasons: &'a [String],
+    stats: &'a Stats,
+    num_failures: usize,
+    /// Whether failures past the first `MAX_FAILURES` were left out
+    truncated: bool,
+    failures: Vec<StrictFailure>,
+}
+
+/// The compile id of a failure, unless its envelope had none
+pub(crate) fn compile_id(compile_id: &Option<CompileId>) -> Option<String> {
+    compile_id
+        .as_ref()
+        .filter(|c| c.frame_id.is_some())
+        .map(|c| c.to_string())
+}
+
+/// The failures of one log, recorded as parsing hits them
+#[derive(Default)]
+pub(crate) struct StrictFailures {
+    failures: Mutex<(Vec<StrictFailure>, usize)>,
+}
+
+impl StrictFailures {
+    pub fn record(&self, failure: StrictFailure) {
+        let mut failures = self.failures.lock().unwrap();
+        failures.1 += 1;
+        if failures.0.len() < MAX_FAILURES {
+            failures.0.push(failure);
+        }
+    }
+
+    /// `strict_failures.json`: why the parse failed, the counters of `stats` and each failure,
+    /// followed by the artifacts logged without a compile id when that is what failed
+    pub fn report(
+        &self,
+        reasons: &[String],
+        stats: &Stats,
+        without_compile_id: &[OutputFile],
+    ) -> anyhow::Result<String> {
+        let (mut failures, mut total) = self.failures.lock().unwrap().clone();
+        for file in without_compile_id {
+            total += 1;
+            if failures.len() < MAX_FAILURES {
+                failures.push(StrictFailure {
+                    kind: "missing_compile_id",
+                    line: None,
+                    compile_id: None,
+                    artifact: Some(file.url.clone()),
+                    message: format!("{} was logged without a compile id", file.name),
+                });
+            }
+        }
+        Ok(serde_json::to_string_pretty(&StrictReport {
+            reasons,
+            stats,
+            num_failures: total,
+            truncated: total > failures.len(),
+            failures,
+        })?)
+    }
+}
+
+/// A strict parse that failed, with the report it rendered anyway (`strict_failures.json`
+/// included)
+pub struct StrictError {
+    pub message: String,
+    pub output: ParseOutput,
+}
+
+impl std::fmt::Debug for StrictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StrictError")
+            .field("message", &self.message)
+            .field("files", &self.output.len())
+            .finish()
+    }
+}
+
+impl std::fmt::Display for StrictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for StrictError {}
@@ -0,0 +1,256 @@
+//! `suggested_config.py`: concrete `torch.compile` settings derived from the guards and
+//! recompiles of the log, for the user to review and apply.
+//!
+//! * A frame compiled at least `DEFAULT_CACHE_SIZE_LIMIT` times hit, or is about to hit, the
+//!   recompile limit, after which Dynamo runs it eagerly: raise
+//!   `torch._dynamo.config.cache_size_limit`.
+//! * A tensor dim whose size differs between the compiles of a frame (in the size of its
+//!   `TENSOR_MATCH` guards, or a `size mismatch` recompile reason) caused a recompile per size:
+//!   mark it dynamic up front with `torch._dynamo.mark_dynamic`.
+
+use indexmap::{IndexMap, IndexSet};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{CompilationMetricsMetadata, CompileId};
+
+pub const SUGGESTED_CONFIG_FILENAME: &str = "suggested_config.py";
+
+/// `torch._dynamo.config.cache_size_limit` (`recompile_limit` in newer releases)
+const DEFAULT_CACHE_SIZE_LIMIT: usize = 8;
+
+// `check_tensor(L['x'], Tensor, torch.float32, size=[8, None], stride=[1, 1])`
+static CHECK_TENSOR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"check_tensor\((?P<source>[^,]+), [^\n]*?\bsize=\[(?P<size>[^\]]*)\]").unwrap()
+});
+// `tensor 'L['x']' size mismatch at index 0. expected 8, actual 16`
+static SIZE_MISMATCH: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"tensor '(?P<source>.+?)' size mismatch at index (?P<dim>\d+)\. expected (?P<expected>\d+), actual (?P<actual>\d+)",
+    )
+    .unwrap()
+});
+static LOCAL_SOURCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[LG]\['(?P<name>[^']+)'\](?P<rest>.*)$").unwrap());
+
+/// (compiled autograd id, frame id): the compiles of one frame
+type FrameKey = (Option<u32>, Option<u32>);
+
+/// (expression, dim, sizes seen) of a dim to mark dynamic
+type DynamicDim<'a> = (&'a str, usize, String);
+
+#[derive(Default)]
+struct Frame {
+    co_name: Option<String>,
+    location: Option<String>,
+    /// Frame compile ids with metrics
+    compiles: IndexSet<Option<u32>>,
+    /// Tensor source -> dim -> sizes seen across compiles, `None` once the dim was dynamic
+    dims: IndexMap<String, IndexMap<usize, IndexSet<Option<u64>>>>,
+}
+
+#[derive(Default)]
+pub struct SuggestionIndex {
+    frames: IndexMap<FrameKey, Frame>,
+}
+
+fn frame_key(compile_id: &Option<CompileId>) -> Option<FrameKey> {
+    let cid = compile_id.as_ref()?;
+    cid.frame_id?;
+    Some((cid.compiled_autograd_id, cid.frame_id))
+}
+
+/// The expression naming a guard source in the frame's scope: `L['x']` is `x`
+fn python_expr(source: &str) -> String {
+    match LOCAL_SOURCE.captures(source) {
+        Some(caps) => format!("{}{}", &caps["name"], &caps["rest"]),
+        None => source.to_string(),
+    }
+}
+
+/// The local a source is reached from, which the user has to pass in
+fn root_name(expr: &str) -> &str {
+    let end = expr
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(expr.len());
+    &expr[..end]
+}
+
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .trim_matches('_')
+        .to_string()
+}
+
+impl SuggestionIndex {
+    fn frame(&mut self, compile_id: &Option<CompileId>) -> Option<&mut Frame> {
+        let key = frame_key(compile_id)?;
+        Some(self.frames.entry(key).or_default())
+    }
+
+    pub fn add_metrics(&mut self, compile_id: &Option<CompileId>, m: &CompilationMetricsMetadata) {
+        let frame_compile_id = compile_id.as_ref().and_then(|c| c.frame_compile_id);
+        let Some(frame) = self.frame(compile_id) else {
+            return;
+        };
+        if frame.co_name.is_none() {
+            frame.co_name = m.co_name.clone();
+            frame.location = m.co_filename.as_ref().map(|f| {
+                let name = f.rsplit(['/', '\\']).next().unwrap_or(f);
+                match m.co_firstlineno {
+                    Some(line) => format!("{name}:{line}"),
+                    None => name.to_string(),
+                }
+            });
+        }
+        frame.compiles.insert(frame_compile_id);
+    }
+
+    /// `dynamo_guards` or `dynamo_cpp_guards_str` payload of a compile
+    pub fn add_guards(&mut self, compile_id: &Option<CompileId>, payload: &str) {
+        let Some(frame) = self.frame(compile_id) else {
+            return;
+        };
+        for caps in CHECK_TENSOR.captures_iter(payload) {
+            let dims = frame
+                .dims
+                .entry(python_expr(caps["source"].trim()))
+                .or_default();
+            for (dim, size) in caps["size"].split(',').map(str::trim).enumerate() {
+                if size.is_empty() {
+                    continue;
+                }
+                dims.entry(dim).or_default().insert(size.parse().ok());
+            }
+        }
+    }
+
+    /// `recompile_reasons` artifact payload
+    pub fn add_recompile_reasons(&mut self, compile_id: &Option<CompileId>, payload: &str) {
+        let Some(frame) = self.frame(compile_id) else {
+            return;
+        };
+        for caps in SIZE_MISMATCH.captures_iter(payload) {
+            let Ok(dim) = caps["dim"].parse() else {
+                continue;
+            };
+            let sizes = frame
+                .dims
+                .entry(python_expr(&caps["source"]))
+                .or_default()
+                .entry(dim)
+                .or_default();
+            sizes.insert(caps["expected"].parse().ok());
+            sizes.insert(caps["actual"].parse().ok());
+        }
+    }
+
+    /// The dims that changed between the compiles of each frame
+    fn dynamic_dims(&self) -> Vec<(&Frame, Vec<DynamicDim<'_>>)> {
+        self.frames
+            .values()
+            .filter_map(|frame| {
+                let dims: Vec<DynamicDim> = frame
+                    .dims
+                    .iter()
+                    .flat_map(|(expr, dims)| {
+                        dims.iter()
+                            .filter(|(_, sizes)| sizes.len() > 1)
+                            .map(|(dim, sizes)| {
+                                let sizes: Vec<String> = sizes
+                                    .iter()
+                                    .map(|s| s.map_or("dynamic".to_string(), |s| s.to_string()))
+                                    .collect();
+                                (expr.as_str(), *dim, sizes.join(", "))
+                            })
+                    })
+                    .collect();
+                (!dims.is_empty()).then_some((frame, dims))
+            })
+            .collect()
+    }
+
+    fn frames_over_limit(&self) -> Vec<&Frame> {
+        self.frames
+            .values()
+            .filter(|f| f.compiles.len() >= DEFAULT_CACHE_SIZE_LIMIT)
+            .collect()
+    }
+
+    /// For the index page, e.g. "2 tensor dims to mark dynamic", or `None` without suggestions
+    pub fn summary(&self) -> Option<String> {
+        let num_dims: usize = self.dynamic_dims().iter().map(|(_, d)| d.len()).sum();
+        let mut parts = Vec::new();
+        if !self.frames_over_limit().is_empty() {
+            parts.push("a higher cache_size_limit".to_string());
+        }
+        match num_dims {
+            0 => {}
+            1 => parts.push("1 tensor dim to mark dynamic".to_string()),
+            n => parts.push(format!("{n} tensor dims to mark dynamic")),
+        }
+        (!parts.is_empty()).then(|| parts.join(" and "))
+    }
+
+    /// Contents of `suggested_config.py`, or `None` without suggestions
+    pub fn render(&self) -> Option<String> {
+        self.summary()?;
+        let describe = |frame: &Frame| {
+            let name = frame.co_name.as_deref().unwrap_or("(unknown)");
+            match &frame.location {
+                Some(location) => format!("`{name}` ({location})"),
+                None => format!("`{name}`"),
+            }
+        };
+        let mut out = String::from(
+            "# Suggested torch.compile settings, generated by tlparse from the guards and recompiles\n\
+             # of the log.  Review each suggestion before applying it.\n\
+             import torch\n",
+        );
+
+        let over_limit = self.frames_over_limit();
+        if let Some(max) = over_limit.iter().map(|f| f.compiles.len()).max() {
+            out.push('\n');
+            for frame in &over_limit {
+                out.push_str(&format!(
+                    "# Frame {} was compiled {} times; past the limit Dynamo runs it eagerly.\n",
+                    describe(frame),
+                    frame.compiles.len()
+                ));
+            }
+            out.push_str("# Newer releases call this setting recompile_limit.\n");
+            out.push_str(&format!(
+                "torch._dynamo.config.cache_size_limit = {}\n",
+                (max * 2).next_power_of_two()
+            ));
+        }
+
+        let mut names: IndexSet<String> = IndexSet::new();
+        for (frame, dims) in self.dynamic_dims() {
+            let base = format!(
+                "mark_dynamic_{}",
+                identifier(frame.co_name.as_deref().unwrap_or("frame"))
+            );
+            let mut name = base.clone();
+            let mut n = 2;
+            while !names.insert(name.clone()) {
+                name = format!("{base}_{n}");
+                n += 1;
+            }
+            let args: IndexSet<&str> = dims.iter().map(|(expr, _, _)| root_name(expr)).collect();
+            out.push_str(&format!(
+                "\n\ndef {name}({}):\n    \"\"\"Call with the inputs of {} before its first compiled call\"\"\"\n",
+                args.into_iter().collect::<Vec<_>>().join(", "),
+                describe(frame)
+            ));
+            for (expr, dim, sizes) in dims {
+                out.push_str(&format!(
+                    "    torch._dynamo.mark_dynamic({expr}, {dim})  # sizes seen: {sizes}\n"
+                ));
+            }
+        }
+        Some(out)
+    }
+}
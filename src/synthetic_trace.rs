@@ -0,0 +1,234 @@
+//! A compilation timeline in Chrome trace format (`tlparse_trace.json`) built from the
+//! structured log itself, for logs of jobs that didn't emit `chromium_event`s.
+//!
+//! Each compile gets its own track: a span from its `dynamo_start` to its
+//! `compilation_metrics`, with the backend, Inductor and code generation spans its metrics time
+//! nested at its end, and markers where its graphs were logged, where it broke the graph,
+//! failed, autotuned or hit the caches.  The line times of the glog prefix have no year (see
+//! `activity`), so timestamps are microseconds since the start of the year the log was written
+//! in; the ranks of a run line up, which is what Perfetto needs to show them side by side.
+
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+use crate::types::{CompilationMetricsMetadata, Envelope};
+
+pub const TRACE_FILENAME: &str = "tlparse_trace.json";
+
+/// Phases of the metrics, outermost first, each nested at the end of the previous one
+const PHASES: [&str; 3] = ["backend_compile", "inductor_compile", "code_gen"];
+
+#[derive(Default)]
+struct Compile {
+    co_name: Option<String>,
+    /// Line time of the `dynamo_start`
+    start: Option<f64>,
+    /// Line time of the `compilation_metrics`
+    end: Option<f64>,
+    /// Last line seen of the compile, for compiles without metrics
+    last: f64,
+    /// Line time of the last `inductor_output_code`, where code generation ends
+    output_code: Option<f64>,
+    /// Seconds of the whole compile and of each of `PHASES`
+    entire_frame: Option<f64>,
+    phases: [Option<f64>; 3],
+    fail_type: Option<String>,
+    /// Name and line time of what the compile logged along the way
+    markers: Vec<(String, f64)>,
+}
+
+#[derive(Default)]
+pub(crate) struct SyntheticTrace {
+    rank: Option<u32>,
+    /// Compile id -> compile, in the order compiles were first seen
+    compiles: IndexMap<String, Compile>,
+}
+
+/// The marker of an envelope, if it's one the timeline shows
+fn marker(e: &Envelope) -> Option<String> {
+    let graphs = [
+        ("dynamo_output_graph", e.dynamo_output_graph.is_some()),
+        ("aot_joint_graph", e.aot_joint_graph.is_some()),
+        ("aot_forward_graph", e.aot_forward_graph.is_some()),
+        ("aot_backward_graph", e.aot_backward_graph.is_some()),
+        ("aot_inference_graph", e.aot_inference_graph.is_some()),
+        (
+            "inductor_post_grad_graph",
+            e.inductor_post_grad_graph.is_some(),
+        ),
+        ("inductor_output_code", e.inductor_output_code.is_some()),
+    ];
+    if let Some((name, _)) = graphs.iter().find(|(_, logged)| *logged) {
+        return Some(name.to_string());
+    }
+    let name = &e.artifact.as_ref()?.name;
+    let shown = name == "dynamo_graph_break_reason"
+        || name.contains("autotune")
+        || name.ends_with("_cache_hit")
+        || name.ends_with("_cache_miss")
+        || name.ends_with("_cache_bypass");
+    shown.then(|| name.clone())
+}
+
+impl SyntheticTrace {
+    pub fn is_empty(&self) -> bool {
+        self.compiles.is_empty()
+    }
+
+    /// A line of the log at `time`, in seconds since the start of the year
+    pub fn add(&mut self, e: &Envelope, time: f64) {
+        if self.rank.is_none() {
+            self.rank = e.rank;
+        }
+        let Some(cid) = &e.compile_id else {
+            return;
+        };
+        let compile = self.compiles.entry(cid.to_string()).or_default();
+        compile.last = compile.last.max(time);
+        if e.dynamo_start.is_some() {
+            compile.start.get_or_insert(time);
+        }
+        if e.inductor_output_code.is_some() {
+            compile.output_code = Some(time);
+        }
+        if let Some(name) = marker(e) {
+            compile.markers.push((name, time));
+        }
+        if let Some(m) = &e.compilation_metrics {
+            compile.add_metrics(m, time);
+        }
+    }
+
+    /// For the index page, e.g. "3 compiles over 4.2s"
+    pub fn summary(&self) -> String {
+        let spans: Vec<(f64, f64)> = self.compiles.values().map(Compile::span).collect();
+        let first = spans.iter().map(|s| s.0).fold(f64::INFINITY, f64::min);
+        let last = spans.iter().map(|s| s.1).fold(f64::NEG_INFINITY, f64::max);
+        format!(
+            "{} {} over {}",
+            spans.len(),
+            if spans.len() == 1 {
+                "compile"
+            } else {
+                "compiles"
+            },
+            crate::units::seconds((last - first).max(0.0), 1)
+        )
+    }
+
+    /// The trace, as a JSON array of trace events
+    pub fn to_json(&self) -> Value {
+        let pid = self.rank.unwrap_or(0);
+        let us = |seconds: f64| (seconds * 1e6).round();
+        let mut events = vec![json!({
+            "name": "process_name",
+            "ph": "M",
+            "pid": pid,
+            "args": {"name": format!("rank {pid} compiles")},
+        })];
+        for (tid, (cid, compile)) in self.compiles.iter().enumerate() {
+            let tid = tid + 1;
+            let title = match &compile.co_name {
+                Some(name) => format!("[{cid}] {name}"),
+                None => format!("[{cid}]"),
+            };
+            events.push(json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": pid,
+                "tid": tid,
+                "args": {"name": title},
+            }));
+            let (start, end) = compile.span();
+            let mut args = json!({"compile_id": cid});
+            if let Some(fail_type) = &compile.fail_type {
+                args["fail_type"] = json!(fail_type);
+            }
+            events.push(json!({
+                "name": title,
+                "cat": "compile",
+                "ph": "X",
+                "ts": us(start),
+                "dur": us(end) - us(start),
+                "pid": pid,
+                "tid": tid,
+                "args": args,
+            }));
+            for (name, phase_start, phase_end) in compile.phases(start, end) {
+                events.push(json!({
+                    "name": name,
+                    "cat": "compile",
+                    "ph": "X",
+                    "ts": us(phase_start),
+                    "dur": us(phase_end) - us(phase_start),
+                    "pid": pid,
+                    "tid": tid,
+                    "args": {"compile_id": cid},
+                }));
+            }
+            let failure = compile
+                .fail_type
+                .as_ref()
+                .map(|fail_type| (format!("failed: {fail_type}"), end));
+            for (name, time) in compile.markers.iter().cloned().chain(failure) {
+                events.push(json!({
+                    "name": name,
+                    "cat": "marker",
+                    "ph": "i",
+                    "s": "t",
+                    "ts": us(time),
+                    "pid": pid,
+                    "tid": tid,
+                    "args": {"compile_id": cid},
+                }));
+            }
+        }
+        Value::Array(events)
+    }
+}
+
+impl Compile {
+    fn add_metrics(&mut self, m: &CompilationMetricsMetadata, time: f64) {
+        self.end = Some(time);
+        self.co_name = m.co_name.clone();
+        self.entire_frame = m.entire_frame_compile_time_s;
+        self.phases = [
+            m.backend_compile_time_s,
+            m.inductor_compile_time_s,
+            m.code_gen_time_s,
+        ];
+        self.fail_type = m.fail_type.clone();
+    }
+
+    /// Start and end of the compile: from its `dynamo_start`, or as long before its metrics
+    /// as they say it took
+    fn span(&self) -> (f64, f64) {
+        let end = self.end.unwrap_or(self.last);
+        let start = self
+            .start
+            .or_else(|| self.entire_frame.map(|s| end - s))
+            .unwrap_or(end)
+            .min(end);
+        (start, end)
+    }
+
+    /// The `PHASES` the metrics have a time for, each ending where its parent ends (code
+    /// generation where the output code was logged) and clamped into it
+    fn phases(&self, start: f64, end: f64) -> Vec<(&'static str, f64, f64)> {
+        let mut phases = Vec::new();
+        let (mut parent_start, mut parent_end) = (start, end);
+        for (name, seconds) in PHASES.iter().zip(self.phases) {
+            let Some(seconds) = seconds.filter(|s| *s > 0.0) else {
+                continue;
+            };
+            let phase_end = match (*name, self.output_code) {
+                ("code_gen", Some(time)) => time.clamp(parent_start, parent_end),
+                _ => parent_end,
+            };
+            let phase_start = (phase_end - seconds).max(parent_start);
+            phases.push((*name, phase_start, phase_end));
+            (parent_start, parent_end) = (phase_start, phase_end);
+        }
+        phases
+    }
+}
@@ -0,0 +1,249 @@
+//! `tlparse tail LOG [--follow] [--compile-id 7/0]`: one human readable line per envelope,
+//! optionally for a single compile, streamed to the terminal as the log grows.  A lightweight
+//! alternative to `--serve --watch` for quick debugging sessions.
+
+use anyhow::{bail, Context};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::torch_logs::parse_compile_id;
+use crate::types::CompileId;
+
+pub(crate) static GLOG_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[VIWEC]\d{4} (?P<time>\d{2}:\d{2}:\d{2})\.\d{6} +\d+ [^:]+:\d+\] (?P<json>\{.*)$")
+        .unwrap()
+});
+
+/// Envelope keys that aren't the kind of the envelope
+pub(crate) const ENVELOPE_FIELDS: [&str; 7] = [
+    "rank",
+    "compiled_autograd_id",
+    "frame_id",
+    "frame_compile_id",
+    "attempt",
+    "has_payload",
+    "stack",
+];
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Which compile to show; without an attempt (`7/0`) every attempt of the compile matches
+#[derive(Debug, Clone)]
+pub struct CompileFilter {
+    compile_id: CompileId,
+    any_attempt: bool,
+}
+
+impl CompileFilter {
+    /// `7/0`, `7/0_1`, `!1/7/0`, optionally in brackets
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim().trim_start_matches('[').trim_end_matches(']');
+        let Some(compile_id) = parse_compile_id(s) else {
+            bail!("Invalid compile id `{s}`, expected e.g. 7/0 or 7/0_1");
+        };
+        Ok(Self {
+            compile_id,
+            any_attempt: !s.contains('_'),
+        })
+    }
+
+    pub(crate) fn matches(&self, compile_id: &CompileId) -> bool {
+        let want = &self.compile_id;
+        want.compiled_autograd_id == compile_id.compiled_autograd_id
+            && want.frame_id == compile_id.frame_id
+            && want.frame_compile_id == compile_id.frame_compile_id
+            && (self.any_attempt || want.attempt == Some(compile_id.attempt.unwrap_or(0)))
+    }
+}
+
+impl std::fmt::Display for CompileFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.compile_id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TailOptions {
+    /// Keep reading as the log grows
+    pub follow: bool,
+    pub compile_id: Option<CompileFilter>,
+    /// How many of the envelopes already in the log to show first
+    pub lines: usize,
+}
+
+/// Turns log lines into summaries, remembering the string table for filenames
+#[derive(Default)]
+struct Summarizer {
+    strings: FxHashMap<u64, String>,
+}
+
+fn basename(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+impl Summarizer {
+    fn filename(&self, frame: &Value) -> String {
+        match &frame["filename"] {
+            Value::Number(n) => n
+                .as_u64()
+                .and_then(|i| self.strings.get(&i))
+                .map_or_else(|| n.to_string(), |f| basename(f).to_string()),
+            Value::String(s) => basename(s).to_string(),
+            _ => "?".to_string(),
+        }
+    }
+
+    fn details(&self, kind: &str, metadata: &Value) -> String {
+        let str_field = |name: &str| metadata[name].as_str().unwrap_or_default().to_string();
+        match kind {
+            "dynamo_start" => metadata["stack"]
+                .as_array()
+                .and_then(|stack| stack.last())
+                .map(|frame| {
+                    format!(
+                        "{} ({}:{})",
+                        frame["name"].as_str().unwrap_or("?"),
+                        self.filename(frame),
+                        frame["line"]
+                    )
+                })
+                .unwrap_or_default(),
+            "compilation_metrics" => {
+                let mut details = str_field("co_name");
+                if let Some(time) = metadata["entire_frame_compile_time_s"].as_f64() {
+                    details.push_str(&format!(" in {}", crate::units::seconds(time, 2)));
+                }
+                if let Some(fail_type) = metadata["fail_type"].as_str() {
+                    details.push_str(&format!(
+                        ", failed with {fail_type}: {}",
+                        str_field("fail_reason")
+                    ));
+                }
+                details
+            }
+            "artifact" | "graph_dump" | "chromium_event" => str_field("name"),
+            _ => String::new(),
+        }
+    }
+
+    /// `15:00:03 [0/0] compilation_metrics  forward in 1.20s`, and the compile id of the line
+    fn summarize(&mut self, line: &str) -> Option<(Option<CompileId>, String)> {
+        let caps = GLOG_LINE.captures(line)?;
+        let Ok(Value::Object(envelope)) = serde_json::from_str::<Value>(&caps["json"]) else {
+            return None;
+        };
+        if let Some(Value::Array(entry)) = envelope.get("str") {
+            if let (Some(s), Some(i)) = (entry.first()?.as_str(), entry.get(1)?.as_u64()) {
+                self.strings.insert(i, s.to_string());
+            }
+            return None;
+        }
+        let compile_id: Option<CompileId> =
+            serde_json::from_value(Value::Object(envelope.clone())).ok();
+        let compile_id = compile_id.filter(|c| c.frame_id.is_some());
+        let (kind, metadata) = envelope
+            .iter()
+            .find(|(key, _)| !ENVELOPE_FIELDS.contains(&key.as_str()))?;
+        let mut summary = format!(
+            "{} {} {kind}",
+            &caps["time"],
+            compile_id
+                .as_ref()
+                .map_or("[-/-]".to_string(), |c| c.to_string())
+        );
+        let details = self.details(kind, metadata);
+        if !details.is_empty() {
+            summary.push_str("  ");
+            summary.push_str(&details);
+        }
+        if envelope.contains_key("has_payload") {
+            summary.push_str(" (+payload)");
+        }
+        Some((compile_id, summary))
+    }
+}
+
+/// Print summaries of the last `options.lines` envelopes of `path` to `out`, then, with
+/// `options.follow`, of every envelope appended to it until the process is stopped
+pub fn tail(path: &Path, options: &TailOptions, out: &mut dyn Write) -> anyhow::Result<()> {
+    if options.follow {
+        crate::compression::require_uncompressed(path, "--follow")?;
+    }
+    let mut file = File::open(path).with_context(|| format!("Opening {}", path.display()))?;
+    let mut summarizer = Summarizer::default();
+    let show = |compile_id: &Option<CompileId>| match &options.compile_id {
+        Some(filter) => compile_id.as_ref().is_some_and(|c| filter.matches(c)),
+        None => true,
+    };
+
+    let mut contents = Vec::new();
+    crate::compression::LogReader::open(path)?.read_to_end(&mut contents)?;
+    let mut offset = contents.len() as u64;
+    // A partially written last line is completed by a later read
+    let complete = contents
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let mut pending = contents.split_off(complete);
+    let mut last: VecDeque<String> = VecDeque::with_capacity(options.lines + 1);
+    for line in String::from_utf8_lossy(&contents).lines() {
+        if let Some((compile_id, summary)) = summarizer.summarize(line) {
+            if show(&compile_id) {
+                last.push_back(summary);
+                if last.len() > options.lines {
+                    last.pop_front();
+                }
+            }
+        }
+    }
+    for summary in last {
+        writeln!(out, "{summary}")?;
+    }
+    out.flush()?;
+    if !options.follow {
+        return Ok(());
+    }
+
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let len = file.metadata()?.len();
+        if len < offset {
+            writeln!(
+                out,
+                "--- {} was truncated, reading it from the start",
+                path.display()
+            )?;
+            offset = 0;
+            pending.clear();
+            summarizer = Summarizer::default();
+        }
+        if len == offset {
+            continue;
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let mut new = Vec::new();
+        file.read_to_end(&mut new)?;
+        offset += new.len() as u64;
+        pending.extend(new);
+        let Some(end) = pending.iter().rposition(|&b| b == b'\n') else {
+            continue;
+        };
+        let rest = pending.split_off(end + 1);
+        for line in String::from_utf8_lossy(&pending).lines() {
+            if let Some((compile_id, summary)) = summarizer.summarize(line) {
+                if show(&compile_id) {
+                    writeln!(out, "{summary}")?;
+                }
+            }
+        }
+        out.flush()?;
+        pending = rest;
+    }
+}
@@ -0,0 +1,136 @@
+//! Reference of the context every report template is rendered with (`_template_context.html`).
+//!
+//! Field lists are extracted from the struct definitions by build.rs, so they can't drift from
+//! the types; only the template -> context struct mapping below is maintained by hand.
+
+use fxhash::FxHashSet;
+use serde::Serialize;
+
+use crate::templates::CSS;
+
+#[derive(Debug, Serialize)]
+pub struct ContextField {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub doc: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContextStruct {
+    pub name: &'static str,
+    pub doc: &'static str,
+    pub source: &'static str,
+    pub fields: &'static [ContextField],
+}
+
+include!(concat!(env!("OUT_DIR"), "/context_structs.rs"));
+
+/// Template name -> context struct it is rendered with
+pub const TEMPLATE_CONTEXTS: &[(&str, &str)] = &[
+    ("index.html", "IndexContext"),
+    ("index.html (--export)", "ExportIndexContext"),
+    ("failures_and_restarts.html", "RestartsAndFailuresContext"),
+    ("dynamo_guards.html", "DynamoGuardsContext"),
+    ("dynamo_cpp_guards.html", "CppGuardsContext"),
+    ("compilation_metrics.html", "CompilationMetricsContext"),
+    ("memory_usage.html", "MemoryUsageContext"),
+    ("op_coverage.html", "OpCoverageContext"),
+    ("duplicate_graphs.html", "DuplicateGraphsContext"),
+    ("fallback_kernels.html", "FallbackKernelsContext"),
+    ("data_dependent.html", "DataDependentContext"),
+    ("graph_breaks.html", "GraphBreaksContext"),
+    ("recompiles.html", "RecompilesContext"),
+    ("collectives.html", "CollectivesContext"),
+    ("compile_times.html", "CompileTimesContext"),
+    ("graph_diff.html", "GraphDiffContext"),
+    ("cache_key_changes.html", "CacheKeyChangesContext"),
+    ("sources.html", "SourcesContext"),
+    ("compile_cancellation.html", "CompileCancellationContext"),
+    ("compiler_logs.html", "CompilerLogsContext"),
+    ("changes.html (--watch)", "ChangesContext"),
+    ("/browse/ (--serve)", "BrowseContext"),
+    (
+        "workspace landing page (tlparse workspace)",
+        "WorkspaceContext",
+    ),
+    ("compare.html", "CompareContext"),
+    ("<dir>/index.html", "DirectoryIndexContext"),
+    (
+        "bwd_compilation_metrics.html",
+        "BwdCompilationMetricsContext",
+    ),
+    (
+        "aot_autograd_backward_compilation_metrics.html",
+        "AOTAutogradBackwardCompilationMetricsContext",
+    ),
+    ("symbolic_guard_information.html", "SymbolicGuardContext"),
+    ("provenance_tracking.html", "ProvenanceContext"),
+    ("vllm_summary.html", "VllmSummaryContext"),
+    ("multi_rank_index.html", "MultiRankContext"),
+    ("vllm_workers.html", "VllmWorkersContext"),
+    (
+        "across_ranks/<compile>/<artifact>.html (multi-rank)",
+        "RankArtifactContext",
+    ),
+    ("diff index.html (tlparse diff)", "DiffContext"),
+];
+
+pub fn find_struct(name: &str) -> Option<&'static ContextStruct> {
+    CONTEXT_STRUCTS.iter().find(|s| s.name == name)
+}
+
+/// Identifiers in a type, e.g. `Vec<(String, Vec<OutputFile>)>` -> Vec, String, Vec, OutputFile
+fn type_names(ty: &str) -> impl Iterator<Item = &str> {
+    ty.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|s| !s.is_empty())
+}
+
+/// The context struct of a template followed by every struct reachable from its fields
+pub fn context_structs_for(context: &str) -> Vec<&'static ContextStruct> {
+    let mut seen = FxHashSet::default();
+    let mut result = Vec::new();
+    let mut queue = vec![context.to_string()];
+    while let Some(name) = queue.pop() {
+        let Some(s) = find_struct(&name) else {
+            continue;
+        };
+        if !seen.insert(s.name) {
+            continue;
+        }
+        result.push(s);
+        // Reverse so nested structs are listed in field order
+        for f in s.fields.iter().rev() {
+            queue.extend(type_names(f.ty).map(str::to_string));
+        }
+    }
+    result
+}
+
+#[derive(Serialize)]
+struct TemplateDoc {
+    template: &'static str,
+    context: &'static str,
+    structs: Vec<&'static ContextStruct>,
+}
+
+#[derive(Serialize)]
+struct TemplateContextDocsContext {
+    css: &'static str,
+    templates: Vec<TemplateDoc>,
+}
+
+pub fn render_template_context_docs() -> anyhow::Result<String> {
+    let tt = crate::report_model::templates(false)?;
+    let context = TemplateContextDocsContext {
+        css: CSS,
+        templates: TEMPLATE_CONTEXTS
+            .iter()
+            .map(|(template, context)| TemplateDoc {
+                template,
+                context,
+                structs: context_structs_for(context),
+            })
+            .collect(),
+    };
+    Ok(tt.render("_template_context.html", &context)?)
+}
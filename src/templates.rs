@@ -15,11 +15,16 @@ table td { vertical-align: top; }
 }
 .stack-trie a { text-decoration: none; }
 .stack-trie a:hover { text-decoration: underline; }
+.stack-trie .frame-repeat { color: #888; }
 .status-missing { background-color: purple; color: white; }
 .status-error { background-color: red; color: white; }
 .status-empty { background-color: white; color: black; }
 .status-ok { background-color: green; color: white; }
 .status-break { background-color: lime; color: black; }
+.status-cancelled { background-color: orange; color: black; }
+details.compiler-logs pre { margin-left: 1em; }
+.log-W { color: darkorange; }
+.log-E, .log-C { color: red; }
 summary::-webkit-details-marker { color: #00ACF3; font-size: 125%; margin-right: 2px; }
 summary:focus { outline-style: none; }
 article > details > summary { font-size: 28px; margin-top: 16px; }
@@ -31,6 +36,50 @@ details > p { margin-left: 24px; }
             margin: 16px 0;
         }
 details details summary { font-size: 16px; }
+.compare-link { font-size: smaller; color: #888; }
+.compile-warnings { list-style-type: none; padding-left: 0; }
+.compile-warnings li { margin: 4px 0; }
+.warning-error { color: #a00; }
+.warning-hint { color: #555; }
+.explanation { max-width: 60em; line-height: 1.5; }
+.frame-hidden { display: none; }
+.frame-origin { color: #555; margin-left: 8px; }
+.size-badge { font-size: smaller; padding: 0 4px; border-radius: 3px; background: #eee; color: #555; }
+.attempt-badge { font-size: smaller; padding: 0 4px; border-radius: 3px; background: #e0ecff; color: #1a4d8f; }
+.compile-label { font-size: smaller; padding: 0 4px; border-radius: 3px; background: #e6f4ea; color: #1e6b34; }
+li.earlier-attempt > a:first-child { color: #888; }
+.size-badge.size-warm { background: #fff1c2; color: #7a5a00; }
+.size-badge.size-hot { background: #ffd0a8; color: #8a3a00; }
+tr.data-dependent-problem { background: #fff0f0; }
+.collective-target { color: #888; font-size: smaller; }
+.collective-wait { color: #666; }
+.graph-diff-added { background: #e6ffec; }
+.graph-diff-removed { background: #ffebe9; text-decoration: line-through; }
+.graph-diff-changed, .graph-diff-rewired { background: #fff8c5; }
+.graph-diff-location, .graph-diff-note { color: #777; }
+.size-badge.size-huge { background: #d93025; color: white; font-weight: bold; }
+.report-metadata th { text-align: left; }
+.guard-diff { list-style-type: none; padding-left: 0; }
+.guard-added { color: #1a7f37; }
+.guard-removed { color: #a00; }
+.all-metrics .metrics-phase { text-align: left; padding-top: 8px; }
+.all-metrics .metrics-doc { color: #555; }
+.config-options td.documented code { text-decoration: underline dotted; cursor: help; }
+.rank-artifact-differs { color: #a00; font-weight: bold; }
+.divergence-matrix td { text-align: center; }
+.divergence-matrix td.divergence-same { background: #e6f4ea; }
+.divergence-matrix td.divergence-differs { background: #fbd3d0; color: #a00; font-weight: bold; }
+.divergence-matrix td.divergence-missing { background: #eee; }
+.rank-variants { display: flex; gap: 16px; align-items: flex-start; }
+.rank-variant { flex: 1; min-width: 30em; overflow-x: auto; }
+.rank-variant iframe { width: 100%; height: 80vh; border: 1px solid #ccc; }
+.compile-times th[onclick] { cursor: pointer; text-decoration: underline dotted; }
+.compile-times td, .compile-time-stats td { text-align: right; }
+.compile-time-failed { color: #a00; }
+.compile-times .cpu-time { color: #888; }
+.compile-time-waiting td.wait { background: #fff3cd; font-weight: bold; }
+.search-hits { margin-top: 0; }
+.search-lineno { display: inline-block; min-width: 4em; padding-right: 1em; text-align: right; color: #888; }
 "#;
 
 pub static JAVASCRIPT: &str = r#"
@@ -46,6 +95,141 @@ pub static JAVASCRIPT: &str = r#"
   }
 "#;
 
+/// Sorts the table of compile_times.html by the clicked column, descending first
+pub static COMPILE_TIMES_JAVASCRIPT: &str = r#"
+  function sortCompileTimes(header, column) {
+    const body = header.closest('table').tBodies[0];
+    const descending = header.dataset.order !== 'desc';
+    const value = (row) => {
+      const cell = row.cells[column];
+      const number = parseFloat(cell.dataset.value ?? cell.textContent);
+      return isNaN(number) ? -1 : number;
+    };
+    const rows = Array.from(body.rows);
+    rows.sort((a, b) => descending ? value(b) - value(a) : value(a) - value(b));
+    rows.forEach((row) => body.appendChild(row));
+    header.closest('tr').querySelectorAll('th').forEach((th) => delete th.dataset.order);
+    header.dataset.order = descending ? 'desc' : 'asc';
+  }
+"#;
+
+/// Line numbers and anchors of code pages, see `parsers::anchored_lines`
+pub static LINE_PERMALINK_CSS: &str = r#"
+.anchored-lines .line {
+    display: block;
+}
+.anchored-lines .line:target,
+.anchored-lines .line:has(.kernel-anchor:target) {
+    background-color: #fff8c5;
+}
+.anchored-lines .lineno {
+    display: inline-block;
+    min-width: 3em;
+    padding-right: 1em;
+    text-align: right;
+    color: #888;
+    text-decoration: none;
+    user-select: none;
+}
+.anchored-lines .lineno::before {
+    content: attr(data-line);
+}
+.anchored-lines .lineno:hover {
+    color: #0366d6;
+    text-decoration: underline;
+}
+.anchored-lines .lineno.copied::after {
+    content: " copied";
+    position: absolute;
+    color: #2da44e;
+}
+.kernel-index {
+    font-family: sans-serif;
+    margin-bottom: 1em;
+}
+.kernel-index ul {
+    margin: 0.5em 0;
+}
+"#;
+
+/// Clicking a line number of a code page selects the line and copies its permalink
+pub static LINE_PERMALINK_JAVASCRIPT: &str = r#"
+  document.addEventListener('click', (event) => {
+    const link = event.target.closest('a.lineno');
+    if (!link) return;
+    event.preventDefault();
+    window.location.hash = link.hash;
+    if (navigator.clipboard) {
+      navigator.clipboard.writeText(window.location.href).then(() => {
+        link.classList.add('copied');
+        setTimeout(() => link.classList.remove('copied'), 1500);
+      });
+    }
+  });
+"#;
+
+/// Hover cards of artifact links, see `previews`; the page's script tag names `previews.js`
+pub static PREVIEW_JAVASCRIPT: &str = r#"
+(() => {
+  const script = document.currentScript;
+  const source = new URL(script.dataset.previews, document.baseURI);
+  const root = new URL('.', source).href;
+  let previews = null;
+  let card = null;
+  let timer = null;
+  window.tlparsePreviewsLoaded = (loaded) => { previews = loaded; };
+  const load = () => {
+    if (document.getElementById('tlparse-previews')) return;
+    const tag = document.createElement('script');
+    tag.id = 'tlparse-previews';
+    tag.src = source.href;
+    document.head.appendChild(tag);
+  };
+  const preview = (link) => {
+    const url = new URL(link.href, document.baseURI);
+    if (!previews || !url.href.startsWith(root)) return null;
+    return previews[decodeURIComponent(url.href.slice(root.length).split(/[?#]/)[0])] || null;
+  };
+  const hide = () => {
+    clearTimeout(timer);
+    if (card) card.remove();
+    card = null;
+  };
+  const show = (link, [text, lines]) => {
+    hide();
+    card = document.createElement('div');
+    card.style.cssText = 'position:absolute;z-index:1000;max-width:60em;max-height:24em;' +
+      'overflow:hidden;background:#fff;border:1px solid #ccc;border-radius:4px;' +
+      'box-shadow:0 2px 8px rgba(0,0,0,.15);padding:0.5em;font-size:12px;pointer-events:none';
+    const title = document.createElement('div');
+    title.style.cssText = 'color:#666;margin-bottom:0.3em;font-family:sans-serif';
+    const shown = text.split('\n').length;
+    title.textContent = lines > shown ? `First ${shown} of ${lines} lines` : `${lines} lines`;
+    const pre = document.createElement('pre');
+    pre.style.cssText = 'margin:0;white-space:pre;font-size:12px';
+    pre.textContent = text;
+    card.append(title, pre);
+    const rect = link.getBoundingClientRect();
+    card.style.left = `${window.scrollX + rect.left}px`;
+    card.style.top = `${window.scrollY + rect.bottom + 4}px`;
+    document.body.appendChild(card);
+  };
+  document.addEventListener('mouseover', (event) => {
+    const link = event.target.closest && event.target.closest('a[href]');
+    if (!link) return;
+    load();
+    clearTimeout(timer);
+    timer = setTimeout(() => {
+      const found = preview(link);
+      if (found) show(link, found);
+    }, 300);
+  });
+  document.addEventListener('mouseout', (event) => {
+    if (event.target.closest && event.target.closest('a[href]')) hide();
+  });
+})();
+"#;
+
 pub static EXPORT_CSS: &str = r#"
 table {
     width: 90%;
@@ -94,197 +278,1516 @@ pub static TEMPLATE_DYNAMO_GUARDS: &str = r#"
 </html>
 "#;
 
-pub static TEMPLATE_INDEX: &str = r#"
+pub static TEMPLATE_CPP_GUARDS: &str = r#"
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+    {css}
+    .guard-tree, .guard-tree ul \{ list-style: none; padding-left: 2ch; }
+    .guard-tree summary \{ cursor: pointer; }
+    .guard-kind \{ font-weight: bold; font-family: monospace; }
+    .guard-count \{ color: #666; font-size: 0.9em; }
+    .guard-comment \{ color: #888; font-family: monospace; font-size: 0.9em; }
+    .guard-tree code \{ white-space: pre-wrap; }
+    .guard-hidden \{ display: none; }
+    </style>
+    <title>Guards {compile_id}</title>
+</head>
+<body>
+    <h1>Guards {compile_id}</h1>
+    <p>
+    {num_guards} guards in {num_managers} guard managers.  Each manager shows the number of guards
+    below it.  See the <a href='{raw_url}'>raw guard dump</a>.
+    </p>
+    <p>
+    <input type="search" id="guard-search" placeholder="Search guards" oninput="searchGuards(this.value)">
+    <span id="guard-matches"></span>
+    <button onclick="setGuardsOpen(true)">Expand all</button> <button onclick="setGuardsOpen(false)">Collapse all</button>
+    </p>
+    {tree_html | format_unescaped}
+    {{ if other_lines }}
+    <pre>{other_lines}</pre>
+    {{ endif }}
+    <script>
+    function setGuardsOpen(open) \{
+        document.querySelectorAll(".guard-tree details").forEach(d => d.open = open);
+    }
+    function searchGuards(query) \{
+        query = query.toLowerCase();
+        let matches = 0;
+        document.querySelectorAll(".guard-tree li.guard").forEach(li => \{
+            const hit = !query || li.textContent.toLowerCase().includes(query);
+            li.classList.toggle("guard-hidden", !hit);
+            if (hit && query) matches++;
+        });
+        // Innermost managers first, so a manager knows whether any child is still shown
+        const managers = Array.from(document.querySelectorAll(".guard-tree li.guard-manager")).reverse();
+        managers.forEach(li => \{
+            const summaryHit = query && li.querySelector("summary").textContent.toLowerCase().includes(query);
+            const shown = li.querySelector(":scope > details > ul > li:not(.guard-hidden)");
+            li.classList.toggle("guard-hidden", Boolean(query) && !summaryHit && !shown);
+            if (query) li.querySelector(":scope > details").open = Boolean(shown);
+        });
+        document.getElementById("guard-matches").textContent = query ? matches + " matching guards" : "";
+    }
+    </script>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_INDEX: &str = r#"
+<html>
+<head>
+  <meta charset="UTF-8">
+</head>
+<style>
+{css | format_unescaped}
+</style>
+<script>
+{javascript | format_unescaped}
+</script>
+<body>
+<div>
+{custom_header_html | format_unescaped}
+<!-- tlparse:rendering-errors -->
+{{ if metadata }}
+<table class="report-metadata">
+{{ for row in metadata }}
+<tr> <th> {row.0} </th> <td> {row.1} </td> </tr>
+{{ endfor }}
+</table>
+{{ endif }}
+{{ if vllm_view }}
+<div style="background: #e8f4fd; border: 1px solid #4a90d9; border-radius: 5px; padding: 10px 15px; margin-bottom: 20px;">
+vLLM artifacts were detected in this log.  <a href='index.html'>← Back to the vLLM compilation view</a>
+</div>
+{{ endif }}
+{{ if sampling }}
+<div class="warning-box">
+<p>{sampling}</p>
+</div>
+{{ endif }}
+{{ if output_collisions }}
+<div class="warning-box">
+<p>{output_collisions}  See <a href='output_collisions.json'>output_collisions.json</a>.</p>
+</div>
+{{ endif }}
+{{ if abnormal_end }}
+<div class="warning-box">
+<h2>Run ended abnormally</h2>
+<p>
+{abnormal_end.reason | format_unescaped}  Compile <a href='#{abnormal_end.compile_id}'>{abnormal_end.compile_id}</a> was in
+<strong>{abnormal_end.phase}</strong> when the log stopped, which is likely where the process crashed or was killed.
+</p>
+{{ if abnormal_end.message }}<pre>{abnormal_end.message}</pre>{{ endif }}
+{abnormal_end.stack_html | format_unescaped}
+</div>
+{{ endif }}
+{{ if compile_activity }}
+<h2>Compile activity</h2>
+<p>
+When compiles started over the run: {compile_activity}.  Compiles after the first half of the run,
+drawn in red, mean the job keeps compiling after warmup.
+</p>
+{compile_activity_html | format_unescaped}
+{{ endif }}
+{{ if search }}
+<h2>Search</h2>
+<p>{search}</p>
+<p>
+<input type="search" id="artifact-search" size="60" placeholder="e.g. aten.mm, s0, L['x'].size()[0]" oninput="searchArtifacts(this.value)">
+<span id="artifact-search-status"></span>
+</p>
+<div id="artifact-search-results"></div>
+<script>
+// Matching lines shown per file, and files shown
+const SEARCH_MAX_LINES = 20;
+const SEARCH_MAX_FILES = 50;
+let searchIndex = null;
+let searchTimer = null;
+
+async function loadSearchIndex() \{
+    if (!searchIndex) \{
+        const response = await fetch('search_index.json');
+        if (!response.ok) \{ throw new Error('search_index.json: ' + response.status); }
+        searchIndex = await response.json();
+    }
+    return searchIndex;
+}
+
+function searchArtifacts(query) \{
+    clearTimeout(searchTimer);
+    searchTimer = setTimeout(() => runSearch(query.trim()), 200);
+}
+
+async function runSearch(query) \{
+    const status = document.getElementById('artifact-search-status');
+    const results = document.getElementById('artifact-search-results');
+    results.replaceChildren();
+    status.textContent = '';
+    if (!query) \{ return; }
+    let index;
+    try \{
+        index = await loadSearchIndex();
+    } catch (err) \{
+        status.textContent = 'Could not load the search index (' + err.message +
+            '). Browsers block loading files from file:// pages; view the report with `tlparse --serve` instead.';
+        return;
+    }
+    const needle = query.toLowerCase();
+    let files = 0;
+    let matches = 0;
+    for (const file of index.files) \{
+        const hits = [];
+        file.lines.forEach((line, i) => \{ if (line.toLowerCase().includes(needle)) hits.push(i); });
+        if (hits.length === 0) \{ continue; }
+        files++;
+        matches += hits.length;
+        if (files > SEARCH_MAX_FILES) \{ continue; }
+        const heading = document.createElement('p');
+        const link = document.createElement('a');
+        link.href = file.url;
+        link.textContent = file.url;
+        heading.append(file.compile_id + ' ', link, ' (' + hits.length + ')');
+        const pre = document.createElement('pre');
+        pre.className = 'search-hits';
+        for (const i of hits.slice(0, SEARCH_MAX_LINES)) \{
+            const lineno = document.createElement(file.anchored ? 'a' : 'span');
+            if (file.anchored) \{ lineno.href = file.url + '#L' + (i + 1); }
+            lineno.className = 'search-lineno';
+            lineno.textContent = String(i + 1);
+            pre.append(lineno, file.lines[i] + '\n');
+        }
+        if (hits.length > SEARCH_MAX_LINES) \{
+            pre.append('... and ' + (hits.length - SEARCH_MAX_LINES) + ' more\n');
+        }
+        results.append(heading, pre);
+    }
+    status.textContent = matches + ' matching lines in ' + files + ' files' +
+        (files > SEARCH_MAX_FILES ? ', showing the first ' + SEARCH_MAX_FILES : '');
+}
+</script>
+{{ endif }}
+<h2>Stack trie</h2>
+<p>
+The <strong>stack trie</strong> is a way of getting a quick orientation on where all the
+compilations in a model take place, esp., if you are compiling a codebase you are unfamiliar with.
+It is a tree of stack frames, for all stacks that triggered PT2 compilation.  If only a single
+stack is in the tree, you will simply see a plain list of frames (most recent call last).  With
+multiple stacks, at every point where two stacks diverge from having a common prefix, we increase
+the indentation of the list and have a separate sub-list per sub-tree.
+</p>
+<p>
+Links to particular compilation are color coded by status:
+<span class="status-ok">[Success]</span>,
+<span class="status-break">[Success with restart (e.g., graph break)]</span>,
+<span class="status-empty">[Empty graph]</span>,
+<span class="status-error">[Error]</span>,
+<span class="status-cancelled">[Timed out or cancelled]</span>,
+<span class="status-missing">[Metrics were missing]</span>
+</p>
+{stack_trie_html | format_unescaped}
+{{ if num_source_files }}
+<p>
+To start from a file instead, see the <a href='sources.html'>{num_source_files} source files</a>
+referenced by compile stacks, guards and graph breaks.
+</p>
+{{ endif }}
+</div>
+<div>
+{{ if num_breaks }}
+<h2> Failures and Restarts </h2>
+<p>
+Various issues may cause Dynamo to restart its analysis or give up on compilation entirely, causing graph breaks and fallbacks to eager mode.
+This run had <strong><a href="failures_and_restarts.html">{num_breaks} restart(s) and/or compilation failure(s)</a></strong>.
+</p>
+{{ endif }}
+<h2>IR dumps</h2>
+<p>
+The <strong>IR dumps</strong> collected dumped intermediate products from various points of the PT2
+compilation process.  The products are organized by compile id, and then sorted in chronological
+order.
+</p>
+<p>
+A <strong>compile id</strong> uniquely identifies are particular compilation inside a PT2
+program.  It is traditionally written as <code>[x/y]</code>, where the <strong>frame id</strong> x
+identifies the particular Python frame which we are compiling, and <strong>frame compile
+id</strong> y identifies how many times we've recompiled this same frame.  For example,
+<code>[0/0]</code> refers to the very first frame compiled by PT2; <code>[0/1]</code> refers to the
+first recompilation of this frame, while <code>[1/0]</code> refers to a different frame, within
+distinct code cache, which we are compiling next (perhaps because of a graph break).  Although
+Dynamo treats distinct frames as completely unrelated, a frame compilation could overlap with another
+frame; for example, if you graph break in an inlined function, Dynamo will typically try to compile
+the nested frame again on an inner frame.  You can identify the hierarchical relationship between
+frames by looking at the stack trie above.
+</p>
+<p>
+In some situations, the compile id will have an extra signifier <code>[x/y_z]</code>, where z is the
+<strong>attempt</strong> for this particular (re)compilation.  Certain conditions will cause Dynamo to
+restart analysis, when Dynamo discovers that it needs to undo a decision it previously made.  The most
+common cause of recompilation is a graph break in an inlined function call, which forces to restart
+and avoid inlining the function in the first place.
+</p>
+<p>
+When compiled autograd is enabled, the compile id will include a prefix signifier <code>[!a/x/y]</code>,
+where a is the <strong>compiled autograd id</strong>. For instance, <code>[!0/-/-]</code> refers 
+to the first graph captured by compiled autograd. It is then traced by torch.compile as <code>[!0/x/y_z]</code>.
+</p>
+<p>
+Here is a high level description of PT2's compilation phases, and the intermediate products each
+phase generates:
+</p>
+<ol>
+<li><em>Optional:</em> If compiled autograd is enabled, and we are processing a backward call, compiled autograd will trace the autograd graph from the autograd engine, and produce an FX graph <code>compiled_autograd_graph</code> that will be Dynamo traced.  Otherwise, Dynamo will directly trace user's bytecode.</li>
+<li>Dynamo symbolically evaluates the Python bytecode of a program, producing <code>dynamo_output_graph</code></li>
+<li><em>Optional:</em> If <code>optimize_ddp</code> is enabled, the DDPOptimizer will split the Dynamo output graph to improve pipelining communications.  Each split subgraph is <code>optimize_ddp_split_child_submod</code>, and the high level graph that plumbs the graphs together is <code>optimize_ddp_split_graph</code>.  If there are multiple splits, each subsequent build product will be produced multiple times, one for each split.</li>
+<li>AOTAutograd traces the (possibly split) Dynamo output graph, producing a <code>aot_joint_graph</code> if backwards is enabled.  It then partitions the graph into <code>aot_forward_graph</code> and <code>aot_backward_graph</code>.  If training is not needed, there may only be an <code>aot_inference_graph</code>.</li>
+<li>Inductor will apply some post grad FX passes, producing <code>inductor_post_grad_graph</code></li>
+<li>Inductor will perform code generation, producing the final <code>inductor_output_code</code> which will be executed at runtime.  This output is a valid Python program and can be directly run.</li>
+</ol>
+
+{{ if has_chromium_events }}
+<h2> Chromium Events </h2>
+PT2 generates <a href='chromium_events.json'>Chromium Trace Events</a> in JSON on specific events during compilation.
+You can download and view them in a tool like <a href='https://ui.perfetto.dev/'>Perfetto</a>.
+{{ endif  }}
+
+{{ if compile_trace }}
+<h2> Compilation Timeline </h2>
+<p>
+From the structured log, {compile_trace}: <a href='tlparse_trace.json'>tlparse_trace.json</a> has a
+track per compile id with its Dynamo, backend, Inductor and code generation spans, and markers for
+its graphs, graph breaks, failures, autotuning and cache lookups.  Open it in
+<a href='https://ui.perfetto.dev/'>Perfetto</a>, even for jobs that didn't log Chromium events.
+</p>
+{{ endif }}
+
+{{ if derived_metric_rows }}
+<h2> Derived Metrics </h2>
+<p>Per-compile metrics computed from the expressions in the config file.</p>
+<table>
+<tr> <th> Compile Id </th> {{ for name in derived_metric_names }}<th> {name} </th> {{ endfor }}</tr>
+{{ for row in derived_metric_rows }}
+<tr> <td> <a href="{row.url}">{row.compile_id}</a> </td> {{ for value in row.values }}<td> {value} </td> {{ endfor }}</tr>
+{{ endfor }}
+</table>
+{{ endif }}
+
+{{ if has_memory_usage }}
+<h2> Memory Usage </h2>
+<p>
+Compilation metrics in this log include process memory counters.
+See <a href='memory_usage.html'>process memory across compile ids</a> to spot memory that keeps growing from one compile to the next.
+</p>
+{{ endif }}
+
+{{ if op_coverage }}
+<h2> Operator Coverage </h2>
+<p>
+Compiled graphs cover <strong>{op_coverage}</strong> of the operator calls in the eager op trace.
+See <a href='op_coverage.html'>operator coverage</a> for the operators that ran outside compiled regions.
+</p>
+{{ endif }}
+
+{{ if duplicate_graphs_wasted }}
+<h2> Duplicate Graphs </h2>
+<p>
+{num_duplicate_compiles} compile ids compiled a graph identical to an earlier one, an estimated
+<strong>{duplicate_graphs_wasted}</strong> of compile time.  See <a href='duplicate_graphs.html'>duplicate graphs</a>.
+</p>
+{{ endif }}
+
+{{ if fallback_kernels }}
+<h2> Fallback Kernels </h2>
+<p>
+In the code Inductor generated, {fallback_kernels}.  See <a href='fallback_kernels.html'>fallback kernels</a>
+for the ops and the compile ids calling them.
+</p>
+{{ endif }}
+{{ if recompiles }}
+<h2> Recompiles </h2>
+<p>
+{recompiles}.  See <a href='recompiles.html'>recompiles</a> for every recompile of each frame
+and the guards that failed to trigger it.
+</p>
+{{ endif }}
+{{ if graph_breaks }}
+<h2> Graph Breaks </h2>
+<p>
+{graph_breaks}.  See <a href='graph_breaks.html'>graph breaks</a> for each unique break, how
+often it was hit and where first.
+</p>
+{{ endif }}
+{{ if data_dependent }}
+<h2> Data-Dependent Operations </h2>
+<p>
+This run has {data_dependent}.  See <a href='data_dependent.html'>data-dependent operations</a>
+for their stacks and whether they graph broke, failed the compile or became runtime asserts.
+</p>
+{{ endif }}
+{{ if compile_times }}
+<h2> Compile Times </h2>
+<p>
+{compile_times}.  See <a href='compile_times.html'>compile times</a> for the Dynamo,
+AOTAutograd and Inductor time of every compile, sortable, with totals and percentiles.
+</p>
+{{ endif }}
+{{ if collectives }}
+<h2> Collectives </h2>
+<p>
+Inductor logged {collectives}.  See <a href='collectives.html'>collectives</a> for the
+collectives each compiled graph issues, in order.
+</p>
+{{ endif }}
+{{ if cache_key_changes }}
+<h2> Cache Key Changes </h2>
+<p>
+Compared with the baseline run, {cache_key_changes}.  See <a href='cache_key_changes.html'>cache key changes</a>
+for the components that changed.
+</p>
+{{ endif }}
+{{ if code_size_outliers }}
+<h2> Generated Code Size Outliers </h2>
+<p>
+These compiles generated at least 4x the median {median_generated_code} of Inductor code per
+compile, which usually shows up as long compiles and a bloated cache:
+</p>
+<ul>
+{{ for outlier in code_size_outliers }}
+<li><a href='#{outlier.compile_id}'>{outlier.compile_id}</a>: {outlier.generated_code} ({outlier.times_median}x the median)</li>
+{{ endfor }}
+</ul>
+{{ endif }}
+
+{{ if suggested_config }}
+<h2> Suggested Config </h2>
+<p>
+From the guards and recompiles of this log, <a href='suggested_config.py'>suggested_config.py</a>
+suggests {suggested_config}.  Review the suggestions before applying them.
+</p>
+{{ endif }}
+
+{{ if device_backends }}
+<h2> Device Backends </h2>
+<ul>
+{{ for backend in device_backends }}
+<li><strong>{backend.label}</strong>{{ if backend.evidence }}: {{ for reason in backend.evidence }}{{ if @first }}{{ else }}, {{ endif }}{reason}{{ endfor }}{{ endif }}
+{{ if backend.artifacts }}
+<details class="device-backend" id="device-{backend.namespace}">
+<summary>{backend.num_artifacts} {backend.label} artifacts</summary>
+<ul>
+{{ for artifact in backend.artifacts }}
+<li>{artifact.compile_id}: <a href="{artifact.url}">{artifact.name}</a></li>
+{{ endfor }}
+</ul>
+</details>
+{{ endif }}
+</li>
+{{ endfor }}
+</ul>
+{{ endif }}
+
+{{ if milestones }}
+<h2> Milestones </h2>
+<p>Detected notable events from Chromium trace:</p>
+<ul>
+  {{ for m in milestones }}
+    <li><strong>{m.name}</strong> at {m.timestamp}</li>
+  {{ endfor }}
+</ul>
+{{ endif }}
+
+{{ if collectives_parity }}
+<p>
+<a href="collectives_parity.json">Collectives Parity report</a> comparing scheduler and Inductor output code collective operations.
+</p>
+{{ endif }}
+{{ if framework_compiles }}
+<h2> Framework-internal Compiles </h2>
+<p>
+These compiles are of PyTorch distributed internals (FSDP, distributed checkpoint, state dict
+handling) rather than of model code:
+</p>
+<ul>
+{{ for group in framework_compiles }}
+<li><strong>{group.label}</strong> ({group.num_compiles}): {{ for compile_id in group.compile_ids }}<a href='#{compile_id}'>{compile_id}</a> {{ endfor }}</li>
+{{ endfor }}
+</ul>
+<p>
+<label><input type="checkbox" id="hide-framework-compiles" onchange="toggleFrameworkCompiles(this.checked)"> Hide framework-internal compiles in the build products below</label>
+</p>
+<script>
+const frameworkCompileIds = {framework_compile_ids_json | format_unescaped};
+function toggleFrameworkCompiles(hide) \{
+  for (const compileId of frameworkCompileIds) \{
+    const anchor = document.getElementById(compileId);
+    if (anchor) \{
+      anchor.parentNode.style.display = hide ? 'none' : '';
+    }
+  }
+}
+</script>
+{{ endif }}
+{{ if frame_presets }}
+<p id="frame-presets">
+Show:
+{{ for preset in frame_presets }}
+{{ if preset.selectable }}<label><input type="radio" name="frame-preset" value="{preset.key}" onchange="applyFramePreset(this.value)"{{ if preset.checked }} checked{{ endif }}> {preset.label} ({preset.num_compiles})</label>
+{{ else }}<span class="frame-origin">{preset.num_compiles} in {preset.label}</span>
+{{ endif }}{{ endfor }}
+</p>
+<script>
+const frameOrigins = {frame_origins_json | format_unescaped};
+function applyFramePreset(preset) \{
+  for (const [compileId, origin] of Object.entries(frameOrigins)) \{
+    const anchor = document.getElementById(compileId);
+    if (anchor) \{
+      anchor.parentNode.classList.toggle('frame-hidden', preset === 'user');
+    }
+  }
+}
+{{ if hide_framework_frames }}document.addEventListener('DOMContentLoaded', () => applyFramePreset('user'));{{ endif }}
+</script>
+{{ endif }}
+<p>
+Build products below:
+</p>
+<ul>
+{{ for compile_directory in directory }}
+    <li{{ if compile_directory.3 }}{{ if not compile_directory.3.is_latest }} class="earlier-attempt"{{ endif }}{{ endif }}><a id="{compile_directory.0}">{compile_directory.0}</a>{{ if compile_directory.4 }} <span class="compile-label">{compile_directory.4}</span>{{ endif }}{{ if compile_directory.3 }} <span class="attempt-badge">{{ if compile_directory.3.is_latest }}latest of {compile_directory.3.num_attempts} attempts{{ if compile_directory.3.latest_compile_time }}, {compile_directory.3.latest_compile_time}{{ endif }}{{ if compile_directory.3.latest_failed }}, failed{{ endif }}{{ else }}attempt {compile_directory.3.attempt} of {compile_directory.3.num_attempts}, superseded by <a href='#{compile_directory.3.latest}'>{compile_directory.3.latest}</a>{{ endif }}</span>{{ endif }}{{ if compile_directory.2 }} <span class="size-badge {compile_directory.2.size_heat}" title="Generated code and the artifacts of the compile, not counting pages">{{ if compile_directory.2.generated_code }}{compile_directory.2.generated_code} code, {{ endif }}{compile_directory.2.artifacts} of artifacts{{ if compile_directory.2.outlier }}, outlier{{ endif }}</span>{{ endif }}
+    <ul>
+        {{ for path_idx in compile_directory.1 }}
+            <li><a href="{path_idx.url}">{path_idx.name}</a>{{ if path_idx.readable_url }} (<a href="{path_idx.readable_url}">readable_html</a>){{ endif }} {path_idx.suffix} ({path_idx.number}){{ if path_idx.size }} <span class="size-badge {path_idx.size_heat}">{path_idx.size}</span>{{ endif }}{{ if path_idx.compare_url }} <a class="compare-link" href="{path_idx.compare_url}">compare with…</a>{{ endif }}</li>
+        {{ endfor }}
+    </ul>
+    </li>
+{{ endfor }}
+</ul>
+</div>
+
+
+{{ if has_inductor_provenance }}
+<h2>Provenance Tracking</h2>
+<div>
+    <p>View detailed provenance tracking information for each rank and frame:</p>
+    <ul>
+    {{ for directory_name in directory_names }}
+        <li><a href='provenance_tracking_{directory_name}.html'>provenance_tracking_{directory_name}</a></li>
+    {{ endfor }}
+    </ul>
+</div>
+{{ endif }}
+
+{{ if has_unknown_stack_trie }}
+<div>
+<h2>Unknown stacks</h2>
+<p>
+  Sometimes, logs are made without a compile id.  This makes it difficult to correlate related
+  logs.  This stack trie shows all places where log entries occurred without compile context; to
+  fix, look an appropriate place in the stack where compile id should have been specified.
+</p>
+{unknown_stack_trie_html | format_unescaped}
+</div>
+{{ endif }}
+{qps | format_unescaped}
+<!-- tlparse:previews -->
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_FAILURES_CSS: &str = r#"
+table {
+    width: 90%;
+    border-collapse: collapse;
+    margin: 20px 0;
+}
+table, th, td {
+    border: 1px solid #999;
+    padding: 10px;
+    text-align: left;
+}
+th {
+    background-color: #d3d3d3;
+    font-weight: bold;
+}
+tr:nth-child(odd) {
+    background-color: #f2f2f2;
+}
+a {
+    color: #0066cc;
+    text-decoration: none;
+}
+a:hover {
+    text-decoration: underline;
+}
+"#;
+
+pub static TEMPLATE_FAILURES_AND_RESTARTS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+</head>
+<body>
+    <h1>Failures and Restarts</h1>
+    <table>
+    <tr> <th> Compile Id </th> <th> Failure Type </th> <th> Failure Description </th> <th> Failure Source (compilation failures only) </th> </tr>
+    {{ for failure in failures }}
+    <tr> <td> {failure.0 | format_unescaped} </td>{failure.1 | format_unescaped}</tr>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static COMPARE_JAVASCRIPT: &str = r#"
+  const params = new URLSearchParams(window.location.search);
+
+  function populate(select, selected) {
+    const empty = document.createElement('option');
+    empty.value = '';
+    empty.textContent = '(choose an artifact)';
+    select.appendChild(empty);
+    for (const artifact of ARTIFACTS) {
+      const option = document.createElement('option');
+      option.value = artifact.url;
+      option.textContent = artifact.label;
+      option.selected = artifact.url === selected;
+      select.appendChild(option);
+    }
+  }
+
+  // Myers O(ND) line diff; returns a list of ['=', i, j], ['-', i, -1] and ['+', -1, j] ops
+  function diffLines(a, b) {
+    const n = a.length, m = b.length, max = n + m;
+    const v = new Array(2 * max + 2).fill(0);
+    const trace = [];
+    for (let d = 0; d <= max; d++) {
+      trace.push(v.slice());
+      for (let k = -d; k <= d; k += 2) {
+        let x = (k === -d || (k !== d && v[k - 1 + max] < v[k + 1 + max]))
+          ? v[k + 1 + max] : v[k - 1 + max] + 1;
+        let y = x - k;
+        while (x < n && y < m && a[x] === b[y]) { x++; y++; }
+        v[k + max] = x;
+        if (x >= n && y >= m) {
+          return backtrack(trace, n, m, max);
+        }
+      }
+    }
+    return [];
+  }
+
+  function backtrack(trace, n, m, max) {
+    let x = n, y = m;
+    const ops = [];
+    for (let d = trace.length - 1; d >= 0; d--) {
+      const v = trace[d];
+      const k = x - y;
+      const prevK = (k === -d || (k !== d && v[k - 1 + max] < v[k + 1 + max])) ? k + 1 : k - 1;
+      const prevX = v[prevK + max], prevY = prevX - prevK;
+      while (x > prevX && y > prevY) { ops.push(['=', x - 1, y - 1]); x--; y--; }
+      if (d > 0) {
+        if (x === prevX) { ops.push(['+', -1, y - 1]); } else { ops.push(['-', x - 1, -1]); }
+      }
+      x = prevX; y = prevY;
+    }
+    return ops.reverse();
+  }
+
+  function cell(row, lineno, text, cls) {
+    const num = row.insertCell();
+    num.className = 'lineno';
+    num.textContent = lineno;
+    const code = row.insertCell();
+    code.className = cls;
+    code.textContent = text;
+  }
+
+  function render(a, b) {
+    const ops = diffLines(a, b);
+    const left = document.getElementById('left-pane').querySelector('tbody');
+    const right = document.getElementById('right-pane').querySelector('tbody');
+    left.innerHTML = '';
+    right.innerHTML = '';
+    let changed = 0;
+    // Pair runs of removals with the insertions that follow so edits line up
+    for (let i = 0; i < ops.length;) {
+      if (ops[i][0] === '=') {
+        cell(left.insertRow(), ops[i][1] + 1, a[ops[i][1]], '');
+        cell(right.insertRow(), ops[i][2] + 1, b[ops[i][2]], '');
+        i++;
+        continue;
+      }
+      const removed = [], added = [];
+      while (i < ops.length && ops[i][0] === '-') { removed.push(ops[i][1]); i++; }
+      while (i < ops.length && ops[i][0] === '+') { added.push(ops[i][2]); i++; }
+      changed += Math.max(removed.length, added.length);
+      for (let r = 0; r < Math.max(removed.length, added.length); r++) {
+        if (r < removed.length) { cell(left.insertRow(), removed[r] + 1, a[removed[r]], 'removed'); }
+        else { cell(left.insertRow(), '', '', 'filler'); }
+        if (r < added.length) { cell(right.insertRow(), added[r] + 1, b[added[r]], 'added'); }
+        else { cell(right.insertRow(), '', '', 'filler'); }
+      }
+    }
+    document.getElementById('status').textContent =
+      changed === 0 ? 'Artifacts are identical.' : changed + ' changed line(s).';
+  }
+
+  async function load() {
+    const leftUrl = document.getElementById('left-select').value;
+    const rightUrl = document.getElementById('right-select').value;
+    const query = new URLSearchParams();
+    if (leftUrl) { query.set('left', leftUrl); }
+    if (rightUrl) { query.set('right', rightUrl); }
+    window.history.replaceState(null, '', '?' + query.toString());
+    if (!leftUrl || !rightUrl) {
+      document.getElementById('status').textContent = 'Choose two artifacts to compare.';
+      return;
+    }
+    try {
+      const [a, b] = await Promise.all([leftUrl, rightUrl].map(async (url) => {
+        const response = await fetch(BASE_URL ? BASE_URL + '/' + url : url);
+        if (!response.ok) { throw new Error(url + ': ' + response.status); }
+        return (await response.text()).split('\n');
+      }));
+      render(a, b);
+    } catch (err) {
+      document.getElementById('status').textContent = 'Could not load artifacts (' + err.message +
+        '). Browsers block loading files from file:// pages; view the report with `tlparse --serve` instead.';
+    }
+  }
+
+  function syncScroll(from, to) {
+    let syncing = false;
+    from.addEventListener('scroll', () => {
+      if (syncing) { syncing = false; return; }
+      syncing = true;
+      to.scrollTop = from.scrollTop;
+      to.scrollLeft = from.scrollLeft;
+    });
+  }
+
+  document.addEventListener('DOMContentLoaded', () => {
+    populate(document.getElementById('left-select'), params.get('left'));
+    populate(document.getElementById('right-select'), params.get('right'));
+    document.getElementById('left-select').addEventListener('change', load);
+    document.getElementById('right-select').addEventListener('change', load);
+    syncScroll(document.getElementById('left-pane'), document.getElementById('right-pane'));
+    syncScroll(document.getElementById('right-pane'), document.getElementById('left-pane'));
+    load();
+  });
+"#;
+
+pub static TEMPLATE_COMPARE: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    .panes \{ display: flex; gap: 8px; }
+    .pane \{ flex: 1; height: 80vh; overflow: auto; border: 1px solid #ccc; }
+    .pane table \{ border-collapse: collapse; font-family: monospace; font-size: 12px; width: 100%; }
+    .pane td \{ white-space: pre; padding: 0 4px; }
+    .pane td.lineno \{ color: #999; text-align: right; user-select: none; }
+    .pane td.removed \{ background-color: #fdd; }
+    .pane td.added \{ background-color: #dfd; }
+    .pane td.filler \{ background-color: #f4f4f4; }
+    .pickers select \{ width: 45%; }
+    </style>
+    <title>Compare Artifacts</title>
+    <script>
+    const ARTIFACTS = {artifacts_json | format_unescaped};
+    const BASE_URL = "{base_url}";
+    {javascript | format_unescaped}
+    </script>
+</head>
+<body>
+{custom_header_html | format_unescaped}
+<h1>Compare Artifacts</h1>
+<div class="pickers">
+    <select id="left-select"></select>
+    <select id="right-select"></select>
+</div>
+<p id="status"></p>
+<div class="panes">
+    <div class="pane" id="left-pane"><table><tbody></tbody></table></div>
+    <div class="pane" id="right-pane"><table><tbody></tbody></table></div>
+</div>
+{qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_MEMORY_USAGE: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Process Memory Usage</title>
+</head>
+<body>
+    <h1>Process Memory Usage</h1>
+    <p>
+    Process memory sampled at the end of each compile, in log order.  Memory that keeps growing
+    across compile ids (total growth: <strong>{total_rss_growth}</strong> RSS) usually points at a
+    compile-time leak.
+    </p>
+    {chart_svg | format_unescaped}
+    <table>
+    <tr> <th> Compile Id </th> <th> RSS </th> <th> RSS Delta </th> <th> PSS </th> <th> PSS Delta </th> </tr>
+    {{ for row in rows }}
+    <tr> <td> {row.compile_id} </td> <td> {row.rss} </td> <td> {row.rss_delta} </td> <td> {row.pss} </td> <td> {row.pss_delta} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_SOURCES: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Source Files</title>
+</head>
+<body>
+    <h1>Source Files</h1>
+    <p>
+    The {num_files} user source files referenced by compile stacks, guards, specializations,
+    graph breaks and compile failures, with the most referenced first.  Files of PyTorch and of
+    installed packages are left out.  Each occurrence links to its compile id on the index.
+    </p>
+    <table>
+    <tr> <th> File </th> <th> Occurrences </th> </tr>
+    {{ for file in files }}
+    <tr> <td> <a href='#{file.anchor}'><code>{file.filename}</code></a> </td> <td> {file.counts} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ for file in files }}
+    <h2 id="{file.anchor}"><code>{file.filename}</code></h2>
+    <ul>
+    {{ for o in file.occurrences }}
+    <li>line {o.line}{{ if o.name }} in <code>{o.name}</code>{{ endif }}: {o.kind} in <a href="{o.url}">{o.compile_id}</a></li>
+    {{ endfor }}
+    </ul>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_DUPLICATE_GRAPHS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Duplicate Graphs</title>
+</head>
+<body>
+    <h1>Duplicate Graphs</h1>
+    <p>
+    Compile ids whose Dynamo graphs are identical up to the numbering of names, such as the
+    same decoder layer compiled once per instance.  This usually points at a missed cache hit
+    or per-instance compilation.  The {num_duplicate_compiles} repeated compiles took an
+    estimated <strong>{total_wasted}</strong>, counting every compile of a cluster but the first.
+    </p>
+    {{ for cluster in clusters }}
+    <h2>Graph <code>{cluster.fingerprint}</code>: {cluster.num_compiles} compiles, {cluster.wasted} wasted</h2>
+    <table>
+    <tr> <th> Compile Id </th> <th> Compile Time </th> </tr>
+    {{ for compile in cluster.compiles }}
+    <tr> <td> <a href="{compile.url}">{compile.compile_id}</a> </td> <td> {compile.compile_time} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_FALLBACK_KERNELS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Fallback Kernels</title>
+</head>
+<body>
+    <h1>Fallback Kernels</h1>
+    <p>
+    Ops the code generated by Inductor calls as eager kernels instead of generated ones.
+    <strong>Fallbacks</strong> ({num_fallback_ops}) are ATen or custom ops Inductor couldn't lower
+    and runs through their eager implementation; they are the usual reason compiled code is
+    slower than expected.  <strong>Extern kernels</strong> ({num_extern_kernels}) are library
+    kernels, such as cuBLAS matmuls, called instead of a generated template.  Calls counts call
+    sites in the generated code, not executions.
+    </p>
+    <table>
+    <tr> <th> Op </th> <th> Kind </th> <th> Calls </th> <th> Compile Ids </th> </tr>
+    {{ for row in rows }}
+    <tr> <td> <code>{row.op}</code> </td> <td> {row.kind} </td> <td> {row.calls} </td>
+    <td> {{ for compile in row.compiles }}{{ if compile.url }}<a href="{compile.url}">{compile.compile_id}</a>{{ else }}{compile.compile_id}{{ endif }} ({compile.calls}) {{ endfor }}</td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_DATA_DEPENDENT: &str = r#"
+<html>
+<head>
+    <style>
+    {css | format_unescaped}
+    </style>
+    <title>Data-Dependent Operations</title>
+</head>
+<body>
+    <h1>Data-Dependent Operations</h1>
+    <p>
+    This run has {summary}.  Ops whose result depends on tensor data, like <code>x.item()</code>
+    or <code>torch.nonzero(mask)</code>, produce <strong>unbacked symbols</strong>: sizes and
+    scalars the compiler can't know.  Guards on them can't be checked at compile time and are
+    deferred to <strong>runtime asserts</strong>; a guard the compiler has to decide right away
+    fails the compile (<code>GuardOnDataDependentSymNode</code>), and Dynamo
+    <strong>graph breaks</strong> on data-dependent control flow.  {num_problems} of them broke
+    the graph or failed the compile; <code>torch._check</code> on the value, or
+    <code>capture_scalar_outputs</code> / <code>capture_dynamic_output_shape_ops</code>, usually
+    avoid that.
+    </p>
+    <table class="data-dependent">
+    <tr> <th> Compile Id </th> <th> Kind </th> <th> Operation </th> <th> User Code </th> <th> Outcome </th> </tr>
+    {{ for row in rows }}
+    <tr{{ if row.problem }} class="data-dependent-problem"{{ endif }}> <td> <a href="index.html#{row.compile_id}">{row.compile_id}</a> </td> <td> {row.kind} </td>
+    <td> <code>{row.operation}</code>{{ if row.detail }} ({row.detail}){{ endif }} </td>
+    <td> {row.location_html | format_unescaped}{row.stack_html | format_unescaped} </td>
+    <td> {row.outcome}{{ if row.runtime_asserts }}<ul>{{ for assert in row.runtime_asserts }}<li><code>{assert}</code></li>{{ endfor }}</ul>{{ endif }} </td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_RECOMPILES: &str = r#"
+<html>
+<head>
+    <style>
+    {css | format_unescaped}
+    </style>
+    <title>Recompiles</title>
+</head>
+<body>
+    <h1>Recompiles</h1>
+    <p>
+    {summary}.  A frame recompiles when a guard of every compile cached for it fails, e.g. on an
+    input of another size or dtype.  Each frame below starts with its original compile, with
+    every recompile under it and the guards that failed to trigger it; frames with the most
+    compiles come first.
+    </p>
+    {{ for frame in frames }}
+    <h2>{frame.label} <code>{frame.co_name}</code> {frame.location}: {frame.num_compiles} compiles</h2>
+    {{ if frame.top_reasons }}
+    <p>Most frequent reasons:</p>
+    <ul>
+    {{ for r in frame.top_reasons }}<li>{r.count} &times; <code>{r.reason}</code></li>
+    {{ endfor }}</ul>
+    {{ endif }}
+    <ul class="recompile-tree">
+    <li><a href="index.html#{frame.original.compile_id}">{frame.original.compile_id}</a> original compile{{ if frame.original.failed }}, <span class="status-error">failed</span>{{ endif }}{{ if frame.original.restarts }}, restarted {frame.original.restarts} times{{ endif }}
+    <ul>
+    {{ for c in frame.recompiles }}
+    <li><a href="index.html#{c.compile_id}">{c.compile_id}</a> recompiled{{ if c.failed }}, <span class="status-error">failed</span>{{ endif }}{{ if c.restarts }}, restarted {c.restarts} times{{ endif }}
+    {{ if c.reasons }}because
+    <ul>{{ for r in c.reasons }}<li><code>{r}</code></li>{{ endfor }}</ul>
+    {{ else }}{{ if c.previous_compile_id }}without logging why; the guards of {c.previous_compile_id} it doesn't have, one of which failed:
+    <ul>{{ for g in c.lost_guards }}<li><code>{g}</code></li>{{ endfor }}</ul>
+    {{ else }}without logging why{{ endif }}{{ endif }}
+    </li>
+    {{ endfor }}
+    </ul>
+    </li>
+    </ul>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_GRAPH_BREAKS: &str = r#"
+<html>
+<head>
+    <style>
+    {css | format_unescaped}
+    </style>
+    <title>Graph Breaks</title>
+</head>
+<body>
+    <h1>Graph Breaks</h1>
+    <p>
+    Dynamo logged {summary}.  Breaks with the same reason and user stack are listed once, most
+    frequent first, with the compile that hit them first; fixing the top ones removes the most
+    graph breaks.
+    </p>
+    <table class="graph-breaks">
+    <tr> <th> Count </th> <th> Reason </th> <th> User Code </th> <th> First Compile </th> <th> Also In </th> </tr>
+    {{ for row in rows }}
+    <tr> <td> {row.count} </td> <td> <code>{row.reason}</code> </td>
+    <td> {row.location}{{ if row.traceback }}<details><summary>User code traceback</summary><pre>{row.traceback}</pre></details>{{ endif }} </td>
+    <td> <a href="index.html#{row.first_compile_id}">{row.first_compile_id}</a> </td>
+    <td> {{ for compile_id in row.other_compile_ids }}<a href="index.html#{compile_id}">{compile_id}</a> {{ endfor }}</td> </tr>
+    {{ endfor }}
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_COLLECTIVES: &str = r#"
+<html>
+<head>
+    <style>
+    {css | format_unescaped}
+    </style>
+    <title>Collectives</title>
+</head>
+<body>
+    <h1>Collectives</h1>
+    <p>
+    Inductor logged {summary}: the collective ops of each compiled graph, in the order its
+    generated code issues them.  Every rank has to issue the same collectives in the same order;
+    a rank whose schedule differs waits on collectives the others never join, and the job hangs.
+    </p>
+    {{ for schedule in schedules }}
+    <h2>Compile <a href='index.html#{schedule.compile_id}'>{schedule.compile_id}</a>: {schedule.counts}</h2>
+    <ol class="collective-schedule">
+    {{ for op in schedule.ops }}
+    <li class="collective-{op.kind}"><code>{op.op}</code> <span class="collective-target">{op.target}</span></li>
+    {{ endfor }}
+    </ol>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_COMPILE_TIMES: &str = r#"
+<html>
+<head>
+    <style>
+    {css | format_unescaped}
+    </style>
+    <title>Compile Times</title>
+    <script>
+    {javascript | format_unescaped}
+    </script>
+</head>
+<body>
+    <h1>Compile Times</h1>
+    <p>
+    {summary}.  Dynamo is the time a compile spent outside of the backend (tracing the frame,
+    building guards and bytecode), AOTAutograd the backend's time outside of Inductor.  Click a
+    column to sort by it.
+    {{ if has_cpu }}Where the metrics have CPU times too, each time is wall / CPU, and Wait is the wall
+    time a compile spent off the CPU: waiting on locks, compile subprocesses or a remote cache.
+    Compiles that waited for most of their time are highlighted.{{ endif }}
+    </p>
+    <table class="compile-time-stats">
+    <tr> <th></th> <th> Dynamo </th> <th> AOTAutograd </th> <th> Inductor </th> <th> Total </th>{{ if has_cpu }} <th> Wait </th>{{ endif }} </tr>
+    {{ for stat in stats }}
+    <tr> <th> {stat.name} </th> <td> {stat.dynamo} </td> <td> {stat.aot} </td> <td> {stat.inductor} </td> <td> {stat.total} </td>{{ if has_cpu }} <td> {stat.wait} </td>{{ endif }} </tr>
+    {{ endfor }}
+    </table>
+    <p>{legend_html | format_unescaped}</p>
+    <table class="compile-times">
+    <thead>
+    <tr>
+    <th onclick="sortCompileTimes(this, 0)"> Compile Id </th>
+    <th> Frame </th>
+    <th> Status </th>
+    <th onclick="sortCompileTimes(this, 3)"> Dynamo </th>
+    <th onclick="sortCompileTimes(this, 4)"> AOTAutograd </th>
+    <th onclick="sortCompileTimes(this, 5)"> Inductor </th>
+    <th onclick="sortCompileTimes(this, 6)"> Total </th>
+    {{ if has_cpu }}<th onclick="sortCompileTimes(this, 7)"> Wait </th>{{ endif }}
+    {{ for column in derived_metric_columns }}<th onclick="sortCompileTimes(this, {column.1})"> {column.0} </th>{{ endfor }}
+    <th></th>
+    </tr>
+    </thead>
+    <tbody>
+    {{ for row in rows }}
+    <tr class="compile-time-{row.status}{{ if row.waiting }} compile-time-waiting{{ endif }}">
+    <td data-value="{row.order}"> <a href="{row.url}">{row.compile_id}</a> </td>
+    <td> {row.co_name} </td> <td> {row.status} </td>
+    <td data-value="{row.dynamo_s}"> {row.dynamo}{{ if row.dynamo_cpu }} <span class="cpu-time">/ {row.dynamo_cpu}</span>{{ endif }} </td>
+    <td data-value="{row.aot_s}"> {row.aot}{{ if row.aot_cpu }} <span class="cpu-time">/ {row.aot_cpu}</span>{{ endif }} </td>
+    <td data-value="{row.inductor_s}"> {row.inductor}{{ if row.inductor_cpu }} <span class="cpu-time">/ {row.inductor_cpu}</span>{{ endif }} </td>
+    <td data-value="{row.total_s}"> {row.total}{{ if row.total_cpu }} <span class="cpu-time">/ {row.total_cpu}</span>{{ endif }} </td>
+    {{ if has_cpu }}<td class="wait" data-value="{row.wait_s}"> {row.wait} </td>{{ endif }}
+    {{ for value in row.derived_metrics }}<td> {value} </td>{{ endfor }}
+    <td> {row.bar_html | format_unescaped} </td>
+    </tr>
+    {{ endfor }}
+    </tbody>
+    </table>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_GRAPH_DIFF: &str = r#"
+<html>
+<head>
+    <style>
+    {css | format_unescaped}
+    </style>
+    <title>Compile {compile_id}: {title}</title>
+    <base href="..">
+</head>
+<body>
+    <h1>Compile <a href='index.html#{compile_id}'>{compile_id}</a>: what the {title} did</h1>
+    {{ if unchanged }}
+    <p>The {title} left all {num_nodes_after} nodes of the graph as they were.</p>
+    {{ else }}
+    <p>
+    The graph went from {num_nodes_before} to {num_nodes_after} nodes: {num_added} added,
+    {num_removed} removed, {num_changed} with a new target and {num_rewired} with new inputs.
+    Nodes are matched by name, which passes keep for the nodes they don't touch.
+    </p>
+    {{ endif }}
+    {{ if replacements }}
+    <h2>Replacements</h2>
+    <p>Nodes removed and added for the same line of user code:</p>
+    <table>
+    <tr> <th> User code </th> <th> Removed </th> <th> Added </th> </tr>
+    {{ for r in replacements }}
+    <tr> <td> <code>{r.location}</code> </td> <td> {{ for node in r.removed }}<code>{node}</code><br>{{ endfor }} </td> <td> {{ for node in r.added }}<code>{node}</code><br>{{ endfor }} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endif }}
+    <h2>Graph</h2>
+    <pre class="graph-diff">{{ for line in lines }}<span class="graph-diff-{line.status}">{line.marker} {line.text}</span>{{ if line.note }}  <span class="graph-diff-note"># {line.note}</span>{{ endif }}
+{{ endfor }}</pre>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_RANK_ARTIFACT: &str = r#"
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+    {css | format_unescaped}
+    </style>
+    <title>{kind} of {compile_id} across ranks</title>
+</head>
+<body>
+    <h1><code>{kind}</code> of {compile_id} across ranks</h1>
+    <p>
+    Logged by {num_ranks} ranks{{ if missing_ranks }} and missing on ranks {missing_ranks}{{ endif }},
+    in {num_variants} distinct versions; the most common comes first.
+    </p>
+    <div class="rank-variants">
+    {{ for variant in variants }}
+    <div class="rank-variant">
+    <h3>Ranks {variant.ranks} ({variant.num_ranks})</h3>
+    <p>{{ for file in variant.files }}<a href="{file.0}">{file.1}</a> {{ endfor }}</p>
+    {{ if variant.content }}
+    <pre>{variant.content}</pre>
+    {{ else }}
+    {{ if variant.is_page }}
+    <iframe src="{variant.files.0.0}"></iframe>
+    {{ endif }}
+    {{ endif }}
+    </div>
+    {{ endfor }}
+    </div>
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_OP_COVERAGE: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Operator Coverage</title>
+</head>
+<body>
+    <h1>Operator Coverage</h1>
+    <p>
+    Operators from the eager op trace compared against the operators in compiled graphs.
+    Compiled graphs cover <strong>{coverage}</strong> of eager operator calls
+    ({covered_calls} of {total_calls} calls, {covered_ops} of {total_ops} distinct operators).
+    Operators that appear in no compiled graph ran outside compiled regions, usually because of
+    graph breaks or skipped frames.
+    </p>
+    <table>
+    <tr> <th> Operator </th> <th> Eager Calls </th> <th> Compiled Graphs </th> </tr>
+    {{ for row in rows }}
+    <tr{{ if row.compiled_graphs }}{{ else }} class="status-missing"{{ endif }}> <td> <code>{row.op}</code> </td> <td> {row.eager_calls} </td> <td> {row.compiled_graphs} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ if compiled_only }}
+    <h2>Only in compiled graphs</h2>
+    <p>These operators don't appear in the eager trace, typically because they come from decompositions.</p>
+    <ul>
+    {{ for op in compiled_only }}
+    <li><code>{op}</code></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_CACHE_KEY_CHANGES: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Cache Key Changes</title>
+</head>
+<body>
+    <h1>Cache Key Changes</h1>
+    <p>
+    Each of the {num_misses} cache misses of this run compared with the same lookup (same cache,
+    compile id and position within the compile) in the baseline <code>{baseline}</code>.
+    {num_changed} of them missed because a logged component of their key changed.
+    </p>
+    {{ for miss in misses }}
+    <h2 id='{miss.compile_id}'>{miss.compile_id} <code>{miss.cache}</code></h2>
+    <p>{miss.summary}</p>
+    {{ if miss.changes }}
+    <table>
+    <tr> <th> Category </th> <th> Component </th> <th> Change </th> <th> Baseline </th> <th> This run </th> </tr>
+    {{ for change in miss.changes }}
+    <tr> <td> {change.category} </td> <td> <code>{change.name}</code> </td> <td> {change.status} </td> <td> <code>{change.baseline_value}</code> </td> <td> <code>{change.value}</code> </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endif }}
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_COMPILER_LOGS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Compiler Logs {compile_id}</title>
+</head>
+<body>
+    <h1>Compiler Logs {compile_id}</h1>
+    <p>
+    {num_lines} TORCH_LOGS text lines interleaved with the trace belong to this compile, by the
+    compile id in their prefix or else by the compile the trace was in at the time.
+    </p>
+    {{ for channel in channels }}
+    <details class="compiler-logs">
+    <summary><code>{channel.name}</code> ({channel.num_lines})</summary>
+    <pre>{{ for line in channel.lines }}<span class="log-{line.level}">{line.level} {line.timestamp} {line.location}]</span> {line.message}
+{{ endfor }}</pre>
+    </details>
+    {{ endfor }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_CHANGES: &str = r#"
+<html>
+<head>
+    <meta http-equiv="refresh" content="10">
+    <style>
+    {css}
+    </style>
+    <title>What's New</title>
+</head>
+<body>
+    <h1>What's New</h1>
+    <p>
+    Artifacts and warnings added to the report of <code>{log}</code> since watching started at
+    {started_at} ({regenerations} regenerations), newest first.  Back to the <a href="index.html">report</a>.
+    </p>
+    {{ if recompile_warning }}
+    <div class="warning-box">⚠️ {recompile_warning}</div>
+    {{ endif }}
+    {{ if entries }}
+    <table>
+    <tr> <th> Time </th> <th> Kind </th> <th> Compile Directory </th> <th> What </th> </tr>
+    {{ for entry in entries }}
+    <tr> <td> {entry.time} </td> <td> {entry.kind} </td> <td> {{ if entry.compile_dir }}<a href="index.html#{entry.compile_dir}">{entry.compile_dir}</a>{{ endif }} </td>
+    <td> {{ if entry.url }}<a href="{entry.url}">{entry.message}</a>{{ else }}{entry.message}{{ endif }} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ else }}
+    <p>Nothing new yet; this page refreshes every 10 seconds.</p>
+    {{ endif }}
+    {qps | format_unescaped}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_BROWSE: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Browse {path}</title>
+</head>
+<body>
+    <h1>{{ for crumb in breadcrumbs }}<a href="{crumb.url}">{crumb.name}</a> / {{ endfor }}</h1>
+    <p>{num_files | format_count} files, {total_size}.{{ if report_url }}  Back to the <a href="{report_url}">report</a>.{{ endif }}</p>
+    <table>
+    <tr> <th> Name </th> <th> Kind </th> <th> Size </th> </tr>
+    {{ for entry in dirs }}
+    <tr> <td> <a href="{entry.url}">{entry.name}</a> </td> <td> {entry.kind} </td> <td> <span class="size-badge {entry.size_heat}">{entry.size}</span> </td> </tr>
+    {{ endfor }}
+    {{ for entry in files }}
+    <tr> <td> <a href="{entry.url}">{entry.name}</a> </td> <td> {entry.kind} </td> <td> {{ if entry.deferred }}<abbr title="Extracted from the log when first opened">not extracted</abbr>{{ else }}<span class="size-badge {entry.size_heat}">{entry.size}</span>{{ endif }} </td> </tr>
+    {{ endfor }}
+    </table>
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_DIRECTORY_INDEX: &str = r#"
+<html>
+<head>
+    <style>
+    {css | format_unescaped}
+    </style>
+    <title>{path}/</title>
+</head>
+<body>
+    <h1>{path}/</h1>
+    <p>{{ if compile_id }}The outputs of compile <a href="{root_url}#{compile_id}">{compile_id}</a>.  {{ endif }}Back to the <a href="{root_url}">report</a>.</p>
+    <table>
+    <tr> <th> Name </th> <th> What </th> <th> Size </th> </tr>
+    {{ for entry in dirs }}
+    <tr> <td> <a href="{entry.url}">{entry.name}</a> </td> <td> {entry.description} </td> <td> <span class="size-badge {entry.size_heat}">{entry.size}</span> </td> </tr>
+    {{ endfor }}
+    {{ for entry in files }}
+    <tr> <td> <a href="{entry.url}">{entry.name}</a> </td> <td> {entry.description} </td> <td> <span class="size-badge {entry.size_heat}">{entry.size}</span> </td> </tr>
+    {{ endfor }}
+    </table>
+<!-- tlparse:previews -->
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_WORKSPACE: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>tlparse workspace</title>
+</head>
+<body>
+    <h1>Runs</h1>
+    <p>{num_runs} runs in <code>{workspace}</code>, newest first.  Parse a log into a new subdirectory (or link a report there) to add a run, delete it to remove it; this page picks up changes on reload.</p>
+    {{ if runs }}
+    <table>
+    <tr> <th> Run </th> <th> Generated </th> <th> Log </th> <th> Ranks </th> <th> Compiles </th> <th> Failed </th> <th> Compile time </th> <th> Warnings </th> <th> Metadata </th> </tr>
+    {{ for run in runs }}
+    <tr> <td> <a href="{run.url}">{run.name}</a>{{ if run.in_progress }} <em>(generating)</em>{{ endif }} </td> <td> {run.generated_at} </td> <td> <code>{run.log}</code> </td> <td> {run.num_ranks} </td> <td> {run.num_compiles} </td> <td> {{ if run.num_failed }}<span class="status-error">{run.num_failed}</span>{{ else }}0{{ endif }} </td> <td> {run.compile_time} </td> <td> {run.num_warnings} </td> <td> {run.metadata} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endif }}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_CONTEXT_DOCS: &str = r#"
+<html>
+<head>
+    <style>
+    {css}
+    </style>
+    <title>Template Context Reference</title>
+</head>
+<body>
+    <h1>Template Context Reference</h1>
+    <p>
+    The fields each report template is rendered with, generated from the Rust context structs.
+    Fields of nested structs are reached with dotted paths, e.g. <code>\{row.compile_id}</code>
+    inside <code>\{\{ for row in rows }}</code>.  A <code>*</code> field stands for the other keys
+    of a map flattened into its struct.
+    </p>
+    <ul>
+    {{ for t in templates }}
+    <li><a href='#{t.template}'>{t.template}</a> ({t.context})</li>
+    {{ endfor }}
+    </ul>
+    {{ for t in templates }}
+    <h2 id="{t.template}">{t.template}</h2>
+    {{ for s in t.structs }}
+    <h3>{s.name}</h3>
+    <p>{s.doc} <small>({s.source})</small></p>
+    <table>
+    <tr> <th> Field </th> <th> Type </th> <th> Description </th> </tr>
+    {{ for f in s.fields }}
+    <tr> <td><code>{f.name}</code></td> <td><code>{f.ty}</code></td> <td> {f.doc} </td> </tr>
+    {{ endfor }}
+    </table>
+    {{ endfor }}
+    {{ endfor }}
+</body>
+</html>
+"#;
+
+pub static TEMPLATE_COMPILE_CANCELLATION: &str = r#"
 <html>
 <head>
-  <meta charset="UTF-8">
+    <style>
+    {css}
+    </style>
+    <title>Compile {compile_id}: {kind}</title>
+    <base href="..">
 </head>
-<style>
-{css | format_unescaped}
-</style>
-<script>
-{javascript | format_unescaped}
-</script>
 <body>
-<div>
-{custom_header_html | format_unescaped}
-<h2>Stack trie</h2>
-<p>
-The <strong>stack trie</strong> is a way of getting a quick orientation on where all the
-compilations in a model take place, esp., if you are compiling a codebase you are unfamiliar with.
-It is a tree of stack frames, for all stacks that triggered PT2 compilation.  If only a single
-stack is in the tree, you will simply see a plain list of frames (most recent call last).  With
-multiple stacks, at every point where two stacks diverge from having a common prefix, we increase
-the indentation of the list and have a separate sub-list per sub-tree.
-</p>
-<p>
-Links to particular compilation are color coded by status:
-<span class="status-ok">[Success]</span>,
-<span class="status-break">[Success with restart (e.g., graph break)]</span>,
-<span class="status-empty">[Empty graph]</span>,
-<span class="status-error">[Error]</span>,
-<span class="status-missing">[Metrics were missing]</span>
-</p>
-{stack_trie_html | format_unescaped}
-</div>
-<div>
-{{ if num_breaks }}
-<h2> Failures and Restarts </h2>
-<p>
-Various issues may cause Dynamo to restart its analysis or give up on compilation entirely, causing graph breaks and fallbacks to eager mode.
-This run had <strong><a href="failures_and_restarts.html">{num_breaks} restart(s) and/or compilation failure(s)</a></strong>.
-</p>
-{{ endif }}
-<h2>IR dumps</h2>
-<p>
-The <strong>IR dumps</strong> collected dumped intermediate products from various points of the PT2
-compilation process.  The products are organized by compile id, and then sorted in chronological
-order.
-</p>
-<p>
-A <strong>compile id</strong> uniquely identifies are particular compilation inside a PT2
-program.  It is traditionally written as <code>[x/y]</code>, where the <strong>frame id</strong> x
-identifies the particular Python frame which we are compiling, and <strong>frame compile
-id</strong> y identifies how many times we've recompiled this same frame.  For example,
-<code>[0/0]</code> refers to the very first frame compiled by PT2; <code>[0/1]</code> refers to the
-first recompilation of this frame, while <code>[1/0]</code> refers to a different frame, within
-distinct code cache, which we are compiling next (perhaps because of a graph break).  Although
-Dynamo treats distinct frames as completely unrelated, a frame compilation could overlap with another
-frame; for example, if you graph break in an inlined function, Dynamo will typically try to compile
-the nested frame again on an inner frame.  You can identify the hierarchical relationship between
-frames by looking at the stack trie above.
-</p>
-<p>
-In some situations, the compile id will have an extra signifier <code>[x/y_z]</code>, where z is the
-<strong>attempt</strong> for this particular (re)compilation.  Certain conditions will cause Dynamo to
-restart analysis, when Dynamo discovers that it needs to undo a decision it previously made.  The most
-common cause of recompilation is a graph break in an inlined function call, which forces to restart
-and avoid inlining the function in the first place.
-</p>
-<p>
-When compiled autograd is enabled, the compile id will include a prefix signifier <code>[!a/x/y]</code>,
-where a is the <strong>compiled autograd id</strong>. For instance, <code>[!0/-/-]</code> refers 
-to the first graph captured by compiled autograd. It is then traced by torch.compile as <code>[!0/x/y_z]</code>.
-</p>
-<p>
-Here is a high level description of PT2's compilation phases, and the intermediate products each
-phase generates:
-</p>
-<ol>
-<li><em>Optional:</em> If compiled autograd is enabled, and we are processing a backward call, compiled autograd will trace the autograd graph from the autograd engine, and produce an FX graph <code>compiled_autograd_graph</code> that will be Dynamo traced.  Otherwise, Dynamo will directly trace user's bytecode.</li>
-<li>Dynamo symbolically evaluates the Python bytecode of a program, producing <code>dynamo_output_graph</code></li>
-<li><em>Optional:</em> If <code>optimize_ddp</code> is enabled, the DDPOptimizer will split the Dynamo output graph to improve pipelining communications.  Each split subgraph is <code>optimize_ddp_split_child_submod</code>, and the high level graph that plumbs the graphs together is <code>optimize_ddp_split_graph</code>.  If there are multiple splits, each subsequent build product will be produced multiple times, one for each split.</li>
-<li>AOTAutograd traces the (possibly split) Dynamo output graph, producing a <code>aot_joint_graph</code> if backwards is enabled.  It then partitions the graph into <code>aot_forward_graph</code> and <code>aot_backward_graph</code>.  If training is not needed, there may only be an <code>aot_inference_graph</code>.</li>
-<li>Inductor will apply some post grad FX passes, producing <code>inductor_post_grad_graph</code></li>
-<li>Inductor will perform code generation, producing the final <code>inductor_output_code</code> which will be executed at runtime.  This output is a valid Python program and can be directly run.</li>
-</ol>
-
-{{ if has_chromium_events }}
-<h2> Chromium Events </h2>
-PT2 generates <a href='chromium_events.json'>Chromium Trace Events</a> in JSON on specific events during compilation.
-You can download and view them in a tool like <a href='https://ui.perfetto.dev/'>Perfetto</a>.
-{{ endif  }}
-
-{{ if milestones }}
-<h2> Milestones </h2>
-<p>Detected notable events from Chromium trace:</p>
-<ul>
-  {{ for m in milestones }}
-    <li><strong>{m.name}</strong> at {m.timestamp}</li>
-  {{ endfor }}
-</ul>
-{{ endif }}
-
-<p>
-<a href="collectives_parity.json">Collectives Parity report</a> comparing scheduler and Inductor output code collective operations.
-</p>
-<p>
-Build products below:
-</p>
-<ul>
-{{ for compile_directory in directory }}
-    <li><a id="{compile_directory.0}">{compile_directory.0}</a>
+    <h1>Compile {compile_id}: <span class="status-cancelled">{kind}</span></h1>
+    <p>
+    This compile was stopped before it finished, so its compile directory only holds what was
+    produced up to that point.
+    </p>
+    <h2>What happened</h2>
+    <table>
+    <tr> <th> Kind </th> <th> Source </th> <th> Reason </th> </tr>
+    {{ for c in cancellations }}
+    <tr> <td> {c.kind} </td> <td> {c.source} </td> <td><pre>{c.reason}</pre></td> </tr>
+    {{ endfor }}
+    </table>
+    <h2>What was lost</h2>
+    {{ if missing }}
+    <p>These phases never produced output:</p>
     <ul>
-        {{ for path_idx in compile_directory.1 }}
-            <li><a href="{path_idx.url}">{path_idx.name}</a>{{ if path_idx.readable_url }} (<a href="{path_idx.readable_url}">readable_html</a>){{ endif }} {path_idx.suffix} ({path_idx.number})</li>
-        {{ endfor }}
+    {{ for phase in missing }}
+        <li>{phase}</li>
+    {{ endfor }}
     </ul>
-    </li>
-{{ endfor }}
-</ul>
-</div>
-
-
-{{ if has_inductor_provenance }}
-<h2>Provenance Tracking</h2>
-<div>
-    <p>View detailed provenance tracking information for each rank and frame:</p>
+    {{ else }}
+    <p>Every phase produced output before the compile was stopped.</p>
+    {{ endif }}
+    <h2>Produced before it stopped</h2>
     <ul>
-    {{ for directory_name in directory_names }}
-        <li><a href='provenance_tracking_{directory_name}.html'>provenance_tracking_{directory_name}</a></li>
+    {{ for f in produced }}
+        <li><a href="{f.url}">{f.name}</a> ({f.number}){{ if f.size }} <span class="size-badge {f.size_heat}">{f.size}</span>{{ endif }}</li>
     {{ endfor }}
     </ul>
-</div>
-{{ endif }}
-
-{{ if has_unknown_stack_trie }}
-<div>
-<h2>Unknown stacks</h2>
-<p>
-  Sometimes, logs are made without a compile id.  This makes it difficult to correlate related
-  logs.  This stack trie shows all places where log entries occurred without compile context; to
-  fix, look an appropriate place in the stack where compile id should have been specified.
-</p>
-{unknown_stack_trie_html | format_unescaped}
-</div>
-{{ endif }}
-{qps | format_unescaped}
+    {qps | format_unescaped}
 </body>
 </html>
 "#;
 
-pub static TEMPLATE_FAILURES_CSS: &str = r#"
-table {
-    width: 90%;
-    border-collapse: collapse;
-    margin: 20px 0;
-}
-table, th, td {
-    border: 1px solid #999;
-    padding: 10px;
-    text-align: left;
-}
-th {
-    background-color: #d3d3d3;
-    font-weight: bold;
-}
-tr:nth-child(odd) {
-    background-color: #f2f2f2;
-}
-a {
-    color: #0066cc;
-    text-decoration: none;
-}
-a:hover {
-    text-decoration: underline;
-}
-"#;
-
-pub static TEMPLATE_FAILURES_AND_RESTARTS: &str = r#"
+pub static TEMPLATE_DIFF_INDEX: &str = r#"
 <html>
 <head>
     <style>
     {css}
+    .diff-changed \{ background-color: #fff3cd; }
+    .diff-added \{ background-color: #d4edda; }
+    .diff-removed \{ background-color: #f8d7da; }
     </style>
+    <title>tlparse diff</title>
 </head>
 <body>
-    <h1>Failures and Restarts</h1>
+    <h1>tlparse diff</h1>
+    <p>Old: <code>{old_log}</code><br>New: <code>{new_log}</code></p>
+    {{ if has_full_reports }}
+    <p>Full reports: <a href="old/index.html">old</a>, <a href="new/index.html">new</a></p>
+    {{ endif }}
+    <p>{num_changed} compile ids differ.  Rendered pages are not compared, only the artifacts.</p>
+    {{ if identical_summary }}
+    <p>{identical_summary}</p>
+    {{ endif }}
+    <h2>Compiles</h2>
+    <p>{changes_summary}  Also in <a href="diff.json">diff.json</a>.</p>
+    {{ for c in changes }}
+    <h3 class="diff-{c.status}">{c.compile_dir}{{ if c.co_name }} ({c.co_name}){{ endif }}: {c.status}</h3>
+    {{ if c.guards.added }}
+    <p>Guards added:</p>
+    <ul class="guard-diff">
+    {{ for g in c.guards.added }}
+        <li class="guard-added">+ <code>{g}</code></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ if c.guards.removed }}
+    <p>Guards removed:</p>
+    <ul class="guard-diff">
+    {{ for g in c.guards.removed }}
+        <li class="guard-removed">- <code>{g}</code></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ if c.recompile_reasons.added }}
+    <p>New recompile reasons:</p>
+    <ul class="guard-diff">
+    {{ for r in c.recompile_reasons.added }}
+        <li class="guard-added">+ {r}</li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ if c.recompile_reasons.removed }}
+    <p>Recompile reasons gone:</p>
+    <ul class="guard-diff">
+    {{ for r in c.recompile_reasons.removed }}
+        <li class="guard-removed">- {r}</li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ if c.graph_breaks.added }}
+    <p>New graph breaks:</p>
+    <ul class="guard-diff">
+    {{ for b in c.graph_breaks.added }}
+        <li class="guard-added">+ {b}</li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ if c.graph_breaks.removed }}
+    <p>Graph breaks gone:</p>
+    <ul class="guard-diff">
+    {{ for b in c.graph_breaks.removed }}
+        <li class="guard-removed">- {b}</li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ endfor }}
+    <h2>Artifacts</h2>
     <table>
-    <tr> <th> Compile Id </th> <th> Failure Type </th> <th> Failure Description </th> <th> Failure Source (compilation failures only) </th> </tr>
-    {{ for failure in failures }}
-    <tr> <td> {failure.0 | format_unescaped} </td>{failure.1 | format_unescaped}</tr>
+    <tr> <th> Compile directory </th> <th> Artifact </th> <th> Status </th> <th> Old </th> <th> New </th> </tr>
+    {{ for c in compiles }}
+    {{ for a in c.artifacts }}
+    <tr class="diff-{a.status}">
+        <td> {c.compile_dir} ({c.status}) </td>
+        <td> {a.name} </td>
+        <td> {a.status} </td>
+        <td> {{ if a.old_url }}<a href="{a.old_url}">old</a>{{ endif }} </td>
+        <td> {{ if a.new_url }}<a href="{a.new_url}">new</a>{{ endif }} </td>
+    </tr>
     {{ endfor }}
-    {qps | format_unescaped}
+    {{ endfor }}
+    </table>
 </body>
 </html>
 "#;
@@ -300,25 +1803,63 @@ pub static TEMPLATE_COMPILATION_METRICS: &str = r#"
 </head>
 <body>
     <h1>Compilation Info for {compile_id}</h1>
+    <!-- tlparse:attempts -->
     <p>{mini_stack_html | format_unescaped}</p>
+    <h2>Explanation</h2>
+    <p class="explanation">{explanation | format_unescaped}</p>
+    {{ if guard_diff }}
+    <h2>Guards changed since {guard_diff.previous_compile_id}</h2>
+    <p>{guard_diff.num_added} added, {guard_diff.num_removed} removed, {guard_diff.num_unchanged} unchanged</p>
+    {{ if guard_diff.added }}
+    <ul class="guard-diff">
+    {{ for g in guard_diff.added }}
+        <li class="guard-added">+ <code>{g}</code></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ if guard_diff.removed }}
+    <ul class="guard-diff">
+    {{ for g in guard_diff.removed }}
+        <li class="guard-removed">- <code>{g}</code></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
+    {{ endif }}
     <h2>Output files:</h2>
     <ul>
         {{ for path_idx in output_files }}
-            <li><a href="{compile_id_dir}/{path_idx.url}">{path_idx.name}</a> ({path_idx.number})</li>
+            <li><a href="{compile_id_dir}/{path_idx.url}">{path_idx.name}</a> ({path_idx.number}){{ if path_idx.size }} <span class="size-badge {path_idx.size_heat}">{path_idx.size}</span>{{ endif }}</li>
         {{ endfor }}
     </ul>
+    {{ if warnings }}
+    <h2>Warnings</h2>
+    <ul class="compile-warnings">
+    {{ for w in warnings }}
+        <li class="warning-{w.severity}" title="{w.severity}">{w.icon} <strong>{w.source}</strong>: <code>{w.message}</code></li>
+    {{ endfor }}
+    </ul>
+    {{ endif }}
     <h2>Stack</h2>
     {stack_html | format_unescaped}
-    <h2>Compile Time(seconds)</h2>
-    <p>Entire Frame <abbr title="Total time spent in convert_frame function">[?]</abbr>: {m.entire_frame_compile_time_s}</div>
-    <p>Backend <abbr title="Time spent running the backend compiler">[?]</abbr>: {m.backend_compile_time_s}</div>
-    {{ if m.inductor_compile_time_s }}
-    <p>Inductor <abbr title="Total time spent running inductor">[?]</abbr>: {m.inductor_compile_time_s}</div>
+    {{ if metric_groups }}
+    <h2>Metrics</h2>
+    <table class="all-metrics">
+    {{ for group in metric_groups }}
+    <tr> <th colspan="3" class="metrics-phase"> {group.phase} </th> </tr>
+    {{ for field in group.fields }}
+    <tr> <td> <code>{field.name}</code> </td> <td{{ if field.raw }} title="{field.raw}"{{ endif }}> {{ if field.options }}<details><summary>{field.value}</summary><table class="config-options">{{ for option in field.options }}<tr><td{{ if option.doc }} class="documented" title="{option.doc}"{{ endif }}><code>{option.name}</code></td><td>{option.value}</td></tr>{{ endfor }}</table></details>{{ else }}{field.value}{{ endif }} </td> <td class="metrics-doc"> {field.doc} </td> </tr>
+    {{ endfor }}
+    {{ endfor }}
+    </table>
+    <details>
+    <summary>Raw JSON</summary>
+    <pre>{raw_metrics}</pre>
+    </details>
     {{ endif }}
-    {{ if m.code_gen_time_s }}
-    <p>Code Gen Time: {m.code_gen_time_s}</p>
-    {{ endif}}
-    <div>Dynamo Time Before Restart <abbr title="Total time spent restarting dynamo analysis">[?]</abbr>: {m.dynamo_time_before_restart_s}</div>
+    <!-- tlparse:phase_gantt -->
+    <!-- tlparse:compile_chromium_events -->
+    <!-- tlparse:output_code_kernels -->
+    <!-- tlparse:inductor_passes -->
     <h2>Restarts and Failures</h2>
     {{ if m.fail_type }}
     <p>Failure Exception: <pre>{m.fail_type}</pre></p>
@@ -337,15 +1878,13 @@ pub static TEMPLATE_COMPILATION_METRICS: &str = r#"
     {{ else }}
     <p> No restarts! </p>
     {{ endif }}
-    <h2>Cache Metrics</h2>
-    <p>Cache Size: {m.cache_size}</p>
-    <p>Accumulated Cache Size: {m.accumulated_cache_size}</p>
-    <h2>Graph Metrics</h2>
-    <p>Guard Count: {m.guard_count}</p>
-    <p>Shape Env Guards: {m.shape_env_guard_count}</p>
-    <p>Graph Ops: {m.graph_op_count}</p>
-    <p>Graph Nodes: {m.graph_node_count}</p>
-    <p>Graph Inputs: {m.graph_input_count}</p>
+    {{ if memory }}
+    <h2>Process Memory</h2>
+    <p>RSS <abbr title="Resident set size at the end of this compile">[?]</abbr>: {memory.rss} ({memory.rss_delta} since previous compile)</p>
+    {{ if memory.pss_bytes }}
+    <p>PSS <abbr title="Proportional set size at the end of this compile">[?]</abbr>: {memory.pss} ({memory.pss_delta} since previous compile)</p>
+    {{ endif }}
+    {{ endif }}
     <h2> Custom Ops </h2>
     {{ if m.compliant_custom_ops }}
     <p> Compliant Custom Ops:</p>
@@ -418,6 +1957,7 @@ pub static TEMPLATE_COMPILATION_METRICS: &str = r#"
     {{ endfor }}
     </table>
     {qps | format_unescaped}
+<!-- tlparse:previews -->
 </body>
 </html>
 "#;
@@ -454,12 +1994,12 @@ pub static TEMPLATE_BWD_COMPILATION_METRICS: &str = r#"
 </head>
 <body>
     <h1>Backward Compilation Info for {compile_id}</h1>
-    <h2>Compile Time(seconds)</h2>
+    <h2>Compile Time</h2>
     {{ if m.inductor_compile_time_s }}
-    <p>Inductor <abbr title="Total time spent running inductor">[?]</abbr>: {m.inductor_compile_time_s}</div>
+    <p>Inductor <abbr title="Total time spent running inductor">[?]</abbr>: {m.inductor_compile_time_s | format_duration}</div>
     {{ endif }}
     {{ if m.code_gen_time_s }}
-    <p>Code Gen Time: {m.code_gen_time_s}</p>
+    <p>Code Gen Time: {m.code_gen_time_s | format_duration}</p>
     {{ endif}}
     <h2>Failures</h2>
     {{ if m.fail_type }}
@@ -523,6 +2063,7 @@ pub static TEMPLATE_EXPORT_INDEX: &str = r#"
 <body>
 <div>
 {custom_header_html | format_unescaped}
+<!-- tlparse:rendering-errors -->
 <h1>Draft Export Report</h1>
 {{ if success }}
 <p>
@@ -591,11 +2132,24 @@ pub static TEMPLATE_MULTI_RANK_INDEX: &str = r#"
 <body>
 <div>
 {custom_header_html | format_unescaped}
+{{ if diagnostics.integrity }}
+<div class="warning-box">
+    <p><strong>Warning:</strong> Problems were found with the rank logs, per-rank sections may be incomplete:</p>
+    <ul>
+    {{ for issue in diagnostics.integrity }}
+        <li>Rank {issue.rank}: {issue.problem}</li>
+    {{ endfor }}
+    </ul>
+</div>
+{{ endif }}
 {{ if show_desync_warning }}
 <div class="warning-box">
     {{ if compile_id_divergence }}
     <p><strong>Warning:</strong> Diverging Compilation IDs detected across ranks. This may lead to hangs or timeouts during distributed execution.</p>
     {{ endif }}
+    {{ if diagnostics.divergence.graphs }}
+    <p><strong>Warning:</strong> {diagnostics.compile_divergence.divergent_ranks} compiled different graphs or guards than the other ranks; see the <a href='#compile-divergence'>compile divergence matrix</a>.</p>
+    {{ endif }}
     {{ if diagnostics.divergence.cache }}
     <p><strong>Warning:</strong> Diverging Cache hit/miss patterns detected across ranks. Cache hit/miss pattern groups:</p>
     <ul>
@@ -644,6 +2198,52 @@ Individual rank reports:
     <li><a href="rank_{rank}/index.html">Rank {rank}</a></li>
 {{ endfor }}
 </ul>
+{{ if diagnostics.activity }}
+<h3>Compile Activity</h3>
+<p>
+When each rank's compiles started, over the whole run.  Compiles after the first half of the run,
+drawn in red, mean the job keeps compiling after warmup.
+</p>
+<table>
+<tr> <th> Rank </th> <th> Compiles over the run </th> <th> Summary </th> </tr>
+{{ for row in diagnostics.activity }}
+<tr> <td> <a href="rank_{row.rank}/index.html">Rank {row.rank}</a> </td> <td> {row.sparkline_html | format_unescaped} </td> <td> {row.summary} </td> </tr>
+{{ endfor }}
+</table>
+{{ endif }}
+{{ if diagnostics.compile_divergence }}
+<h3 id="compile-divergence">Compile Divergence</h3>
+<p>
+{diagnostics.compile_divergence.summary}
+Each compile that differs gets a row, with a column per rank: ranks with the same letter compiled
+the same graph and guards, <code>A</code> being the most common; empty cells are ranks that didn't
+compile it at all.
+</p>
+{{ if diagnostics.compile_divergence.rows }}
+<table class="divergence-matrix">
+<tr> <th> Compile Id </th> {{ for rank in diagnostics.compile_divergence.ranks }}<th> {rank} </th> {{ endfor }}<th> Differs in </th> </tr>
+{{ for row in diagnostics.compile_divergence.rows }}
+<tr> <td> {row.compile_id} </td> {{ for cell in row.cells }}<td class="{cell.class}">{cell.variant}</td> {{ endfor }}<td> {row.differs_in}{{ if row.graph_url }} (<a href="{row.graph_url}">graphs</a>{{ if row.guards_url }}, <a href="{row.guards_url}">guards</a>{{ endif }}){{ else }}{{ if row.guards_url }} (<a href="{row.guards_url}">guards</a>){{ endif }}{{ endif }} </td> </tr>
+{{ endfor }}
+</table>
+{{ endif }}
+{{ endif }}
+{{ if diagnostics.rank_artifacts }}
+<h3>Artifacts Across Ranks</h3>
+<p>
+The same artifact of a compile from every rank, side by side, with ranks that logged identical
+content grouped together.  <span class="rank-artifact-differs">Highlighted</span> artifacts
+aren't the same on every rank.
+</p>
+<table>
+<tr> <th> Compile Id </th> <th> Artifacts </th> </tr>
+{{ for row in diagnostics.rank_artifacts }}
+<tr> <td> {row.compile_id} </td> <td>
+{{ for artifact in row.artifacts }}<a href="{artifact.url}"{{ if artifact.differs }} class="rank-artifact-differs"{{ endif }}>{artifact.kind}</a>{{ if artifact.note }} ({artifact.note}){{ endif }} {{ endfor }}
+</td> </tr>
+{{ endfor }}
+</table>
+{{ endif }}
 {{ if diagnostics.analysis }}
 {{ if diagnostics.analysis.has_mismatched_graph_counts }}
 <h3>Graph Runtime Analysis</h3>
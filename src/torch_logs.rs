@@ -0,0 +1,120 @@
+//! Plain TORCH_LOGS text output interleaved with the structured trace (common when both
+//! TORCH_LOGS and TORCH_TRACE are set).  These lines share the glog prefix of trace lines but
+//! carry text instead of a JSON envelope, e.g.
+//!
+//! ```text
+//! V1010 12:00:00.000000 123 torch/_dynamo/guards.py:2300] [0/0] [__guards] GUARDS:
+//! ```
+//!
+//! They are classified by logger and attached to the compile id in their prefix, or else to the
+//! compile the trace was last in, and rendered as `compiler_logs.html` per compile id.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{CompileId, CompilerLogChannel, CompilerLogLine};
+
+// `[0/0] [__guards] message`, `[!1/0/0_1] message`; both prefixes are optional
+static TEXT_PREFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:\[(?P<cid>!?[\d/_]+)\]\s*)?(?:\[__(?P<artifact>\w+)\]\s*)?(?P<msg>.*)$")
+        .unwrap()
+});
+
+/// TORCH_LOGS component owning the modules under each path
+const CHANNELS: [(&str, &str); 7] = [
+    ("torch/_dynamo/", "dynamo"),
+    ("torch/_inductor/", "inductor"),
+    ("torch/_functorch/", "aot"),
+    ("torch/fx/experimental/symbolic_shapes", "dynamic"),
+    ("torch/_export/", "export"),
+    ("torch/export/", "export"),
+    ("torch/distributed/", "distributed"),
+];
+
+/// `[0/0_1]` -> frame 0, frame compile 0, attempt 1; `[!1/0/0]` is compiled autograd 1
+pub fn parse_compile_id(s: &str) -> Option<CompileId> {
+    let (compiled_autograd_id, rest) = match s.strip_prefix('!') {
+        Some(rest) => {
+            let (ca, rest) = rest.split_once('/')?;
+            (Some(ca.parse().ok()?), rest)
+        }
+        None => (None, s),
+    };
+    let (frame_id, rest) = rest.split_once('/')?;
+    let (frame_compile_id, attempt) = match rest.split_once('_') {
+        Some((fcid, attempt)) => (fcid, attempt.parse().ok()?),
+        None => (rest, 0),
+    };
+    Some(CompileId {
+        compiled_autograd_id,
+        frame_id: Some(frame_id.parse().ok()?),
+        frame_compile_id: Some(frame_compile_id.parse().ok()?),
+        attempt: Some(attempt),
+    })
+}
+
+/// The channel a line was logged on: its artifact logger if it has one (`__guards` ->
+/// `guards`), else the component of the logging module
+fn channel(pathname: &str, artifact: Option<&str>) -> String {
+    if let Some(artifact) = artifact {
+        return artifact.to_string();
+    }
+    let pathname = pathname.replace('\\', "/");
+    CHANNELS
+        .iter()
+        .find(|(prefix, _)| pathname.contains(prefix))
+        .map_or_else(
+            || {
+                pathname
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(&pathname)
+                    .trim_end_matches(".py")
+                    .to_string()
+            },
+            |(_, channel)| channel.to_string(),
+        )
+}
+
+/// Classify a text line; returns the compile id from its prefix, if any
+pub fn parse_line(
+    level: &str,
+    timestamp: String,
+    pathname: &str,
+    lineno: &str,
+    text: &str,
+) -> (Option<CompileId>, CompilerLogLine) {
+    let caps = TEXT_PREFIX.captures(text).unwrap();
+    let compile_id = caps.name("cid").and_then(|c| parse_compile_id(c.as_str()));
+    let channel = channel(pathname, caps.name("artifact").map(|a| a.as_str()));
+    let filename = pathname.rsplit(['/', '\\']).next().unwrap_or(pathname);
+    (
+        compile_id,
+        CompilerLogLine {
+            level: level.to_string(),
+            timestamp,
+            channel,
+            location: format!("{filename}:{lineno}"),
+            message: caps["msg"].to_string(),
+        },
+    )
+}
+
+/// Group one compile's lines by channel, channels in order of first appearance
+pub fn group_by_channel(lines: &[CompilerLogLine]) -> Vec<CompilerLogChannel> {
+    let mut channels: Vec<CompilerLogChannel> = Vec::new();
+    for line in lines {
+        match channels.iter_mut().find(|c| c.name == line.channel) {
+            Some(channel) => {
+                channel.num_lines += 1;
+                channel.lines.push(line.clone());
+            }
+            None => channels.push(CompilerLogChannel {
+                name: line.channel.clone(),
+                num_lines: 1,
+                lines: vec![line.clone()],
+            }),
+        }
+    }
+    channels
+}
@@ -5,12 +5,11 @@ use indexmap::IndexMap;
 use regex::Regex;
 use serde_json::Value;
 
+use std::cell::RefCell;
 use std::fmt::{self, Display, Write};
 use std::path::PathBuf;
 
-use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
 
 // Main function returns a list of files to save
 pub type ParseOutput = Vec<(PathBuf, String)>;
@@ -22,6 +21,11 @@ pub type GuardAddedFastIndex = FxHashMap<Option<CompileId>, Vec<GuardAddedFastMe
 pub type SymExprInfoIndex = FxHashMap<u64, SymExprInfoMetadata>;
 pub type CreateSymbolIndex = FxHashMap<Option<CompileId>, Vec<CreateSymbolMetadata>>;
 pub type UnbackedSymbolIndex = FxHashMap<Option<CompileId>, Vec<UnbackedSymbolMetadata>>;
+pub type CompileWarningIndex = FxHashMap<Option<CompileId>, Vec<CompileWarning>>;
+pub type GraphBreakIndex = FxHashMap<Option<CompileId>, Vec<GraphBreak>>;
+pub type CompileCancellationIndex = FxIndexMap<Option<CompileId>, Vec<CompileCancellation>>;
+/// Positions in the run's chromium events, per compile id and metrics page
+pub type ChromiumEventIndex = FxHashMap<Option<CompileId>, Vec<Vec<usize>>>;
 
 pub type FxIndexMap<K, V> = IndexMap<K, V, BuildHasherDefault<FxHasher>>;
 
@@ -114,28 +118,124 @@ pub fn extract_eval_with_key_id(filename: &str) -> Option<u64> {
         .and_then(|m| m.as_str().parse::<u64>().ok())
 }
 
-pub static INTERN_TABLE: Lazy<Mutex<FxHashMap<u32, String>>> =
-    Lazy::new(|| Mutex::new(FxHashMap::default()));
+thread_local! {
+    // Interned strings of the log being parsed, cleared per parse_path call so that ranks
+    // parsed on different threads (or one after another) don't see each other's strings
+    pub static INTERN_TABLE: RefCell<FxHashMap<u32, String>> =
+        RefCell::new(FxHashMap::default());
+}
+
+/// How stack frames are rendered everywhere in the report
+#[derive(Debug, Clone, Default)]
+pub struct StackRenderOptions {
+    /// Collapse runs of identical consecutive frames into one frame with a repeat count
+    pub collapse_repeated: bool,
+    /// Drop everything up to and including `site-packages/` or `dist-packages/`
+    pub strip_site_packages: bool,
+    /// Filename prefix rewrites `(from, to)`; the first matching prefix wins
+    pub path_map: Vec<(String, String)>,
+}
+
+thread_local! {
+    // Set per parse_path call; frames are rendered via Display, which can't take the config
+    pub static STACK_RENDER_OPTIONS: RefCell<StackRenderOptions> =
+        RefCell::new(StackRenderOptions::default());
+}
+
+/// Apply the path map and site-packages stripping to a frame's filename
+pub fn symbolicate_filename(filename: &str) -> String {
+    STACK_RENDER_OPTIONS.with(|options| {
+        let options = options.borrow();
+        let mut filename = options
+            .path_map
+            .iter()
+            .find_map(|(from, to)| {
+                filename
+                    .strip_prefix(from.as_str())
+                    .map(|rest| format!("{to}{rest}"))
+            })
+            .unwrap_or_else(|| filename.to_string());
+        if options.strip_site_packages {
+            if let Some(pos) = ["site-packages/", "dist-packages/"]
+                .iter()
+                .filter_map(|marker| filename.rfind(marker).map(|p| p + marker.len()))
+                .max()
+            {
+                filename = filename[pos..].to_string();
+            }
+        }
+        filename
+    })
+}
+
+/// Apply the path map to every path in free text (tracebacks, guards, graph code comments):
+/// a `from` prefix is rewritten wherever a path can start, i.e. not in the middle of a word or
+/// another path.  None if nothing was rewritten.
+pub fn map_paths_in_text(text: &str) -> Option<String> {
+    STACK_RENDER_OPTIONS.with(|options| {
+        let options = options.borrow();
+        if options.path_map.is_empty() {
+            return None;
+        }
+        let is_path_char = |c: char| c.is_alphanumeric() || "_-./\\~".contains(c);
+        let mut mapped = String::with_capacity(text.len());
+        let mut changed = false;
+        let mut prev: Option<char> = None;
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            if !prev.is_some_and(is_path_char) {
+                if let Some((from, to)) = options
+                    .path_map
+                    .iter()
+                    .find(|(from, _)| !from.is_empty() && rest.starts_with(from.as_str()))
+                {
+                    mapped.push_str(to);
+                    rest = &rest[from.len()..];
+                    prev = from.chars().last();
+                    changed = true;
+                    continue;
+                }
+            }
+            mapped.push(c);
+            rest = &rest[c.len_utf8()..];
+            prev = Some(c);
+        }
+        changed.then_some(mapped)
+    })
+}
+
+/// Pair each frame with how many times it repeats consecutively (always 1 unless collapsing)
+fn collapse_frames(stack: StackSummary) -> Vec<(FrameSummary, usize)> {
+    let collapse = STACK_RENDER_OPTIONS.with(|options| options.borrow().collapse_repeated);
+    let mut frames: Vec<(FrameSummary, usize)> = Vec::new();
+    for frame in stack {
+        match frames.last_mut() {
+            Some((last, count)) if collapse && *last == frame => *count += 1,
+            _ => frames.push((frame, 1)),
+        }
+    }
+    frames
+}
 
 #[derive(Default)]
 pub struct StackTrieNode {
     terminal: Vec<Option<CompileId>>,
     // Ordered map so that when we print we roughly print in chronological order
-    children: FxIndexMap<FrameSummary, StackTrieNode>,
+    children: FxIndexMap<(FrameSummary, usize), StackTrieNode>,
 }
 
 impl StackTrieNode {
-    pub fn insert(&mut self, mut stack: StackSummary, compile_id: Option<CompileId>) {
+    pub fn insert(&mut self, stack: StackSummary, compile_id: Option<CompileId>) {
         let mut cur = self;
-        for frame in stack.drain(..) {
+        for frame in collapse_frames(stack) {
             cur = cur.children.entry(frame).or_default();
         }
         cur.terminal.push(compile_id);
     }
 
-    pub fn insert_no_terminal(&mut self, mut stack: StackSummary) {
+    pub fn insert_no_terminal(&mut self, stack: StackSummary) {
         let mut cur = self;
-        for frame in stack.drain(..) {
+        for frame in collapse_frames(stack) {
             cur = cur.children.entry(frame).or_default();
         }
     }
@@ -147,6 +247,7 @@ impl StackTrieNode {
     pub fn fmt(
         &self,
         metrics_index: Option<&CompilationMetricsIndex>,
+        cancelled: Option<&CompileCancellationIndex>,
         caption: &str,
         open: bool,
     ) -> Result<String, fmt::Error> {
@@ -155,7 +256,7 @@ impl StackTrieNode {
         write!(f, "<summary>{}</summary>", caption)?;
         write!(f, "<div class='stack-trie'>")?;
         write!(f, "<ul>")?;
-        self.fmt_inner(&mut f, metrics_index)?;
+        self.fmt_inner(&mut f, metrics_index, cancelled)?;
         write!(f, "</ul>")?;
         write!(f, "</div>")?;
         write!(f, "</details>")?;
@@ -166,11 +267,16 @@ impl StackTrieNode {
         &self,
         f: &mut String,
         mb_metrics_index: Option<&CompilationMetricsIndex>,
+        mb_cancelled: Option<&CompileCancellationIndex>,
     ) -> fmt::Result {
-        for (frame, node) in self.children.iter() {
+        for ((frame, repeat), node) in self.children.iter() {
             let mut star = String::new();
+            if *repeat > 1 {
+                write!(star, "<span class='frame-repeat'>({repeat}&times;)</span> ")?;
+            }
             for t in &node.terminal {
                 if let Some(c) = t {
+                    let cancelled = mb_cancelled.is_some_and(|index| index.contains_key(t));
                     let ok_class = mb_metrics_index.map_or("status-missing", |metrics_index| {
                         metrics_index.get(t).map_or("status-missing", |m| {
                             if m.iter().any(|n| n.fail_type.is_some()) {
@@ -186,11 +292,17 @@ impl StackTrieNode {
                             }
                         })
                     });
+                    let ok_class = if cancelled {
+                        "status-cancelled"
+                    } else {
+                        ok_class
+                    };
                     write!(
                         star,
-                        "<a href='#{cid}' class='{ok_class}'>{cid}</a> ",
+                        "<a href='#{cid}' class='{ok_class}'>{cid}</a>{label} ",
                         cid = c,
-                        ok_class = ok_class
+                        ok_class = ok_class,
+                        label = crate::compile_labels::label_html(t)
                     )?;
                 } else {
                     write!(star, "(unknown) ")?;
@@ -205,12 +317,12 @@ impl StackTrieNode {
                     star = star
                 )?;
                 writeln!(f, "{}<ul>", frame)?;
-                node.fmt_inner(f, mb_metrics_index)?;
+                node.fmt_inner(f, mb_metrics_index, mb_cancelled)?;
                 write!(f, "</ul></li>")?;
             } else {
                 // If the node has only one child, don't increase the indent and don't print a hyphen
                 writeln!(f, "<li>{star}{}</li>", frame, star = star)?;
-                node.fmt_inner(f, mb_metrics_index)?;
+                node.fmt_inner(f, mb_metrics_index, mb_cancelled)?;
             }
         }
         Ok(())
@@ -263,7 +375,7 @@ impl CompileId {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct Stats {
     pub ok: u64,
     pub other_rank: u64,
@@ -275,6 +387,10 @@ pub struct Stats {
     pub fail_key_conflict: u64,
     pub fail_json_serialization: u64,
     pub unknown: u64,
+    /// TORCH_LOGS text lines interleaved with the trace
+    pub text_logs: u64,
+    /// Envelopes of compiles left out by --sample-rate / --max-compiles
+    pub sampled_out: u64,
 }
 
 impl std::fmt::Display for Stats {
@@ -317,6 +433,12 @@ impl std::fmt::Display for Stats {
         if self.unknown > 0 {
             fields.push(format!("unknown: {}", self.unknown));
         }
+        if self.text_logs > 0 {
+            fields.push(format!("text_logs: {}", self.text_logs));
+        }
+        if self.sampled_out > 0 {
+            fields.push(format!("sampled_out: {}", self.sampled_out));
+        }
 
         if fields.is_empty() {
             write!(f, "Stats {{ }}")
@@ -350,23 +472,22 @@ pub fn simplify_filename<'a>(filename: &'a str) -> &'a str {
 }
 
 pub fn unintern_str(interned_str: u32) -> String {
-    let intern_table = INTERN_TABLE.lock().unwrap();
-    let filename = intern_table
-        .get(&interned_str)
-        .map_or("(unknown)", |s| s.as_str());
-    return filename.to_string();
+    INTERN_TABLE.with(|intern_table| {
+        intern_table
+            .borrow()
+            .get(&interned_str)
+            .map_or("(unknown)", |s| s.as_str())
+            .to_string()
+    })
 }
 
 impl fmt::Display for FrameSummary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let intern_table = INTERN_TABLE.lock().unwrap();
-        let filename = if let Some(f) = &self.uninterned_filename {
-            f.as_str()
-        } else {
-            intern_table
-                .get(&self.filename)
-                .map_or("(unknown)", |s| s.as_str())
+        let filename = match &self.uninterned_filename {
+            Some(f) => symbolicate_filename(f),
+            None => symbolicate_filename(&unintern_str(self.filename)),
         };
+        let filename = filename.as_str();
         if let Some(fx_id) = extract_eval_with_key_id(filename) {
             write!(
                 f,
@@ -473,6 +594,13 @@ pub struct CompilationMetricsMetadata {
     pub backend_compile_time_s: Option<f64>,
     pub inductor_compile_time_s: Option<f64>,
     pub code_gen_time_s: Option<f64>,
+    // CPU time of the same spans, if the logger measures it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entire_frame_compile_cpu_time_s: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend_compile_cpu_time_s: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inductor_compile_cpu_time_s: Option<f64>,
     pub fail_type: Option<String>,
     pub fail_reason: Option<String>,
     pub fail_user_frame_filename: Option<String>,
@@ -481,6 +609,13 @@ pub struct CompilationMetricsMetadata {
     pub compliant_custom_ops: Option<Vec<String>>,
     pub restart_reasons: Option<Vec<String>>,
     pub dynamo_time_before_restart_s: Option<f64>,
+    // Process memory counters sampled at the end of the compile, if the logger provides them
+    pub process_rss_bytes: Option<u64>,
+    pub process_pss_bytes: Option<u64>,
+    pub cuda_version: Option<String>,
+    /// Every other field, for the Metrics table
+    #[serde(flatten)]
+    pub extra: FxIndexMap<String, Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -511,8 +646,8 @@ pub struct SymbolicShapeSpecializationMetadata {
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct FrameLocals {
-    pub locals: Option<FxHashMap<String, Option<String>>>,
-    pub symbols: Option<FxHashMap<String, Option<String>>>,
+    pub locals: Option<FxIndexMap<String, Option<String>>>,
+    pub symbols: Option<FxIndexMap<String, Option<String>>>,
 }
 impl Display for FrameLocals {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -547,7 +682,7 @@ pub struct SymbolicShapePropagateRealTensorMetadata {
     pub user_stack: Option<StackSummary>,
     pub stack: Option<StackSummary>,
     pub expr_node_id: Option<u64>,
-    pub symbol_to_sources: Option<FxHashMap<String, String>>,
+    pub symbol_to_sources: Option<FxIndexMap<String, String>>,
     pub frame_locals: Option<FrameLocals>,
     pub prefix: Option<String>,
 }
@@ -612,6 +747,554 @@ pub struct OutputFile {
     pub suffix: String,
     /// URL to a human-readable HTML version of inductor_provenance_tracking_kernel_stack_traces.json
     pub readable_url: Option<String>,
+    /// URL opening this artifact in compare.html, for text artifacts
+    pub compare_url: Option<String>,
+    /// Size as recorded in the manifest, empty for links
+    pub size: String,
+    /// `manifest::size_heat` class of the size badge
+    pub size_heat: &'static str,
+}
+
+/// A warning or perf hint found in an artifact logged for a compile
+#[derive(Debug, Serialize, Clone)]
+pub struct CompileWarning {
+    pub severity: &'static str,
+    pub icon: &'static str,
+    pub source: String,
+    pub message: String,
+}
+
+/// Evidence that a compile was timed out or cancelled before it finished
+#[derive(Debug, Serialize, Clone)]
+pub struct CompileCancellation {
+    /// "Timeout" or "Cancelled"
+    pub kind: &'static str,
+    pub source: String,
+    pub reason: String,
+}
+
+/// Something a `--watch` regeneration added to the report
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeEntry {
+    /// Local time of the regeneration
+    pub time: String,
+    /// "artifact", "warning" or "recompile_rate"
+    pub kind: &'static str,
+    pub compile_dir: Option<String>,
+    pub url: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangesContext {
+    pub css: &'static str,
+    pub log: String,
+    pub started_at: String,
+    pub regenerations: usize,
+    /// Newest first
+    pub entries: Vec<ChangeEntry>,
+    /// Raised by the last regeneration, when frames keep recompiling after warmup
+    pub recompile_warning: Option<String>,
+    pub qps: &'static str,
+}
+
+/// A TORCH_LOGS text line found in the trace
+#[derive(Debug, Clone, Serialize)]
+pub struct CompilerLogLine {
+    pub level: String,
+    pub timestamp: String,
+    /// Artifact logger (e.g. `guards`) or component (e.g. `dynamo`) the line was logged on
+    pub channel: String,
+    pub location: String,
+    /// Message, including its continuation lines
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompilerLogChannel {
+    pub name: String,
+    pub num_lines: usize,
+    pub lines: Vec<CompilerLogLine>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompilerLogsContext {
+    pub css: &'static str,
+    pub compile_id: String,
+    pub num_lines: usize,
+    pub channels: Vec<CompilerLogChannel>,
+    pub qps: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompileCancellationContext {
+    pub css: &'static str,
+    pub compile_id: String,
+    pub kind: &'static str,
+    pub cancellations: Vec<CompileCancellation>,
+    pub produced: Vec<OutputFile>,
+    pub missing: Vec<String>,
+    pub qps: &'static str,
+}
+
+/// Process memory at the end of a compile, along with the growth since the previous compile
+#[derive(Debug, Serialize, Clone)]
+pub struct MemoryUsageRow {
+    pub compile_id: String,
+    pub rss_bytes: u64,
+    pub pss_bytes: Option<u64>,
+    pub rss_delta_bytes: i64,
+    pub pss_delta_bytes: Option<i64>,
+    pub rss: String,
+    pub pss: String,
+    pub rss_delta: String,
+    pub pss_delta: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryUsageContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub chart_svg: String,
+    pub rows: Vec<MemoryUsageRow>,
+    pub total_rss_growth: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpCoverageRow {
+    pub op: String,
+    pub eager_calls: u64,
+    /// Number of compiled graphs the op appears in; 0 means it only ran eagerly
+    pub compiled_graphs: usize,
+}
+
+/// Where compilation was when the log stopped, for runs that crashed or were killed
+#[derive(Debug, Serialize)]
+pub struct AbnormalEnd {
+    /// HTML
+    pub reason: String,
+    pub compile_id: String,
+    /// First compile phase without output
+    pub phase: &'static str,
+    /// What was raised, if the log ends with an exception
+    pub message: Option<String>,
+    pub stack_html: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FrameworkCompileGroup {
+    /// `dcp`, `fsdp`, `composable` or `state_dict`
+    pub framework: &'static str,
+    pub label: &'static str,
+    pub num_compiles: usize,
+    pub compile_ids: Vec<String>,
+}
+
+/// Disk usage badge of a compile on the index
+#[derive(Debug, Serialize)]
+pub struct CompileDiskUsage {
+    /// Bytes of `inductor_output_code`, if Inductor generated any
+    pub generated_code: Option<String>,
+    /// Bytes of every file in the compile directory
+    pub artifacts: String,
+    pub size_heat: &'static str,
+    /// Generated far more code than the median compile
+    pub outlier: bool,
+}
+
+/// (compile id, files, disk usage, attempts, label) of a compile directory on the index
+pub type CompileDirectoryEntry = (
+    String,
+    Vec<OutputFile>,
+    Option<CompileDiskUsage>,
+    Option<AttemptRollup>,
+    Option<String>,
+);
+
+/// Where a compile stands among the attempts of its compile, on the index
+#[derive(Debug, Serialize)]
+pub struct AttemptRollup {
+    /// 1-based
+    pub attempt: usize,
+    pub num_attempts: usize,
+    pub is_latest: bool,
+    pub latest: String,
+    /// `entire_frame_compile_time_s` of the latest attempt
+    pub latest_compile_time: Option<String>,
+    pub latest_failed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodeSizeOutlier {
+    pub compile_id: String,
+    pub generated_code: String,
+    /// Generated code of the compile over the median, e.g. `6.3`
+    pub times_median: String,
+}
+
+/// A choice of the "user code only" filter of the index, with the compiles it shows
+#[derive(Debug, Serialize)]
+pub struct FramePreset {
+    /// `all`, `user`, or a `FRAME_ORIGINS` key for the compiles the `user` preset hides
+    pub key: &'static str,
+    pub label: &'static str,
+    pub num_compiles: usize,
+    /// `all` and `user` are choices; the origins are shown as counts of what `user` hides
+    pub selectable: bool,
+    pub checked: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceOccurrence {
+    /// `compile stack`, `graph break`, `failure`, `guard`, `specialization` or `other event`
+    pub kind: &'static str,
+    pub compile_id: String,
+    pub url: String,
+    pub line: i32,
+    /// Function the line is in, if known
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceFileRow {
+    pub filename: String,
+    pub anchor: String,
+    pub num_occurrences: usize,
+    /// Occurrences per kind, e.g. `2 compile stacks, 1 guard`
+    pub counts: String,
+    /// Ordered by line
+    pub occurrences: Vec<SourceOccurrence>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourcesContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub num_files: usize,
+    pub files: Vec<SourceFileRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateGraphCompile {
+    pub compile_id: String,
+    /// The compile's dynamo_output_graph artifact
+    pub url: String,
+    pub compile_time: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateGraphCluster {
+    pub fingerprint: String,
+    pub num_compiles: usize,
+    /// Compile time of every compile but the first
+    pub wasted: String,
+    pub compiles: Vec<DuplicateGraphCompile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateGraphsContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub num_duplicate_compiles: usize,
+    pub total_wasted: String,
+    pub clusters: Vec<DuplicateGraphCluster>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FallbackCompile {
+    pub compile_id: String,
+    /// Output code of the compile
+    pub url: Option<String>,
+    pub calls: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FallbackOpRow {
+    pub op: String,
+    /// `fallback` or `extern kernel`
+    pub kind: &'static str,
+    /// Call sites in generated code, over all compiles
+    pub calls: usize,
+    pub num_compiles: usize,
+    pub compiles: Vec<FallbackCompile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FallbackKernelsContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub num_fallback_ops: usize,
+    pub num_extern_kernels: usize,
+    pub rows: Vec<FallbackOpRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataDependentRow {
+    pub compile_id: String,
+    /// `unbacked symbol`, `specialized expression`, `graph break` or `compile error`
+    pub kind: &'static str,
+    /// The unbacked symbol, expression or reason
+    pub operation: String,
+    /// Value range of an unbacked symbol, result of a specialized expression, exception type
+    pub detail: Option<String>,
+    /// User code of the operation
+    pub location_html: String,
+    pub outcome: String,
+    /// Graph breaks and compile failures
+    pub problem: bool,
+    pub runtime_asserts: Vec<String>,
+    pub stack_html: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataDependentContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub summary: String,
+    pub num_problems: usize,
+    pub rows: Vec<DataDependentRow>,
+}
+
+/// A compile of a frame on recompiles.html
+#[derive(Debug, Serialize)]
+pub struct RecompileNode {
+    pub compile_id: String,
+    pub failed: bool,
+    /// Attempts after the first
+    pub restarts: usize,
+    /// The guards that failed, from `recompile_reasons`
+    pub reasons: Vec<String>,
+    /// Without reasons, the compile whose guards it is compared with
+    pub previous_compile_id: Option<String>,
+    /// Without reasons, the guards of the previous compile it doesn't have
+    pub lost_guards: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecompileReasonCount {
+    pub reason: String,
+    pub count: usize,
+}
+
+/// A frame that recompiled, with its compiles in order
+#[derive(Debug, Serialize)]
+pub struct RecompileFrame {
+    /// `[1/*]`
+    pub label: String,
+    pub co_name: String,
+    /// `model.py:40`
+    pub location: String,
+    pub num_compiles: usize,
+    pub original: RecompileNode,
+    pub recompiles: Vec<RecompileNode>,
+    /// The reasons recompiles of the frame gave most often, most frequent first
+    pub top_reasons: Vec<RecompileReasonCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecompilesContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub summary: String,
+    /// Most compiles first
+    pub frames: Vec<RecompileFrame>,
+}
+
+/// A graph break of graph_breaks.html, with the breaks of the same reason and stack
+#[derive(Debug, Serialize)]
+pub struct GraphBreaksRow {
+    pub count: usize,
+    pub reason: String,
+    /// `file.py:line`, empty if the payload has none
+    pub location: String,
+    /// The user code traceback, empty if the payload has none
+    pub traceback: String,
+    pub first_compile_id: String,
+    /// The other compile ids hitting the break, in log order
+    pub other_compile_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphBreaksContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub summary: String,
+    pub rows: Vec<GraphBreaksRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectiveOpRow {
+    /// `all_reduce_`
+    pub op: String,
+    /// As logged, `torch.ops._c10d_functional.all_reduce_.default`
+    pub target: String,
+    /// `all_reduce`, `all_gather`, `reduce_scatter`, `broadcast`, `all_to_all`, `wait` or
+    /// `other`
+    pub kind: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectiveScheduleSection {
+    pub compile_id: String,
+    /// e.g. "2 all_reduce, 1 all_gather and 3 waits"
+    pub counts: String,
+    pub ops: Vec<CollectiveOpRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectivesContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub summary: String,
+    pub schedules: Vec<CollectiveScheduleSection>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompileTimesRow {
+    /// Position in the log, what the compile id column sorts by
+    pub order: usize,
+    pub compile_id: String,
+    /// The compilation metrics page
+    pub url: String,
+    pub co_name: String,
+    /// "ok" or "failed"
+    pub status: &'static str,
+    /// Durations, empty if unknown
+    pub dynamo: String,
+    pub aot: String,
+    pub inductor: String,
+    pub total: String,
+    /// The same in seconds, what their columns sort by
+    pub dynamo_s: Option<f64>,
+    pub aot_s: Option<f64>,
+    pub inductor_s: Option<f64>,
+    pub total_s: Option<f64>,
+    pub wait_s: Option<f64>,
+    /// CPU time of the same spans, empty if unknown
+    pub dynamo_cpu: String,
+    pub aot_cpu: String,
+    pub inductor_cpu: String,
+    pub total_cpu: String,
+    /// Wall time spent off the CPU, empty without CPU times
+    pub wait: String,
+    /// Waited long enough to be worth a look, see `CompileTimesIndex::is_waiting`
+    pub waiting: bool,
+    pub bar_html: String,
+    /// Values of `CompileTimesContext::derived_metric_columns`
+    pub derived_metrics: Vec<String>,
+}
+
+/// Total, p50, p90 or max of each column of compile_times.html
+#[derive(Debug, Serialize)]
+pub struct CompileTimesStat {
+    pub name: &'static str,
+    pub dynamo: String,
+    pub aot: String,
+    pub inductor: String,
+    pub total: String,
+    pub wait: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompileTimesContext {
+    pub css: &'static str,
+    pub javascript: &'static str,
+    pub qps: &'static str,
+    pub summary: String,
+    /// Some compile's metrics have CPU times, so the page has a Wait column
+    pub has_cpu: bool,
+    pub legend_html: String,
+    pub stats: Vec<CompileTimesStat>,
+    pub rows: Vec<CompileTimesRow>,
+    /// The derived metrics of the config file with the column sorting by them
+    pub derived_metric_columns: Vec<(String, usize)>,
+}
+
+/// A node of the graph listing on a pass group diff page
+#[derive(Debug, Serialize)]
+pub struct GraphDiffLine {
+    /// `kept`, `added`, `removed`, `changed` (new target), `rewired` (new inputs) or
+    /// `location` (the user code the following nodes were traced from)
+    pub status: &'static str,
+    pub marker: &'static str,
+    pub text: String,
+    /// What the node replaced or was replaced by, or what changed
+    pub note: String,
+}
+
+/// Nodes traced from one line of user code that a pass group replaced with others
+#[derive(Debug, Serialize)]
+pub struct GraphReplacement {
+    pub location: String,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphDiffContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub compile_id: String,
+    /// The pass group, e.g. `post-grad passes`
+    pub title: &'static str,
+    pub num_nodes_before: usize,
+    pub num_nodes_after: usize,
+    pub num_added: usize,
+    pub num_removed: usize,
+    pub num_changed: usize,
+    pub num_rewired: usize,
+    /// The passes left every node as it was
+    pub unchanged: bool,
+    pub replacements: Vec<GraphReplacement>,
+    pub lines: Vec<GraphDiffLine>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpCoverageContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    /// Share of eager op calls whose op appears in some compiled graph
+    pub coverage: String,
+    pub covered_calls: u64,
+    pub total_calls: u64,
+    pub covered_ops: usize,
+    pub total_ops: usize,
+    pub rows: Vec<OpCoverageRow>,
+    /// Ops only seen in compiled graphs, typically from decompositions
+    pub compiled_only: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheKeyChange {
+    /// `config`, `source`, `tensor metadata`, `environment` or `other`
+    pub category: &'static str,
+    pub name: String,
+    /// `changed`, `added` or `removed`
+    pub status: &'static str,
+    pub baseline_value: Option<String>,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheMissExplanation {
+    pub compile_id: String,
+    /// `fx_graph_cache`, `aotautograd_cache`, ...
+    pub cache: String,
+    pub key: String,
+    /// One sentence on why the lookup missed, e.g. "config changed (2 components)"
+    pub summary: String,
+    pub changes: Vec<CacheKeyChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheKeyChangesContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub baseline: String,
+    pub num_misses: usize,
+    /// Misses whose key differs from the same lookup in the baseline
+    pub num_changed: usize,
+    pub misses: Vec<CacheMissExplanation>,
 }
 
 #[derive(Debug, Serialize)]
@@ -627,9 +1310,148 @@ pub struct CompilationMetricsContext<'e> {
     pub output_files: &'e Vec<OutputFile>,
     pub compile_id_dir: &'e PathBuf,
     pub mini_stack_html: String,
+    pub memory: Option<MemoryUsageRow>,
+    pub warnings: Vec<CompileWarning>,
+    /// `explain::explain` sentences as HTML
+    pub explanation: String,
+    pub guard_diff: Option<GuardDiff>,
+    /// Every logged field by phase, see `metrics_catalog`
+    pub metric_groups: Vec<MetricsGroup>,
+    pub raw_metrics: String,
     pub qps: &'static str,
 }
 
+#[derive(Debug, Serialize)]
+pub struct MetricsGroup {
+    pub phase: String,
+    pub fields: Vec<MetricsField>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricsField {
+    pub name: String,
+    pub value: String,
+    /// The logged value, if `value` formats it
+    pub raw: String,
+    /// Explanation of the field from the catalog
+    pub doc: String,
+    /// The options of a config metric (`dynamo_config`, `inductor_config`), shown instead of
+    /// `value`
+    pub options: Option<Vec<ConfigOption>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigOption {
+    pub name: String,
+    pub value: String,
+    /// See `config_docs`; empty if the option isn't documented
+    pub doc: String,
+}
+
+/// Guards a compile added and removed relative to the previous compile of its frame
+#[derive(Debug, Clone, Serialize)]
+pub struct GuardDiff {
+    pub previous_compile_id: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub num_added: usize,
+    pub num_removed: usize,
+    pub num_unchanged: usize,
+}
+
+/// A `dynamo_graph_break_reason` artifact, reduced to what the compile explanation mentions
+#[derive(Debug, Clone)]
+pub struct GraphBreak {
+    pub reason: String,
+    /// `file.py:line` of the user code that broke the graph
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrowseCrumb {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrowseEntry {
+    pub name: String,
+    pub url: String,
+    /// Artifact kind of a file, file count of a directory
+    pub kind: String,
+    pub size: String,
+    pub size_heat: &'static str,
+    /// Deferred with --lazy-artifacts and not extracted from the log yet
+    pub deferred: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrowseContext {
+    pub css: &'static str,
+    /// URL the report is served under, empty at the root of the server
+    pub base: String,
+    /// Index of the report, None for a served directory without one
+    pub report_url: Option<String>,
+    pub path: String,
+    pub breadcrumbs: Vec<BrowseCrumb>,
+    pub num_files: usize,
+    pub total_size: String,
+    pub dirs: Vec<BrowseEntry>,
+    pub files: Vec<BrowseEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryIndexEntry {
+    pub name: String,
+    pub url: String,
+    /// What an artifact is, file count of a directory
+    pub description: String,
+    pub size: String,
+    pub size_heat: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryIndexContext {
+    pub css: &'static str,
+    /// Of the directory, relative to the report
+    pub path: String,
+    /// The report's index page, relative to the directory
+    pub root_url: String,
+    /// Of a compile directory
+    pub compile_id: Option<String>,
+    pub dirs: Vec<DirectoryIndexEntry>,
+    pub files: Vec<DirectoryIndexEntry>,
+}
+
+/// A run on the `tlparse workspace` landing page
+#[derive(Debug, Default, Serialize)]
+pub struct WorkspaceRun {
+    pub name: String,
+    pub url: String,
+    pub log: String,
+    pub generated_at: String,
+    pub tlparse_version: String,
+    /// `KEY=VALUE` pairs of the run's metadata
+    pub metadata: String,
+    /// Reports of the run, one per rank for `--all-ranks-html`
+    pub num_ranks: usize,
+    pub num_compiles: usize,
+    pub num_failed: usize,
+    pub num_warnings: usize,
+    /// Total compile time of every compile of every rank
+    pub compile_time: String,
+    /// A tlparse run is writing the report
+    pub in_progress: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceContext {
+    pub css: &'static str,
+    pub workspace: String,
+    pub num_runs: usize,
+    pub runs: Vec<WorkspaceRun>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SymbolicGuardContext {
     pub css: &'static str,
@@ -797,7 +1619,7 @@ pub struct TensorDesc {
     describer_id: u64,
     ndim: u64,
     dtype: String,
-    device: String,
+    pub(crate) device: String,
     size: Vec<SymInt>,
     dynamo_dynamic_indices: Option<Vec<u64>>,
     // TODO: Make layout an enum
@@ -878,6 +1700,22 @@ pub struct DynamoGuard {
     pub user_stack: Option<StackSummary>,
 }
 
+/// Readable page of a `dynamo_cpp_guards_str` dump
+#[derive(Debug, Serialize)]
+pub struct CppGuardsContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub compile_id: String,
+    /// The raw dump, relative to the page
+    pub raw_url: String,
+    pub num_guards: usize,
+    pub num_managers: usize,
+    /// Nested lists of guard managers and their guards
+    pub tree_html: String,
+    /// Lines of the dump outside the tree
+    pub other_lines: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct DynamoGuardsContext {
     pub guards: Vec<DynamoGuard>,
@@ -894,7 +1732,7 @@ pub struct Milestone {
 pub struct IndexContext {
     pub css: &'static str,
     pub javascript: &'static str,
-    pub directory: Vec<(String, Vec<OutputFile>)>,
+    pub directory: Vec<CompileDirectoryEntry>,
     pub stack_trie_html: String,
     pub unknown_stack_trie_html: String,
     pub has_unknown_stack_trie: bool,
@@ -905,6 +1743,199 @@ pub struct IndexContext {
     pub has_inductor_provenance: bool,
     pub directory_names: Vec<String>,
     pub milestones: Vec<Milestone>,
+    pub has_memory_usage: bool,
+    /// Coverage against the eager op trace, if one was given
+    pub op_coverage: Option<String>,
+    /// Set if the log stops mid-compile or with an exception
+    pub abnormal_end: Option<AbnormalEnd>,
+    /// What --sample-rate / --max-compiles left out, if sampling
+    pub sampling: Option<String>,
+    /// User source files in sources.html
+    pub num_source_files: usize,
+    /// Compiles of distributed framework internals, by framework
+    pub framework_compiles: Vec<FrameworkCompileGroup>,
+    /// Their compile ids as a JSON array, for the toggle hiding them
+    pub framework_compile_ids_json: String,
+    /// `all` and `user` presets, then the library origins `user` hides; empty when every
+    /// compile is rooted in user code
+    pub frame_presets: Vec<FramePreset>,
+    /// Compile id -> origin key of the compiles rooted in library code, as a JSON object
+    pub frame_origins_json: String,
+    /// `--hide-framework-frames`: start with the `user` preset selected
+    pub hide_framework_frames: bool,
+    /// Compile time spent recompiling identical graphs, if any were found
+    pub duplicate_graphs_wasted: Option<String>,
+    pub num_duplicate_compiles: usize,
+    /// `FallbackIndex::summary` if generated code calls eager kernels
+    pub fallback_kernels: Option<String>,
+    /// `DataDependentIndex::summary` if the run has data-dependent operations
+    pub data_dependent: Option<String>,
+    /// `Recompiles::summary` if frames recompiled
+    pub recompiles: Option<String>,
+    /// `GraphBreaks::summary` if Dynamo logged graph breaks
+    pub graph_breaks: Option<String>,
+    /// `SyntheticTrace::summary` if tlparse_trace.json was written
+    pub compile_trace: Option<String>,
+    /// `CollectiveIndex::summary` if Inductor logged collective schedules
+    pub collectives: Option<String>,
+    /// `ParseConfig::collectives_parity`: link collectives_parity.json
+    pub collectives_parity: bool,
+    /// `CompileTimesIndex::summary` if any compile logged its metrics
+    pub compile_times: Option<String>,
+    /// `SearchIndex::summary` if search_index.json was written
+    pub search: Option<String>,
+    /// `collisions::summary` if outputs mapped to the same path
+    pub output_collisions: Option<String>,
+    /// `SuggestionIndex::summary` if suggested_config.py was written
+    pub suggested_config: Option<String>,
+    /// Summary of cache_key_changes.html, with a `--baseline` and cache misses to explain
+    pub cache_key_changes: Option<String>,
+    /// Compiles generating far more code than the median compile, most code first
+    pub code_size_outliers: Vec<CodeSizeOutlier>,
+    /// Median generated code per compile, once enough compiles generated code
+    pub median_generated_code: Option<String>,
+    /// `ParseConfig::metadata` as (key, value) rows
+    pub metadata: Vec<(String, String)>,
+    /// vLLM artifacts were detected, so this page is tlparse_index.html and index.html is the
+    /// vLLM view
+    pub vllm_view: bool,
+    pub derived_metric_names: Vec<String>,
+    pub derived_metric_rows: Vec<DerivedMetricRow>,
+    /// Device backends detected in the run, with their device-specific artifacts
+    pub device_backends: Vec<DeviceBackendSection>,
+    /// `CompileActivity::summary` and the strip of compiles over the run
+    pub compile_activity: Option<String>,
+    pub compile_activity_html: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceBackendArtifact {
+    pub compile_id: String,
+    pub url: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceBackendSection {
+    /// `cuda`, `rocm`, `xpu`, `mtia`, `mps` or `cpu`
+    pub namespace: &'static str,
+    pub label: &'static str,
+    /// Why the backend is thought to be in use
+    pub evidence: Vec<String>,
+    pub num_artifacts: usize,
+    pub artifacts: Vec<DeviceBackendArtifact>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DerivedMetricRow {
+    pub compile_id: String,
+    pub url: String,
+    pub values: Vec<String>,
+}
+
+/// Per-compile entry of summary.json
+#[derive(Debug, Serialize)]
+pub struct CompileSummary {
+    pub compile_id: String,
+    /// `ParseConfig::compile_labels` label of the compile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub co_name: Option<String>,
+    pub co_filename: Option<String>,
+    pub co_firstlineno: Option<i32>,
+    pub entire_frame_compile_time_s: Option<f64>,
+    pub backend_compile_time_s: Option<f64>,
+    pub inductor_compile_time_s: Option<f64>,
+    pub fail_type: Option<String>,
+    pub metrics_url: String,
+    /// Framework whose internals were compiled (`fsdp`, `dcp`, ...), None for model code
+    pub framework: Option<&'static str>,
+    /// Library the compiled frame lives in (`torch`, `vllm` or `library`), None for user code
+    pub frame_origin: Option<&'static str>,
+    /// Bytes of Inductor generated code (`inductor_output_code`)
+    pub generated_code_bytes: u64,
+    /// Bytes of the payloads the compile's artifacts were written from
+    pub artifact_bytes: u64,
+    /// Generated at least 4x the median code of the run's compiles
+    pub code_size_outlier: bool,
+    pub derived_metrics: IndexMap<String, Option<f64>>,
+    pub warnings: Vec<CompileWarning>,
+    /// What happened in this compile, in a few plain sentences
+    pub explanation: String,
+}
+
+/// Machine-readable overview of a run, written to summary.json
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    /// Namespaces of the detected device backends
+    pub device_backends: Vec<&'static str>,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub metadata: IndexMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<crate::sampling::Sampling>,
+    pub compiles: Vec<CompileSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffArtifactRow {
+    pub name: String,
+    pub status: &'static str,
+    pub old_url: Option<String>,
+    pub new_url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffCompileRow {
+    pub compile_dir: String,
+    pub status: &'static str,
+    pub artifacts: Vec<DiffArtifactRow>,
+}
+
+/// What one log has and the other hasn't
+#[derive(Debug, Default, Serialize)]
+pub struct DiffDelta {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// A compile id whose guards, recompile reasons or graph breaks differ between the logs, or
+/// that only one of them has
+#[derive(Debug, Serialize)]
+pub struct DiffCompileChanges {
+    pub compile_dir: String,
+    pub co_name: Option<String>,
+    /// `added`, `removed` or `changed`
+    pub status: &'static str,
+    /// Guards of compiles in both logs; a new or removed compile only has its count
+    pub guards: DiffDelta,
+    pub num_guards: usize,
+    pub recompile_reasons: DiffDelta,
+    pub graph_breaks: DiffDelta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffContext {
+    pub css: &'static str,
+    pub old_log: String,
+    pub new_log: String,
+    /// Whether old/ and new/ hold complete reports, or only the artifacts that changed
+    pub has_full_reports: bool,
+    pub num_changed: usize,
+    pub compiles: Vec<DiffCompileRow>,
+    /// One line accounting for what --only-changed left out
+    pub identical_summary: String,
+    pub changes_summary: String,
+    pub changes: Vec<DiffCompileChanges>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareContext {
+    pub css: &'static str,
+    pub javascript: &'static str,
+    pub base_url: String,
+    pub custom_header_html: String,
+    pub artifacts_json: String,
+    pub qps: &'static str,
 }
 
 #[derive(Debug, Serialize)]
@@ -970,6 +2001,9 @@ pub struct DivergenceFlags {
     pub cache: bool,
     pub collective: bool,
     pub tensor_meta: bool,
+    /// Some compile has a different graph or guards on some rank
+    #[serde(default)]
+    pub graphs: bool,
 }
 
 #[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
@@ -995,8 +2029,17 @@ pub struct ExecOrderSummary {
     pub ranks_cache_str: String,
 }
 
+/// A problem found with a rank's log before parsing it
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RankLogIssue {
+    pub rank: u32,
+    pub problem: String,
+}
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct Diagnostics {
+    /// Findings of the pre-parse scan of the rank logs
+    pub integrity: Vec<RankLogIssue>,
     pub divergence: DivergenceFlags,
     pub artifacts: ArtifactFlags,
     pub analysis: Option<RuntimeAnalysis>,
@@ -1004,6 +2047,98 @@ pub struct Diagnostics {
     pub collective_groups: Vec<DivergenceGroup>,
     pub tensor_meta_groups: Vec<DivergenceGroup>,
     pub exec_order: Option<ExecOrderSummary>,
+    /// Artifacts logged by several ranks, linking to their side by side pages
+    #[serde(default)]
+    pub rank_artifacts: Vec<RankArtifactRow>,
+    /// Compiles over the run of each rank that compiled, against the same time axis
+    #[serde(default)]
+    pub activity: Vec<RankActivity>,
+    /// Graph and guards of every compile compared across ranks
+    #[serde(default)]
+    pub compile_divergence: Option<CompileDivergence>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct RankActivity {
+    pub rank: u32,
+    /// `CompileActivity::summary`
+    pub summary: String,
+    pub sparkline_html: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct RankArtifactLink {
+    pub kind: String,
+    pub url: String,
+    /// Not the same on every rank
+    pub differs: bool,
+    /// e.g. "2 variants, on 3 of 4 ranks"
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct RankArtifactRow {
+    pub compile_id: String,
+    pub artifacts: Vec<RankArtifactLink>,
+}
+
+/// Whether each rank compiled the same graph and guards as the others, compile by compile
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct CompileDivergence {
+    /// e.g. "2 of 12 compiles differ between ranks"
+    pub summary: String,
+    pub ranks: Vec<u32>,
+    /// The compiles that differ, in compile id order
+    pub rows: Vec<CompileDivergenceRow>,
+    /// Ranks off the most common graph or guards of some compile, e.g. "Ranks 3, 5-7"
+    pub divergent_ranks: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct CompileDivergenceRow {
+    pub compile_id: String,
+    /// One per rank of `CompileDivergence::ranks`
+    pub cells: Vec<CompileDivergenceCell>,
+    /// e.g. "graph, guards, missing on 2 ranks"
+    pub differs_in: String,
+    /// Side by side pages of the compile's graph and guards on every rank
+    pub graph_url: Option<String>,
+    pub guards_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct CompileDivergenceCell {
+    /// `A` for the most common graph and guards of the compile, `B` for the next, ...; empty
+    /// on ranks that didn't compile it
+    pub variant: String,
+    /// `divergence-same`, `divergence-differs` or `divergence-missing`
+    pub class: String,
+}
+
+/// One distinct version of an artifact, with the ranks that logged it
+#[derive(Debug, Serialize)]
+pub struct RankArtifactVariant {
+    /// e.g. "0-3, 5"
+    pub ranks: String,
+    pub num_ranks: usize,
+    /// (url, name) of the files of the first of the ranks
+    pub files: Vec<(String, String)>,
+    pub is_page: bool,
+    /// Text artifacts small enough to show inline
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RankArtifactContext {
+    pub css: &'static str,
+    pub qps: &'static str,
+    pub compile_id: String,
+    pub kind: String,
+    /// Ranks that logged the artifact
+    pub num_ranks: usize,
+    pub num_variants: usize,
+    pub missing_ranks: String,
+    pub variants: Vec<RankArtifactVariant>,
 }
 
 #[derive(Serialize)]
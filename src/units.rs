@@ -0,0 +1,104 @@
+//! How reports show durations, sizes and counts, so that a compile time or a code size reads
+//! the same on the index, the metrics pages, the vLLM summary and everywhere else.
+//!
+//! Durations are in seconds up to two minutes, then in minutes, then in hours above two hours;
+//! the metrics tables and phase charts, which hold phases of a few microseconds, also scale down
+//! to ms and µs.  A unit is always one space away from its number ("8.3 s", "12.4 ms").
+//! Sizes are in binary units; memory, compared across compiles, stays in MiB.  Counts get
+//! thousands separators.  JSON exports keep the raw value next to the one shown
+//! (`compile_time_s` next to `compile_time`, `code_bytes` next to `code_size`, ...).
+
+use std::fmt::Write;
+
+use serde_json::Value;
+use tinytemplate::TinyTemplate;
+
+/// `seconds` with `decimals` decimals, e.g. "8.3 s", "2.250 s" or "12.5 min"
+pub fn seconds(seconds: f64, decimals: usize) -> String {
+    if seconds.abs() < 120.0 {
+        format!("{seconds:.decimals$} s")
+    } else if seconds.abs() < 7200.0 {
+        format!("{:.1} min", seconds / 60.0)
+    } else {
+        format!("{:.1} h", seconds / 3600.0)
+    }
+}
+
+/// A duration of the metrics tables: "1.234 s", "12.4 ms", "850 µs", or minutes and hours
+pub fn duration(seconds: f64) -> String {
+    if seconds.abs() >= 120.0 {
+        self::seconds(seconds, 1)
+    } else if seconds == 0.0 || seconds.abs() >= 1.0 {
+        self::seconds(seconds, 3)
+    } else if seconds.abs() >= 1e-3 {
+        format!("{:.1} ms", seconds * 1e3)
+    } else {
+        format!("{:.0} µs", seconds * 1e6)
+    }
+}
+
+/// `bytes` in the largest binary unit that keeps it above 1, e.g. "28.7 KiB"
+pub fn bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Memory, always in MiB so that compiles compare at a glance
+pub fn mib(bytes: i64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// A change of memory, e.g. "+350.0 MiB"
+pub fn mib_delta(bytes: i64) -> String {
+    let sign = if bytes >= 0 { "+" } else { "-" };
+    format!("{sign}{}", mib(bytes.abs()))
+}
+
+/// `n` with thousands separators, e.g. "12,345"
+pub fn count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// The formatters templates show raw values of their context with: `format_duration` (of
+/// seconds), `format_bytes` and `format_count`.  Values that aren't numbers are shown as they
+/// are.
+pub fn add_formatters(tt: &mut TinyTemplate) {
+    fn number(
+        value: &Value,
+        output: &mut String,
+        format: impl Fn(f64) -> String,
+    ) -> tinytemplate::error::Result<()> {
+        match value.as_f64() {
+            Some(x) => write!(output, "{}", format(x))?,
+            None => tinytemplate::format(value, output)?,
+        }
+        Ok(())
+    }
+    tt.add_formatter("format_duration", |value, output| {
+        number(value, output, duration)
+    });
+    tt.add_formatter("format_bytes", |value, output| {
+        number(value, output, |x| bytes(x.max(0.0) as u64))
+    });
+    tt.add_formatter("format_count", |value, output| {
+        number(value, output, |x| count(x.max(0.0) as u64))
+    });
+}
@@ -0,0 +1,289 @@
+//! `--vllm-merge DIR`: the torch trace logs vLLM's engine-core worker processes write side by
+//! side, each parsed into its own `worker_N/` report like the ranks of `--all-ranks-html`, with
+//! a landing page grouping the subgraphs and cudagraph captures of every worker.
+//!
+//! Workers are told apart by the id in their log's name (`..._worker_3.log`,
+//! `VllmWorker-3`), else by the rank of a rank log; logs with neither are numbered after the
+//! others, in name order.  The landing page is built from the `vllm_summary.json` of each
+//! worker report, so it only needs the reports, not the logs.
+
+use anyhow::{bail, Context};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::types::{
+    VllmWorkerCapture, VllmWorkerSubgraph, VllmWorkerSummary, VllmWorkersContext, VllmWorkersJson,
+};
+use crate::units;
+
+static WORKER_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)worker[_-]?(\d+)").unwrap());
+
+/// Subdirectory of the report of worker `worker`
+pub fn worker_dir(worker: u32) -> String {
+    format!("worker_{worker}")
+}
+
+/// `..._worker_3.log` (or `VllmWorker-3.log`, `dedicated_log_torch_trace_rank_3.log`) -> 3
+pub fn worker_of_log(path: &Path) -> Option<u32> {
+    let name = path.file_name()?.to_str()?;
+    match WORKER_ID.captures(name) {
+        Some(c) => c[1].parse().ok(),
+        None => crate::archive::rank_of_log(path),
+    }
+}
+
+/// The logs of `dir`, by worker id
+pub fn worker_logs(dir: &Path) -> anyhow::Result<Vec<(u32, PathBuf)>> {
+    if !dir.is_dir() {
+        bail!(
+            "{} must be a directory of vLLM worker logs when using --vllm-merge",
+            dir.display()
+        );
+    }
+    let mut logs: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Couldn't access directory {}", dir.display()))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path.file_name().is_some_and(|name| {
+                    crate::compression::strip_extension(&name.to_string_lossy()).ends_with(".log")
+                })
+        })
+        .collect();
+    logs.sort();
+    let mut workers: BTreeMap<u32, PathBuf> = BTreeMap::new();
+    let mut unnamed = Vec::new();
+    for log in logs {
+        let Some(worker) = worker_of_log(&log) else {
+            unnamed.push(log);
+            continue;
+        };
+        if let Some(other) = workers.get(&worker) {
+            bail!(
+                "{} and {} are both logs of worker {worker}",
+                other.display(),
+                log.display()
+            );
+        }
+        workers.insert(worker, log);
+    }
+    let next = workers.keys().next_back().map_or(0, |last| last + 1);
+    for (worker, log) in (next..).zip(unnamed) {
+        workers.insert(worker, log);
+    }
+    if workers.is_empty() {
+        bail!("No logs found in directory {}", dir.display());
+    }
+    Ok(workers.into_iter().collect())
+}
+
+/// `vllm_summary.json` subgraph or cudagraph size field
+fn field<'a>(value: &'a Value, name: &str) -> &'a Value {
+    value.get(name).unwrap_or(&Value::Null)
+}
+
+fn size_or_range(subgraph: &Value) -> String {
+    let start = field(subgraph, "compile_range_start");
+    if field(subgraph, "is_single_size").as_bool() == Some(true) {
+        format!("size {start}")
+    } else {
+        format!("range [{start}, {}]", field(subgraph, "compile_range_end"))
+    }
+}
+
+/// What a worker's report says about its subgraphs and captures
+fn worker_summary(out_dir: &Path, worker: u32, log: &Path) -> anyhow::Result<VllmWorkerSummary> {
+    let dir = worker_dir(worker);
+    let summary_path = out_dir.join(&dir).join("vllm_summary.json");
+    let summary: Option<Value> = match fs::read_to_string(&summary_path) {
+        Ok(contents) => Some(
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Parsing {}", summary_path.display()))?,
+        ),
+        Err(_) => None,
+    };
+    let mut subgraphs = Vec::new();
+    let mut cudagraph_captures = Vec::new();
+    let mut captures = (0, 0);
+    let engines = summary
+        .as_ref()
+        .and_then(|s| s["engines"].as_array().cloned())
+        .unwrap_or_default();
+    for engine in &engines {
+        let name = engine["name"].as_str().unwrap_or_default();
+        for subgraph in engine["subgraphs"].as_array().into_iter().flatten() {
+            let code_bytes = subgraph["code_bytes"].as_u64().unwrap_or(0) as usize;
+            let compile_time_s = subgraph["compile_time_s"].as_f64();
+            subgraphs.push(VllmWorkerSubgraph {
+                engine: name.to_string(),
+                submod_name: subgraph["submod_name"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("subgraph_{}", subgraph["piecewise_index"])),
+                size_or_range: size_or_range(subgraph),
+                kernel_count: subgraph["kernel_count"].as_u64().unwrap_or(0) as usize,
+                code_bytes,
+                code_size: units::bytes(code_bytes as u64),
+                compile_time_s,
+                compile_time: compile_time_s.map_or(String::new(), |t| units::seconds(t, 2)),
+            });
+        }
+        let checklist = &engine["cudagraph_checklist"];
+        if checklist["captures_logged"].as_bool() != Some(true) {
+            continue;
+        }
+        for size in checklist["sizes"].as_array().into_iter().flatten() {
+            let captured = size["captured"].as_bool() == Some(true);
+            let expected = size["expected"].as_bool() == Some(true);
+            captures.0 += usize::from(captured && expected);
+            captures.1 += usize::from(expected);
+            cudagraph_captures.push(VllmWorkerCapture {
+                engine: name.to_string(),
+                size: size["size"].as_i64().unwrap_or_default(),
+                captured,
+                status: size["status"].as_str().unwrap_or_default().to_string(),
+                problem: size["problem"].as_bool() == Some(true),
+            });
+        }
+    }
+    let code_bytes = subgraphs.iter().map(|s| s.code_bytes).sum();
+    let compile_time_s = subgraphs
+        .iter()
+        .filter_map(|s| s.compile_time_s)
+        .fold(0.0, |total, t| total + t);
+    Ok(VllmWorkerSummary {
+        worker,
+        name: format!("worker {worker}"),
+        anchor: dir.clone(),
+        log: log.display().to_string(),
+        report_url: format!("{dir}/index.html"),
+        has_vllm: summary.is_some(),
+        num_subgraphs: subgraphs.len(),
+        num_kernels: subgraphs.iter().map(|s| s.kernel_count).sum(),
+        code_bytes,
+        code_size: units::bytes(code_bytes as u64),
+        compile_time_s,
+        compile_time: units::seconds(compile_time_s, 2),
+        captures_summary: if cudagraph_captures.is_empty() {
+            String::new()
+        } else {
+            format!("{} of {} sizes captured", captures.0, captures.1)
+        },
+        differs: false,
+        subgraphs,
+        cudagraph_captures,
+    })
+}
+
+/// The subgraphs of a worker, to compare workers by
+fn subgraph_keys(worker: &VllmWorkerSummary) -> Vec<(&str, &str, &str, usize)> {
+    let mut keys: Vec<_> = worker
+        .subgraphs
+        .iter()
+        .map(|s| {
+            (
+                s.engine.as_str(),
+                s.submod_name.as_str(),
+                s.size_or_range.as_str(),
+                s.kernel_count,
+            )
+        })
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Mark the vLLM workers whose subgraphs differ from the first one's, returning e.g. "4
+/// workers, each compiled the same 12 subgraphs"
+fn compare_workers(workers: &mut [VllmWorkerSummary]) -> String {
+    let num_workers = match workers.len() {
+        1 => "1 worker".to_string(),
+        n => format!("{n} workers"),
+    };
+    let vllm: Vec<usize> = (0..workers.len())
+        .filter(|i| workers[*i].has_vllm)
+        .collect();
+    let Some(&first) = vllm.first() else {
+        return format!("{num_workers}, none with vLLM artifacts");
+    };
+    let first_keys = subgraph_keys(&workers[first]);
+    let differing: Vec<usize> = vllm
+        .iter()
+        .copied()
+        .filter(|i| subgraph_keys(&workers[*i]) != first_keys)
+        .collect();
+    for i in &differing {
+        workers[*i].differs = true;
+    }
+    let mut summary = if !differing.is_empty() {
+        let names: Vec<&str> = differing
+            .iter()
+            .map(|i| workers[*i].name.as_str())
+            .collect();
+        format!(
+            "{num_workers}; {} compiled other subgraphs than {}",
+            names.join(", "),
+            workers[first].name
+        )
+    } else if vllm.len() == 1 {
+        format!(
+            "{num_workers}, {} compiled {} subgraphs",
+            workers[first].name, workers[first].num_subgraphs
+        )
+    } else {
+        format!(
+            "{num_workers}, each compiled the same {} subgraphs",
+            workers[first].num_subgraphs
+        )
+    };
+    let without = workers.len() - vllm.len();
+    if without > 0 {
+        summary.push_str(&format!("; {without} without vLLM artifacts"));
+    }
+    summary
+}
+
+/// Write the landing page and `vllm_summary.json` of the worker reports parsed into `out_dir`,
+/// returning the landing page's path
+pub fn write_merged_summary(
+    out_dir: &Path,
+    workers: &[(u32, PathBuf)],
+    cfg: &crate::ParseConfig,
+) -> anyhow::Result<PathBuf> {
+    let mut summaries = workers
+        .iter()
+        .map(|(worker, log)| worker_summary(out_dir, *worker, log))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let summary = compare_workers(&mut summaries);
+    let context = VllmWorkersContext {
+        css: super::templates::VLLM_CSS.to_string(),
+        qps: crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT.to_string(),
+        custom_header_html: cfg.custom_header_html.clone(),
+        num_workers: summaries.len(),
+        summary: summary.clone(),
+        workers: summaries.clone(),
+    };
+    let tt = crate::report_model::templates(false)?;
+    crate::report_model::start_recording(cfg.report_model);
+    let html = crate::report_model::render(&tt, "vllm_workers.html", &context);
+    let json = VllmWorkersJson {
+        summary,
+        workers: summaries,
+    };
+    let index = out_dir.join("index.html");
+    let mut output = vec![
+        (PathBuf::from("index.html"), html),
+        (
+            PathBuf::from("vllm_summary.json"),
+            serde_json::to_string_pretty(&json)?,
+        ),
+    ];
+    crate::report_model::attach(&mut output, false, None)?;
+    crate::paths::write_output(out_dir, output)?;
+    Ok(index)
+}
@@ -3,9 +3,10 @@
 //! This module provides parsers and templates for vLLM's structured logs,
 //! including piecewise compilation, subgraph tracking, and cudagraph captures.
 
+pub mod merge;
 pub mod parsers;
 pub mod templates;
 pub mod types;
 
-pub use parsers::{generate_vllm_summary, vllm_parsers_with_state, VllmState};
-pub use types::VllmSummaryContext;
+pub use parsers::{generate_vllm_summary, generate_vllm_summary_json, vllm_parsers, VllmState};
+pub use types::{VllmCompileTiming, VllmSummaryContext, VllmSummaryJson};
@@ -1,36 +1,161 @@
+use crate::parser_state::ParserState;
 use crate::parsers::{build_file_path, Metadata, ParserOutput, ParserResults, StructuredLogParser};
 use crate::templates::TEMPLATE_QUERY_PARAM_SCRIPT;
 use crate::types::{CompileId, Envelope};
 
 use super::types::{
-    ArtifactInfo, VllmCompilationConfig, VllmCompileRangeGroup, VllmSubgraphInfo,
-    VllmSubgraphWithArtifacts, VllmSummaryContext,
+    ArtifactInfo, VllmCompilationConfig, VllmCompileRangeGroup, VllmCompileTiming,
+    VllmCudagraphChecklist, VllmCudagraphSize, VllmEngineJson, VllmEngineSummary, VllmOpComparison,
+    VllmOpComparisonRow, VllmPartitionSegment, VllmPartitionSummary, VllmPartitionedGraph,
+    VllmSizeSweep, VllmSubgraphInfo, VllmSubgraphWithArtifacts, VllmSummaryContext,
+    VllmSummaryJson, VllmSweepEntry, VllmSweepSubmod,
 };
 
-use std::cell::RefCell;
-use std::rc::Rc;
+use indexmap::IndexMap;
+use md5::{Digest, Md5};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
 use tinytemplate::TinyTemplate;
 
+// A subgraph is flagged when it has at least this many kernels and this many times the median
+const OUTLIER_MIN_KERNELS: usize = 10;
+const OUTLIER_FACTOR: usize = 4;
+
+pub const GRAPH_PARTITION_ARTIFACT: &str = "vllm_graph_partition";
+/// The graph vLLM captured before compiling it, with the custom ops as the model called them
+pub const EAGER_GRAPH_DUMP: &str = "vllm_eager_graph";
+
+/// Namespaces of `torch.ops` that aren't custom ops
+const BUILTIN_OP_NAMESPACES: [&str; 4] = ["aten", "prims", "higher_order", "inductor"];
+
+// In partitioned generated code: the partition functions, their calls from `call`, and the ops
+// `call` runs itself between them
+static PARTITION_DEF: Lazy<Regex> = Lazy::new(|| Regex::new(r"^def partition_(\d+)\(").unwrap());
+static PARTITION_CALL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bpartition_(\d+)\(|\bpartitions\[(\d+)\]").unwrap());
+static TORCH_OP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\btorch\.ops\.([A-Za-z_]\w*\.[A-Za-z_]\w*)").unwrap());
+
+/// One vLLM engine instance in the log, identified by its compilation config prefix.  Engines
+/// with different prefixes (e.g. a speculative decoding head next to the backbone) each compile
+/// their own subgraphs.
+#[derive(Debug, Default)]
+pub struct VllmEngine {
+    pub prefix: Option<String>,
+    pub config: Option<VllmCompilationConfig>,
+    pub piecewise_graph_file: Option<String>,
+    pub subgraphs: Vec<VllmSubgraphInfo>,
+    pub pre_subgraph_artifacts: Vec<ArtifactInfo>,
+    /// Runtime shapes of the `vllm_cudagraph_capture` artifacts, in log order
+    pub captured_sizes: Vec<i64>,
+    /// Graphs Inductor partitioned, with `use_inductor_graph_partition`
+    pub graph_partitions: Vec<VllmPartitionedGraph>,
+    /// The `vllm_eager_graph` file and its `torch.ops` calls
+    pub eager_graph: Option<(String, IndexMap<String, usize>)>,
+    /// `torch.ops` calls of generated code compiled outside a piecewise subgraph
+    pub whole_graph_ops: IndexMap<String, usize>,
+}
+
+/// What the vLLM parsers found in the log, kept in its `ParserState`
 #[derive(Debug, Default)]
 pub struct VllmState {
-    pub config: RefCell<Option<VllmCompilationConfig>>,
-    pub piecewise_graph_file: RefCell<Option<String>>,
-    pub subgraphs: RefCell<Vec<VllmSubgraphInfo>>,
-    pub pre_subgraph_artifacts: RefCell<Vec<ArtifactInfo>>,
-    pub has_vllm_artifacts: RefCell<bool>,
+    /// In order of first appearance
+    pub engines: Mutex<Vec<VllmEngine>>,
+    /// Index of the engine artifacts are attributed to
+    current_engine: AtomicUsize,
+    pub has_vllm_artifacts: AtomicBool,
+    /// Some engine logged a cudagraph capture, so sizes without one weren't captured
+    captures_logged: AtomicBool,
+    /// Seconds since midnight of the log line being parsed
+    line_time: Mutex<Option<f64>>,
 }
 
 impl VllmState {
-    pub fn new() -> Rc<Self> {
-        Rc::new(Self::default())
+    pub fn has_artifacts(&self) -> bool {
+        self.has_vllm_artifacts.load(Ordering::Relaxed)
     }
 
-    pub fn has_artifacts(&self) -> bool {
-        *self.has_vllm_artifacts.borrow()
+    /// Called for every envelope of the log: any `vllm_*` artifact or graph dump, whether or
+    /// not a vLLM parser handles it, marks the log as a vLLM log and activates the vLLM view
+    pub fn observe(&self, e: &Envelope) {
+        let artifact = e.artifact.as_ref().map(|a| a.name.as_str());
+        let graph_dump = e.graph_dump.as_ref().map(|g| g.name.as_str());
+        if [artifact, graph_dump]
+            .into_iter()
+            .flatten()
+            .any(|name| name.starts_with("vllm_"))
+        {
+            self.has_vllm_artifacts.store(true, Ordering::Relaxed);
+        }
     }
 
-    // Add artifact to current subgraph, or pre_subgraph_artifacts if no subgraph yet
-    pub fn add_artifact(&self, filename: &std::path::Path, suffix: String) {
+    /// Called for every log line before its envelope is parsed, to time subgraph compiles
+    pub fn set_line_time(&self, seconds: f64) {
+        *self.line_time.lock().unwrap() = Some(seconds);
+    }
+
+    fn with_current<R>(&self, f: impl FnOnce(&mut VllmEngine) -> R) -> R {
+        let mut engines = self.engines.lock().unwrap();
+        if engines.is_empty() {
+            engines.push(VllmEngine::default());
+        }
+        let current = self
+            .current_engine
+            .load(Ordering::Relaxed)
+            .min(engines.len() - 1);
+        f(&mut engines[current])
+    }
+
+    /// A compilation config starts (or returns to) the engine with its prefix
+    fn start_engine(&self, config: VllmCompilationConfig) {
+        let mut engines = self.engines.lock().unwrap();
+        let index = match engines.iter().position(|e| e.prefix == config.prefix) {
+            Some(index) => index,
+            // Artifacts logged before the first config belong to its engine
+            None if engines.len() == 1 && engines[0].config.is_none() => {
+                engines[0].prefix = config.prefix.clone();
+                0
+            }
+            None => {
+                engines.push(VllmEngine {
+                    prefix: config.prefix.clone(),
+                    ..Default::default()
+                });
+                engines.len() - 1
+            }
+        };
+        engines[index].config = Some(config);
+        self.current_engine.store(index, Ordering::Relaxed);
+    }
+
+    // Add artifact to the current engine's current subgraph, or its pre_subgraph_artifacts if
+    // no subgraph yet.  `payload` is the raw payload the artifact was made from, for the kernel
+    // stats, and `bytes` the size of the written file
+    pub fn add_artifact(
+        &self,
+        filename: &std::path::Path,
+        suffix: String,
+        payload: &str,
+        bytes: u64,
+    ) {
+        let now = *self.line_time.lock().unwrap();
+        self.with_current(|engine| engine.add_artifact(filename, suffix, payload, bytes, now));
+    }
+}
+
+impl VllmEngine {
+    fn add_artifact(
+        &mut self,
+        filename: &std::path::Path,
+        suffix: String,
+        payload: &str,
+        bytes: u64,
+        now: Option<f64>,
+    ) {
         let url = filename.to_string_lossy().to_string();
         let name = filename
             .file_stem()
@@ -40,26 +165,142 @@ impl VllmState {
 
         // Track piecewise split graph file for linking in summary
         if name.starts_with("vllm_piecewise_split_graph") {
-            *self.piecewise_graph_file.borrow_mut() = Some(url.clone());
+            self.piecewise_graph_file = Some(url.clone());
+        }
+        if name.starts_with(EAGER_GRAPH_DUMP) && self.eager_graph.is_none() {
+            self.eager_graph = Some((url.clone(), count_ops(payload)));
         }
 
-        let artifact = ArtifactInfo { name, url, suffix };
-        let mut subgraphs = self.subgraphs.borrow_mut();
-        if let Some(last) = subgraphs.last_mut() {
+        let is_output_code = name.starts_with("inductor_output_code");
+        let artifact = ArtifactInfo {
+            name,
+            url,
+            suffix,
+            bytes,
+            size: crate::units::bytes(bytes),
+            size_heat: crate::manifest::size_heat(bytes),
+        };
+        if is_output_code && payload.contains("def partition_") {
+            self.add_partitions(partitions_from_code(payload), "inductor_output_code");
+        }
+        if is_output_code {
+            let ops = match self.subgraphs.last_mut() {
+                Some(last) => &mut last.ops,
+                None => &mut self.whole_graph_ops,
+            };
+            for (op, n) in count_ops(payload) {
+                *ops.entry(op).or_default() += n;
+            }
+        }
+        if let Some(last) = self.subgraphs.last_mut() {
+            if is_output_code {
+                last.kernel_count += payload.matches("async_compile.triton(").count();
+                last.code_bytes += payload.len();
+                let mut hasher = Md5::new();
+                hasher.update(last.code_hash.take().unwrap_or_default());
+                hasher.update(payload);
+                last.code_hash = Some(format!("{:x}", hasher.finalize()));
+            }
+            // The file of the next subgraph's compile start is written before the next subgraph
+            // is pushed, so it doesn't count towards this one's compile time
+            let is_next_start = artifact.name.starts_with("vllm_piecewise_compile_start");
+            if let (Some(start), Some(now), false) = (last.start_s, now, is_next_start) {
+                // Logs don't have dates; a compile past midnight wraps around
+                last.compile_time_s = Some((now - start).rem_euclid(86400.0));
+            }
             last.artifacts.push(artifact);
         } else {
-            self.pre_subgraph_artifacts.borrow_mut().push(artifact);
+            self.pre_subgraph_artifacts.push(artifact);
+        }
+    }
+
+    // The partitions of the graph being compiled.  A graph's `vllm_graph_partition` artifact and
+    // its generated code are logged one after the other; the artifact wins.
+    fn add_partitions(&mut self, mut segments: Vec<VllmPartitionSegment>, source: &'static str) {
+        for segment in segments.iter_mut() {
+            segment.label = match segment.partition {
+                Some(i) if segment.ops.is_empty() => format!("partition_{i}"),
+                _ if segment.ops.is_empty() => "eager".to_string(),
+                _ => segment.ops.join(", "),
+            };
+        }
+        let subgraph = self.subgraphs.len().checked_sub(1);
+        let graph = VllmPartitionedGraph {
+            compiled_for: String::new(),
+            submod_name: None,
+            source,
+            num_partitions: segments
+                .iter()
+                .filter(|s| s.partition.is_some() || s.cudagraphable)
+                .count(),
+            num_cudagraphable: segments.iter().filter(|s| s.cudagraphable).count(),
+            segments,
+            subgraph,
+        };
+        match self.graph_partitions.last_mut() {
+            Some(last) if last.subgraph == subgraph && last.source != source => {
+                if source == GRAPH_PARTITION_ARTIFACT {
+                    *last = graph;
+                }
+            }
+            _ => self.graph_partitions.push(graph),
+        }
+    }
+
+    pub fn build_graph_partitions(&self) -> Option<VllmPartitionSummary> {
+        let enabled = self
+            .config
+            .as_ref()
+            .and_then(|c| c.use_inductor_graph_partition)
+            .unwrap_or(false);
+        if self.graph_partitions.is_empty() && !enabled {
+            return None;
         }
+        let graphs: Vec<VllmPartitionedGraph> = self
+            .graph_partitions
+            .iter()
+            .map(|graph| {
+                let subgraph = graph.subgraph.and_then(|i| self.subgraphs.get(i));
+                VllmPartitionedGraph {
+                    compiled_for: subgraph.map_or("whole graph".to_string(), |s| s.size_or_range()),
+                    submod_name: subgraph.map(|s| s.display_submod_name()),
+                    ..graph.clone()
+                }
+            })
+            .collect();
+        let summary = if graphs.is_empty() {
+            format!("use_inductor_graph_partition is on, but the log has neither {GRAPH_PARTITION_ARTIFACT} artifacts nor generated code with partitions.")
+        } else {
+            let mut eager_ops: Vec<&str> = Vec::new();
+            for op in graphs.iter().flat_map(|g| &g.segments).flat_map(|s| &s.ops) {
+                if !eager_ops.contains(&op.as_str()) {
+                    eager_ops.push(op);
+                }
+            }
+            let plural = |n: usize, what: &str| match n {
+                1 => format!("1 {what}"),
+                n => format!("{n} {what}s"),
+            };
+            format!(
+                "{} split into {}, {} of them cudagraph-able; {}.",
+                plural(graphs.len(), "graph"),
+                plural(graphs.iter().map(|g| g.num_partitions).sum(), "partition"),
+                graphs.iter().map(|g| g.num_cudagraphable).sum::<usize>(),
+                if eager_ops.is_empty() {
+                    "nothing runs eagerly between them".to_string()
+                } else {
+                    format!("run eagerly between them: {}", eager_ops.join(", "))
+                }
+            )
+        };
+        Some(VllmPartitionSummary { summary, graphs })
     }
 
     // Group subgraphs by compile range/size for hierarchical display
     pub fn build_compile_range_groups(&self) -> Vec<VllmCompileRangeGroup> {
-        use indexmap::IndexMap;
-
-        let subgraphs = self.subgraphs.borrow();
         let mut groups: IndexMap<String, Vec<VllmSubgraphWithArtifacts>> = IndexMap::new();
 
-        for subgraph in subgraphs.iter() {
+        for subgraph in self.subgraphs.iter() {
             let size_or_range = subgraph.size_or_range();
             let artifact_count = subgraph.artifacts.len();
             groups
@@ -69,19 +310,330 @@ impl VllmState {
                     submod_name: subgraph.display_submod_name(),
                     artifacts: subgraph.artifacts.clone(),
                     artifact_count,
+                    kernel_count: subgraph.kernel_count,
+                    code_bytes: subgraph.code_bytes,
+                    code_size: crate::units::bytes(subgraph.code_bytes as u64),
+                    is_outlier: false,
                 });
         }
 
         groups
             .into_iter()
-            .map(|(size_or_range, submods)| VllmCompileRangeGroup {
-                size_or_range,
-                submod_count: submods.len(),
-                submods,
+            .map(|(size_or_range, mut submods)| {
+                // Flag subgraphs with many times the kernels of the group's median subgraph
+                let mut counts: Vec<usize> = submods.iter().map(|s| s.kernel_count).collect();
+                counts.sort_unstable();
+                let median = counts[(counts.len() - 1) / 2].max(1);
+                for submod in submods.iter_mut() {
+                    submod.is_outlier = counts.len() > 1
+                        && submod.kernel_count >= OUTLIER_MIN_KERNELS
+                        && submod.kernel_count >= OUTLIER_FACTOR * median;
+                }
+                VllmCompileRangeGroup {
+                    size_or_range,
+                    submod_count: submods.len(),
+                    total_kernels: submods.iter().map(|s| s.kernel_count).sum(),
+                    submods,
+                }
             })
             .collect()
     }
 
+    // Compare each submod across the sizes it was compiled for, when at least one submod was
+    // compiled for a single size and something else
+    pub fn build_size_sweep(&self) -> Option<VllmSizeSweep> {
+        let mut by_submod: IndexMap<String, Vec<&VllmSubgraphInfo>> = IndexMap::new();
+        for subgraph in self.subgraphs.iter() {
+            let compiles = by_submod.entry(subgraph.display_submod_name()).or_default();
+            // A size compiled twice (e.g. after a restart) is shown once
+            if !compiles
+                .iter()
+                .any(|s| s.size_or_range() == subgraph.size_or_range())
+            {
+                compiles.push(subgraph);
+            }
+        }
+        if !by_submod
+            .values()
+            .any(|c| c.len() > 1 && c.iter().any(|s| s.is_single_size))
+        {
+            return None;
+        }
+
+        let submods: Vec<VllmSweepSubmod> = by_submod
+            .into_iter()
+            .map(|(submod_name, mut compiles)| {
+                compiles.sort_by_key(|s| {
+                    (s.is_single_size, s.compile_range_start, s.compile_range_end)
+                });
+                let baseline = compiles[0];
+                let entries = compiles
+                    .iter()
+                    .map(|s| {
+                        let same_code_as = compiles
+                            .iter()
+                            .find(|other| {
+                                other.size_or_range() != s.size_or_range()
+                                    && other.code_hash.is_some()
+                                    && other.code_hash == s.code_hash
+                            })
+                            .filter(|_| s.is_single_size)
+                            .map(|other| other.size_or_range());
+                        sweep_entry(
+                            s.size_or_range(),
+                            s.is_single_size,
+                            1,
+                            (s.kernel_count, s.code_bytes, s.compile_time_s),
+                            (baseline.kernel_count, baseline.code_bytes),
+                            std::ptr::eq(*s, baseline),
+                            same_code_as,
+                        )
+                    })
+                    .collect();
+                VllmSweepSubmod {
+                    submod_name,
+                    entries,
+                }
+            })
+            .collect();
+
+        // Totals per size or range: ranges first, then sizes from the smallest
+        let mut order: Vec<&VllmSubgraphInfo> = self.subgraphs.iter().collect();
+        order.sort_by_key(|s| (s.is_single_size, s.compile_range_start, s.compile_range_end));
+        let mut totals: IndexMap<String, Vec<&VllmSweepEntry>> = order
+            .iter()
+            .map(|s| (s.size_or_range(), Vec::new()))
+            .collect();
+        for entry in submods.iter().flat_map(|s| s.entries.iter()) {
+            if let Some(entries) = totals.get_mut(&entry.size_or_range) {
+                entries.push(entry);
+            }
+        }
+        let sum = |entries: &[&VllmSweepEntry]| {
+            let times: Vec<f64> = entries.iter().filter_map(|e| e.compile_time_s).collect();
+            (
+                entries.iter().map(|e| e.kernel_count).sum::<usize>(),
+                entries.iter().map(|e| e.code_bytes).sum::<usize>(),
+                (!times.is_empty()).then(|| times.iter().sum::<f64>()),
+            )
+        };
+        let baseline = sum(&totals[0]);
+        let sizes = totals
+            .iter()
+            .enumerate()
+            .map(|(i, (size_or_range, entries))| {
+                let same_code_as = entries[0].same_code_as.clone().filter(|target| {
+                    entries
+                        .iter()
+                        .all(|e| e.same_code_as.as_ref() == Some(target))
+                });
+                sweep_entry(
+                    size_or_range.clone(),
+                    entries[0].is_single_size,
+                    entries.len(),
+                    sum(entries),
+                    (baseline.0, baseline.1),
+                    i == 0,
+                    same_code_as,
+                )
+            })
+            .collect();
+        Some(VllmSizeSweep { sizes, submods })
+    }
+
+    // Check each size of `cudagraph_capture_sizes` off against the captures and compiles seen
+    pub fn build_cudagraph_checklist(
+        &self,
+        captures_logged: bool,
+    ) -> Option<VllmCudagraphChecklist> {
+        let expected: BTreeSet<i64> = self
+            .config
+            .as_ref()
+            .and_then(|c| c.cudagraph_capture_sizes.as_deref())
+            .map(parse_sizes)
+            .unwrap_or_default();
+        let captured: BTreeSet<i64> = self.captured_sizes.iter().copied().collect();
+        if expected.is_empty() && captured.is_empty() {
+            return None;
+        }
+        let sizes: Vec<VllmCudagraphSize> = expected
+            .union(&captured)
+            .map(|&size| {
+                let compiled_as = self
+                    .subgraphs
+                    .iter()
+                    .find(|s| s.is_single_size && s.compile_range_start == size)
+                    .or_else(|| {
+                        self.subgraphs.iter().find(|s| {
+                            !s.is_single_size
+                                && s.compile_range_start <= size
+                                && size <= s.compile_range_end
+                        })
+                    })
+                    .map(|s| s.size_or_range());
+                let (is_expected, is_captured) =
+                    (expected.contains(&size), captured.contains(&size));
+                let status = match (is_expected, is_captured, &compiled_as) {
+                    (false, _, _) => "unexpected",
+                    (true, true, _) => "captured",
+                    (true, false, None) => "missing",
+                    (true, false, Some(_)) if captures_logged => "compiled only",
+                    (true, false, Some(_)) => "expected",
+                };
+                VllmCudagraphSize {
+                    size,
+                    expected: is_expected,
+                    captured: is_captured,
+                    compiled_as,
+                    status,
+                    problem: matches!(status, "missing" | "compiled only"),
+                }
+            })
+            .collect();
+        let count = |status: &str| sizes.iter().filter(|s| s.status == status).count();
+        let mut problems: Vec<String> = ["compiled only", "missing", "unexpected"]
+            .iter()
+            .map(|status| (status, count(status)))
+            .filter(|(_, n)| *n > 0)
+            .map(|(status, n)| format!("{n} {status}"))
+            .collect();
+        let summary = if !captures_logged {
+            problems.retain(|p| p.ends_with("missing"));
+            format!(
+                "{} capture sizes expected; captures aren't logged, so only compiles are checked{}.",
+                expected.len(),
+                if problems.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", problems.join(", "))
+                }
+            )
+        } else if problems.is_empty() {
+            format!("All {} capture sizes got cudagraphs.", expected.len())
+        } else {
+            format!(
+                "{} of {} capture sizes got cudagraphs: {}.",
+                count("captured"),
+                expected.len(),
+                problems.join(", ")
+            )
+        };
+        Some(VllmCudagraphChecklist {
+            captures_logged,
+            summary,
+            sizes,
+        })
+    }
+
+    // Compare the custom ops of the eager graph capture with the calls the generated code has left
+    pub fn build_op_comparison(&self) -> Option<VllmOpComparison> {
+        let (eager_graph_file, eager_ops) = self.eager_graph.as_ref()?;
+        // Each size or range compiles every subgraph again; each is compared on its own and the
+        // one with the most calls left wins
+        let mut compiles: IndexMap<String, IndexMap<&str, usize>> = IndexMap::new();
+        compiles.insert(
+            "whole graph".to_string(),
+            self.whole_graph_ops
+                .iter()
+                .map(|(op, n)| (op.as_str(), *n))
+                .collect(),
+        );
+        for subgraph in &self.subgraphs {
+            let ops = compiles.entry(subgraph.size_or_range()).or_default();
+            for (op, n) in &subgraph.ops {
+                *ops.entry(op).or_default() += n;
+            }
+        }
+        let compiled_count = |op: &str| {
+            compiles
+                .values()
+                .map(|ops| ops.get(op).copied().unwrap_or(0))
+                .max()
+                .unwrap_or(0)
+        };
+        let splitting_ops: Vec<&str> = self
+            .config
+            .as_ref()
+            .and_then(|c| c.splitting_ops.as_deref())
+            .map(|ops| {
+                ops.split(|c: char| !(c.is_alphanumeric() || "_:.".contains(c)))
+                    .filter(|op| !op.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut fused_ops: Vec<&str> = Vec::new();
+        for op in compiles.values().flat_map(|ops| ops.keys()) {
+            if is_custom_op(op) && !eager_ops.contains_key(*op) && !fused_ops.contains(op) {
+                fused_ops.push(op);
+            }
+        }
+        let op_name = |op: &str| op.split_once('.').map_or(op, |(_, name)| name).to_string();
+
+        let mut rows: Vec<VllmOpComparisonRow> = eager_ops
+            .iter()
+            .filter(|(op, _)| is_custom_op(op))
+            .map(|(op, &eager_count)| {
+                let compiled_count = compiled_count(op);
+                let fused_into = fused_ops.iter().find(|f| op_name(f).contains(&op_name(op)));
+                let (status, problem) =
+                    if splitting_ops.contains(&op.replacen('.', "::", 1).as_str()) {
+                        ("split out".to_string(), false)
+                    } else if compiled_count >= eager_count {
+                        ("unfused".to_string(), true)
+                    } else if compiled_count > 0 {
+                        ("partly fused".to_string(), true)
+                    } else if let Some(fused) = fused_into {
+                        (format!("fused into {fused}"), false)
+                    } else {
+                        ("decomposed".to_string(), true)
+                    };
+                VllmOpComparisonRow {
+                    op: op.clone(),
+                    eager_count,
+                    compiled_count,
+                    status,
+                    problem,
+                }
+            })
+            .collect();
+        let num_eager = rows.len();
+        let mut counts: IndexMap<&str, usize> = IndexMap::new();
+        for row in &rows {
+            let kind = row.status.split(" into").next().unwrap();
+            *counts.entry(kind).or_default() += 1;
+        }
+        let mut summary = match num_eager {
+            0 => "No custom ops in the eager graph.".to_string(),
+            n => format!(
+                "{n} custom {} in the eager graph: {}.",
+                if n == 1 { "op" } else { "ops" },
+                counts
+                    .iter()
+                    .map(|(kind, n)| format!("{n} {kind}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        if !fused_ops.is_empty() {
+            summary.push_str(&format!(
+                "  {} only in the generated code, from fusion passes.",
+                fused_ops.join(", ")
+            ));
+        }
+        rows.extend(fused_ops.iter().map(|op| VllmOpComparisonRow {
+            op: op.to_string(),
+            eager_count: 0,
+            compiled_count: compiled_count(op),
+            status: "from a fusion".to_string(),
+            problem: false,
+        }));
+        Some(VllmOpComparison {
+            eager_graph_file: eager_graph_file.clone(),
+            summary,
+            rows,
+        })
+    }
+
     // Get dynamo artifacts from pre_subgraph_artifacts
     pub fn build_dynamo_artifacts(&self) -> Vec<ArtifactInfo> {
         let dynamo_names = [
@@ -91,26 +643,174 @@ impl VllmState {
             "compilation_metrics",
         ];
         self.pre_subgraph_artifacts
-            .borrow()
             .iter()
             .filter(|a| dynamo_names.iter().any(|name| a.name.starts_with(name)))
             .cloned()
             .collect()
     }
+
+    // Every artifact seen, in log order: pre-subgraph ones first, then per subgraph
+    pub fn build_artifact_inventory(&self) -> Vec<ArtifactInfo> {
+        self.pre_subgraph_artifacts
+            .iter()
+            .chain(self.subgraphs.iter().flat_map(|s| s.artifacts.iter()))
+            .cloned()
+            .collect()
+    }
 }
 
-// Parses vllm_compilation_config artifacts.
-// Stores config in state for display, outputs formatted JSON file.
-pub struct VllmCompilationConfigParser {
-    state: Rc<VllmState>,
+fn is_custom_op(op: &str) -> bool {
+    op.split_once('.')
+        .is_some_and(|(namespace, _)| !BUILTIN_OP_NAMESPACES.contains(&namespace))
 }
 
-impl VllmCompilationConfigParser {
-    pub fn new(state: Rc<VllmState>) -> Self {
-        Self { state }
+/// `torch.ops` calls of a graph or generated code, comments left out, e.g. `vllm.rms_norm` -> 2
+fn count_ops(code: &str) -> IndexMap<String, usize> {
+    let mut ops: IndexMap<String, usize> = IndexMap::new();
+    for line in code.lines().filter(|l| !l.trim_start().starts_with('#')) {
+        for caps in TORCH_OP.captures_iter(line) {
+            *ops.entry(caps[1].to_string()).or_default() += 1;
+        }
     }
+    ops
 }
 
+/// The sizes of a config list like `[1, 2, 4]` or `1, 2, 4`
+/// The segments of partitioned generated code, in the order `call` runs them: a segment per
+/// partition it calls, with the Triton kernels the partition launches, and one for the ops it
+/// runs itself between partitions
+fn partitions_from_code(code: &str) -> Vec<VllmPartitionSegment> {
+    let mut kernels: IndexMap<usize, usize> = IndexMap::new();
+    let mut segments: Vec<VllmPartitionSegment> = Vec::new();
+    let mut function: Option<&str> = None;
+    let mut partition = None;
+    for line in code.lines() {
+        if let Some(name) = line.strip_prefix("def ") {
+            function = name.split('(').next();
+            partition = PARTITION_DEF
+                .captures(line)
+                .and_then(|caps| caps[1].parse::<usize>().ok());
+            if let Some(i) = partition {
+                kernels.insert(i, 0);
+            }
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            function = None;
+            partition = None;
+            continue;
+        }
+        if let Some(i) = partition {
+            *kernels.entry(i).or_default() += line.matches(".run(").count();
+            continue;
+        }
+        if function != Some("call") {
+            continue;
+        }
+        if let Some(caps) = PARTITION_CALL.captures(line) {
+            let i = caps
+                .get(1)
+                .or(caps.get(2))
+                .and_then(|m| m.as_str().parse().ok());
+            segments.push(VllmPartitionSegment {
+                partition: i,
+                cudagraphable: true,
+                num_nodes: None,
+                num_kernels: 0,
+                ops: Vec::new(),
+                label: String::new(),
+            });
+        } else if let Some(caps) = TORCH_OP.captures(line) {
+            let op = caps[1].to_string();
+            match segments.last_mut() {
+                Some(last) if !last.cudagraphable => {
+                    if !last.ops.contains(&op) {
+                        last.ops.push(op);
+                    }
+                }
+                _ => segments.push(VllmPartitionSegment {
+                    partition: None,
+                    cudagraphable: false,
+                    num_nodes: None,
+                    num_kernels: 0,
+                    ops: vec![op],
+                    label: String::new(),
+                }),
+            }
+        }
+    }
+    // Code whose `call` doesn't name its partitions still has them
+    if !segments.iter().any(|s| s.cudagraphable) {
+        segments.extend(kernels.keys().map(|&i| VllmPartitionSegment {
+            partition: Some(i),
+            cudagraphable: true,
+            num_nodes: None,
+            num_kernels: 0,
+            ops: Vec::new(),
+            label: String::new(),
+        }));
+    }
+    for segment in segments.iter_mut() {
+        if let Some(n) = segment.partition.and_then(|i| kernels.get(&i)) {
+            segment.num_kernels = *n;
+        }
+    }
+    segments
+}
+
+fn parse_sizes(sizes: &str) -> BTreeSet<i64> {
+    sizes
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
+
+fn sweep_entry(
+    size_or_range: String,
+    is_single_size: bool,
+    num_submods: usize,
+    (kernel_count, code_bytes, compile_time_s): (usize, usize, Option<f64>),
+    (baseline_kernels, baseline_bytes): (usize, usize),
+    is_baseline: bool,
+    same_code_as: Option<String>,
+) -> VllmSweepEntry {
+    let (kernel_trend, code_trend) = if is_baseline {
+        (String::new(), String::new())
+    } else {
+        let kernel_trend = match kernel_count.cmp(&baseline_kernels) {
+            std::cmp::Ordering::Equal => "=".to_string(),
+            std::cmp::Ordering::Greater => format!("+{}", kernel_count - baseline_kernels),
+            std::cmp::Ordering::Less => format!("-{}", baseline_kernels - kernel_count),
+        };
+        let code_trend = if baseline_bytes == 0 {
+            String::new()
+        } else {
+            format!(
+                "{:+.0}%",
+                (code_bytes as f64 / baseline_bytes as f64 - 1.0) * 100.0
+            )
+        };
+        (kernel_trend, code_trend)
+    };
+    VllmSweepEntry {
+        size_or_range,
+        is_single_size,
+        num_submods,
+        kernel_count,
+        code_bytes,
+        code_size: crate::units::bytes(code_bytes as u64),
+        compile_time: compile_time_s.map_or(String::new(), |t| crate::units::seconds(t, 2)),
+        compile_time_s,
+        kernel_trend,
+        code_trend,
+        same_code_as,
+    }
+}
+
+// Parses vllm_compilation_config artifacts.
+// Stores config in state for display, outputs formatted JSON file.
+pub struct VllmCompilationConfigParser;
+
 impl StructuredLogParser for VllmCompilationConfigParser {
     fn name(&self) -> &'static str {
         "vllm_compilation_config"
@@ -125,17 +825,19 @@ impl StructuredLogParser for VllmCompilationConfigParser {
         None
     }
 
-    fn parse<'e>(
+    fn parse_with_state<'e>(
         &self,
+        state: &ParserState,
         lineno: usize,
         _metadata: Metadata<'e>,
         _rank: Option<u32>,
         compile_id: &Option<CompileId>,
         payload: &str,
     ) -> anyhow::Result<ParserResults> {
+        let state = state.get::<VllmState>();
         if let Ok(config) = serde_json::from_str::<VllmCompilationConfig>(payload) {
-            *self.state.config.borrow_mut() = Some(config);
-            *self.state.has_vllm_artifacts.borrow_mut() = true;
+            state.start_engine(config);
+            state.has_vllm_artifacts.store(true, Ordering::Relaxed);
         }
 
         let f = build_file_path("vllm_compilation_config.json", lineno, compile_id);
@@ -149,15 +851,7 @@ impl StructuredLogParser for VllmCompilationConfigParser {
 // Parses vllm_piecewise_compile_start artifacts and vllm_subgraph_*/vllm_submod_* graph dumps.
 // On compile_start: pushes new VllmSubgraphInfo to state.subgraphs (subsequent artifacts attach here).
 // On graph_dump: adds artifact to current subgraph and outputs the graph file.
-pub struct VllmPiecewiseCompileParser {
-    state: Rc<VllmState>,
-}
-
-impl VllmPiecewiseCompileParser {
-    pub fn new(state: Rc<VllmState>) -> Self {
-        Self { state }
-    }
-}
+pub struct VllmPiecewiseCompileParser;
 
 impl StructuredLogParser for VllmPiecewiseCompileParser {
     fn name(&self) -> &'static str {
@@ -180,20 +874,23 @@ impl StructuredLogParser for VllmPiecewiseCompileParser {
         None
     }
 
-    fn parse<'e>(
+    fn parse_with_state<'e>(
         &self,
+        state: &ParserState,
         lineno: usize,
         metadata: Metadata<'e>,
         _rank: Option<u32>,
         compile_id: &Option<CompileId>,
         payload: &str,
     ) -> anyhow::Result<ParserResults> {
-        *self.state.has_vllm_artifacts.borrow_mut() = true;
+        let state = state.get::<VllmState>();
+        state.has_vllm_artifacts.store(true, Ordering::Relaxed);
 
         match metadata {
             Metadata::Artifact(_artifact) => {
-                if let Ok(subgraph) = serde_json::from_str::<VllmSubgraphInfo>(payload) {
-                    self.state.subgraphs.borrow_mut().push(subgraph);
+                if let Ok(mut subgraph) = serde_json::from_str::<VllmSubgraphInfo>(payload) {
+                    subgraph.start_s = *state.line_time.lock().unwrap();
+                    state.with_current(|engine| engine.subgraphs.push(subgraph));
                 }
                 Ok(Vec::new())
             }
@@ -211,15 +908,7 @@ impl StructuredLogParser for VllmPiecewiseCompileParser {
 
 // Parses vllm_piecewise_split_graph graph dumps.
 // Stores path in state for linking in summary, outputs the graph file.
-pub struct VllmPiecewiseSplitGraphParser {
-    state: Rc<VllmState>,
-}
-
-impl VllmPiecewiseSplitGraphParser {
-    pub fn new(state: Rc<VllmState>) -> Self {
-        Self { state }
-    }
-}
+pub struct VllmPiecewiseSplitGraphParser;
 
 impl StructuredLogParser for VllmPiecewiseSplitGraphParser {
     fn name(&self) -> &'static str {
@@ -235,53 +924,254 @@ impl StructuredLogParser for VllmPiecewiseSplitGraphParser {
         None
     }
 
-    fn parse<'e>(
+    fn parse_with_state<'e>(
         &self,
+        state: &ParserState,
         lineno: usize,
         _metadata: Metadata<'e>,
         _rank: Option<u32>,
         compile_id: &Option<CompileId>,
         _payload: &str,
     ) -> anyhow::Result<ParserResults> {
+        let state = state.get::<VllmState>();
         let filename = "vllm_piecewise_split_graph.txt";
         let f = build_file_path(filename, lineno, compile_id);
-        *self.state.has_vllm_artifacts.borrow_mut() = true;
+        state.has_vllm_artifacts.store(true, Ordering::Relaxed);
+        Ok(vec![ParserOutput::PayloadFile(f)])
+    }
+}
+
+// Parses vllm_eager_graph graph dumps: the graph captured before compiling, whose custom ops
+// are compared against the generated code.  Outputs the graph file; logged as an artifact, the
+// generic artifact parser writes it instead.
+pub struct VllmEagerGraphParser;
+
+impl StructuredLogParser for VllmEagerGraphParser {
+    fn name(&self) -> &'static str {
+        EAGER_GRAPH_DUMP
+    }
+
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        if let Some(graph_dump) = &e.graph_dump {
+            if graph_dump.name == EAGER_GRAPH_DUMP {
+                return Some(Metadata::GraphDump(graph_dump));
+            }
+        }
+        None
+    }
+
+    fn parse_with_state<'e>(
+        &self,
+        state: &ParserState,
+        lineno: usize,
+        _metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        compile_id: &Option<CompileId>,
+        _payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let state = state.get::<VllmState>();
+        state.has_vllm_artifacts.store(true, Ordering::Relaxed);
+        let f = build_file_path(&format!("{EAGER_GRAPH_DUMP}.txt"), lineno, compile_id);
+        // add_file_output will call add_artifact for us, which counts the graph's ops
         Ok(vec![ParserOutput::PayloadFile(f)])
     }
 }
 
-pub fn vllm_parsers_with_state(state: Rc<VllmState>) -> Vec<Box<dyn StructuredLogParser>> {
+#[derive(Deserialize)]
+struct VllmCudagraphCapture {
+    #[serde(alias = "batch_size", alias = "num_tokens")]
+    runtime_shape: i64,
+}
+
+// Parses vllm_cudagraph_capture artifacts, logged once per captured cudagraph.
+// Records the captured size on the current engine for the capture checklist.
+pub struct VllmCudagraphCaptureParser;
+
+impl StructuredLogParser for VllmCudagraphCaptureParser {
+    fn name(&self) -> &'static str {
+        "vllm_cudagraph_capture"
+    }
+
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        if let Some(artifact) = &e.artifact {
+            if artifact.name == "vllm_cudagraph_capture" {
+                return Some(Metadata::Artifact(artifact));
+            }
+        }
+        None
+    }
+
+    fn parse_with_state<'e>(
+        &self,
+        state: &ParserState,
+        _lineno: usize,
+        _metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        _compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let state = state.get::<VllmState>();
+        state.has_vllm_artifacts.store(true, Ordering::Relaxed);
+        let capture: VllmCudagraphCapture = serde_json::from_str(payload)?;
+        state.captures_logged.store(true, Ordering::Relaxed);
+        state.with_current(|engine| engine.captured_sizes.push(capture.runtime_shape));
+        Ok(Vec::new())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VllmGraphPartition {
+    partitions: Vec<VllmPartitionSegment>,
+}
+
+// Parses vllm_graph_partition artifacts, logged per graph compiled with
+// use_inductor_graph_partition: its segments, in order, and whether each is cudagraph-able.
+pub struct VllmGraphPartitionParser;
+
+impl StructuredLogParser for VllmGraphPartitionParser {
+    fn name(&self) -> &'static str {
+        GRAPH_PARTITION_ARTIFACT
+    }
+
+    fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+        if let Some(artifact) = &e.artifact {
+            if artifact.name == GRAPH_PARTITION_ARTIFACT {
+                return Some(Metadata::Artifact(artifact));
+            }
+        }
+        None
+    }
+
+    fn parse_with_state<'e>(
+        &self,
+        state: &ParserState,
+        _lineno: usize,
+        _metadata: Metadata<'e>,
+        _rank: Option<u32>,
+        _compile_id: &Option<CompileId>,
+        payload: &str,
+    ) -> anyhow::Result<ParserResults> {
+        let state = state.get::<VllmState>();
+        state.has_vllm_artifacts.store(true, Ordering::Relaxed);
+        let metadata: VllmGraphPartition = serde_json::from_str(payload)?;
+        state.with_current(|engine| {
+            engine.add_partitions(metadata.partitions, GRAPH_PARTITION_ARTIFACT)
+        });
+        Ok(Vec::new())
+    }
+}
+
+pub fn vllm_parsers() -> Vec<Box<dyn StructuredLogParser>> {
     vec![
-        Box::new(VllmCompilationConfigParser::new(state.clone())),
-        Box::new(VllmPiecewiseSplitGraphParser::new(state.clone())),
-        Box::new(VllmPiecewiseCompileParser::new(state.clone())),
+        Box::new(VllmCompilationConfigParser),
+        Box::new(VllmPiecewiseSplitGraphParser),
+        Box::new(VllmEagerGraphParser),
+        Box::new(VllmPiecewiseCompileParser),
+        Box::new(VllmCudagraphCaptureParser),
+        Box::new(VllmGraphPartitionParser),
     ]
 }
 
+/// Display name of the `i`th engine: its prefix, if it has one
+fn engine_name(i: usize, engine: &VllmEngine) -> String {
+    engine
+        .prefix
+        .clone()
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| format!("engine {i}"))
+}
+
 pub fn generate_vllm_summary(
     state: &VllmState,
     tt: &TinyTemplate,
     custom_header_html: &str,
+    metadata: Vec<(String, String)>,
+    config_docs: &crate::config_docs::ConfigDocs,
 ) -> anyhow::Result<String> {
-    let config = state.config.borrow().clone().unwrap_or_default();
-    let dynamo_artifacts = state.build_dynamo_artifacts();
-    let has_dynamo_artifacts = !dynamo_artifacts.is_empty();
-    let piecewise_graph_file = state.piecewise_graph_file.borrow().clone();
-    let has_piecewise = piecewise_graph_file.is_some();
-    let compile_range_groups = state.build_compile_range_groups();
+    let engines = state.engines.lock().unwrap();
+    let summaries: Vec<VllmEngineSummary> = engines
+        .iter()
+        .enumerate()
+        .map(|(i, engine)| {
+            let dynamo_artifacts = engine.build_dynamo_artifacts();
+            VllmEngineSummary {
+                name: engine_name(i, engine),
+                anchor: format!("engine-{i}"),
+                has_config: engine.config.is_some(),
+                config: engine.config.clone().unwrap_or_default(),
+                has_dynamo_artifacts: !dynamo_artifacts.is_empty(),
+                dynamo_artifacts,
+                has_piecewise: engine.piecewise_graph_file.is_some(),
+                piecewise_graph_file: engine.piecewise_graph_file.clone(),
+                num_subgraphs: engine.subgraphs.len(),
+                compile_range_groups: engine.build_compile_range_groups(),
+                size_sweep: engine.build_size_sweep(),
+                cudagraph_checklist: engine
+                    .build_cudagraph_checklist(state.captures_logged.load(Ordering::Relaxed)),
+                graph_partitions: engine.build_graph_partitions(),
+                op_comparison: engine.build_op_comparison(),
+            }
+        })
+        .collect();
 
     let context = VllmSummaryContext {
         css: super::templates::VLLM_CSS.to_string(),
         qps: TEMPLATE_QUERY_PARAM_SCRIPT.to_string(),
         custom_header_html: custom_header_html.to_string(),
-        has_config: state.config.borrow().is_some(),
-        config,
-        dynamo_artifacts,
-        has_dynamo_artifacts,
-        piecewise_graph_file,
-        has_piecewise,
-        compile_range_groups,
+        metadata,
+        config_docs: config_docs.vllm_fields(),
+        multiple_engines: summaries.len() > 1,
+        num_engines: summaries.len(),
+        engines: summaries,
     };
 
-    Ok(tt.render("vllm_summary.html", &context)?)
+    Ok(crate::report_model::render(
+        tt,
+        "vllm_summary.html",
+        &context,
+    ))
+}
+
+pub fn generate_vllm_summary_json(
+    state: &VllmState,
+    timing: Vec<VllmCompileTiming>,
+) -> anyhow::Result<String> {
+    let engines = state.engines.lock().unwrap();
+    let engine_summaries: Vec<VllmEngineJson> = engines
+        .iter()
+        .enumerate()
+        .map(|(i, engine)| VllmEngineJson {
+            name: engine_name(i, engine),
+            prefix: engine.prefix.clone(),
+            config: engine.config.clone(),
+            piecewise_graph_file: engine.piecewise_graph_file.clone(),
+            subgraphs: engine.subgraphs.clone(),
+            compile_range_groups: engine.build_compile_range_groups(),
+            dynamo_artifacts: engine.build_dynamo_artifacts(),
+            size_sweep: engine.build_size_sweep(),
+            cudagraph_checklist: engine
+                .build_cudagraph_checklist(state.captures_logged.load(Ordering::Relaxed)),
+            graph_partitions: engine.build_graph_partitions(),
+            op_comparison: engine.build_op_comparison(),
+        })
+        .collect();
+    let first = engine_summaries.first();
+    let summary = VllmSummaryJson {
+        config: first.and_then(|e| e.config.clone()),
+        piecewise_graph_file: first.and_then(|e| e.piecewise_graph_file.clone()),
+        subgraphs: first.map(|e| e.subgraphs.clone()).unwrap_or_default(),
+        compile_range_groups: first
+            .map(|e| e.compile_range_groups.clone())
+            .unwrap_or_default(),
+        dynamo_artifacts: first
+            .map(|e| e.dynamo_artifacts.clone())
+            .unwrap_or_default(),
+        artifacts: engines
+            .iter()
+            .flat_map(|e| e.build_artifact_inventory())
+            .collect(),
+        engines: engine_summaries,
+        timing,
+    };
+    Ok(serde_json::to_string_pretty(&summary)?)
 }
@@ -31,6 +31,10 @@ h3 {
     border: 1px solid #ddd;
     text-align: left;
 }
+.config-table td[title]:not([title=""]) strong {
+    text-decoration: underline dotted;
+    cursor: help;
+}
 .config-table tr:nth-child(even) {
     background: #f9f9f9;
 }
@@ -117,6 +121,47 @@ h3 {
 .artifact-list a:hover {
     text-decoration: underline;
 }
+.kernel-stats td:nth-child(n+2) {
+    text-align: right;
+}
+.config-table tr.same-code {
+    color: #888;
+}
+.sweep-trend {
+    color: #888;
+    font-size: 0.9em;
+}
+.config-table tr.kernel-outlier {
+    background: #fde2e2;
+    font-weight: bold;
+}
+.config-table tr.cudagraph-problem {
+    background: #fde2e2;
+}
+.config-table tr.op-problem {
+    background: #fde2e2;
+}
+.config-table tr.eager-segment {
+    background: #fff4e5;
+}
+.partition-source {
+    color: #888;
+    font-size: 0.9em;
+}
+.engine-name {
+    margin-top: 40px;
+    font-size: 1.5em;
+}
+.size-badge {
+    font-size: 0.8em;
+    padding: 0 4px;
+    border-radius: 3px;
+    background: #eee;
+    color: #555;
+}
+.size-badge.size-warm { background: #fff1c2; color: #7a5a00; }
+.size-badge.size-hot { background: #ffd0a8; color: #8a3a00; }
+.size-badge.size-huge { background: #d93025; color: white; font-weight: bold; }
 .summary-box {
     background: white;
     padding: 15px;
@@ -144,91 +189,247 @@ pub const VLLM_SUMMARY_TEMPLATE: &str = r#"<!DOCTYPE html>
 </head>
 <body>
 {custom_header_html | format_unescaped}
+{{ if metadata }}
+<table class="report-metadata">
+{{ for row in metadata }}
+<tr> <th> {row.0} </th> <td> {row.1} </td> </tr>
+{{ endfor }}
+</table>
+{{ endif }}
     <div style="background: #e8f4fd; border: 1px solid #4a90d9; border-radius: 5px; padding: 10px 15px; margin-bottom: 20px;">
         This is the vLLM compilation view. <a href="tlparse_index.html">View original tlparse output →</a>
+        (also available as <a href="vllm_summary.json">vllm_summary.json</a>)
     </div>
     <h1>vLLM Compilation Summary</h1>
 
-    {{ if has_config }}
-    <h2>Compilation Configuration</h2>
-    <details open>
-        <summary><strong>Core Settings</strong></summary>
-        <table class="config-table">
-            <tr><td><strong>Model</strong></td><td>{config.model}</td></tr>
-            <tr><td><strong>Mode</strong></td><td>{config.mode}</td></tr>
-            <tr><td><strong>Backend</strong></td><td>{config.backend}</td></tr>
-            <tr><td><strong>Prefix</strong></td><td>{config.prefix}</td></tr>
-            <tr><td><strong>Custom Ops</strong></td><td>{config.custom_ops}</td></tr>
-            <tr><td><strong>Splitting Ops</strong></td><td>{config.splitting_ops}</td></tr>
-        </table>
-    </details>
-    <details open>
-        <summary><strong>Compile Settings</strong></summary>
-        <table class="config-table">
-            <tr><td><strong>CUDAGraph Mode</strong></td><td>{config.cudagraph_mode}</td></tr>
-            <tr><td><strong>Use Inductor Graph Partition</strong></td><td>{config.use_inductor_graph_partition}</td></tr>
-            <tr><td><strong>Compile Sizes</strong></td><td>{config.compile_sizes}</td></tr>
-            <tr><td><strong>Compile Ranges Split Points</strong></td><td>{config.compile_ranges_split_points}</td></tr>
-            <tr><td><strong>Inductor Passes</strong></td><td>{config.inductor_passes}</td></tr>
-            <tr><td><strong>Enabled Passes</strong></td><td>{config.enabled_passes}</td></tr>
-            <tr><td><strong>Dynamic Shapes Type</strong></td><td>{config.dynamic_shapes_type}</td></tr>
-            <tr><td><strong>Dynamic Shapes Evaluate Guards</strong></td><td>{config.dynamic_shapes_evaluate_guards}</td></tr>
-        </table>
-    </details>
-    {{ endif }}
-
     <div class="summary-box">
         <p>PT2 generates <a href="chromium_events.json">Chromium Trace Events</a> in JSON on specific events during compilation.
         You can download and view them in a tool like <a href="https://ui.perfetto.dev/">Perfetto</a>.</p>
     </div>
 
-    {{ if has_dynamo_artifacts }}
-    <h2>Dynamo Compilation</h2>
+    {{ if multiple_engines }}
     <div class="summary-box">
+        <p>This log has compiles of {num_engines} vLLM engine instances, shown separately:</p>
         <ul class="artifact-list">
-        {{ for artifact in dynamo_artifacts }}
-            <li><a href="{artifact.url}">{artifact.name}</a> {artifact.suffix}</li>
+        {{ for engine in engines }}
+            <li><a href='#{engine.anchor}'>{engine.name}</a>, subgraphs: {engine.num_subgraphs}</li>
         {{ endfor }}
         </ul>
     </div>
     {{ endif }}
 
-    {{ if has_piecewise }}
-    <h2>Piecewise Split Graph</h2>
+    {{ for engine in engines }}
+    <div class="engine" id="{engine.anchor}">
+        {{ if multiple_engines }}<h1 class="engine-name">Engine {engine.name}</h1>{{ endif }}
+        {{ if engine.has_config }}
+        <h2>Compilation Configuration</h2>
+        <details open>
+            <summary><strong>Core Settings</strong></summary>
+            <table class="config-table">
+                <tr><td title="{config_docs.model}"><strong>Model</strong></td><td>{engine.config.model}</td></tr>
+                <tr><td title="{config_docs.mode}"><strong>Mode</strong></td><td>{engine.config.mode}</td></tr>
+                <tr><td title="{config_docs.backend}"><strong>Backend</strong></td><td>{engine.config.backend}</td></tr>
+                <tr><td title="{config_docs.prefix}"><strong>Prefix</strong></td><td>{engine.config.prefix}</td></tr>
+                <tr><td title="{config_docs.custom_ops}"><strong>Custom Ops</strong></td><td>{engine.config.custom_ops}</td></tr>
+                <tr><td title="{config_docs.splitting_ops}"><strong>Splitting Ops</strong></td><td>{engine.config.splitting_ops}</td></tr>
+            </table>
+        </details>
+        <details open>
+            <summary><strong>Compile Settings</strong></summary>
+            <table class="config-table">
+                <tr><td title="{config_docs.cudagraph_mode}"><strong>CUDAGraph Mode</strong></td><td>{engine.config.cudagraph_mode}</td></tr>
+                <tr><td title="{config_docs.use_inductor_graph_partition}"><strong>Use Inductor Graph Partition</strong></td><td>{engine.config.use_inductor_graph_partition}</td></tr>
+                <tr><td title="{config_docs.cudagraph_capture_sizes}"><strong>CUDAGraph Capture Sizes</strong></td><td>{engine.config.cudagraph_capture_sizes}</td></tr>
+                <tr><td title="{config_docs.compile_sizes}"><strong>Compile Sizes</strong></td><td>{engine.config.compile_sizes}</td></tr>
+                <tr><td title="{config_docs.compile_ranges_split_points}"><strong>Compile Ranges Split Points</strong></td><td>{engine.config.compile_ranges_split_points}</td></tr>
+                <tr><td title="{config_docs.inductor_passes}"><strong>Inductor Passes</strong></td><td>{engine.config.inductor_passes}</td></tr>
+                <tr><td title="{config_docs.enabled_passes}"><strong>Enabled Passes</strong></td><td>{engine.config.enabled_passes}</td></tr>
+                <tr><td title="{config_docs.dynamic_shapes_type}"><strong>Dynamic Shapes Type</strong></td><td>{engine.config.dynamic_shapes_type}</td></tr>
+                <tr><td title="{config_docs.dynamic_shapes_evaluate_guards}"><strong>Dynamic Shapes Evaluate Guards</strong></td><td>{engine.config.dynamic_shapes_evaluate_guards}</td></tr>
+            </table>
+        </details>
+        {{ endif }}
+
+        {{ if engine.cudagraph_checklist }}
+        <h2>CUDAGraph Capture Sizes</h2>
+        <div class="summary-box">
+            <p>{engine.cudagraph_checklist.summary}  Each size of <code>cudagraph_capture_sizes</code> should be captured, and served by its own compile or a range compile covering it.  <em>compiled only</em>: compiled for, but no cudagraph was captured; <em>missing</em>: no compile covers the size; <em>unexpected</em>: captured without being listed.</p>
+            <table class="config-table kernel-stats cudagraph-checklist">
+                <tr><th>Size</th><th>Expected</th><th>Captured</th><th>Compiled As</th><th>Status</th></tr>
+                {{ for entry in engine.cudagraph_checklist.sizes }}
+                <tr{{ if entry.problem }} class="cudagraph-problem"{{ endif }}><td>{entry.size}</td><td>{{ if entry.expected }}✓{{ endif }}</td><td>{{ if entry.captured }}✓{{ endif }}</td><td>{entry.compiled_as}</td><td>{entry.status}</td></tr>
+                {{ endfor }}
+            </table>
+        </div>
+        {{ endif }}
+
+        {{ if engine.has_dynamo_artifacts }}
+        <h2>Dynamo Compilation</h2>
+        <div class="summary-box">
+            <ul class="artifact-list">
+            {{ for artifact in engine.dynamo_artifacts }}
+                <li><a href="{artifact.url}">{artifact.name}</a> {artifact.suffix}{{ if artifact.size }} <span class="size-badge {artifact.size_heat}">{artifact.size}</span>{{ endif }}</li>
+            {{ endfor }}
+            </ul>
+        </div>
+        {{ endif }}
+
+        {{ if engine.has_piecewise }}
+        <h2>Piecewise Split Graph</h2>
+        <div class="summary-box">
+            <ul class="artifact-list">
+                <li><a href="{engine.piecewise_graph_file}">vllm_piecewise_split_graph</a></li>
+            </ul>
+        </div>
+        {{ endif }}
+
+        {{ if engine.op_comparison }}
+        <h2>Eager vs Compiled Ops</h2>
+        <div class="summary-box">
+            <p>{engine.op_comparison.summary}  The custom ops of the <a href="{engine.op_comparison.eager_graph_file}">eager graph</a> vLLM captured before compiling, against the calls left in the generated code of one size or range.  <em>unfused</em>: still called as is; <em>decomposed</em>: gone with no fused op in its place, so Inductor generated code for it; <em>split out</em>: a splitting op, run eagerly between the subgraphs.</p>
+            <table class="config-table kernel-stats op-comparison">
+                <tr><th>Op</th><th>Eager Calls</th><th>Compiled Calls</th><th>Status</th></tr>
+                {{ for row in engine.op_comparison.rows }}
+                <tr{{ if row.problem }} class="op-problem"{{ endif }}><td><code>{row.op}</code></td><td>{row.eager_count}</td><td>{row.compiled_count}</td><td>{row.status}</td></tr>
+                {{ endfor }}
+            </table>
+        </div>
+        {{ endif }}
+
+        {{ if engine.graph_partitions }}
+        <h2>Inductor Graph Partitions</h2>
+        <div class="summary-box">
+            <p>{engine.graph_partitions.summary}  With <code>use_inductor_graph_partition</code> the graph isn't split by Dynamo into piecewise subgraphs; Inductor partitions it around the splitting ops instead, and each partition is captured as its own cudagraph.</p>
+            {{ for graph in engine.graph_partitions.graphs }}
+            <h4>{graph.compiled_for}{{ if graph.submod_name }}, {graph.submod_name}{{ endif }}</h4>
+            <table class="config-table kernel-stats graph-partitions">
+                <tr><th>Segment</th><th>CUDAGraph-able</th><th>Nodes</th><th>Triton Kernels</th></tr>
+                {{ for segment in graph.segments }}
+                <tr{{ if not segment.cudagraphable }} class="eager-segment"{{ endif }}><td>{segment.label}</td><td>{{ if segment.cudagraphable }}✓{{ endif }}</td><td>{segment.num_nodes}</td><td>{segment.num_kernels}</td></tr>
+                {{ endfor }}
+            </table>
+            <p class="partition-source">From {graph.source}</p>
+            {{ endfor }}
+        </div>
+        {{ endif }}
+
+        {{ if engine.size_sweep }}
+        <h2>Compile Sizes Sweep</h2>
+        <div class="summary-box">
+            <p>The same subgraphs compiled for each of <code>compile_sizes</code>.  Trends are relative to the first row.  A size whose generated code is identical to another's gains nothing from its own compile, and is a candidate to drop from <code>compile_sizes</code>.</p>
+            <table class="config-table kernel-stats size-sweep">
+                <tr><th>Size</th><th>Subgraphs</th><th>Kernels</th><th>Generated Code</th><th>Compile Time</th><th>Note</th></tr>
+                {{ for entry in engine.size_sweep.sizes }}
+                <tr{{ if entry.same_code_as }} class="same-code"{{ endif }}><td>{entry.size_or_range}</td><td>{entry.num_submods}</td><td>{entry.kernel_count | format_count} <span class="sweep-trend">{entry.kernel_trend}</span></td><td>{entry.code_size} <span class="sweep-trend">{entry.code_trend}</span></td><td>{entry.compile_time}</td><td>{{ if entry.same_code_as }}same code as {entry.same_code_as}{{ endif }}</td></tr>
+                {{ endfor }}
+            </table>
+            <details>
+                <summary>By subgraph</summary>
+                {{ for submod in engine.size_sweep.submods }}
+                <h4>{submod.submod_name}</h4>
+                <table class="config-table kernel-stats size-sweep">
+                    <tr><th>Size</th><th>Kernels</th><th>Generated Code</th><th>Compile Time</th><th>Note</th></tr>
+                    {{ for entry in submod.entries }}
+                    <tr{{ if entry.same_code_as }} class="same-code"{{ endif }}><td>{entry.size_or_range}</td><td>{entry.kernel_count | format_count} <span class="sweep-trend">{entry.kernel_trend}</span></td><td>{entry.code_size} <span class="sweep-trend">{entry.code_trend}</span></td><td>{entry.compile_time}</td><td>{{ if entry.same_code_as }}same code as {entry.same_code_as}{{ endif }}</td></tr>
+                    {{ endfor }}
+                </table>
+                {{ endfor }}
+            </details>
+        </div>
+        {{ endif }}
+
+        <h2>Inductor Compilation</h2>
+
+        {{ for group in engine.compile_range_groups }}
+        <div class="compile-range-group">
+            <h3>{group.size_or_range}</h3>
+
+            {{ if group.total_kernels }}
+            <table class="config-table kernel-stats">
+                <tr><th>Subgraph</th><th>Triton Kernels</th><th>Generated Code</th></tr>
+                {{ for subgraph in group.submods }}
+                <tr{{ if subgraph.is_outlier }} class="kernel-outlier"{{ endif }}><td>{subgraph.submod_name}</td><td>{subgraph.kernel_count | format_count}</td><td>{subgraph.code_size}</td></tr>
+                {{ endfor }}
+            </table>
+            {{ endif }}
+
+            <details open class="submods-container">
+                <summary>Subgraphs ({group.submod_count})</summary>
+                {{ for subgraph in group.submods }}
+                <div class="subgraph">
+                    <h4>{subgraph.submod_name}</h4>
+                    {{ if subgraph.artifacts }}
+                    <div class="artifact-section">
+                        <details open>
+                            <summary>Artifacts ({subgraph.artifact_count} files)</summary>
+                            <ul class="artifact-list">
+                            {{ for artifact in subgraph.artifacts }}
+                                <li><a href="{artifact.url}">{artifact.name}</a> {artifact.suffix}{{ if artifact.size }} <span class="size-badge {artifact.size_heat}">{artifact.size}</span>{{ endif }}</li>
+                            {{ endfor }}
+                            </ul>
+                        </details>
+                    </div>
+                    {{ endif }}
+                </div>
+                {{ endfor }}
+            </details>
+        </div>
+        {{ endfor }}
+    </div>
+    {{ endfor }}
+{qps | format_unescaped}
+<!-- tlparse:previews -->
+</body>
+</html>
+"#;
+
+pub const VLLM_WORKERS_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>vLLM Workers</title>
+    <style>
+{css | format_unescaped}
+    </style>
+</head>
+<body>
+{custom_header_html | format_unescaped}
+    <h1>vLLM Workers</h1>
+
     <div class="summary-box">
-        <ul class="artifact-list">
-            <li><a href="{piecewise_graph_file}">vllm_piecewise_split_graph</a></li>
-        </ul>
+        <p>{summary}.  Each worker's log was parsed into its own report; the subgraphs and cudagraph captures of every worker are below (also available as <a href="vllm_summary.json">vllm_summary.json</a>).  Highlighted workers compiled other subgraphs than the first one.</p>
+        <table class="config-table kernel-stats vllm-workers">
+            <tr><th>Worker</th><th>Subgraphs</th><th>Triton Kernels</th><th>Generated Code</th><th>Compile Time</th><th>CUDAGraphs</th><th>Log</th></tr>
+            {{ for worker in workers }}
+            <tr{{ if worker.differs }} class="kernel-outlier"{{ endif }}><td><a href="{worker.report_url}">{worker.name}</a></td>{{ if worker.has_vllm }}<td><a href='#{worker.anchor}'>{worker.num_subgraphs | format_count}</a></td><td>{worker.num_kernels | format_count}</td><td>{worker.code_size}</td><td>{worker.compile_time}</td><td>{worker.captures_summary}</td>{{ else }}<td colspan="5">no vLLM artifacts</td>{{ endif }}<td><code>{worker.log}</code></td></tr>
+            {{ endfor }}
+        </table>
     </div>
-    {{ endif }}
 
-    <h2>Inductor Compilation</h2>
-
-    {{ for group in compile_range_groups }}
-    <div class="compile-range-group">
-        <h3>{group.size_or_range}</h3>
-
-        <details open class="submods-container">
-            <summary>Subgraphs ({group.submod_count})</summary>
-            {{ for subgraph in group.submods }}
-            <div class="subgraph">
-                <h4>{subgraph.submod_name}</h4>
-                {{ if subgraph.artifacts }}
-                <div class="artifact-section">
-                    <details open>
-                        <summary>Artifacts ({subgraph.artifact_count} files)</summary>
-                        <ul class="artifact-list">
-                        {{ for artifact in subgraph.artifacts }}
-                            <li><a href="{artifact.url}">{artifact.name}</a> {artifact.suffix}</li>
-                        {{ endfor }}
-                        </ul>
-                    </details>
-                </div>
-                {{ endif }}
-            </div>
+    {{ for worker in workers }}
+    {{ if worker.has_vllm }}
+    <div class="engine" id="{worker.anchor}">
+        <h2><a href="{worker.report_url}">{worker.name}</a></h2>
+        {{ if worker.subgraphs }}
+        <h3>Subgraphs</h3>
+        <table class="config-table kernel-stats">
+            <tr><th>Engine</th><th>Subgraph</th><th>Size or Range</th><th>Triton Kernels</th><th>Generated Code</th><th>Compile Time</th></tr>
+            {{ for subgraph in worker.subgraphs }}
+            <tr><td>{subgraph.engine}</td><td>{subgraph.submod_name}</td><td>{subgraph.size_or_range}</td><td>{subgraph.kernel_count | format_count}</td><td>{subgraph.code_size}</td><td>{subgraph.compile_time}</td></tr>
             {{ endfor }}
-        </details>
+        </table>
+        {{ endif }}
+        {{ if worker.cudagraph_captures }}
+        <h3>CUDAGraph Captures</h3>
+        <table class="config-table kernel-stats cudagraph-checklist">
+            <tr><th>Engine</th><th>Size</th><th>Captured</th><th>Status</th></tr>
+            {{ for capture in worker.cudagraph_captures }}
+            <tr{{ if capture.problem }} class="cudagraph-problem"{{ endif }}><td>{capture.engine}</td><td>{capture.size}</td><td>{{ if capture.captured }}✓{{ endif }}</td><td>{capture.status}</td></tr>
+            {{ endfor }}
+        </table>
+        {{ endif }}
     </div>
+    {{ endif }}
     {{ endfor }}
 {qps | format_unescaped}
 </body>
@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -9,6 +10,7 @@ pub struct VllmCompilationConfig {
     pub custom_ops: Option<String>,
     pub splitting_ops: Option<String>,
     pub cudagraph_mode: Option<String>,
+    pub cudagraph_capture_sizes: Option<String>,
     pub compile_sizes: Option<String>,
     pub compile_ranges_split_points: Option<String>,
     pub use_inductor_graph_partition: Option<bool>,
@@ -31,6 +33,24 @@ pub struct VllmSubgraphInfo {
     pub is_cudagraph_size: bool,
     #[serde(skip)]
     pub artifacts: Vec<ArtifactInfo>,
+    /// Triton kernels defined in the subgraph's inductor output code
+    #[serde(skip_deserializing)]
+    pub kernel_count: usize,
+    /// Size of the subgraph's inductor output code
+    #[serde(skip_deserializing)]
+    pub code_bytes: usize,
+    /// From the compile start to the subgraph's last artifact, by the log timestamps
+    #[serde(skip_deserializing)]
+    pub compile_time_s: Option<f64>,
+    /// Seconds since midnight of the compile start line
+    #[serde(skip)]
+    pub start_s: Option<f64>,
+    /// md5 of the subgraph's inductor output code, to spot sizes that generate the same code
+    #[serde(skip)]
+    pub code_hash: Option<String>,
+    /// `torch.ops` calls of the subgraph's inductor output code, e.g. `vllm.rms_norm` -> 2
+    #[serde(skip)]
+    pub ops: IndexMap<String, usize>,
 }
 
 impl VllmSubgraphInfo {
@@ -57,13 +77,33 @@ pub struct VllmSummaryContext {
     pub css: String,
     pub qps: String,
     pub custom_header_html: String,
+    /// `ParseConfig::metadata` as (key, value) rows
+    pub metadata: Vec<(String, String)>,
+    /// Explanation of each config field, keyed by field name (empty if undocumented)
+    pub config_docs: indexmap::IndexMap<String, String>,
+    /// The log has compiles of several engine instances, rendered as one section each
+    pub multiple_engines: bool,
+    pub num_engines: usize,
+    pub engines: Vec<VllmEngineSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VllmEngineSummary {
+    /// Config prefix, or `engine N` without one
+    pub name: String,
+    pub anchor: String,
     pub config: VllmCompilationConfig,
     pub has_config: bool,
     pub dynamo_artifacts: Vec<ArtifactInfo>,
     pub has_dynamo_artifacts: bool,
     pub piecewise_graph_file: Option<String>,
     pub has_piecewise: bool,
+    pub num_subgraphs: usize,
     pub compile_range_groups: Vec<VllmCompileRangeGroup>,
+    pub size_sweep: Option<VllmSizeSweep>,
+    pub cudagraph_checklist: Option<VllmCudagraphChecklist>,
+    pub graph_partitions: Option<VllmPartitionSummary>,
+    pub op_comparison: Option<VllmOpComparison>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -71,18 +111,269 @@ pub struct VllmSubgraphWithArtifacts {
     pub submod_name: String,
     pub artifacts: Vec<ArtifactInfo>,
     pub artifact_count: usize,
+    pub kernel_count: usize,
+    pub code_bytes: usize,
+    pub code_size: String,
+    /// Far more kernels than the other subgraphs of its group
+    pub is_outlier: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct VllmCompileRangeGroup {
     pub size_or_range: String,
     pub submod_count: usize,
+    pub total_kernels: usize,
     pub submods: Vec<VllmSubgraphWithArtifacts>,
 }
 
+/// `cudagraph_capture_sizes` of the config checked off against the cudagraphs captured and the
+/// sizes and ranges the subgraphs were compiled for
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmCudagraphChecklist {
+    /// The log has `vllm_cudagraph_capture` artifacts; without them captures can't be checked
+    pub captures_logged: bool,
+    pub summary: String,
+    pub sizes: Vec<VllmCudagraphSize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmCudagraphSize {
+    pub size: i64,
+    /// Listed in `cudagraph_capture_sizes`
+    pub expected: bool,
+    pub captured: bool,
+    /// The compile serving this size: its own single-size compile, else a range covering it
+    pub compiled_as: Option<String>,
+    /// `captured`, `expected` (captures not logged), `compiled only`, `missing` or `unexpected`
+    pub status: &'static str,
+    /// The size didn't get the cudagraph it was meant to
+    pub problem: bool,
+}
+
+/// One segment of a graph Inductor partitioned (`use_inductor_graph_partition`): a
+/// cudagraph-able partition, or the ops between partitions that run eagerly
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VllmPartitionSegment {
+    /// `partition_N` of the generated code; none for eager segments
+    #[serde(default)]
+    pub partition: Option<usize>,
+    #[serde(default = "default_cudagraphable")]
+    pub cudagraphable: bool,
+    #[serde(default)]
+    pub num_nodes: Option<usize>,
+    /// Triton kernels the partition launches
+    #[serde(default)]
+    pub num_kernels: usize,
+    /// Ops of an eager segment, e.g. `vllm.unified_attention_with_output`
+    #[serde(default)]
+    pub ops: Vec<String>,
+    /// `partition_N`, or the eager ops
+    #[serde(skip_deserializing)]
+    pub label: String,
+}
+
+fn default_cudagraphable() -> bool {
+    true
+}
+
+/// The partitions of one compiled graph, from its `vllm_graph_partition` artifact or, without
+/// one, its generated code
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmPartitionedGraph {
+    /// Size or range of the subgraph compile, `whole graph` outside of one
+    pub compiled_for: String,
+    pub submod_name: Option<String>,
+    /// `vllm_graph_partition` or `inductor_output_code`
+    pub source: &'static str,
+    pub num_partitions: usize,
+    pub num_cudagraphable: usize,
+    pub segments: Vec<VllmPartitionSegment>,
+    /// Index of the subgraph compile the graph belongs to
+    #[serde(skip)]
+    pub subgraph: Option<usize>,
+}
+
+/// The engine's graphs as Inductor partitioned them, in place of the piecewise split graph
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmPartitionSummary {
+    pub summary: String,
+    pub graphs: Vec<VllmPartitionedGraph>,
+}
+
+/// A custom op of the eager graph capture, or of the generated code only, and what compilation
+/// made of it
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmOpComparisonRow {
+    /// e.g. `vllm.rms_norm`
+    pub op: String,
+    pub eager_count: usize,
+    /// Calls left in the generated code of the subgraphs compiled for one size or range
+    pub compiled_count: usize,
+    /// `unfused`, `partly fused`, `fused into ...`, `decomposed`, `split out` or
+    /// `from a fusion`
+    pub status: String,
+    /// Left unfused, or decomposed without a fused op taking its place
+    pub problem: bool,
+}
+
+/// The custom ops of the eager graph vLLM captured before compiling, against those left in
+/// the generated code
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmOpComparison {
+    pub eager_graph_file: String,
+    pub summary: String,
+    pub rows: Vec<VllmOpComparisonRow>,
+}
+
+/// The same submods compiled for several entries of `compile_sizes`, compared size by size.
+/// Trends are relative to the general (range) compile of a submod, or its smallest size without
+/// one.
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmSizeSweep {
+    /// Totals over every submod, one row per size or range
+    pub sizes: Vec<VllmSweepEntry>,
+    pub submods: Vec<VllmSweepSubmod>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmSweepSubmod {
+    pub submod_name: String,
+    pub entries: Vec<VllmSweepEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmSweepEntry {
+    pub size_or_range: String,
+    pub is_single_size: bool,
+    pub num_submods: usize,
+    pub kernel_count: usize,
+    pub code_bytes: usize,
+    pub code_size: String,
+    pub compile_time_s: Option<f64>,
+    pub compile_time: String,
+    /// e.g. `+3` or `-1` kernels, empty for the baseline
+    pub kernel_trend: String,
+    /// e.g. `+40%` generated code, empty for the baseline
+    pub code_trend: String,
+    /// The size or range whose generated code is identical, making this size a candidate to
+    /// drop from `compile_sizes`.  For totals, set when that holds for every submod.
+    pub same_code_as: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ArtifactInfo {
     pub name: String,
     pub url: String,
     pub suffix: String,
+    pub bytes: u64,
+    pub size: String,
+    /// `manifest::size_heat` class of the size badge
+    pub size_heat: &'static str,
+}
+
+/// Machine-readable vLLM summary, written to vllm_summary.json for serving dashboards.
+/// The top-level config, subgraphs and artifact lists are those of the first engine; `engines`
+/// has every engine instance in the log.
+#[derive(Debug, Serialize)]
+pub struct VllmSummaryJson {
+    pub config: Option<VllmCompilationConfig>,
+    pub piecewise_graph_file: Option<String>,
+    pub subgraphs: Vec<VllmSubgraphInfo>,
+    pub compile_range_groups: Vec<VllmCompileRangeGroup>,
+    pub dynamo_artifacts: Vec<ArtifactInfo>,
+    /// Every artifact of the log, in log order
+    pub artifacts: Vec<ArtifactInfo>,
+    pub engines: Vec<VllmEngineJson>,
+    pub timing: Vec<VllmCompileTiming>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VllmEngineJson {
+    pub name: String,
+    pub prefix: Option<String>,
+    pub config: Option<VllmCompilationConfig>,
+    pub piecewise_graph_file: Option<String>,
+    pub subgraphs: Vec<VllmSubgraphInfo>,
+    pub compile_range_groups: Vec<VllmCompileRangeGroup>,
+    pub dynamo_artifacts: Vec<ArtifactInfo>,
+    pub size_sweep: Option<VllmSizeSweep>,
+    pub cudagraph_checklist: Option<VllmCudagraphChecklist>,
+    pub graph_partitions: Option<VllmPartitionSummary>,
+    pub op_comparison: Option<VllmOpComparison>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmCompileTiming {
+    pub compile_id: String,
+    pub entire_frame_compile_time_s: Option<f64>,
+    pub backend_compile_time_s: Option<f64>,
+    pub inductor_compile_time_s: Option<f64>,
+}
+
+/// The landing page of `--vllm-merge`: the subgraphs and cudagraph captures of every vLLM
+/// worker, each parsed into its own `worker_N/` report
+#[derive(Debug, Serialize)]
+pub struct VllmWorkersContext {
+    pub css: String,
+    pub qps: String,
+    pub custom_header_html: String,
+    pub num_workers: usize,
+    /// E.g. "4 workers, each compiled 12 subgraphs" or how their subgraphs differ
+    pub summary: String,
+    pub workers: Vec<VllmWorkerSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmWorkerSummary {
+    pub worker: u32,
+    /// `worker N`
+    pub name: String,
+    pub anchor: String,
+    pub log: String,
+    /// The worker's own report
+    pub report_url: String,
+    /// The worker's log has vLLM artifacts; others have only a plain tlparse report
+    pub has_vllm: bool,
+    pub num_subgraphs: usize,
+    pub num_kernels: usize,
+    pub code_bytes: usize,
+    pub code_size: String,
+    pub compile_time_s: f64,
+    pub compile_time: String,
+    /// E.g. "5 of 6 sizes captured", empty without cudagraph captures to check
+    pub captures_summary: String,
+    /// Its subgraphs (sizes, ranges and kernels) aren't those of the first vLLM worker
+    pub differs: bool,
+    pub subgraphs: Vec<VllmWorkerSubgraph>,
+    pub cudagraph_captures: Vec<VllmWorkerCapture>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmWorkerSubgraph {
+    pub engine: String,
+    pub submod_name: String,
+    pub size_or_range: String,
+    pub kernel_count: usize,
+    pub code_bytes: usize,
+    pub code_size: String,
+    pub compile_time_s: Option<f64>,
+    pub compile_time: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VllmWorkerCapture {
+    pub engine: String,
+    pub size: i64,
+    pub captured: bool,
+    /// As on the worker's cudagraph checklist: `captured`, `compiled only`, `missing`, ...
+    pub status: String,
+    pub problem: bool,
+}
+
+/// Machine-readable summary of `--vllm-merge`, written to vllm_summary.json next to the
+/// worker reports
+#[derive(Debug, Serialize)]
+pub struct VllmWorkersJson {
+    pub summary: String,
+    pub workers: Vec<VllmWorkerSummary>,
 }
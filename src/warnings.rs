@@ -0,0 +1,116 @@
+//! Pull warnings and perf hints out of artifact payloads so they can be shown on the
+//! compile id they belong to.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::CompileWarning;
+
+static PYTHON_WARNING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b([A-Z]\w*Warning):\s*(.+)").unwrap());
+static PERF_HINT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bperf[ _]hints?\b").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarningSeverity {
+    Error,
+    Warning,
+    Hint,
+}
+
+impl WarningSeverity {
+    /// Severity implied by the glog level of the line the artifact was logged at
+    fn from_level(level: &str) -> Option<Self> {
+        match level {
+            "E" | "C" => Some(Self::Error),
+            "W" => Some(Self::Warning),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Hint => "hint",
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            Self::Error => "⛔",
+            Self::Warning => "⚠️",
+            Self::Hint => "💡",
+        }
+    }
+}
+
+fn make_warning(severity: WarningSeverity, source: &str, message: &str) -> CompileWarning {
+    CompileWarning {
+        severity: severity.name(),
+        icon: severity.icon(),
+        source: source.to_string(),
+        message: message.trim().to_string(),
+    }
+}
+
+/// Artifacts that exist to carry warnings (e.g. `inductor_perf_hints`, `*_warnings`) contribute
+/// every line; an artifact logged at warning level or above contributes its whole payload as
+/// one entry.  Any other text artifact only contributes lines that look like a Python warning
+/// or a perf hint.
+pub fn extract_warnings(
+    artifact_name: &str,
+    encoding: &str,
+    level: &str,
+    payload: &str,
+) -> Vec<CompileWarning> {
+    let name = artifact_name.to_lowercase();
+    let dedicated = if name.contains("perf_hint") {
+        Some(WarningSeverity::Hint)
+    } else if name.contains("warning") {
+        Some(WarningSeverity::from_level(level).unwrap_or(WarningSeverity::Warning))
+    } else {
+        None
+    };
+    if dedicated.is_none() {
+        if let Some(severity) = WarningSeverity::from_level(level) {
+            if payload.trim().is_empty() {
+                return Vec::new();
+            }
+            return vec![make_warning(severity, artifact_name, payload)];
+        }
+    }
+
+    let messages: Vec<String> = if encoding == "json" {
+        match serde_json::from_str::<serde_json::Value>(payload) {
+            Ok(serde_json::Value::Array(items)) => items
+                .iter()
+                .map(|item| match item {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other
+                        .get("message")
+                        .and_then(|m| m.as_str())
+                        .map_or_else(|| other.to_string(), |m| m.to_string()),
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    } else {
+        payload.lines().map(|l| l.to_string()).collect()
+    };
+
+    messages
+        .iter()
+        .filter(|m| !m.trim().is_empty())
+        .filter_map(|m| {
+            if let Some(severity) = dedicated {
+                Some(make_warning(severity, artifact_name, m))
+            } else if let Some(caps) = PYTHON_WARNING.captures(m) {
+                Some(make_warning(WarningSeverity::Warning, &caps[1], &caps[2]))
+            } else if PERF_HINT.is_match(m) {
+                Some(make_warning(WarningSeverity::Hint, artifact_name, m))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
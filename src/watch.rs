@@ -0,0 +1,269 @@
+//! `--watch`: while serving a report, re-parse the log whenever it changes and keep a feed of
+//! what each regeneration added since the session started (`changes.html`, `changes.json`), so
+//! a long running job can be followed without re-reading the whole report.
+//!
+//! Each regeneration also looks at how often frames are still recompiling: recompiles that
+//! keep coming after warmup usually mean unstable guards throwing away compiled code every few
+//! steps, which silently destroys throughput.  Past the first `WARMUP_S` of the run, a
+//! regeneration that brings new recompiles with at least `RECOMPILE_ALERT_MIN` of them in the
+//! last `RECOMPILE_WINDOW_S` raises a warning, on the console and at the top of `changes.html`.
+//! Times are those of the log lines (`compile_activity.json`), so the window is the job's time,
+//! not how long watching has been going on.
+//!
+//! The index of a watched report carries `auto_refresh_script`, which polls `changes.json` and
+//! reloads the page once a regeneration has rewritten it, so an open tab follows the job like a
+//! dashboard.  Only the index at the root gets it, where `changes.json` is next to the page.
+
+use anyhow::Context;
+use fxhash::FxHashSet;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::activity::{CompileActivity, ACTIVITY_FILENAME};
+use crate::manifest::MANIFEST_FILENAME;
+use crate::report_model::REPORT_MODEL_FILENAME;
+use crate::templates::{CSS, TEMPLATE_QUERY_PARAM_SCRIPT};
+use crate::torch_logs::parse_compile_id;
+use crate::types::{ChangeEntry, ChangesContext};
+
+pub const CHANGES_FILENAME: &str = "changes.html";
+pub const CHANGES_JSON_FILENAME: &str = "changes.json";
+
+/// Files rewritten on every regeneration, which are never news by themselves
+const REGENERATED: [&str; 5] = [
+    CHANGES_FILENAME,
+    CHANGES_JSON_FILENAME,
+    MANIFEST_FILENAME,
+    REPORT_MODEL_FILENAME,
+    "payloads",
+];
+
+/// Compiles in the first ten minutes of a run are warmup
+const WARMUP_S: f64 = 600.0;
+/// Recompiles this close to the end of the log count as recent
+const RECOMPILE_WINDOW_S: f64 = 600.0;
+/// Recent recompiles past warmup worth a warning
+const RECOMPILE_ALERT_MIN: usize = 2;
+
+/// Seconds between the polls of `auto_refresh_script`, unless `--refresh-interval` says otherwise
+pub const DEFAULT_REFRESH_INTERVAL_S: u64 = 5;
+
+/// A script reloading the page it is on, a page at the root of a watched report, when a
+/// regeneration bumps the `regenerations` count of `changes.json`
+pub fn auto_refresh_script(interval_s: u64) -> String {
+    format!(
+        r#"<script>
+(function () {{
+  let seen = null;
+  setInterval(function () {{
+    fetch('{CHANGES_JSON_FILENAME}', {{ cache: 'no-store' }})
+      .then(function (response) {{ return response.json(); }})
+      .then(function (feed) {{
+        if (seen !== null && feed.regenerations !== seen) location.reload();
+        seen = feed.regenerations;
+      }})
+      .catch(function () {{}});
+  }}, {});
+}})();
+</script>"#,
+        interval_s * 1000
+    )
+}
+
+/// Add `auto_refresh_script` to the index of the report written to `out_dir`
+pub fn add_auto_refresh(out_dir: &Path, interval_s: u64) -> anyhow::Result<()> {
+    let path = out_dir.join("index.html");
+    let mut html =
+        fs::read_to_string(&path).with_context(|| format!("Reading {}", path.display()))?;
+    let script = auto_refresh_script(interval_s);
+    if html.contains(&script) {
+        return Ok(());
+    }
+    let at = html.rfind("</body>").unwrap_or(html.len());
+    html.insert_str(at, &script);
+    crate::paths::write_atomic(&path, html)
+}
+
+/// What a report directory contained at one point
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    /// Paths relative to the report directory, `/` separated
+    files: FxHashSet<String>,
+    /// (compile directory, warning) pairs from summary.json
+    warnings: FxHashSet<(String, String)>,
+    /// Log times of the run's first and last lines
+    run_span: Option<(f64, f64)>,
+    /// Log time of each recompile, a compile of a frame after its first one
+    recompiles: Vec<f64>,
+}
+
+fn list_files(root: &Path, dir: &Path, files: &mut FxHashSet<String>) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Listing {}", dir.display()))? {
+        let path = entry?.path();
+        let rel = path
+            .strip_prefix(root)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        if REGENERATED.contains(&rel.as_str()) {
+            continue;
+        }
+        if path.is_dir() {
+            list_files(root, &path, files)?;
+        } else {
+            files.insert(rel);
+        }
+    }
+    Ok(())
+}
+
+impl Snapshot {
+    pub fn load(out_dir: &Path) -> anyhow::Result<Self> {
+        let mut snapshot = Self::default();
+        list_files(out_dir, out_dir, &mut snapshot.files)?;
+        if let Ok(contents) = fs::read_to_string(out_dir.join(ACTIVITY_FILENAME)) {
+            let activity: CompileActivity = serde_json::from_str(&contents)?;
+            snapshot.run_span = activity.start.zip(activity.end);
+            // Restarts of a compile (`[0/1_1]`) aren't recompiles of their own
+            snapshot.recompiles = activity
+                .compiles
+                .iter()
+                .filter(|(compile_id, _)| {
+                    parse_compile_id(compile_id.trim_matches(['[', ']'])).is_some_and(|c| {
+                        c.frame_compile_id.unwrap_or(0) > 0 && c.attempt.unwrap_or(0) == 0
+                    })
+                })
+                .map(|(_, time)| *time)
+                .collect();
+        }
+        let summary: serde_json::Value = match fs::read_to_string(out_dir.join("summary.json")) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(_) => return Ok(snapshot),
+        };
+        for compile in summary["compiles"].as_array().into_iter().flatten() {
+            let metrics_url = compile["metrics_url"].as_str().unwrap_or_default();
+            let compile_dir = metrics_url.split('/').next().unwrap_or_default();
+            for warning in compile["warnings"].as_array().into_iter().flatten() {
+                snapshot.warnings.insert((
+                    compile_dir.to_string(),
+                    format!(
+                        "{} {}: {}",
+                        warning["icon"].as_str().unwrap_or_default(),
+                        warning["source"].as_str().unwrap_or_default(),
+                        warning["message"].as_str().unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Recompiles in the last `RECOMPILE_WINDOW_S` of the run that are past warmup, `None`
+    /// while the run is still warming up
+    fn recent_recompiles(&self) -> Option<usize> {
+        let (start, end) = self.run_span?;
+        if end - start < WARMUP_S {
+            return None;
+        }
+        let since = (end - RECOMPILE_WINDOW_S).max(start + WARMUP_S);
+        Some(self.recompiles.iter().filter(|t| **t > since).count())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeFeed {
+    pub log: PathBuf,
+    pub started_at: String,
+    pub regenerations: usize,
+    /// Oldest first
+    pub entries: Vec<ChangeEntry>,
+    /// The recompile rate warning of the last regeneration, if it raised one
+    pub recompile_warning: Option<String>,
+}
+
+impl ChangeFeed {
+    pub fn new(log: &Path) -> Self {
+        Self {
+            log: log.to_path_buf(),
+            started_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            regenerations: 0,
+            entries: Vec::new(),
+            recompile_warning: None,
+        }
+    }
+
+    /// Add entries for the files and warnings `after` has and `before` doesn't; returns how
+    /// many were added
+    pub fn record(&mut self, before: &Snapshot, after: &Snapshot) -> usize {
+        self.regenerations += 1;
+        let time = chrono::Local::now().format("%H:%M:%S").to_string();
+        let mut files: Vec<&String> = after.files.difference(&before.files).collect();
+        files.sort();
+        let mut added: Vec<ChangeEntry> = Vec::new();
+        for file in files {
+            added.push(ChangeEntry {
+                time: time.clone(),
+                kind: "artifact",
+                compile_dir: file.split_once('/').map(|(dir, _)| dir.to_string()),
+                url: Some(file.clone()),
+                message: file.rsplit('/').next().unwrap_or(file).to_string(),
+            });
+        }
+        let mut warnings: Vec<&(String, String)> =
+            after.warnings.difference(&before.warnings).collect();
+        warnings.sort();
+        for (compile_dir, warning) in warnings {
+            added.push(ChangeEntry {
+                time: time.clone(),
+                kind: "warning",
+                compile_dir: Some(compile_dir.clone()),
+                url: None,
+                message: warning.clone(),
+            });
+        }
+        self.recompile_warning = after
+            .recent_recompiles()
+            .filter(|n| *n >= RECOMPILE_ALERT_MIN && after.recompiles.len() > before.recompiles.len())
+            .map(|n| {
+                format!(
+                    "{n} recompiles in the last {} minutes of the run, after warmup; unstable guards may be recompiling frames over and over",
+                    RECOMPILE_WINDOW_S / 60.0
+                )
+            });
+        if let Some(warning) = &self.recompile_warning {
+            added.push(ChangeEntry {
+                time: time.clone(),
+                kind: "recompile_rate",
+                compile_dir: None,
+                url: None,
+                message: warning.clone(),
+            });
+        }
+        let count = added.len();
+        self.entries.extend(added);
+        count
+    }
+
+    /// Write `changes.html` and `changes.json` into `out_dir`
+    pub fn write(&self, out_dir: &Path) -> anyhow::Result<()> {
+        let tt = crate::report_model::templates(false)?;
+        let context = ChangesContext {
+            css: CSS,
+            log: self.log.display().to_string(),
+            started_at: self.started_at.clone(),
+            regenerations: self.regenerations,
+            entries: self.entries.iter().rev().cloned().collect(),
+            recompile_warning: self.recompile_warning.clone(),
+            qps: TEMPLATE_QUERY_PARAM_SCRIPT,
+        };
+        fs::write(
+            out_dir.join(CHANGES_FILENAME),
+            tt.render(CHANGES_FILENAME, &context)?,
+        )?;
+        fs::write(
+            out_dir.join(CHANGES_JSON_FILENAME),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! `tlparse workspace DIR`: serve every report in DIR, each under `/runs/<name>/`, with a
+//! landing page listing the runs and their summary metrics, as a small compile dashboard for a
+//! team.
+//!
+//! A run is a subdirectory (or a symlink to one) holding a tlparse report, single-log or
+//! `--all-ranks-html`.  The directory is rescanned on every request, so runs are added by
+//! parsing into it (`tlparse log -o DIR/name`) or linking a report there, and removed by
+//! deleting them, without restarting the server.  A run still being written shows up as
+//! generating until its lock is released.
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::lock::lock_path;
+use crate::manifest::load_reports;
+use crate::templates::CSS;
+use crate::types::{WorkspaceContext, WorkspaceRun};
+
+/// URL prefix of the runs; `/runs/<name>/` serves the report in `<name>` of the workspace
+pub const RUNS_PREFIX: &str = "runs";
+
+/// Where a request to the workspace server goes
+#[derive(Debug, PartialEq)]
+pub enum Route {
+    /// The list of runs
+    Landing,
+    /// `/runs/<name>` without the trailing slash the report's relative links need
+    Redirect(String),
+    /// `path` (relative, possibly empty) of the report of run `name`, in `dir`
+    Run {
+        name: String,
+        dir: PathBuf,
+        path: String,
+    },
+    NotFound,
+}
+
+fn is_run_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+        && !name.starts_with('.')
+}
+
+/// Route the decoded path of a request, without its leading `/`
+pub fn route(workspace: &Path, url_path: &str) -> Route {
+    if matches!(url_path, "" | "index.html") {
+        return Route::Landing;
+    }
+    let Some(rest) = url_path
+        .strip_prefix(RUNS_PREFIX)
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+    else {
+        return Route::NotFound;
+    };
+    let rest = rest.trim_start_matches('/');
+    if rest.is_empty() {
+        return Route::Landing;
+    }
+    let (name, path) = match rest.split_once('/') {
+        Some((name, path)) => (name, Some(path)),
+        None => (rest, None),
+    };
+    let dir = workspace.join(name);
+    if !is_run_name(name) || !dir.is_dir() {
+        return Route::NotFound;
+    }
+    match path {
+        None => Route::Redirect(format!("/{RUNS_PREFIX}/{name}/")),
+        Some(path) => Route::Run {
+            name: name.to_string(),
+            dir,
+            path: path.to_string(),
+        },
+    }
+}
+
+/// The summary of the report(s) in `dir`, `None` if it isn't a report
+fn summarize(name: String, dir: &Path) -> Option<WorkspaceRun> {
+    let in_progress = lock_path(dir).exists();
+    let reports = match load_reports(dir) {
+        Ok(reports) => reports,
+        Err(_) if in_progress => Vec::new(),
+        Err(_) => return None,
+    };
+    let mut run = WorkspaceRun {
+        url: format!("/{RUNS_PREFIX}/{name}/"),
+        name,
+        in_progress,
+        num_ranks: reports.len(),
+        ..Default::default()
+    };
+    let mut compile_time = 0.0;
+    for (report_dir, manifest) in &reports {
+        // The first rank stands for the run
+        if run.log.is_empty() {
+            run.log = manifest.log.display().to_string();
+            run.generated_at = manifest.generated_at.clone();
+            run.tlparse_version = manifest.tlparse_version.clone();
+            run.metadata = manifest
+                .metadata
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+        }
+        let Some(summary) = fs::read_to_string(report_dir.join("summary.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        else {
+            continue;
+        };
+        for compile in summary["compiles"].as_array().into_iter().flatten() {
+            run.num_compiles += 1;
+            if !compile["fail_type"].is_null() {
+                run.num_failed += 1;
+            }
+            compile_time += compile["entire_frame_compile_time_s"]
+                .as_f64()
+                .unwrap_or_default();
+            run.num_warnings += compile["warnings"].as_array().map_or(0, |w| w.len());
+        }
+    }
+    run.compile_time = crate::units::seconds(compile_time, 2);
+    Some(run)
+}
+
+/// The runs of the workspace, most recently generated first
+pub fn runs(workspace: &Path) -> anyhow::Result<Vec<WorkspaceRun>> {
+    let mut runs: Vec<WorkspaceRun> = fs::read_dir(workspace)?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            is_run_name(&name).then_some(())?;
+            summarize(name, &entry.path())
+        })
+        .collect();
+    runs.sort_by(|a, b| {
+        (b.in_progress, &b.generated_at, &a.name).cmp(&(a.in_progress, &a.generated_at, &b.name))
+    });
+    Ok(runs)
+}
+
+pub fn render_landing(workspace: &Path) -> anyhow::Result<String> {
+    let runs = runs(workspace)?;
+    let tt = crate::report_model::templates(false)?;
+    let context = WorkspaceContext {
+        css: CSS,
+        workspace: workspace.display().to_string(),
+        num_runs: runs.len(),
+        runs,
+    };
+    Ok(tt.render("workspace.html", &context)?)
+}
@@ -283,6 +283,255 @@ fn test_parse_compilation_metrics() {
     );
 }
 
+#[test]
+fn test_compilation_metrics_memory_usage() {
+    // memory_usage.log has three compiles reporting process_rss_bytes/process_pss_bytes
+    // of 1200/1100, 1450/1300 and 1800/1650 MiB
+    let path = Path::new("tests/inputs/memory_usage.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+
+    let memory_page = map
+        .get(&PathBuf::from("memory_usage.html"))
+        .expect("memory_usage.html not found in output");
+    assert!(memory_page.contains("<svg"));
+    assert!(memory_page.contains("1800.0 MiB"));
+    assert!(memory_page.contains("+350.0 MiB"));
+    // Total growth from first to last compile
+    assert!(memory_page.contains("+600.0 MiB"));
+    assert!(map[&PathBuf::from("index.html")].contains("memory_usage.html"));
+
+    let second_metrics = map
+        .iter()
+        .find(|(k, _)| {
+            k.to_str()
+                .unwrap()
+                .starts_with("-_1_0_0/compilation_metrics")
+        })
+        .map(|(_, v)| v)
+        .expect("compilation metrics for [1/0] not found");
+    assert!(second_metrics.contains("Process Memory"));
+    assert!(second_metrics.contains("+250.0 MiB since previous compile"));
+    assert!(second_metrics.contains("+200.0 MiB since previous compile"));
+
+    // Logs without memory counters don't get the page or the link
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("memory_usage.html")));
+    assert!(!map[&PathBuf::from("index.html")].contains("memory_usage.html"));
+}
+
+#[test]
+fn test_derived_metrics_config() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let config_path = temp_dir.path().join("tlparse.json");
+    fs::write(
+        &config_path,
+        r#"{"derived_metrics": {
+            "backend_fraction": "backend_compile_time_s / entire_frame_compile_time_s",
+            "rss_gib": "process_rss_bytes / (1024 * 1024 * 1024)",
+            "inductor_fraction": "inductor_compile_time_s / entire_frame_compile_time_s"
+        }}"#,
+    )?;
+    let out_dir = temp_dir.path().join("out");
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/memory_usage.log")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let index = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(index.contains("Derived Metrics"));
+    assert!(index.contains("<th> backend_fraction </th> <th> rss_gib </th>"));
+    assert!(index.contains("<td> 0.667 </td>"));
+    // inductor_compile_time_s isn't logged, so the metric can't be computed
+    assert!(index.contains("<td> N/A </td>"));
+    // and columns of the compile times table, sorting like the times
+    let compile_times = fs::read_to_string(out_dir.join("compile_times.html"))?;
+    assert!(compile_times.contains(
+        r#"<th onclick="sortCompileTimes(this, 7)"> backend_fraction </th><th onclick="sortCompileTimes(this, 8)"> rss_gib </th>"#
+    ));
+    assert!(compile_times.contains("<td> 0.667 </td><td> 1.758 </td><td> N/A </td>"));
+
+    let summary: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out_dir.join("summary.json"))?)?;
+    let compiles = summary["compiles"].as_array().unwrap();
+    assert_eq!(compiles.len(), 3);
+    assert_eq!(compiles[2]["compile_id"], "[2/0]");
+    let derived = &compiles[2]["derived_metrics"];
+    assert!((derived["rss_gib"].as_f64().unwrap() - 1800.0 / 1024.0).abs() < 1e-9);
+    assert!(derived["inductor_fraction"].is_null());
+
+    // Bad expressions are rejected before parsing the log
+    fs::write(
+        &config_path,
+        r#"{"derived_metrics": {"broken": "guard_count / (1 +"}}"#,
+    )?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/memory_usage.log")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("-o")
+        .arg(temp_dir.path().join("out2"))
+        .arg("--no-browser");
+    cmd.assert()
+        .failure()
+        .stderr(str::contains("Invalid derived metric `broken`"));
+
+    // and so are misspelled field names
+    fs::write(
+        &config_path,
+        r#"{"derived_metrics": {"typo": "guard_count / entire_frame_compile_time"}}"#,
+    )?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/memory_usage.log")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("-o")
+        .arg(temp_dir.path().join("out3"))
+        .arg("--no-browser");
+    cmd.assert()
+        .failure()
+        .stderr(str::contains("Invalid derived metric `typo`"))
+        .stderr(str::contains(
+            "`entire_frame_compile_time` isn't a compilation metrics field",
+        ));
+    assert!(!temp_dir.path().join("out3").exists());
+    Ok(())
+}
+
+#[test]
+fn test_compile_labels() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let config_path = temp_dir.path().join("tlparse.json");
+    fs::write(
+        &config_path,
+        r#"{"compile_labels": {"[01]/.*": "decoder layer", "2/0": "sampler"}}"#,
+    )?;
+    let out_dir = temp_dir.path().join("out");
+
+    // --compile-label rules are tried before those of the config file
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/comp_metrics.log")
+        .arg("--config")
+        .arg(&config_path)
+        .args(["--compile-label", r"1/0(_\d+)?=embedding"])
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .status()?;
+    assert!(status.success());
+
+    let index = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(index
+        .contains(r#"<a id="[0/0]">[0/0]</a> <span class="compile-label">decoder layer</span>"#));
+    assert!(
+        index.contains(r#"<a id="[1/0]">[1/0]</a> <span class="compile-label">embedding</span>"#)
+    );
+    assert!(index.contains(r#"<a id="[2/0]">[2/0]</a> <span class="compile-label">sampler</span>"#));
+    let metrics = fs::read_to_string(out_dir.join("-_2_0_0/compilation_metrics_8.html"))?;
+    assert!(metrics.contains("Compilation Info for [2/0] (sampler)"));
+
+    let summary: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out_dir.join("summary.json"))?)?;
+    let labels: Vec<&str> = summary["compiles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(labels, ["decoder layer", "embedding", "sampler"]);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/comp_metrics.log")
+        .args(["--compile-label", "(=broken"])
+        .arg("-o")
+        .arg(temp_dir.path().join("out2"))
+        .arg("--no-browser")
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Invalid compile id pattern `(`"));
+    Ok(())
+}
+
+#[test]
+fn test_compare_viewer() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+
+    let compare = map
+        .get(&PathBuf::from("compare.html"))
+        .expect("compare.html not found in output");
+    // Text artifacts are offered in the pickers, rendered html pages are not
+    assert!(compare.contains(r#""url":"-_0_0_0/dynamo_output_graph_0.txt""#));
+    assert!(compare.contains(r#""label":"[0/0] dynamo_output_graph_0.txt""#));
+    assert!(!compare.contains(r#"compilation_metrics_"#));
+
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(r#"href="compare.html?left=-_0_0_0/dynamo_output_graph_0.txt""#));
+    assert!(!index.contains("compare.html?left=-_0_0_0/compilation_metrics"));
+}
+
+#[test]
+fn test_compile_warnings() {
+    let path = Path::new("tests/inputs/compile_warnings.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    let metrics_page = |prefix: &str| {
+        map.iter()
+            .find(|(k, _)| k.to_str().unwrap().starts_with(prefix))
+            .map(|(_, v)| v.clone())
+            .unwrap()
+    };
+
+    let first = metrics_page("-_0_0_0/compilation_metrics");
+    assert!(first.contains("<h2>Warnings</h2>"));
+    // Every line of a perf hints artifact is a hint
+    assert!(first.contains("💡 <strong>inductor_perf_hints</strong>: <code>padding mm: [128, 250] x [250, 512] would be faster padded to 256</code>"));
+    // Python warnings buried in other artifacts are picked out by class
+    assert!(first.contains(
+        "⚠️ <strong>UserWarning</strong>: <code>The given NumPy array is not writable</code>"
+    ));
+    assert!(!first.contains("graph():"));
+    // Artifacts logged at warning level become a single warning
+    assert!(first.contains(
+        "⚠️ <strong>cudagraph_skip_reason</strong>: <code>skipping cudagraphs due to mutated inputs (1 instances)</code>"
+    ));
+
+    // Warnings stay with their own compile id
+    let second = metrics_page("-_1_0_0/compilation_metrics");
+    assert!(second.contains(
+        "⛔ <strong>dynamo_guard_warnings</strong>: <code>guard on tensor data pointer</code>"
+    ));
+    assert!(second.contains(
+        "⛔ <strong>dynamo_guard_warnings</strong>: <code>mutable default argument</code>"
+    ));
+    assert!(!second.contains("inductor_perf_hints"));
+}
+
 #[test]
 fn test_parse_compilation_failures() {
     let expected_files = [
@@ -363,389 +612,836 @@ fn test_parse_chromium_event() {
 }
 
 #[test]
-fn test_cache_hit_miss() {
-    let expected_files = [
-        "-_1_0_0/fx_graph_cache_miss_33.json",
-        "-_1_0_0/fx_graph_cache_miss_9.json",
-        "-_1_0_0/fx_graph_cache_hit_20.json",
-        "compile_directory.json",
-        "index.html",
-    ];
-    // Generated via TORCH_TRACE=~/trace_logs/test python test/inductor/test_codecache.py -k test_flex_attention_caching
-    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+fn test_compile_phase_gantt() {
+    let path = Path::new("tests/inputs/chromium_events.log").to_path_buf();
     let config = tlparse::ParseConfig {
         strict: true,
         ..Default::default()
     };
-    let output = tlparse::parse_path(&path, &config);
-    assert!(output.is_ok());
-    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
-    // Check all files are present
-    for prefix in expected_files {
-        assert!(
-            prefix_exists(&map, prefix),
-            "{} not found in output",
-            prefix
-        );
-    }
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let (_, metrics_page) = map
+        .iter()
+        .find(|(k, _)| {
+            k.to_str()
+                .unwrap()
+                .starts_with("-_0_0_0/compilation_metrics")
+        })
+        .unwrap();
+    assert!(metrics_page.contains("<h2>Compiler Phases</h2>"));
+    // The closing `dynamo` event is logged after compilation_metrics but still makes it in
+    assert_eq!(metrics_page.matches("<rect").count(), 3);
+    assert!(metrics_page.contains("<title>dynamo (dynamo tracing): 163.1 ms</title>"));
+    assert!(metrics_page.contains("<title>backend_compile (backend): 478 µs</title>"));
+    assert!(!metrics_page.contains("tlparse:phase_gantt"));
+
+    // Repeated compiles of the same compile id each get their own phases
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let rects: Vec<usize> = map
+        .iter()
+        .filter(|(k, _)| {
+            k.to_str()
+                .unwrap()
+                .starts_with("-_0_0_0/compilation_metrics")
+        })
+        .map(|(_, v)| v.matches("<rect").count())
+        .collect();
+    assert_eq!(rects, vec![3, 3, 3]);
 }
 
 #[test]
-fn test_export_report() {
-    let expected_files = [
-        "-_-_-_-/exported_program",
-        "index.html",
-        "-_-_-_-/symbolic_guard_information",
-    ];
-    // Read the test file
-    // chromium_events.log was generated from the following:
-    // TORCH_TRACE=~/trace_logs/test python test/export/test_draft_export.py -k test_complex_data_dependent
-    let path = Path::new("tests/inputs/export.log").to_path_buf();
+fn test_inductor_pass_breakdown() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
     let config = tlparse::ParseConfig {
         strict: true,
-        export: true,
         ..Default::default()
     };
-    let output = tlparse::parse_path(&path, &config);
-    assert!(output.is_ok());
-    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
-    println!("{:?}", map.keys());
-    // Check all files are present
-    for prefix in expected_files {
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let (_, metrics_page) = map
+        .iter()
+        .find(|(k, _)| {
+            k.to_str()
+                .unwrap()
+                .starts_with("-_0_0_0/compilation_metrics")
+        })
+        .unwrap();
+    assert!(metrics_page.contains("<h2>Inductor Passes</h2>"));
+    // Ranked by self time: `code_gen` spends most of its time waiting on kernel compilation
+    assert!(metrics_page.contains(
+        "The slowest pass, <code>async_compile.wait</code> (kernel compilation), took 49.9% of the 706.6 ms Inductor compile."
+    ));
+    for pass in [
+        "_recursive_pre_grad_passes",
+        "_recursive_joint_graph_passes",
+        "_recursive_post_grad_passes",
+        "Scheduler.__init__",
+    ] {
         assert!(
-            prefix_exists(&map, prefix),
-            "{} not found in output",
-            prefix
+            metrics_page.contains(&format!("<code>{pass}</code>")),
+            "{pass}"
         );
     }
-}
+    assert!(!metrics_page.contains("tlparse:inductor_passes"));
 
+    // Compiles without any Inductor spans get no section
+    let path = Path::new("tests/inputs/chromium_events.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(map
+        .iter()
+        .filter(|(k, _)| k.to_str().unwrap().contains("compilation_metrics"))
+        .all(|(_, v)| !v.contains("Inductor Passes") && !v.contains("tlparse:inductor_passes")));
+}
 #[test]
-fn test_export_guard_report() {
-    let expected_files = [
-        "-_-_-_-/exported_program",
-        "index.html",
-        "-_-_-_-/symbolic_guard_information",
-    ];
-    // Read the test file
-    // chromium_events.log was generated from the following:
-    // TORCH_TRACE=~/trace_logs/test python test/export/test_draft_export.py -k test_shape_failure
-    let path = Path::new("tests/inputs/export_guard_added.log").to_path_buf();
+fn test_attempt_rollups() {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
     let config = tlparse::ParseConfig {
         strict: true,
-        export: true,
         ..Default::default()
     };
-    let output = tlparse::parse_path(&path, &config);
-    assert!(output.is_ok());
-    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
-    println!("{:?}", map.keys());
-    // Check all files are present
-    for prefix in expected_files {
-        assert!(
-            prefix_exists(&map, prefix),
-            "{} not found in output",
-            prefix
-        );
-    }
-}
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    // The restarted attempt points at the one that produced the compiled code
+    assert!(index.contains(
+        "<li class=\"earlier-attempt\"><a id=\"[0/0]\">[0/0]</a> <span class=\"attempt-badge\">attempt 1 of 2, superseded by <a href='#[0/0_1]'>[0/0_1]</a></span>"
+    ));
+    assert!(index.contains(
+        "<a id=\"[0/0_1]\">[0/0_1]</a> <span class=\"attempt-badge\">latest of 2 attempts, 0.01 s</span>"
+    ));
+    // Compiles with a single attempt get no badge
+    assert!(index.contains("<li><a id=\"[2/0]\">[2/0]</a> <span class=\"size-badge"));
 
+    let metrics_page = &map[&PathBuf::from("-_0_0_1/compilation_metrics_2.html")];
+    assert!(metrics_page.contains(
+        "<p class='attempt-switcher'>Attempt 2 of 2: <a href='index.html#[0/0]'>[0/0]</a> · <strong>[0/0_1]</strong></p>"
+    ));
+    let single = &map[&PathBuf::from("-_2_0_0/compilation_metrics_8.html")];
+    assert!(!single.contains("attempt-switcher") && !single.contains("tlparse:attempts"));
+}
 #[test]
-fn test_provenance_tracking_aot_cuda() {
-    let expected_files = [
-        "-_-_-_-/before_pre_grad_graph_0.txt",
-        "-_-_-_-/after_post_grad_graph_6.txt",
-        "provenance_tracking_-_-_-_-.html",
-        "-_-_-_-/inductor_provenance_tracking_node_mappings_12.json",
-    ];
-    // Read the test file
-    let path = Path::new("tests/inputs/inductor_provenance_aot_cuda_log.txt").to_path_buf();
+fn test_config_docs_tooltips() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
     let config = tlparse::ParseConfig {
-        inductor_provenance: true,
+        strict: true,
         ..Default::default()
     };
-    let output = tlparse::parse_path(&path, &config);
-    assert!(output.is_ok());
-    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
-    println!("{:?}", map.keys());
-    // Check all files are present
-    for prefix in expected_files {
-        assert!(
-            prefix_exists(&map, prefix),
-            "{} not found in output",
-            prefix
-        );
-    }
-
-    // Read the HTML file and verify the line mappings
-    let html_path = map
-        .keys()
-        .find(|p| {
-            p.to_str()
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let (_, metrics_page) = map
+        .iter()
+        .find(|(k, _)| {
+            k.to_str()
                 .unwrap()
-                .contains("provenance_tracking_-_-_-_-.html")
+                .starts_with("-_0_0_0/compilation_metrics")
         })
         .unwrap();
-    let html_content = map.get(html_path).unwrap();
-
-    // Extract the line mappings JSON from the script tag
-    let script_start = html_content
-        .find(r#"<script id="lineMappings" type="application/json">"#)
-        .unwrap();
-    let json_start = html_content[script_start..].find(">").unwrap() + script_start + 1;
-    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
-    let line_mappings_str = &html_content[json_start..json_end];
-    let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
+    // `dynamo_config` is shown option by option, documented options with a tooltip
+    assert!(metrics_page.contains("<summary>67 options</summary>"));
+    assert!(metrics_page.contains(
+        "<td class=\"documented\" title=\"Fall back to eager when compiling a frame raises, instead of raising\"><code>suppress_errors</code></td><td>false</td>"
+    ));
+    assert!(
+        metrics_page.contains("<tr><td><code>accumulated_cache_size_limit</code></td><td>256</td>")
+    );
 
-    // Verify the line mappings match the expected values
-    let expected_mappings = serde_json::json!({
-        "cppCodeToPost": {
-            "1060": [14, 27, 24],
-            "1064": [21, 18, 15],
-            "1071": [24],
-            "1079": [14, 27, 24],
-            "1084": [35, 31, 28, 34, 33, 32]
-        },
-        "postToCppCode": {
-            "14": [1060, 1079],
-            "15": [1064],
-            "18": [1064],
-            "21": [1064],
-            "24": [1071, 1060, 1079],
-            "27": [1060, 1079],
-            "28": [1084],
-            "31": [1084],
-            "32": [1084],
-            "33": [1084],
-            "34": [1084],
-            "35": [1084]
-        },
-        "postToPre": {
-            "11": [8],
-            "14": [8],
-            "15": [8],
-            "18": [11],
-            "21": [14],
-            "24": [17],
-            "27": [20],
-            "28": [20],
-            "31": [23],
-            "32": [23],
-            "33": [23],
-            "34": [23],
-            "35": [23]
-        },
-        "postToPyCode": {},
-        "preToPost": {
-            "11": [18],
-            "14": [21],
-            "17": [24],
-            "20": [27, 28],
-            "23": [31, 32, 33, 34, 35],
-            "8": [11, 14, 15]
-        },
-        "pyCodeToPost": {}
-    });
+    // Explanations from a file replace the bundled ones
+    let temp_dir = tempdir()?;
+    let docs_path = temp_dir.path().join("docs.json");
+    fs::write(
+        &docs_path,
+        r#"{"dynamo": {"suppress_errors": "Never set in CI"}, "vllm": {"model": "The served model"}}"#,
+    )?;
+    let config = tlparse::ParseConfig {
+        strict: true,
+        config_docs: tlparse::config_docs::ConfigDocs::load(&docs_path)?,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let (_, metrics_page) = map
+        .iter()
+        .find(|(k, _)| {
+            k.to_str()
+                .unwrap()
+                .starts_with("-_0_0_0/compilation_metrics")
+        })
+        .unwrap();
+    assert!(metrics_page.contains("title=\"Never set in CI\"><code>suppress_errors</code>"));
+    assert!(metrics_page.contains("<code>specialize_int</code>"));
 
-    assert_eq!(line_mappings, expected_mappings);
-}
+    // vLLM config tables, where the bundled explanations still apply to the other fields
+    let path = Path::new("tests/inputs/vllm_sample.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("<td title=\"The served model\"><strong>Model</strong></td>"));
+    assert!(index.contains(
+        "<td title=\"Ops (usually attention) the graph is split at into pieces compiled separately\"><strong>Splitting Ops</strong></td>"
+    ));
 
+    fs::write(&docs_path, r#"{"dynamo": {"x": 1}}"#)?;
+    assert!(tlparse::config_docs::ConfigDocs::load(&docs_path).is_err());
+    Ok(())
+}
 #[test]
-fn test_provenance_tracking_aot_debug_handle() {
-    let expected_files = [
-        "-_-_-_-/before_pre_grad_graph_0.txt",
-        "-_-_-_-/after_post_grad_graph_6.txt",
-        "provenance_tracking_-_-_-_-.html",
-        "-_-_-_-/inductor_provenance_tracking_node_mappings_10.json",
-    ];
-
-    let path = Path::new("tests/inputs/inductor_provenance_aot_debug_handle_log.txt").to_path_buf();
+fn test_base_url_link_rewriting() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
     let config = tlparse::ParseConfig {
-        inductor_provenance: true,
+        strict: true,
+        base_url: Some("https://host/reports/run1/".to_string()),
         ..Default::default()
     };
-    let output = tlparse::parse_path(&path, &config);
-    assert!(output.is_ok());
-    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
 
-    // Check all files are present
-    for prefix in expected_files {
-        assert!(
-            prefix_exists(&map, prefix),
-            "{} not found in output",
-            prefix
-        );
-    }
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(r#"href="https://host/reports/run1/-_0_0_0/dynamo_output_graph_0.txt""#));
+    assert!(index.contains(
+        r#"href="https://host/reports/run1/compare.html?left=-_0_0_0/dynamo_output_graph_0.txt""#
+    ));
+    assert!(!index.contains(r#"href="-_0_0_0/"#));
 
-    // Read the HTML file and verify the line mappings
-    let html_path = map
-        .keys()
-        .find(|p| {
-            p.to_str()
+    // Compile pages resolve links against <base href="..">, which is dropped once links are absolute
+    let (_, metrics_page) = map
+        .iter()
+        .find(|(k, _)| {
+            k.to_str()
                 .unwrap()
-                .contains("provenance_tracking_-_-_-_-.html")
+                .starts_with("-_0_0_0/compilation_metrics")
         })
         .unwrap();
-    let html_content = map.get(html_path).unwrap();
-
-    // Extract the line mappings JSON from the script tag
-    let script_start = html_content
-        .find(r#"<script id="lineMappings" type="application/json">"#)
-        .unwrap();
-    let json_start = html_content[script_start..].find(">").unwrap() + script_start + 1;
-    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
-    let line_mappings_str = &html_content[json_start..json_end];
-    let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
-
-    // Verify the line mappings match the expected values for debug handle version
-    let expected_mappings = serde_json::json!(        {
-      "cppCodeToPost": {
-        "1074": [
-          12
-        ],
-        "1079": [
-          19,
-          16,
-          13
-        ],
-        "1087": [
-          22
-        ],
-        "1096": [
-          25
-        ],
-        "1102": [
-          33,
-          29,
-          26,
-          32,
-          31,
-          30
-        ]
-      },
-      "postToCppCode": {
-        "12": [
-          1074
-        ],
-        "13": [
-          1079
-        ],
-        "16": [
-          1079
-        ],
-        "19": [
-          1079
-        ],
-        "22": [
-          1087
-        ],
-        "25": [
-          1096
-        ],
-        "26": [
-          1102
-        ],
-        "29": [
-          1102
-        ],
-        "30": [
-          1102
-        ],
-        "31": [
-          1102
-        ],
-        "32": [
-          1102
-        ],
-        "33": [
-          1102
-        ]
-      },
-      "postToPre": {
-        "11": [
-          8
-        ],
-        "12": [
-          8
-        ],
-        "13": [
-          8
-        ],
-        "16": [
-          11
-        ],
-        "19": [
-          14
-        ],
-        "22": [
-          17
-        ],
-        "25": [
-          20
-        ],
-        "26": [
-          20
-        ],
-        "29": [
-          23
-        ],
-        "30": [
-          23
-        ],
-        "31": [
-          23
-        ],
-        "32": [
-          23
-        ],
-        "33": [
-          23
-        ]
-      },
-      "postToPyCode": {},
-      "preToPost": {
-        "11": [
-          16
-        ],
-        "14": [
-          19
-        ],
-        "17": [
-          22
-        ],
-        "20": [
-          25,
-          26
-        ],
-        "23": [
-          29,
-          30,
-          31,
-          32,
-          33
-        ],
-        "8": [
-          11,
-          12,
-          13
-        ]
-      },
-      "pyCodeToPost": {}
-    });
+    assert!(!metrics_page.contains("<base href"));
+    assert!(metrics_page
+        .contains(r#"href="https://host/reports/run1/-_0_0_0/dynamo_output_graph_0.txt""#));
 
-    assert_eq!(line_mappings, expected_mappings);
+    // Rank reports are prefixed with their own rank_N directory
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/multi_rank_schedule")
+        .arg("--all-ranks-html")
+        .arg("--base-url")
+        .arg("/reports/run2")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+    let landing = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(landing.contains(r#"href="/reports/run2/rank_1/index.html""#));
+    let rank_index = fs::read_to_string(out_dir.join("rank_1/index.html"))?;
+    assert!(rank_index.contains(r#"href="/reports/run2/rank_1/-_0_0_0/dynamo_output_graph_0.txt""#));
+    Ok(())
 }
 
 #[test]
-fn test_provenance_tracking_aot_log() {
-    let expected_files = [
+fn test_lazy_artifacts() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let eager: HashMap<PathBuf, String> =
+        tlparse::parse_path(&path, &tlparse::ParseConfig::default())?
+            .into_iter()
+            .collect();
+    let config = tlparse::ParseConfig {
+        lazy_artifact_min_bytes: Some(1024),
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config)?;
+    let lazy: HashMap<PathBuf, String> = output.iter().cloned().collect();
+
+    let index: tlparse::lazy::LazyArtifactIndex =
+        serde_json::from_str(&lazy[&PathBuf::from("artifact_index.json")])?;
+    assert!(!index.artifacts.is_empty());
+    let graph = "-_0_0_0/fx_graph_runnable_3.txt";
+    assert!(index.artifacts.contains_key(graph));
+    assert!(!lazy.contains_key(&PathBuf::from(graph)));
+    // Rendered pages are always written eagerly
+    assert!(lazy.contains_key(&PathBuf::from("index.html")));
+
+    // Every deferred artifact can be extracted from the log exactly as it was parsed
+    let temp_dir = tempdir()?;
+    for (name, contents) in &output {
+        let out_path = temp_dir.path().join(name);
+        fs::create_dir_all(out_path.parent().unwrap())?;
+        fs::write(out_path, contents)?;
+    }
+    for name in index.artifacts.keys() {
+        let written = tlparse::lazy::materialize_lazy_artifact(temp_dir.path(), Path::new(name))?
+            .expect("artifact should be materialized");
+        assert_eq!(fs::read_to_string(written)?, eager[&PathBuf::from(name)]);
+    }
+    assert!(
+        tlparse::lazy::materialize_lazy_artifact(temp_dir.path(), Path::new("missing.txt"))?
+            .is_none()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_low_memory() -> Result<(), Box<dyn std::error::Error>> {
+    // Large raw payloads are dropped from memory during the parse...
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        low_memory: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    let index: tlparse::lazy::LazyArtifactIndex =
+        serde_json::from_str(&map[&PathBuf::from("artifact_index.json")])?;
+    let guards = "-_1_0_0/dynamo_cpp_guards_str_11.txt";
+    assert!(index.artifacts.contains_key(guards));
+    assert!(!map.contains_key(&PathBuf::from(guards)));
+    // ...but still re-read for the pages rendered from them
+    assert!(map[&PathBuf::from("-_1_0_0/dynamo_cpp_guards_str_11.html")].contains("guards in"));
+
+    // ...and written out after the rest of the report, which comes out the same
+    let temp_dir = tempdir()?;
+    let run = |out: &Path, low_memory: bool| {
+        let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"));
+        cmd.arg(&path).arg("-o").arg(out).arg("--no-browser");
+        if low_memory {
+            cmd.arg("--low-memory");
+        }
+        assert!(cmd.output().unwrap().status.success());
+    };
+    let (normal, low) = (temp_dir.path().join("normal"), temp_dir.path().join("low"));
+    run(&normal, false);
+    run(&low, true);
+    assert!(!low.join("artifact_index.json").exists());
+    let mut pending = vec![PathBuf::new()];
+    while let Some(rel) = pending.pop() {
+        for entry in fs::read_dir(normal.join(&rel))? {
+            let rel = rel.join(entry?.file_name());
+            if normal.join(&rel).is_dir() {
+                pending.push(rel);
+            } else if rel != Path::new("manifest.json") {
+                assert!(
+                    fs::read(normal.join(&rel))? == fs::read(low.join(&rel))?,
+                    "{} differs",
+                    rel.display()
+                );
+            }
+        }
+    }
+    let manifest = tlparse::manifest::Manifest::load(&low)?;
+    assert!(manifest
+        .files
+        .iter()
+        .any(|f| f.path == guards && f.bytes == 26208));
+    assert!(!manifest
+        .files
+        .iter()
+        .any(|f| f.path == "artifact_index.json"));
+    Ok(())
+}
+#[test]
+fn test_stack_render_options() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/deep_stack.log").to_path_buf();
+    let render = |stack_render| -> Result<HashMap<PathBuf, String>, Box<dyn std::error::Error>> {
+        let config = tlparse::ParseConfig {
+            strict: true,
+            stack_render,
+            ..Default::default()
+        };
+        Ok(tlparse::parse_path(&path, &config)?.into_iter().collect())
+    };
+
+    let plain = render(tlparse::StackRenderOptions::default())?;
+    let index = &plain[&PathBuf::from("index.html")];
+    assert_eq!(
+        index.matches("site-packages/framework/loop.py:42").count(),
+        300
+    );
+
+    let map = render(tlparse::StackRenderOptions {
+        collapse_repeated: true,
+        strip_site_packages: true,
+        path_map: vec![("/workspace/".to_string(), "repo/".to_string())],
+    })?;
+    let index = &map[&PathBuf::from("index.html")];
+    assert_eq!(index.matches("framework/loop.py:42").count(), 1);
+    assert!(index.contains("(300&times;)"));
+    assert!(!index.contains("site-packages"));
+    assert!(index.contains("repo/train.py:20 in main"));
+    assert!(!index.contains("/workspace/train.py:20"));
+    // The compile page renders its stack the same way
+    let (_, metrics_page) = map
+        .iter()
+        .find(|(k, _)| k.to_str().unwrap().contains("compilation_metrics"))
+        .unwrap();
+    assert!(metrics_page.contains("(300&times;)"));
+    assert!(metrics_page.contains("repo/train.py:20 in main"));
+    Ok(())
+}
+
+#[test]
+fn test_op_coverage() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let eager_ops =
+        tlparse::op_coverage::load_eager_ops(Path::new("tests/inputs/eager_ops.txt")).unwrap();
+    let config = tlparse::ParseConfig {
+        eager_ops: Some(eager_ops),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let coverage = map.get(Path::new("op_coverage.html")).unwrap();
+    // mul, add and erf are compiled; linear and relu only ran eagerly
+    assert!(coverage.contains("<strong>80.0%</strong>"));
+    assert!(coverage.contains("(16 of 20 calls, 3 of 5 distinct operators)"));
+    let linear = coverage.find("<code>aten.linear</code>").unwrap();
+    let mul = coverage.find("<code>aten.mul</code>").unwrap();
+    assert!(linear < mul, "uncovered ops are listed first");
+    assert!(coverage.contains("<li><code>aten.gt</code></li>"));
+    let index = map.get(Path::new("index.html")).unwrap();
+    assert!(index.contains("href='op_coverage.html'"));
+}
+
+#[test]
+fn test_torch_logs_text_lines() {
+    let path = Path::new("tests/inputs/torch_logs_text.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let logs = map.get(Path::new("-_0_0_0/compiler_logs.html")).unwrap();
+    // Channels come from artifact loggers or the logging module
+    for channel in ["dynamo", "graph_code", "inductor", "guards"] {
+        assert!(logs.contains(&format!("<summary><code>{channel}</code>")));
+    }
+    // Unprefixed lines continue the text line before them
+    assert!(logs.contains("TRACED GRAPH\n ===== __compiled_fn_1 ====="));
+    // The compile id prefix wins over the compile the trace is in
+    let other = map.get(Path::new("-_1_0_0/compiler_logs.html")).unwrap();
+    assert!(other.contains("hit config.recompile_limit"));
+    assert!(!logs.contains("hit config.recompile_limit"));
+}
+
+#[test]
+fn test_watch_change_feed() -> Result<(), Box<dyn std::error::Error>> {
+    use tlparse::watch::{ChangeFeed, Snapshot};
+    let temp_dir = tempdir().unwrap();
+    let log = fs::read_to_string("tests/inputs/simple.log")?;
+    // The log as it looked before the compile finished
+    let cut = log.find("{\"compilation_metrics\"").unwrap();
+    let cut = log[..cut].rfind('\n').unwrap() + 1;
+    let write_report = |log: &str, name: &str| -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let log_path = temp_dir.path().join(format!("{name}.log"));
+        fs::write(&log_path, log)?;
+        let out_dir = temp_dir.path().join(name);
+        for (path, contents) in tlparse::parse_path(&log_path, &Default::default())? {
+            let path = out_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, contents)?;
+        }
+        Ok(out_dir)
+    };
+    let before = Snapshot::load(&write_report(&log[..cut], "before")?)?;
+    let out_dir = write_report(&log, "after")?;
+    let after = Snapshot::load(&out_dir)?;
+
+    let mut feed = ChangeFeed::new(Path::new("simple.log"));
+    assert!(feed.record(&before, &after) > 0);
+    assert_eq!(feed.record(&after, &after), 0);
+    feed.write(&out_dir)?;
+    let changes: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out_dir.join("changes.json"))?)?;
+    assert_eq!(changes["regenerations"], 2);
+    let urls: Vec<&str> = changes["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["url"].as_str())
+        .collect();
+    assert!(urls.iter().any(|u| u.contains("compilation_metrics")));
+    assert!(
+        !urls.contains(&"index.html"),
+        "rewritten pages are not news"
+    );
+    let html = fs::read_to_string(out_dir.join("changes.html"))?;
+    assert!(html.contains("compilation_metrics"));
+    Ok(())
+}
+
+#[test]
+fn test_watch_recompile_rate() -> Result<(), Box<dyn std::error::Error>> {
+    use tlparse::watch::{ChangeFeed, Snapshot};
+    let temp_dir = tempdir()?;
+    // compile_activity.json of a run `end` seconds long with compiles at the given times
+    let snapshot = |name: &str, end: f64, compiles: &[(&str, f64)]| {
+        let dir = temp_dir.path().join(name);
+        fs::create_dir_all(&dir).unwrap();
+        let activity = serde_json::json!({"start": 0.0, "end": end, "compiles": compiles});
+        fs::write(dir.join("compile_activity.json"), activity.to_string()).unwrap();
+        Snapshot::load(&dir).unwrap()
+    };
+    let warmup = snapshot(
+        "warmup",
+        300.0,
+        &[("[0/0]", 1.0), ("[0/1]", 100.0), ("[0/2]", 200.0)],
+    );
+    let steady = snapshot(
+        "steady",
+        1500.0,
+        &[("[0/0]", 1.0), ("[0/1]", 1100.0), ("[0/1_1]", 1101.0)],
+    );
+    let unstable = snapshot(
+        "unstable",
+        1600.0,
+        &[
+            ("[0/0]", 1.0),
+            ("[0/1]", 1100.0),
+            ("[0/1_1]", 1101.0),
+            ("[0/2]", 1550.0),
+        ],
+    );
+
+    let mut feed = ChangeFeed::new(Path::new("train.log"));
+    // Recompiling during warmup is expected, a single late recompile isn't a trend
+    feed.record(&Snapshot::default(), &warmup);
+    assert_eq!(feed.recompile_warning, None);
+    feed.record(&warmup, &steady);
+    assert_eq!(feed.recompile_warning, None);
+    feed.record(&steady, &unstable);
+    let warning = feed.recompile_warning.clone().unwrap();
+    assert!(warning.starts_with("2 recompiles in the last 10 minutes"));
+    assert_eq!(feed.entries.last().unwrap().kind, "recompile_rate");
+    // No new recompiles, no new warning
+    feed.record(&unstable, &unstable);
+    assert_eq!(feed.recompile_warning, None);
+
+    feed.record(&steady, &unstable);
+    let out_dir = temp_dir.path().join("unstable");
+    feed.write(&out_dir)?;
+    assert!(fs::read_to_string(out_dir.join("changes.html"))?.contains(&warning));
+    Ok(())
+}
+
+#[test]
+fn test_about_run() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/simple.log")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out_dir.join("manifest.json"))?)?;
+    let files = manifest["files"].as_array().unwrap();
+    assert!(files
+        .iter()
+        .any(|f| f["path"] == "index.html" && f["kind"] == "page"));
+    assert!(files
+        .iter()
+        .any(|f| f["path"] == "-_0_0_0/dynamo_output_graph_0.txt"
+            && f["kind"] == "dynamo_output_graph"));
+    assert!(manifest["parse_duration_s"].as_f64().is_some());
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("about-run").arg(&out_dir);
+    cmd.assert().success().stdout(
+        str::contains("simple.log")
+            .and(str::contains("Parse time:"))
+            .and(str::contains("pages)"))
+            .and(str::contains("dynamo_output_graph")),
+    );
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("about-run").arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(str::contains("No manifest.json found"));
+    Ok(())
+}
+
+#[test]
+fn test_template_context_docs() -> Result<(), Box<dyn std::error::Error>> {
+    use tlparse::template_docs::{context_structs_for, find_struct, TEMPLATE_CONTEXTS};
+    // Every template maps to a struct that build.rs found
+    for (template, context) in TEMPLATE_CONTEXTS {
+        let s = find_struct(context).unwrap_or_else(|| panic!("{template}: no {context}"));
+        assert!(!s.fields.is_empty(), "{context} has no fields");
+    }
+    // Nested structs are listed after the context that uses them
+    let names: Vec<&str> = context_structs_for("MemoryUsageContext")
+        .iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names, ["MemoryUsageContext", "MemoryUsageRow"]);
+    let memory_row = find_struct("MemoryUsageRow").unwrap();
+    assert!(memory_row
+        .fields
+        .iter()
+        .any(|f| f.name == "rss_delta_bytes" && f.ty == "i64"));
+    // Fields go by what serde writes: flattened maps as any other key, renamed fields by their
+    // new name, skipped ones not at all
+    let field_names = |name: &str| -> Vec<&str> {
+        find_struct(name)
+            .unwrap()
+            .fields
+            .iter()
+            .map(|f| f.name)
+            .collect()
+    };
+    let metrics = field_names("CompilationMetricsMetadata");
+    assert!(!metrics.contains(&"extra"));
+    assert_eq!(metrics.last(), Some(&"*"));
+    let subgraph = field_names("VllmSubgraphInfo");
+    assert!(subgraph.contains(&"piecewise_index"));
+    assert!(!subgraph.contains(&"index"));
+    assert!(!subgraph.contains(&"artifacts"));
+    assert!(subgraph.contains(&"kernel_count"));
+
+    let html = tlparse::template_docs::render_template_context_docs()?;
+    assert!(html.contains(r#"<h2 id="compilation_metrics.html">"#));
+    assert!(html.contains("<h3>CompilationMetricsMetadata</h3>"));
+    assert!(html.contains("<code>derived_metric_rows</code>"));
+    Ok(())
+}
+
+#[test]
+fn test_compile_cancellation() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/compile_cancellation.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("class='status-cancelled'>[0/0]"));
+    assert!(index.contains("class='status-cancelled'>[1/0]"));
+    assert!(!index.contains("class='status-cancelled'>[2/0]"));
+    assert!(index.contains("-_0_0_0/compile_cancellation.html"));
+
+    // The worker was killed after graph capture: the Dynamo graph survived, the rest was lost
+    let cancelled = &map[&PathBuf::from("-_0_0_0/compile_cancellation.html")];
+    assert!(cancelled.contains("compile worker pool shut down"));
+    assert!(cancelled.contains("-_0_0_0/dynamo_output_graph_0.txt"));
+    assert!(cancelled.contains("<li>Inductor output code (inductor_output_code)</li>"));
+    assert!(!cancelled.contains("<li>Dynamo graph capture"));
+
+    let timed_out = &map[&PathBuf::from("-_1_0_0/compile_cancellation.html")];
+    assert!(timed_out.contains("TimeoutError: Compile exceeded 600s"));
+    assert!(timed_out.contains("<li>Dynamo graph capture (dynamo_output_graph)</li>"));
+    assert!(!map.contains_key(&PathBuf::from("-_2_0_0/compile_cancellation.html")));
+    Ok(())
+}
+
+#[test]
+fn test_diff_only_changed() -> Result<(), Box<dyn std::error::Error>> {
+    let old_log = Path::new("tests/inputs/diff_old.log").to_path_buf();
+    let new_log = Path::new("tests/inputs/diff_new.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let run = |only_changed| -> Result<HashMap<PathBuf, String>, Box<dyn std::error::Error>> {
+        let old = tlparse::parse_path(&old_log, &config)?;
+        let new = tlparse::parse_path(&new_log, &config)?;
+        Ok(
+            tlparse::diff::render_diff(&old_log, &new_log, old, new, only_changed)?
+                .into_iter()
+                .collect(),
+        )
+    };
+
+    // [0/0] is identical, [1/0] changed its graph, [2/0] was removed and [3/0] added
+    let full = run(false)?;
+    let index = &full[&PathBuf::from("index.html")];
+    assert!(index.contains("-_0_0_0 (identical)"));
+    assert!(index.contains("-_1_0_0 (changed)"));
+    assert!(index.contains("-_2_0_0 (removed)"));
+    assert!(index.contains("-_3_0_0 (added)"));
+    assert!(full.contains_key(&PathBuf::from("old/index.html")));
+    assert!(full.contains_key(&PathBuf::from("new/-_0_0_0/dynamo_output_graph_0.txt")));
+
+    let changed = run(true)?;
+    let index = &changed[&PathBuf::from("index.html")];
+    assert!(!index.contains("-_0_0_0"));
+    assert!(index.contains("1 identical compile ids and 0 identical artifacts"));
+    assert!(index.contains(r#"href="new/-_1_0_0/dynamo_output_graph_2.txt""#));
+    let mut files: Vec<_> = changed.keys().map(|p| p.to_str().unwrap()).collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "diff.json",
+            "index.html",
+            "new/-_1_0_0/dynamo_output_graph_2.txt",
+            "new/-_3_0_0/dynamo_output_graph_4.txt",
+            "old/-_1_0_0/dynamo_output_graph_2.txt",
+            "old/-_2_0_0/dynamo_output_graph_4.txt",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_diff_compile_changes() -> Result<(), Box<dyn std::error::Error>> {
+    // [0/0] lost its graph break, [0/1] now has a dynamic size guard, [1/0] is gone and the
+    // new log recompiled forward once more, as [0/2]
+    let old_log = Path::new("tests/inputs/diff_compiles_old.log").to_path_buf();
+    let new_log = Path::new("tests/inputs/diff_compiles_new.log").to_path_buf();
+    let changes = tlparse::diff::compile_changes(&old_log, &new_log)?;
+    let statuses: Vec<(&str, &str)> = changes
+        .iter()
+        .map(|c| (c.compile_dir.as_str(), c.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("-_0_0_0", "changed"),
+            ("-_0_1_0", "changed"),
+            ("-_1_0_0", "removed"),
+            ("-_0_2_0", "added"),
+        ]
+    );
+    assert_eq!(
+        changes[0].graph_breaks.removed,
+        ["Unsupported: call_function print at model.py:25"]
+    );
+    assert_eq!(
+        changes[1].guards.added,
+        ["TENSOR_MATCH: check_tensor(L['x'], Tensor, torch.float32, size=[None], stride=[1])"]
+    );
+    assert!(changes[1].recompile_reasons.added.is_empty());
+    assert_eq!(
+        changes[3].recompile_reasons.added,
+        ["___check_obj_id(G['flag'], 1234)"]
+    );
+
+    let config = tlparse::ParseConfig::default();
+    let old = tlparse::parse_path(&old_log, &config)?;
+    let new = tlparse::parse_path(&new_log, &config)?;
+    let output: HashMap<PathBuf, String> =
+        tlparse::diff::render_diff(&old_log, &new_log, old, new, true)?
+            .into_iter()
+            .collect();
+    let index = &output[&PathBuf::from("index.html")];
+    assert!(index.contains(
+        "1 compile id is new and 1 gone; guards differ in 1 of the others, recompile reasons in 0 and graph breaks in 1."
+    ));
+    assert!(index.contains(
+        "<li class=\"guard-removed\">- Unsupported: call_function print at model.py:25</li>"
+    ));
+    let json: serde_json::Value = serde_json::from_str(&output[&PathBuf::from("diff.json")])?;
+    assert_eq!(json["changes"].as_array().unwrap().len(), 4);
+    Ok(())
+}
+
+#[test]
+fn test_cache_hit_miss() {
+    let expected_files = [
+        "-_1_0_0/fx_graph_cache_miss_33.json",
+        "-_1_0_0/fx_graph_cache_miss_9.json",
+        "-_1_0_0/fx_graph_cache_hit_20.json",
+        "compile_directory.json",
+        "index.html",
+    ];
+    // Generated via TORCH_TRACE=~/trace_logs/test python test/inductor/test_codecache.py -k test_flex_attention_caching
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    // Check all files are present
+    for prefix in expected_files {
+        assert!(
+            prefix_exists(&map, prefix),
+            "{} not found in output",
+            prefix
+        );
+    }
+}
+
+#[test]
+fn test_export_report() {
+    let expected_files = [
+        "-_-_-_-/exported_program",
+        "index.html",
+        "-_-_-_-/symbolic_guard_information",
+    ];
+    // Read the test file
+    // chromium_events.log was generated from the following:
+    // TORCH_TRACE=~/trace_logs/test python test/export/test_draft_export.py -k test_complex_data_dependent
+    let path = Path::new("tests/inputs/export.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        export: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    println!("{:?}", map.keys());
+    // Check all files are present
+    for prefix in expected_files {
+        assert!(
+            prefix_exists(&map, prefix),
+            "{} not found in output",
+            prefix
+        );
+    }
+}
+
+#[test]
+fn test_export_guard_report() {
+    let expected_files = [
+        "-_-_-_-/exported_program",
+        "index.html",
+        "-_-_-_-/symbolic_guard_information",
+    ];
+    // Read the test file
+    // chromium_events.log was generated from the following:
+    // TORCH_TRACE=~/trace_logs/test python test/export/test_draft_export.py -k test_shape_failure
+    let path = Path::new("tests/inputs/export_guard_added.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        export: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    println!("{:?}", map.keys());
+    // Check all files are present
+    for prefix in expected_files {
+        assert!(
+            prefix_exists(&map, prefix),
+            "{} not found in output",
+            prefix
+        );
+    }
+}
+
+#[test]
+fn test_provenance_tracking_aot_cuda() {
+    let expected_files = [
         "-_-_-_-/before_pre_grad_graph_0.txt",
         "-_-_-_-/after_post_grad_graph_6.txt",
         "provenance_tracking_-_-_-_-.html",
-        "-_-_-_-/inductor_provenance_tracking_node_mappings_11.json",
+        "-_-_-_-/inductor_provenance_tracking_node_mappings_12.json",
     ];
-
-    let path = Path::new("tests/inputs/inductor_provenance_aot_log.txt").to_path_buf();
+    // Read the test file
+    let path = Path::new("tests/inputs/inductor_provenance_aot_cuda_log.txt").to_path_buf();
     let config = tlparse::ParseConfig {
         inductor_provenance: true,
         ..Default::default()
@@ -753,7 +1449,7 @@ fn test_provenance_tracking_aot_log() {
     let output = tlparse::parse_path(&path, &config);
     assert!(output.is_ok());
     let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
-
+    println!("{:?}", map.keys());
     // Check all files are present
     for prefix in expected_files {
         assert!(
@@ -783,66 +1479,168 @@ fn test_provenance_tracking_aot_log() {
     let line_mappings_str = &html_content[json_start..json_end];
     let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
 
-    // For jit log, we expect similar structure to jit cuda but with different kernel names
-    let expected_mappings = serde_json::json!(  {
-      "cppCodeToPost": {
-        "813": [
-          12,
-          24,
-          21
+    // Verify the line mappings match the expected values
+    let expected_mappings = serde_json::json!({
+        "cppCodeToPost": {
+            "1060": [14, 27, 24],
+            "1064": [21, 18, 15],
+            "1071": [24],
+            "1079": [14, 27, 24],
+            "1084": [35, 31, 28, 34, 33, 32]
+        },
+        "postToCppCode": {
+            "14": [1060, 1079],
+            "15": [1064],
+            "18": [1064],
+            "21": [1064],
+            "24": [1071, 1060, 1079],
+            "27": [1060, 1079],
+            "28": [1084],
+            "31": [1084],
+            "32": [1084],
+            "33": [1084],
+            "34": [1084],
+            "35": [1084]
+        },
+        "postToPre": {
+            "11": [8],
+            "14": [8],
+            "15": [8],
+            "18": [11],
+            "21": [14],
+            "24": [17],
+            "27": [20],
+            "28": [20],
+            "31": [23],
+            "32": [23],
+            "33": [23],
+            "34": [23],
+            "35": [23]
+        },
+        "postToPyCode": {},
+        "preToPost": {
+            "11": [18],
+            "14": [21],
+            "17": [24],
+            "20": [27, 28],
+            "23": [31, 32, 33, 34, 35],
+            "8": [11, 14, 15]
+        },
+        "pyCodeToPost": {}
+    });
+
+    assert_eq!(line_mappings, expected_mappings);
+}
+
+#[test]
+fn test_provenance_tracking_aot_debug_handle() {
+    let expected_files = [
+        "-_-_-_-/before_pre_grad_graph_0.txt",
+        "-_-_-_-/after_post_grad_graph_6.txt",
+        "provenance_tracking_-_-_-_-.html",
+        "-_-_-_-/inductor_provenance_tracking_node_mappings_10.json",
+    ];
+
+    let path = Path::new("tests/inputs/inductor_provenance_aot_debug_handle_log.txt").to_path_buf();
+    let config = tlparse::ParseConfig {
+        inductor_provenance: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+
+    // Check all files are present
+    for prefix in expected_files {
+        assert!(
+            prefix_exists(&map, prefix),
+            "{} not found in output",
+            prefix
+        );
+    }
+
+    // Read the HTML file and verify the line mappings
+    let html_path = map
+        .keys()
+        .find(|p| {
+            p.to_str()
+                .unwrap()
+                .contains("provenance_tracking_-_-_-_-.html")
+        })
+        .unwrap();
+    let html_content = map.get(html_path).unwrap();
+
+    // Extract the line mappings JSON from the script tag
+    let script_start = html_content
+        .find(r#"<script id="lineMappings" type="application/json">"#)
+        .unwrap();
+    let json_start = html_content[script_start..].find(">").unwrap() + script_start + 1;
+    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
+    let line_mappings_str = &html_content[json_start..json_end];
+    let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
+
+    // Verify the line mappings match the expected values for debug handle version
+    let expected_mappings = serde_json::json!(        {
+      "cppCodeToPost": {
+        "1074": [
+          12
         ],
-        "821": [
-          18,
-          15,
-          21
+        "1079": [
+          19,
+          16,
+          13
         ],
-        "829": [
-          12,
-          24,
-          21
+        "1087": [
+          22
         ],
-        "834": [
-          31,
-          27,
-          30,
+        "1096": [
+          25
+        ],
+        "1102": [
+          33,
           29,
-          28
+          26,
+          32,
+          31,
+          30
         ]
       },
       "postToCppCode": {
         "12": [
-          813,
-          829
+          1074
         ],
-        "15": [
-          821
+        "13": [
+          1079
         ],
-        "18": [
-          821
+        "16": [
+          1079
         ],
-        "21": [
-          821,
-          813,
-          829
+        "19": [
+          1079
         ],
-        "24": [
-          813,
-          829
+        "22": [
+          1087
         ],
-        "27": [
-          834
+        "25": [
+          1096
         ],
-        "28": [
-          834
+        "26": [
+          1102
         ],
         "29": [
-          834
+          1102
         ],
         "30": [
-          834
+          1102
         ],
         "31": [
-          834
+          1102
+        ],
+        "32": [
+          1102
+        ],
+        "33": [
+          1102
         ]
       },
       "postToPre": {
@@ -852,23 +1650,23 @@ fn test_provenance_tracking_aot_log() {
         "12": [
           8
         ],
-        "15": [
+        "13": [
+          8
+        ],
+        "16": [
           11
         ],
-        "18": [
+        "19": [
           14
         ],
-        "21": [
+        "22": [
           17
         ],
-        "24": [
+        "25": [
           20
         ],
-        "27": [
-          23
-        ],
-        "28": [
-          23
+        "26": [
+          20
         ],
         "29": [
           23
@@ -878,32 +1676,40 @@ fn test_provenance_tracking_aot_log() {
         ],
         "31": [
           23
+        ],
+        "32": [
+          23
+        ],
+        "33": [
+          23
         ]
       },
       "postToPyCode": {},
       "preToPost": {
         "11": [
-          15
+          16
         ],
         "14": [
-          18
+          19
         ],
         "17": [
-          21
+          22
         ],
         "20": [
-          24
+          25,
+          26
         ],
         "23": [
-          27,
-          28,
           29,
           30,
-          31
+          31,
+          32,
+          33
         ],
         "8": [
           11,
-          12
+          12,
+          13
         ]
       },
       "pyCodeToPost": {}
@@ -913,15 +1719,15 @@ fn test_provenance_tracking_aot_log() {
 }
 
 #[test]
-fn test_provenance_tracking_aot_log_old() {
+fn test_provenance_tracking_aot_log() {
     let expected_files = [
-        "-_-_-_-/inductor_pre_grad_graph_0.txt",
-        "-_-_-_-/inductor_post_grad_graph_8.txt",
+        "-_-_-_-/before_pre_grad_graph_0.txt",
+        "-_-_-_-/after_post_grad_graph_6.txt",
         "provenance_tracking_-_-_-_-.html",
         "-_-_-_-/inductor_provenance_tracking_node_mappings_11.json",
     ];
 
-    let path = Path::new("tests/inputs/inductor_provenance_aot_log_old.txt").to_path_buf();
+    let path = Path::new("tests/inputs/inductor_provenance_aot_log.txt").to_path_buf();
     let config = tlparse::ParseConfig {
         inductor_provenance: true,
         ..Default::default()
@@ -959,13 +1765,25 @@ fn test_provenance_tracking_aot_log_old() {
     let line_mappings_str = &html_content[json_start..json_end];
     let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
 
-    // For old log, we expect the same structure as the regular aot log
-    let expected_mappings = serde_json::json!(       {
+    // For jit log, we expect similar structure to jit cuda but with different kernel names
+    let expected_mappings = serde_json::json!(  {
       "cppCodeToPost": {
-        "704": [
+        "813": [
+          12,
+          24,
           21
         ],
-        "717": [
+        "821": [
+          18,
+          15,
+          21
+        ],
+        "829": [
+          12,
+          24,
+          21
+        ],
+        "834": [
           31,
           27,
           30,
@@ -974,14 +1792,178 @@ fn test_provenance_tracking_aot_log_old() {
         ]
       },
       "postToCppCode": {
-        "21": [
-          704
+        "12": [
+          813,
+          829
         ],
-        "27": [
-          717
+        "15": [
+          821
         ],
-        "28": [
-          717
+        "18": [
+          821
+        ],
+        "21": [
+          821,
+          813,
+          829
+        ],
+        "24": [
+          813,
+          829
+        ],
+        "27": [
+          834
+        ],
+        "28": [
+          834
+        ],
+        "29": [
+          834
+        ],
+        "30": [
+          834
+        ],
+        "31": [
+          834
+        ]
+      },
+      "postToPre": {
+        "11": [
+          8
+        ],
+        "12": [
+          8
+        ],
+        "15": [
+          11
+        ],
+        "18": [
+          14
+        ],
+        "21": [
+          17
+        ],
+        "24": [
+          20
+        ],
+        "27": [
+          23
+        ],
+        "28": [
+          23
+        ],
+        "29": [
+          23
+        ],
+        "30": [
+          23
+        ],
+        "31": [
+          23
+        ]
+      },
+      "postToPyCode": {},
+      "preToPost": {
+        "11": [
+          15
+        ],
+        "14": [
+          18
+        ],
+        "17": [
+          21
+        ],
+        "20": [
+          24
+        ],
+        "23": [
+          27,
+          28,
+          29,
+          30,
+          31
+        ],
+        "8": [
+          11,
+          12
+        ]
+      },
+      "pyCodeToPost": {}
+    });
+
+    assert_eq!(line_mappings, expected_mappings);
+}
+
+#[test]
+fn test_provenance_tracking_aot_log_old() {
+    let expected_files = [
+        "-_-_-_-/inductor_pre_grad_graph_0.txt",
+        "-_-_-_-/inductor_post_grad_graph_8.txt",
+        "provenance_tracking_-_-_-_-.html",
+        "-_-_-_-/inductor_provenance_tracking_node_mappings_11.json",
+    ];
+
+    let path = Path::new("tests/inputs/inductor_provenance_aot_log_old.txt").to_path_buf();
+    let config = tlparse::ParseConfig {
+        inductor_provenance: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+
+    // Check all files are present
+    for prefix in expected_files {
+        assert!(
+            prefix_exists(&map, prefix),
+            "{} not found in output",
+            prefix
+        );
+    }
+
+    // Read the HTML file and verify the line mappings
+    let html_path = map
+        .keys()
+        .find(|p| {
+            p.to_str()
+                .unwrap()
+                .contains("provenance_tracking_-_-_-_-.html")
+        })
+        .unwrap();
+    let html_content = map.get(html_path).unwrap();
+
+    // Extract the line mappings JSON from the script tag
+    let script_start = html_content
+        .find(r#"<script id="lineMappings" type="application/json">"#)
+        .unwrap();
+    let json_start = html_content[script_start..].find(">").unwrap() + script_start + 1;
+    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
+    let line_mappings_str = &html_content[json_start..json_end];
+    let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
+
+    // For old log, we expect the same structure as the regular aot log
+    let expected_mappings = serde_json::json!(       {
+      "cppCodeToPost": {
+        "704": [
+          21
+        ],
+        "717": [
+          31,
+          27,
+          30,
+          29,
+          28
+        ]
+      },
+      "postToCppCode": {
+        "21": [
+          704
+        ],
+        "27": [
+          717
+        ],
+        "28": [
+          717
         ],
         "29": [
           717
@@ -1427,218 +2409,1971 @@ fn test_provenance_tracking_jit_log() {
       }
     });
 
-    assert_eq!(line_mappings, expected_mappings);
+    assert_eq!(line_mappings, expected_mappings);
+}
+
+#[test]
+fn test_provenance_tracking_jit_debug_handle() {
+    let expected_files = [
+        "-_0_0_0/before_pre_grad_graph_1.txt",
+        "-_0_0_0/after_post_grad_graph_11.txt",
+        "provenance_tracking_-_0_0_0.html",
+        "-_0_0_0/inductor_provenance_tracking_node_mappings_14.json",
+    ];
+
+    let path = Path::new("tests/inputs/inductor_provenance_jit_debug_handle_log.txt").to_path_buf();
+    let config = tlparse::ParseConfig {
+        inductor_provenance: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+
+    // Check all files are present
+    for prefix in expected_files {
+        assert!(
+            prefix_exists(&map, prefix),
+            "{} not found in output",
+            prefix
+        );
+    }
+
+    // Read the HTML file and verify the line mappings
+    let html_path = map
+        .keys()
+        .find(|p| {
+            p.to_str()
+                .unwrap()
+                .contains("provenance_tracking_-_0_0_0.html")
+        })
+        .unwrap();
+    let html_content = map.get(html_path).unwrap();
+
+    // Extract the line mappings JSON from the script tag
+    let script_start = html_content
+        .find(r#"<script id="lineMappings" type="application/json">"#)
+        .unwrap();
+    let json_start = html_content[script_start..].find(">").unwrap() + script_start + 1;
+    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
+    let line_mappings_str = &html_content[json_start..json_end];
+    let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
+
+    // For jit log, we expect similar structure to jit cuda but with different kernel names
+    let expected_mappings = serde_json::json!(         {
+      "cppCodeToPost": {},
+      "postToCppCode": {},
+      "postToPre": {
+        "12": [
+          17
+        ],
+        "15": [
+          20
+        ],
+        "18": [
+          23
+        ],
+        "19": [
+          23
+        ],
+        "22": [
+          26
+        ],
+        "23": [
+          26
+        ],
+        "24": [
+          26
+        ],
+        "25": [
+          26
+        ],
+        "26": [
+          26
+        ],
+        "4": [
+          11
+        ],
+        "5": [
+          11
+        ],
+        "6": [
+          11
+        ],
+        "9": [
+          14
+        ]
+      },
+      "postToPyCode": {
+        "12": [
+          204
+        ],
+        "15": [
+          211
+        ],
+        "18": [
+          216
+        ],
+        "19": [
+          223
+        ],
+        "22": [
+          223
+        ],
+        "23": [
+          223
+        ],
+        "24": [
+          223
+        ],
+        "25": [
+          223
+        ],
+        "26": [
+          223
+        ],
+        "29": [
+          204
+        ],
+        "5": [
+          197
+        ],
+        "6": [
+          204
+        ],
+        "9": [
+          204
+        ]
+      },
+      "preToPost": {
+        "11": [
+          4,
+          5,
+          6
+        ],
+        "14": [
+          9
+        ],
+        "17": [
+          12
+        ],
+        "20": [
+          15
+        ],
+        "23": [
+          18,
+          19
+        ],
+        "26": [
+          22,
+          23,
+          24,
+          25,
+          26
+        ]
+      },
+      "pyCodeToPost": {
+        "197": [
+          5
+        ],
+        "204": [
+          12,
+          9,
+          6,
+          29
+        ],
+        "211": [
+          15
+        ],
+        "216": [
+          18
+        ],
+        "223": [
+          26,
+          22,
+          19,
+          25,
+          24,
+          23
+        ]
+      }
+    });
+
+    assert_eq!(line_mappings, expected_mappings);
+}
+
+#[test]
+fn test_provenance_tracking_multiple_colons_in_kernel_name() {
+    let expected_files = [
+        "-_-_-_-/before_pre_grad_graph_0.txt",
+        "-_-_-_-/after_post_grad_graph_8.txt",
+        "provenance_tracking_-_-_-_-.html",
+        "-_-_-_-/inductor_provenance_tracking_node_mappings_12.json",
+    ];
+
+    let path = Path::new("tests/inputs/inductor_provenance_long_log.txt").to_path_buf();
+    let config = tlparse::ParseConfig {
+        inductor_provenance: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config);
+    assert!(output.is_ok());
+    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+
+    // Check all files are present
+    for prefix in expected_files {
+        assert!(
+            prefix_exists(&map, prefix),
+            "{} not found in output",
+            prefix
+        );
+    }
+
+    // Read the HTML file and verify the line mappings
+    let html_path = map
+        .keys()
+        .find(|p| {
+            p.to_str()
+                .unwrap()
+                .contains("provenance_tracking_-_-_-_-.html")
+        })
+        .unwrap();
+    let html_content = map.get(html_path).unwrap();
+
+    // Extract the line mappings JSON from the script tag
+    let script_start = html_content
+        .find(r#"<script id="lineMappings" type="application/json">"#)
+        .unwrap();
+    let json_start = html_content[script_start..].find(">").unwrap() + script_start + 1;
+    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
+    let line_mappings_str = &html_content[json_start..json_end];
+    let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
+
+    // For jit log, we expect similar structure to jit cuda but with different kernel names
+    let expected_post_nodes = vec![
+        10197, 10194, 10191, 10200, 10225, 10223, 10221, 10220, 10219, 10218, 10215, 10214, 10213,
+        10212, 10211, 10205, 10204, 10203, 10199, 10198, 10202, 10201, 10217, 10216, 10210, 10209,
+        10208, 10207, 10206, 10224, 10222, 10227, 10226,
+    ];
+
+    assert_eq!(
+        line_mappings["cppCodeToPost"]["15892"],
+        serde_json::json!(expected_post_nodes)
+    );
+}
+
+#[test]
+fn test_provenance_stack_trace_readable() {
+    let path = Path::new("tests/inputs/inductor_provenance_extended_log.txt").to_path_buf();
+    let config = tlparse::ParseConfig {
+        inductor_provenance: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+
+    assert!(map.keys().any(|k| {
+        let s = k.to_str().unwrap_or("");
+        s.contains("inductor_provenance_tracking_kernel_stack_traces") && s.ends_with(".json")
+    }));
+    assert!(map.keys().any(|k| {
+        let s = k.to_str().unwrap_or("");
+        s.contains("inductor_provenance_tracking_kernel_stack_traces")
+            && s.ends_with("_readable.html")
+    }));
+
+    let html = map
+        .iter()
+        .find(|(k, _)| {
+            let s = k.to_str().unwrap_or("");
+            s.contains("inductor_provenance_tracking_kernel_stack_traces")
+                && s.ends_with("_readable.html")
+        })
+        .unwrap()
+        .1;
+    assert!(html.contains("line 1\n  foo()"));
+    assert!(!html.contains("\\n"));
+
+    let index_html = map.get(Path::new("index.html")).unwrap();
+    assert!(index_html.contains("_readable.html\">readable_html</a>"));
+}
+
+#[test]
+fn test_all_ranks_basic() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let rank0_index = out_dir.join("rank_0/index.html");
+    let rank1_index = out_dir.join("rank_1/index.html");
+    let landing_page = out_dir.join("index.html");
+
+    assert!(rank0_index.exists());
+    assert!(rank1_index.exists());
+    assert!(landing_page.exists());
+
+    let landing_content = fs::read_to_string(landing_page).unwrap();
+    assert!(landing_content.contains(r#"<a href="rank_0/index.html">"#));
+    assert!(landing_content.contains(r#"<a href="rank_1/index.html">"#));
+    assert!(
+        !landing_content.contains("collectives_parity.json"),
+        "multi-rank landing page should not link collectives parity"
+    );
+
+    let rank0_content = fs::read_to_string(rank0_index).unwrap();
+    assert!(rank0_content.contains("collectives_parity.json"));
+    Ok(())
+}
+
+#[test]
+fn test_rank_prefixed_log() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir().unwrap();
+    // Interleave the entries of two ranks the way a launcher gathering their output would
+    let blocks = |rank: u32, log: &str| -> Vec<String> {
+        let log = fs::read_to_string(Path::new("tests/inputs/rank_artifacts").join(log)).unwrap();
+        let mut blocks: Vec<String> = Vec::new();
+        for line in log.lines() {
+            let line = format!("[rank{rank}]:{line}\n");
+            match blocks.last_mut() {
+                Some(block) if line.starts_with(&format!("[rank{rank}]:\t")) => {
+                    block.push_str(&line)
+                }
+                _ => blocks.push(line),
+            }
+        }
+        blocks
+    };
+    let rank0 = blocks(0, "dedicated_log_torch_trace_rank_0.log");
+    let rank1 = blocks(1, "dedicated_log_torch_trace_rank_1.log");
+    let mut combined = String::new();
+    for i in 0..rank0.len().max(rank1.len()) {
+        combined.extend(rank0.get(i).cloned());
+        combined.extend(rank1.get(i).cloned());
+    }
+    let log = temp_dir.path().join("combined.log");
+    fs::write(&log, combined)?;
+
+    let out_dir = temp_dir.path().join("out");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(&log)
+        .arg("--all-ranks-html")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    assert!(out_dir
+        .join("split_logs/dedicated_log_torch_trace_rank_1.log")
+        .exists());
+    let landing = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(landing.contains(r#"<a href="rank_0/index.html">"#));
+    assert!(landing.contains(r#"<a href="rank_1/index.html">"#));
+
+    // Each rank's report is the one of its own log (but for the string table, which is shared
+    // by the ranks parsed in one run)
+    let direct = temp_dir.path().join("direct");
+    std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/rank_artifacts/dedicated_log_torch_trace_rank_1.log")
+        .arg("-o")
+        .arg(&direct)
+        .arg("--no-browser")
+        .output()?;
+    let entries = |path: PathBuf| -> Vec<String> {
+        let raw = fs::read_to_string(path).unwrap();
+        raw.lines().skip(1).map(str::to_string).collect()
+    };
+    assert_eq!(
+        entries(out_dir.join("rank_1/raw.jsonl")),
+        entries(direct.join("raw.jsonl"))
+    );
+
+    // Parsed as a single log, the first rank is reported, pointing at --all-ranks-html
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(&log)
+        .arg("-o")
+        .arg(temp_dir.path().join("single"))
+        .arg("--no-browser")
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Use --all-ranks-html"), "{stderr}");
+    let index = fs::read_to_string(temp_dir.path().join("single/index.html"))?;
+    assert!(index.contains("0_0_0"));
+    Ok(())
+}
+
+#[test]
+fn test_all_ranks_log_integrity() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir().unwrap();
+    let input_dir = temp_dir.path().join("logs");
+    let out_dir = temp_dir.path().join("out");
+    fs::create_dir(&input_dir)?;
+    let log = fs::read_to_string("tests/inputs/simple.log")?;
+    // rank 0 reports the world size and ends with a TORCH_LOGS line, rank 1 is missing, rank 2
+    // is cut off mid-payload and rank 3 is empty; rank 4 is implied by the world size
+    fs::write(
+        input_dir.join("dedicated_log_torch_trace_rank_0.log"),
+        format!(
+            "{log}\nV1010 00:00:00.000000 1 distributed.py:1] {{\"artifact\": {{\"name\": \"pg_info\", \"encoding\": \"string\"}}, \"has_payload\": \"182b9985dca3a0f61b46ec3e94e2ac94\"}}\n\tworld_size=5\nV1010 00:00:00.000000 1 torch/_dynamo/convert_frame.py:1] [0/0] not a JSON envelope\n"
+        ),
+    )?;
+    fs::write(
+        input_dir.join("dedicated_log_torch_trace_rank_2.log"),
+        &log[..log.len() - 10],
+    )?;
+    fs::write(input_dir.join("dedicated_log_torch_trace_rank_3.log"), "")?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    assert!(out_dir.join("rank_0/index.html").exists());
+    assert!(out_dir.join("rank_2/index.html").exists());
+
+    let landing = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(!landing.contains("Rank 0:"));
+    assert!(landing.contains("Rank 1: no log found (world size is 5)"));
+    assert!(landing.contains("Rank 2: payload of the last entry is incomplete (truncated?)"));
+    assert!(landing.contains("Rank 3: log is empty"));
+    assert!(landing.contains("Rank 4: no log found (world size is 5)"));
+    Ok(())
+}
+
+#[test]
+fn test_rank_log_check_line_endings() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    // simple.log stops after its last payload line, without a newline
+    let log = fs::read_to_string("tests/inputs/simple.log")?;
+    let crlf = log.replace('\n', "\r\n");
+    let logs = [
+        log.clone(),
+        crlf.clone(),
+        // A payload that happens to mention a world size isn't the process group's
+        format!(
+            "{log}\nV1010 00:00:00.000000 1 notes.py:1] {{\"artifact\": {{\"name\": \"notes\", \"encoding\": \"string\"}}, \"has_payload\": \"4f3f2ccdf6134ffc92f0ed300152e1bb\"}}\n\tworld_size=9"
+        ),
+        format!("{log}\nV1010 00:00:00.000000 1 torch/_dynamo/convert_frame.py:1] {{\"str\": []}}"),
+        crlf[..crlf.len() - 10].to_string(),
+    ];
+    let mut rank_logs = Vec::new();
+    for (rank, contents) in logs.iter().enumerate() {
+        let path = temp_dir
+            .path()
+            .join(format!("dedicated_log_torch_trace_rank_{rank}.log"));
+        fs::write(&path, contents)?;
+        rank_logs.push((path, rank as u32));
+    }
+    let issues = tlparse::check_rank_logs(&rank_logs)?;
+    let issues: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.rank, issue.problem.as_str()))
+        .collect();
+    assert_eq!(
+        issues,
+        [(4, "payload of the last entry is incomplete (truncated?)")]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_all_ranks_messy_input() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_messy_input");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+
+    cmd.assert().success();
+
+    // Check for landing page and rank-specific index files
+    let landing_page = out_dir.join("index.html");
+    let rank0_index = out_dir.join("rank_0/index.html");
+    let rank1_index = out_dir.join("rank_1/index.html");
+
+    assert!(
+        rank0_index.exists(),
+        "rank 0 index.html should exist in messy input test"
+    );
+    assert!(
+        rank1_index.exists(),
+        "rank 1 index.html should exist in messy input test"
+    );
+    assert!(
+        landing_page.exists(),
+        "toplevel index.html should exist in messy input test"
+    );
+
+    let landing_content = fs::read_to_string(landing_page).unwrap();
+    assert!(landing_content.contains(r#"<a href="rank_0/index.html">"#));
+    assert!(landing_content.contains(r#"<a href="rank_1/index.html">"#));
+    Ok(())
+}
+
+#[test]
+fn test_all_ranks_no_browser() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+
+    cmd.assert().success().stdout(
+        str::contains("Multi-rank report generated").and(str::contains(out_dir.to_str().unwrap())),
+    );
+
+    // Check that files were created but don't try to open them
+    let rank0_index = out_dir.join("rank_0/index.html");
+    let rank1_index = out_dir.join("rank_1/index.html");
+    let landing_page = out_dir.join("index.html");
+
+    assert!(rank0_index.exists());
+    assert!(rank1_index.exists());
+    assert!(landing_page.exists());
+    Ok(())
+}
+
+#[test]
+fn test_all_ranks_with_latest_fails() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--latest")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+
+    cmd.assert().failure().stderr(str::contains(
+        "--latest cannot be used with --all-ranks-html",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_all_ranks_no_logs() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let empty_dir = temp_dir.path();
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(empty_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("--no-browser");
+
+    cmd.assert()
+        .failure()
+        .stderr(str::contains("No rank log files found"));
+
+    Ok(())
+}
+
+#[test]
+fn test_all_ranks_chromium_events_combined() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_out_dir = tempdir()?;
+    let out_dir = temp_out_dir.path();
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    // check that chromium_events.json is created and contains events from all ranks
+    let combined_events_path = out_dir.join("chromium_events.json");
+    assert!(combined_events_path.exists());
+
+    let events_content = fs::read_to_string(combined_events_path)?;
+    let events: Vec<serde_json::Value> = serde_json::from_str(&events_content)?;
+    assert!(!events.is_empty());
+
+    // collect all unique process IDs (ranks) from the events
+    let pids: std::collections::HashSet<u64> = events
+        .iter()
+        .filter_map(|event| event.get("pid").and_then(|v| v.as_u64()))
+        .collect();
+
+    let expected_pids: std::collections::HashSet<u64> = [0, 1, 2, 3].iter().cloned().collect();
+    assert_eq!(pids, expected_pids);
+
+    // verify each rank-specific chromium_events.json file
+    for rank in 0u64..=3 {
+        let rank_events_path = out_dir.join(format!("rank_{}/chromium_events.json", rank));
+        assert!(rank_events_path.exists());
+        let rank_events_content = fs::read_to_string(&rank_events_path)?;
+        let rank_events: Vec<serde_json::Value> = serde_json::from_str(&rank_events_content)?;
+
+        if expected_pids.contains(&(rank as u64)) {
+            assert!(!rank_events.is_empty());
+            let combined_for_rank: Vec<&serde_json::Value> = events
+                .iter()
+                .filter(|ev| ev.get("pid").and_then(|v| v.as_u64()) == Some(rank as u64))
+                .collect();
+            assert_eq!(rank_events.len(), combined_for_rank.len());
+        } else {
+            assert!(rank_events.is_empty());
+        }
+    }
+
+    let landing_page_path = out_dir.join("index.html");
+    assert!(landing_page_path.exists());
+    let landing_content = fs::read_to_string(landing_page_path)?;
+    // The logs are complete, rank 1's with CRLF payload lines and rank 2's without a final newline
+    assert!(!landing_content.contains("(truncated?)"));
+    for i in 0..4 {
+        assert!(landing_content.contains(&format!("rank_{}", i)));
+        assert!(out_dir.join(format!("rank_{}/index.html", i)).exists());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_all_ranks_chromium_events_sparse() -> Result<(), Box<dyn std::error::Error>> {
+    // Work on a copy of the fixtures: the logs below are overwritten, and other
+    // multi-rank tests read the originals concurrently.
+    let temp_in_dir = tempdir()?;
+    let input_dir = temp_in_dir.path().to_path_buf();
+    for entry in fs::read_dir("tests/inputs/multi_rank_logs")? {
+        let entry = entry?;
+        fs::copy(entry.path(), input_dir.join(entry.file_name()))?;
+    }
+    let temp_out_dir = tempdir()?;
+    let out_dir = temp_out_dir.path();
+
+    let chromium_log_source = Path::new("tests/inputs/chromium_events.log");
+
+    // Rank 0 and 2 will have traces rank 1 will have an empty log (no trace events).
+    fs::copy(
+        &chromium_log_source,
+        input_dir.join("dedicated_log_torch_trace_rank_0.log"),
+    )?;
+
+    {
+        let rank1_path = input_dir.join("dedicated_log_torch_trace_rank_1.log");
+        fs::File::create(rank1_path)?;
+    }
+
+    fs::copy(
+        &chromium_log_source,
+        input_dir.join("dedicated_log_torch_trace_rank_2.log"),
+    )?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let combined_events_path = out_dir.join("chromium_events.json");
+    assert!(combined_events_path.exists());
+
+    let events_content = fs::read_to_string(combined_events_path)?;
+    let events: Vec<serde_json::Value> = serde_json::from_str(&events_content)?;
+    assert!(!events.is_empty());
+
+    // collect all unique process IDs (ranks) from the events
+    let pids: std::collections::HashSet<u64> = events
+        .iter()
+        .filter_map(|event| event.get("pid").and_then(|v| v.as_u64()))
+        .collect();
+
+    let expected_pids: std::collections::HashSet<u64> = [0, 2, 3].iter().cloned().collect();
+    assert_eq!(pids, expected_pids);
+
+    // verify each rank-specific chromium_events.json file
+    for rank in 0u64..=3 {
+        let rank_events_path = out_dir.join(format!("rank_{}/chromium_events.json", rank));
+        assert!(rank_events_path.exists());
+        let rank_events_content = fs::read_to_string(&rank_events_path)?;
+        let rank_events: Vec<serde_json::Value> = serde_json::from_str(&rank_events_content)?;
+
+        if expected_pids.contains(&(rank as u64)) {
+            assert!(!rank_events.is_empty());
+            let combined_for_rank: Vec<&serde_json::Value> = events
+                .iter()
+                .filter(|ev| ev.get("pid").and_then(|v| v.as_u64()) == Some(rank as u64))
+                .collect();
+            assert_eq!(rank_events.len(), combined_for_rank.len());
+        } else {
+            assert!(rank_events.is_empty());
+        }
+    }
+
+    let landing_page_path = out_dir.join("index.html");
+    assert!(landing_page_path.exists());
+    let landing_content = fs::read_to_string(landing_page_path)?;
+
+    for i in 0..4 {
+        assert!(landing_content.contains(&format!("rank_{}", i)));
+    }
+
+    assert!(landing_content.contains("chromium_events.json"));
+
+    Ok(())
+}
+
+// Detect diverging compile-ID sets: should raise warning.
+#[test]
+fn test_diverging_compile_ids_warning() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path();
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = out_dir.join("index.html");
+    assert!(
+        landing_page.exists(),
+        "Expected {} to exist",
+        landing_page.display()
+    );
+    let landing_content = fs::read_to_string(&landing_page)?;
+    assert!(
+        landing_content.contains("Diverging Compilation IDs detected"),
+        "Expected divergence warning to be present"
+    );
+
+    Ok(())
+}
+
+// Two ranks with identical logs, no divergence warning
+#[test]
+fn test_no_compile_id_divergence() -> Result<(), Box<dyn std::error::Error>> {
+    // Create temp input dir with identical logs for rank 0 and 1
+    let temp_in = tempdir()?;
+    let src_log = PathBuf::from("tests/inputs/simple.log");
+
+    for rank in 0..=1 {
+        let dest = temp_in
+            .path()
+            .join(format!("dedicated_log_torch_trace_rank_{}.log", rank));
+        fs::copy(&src_log, dest)?;
+    }
+
+    let temp_out = tempdir()?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(temp_in.path())
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(temp_out.path())
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = temp_out.path().join("index.html");
+    assert!(
+        landing_page.exists(),
+        "Expected {} to exist",
+        landing_page.display()
+    );
+    let landing_content = fs::read_to_string(&landing_page)?;
+    assert!(
+        !landing_content.contains("Diverging Compilation IDs detected"),
+        "Did not expect divergence warning for identical logs"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_compressed_logs() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+    let temp_dir = tempdir()?;
+    let gzip = |to: &Path| -> std::io::Result<()> {
+        let mut encoder =
+            flate2::write::GzEncoder::new(fs::File::create(to)?, flate2::Compression::default());
+        encoder.write_all(&fs::read("tests/inputs/simple.log")?)?;
+        encoder.finish()?;
+        Ok(())
+    };
+    let compressed = temp_dir.path().join("simple.log.gz");
+    gzip(&compressed)?;
+
+    // The same report as from the uncompressed log
+    let config = tlparse::ParseConfig::default();
+    let plain: HashMap<PathBuf, String> =
+        tlparse::parse_path(&PathBuf::from("tests/inputs/simple.log"), &config)?
+            .into_iter()
+            .collect();
+    let from_gzip: HashMap<PathBuf, String> = tlparse::parse_path(&compressed, &config)?
+        .into_iter()
+        .collect();
+    let mut paths: Vec<&PathBuf> = plain.keys().collect();
+    paths.sort();
+    assert_eq!(paths, {
+        let mut paths: Vec<&PathBuf> = from_gzip.keys().collect();
+        paths.sort();
+        paths
+    });
+    for path in paths {
+        assert!(plain[path] == from_gzip[path], "{} differs", path.display());
+    }
+
+    // zstd logs are read through the zstd command, when there is one
+    let zstd = temp_dir.path().join("simple.log.zst");
+    let zstd_status = std::process::Command::new("zstd")
+        .args(["-q", "tests/inputs/simple.log", "-o"])
+        .arg(&zstd)
+        .status();
+    if zstd_status.is_ok_and(|status| status.success()) {
+        let from_zstd: HashMap<PathBuf, String> =
+            tlparse::parse_path(&zstd, &config)?.into_iter().collect();
+        for (path, contents) in &plain {
+            assert!(*contents == from_zstd[path], "{} differs", path.display());
+        }
+        // Sniffing the first bytes of a zstd log, as checking for a tarball does, stops zstd
+        assert!(!tlparse::archive::is_tar(&zstd)?);
+    }
+
+    // Payloads can't be left in a compressed log for later
+    let config = tlparse::ParseConfig {
+        low_memory: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&compressed, &config)?;
+    assert!(!output
+        .iter()
+        .any(|(p, _)| p == Path::new("artifact_index.json")));
+
+    // Compressed rank logs are found by --all-ranks-html
+    let ranks = temp_dir.path().join("ranks");
+    fs::create_dir(&ranks)?;
+    for rank in 0..2 {
+        gzip(&ranks.join(format!("dedicated_log_torch_trace_rank_{rank}.log.gz")))?;
+    }
+    let out = temp_dir.path().join("out");
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(&ranks)
+        .args(["--all-ranks-html", "--no-browser", "-o"])
+        .arg(&out)
+        .status()?;
+    assert!(status.success());
+    assert!(out.join("rank_0/index.html").is_file());
+    assert!(out.join("rank_1/index.html").is_file());
+
+    // A compressed log can't be followed
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .args(["tail", "--follow"])
+        .arg(&compressed)
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("is gzip compressed; --follow needs an uncompressed log"));
+    Ok(())
+}
+
+#[test]
+fn test_search_index() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let output: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index: serde_json::Value =
+        serde_json::from_str(&output[&PathBuf::from("search_index.json")]).unwrap();
+    let files = index["files"].as_array().unwrap();
+    let urls: Vec<&str> = files.iter().map(|f| f["url"].as_str().unwrap()).collect();
+    assert!(urls.contains(&"-_0_0_0/dynamo_output_graph_0.txt"));
+    assert!(urls.contains(&"-_0_0_0/dynamo_cpp_guards_str_8.txt"));
+    // Cache artifacts and metrics aren't searched
+    assert!(!urls
+        .iter()
+        .any(|u| u.contains("cache") || u.contains("metrics")));
+
+    // Code pages have line anchors, lines are those of the payload
+    let code = files
+        .iter()
+        .find(|f| {
+            f["url"]
+                .as_str()
+                .unwrap()
+                .starts_with("-_0_0_0/inductor_output_code_")
+        })
+        .unwrap();
+    assert_eq!(code["anchored"], true);
+    assert_eq!(code["compile_id"], "[0/0]");
+    let page = &output[&PathBuf::from(code["url"].as_str().unwrap())];
+    let num_lines = code["lines"].as_array().unwrap().len();
+    assert!(page.contains(&format!(r#"id="L{num_lines}""#)));
+    assert!(!page.contains(&format!(r#"id="L{}""#, num_lines + 1)));
+
+    let index_page = &output[&PathBuf::from("index.html")];
+    assert!(index_page.contains(
+        "Search the 6 graphs, guards and code files of this run for an op name, symbol or guard expression."
+    ));
+    assert!(index_page.contains(r#"<input type="search" id="artifact-search""#));
+}
+
+#[test]
+fn test_code_line_permalinks() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let output = tlparse::parse_path(&path, &config).unwrap();
+    let (_, code) = output
+        .iter()
+        .find(|(p, _)| {
+            p.to_string_lossy()
+                .starts_with("-_0_0_0/inductor_output_code_")
+        })
+        .unwrap();
+    assert!(code.contains(r#"<pre class="anchored-lines""#));
+    assert!(code.contains(
+        r#"<span class="line" id="L1"><a class="lineno" href='#L1' data-line="1"></a><span style="font-style:italic;color:#969896;"># AOT ID:"#
+    ));
+    assert!(code.contains("navigator.clipboard.writeText(window.location.href)"));
+    // One anchored line per line of code
+    let lines = code.matches(r#"<span class="line" id="L"#).count();
+    assert!(code.contains(&format!(r#"id="L{lines}""#)));
+    assert!(!code.contains(&format!(r#"id="L{}""#, lines + 1)));
+}
+
+#[test]
+fn test_env_vars_and_profiles() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let tlparse = |args: &[&str], env: &[(&str, &str)]| {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"));
+        command.arg("tests/inputs/simple.log").args(args);
+        for (key, value) in env {
+            command.env(key, value);
+        }
+        command.output()
+    };
+
+    // Options from the environment, `-o` included
+    let out = temp_dir.path().join("env");
+    let output = tlparse(
+        &[],
+        &[
+            ("TLPARSE_OUT", out.to_str().unwrap()),
+            ("TLPARSE_NO_BROWSER", "1"),
+            ("TLPARSE_FORMAT", "html,json"),
+        ],
+    )?;
+    assert!(output.status.success());
+    assert!(out.join("index.html").is_file());
+    assert!(out.join("report.json").is_file());
+
+    // The ci profile: report.json only, strict
+    let out = temp_dir.path().join("ci");
+    let output = tlparse(&["--profile", "ci", "-o", out.to_str().unwrap()], &[])?;
+    assert!(output.status.success());
+    assert!(out.join("report.json").is_file());
+    assert!(!out.join("index.html").exists());
+
+    // The command line and environment override the profile
+    let out = temp_dir.path().join("ci_html");
+    let output = tlparse(
+        &[
+            "--profile",
+            "ci",
+            "--format",
+            "html",
+            "-o",
+            out.to_str().unwrap(),
+        ],
+        &[],
+    )?;
+    assert!(output.status.success());
+    assert!(out.join("index.html").is_file());
+    assert!(!out.join("report.json").exists());
+    let out = temp_dir.path().join("ci_env");
+    let output = tlparse(
+        &["--profile", "ci", "-o", out.to_str().unwrap()],
+        &[("TLPARSE_FORMAT", "html")],
+    )?;
+    assert!(output.status.success());
+    assert!(out.join("index.html").is_file());
+
+    // deep-dive: everything, with provenance tracking
+    let out = temp_dir.path().join("deep_dive");
+    let output = tlparse(
+        &[
+            "--profile",
+            "deep-dive",
+            "--no-browser",
+            "-o",
+            out.to_str().unwrap(),
+        ],
+        &[],
+    )?;
+    assert!(output.status.success());
+    assert!(out.join("index.html").is_file());
+    assert!(out.join("report.json").is_file());
+
+    // Environment variables of the report don't get in the way of subcommands
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .args(["about-run"])
+        .arg(&out)
+        .env("TLPARSE_STRICT", "1")
+        .env("TLPARSE_OUT", temp_dir.path())
+        .output()?;
+    assert!(output.status.success());
+    Ok(())
+}
+
+#[test]
+fn test_compile_divergence_matrix() -> Result<(), Box<dyn std::error::Error>> {
+    // Ranks 0 and 1 compile the same graphs, rank 2 different ones and fewer of them
+    let temp_in = tempdir()?;
+    for (rank, log) in [
+        (0, "comp_metrics.log"),
+        (1, "comp_metrics.log"),
+        (2, "cache_hit_miss.log"),
+    ] {
+        fs::copy(
+            Path::new("tests/inputs").join(log),
+            temp_in
+                .path()
+                .join(format!("dedicated_log_torch_trace_rank_{rank}.log")),
+        )?;
+    }
+    let temp_out = tempdir()?;
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(temp_in.path())
+        .args(["--all-ranks-html", "--no-browser", "--overwrite", "-o"])
+        .arg(temp_out.path())
+        .status()?;
+    assert!(status.success());
+
+    let landing = fs::read_to_string(temp_out.path().join("index.html"))?;
+    assert!(landing.contains("Rank 2 compiled different graphs or guards than the other ranks"));
+    assert!(landing.contains("5 of 5 compiles differ between ranks."));
+    assert!(landing.contains(
+        r#"<tr> <td> [1/0] </td> <td class="divergence-same">A</td> <td class="divergence-same">A</td> <td class="divergence-differs">B</td> <td> graph, guards </td> </tr>"#
+    ));
+    assert!(landing.contains(
+        r#"<td class="divergence-missing"></td> <td> missing on 1 rank (<a href="across_ranks/-_2_0_0/dynamo_output_graph.html">graphs</a>, <a href="across_ranks/-_2_0_0/dynamo_guards.html">guards</a>) </td>"#
+    ));
+    assert!(temp_out
+        .path()
+        .join("across_ranks/-_2_0_0/dynamo_output_graph.html")
+        .is_file());
+
+    // Identical ranks: no rows and no warning
+    let temp_in = tempdir()?;
+    for rank in 0..=1 {
+        fs::copy(
+            "tests/inputs/comp_metrics.log",
+            temp_in
+                .path()
+                .join(format!("dedicated_log_torch_trace_rank_{rank}.log")),
+        )?;
+    }
+    let temp_out = tempdir()?;
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(temp_in.path())
+        .args(["--all-ranks-html", "--no-browser", "--overwrite", "-o"])
+        .arg(temp_out.path())
+        .status()?;
+    assert!(status.success());
+    let landing = fs::read_to_string(temp_out.path().join("index.html"))?;
+    assert!(landing.contains("All 5 compiles have the same graph and guards on every rank."));
+    assert!(!landing.contains("divergence-matrix\">"));
+    assert!(!landing.contains("compiled different graphs"));
+    Ok(())
+}
+
+// Detect diverging cache hit/miss patterns: should raise warning
+#[test]
+fn test_diverging_cache_events_warning() -> Result<(), Box<dyn std::error::Error>> {
+    // Create temp input dir with different logs for rank 0 and 1
+    let temp_in = tempdir()?;
+    let src_log_hits = PathBuf::from("tests/inputs/cache_hit_miss.log");
+    let src_log_no_hits = PathBuf::from("tests/inputs/simple.log");
+
+    fs::copy(
+        &src_log_hits,
+        temp_in.path().join("dedicated_log_torch_trace_rank_0.log"),
+    )?;
+    fs::copy(
+        &src_log_no_hits,
+        temp_in.path().join("dedicated_log_torch_trace_rank_1.log"),
+    )?;
+
+    let temp_out = tempdir()?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(temp_in.path())
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(temp_out.path())
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = temp_out.path().join("index.html");
+    let landing_content = fs::read_to_string(&landing_page)?;
+    assert!(landing_content.contains("Diverging Cache hit/miss patterns detected"));
+
+    Ok(())
+}
+
+// Two ranks with identical cache logs, no divergence warning
+#[test]
+fn test_no_cache_event_divergence() -> Result<(), Box<dyn std::error::Error>> {
+    // Create temp input dir with identical logs for rank 0 and 1
+    let temp_in = tempdir()?;
+    let src_log = PathBuf::from("tests/inputs/cache_hit_miss.log");
+
+    for rank in 0..=1 {
+        let dest = temp_in
+            .path()
+            .join(format!("dedicated_log_torch_trace_rank_{}.log", rank));
+        fs::copy(&src_log, dest)?;
+    }
+
+    let temp_out = tempdir()?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(temp_in.path())
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(temp_out.path())
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = temp_out.path().join("index.html");
+    let landing_content = fs::read_to_string(&landing_page)?;
+    assert!(!landing_content.contains("Diverging Cache hit/miss patterns detected"));
+
+    Ok(())
+}
+
+// Test diverging cache hit/miss patterns using the existing multi_rank_logs directory should create > 2 groups
+#[test]
+fn test_diverging_cache_events_multiple_groups() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+    let temp_out = tempdir()?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(temp_out.path())
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = temp_out.path().join("index.html");
+    let landing_content = fs::read_to_string(&landing_page)?;
+    assert!(landing_content.contains("Diverging Cache hit/miss patterns detected"));
+
+    Ok(())
+}
+
+#[test]
+fn test_collective_schedule_parsing() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_schedule");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().join("out");
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    // Check that collective schedule files are created for each rank
+    for rank in 0..=2 {
+        let rank_dir = out_dir.join(format!("rank_{}", rank));
+        assert!(rank_dir.exists(), "rank_{} directory should exist", rank);
+
+        let index_file = rank_dir.join("index.html");
+        assert!(index_file.exists(), "rank_{} index.html should exist", rank);
+    }
+
+    // Check that landing page exists
+    let landing_page = out_dir.join("index.html");
+    assert!(landing_page.exists(), "Landing page should exist");
+
+    // Check collective_schedules.json exists and has correct structure
+    let collective_schedules_file = out_dir.join("collective_schedules.json");
+    assert!(collective_schedules_file.exists());
+
+    let schedules: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&collective_schedules_file)?)?;
+    assert!(!schedules.is_empty());
+
+    // Verify ranks 0 and 2 have same ops, rank 1 is different
+    let rank0_ops = schedules
+        .iter()
+        .find(|s| s["rank"] == 0 && s["graph"] == "-_0_0_0")
+        .map(|s| &s["ops"])
+        .unwrap();
+    let rank1_ops = schedules
+        .iter()
+        .find(|s| s["rank"] == 1 && s["graph"] == "-_0_0_0")
+        .map(|s| &s["ops"])
+        .unwrap();
+    let rank2_ops = schedules
+        .iter()
+        .find(|s| s["rank"] == 2 && s["graph"] == "-_0_0_0")
+        .map(|s| &s["ops"])
+        .unwrap();
+
+    assert_eq!(rank0_ops, rank2_ops);
+    assert_ne!(rank0_ops, rank1_ops);
+    assert_eq!(rank0_ops.as_array().unwrap().len(), 6);
+    assert_eq!(rank1_ops.as_array().unwrap().len(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_compile_times_page() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/cache_hit_miss.log")
+        .arg("-o")
+        .arg(temp_dir.path())
+        .arg("--overwrite")
+        .arg("--no-browser")
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let index = fs::read_to_string(temp_dir.path().join("index.html"))?;
+    assert!(index.contains("6 compiles took 8.3 s; the slowest, [1/0] (fn), 4.3 s."));
+    let page = fs::read_to_string(temp_dir.path().join("compile_times.html"))?;
+    assert_eq!(page.matches("<tr class=\"compile-time-").count(), 6);
+    assert!(page.contains(
+        "<tr> <th> Total </th> <td> 0.651 s </td> <td> 0.272 s </td> <td> 7.374 s </td> <td> 8.297 s </td> </tr>"
+    ));
+    // Each compile links to its metrics and has a bar of its phases
+    assert!(page.contains(
+        r#"<td data-value="0"> <a href="-_0_0_0/compilation_metrics_2.html">[0/0]</a> </td>"#
+    ));
+    assert!(page.contains("<title>Inductor: 4.016 s</title>"));
+    Ok(())
+}
+
+#[test]
+fn test_compile_wait_times() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let log = temp_dir.path().join("cpu_times.log");
+    fs::write(
+        &log,
+        fs::read_to_string("tests/inputs/cache_hit_miss.log")?.replace(
+            r#"{"compilation_metrics": {"#,
+            r#"{"compilation_metrics": {"entire_frame_compile_cpu_time_s": 0.2, "inductor_compile_cpu_time_s": 0.1, "#,
+        ),
+    )?;
+    let out_dir = temp_dir.path().join("out");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(&log)
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let index = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(index.contains(
+        "6 compiles took 8.3 s; the slowest, [1/0] (fn), 4.3 s; they waited 7.4 s off the CPU, 2 of them for at least 50% of their time."
+    ));
+    let page = fs::read_to_string(out_dir.join("compile_times.html"))?;
+    assert!(page.contains(
+        "<tr> <th> Total </th> <td> 0.651 s </td> <td> 0.272 s </td> <td> 7.374 s </td> <td> 8.297 s </td> <td> 7.380 s </td> </tr>"
+    ));
+    // Wall and CPU time side by side, and the compile that mostly waited highlighted
+    assert_eq!(page.matches("compile-time-waiting\">").count(), 2);
+    assert!(page.contains(
+        "<td data-value=\"4.0164835\"> 4.016 s <span class=\"cpu-time\">/ 0.100 s</span> </td>\n    <td data-value=\"4.29867025\"> 4.299 s <span class=\"cpu-time\">/ 0.200 s</span> </td>\n    <td class=\"wait\" data-value=\"4.09867025\"> 4.099 s </td>"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_collectives_page() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/multi_rank_schedule/dedicated_log_torch_trace_rank_0_6u3fubwl.log")
+        .arg("-o")
+        .arg(temp_dir.path())
+        .arg("--overwrite")
+        .arg("--no-browser")
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let index = fs::read_to_string(temp_dir.path().join("index.html"))?;
+    assert!(index.contains("Inductor logged 2 collective schedules with 4 collectives."));
+    let page = fs::read_to_string(temp_dir.path().join("collectives.html"))?;
+    assert!(
+        page.contains("[0/0]</a>: 1 all_reduce, 1 all_gather, 1 reduce_scatter and 3 waits</h2>")
+    );
+    assert!(page.contains(r#"<li class="collective-all_reduce"><code>all_reduce_</code>"#));
+
+    // Read back through the library
+    let schedules = tlparse::collectives::read_report(temp_dir.path())?;
+    assert_eq!(schedules.len(), 2);
+    assert_eq!(schedules[1].compile_id.as_ref().unwrap().frame_id, Some(1));
+    let kinds: Vec<_> = schedules[1].ops.iter().map(|op| op.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            tlparse::collectives::CollectiveKind::AllReduce,
+            tlparse::collectives::CollectiveKind::Wait
+        ]
+    );
+    assert_eq!(schedules[1].ops[0].namespace, "_c10d_functional");
+    assert!(tlparse::collectives::read_report(Path::new("tests/inputs"))?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_collective_schedule_no_divergence() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir().unwrap();
+    let input_dir = temp_dir.path();
+
+    // Copy identical logs (rank 0 and 2 have same collective schedule)
+    fs::copy(
+        "tests/inputs/multi_rank_schedule/dedicated_log_torch_trace_rank_0_6u3fubwl.log",
+        input_dir.join("dedicated_log_torch_trace_rank_0.log"),
+    )?;
+    fs::copy(
+        "tests/inputs/multi_rank_schedule/dedicated_log_torch_trace_rank_2.log",
+        input_dir.join("dedicated_log_torch_trace_rank_2.log"),
+    )?;
+
+    let temp_out_dir = tempdir().unwrap();
+    let out_dir = temp_out_dir.path();
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = out_dir.join("index.html");
+    assert!(landing_page.exists(), "Landing page should exist");
+    let html_content = fs::read_to_string(&landing_page)?;
+
+    // Should NOT have desync warning since ranks 0 and 2 have identical collective schedules
+    assert!(!html_content.contains("Warning:</strong> Diverging collective operation sequences"));
+
+    Ok(())
+}
+
+#[test]
+fn test_collective_schedule_with_divergence() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_schedule");
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path();
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = out_dir.join("index.html");
+    assert!(landing_page.exists(), "Landing page should exist");
+    let html_content = fs::read_to_string(&landing_page)?;
+
+    // Should have desync warning since rank 1 has different collective schedule
+    assert!(html_content.contains("Warning:</strong> Diverging collective operation sequences"));
+
+    // Check that ranks 0 and 2 are grouped (same sequence)
+    assert!(html_content.contains("Ranks: 0, 2"));
+
+    // Check that rank 1 separate (different sequence)
+    assert!(html_content.contains("Ranks: 1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_runtime_estimation_parsing() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
+    let out_dir = input_dir.join("out");
+
+    Command::cargo_bin("tlparse")?
+        .arg(&input_dir)
+        .args(&["--all-ranks-html", "--overwrite", "-o"])
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .assert()
+        .success();
+
+    let estimations: Vec<serde_json::Value> = serde_json::from_str(&fs::read_to_string(
+        out_dir.join("runtime_estimations.json"),
+    )?)?;
+
+    assert!(!estimations.is_empty());
+    assert!(estimations.iter().any(|e| e["rank"] == 0));
+    assert!(estimations.iter().any(|e| e["rank"] == 1));
+
+    // Verify structure
+    for estimation in &estimations {
+        for op in estimation["ops"].as_array().unwrap() {
+            assert!(op["name"].is_string() && op["estimated_runtime_ns"].is_number());
+            assert!(!op.as_object().unwrap().contains_key("type"));
+        }
+    }
+
+    Ok(())
+}
+
+fn setup_runtime_test_with_ranks(
+    ranks: &[u32],
+) -> Result<(tempfile::TempDir, tempfile::TempDir), Box<dyn std::error::Error>> {
+    let temp_in = tempdir()?;
+    let temp_out = tempdir()?;
+    let src_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
+
+    for &rank in ranks {
+        let src_file = src_dir.join(format!("dedicated_log_torch_trace_rank_{}.log", rank));
+        let dest_file = temp_in
+            .path()
+            .join(format!("dedicated_log_torch_trace_rank_{}.log", rank));
+        fs::copy(&src_file, &dest_file)?;
+    }
+
+    Ok((temp_in, temp_out))
+}
+
+#[test]
+fn test_runtime_analysis_working() -> Result<(), Box<dyn std::error::Error>> {
+    let (input_dir, output_dir) = setup_runtime_test_with_ranks(&[0, 1, 2, 3])?;
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(input_dir.path())
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(output_dir.path())
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = output_dir.path().join("index.html");
+    assert!(landing_page.exists(), "Landing page should exist");
+
+    let html_content = fs::read_to_string(&landing_page)?;
+
+    assert!(html_content.contains("Graph Runtime Analysis"));
+    assert!(!html_content.contains("Runtime analysis not available"));
+    assert!(html_content.contains("ms delta"));
+
+    Ok(())
+}
+
+#[test]
+fn test_runtime_analysis_mismatched_graphs() -> Result<(), Box<dyn std::error::Error>> {
+    // Use entire directory - rank 4 is missing a graph compared to ranks 0,1,2,3
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
+    let temp_out = tempdir()?;
+    let output_dir = temp_out.path();
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = output_dir.join("index.html");
+    assert!(landing_page.exists(), "Landing page should exist");
+
+    let html_content = fs::read_to_string(&landing_page)?;
+
+    assert!(html_content.contains("Graph Runtime Analysis"));
+    assert!(html_content.contains("Runtime analysis not available"));
+    assert!(!html_content.contains("ms delta"));
+
+    Ok(())
+}
+
+#[test]
+fn test_chromium_trace_with_runtime() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
+    let temp_out = tempdir()?;
+    let out_dir = temp_out.path();
+
+    Command::cargo_bin("tlparse")?
+        .arg(&input_dir)
+        .args(&["--all-ranks-html", "--overwrite", "-o"])
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .assert()
+        .success();
+
+    let runtime_trace_path = out_dir.join("chromium_trace_with_runtime.json");
+    assert!(runtime_trace_path.exists());
+
+    let trace_events: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&runtime_trace_path)?)?;
+    assert!(!trace_events.is_empty());
+
+    let runtime_events: Vec<&serde_json::Value> = trace_events
+        .iter()
+        .filter(|e| e["ph"] == "X" && e["cat"] == "runtime")
+        .collect();
+    assert!(!runtime_events.is_empty());
+
+    for e in &runtime_events {
+        assert!(e["name"].is_string());
+        let dur = e["dur"].as_u64().expect("dur should be u64");
+        assert!(dur > 0);
+        assert!(e["pid"].as_u64().is_some());
+        assert!(e["tid"].as_u64().is_some());
+        assert!(e["args"]["runtime_ns"].is_number());
+        assert!(e["args"]["graph"].is_string());
+        if let (Some(pid), Some(rank)) = (e["pid"].as_u64(), e["args"]["rank"].as_u64()) {
+            assert_eq!(pid, rank);
+        }
+    }
+
+    // Verify exact rank set matches input logs
+    let expected_ranks: std::collections::HashSet<u64> = std::fs::read_dir(&input_dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter_map(|name| {
+            name.strip_prefix("dedicated_log_torch_trace_rank_")
+                .and_then(|s| s.strip_suffix(".log"))
+                .and_then(|n| n.parse::<u64>().ok())
+        })
+        .collect();
+
+    let pids: std::collections::HashSet<u64> = runtime_events
+        .iter()
+        .filter_map(|e| e["pid"].as_u64())
+        .collect();
+    assert_eq!(pids, expected_ranks, "pid set != expected rank set");
+
+    Ok(())
+}
+
+#[test]
+fn test_tensor_meta_divergence_groups() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
+    let temp_out = tempdir()?;
+    let out_dir = temp_out.path();
+
+    Command::cargo_bin("tlparse")?
+        .arg(&input_dir)
+        .args(&["--all-ranks-html", "--overwrite", "-o"])
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .assert()
+        .success();
+
+    let landing_page = out_dir.join("index.html");
+    let html_content = fs::read_to_string(&landing_page)?;
+
+    // Should always show tensor meta analysis section
+    assert!(html_content.contains("Tensor Metadata Analysis"));
+
+    // Should show divergence since ranks have different tensor meta
+    assert!(html_content.contains("Ranks exhibit divergent inductor tensor meta"));
+
+    // Ranks 5 and 6 should be grouped together (same tensor meta)
+    assert!(html_content.contains("Ranks: 5, 6"));
+
+    Ok(())
+}
+
+#[test]
+fn test_execution_order_multi_rank_divergence() -> anyhow::Result<()> {
+    // Test data: Two ranks with different execution orders
+    let exec_orders: fxhash::FxHashMap<u32, Vec<String>> = vec![
+        (
+            0_u32,
+            vec!["0/0".to_string(), "0/1".to_string(), "0/2".to_string()],
+        ),
+        (
+            1_u32,
+            vec!["0/1".to_string(), "0/0".to_string(), "0/2".to_string()],
+        ),
+    ]
+    .into_iter()
+    .collect();
+
+    // Mock collective schedules - different at index 0
+    let collective_schedule_by_graph: fxhash::FxHashMap<(u32, String), Vec<String>> = vec![
+        ((0_u32, "0/0".to_string()), vec!["all_reduce".to_string()]),
+        ((0_u32, "0/1".to_string()), vec!["all_gather".to_string()]),
+        ((1_u32, "0/0".to_string()), vec!["all_reduce".to_string()]),
+        ((1_u32, "0/1".to_string()), vec!["broadcast".to_string()]), // Different!
+    ]
+    .into_iter()
+    .collect();
+
+    // Mock cache status - different at index 1
+    let cache_status: fxhash::FxHashMap<(u32, String), String> = vec![
+        ((0_u32, "0/0".to_string()), "✅".to_string()),
+        ((0_u32, "0/1".to_string()), "❌".to_string()),
+        ((1_u32, "0/0".to_string()), "✅".to_string()),
+        ((1_u32, "0/1".to_string()), "✅".to_string()), // Different!
+    ]
+    .into_iter()
+    .collect();
+
+    let report = tlparse::execution_order::analyze_execution_order(
+        &exec_orders,
+        &collective_schedule_by_graph,
+        &cache_status,
+    );
+
+    assert_eq!(report.by_index.len(), 3);
+
+    // Index 0: Rank 0 has "0/0", Rank 1 has "0/1"
+    // Should detect collective schedule mismatch
+    let row_0 = &report.by_index[0];
+    assert_eq!(row_0.by_rank.get(&0_u32), Some(&"0/0".to_string()));
+    assert_eq!(row_0.by_rank.get(&1_u32), Some(&"0/1".to_string()));
+    assert!(row_0
+        .issues
+        .contains(&tlparse::execution_order::ExecOrderIssue::ScheduleMismatch));
+
+    // Index 1: Rank 0 has "0/1", Rank 1 has "0/0"
+    // Should detect cache mismatch
+    let row_1 = &report.by_index[1];
+    assert_eq!(row_1.by_rank.get(&0_u32), Some(&"0/1".to_string()));
+    assert_eq!(row_1.by_rank.get(&1_u32), Some(&"0/0".to_string()));
+    assert!(row_1
+        .issues
+        .contains(&tlparse::execution_order::ExecOrderIssue::CacheMismatch));
+
+    // Index 2: Both ranks have "0/2" - no issues
+    let row_2 = &report.by_index[2];
+    assert_eq!(row_2.by_rank.get(&0_u32), Some(&"0/2".to_string()));
+    assert_eq!(row_2.by_rank.get(&1_u32), Some(&"0/2".to_string()));
+    assert!(row_2.issues.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_collectives_parity_detects_mismatch() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/inputs/collectives_parity");
+    let temp_out = tempdir()?;
+    let out_dir = temp_out.path();
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    // Run collectives parity check on generated outputs
+    let out_dir_buf = out_dir.to_path_buf();
+    parsers::check_collectives_parity(&out_dir_buf, &[0, 1, 2])?;
+
+    // Verify rank 0 parity report exists and contains at least one mismatch
+    let rank_0_report_path = out_dir.join("rank_0").join("collectives_parity.json");
+    assert!(
+        rank_0_report_path.exists(),
+        "collectives_parity.json for rank 0 should exist"
+    );
+    let rank_0_report: CollectivesParityReport =
+        serde_json::from_str(&fs::read_to_string(&rank_0_report_path)?)?;
+    // Expect mismatches: graph -_0_1_0 has offset 1, graph -_0_0_0 missing wait
+    assert_eq!(rank_0_report.graphs.len(), 2);
+    let mut by_graph = HashMap::new();
+    for g in &rank_0_report.graphs {
+        by_graph.insert(g.graph.as_str(), g);
+    }
+    let g = by_graph.get("-_0_1_0").expect("missing -_0_1_0 entry");
+    assert_eq!(g.compile_id, "[0/1]");
+    assert_eq!(g.offset, 0);
+    assert_eq!(g.missing_waits, 3);
+    let g0 = by_graph.get("-_0_0_0").expect("missing -_0_0_0 entry");
+    assert_eq!(g0.compile_id, "[0/0]");
+    assert_eq!(g0.offset, 0);
+    assert_eq!(g0.missing_waits, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_graph_execution_order_diagnostics() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = PathBuf::from("tests/graph_exec_order_tests");
+    let temp_out = tempdir()?;
+    let out_dir = temp_out.path();
+
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg(&input_dir)
+        .arg("--all-ranks-html")
+        .arg("--overwrite")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+
+    let landing_page = out_dir.join("index.html");
+    assert!(landing_page.exists(), "Landing page should exist");
+
+    let html_content = fs::read_to_string(&landing_page)?;
+
+    assert!(html_content.contains("Graph Execution-Order Diagnostics"));
+
+    assert!(html_content.contains("Graph execution order differs across ranks"));
+
+    assert!(html_content.contains("Cache hit/miss sequence: Consistent across ranks"));
+    assert!(!html_content.contains("Warning:</strong> Cache hit/miss mismatch across ranks"));
+
+    Ok(())
+}
+
+#[test]
+fn test_vllm_kernel_stats() {
+    let path = Path::new("tests/inputs/vllm_kernels.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("vllm_summary.json")]).unwrap();
+    let group = &summary["compile_range_groups"][0];
+    assert_eq!(group["total_kernels"], 4);
+    assert_eq!(group["submods"][0]["submod_name"], "submod_0");
+    assert_eq!(group["submods"][0]["kernel_count"], 3);
+    assert_eq!(group["submods"][1]["kernel_count"], 1);
+    assert_eq!(summary["subgraphs"][0]["code_bytes"], 738);
+
+    let index_html = &map[&PathBuf::from("index.html")];
+    assert!(index_html.contains("<tr><td>submod_0</td><td>3</td><td>738 B</td></tr>"));
+    // Groups without inductor output code get no kernel table
+    assert_eq!(index_html.matches("<th>Triton Kernels</th>").count(), 1);
+}
+
+#[test]
+fn test_vllm_compile_sizes_sweep() {
+    // Two submods compiled for the general range and sizes 1, 8 and 16; size 1 generates the
+    // same code as the general graph
+    let path = Path::new("tests/inputs/vllm_compile_sizes.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("vllm_summary.json")]).unwrap();
+    let sweep = &summary["engines"][0]["size_sweep"];
+    let sizes: Vec<&str> = sweep["sizes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["size_or_range"].as_str().unwrap())
+        .collect();
+    assert_eq!(sizes, ["range [1, 8192]", "size 1", "size 8", "size 16"]);
+    assert_eq!(sweep["sizes"][1]["same_code_as"], "range [1, 8192]");
+    assert_eq!(sweep["sizes"][3]["kernel_count"], 9);
+    assert_eq!(sweep["sizes"][3]["kernel_trend"], "+4");
+    assert_eq!(sweep["sizes"][3]["compile_time_s"], 7.5);
+    let submod_0 = &sweep["submods"][0];
+    assert_eq!(submod_0["submod_name"], "submod_0");
+    assert_eq!(submod_0["entries"][2]["size_or_range"], "size 8");
+    assert_eq!(submod_0["entries"][2]["compile_time"], "3.00 s");
+    assert!(submod_0["entries"][2]["same_code_as"].is_null());
+    assert_eq!(summary["subgraphs"][0]["compile_time_s"], 4.0);
+
+    let index_html = &map[&PathBuf::from("index.html")];
+    assert!(index_html.contains("<h2>Compile Sizes Sweep</h2>"));
+    assert!(index_html.contains(
+        r#"<tr class="same-code"><td>size 1</td><td>2</td><td>5 <span class="sweep-trend">=</span></td>"#
+    ));
+
+    // An engine whose submods were only compiled for the general range gets no sweep
+    let path = Path::new("tests/inputs/vllm_engines.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("vllm_summary.json")]).unwrap();
+    assert!(summary["engines"][0]["size_sweep"].is_object());
+    assert!(summary["engines"][1]["size_sweep"].is_null());
+    let index_html = &map[&PathBuf::from("index.html")];
+    assert_eq!(
+        index_html.matches("<h2>Compile Sizes Sweep</h2>").count(),
+        1
+    );
+}
+
+#[test]
+fn test_vllm_cudagraph_checklist() {
+    // Capture sizes 1, 2, 4, 8 and 32; 4, 8 and 16 were captured, and submod_0 was compiled for
+    // the range [1, 4] and size 8
+    let path = Path::new("tests/inputs/vllm_cudagraphs.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("vllm_summary.json")]).unwrap();
+    let checklist = &summary["engines"][0]["cudagraph_checklist"];
+    assert_eq!(checklist["captures_logged"], true);
+    let statuses: Vec<(i64, &str)> = checklist["sizes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["size"].as_i64().unwrap(), s["status"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            (1, "compiled only"),
+            (2, "compiled only"),
+            (4, "captured"),
+            (8, "captured"),
+            (16, "unexpected"),
+            (32, "missing"),
+        ]
+    );
+    assert_eq!(checklist["sizes"][2]["compiled_as"], "range [1, 4]");
+    assert_eq!(checklist["sizes"][3]["compiled_as"], "size 8");
+
+    let index_html = &map[&PathBuf::from("index.html")];
+    assert!(index_html.contains("<h2>CUDAGraph Capture Sizes</h2>"));
+    assert!(index_html.contains(
+        "2 of 5 capture sizes got cudagraphs: 2 compiled only, 1 missing, 1 unexpected."
+    ));
+    assert!(index_html.contains(
+        r#"<tr class="cudagraph-problem"><td>32</td><td>✓</td><td></td><td></td><td>missing</td></tr>"#
+    ));
 }
-
 #[test]
-fn test_provenance_tracking_jit_debug_handle() {
-    let expected_files = [
-        "-_0_0_0/before_pre_grad_graph_1.txt",
-        "-_0_0_0/after_post_grad_graph_11.txt",
-        "provenance_tracking_-_0_0_0.html",
-        "-_0_0_0/inductor_provenance_tracking_node_mappings_14.json",
-    ];
-
-    let path = Path::new("tests/inputs/inductor_provenance_jit_debug_handle_log.txt").to_path_buf();
+fn test_vllm_graph_partitions() {
+    // use_inductor_graph_partition: submod_0 compiled for the range [1, 8], whose generated code
+    // has two partitions around the attention op, and for size 8, which also logged a
+    // vllm_graph_partition artifact
+    let path = Path::new("tests/inputs/vllm_graph_partition.log").to_path_buf();
     let config = tlparse::ParseConfig {
-        inductor_provenance: true,
+        strict: true,
         ..Default::default()
     };
-    let output = tlparse::parse_path(&path, &config);
-    assert!(output.is_ok());
-    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
-
-    // Check all files are present
-    for prefix in expected_files {
-        assert!(
-            prefix_exists(&map, prefix),
-            "{} not found in output",
-            prefix
-        );
-    }
-
-    // Read the HTML file and verify the line mappings
-    let html_path = map
-        .keys()
-        .find(|p| {
-            p.to_str()
-                .unwrap()
-                .contains("provenance_tracking_-_0_0_0.html")
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("vllm_summary.json")]).unwrap();
+    let partitions = &summary["engines"][0]["graph_partitions"];
+    let graphs = partitions["graphs"].as_array().unwrap();
+    assert_eq!(graphs.len(), 2);
+    assert_eq!(graphs[0]["compiled_for"], "range [1, 8]");
+    assert_eq!(graphs[0]["source"], "inductor_output_code");
+    let labels: Vec<(&str, bool, u64)> = graphs[0]["segments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| {
+            (
+                s["label"].as_str().unwrap(),
+                s["cudagraphable"].as_bool().unwrap(),
+                s["num_kernels"].as_u64().unwrap(),
+            )
         })
-        .unwrap();
-    let html_content = map.get(html_path).unwrap();
-
-    // Extract the line mappings JSON from the script tag
-    let script_start = html_content
-        .find(r#"<script id="lineMappings" type="application/json">"#)
-        .unwrap();
-    let json_start = html_content[script_start..].find(">").unwrap() + script_start + 1;
-    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
-    let line_mappings_str = &html_content[json_start..json_end];
-    let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
-
-    // For jit log, we expect similar structure to jit cuda but with different kernel names
-    let expected_mappings = serde_json::json!(         {
-      "cppCodeToPost": {},
-      "postToCppCode": {},
-      "postToPre": {
-        "12": [
-          17
-        ],
-        "15": [
-          20
-        ],
-        "18": [
-          23
-        ],
-        "19": [
-          23
-        ],
-        "22": [
-          26
-        ],
-        "23": [
-          26
-        ],
-        "24": [
-          26
-        ],
-        "25": [
-          26
-        ],
-        "26": [
-          26
-        ],
-        "4": [
-          11
-        ],
-        "5": [
-          11
-        ],
-        "6": [
-          11
-        ],
-        "9": [
-          14
-        ]
-      },
-      "postToPyCode": {
-        "12": [
-          204
-        ],
-        "15": [
-          211
-        ],
-        "18": [
-          216
-        ],
-        "19": [
-          223
-        ],
-        "22": [
-          223
-        ],
-        "23": [
-          223
-        ],
-        "24": [
-          223
-        ],
-        "25": [
-          223
-        ],
-        "26": [
-          223
-        ],
-        "29": [
-          204
-        ],
-        "5": [
-          197
-        ],
-        "6": [
-          204
-        ],
-        "9": [
-          204
-        ]
-      },
-      "preToPost": {
-        "11": [
-          4,
-          5,
-          6
-        ],
-        "14": [
-          9
-        ],
-        "17": [
-          12
-        ],
-        "20": [
-          15
-        ],
-        "23": [
-          18,
-          19
-        ],
-        "26": [
-          22,
-          23,
-          24,
-          25,
-          26
-        ]
-      },
-      "pyCodeToPost": {
-        "197": [
-          5
-        ],
-        "204": [
-          12,
-          9,
-          6,
-          29
-        ],
-        "211": [
-          15
-        ],
-        "216": [
-          18
-        ],
-        "223": [
-          26,
-          22,
-          19,
-          25,
-          24,
-          23
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            ("partition_0", true, 2),
+            ("vllm.unified_attention_with_output", false, 0),
+            ("partition_1", true, 1),
         ]
-      }
-    });
+    );
+    // The artifact wins over the generated code of the same compile
+    assert_eq!(graphs[1]["compiled_for"], "size 8");
+    assert_eq!(graphs[1]["source"], "vllm_graph_partition");
+    assert_eq!(graphs[1]["segments"][0]["num_nodes"], 14);
 
-    assert_eq!(line_mappings, expected_mappings);
+    let index_html = &map[&PathBuf::from("index.html")];
+    assert!(index_html.contains("<h2>Inductor Graph Partitions</h2>"));
+    assert!(index_html.contains(
+        "2 graphs split into 4 partitions, 4 of them cudagraph-able; run eagerly between them: vllm.unified_attention_with_output."
+    ));
 }
-
 #[test]
-fn test_provenance_tracking_multiple_colons_in_kernel_name() {
-    let expected_files = [
-        "-_-_-_-/before_pre_grad_graph_0.txt",
-        "-_-_-_-/after_post_grad_graph_8.txt",
-        "provenance_tracking_-_-_-_-.html",
-        "-_-_-_-/inductor_provenance_tracking_node_mappings_12.json",
-    ];
-
-    let path = Path::new("tests/inputs/inductor_provenance_long_log.txt").to_path_buf();
+fn test_parse_vllm_sample() {
+    let path = Path::new("tests/inputs/vllm_sample.log").to_path_buf();
     let config = tlparse::ParseConfig {
-        inductor_provenance: true,
+        strict: true,
         ..Default::default()
     };
     let output = tlparse::parse_path(&path, &config);
     assert!(output.is_ok());
     let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
 
-    // Check all files are present
+    // check for vLLM-specific artifacts
+    let expected_files = [
+        "-_0_0_0/vllm_compilation_config",
+        "-_0_0_0/vllm_piecewise_split_graph",
+        "-_-_-_-/vllm_submod_0",
+        "-_-_-_-/vllm_submod_2",
+    ];
+
     for prefix in expected_files {
         assert!(
             prefix_exists(&map, prefix),
@@ -1647,1026 +4382,3182 @@ fn test_provenance_tracking_multiple_colons_in_kernel_name() {
         );
     }
 
-    // Read the HTML file and verify the line mappings
-    let html_path = map
-        .keys()
-        .find(|p| {
-            p.to_str()
-                .unwrap()
-                .contains("provenance_tracking_-_-_-_-.html")
-        })
-        .unwrap();
-    let html_content = map.get(html_path).unwrap();
-
-    // Extract the line mappings JSON from the script tag
-    let script_start = html_content
-        .find(r#"<script id="lineMappings" type="application/json">"#)
-        .unwrap();
-    let json_start = html_content[script_start..].find(">").unwrap() + script_start + 1;
-    let json_end = html_content[json_start..].find("</script>").unwrap() + json_start;
-    let line_mappings_str = &html_content[json_start..json_end];
-    let line_mappings: serde_json::Value = serde_json::from_str(line_mappings_str).unwrap();
+    let index_html = &map[&PathBuf::from("index.html")];
 
-    // For jit log, we expect similar structure to jit cuda but with different kernel names
-    let expected_post_nodes = vec![
-        10197, 10194, 10191, 10200, 10225, 10223, 10221, 10220, 10219, 10218, 10215, 10214, 10213,
-        10212, 10211, 10205, 10204, 10203, 10199, 10198, 10202, 10201, 10217, 10216, 10210, 10209,
-        10208, 10207, 10206, 10224, 10222, 10227, 10226,
-    ];
+    assert!(index_html.contains("vLLM Compilation Summary"),);
+    assert!(index_html.contains("Dynamo Compilation"),);
+    assert!(index_html.contains("Compilation Configuration"),);
+    assert!(index_html.contains("Piecewise Split Graph"),);
+    assert!(index_html.contains("range [1, 16384]"),);
+    assert!(index_html.contains("size 8"),);
+    assert!(index_html.contains("submod_0"),);
+    assert!(index_html.contains("submod_2"),);
+    assert!(index_html.contains("vllm_summary.json"));
 
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("vllm_summary.json")]).unwrap();
+    assert_eq!(summary["config"]["model"], "meta-llama/Llama-3.1-8B");
+    assert_eq!(summary["subgraphs"].as_array().unwrap().len(), 4);
+    assert_eq!(summary["subgraphs"][0]["submod_name"], "submod_0");
     assert_eq!(
-        line_mappings["cppCodeToPost"]["15892"],
-        serde_json::json!(expected_post_nodes)
+        summary["compile_range_groups"][0]["size_or_range"],
+        "range [1, 16384]"
     );
+    assert!(summary["artifacts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|a| a["name"].as_str().unwrap().starts_with("vllm_submod_2")));
+    assert_eq!(summary["timing"][0]["compile_id"], "[0/0]");
 }
 
 #[test]
-fn test_provenance_stack_trace_readable() {
-    let path = Path::new("tests/inputs/inductor_provenance_extended_log.txt").to_path_buf();
+fn test_vllm_multiple_engines() {
+    // A backbone and a speculative decoding head, then the backbone again
+    let path = Path::new("tests/inputs/vllm_engines.log").to_path_buf();
     let config = tlparse::ParseConfig {
-        inductor_provenance: true,
+        strict: true,
         ..Default::default()
     };
     let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
         .unwrap()
         .into_iter()
         .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("vllm_summary.json")]).unwrap();
+    let engines = summary["engines"].as_array().unwrap();
+    assert_eq!(engines.len(), 2);
+    assert_eq!(engines[0]["name"], "backbone");
+    assert_eq!(engines[0]["subgraphs"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        engines[0]["compile_range_groups"][1]["size_or_range"],
+        "size 8"
+    );
+    assert_eq!(engines[1]["name"], "eagle_head");
+    assert_eq!(engines[1]["subgraphs"].as_array().unwrap().len(), 1);
+    // Top-level fields describe the first engine only
+    assert_eq!(summary["subgraphs"].as_array().unwrap().len(), 3);
 
-    assert!(map.keys().any(|k| {
-        let s = k.to_str().unwrap_or("");
-        s.contains("inductor_provenance_tracking_kernel_stack_traces") && s.ends_with(".json")
-    }));
-    assert!(map.keys().any(|k| {
-        let s = k.to_str().unwrap_or("");
-        s.contains("inductor_provenance_tracking_kernel_stack_traces")
-            && s.ends_with("_readable.html")
-    }));
+    let index_html = &map[&PathBuf::from("index.html")];
+    assert!(index_html.contains("This log has compiles of 2 vLLM engine instances"));
+    assert!(index_html.contains("<li><a href='#engine-1'>eagle_head</a>, subgraphs: 1</li>"));
+    assert!(index_html.contains(r#"<h1 class="engine-name">Engine backbone</h1>"#));
+    assert_eq!(
+        index_html.matches("<h2>Inductor Compilation</h2>").count(),
+        2
+    );
 
-    let html = map
+    // A single engine gets no engine headings
+    let path = Path::new("tests/inputs/vllm_sample.log").to_path_buf();
+    let output = tlparse::parse_path(&path, &config).unwrap();
+    let index_html = &output
         .iter()
-        .find(|(k, _)| {
-            let s = k.to_str().unwrap_or("");
-            s.contains("inductor_provenance_tracking_kernel_stack_traces")
-                && s.ends_with("_readable.html")
-        })
+        .find(|(p, _)| p == Path::new("index.html"))
         .unwrap()
         .1;
-    assert!(html.contains("line 1\n  foo()"));
-    assert!(!html.contains("\\n"));
-
-    let index_html = map.get(Path::new("index.html")).unwrap();
-    assert!(index_html.contains("_readable.html\">readable_html</a>"));
+    assert!(!index_html.contains(r#"<h1 class="engine-name">"#));
 }
 
+#[cfg(feature = "ffi")]
 #[test]
-fn test_all_ranks_basic() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
-    let temp_dir = tempdir().unwrap();
-    let out_dir = temp_dir.path().join("out");
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(&out_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
-
-    let rank0_index = out_dir.join("rank_0/index.html");
-    let rank1_index = out_dir.join("rank_1/index.html");
-    let landing_page = out_dir.join("index.html");
-
-    assert!(rank0_index.exists());
-    assert!(rank1_index.exists());
-    assert!(landing_page.exists());
-
-    let landing_content = fs::read_to_string(landing_page).unwrap();
-    assert!(landing_content.contains(r#"<a href="rank_0/index.html">"#));
-    assert!(landing_content.contains(r#"<a href="rank_1/index.html">"#));
-    assert!(
-        !landing_content.contains("collectives_parity.json"),
-        "multi-rank landing page should not link collectives parity"
-    );
+fn test_ffi() {
+    use std::ffi::{c_void, CStr, CString};
+    use std::ptr;
+    use tlparse::ffi::*;
+
+    extern "C" fn on_progress(read: u64, total: u64, user_data: *mut c_void) {
+        let calls = unsafe { &mut *(user_data as *mut Vec<(u64, u64)>) };
+        calls.push((read, total));
+    }
 
-    let rank0_content = fs::read_to_string(rank0_index).unwrap();
-    assert!(rank0_content.contains("collectives_parity.json"));
-    Ok(())
+    let log = CString::new("tests/inputs/simple.log").unwrap();
+    let temp_dir = tempdir().unwrap();
+    let out_dir = CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+    let options = CString::new(r#"{"plain_text": true}"#).unwrap();
+    let mut calls: Vec<(u64, u64)> = Vec::new();
+    let mut error = ptr::null_mut();
+    let code = unsafe {
+        tlparse_parse_to_dir(
+            log.as_ptr(),
+            out_dir.as_ptr(),
+            options.as_ptr(),
+            Some(on_progress),
+            &mut calls as *mut _ as *mut c_void,
+            &mut error,
+        )
+    };
+    assert_eq!(code, TLPARSE_OK);
+    assert!(error.is_null());
+    assert!(temp_dir.path().join("index.html").exists());
+    assert!(temp_dir.path().join("manifest.json").exists());
+    let size = fs::metadata("tests/inputs/simple.log").unwrap().len();
+    assert!(calls.len() > 1);
+    assert_eq!(calls.last(), Some(&(size, size)));
+
+    let summary = unsafe { tlparse_summary_json(log.as_ptr(), ptr::null(), &mut error) };
+    assert!(!summary.is_null());
+    let json: serde_json::Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(summary) }.to_str().unwrap()).unwrap();
+    assert!(!json["compiles"].as_array().unwrap().is_empty());
+    unsafe { tlparse_string_free(summary) };
+
+    let missing = CString::new("tests/inputs/does_not_exist.log").unwrap();
+    let summary = unsafe { tlparse_summary_json(missing.as_ptr(), ptr::null(), &mut error) };
+    assert!(summary.is_null());
+    assert!(!error.is_null());
+    unsafe { tlparse_string_free(error) };
+
+    let bad_options = CString::new("{").unwrap();
+    let code = unsafe {
+        tlparse_parse_to_dir(
+            log.as_ptr(),
+            out_dir.as_ptr(),
+            bad_options.as_ptr(),
+            None,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        )
+    };
+    assert_eq!(code, TLPARSE_ERR_INVALID_ARGUMENT);
 }
 
 #[test]
-fn test_all_ranks_messy_input() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_messy_input");
-    let temp_dir = tempdir().unwrap();
-    let out_dir = temp_dir.path().join("out");
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(&out_dir)
-        .arg("--no-browser");
-
-    cmd.assert().success();
-
-    // Check for landing page and rank-specific index files
-    let landing_page = out_dir.join("index.html");
-    let rank0_index = out_dir.join("rank_0/index.html");
-    let rank1_index = out_dir.join("rank_1/index.html");
-
-    assert!(
-        rank0_index.exists(),
-        "rank 0 index.html should exist in messy input test"
-    );
-    assert!(
-        rank1_index.exists(),
-        "rank 1 index.html should exist in messy input test"
-    );
-    assert!(
-        landing_page.exists(),
-        "toplevel index.html should exist in messy input test"
+fn test_device_backends() {
+    let path = Path::new("tests/inputs/device_backends.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    // HIP reports cuda tensors; the HIP version in system_info makes this a ROCm run
+    assert!(index.contains("<strong>ROCm</strong>: ROCm 6.2.41133 (system_info)"));
+    assert!(!index.contains("<strong>CUDA</strong>"));
+    // cudagraph artifacts of a ROCm run are listed under ROCm, backend-named ones under theirs
+    let rocm = index.split("id=\"device-rocm\"").nth(1).unwrap();
+    let rocm = rocm.split("</details>").next().unwrap();
+    assert!(rocm.contains("cudagraph_skip_reason_1.txt"));
+    assert!(!rocm.contains("fx_graph_cache_miss"));
+    let mtia = index.split("id=\"device-mtia\"").nth(1).unwrap();
+    assert!(mtia.contains("mtia_kernel_info_2.json"));
+
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("summary.json")]).unwrap();
+    assert_eq!(
+        summary["device_backends"],
+        serde_json::json!(["rocm", "mtia"])
     );
-
-    let landing_content = fs::read_to_string(landing_page).unwrap();
-    assert!(landing_content.contains(r#"<a href="rank_0/index.html">"#));
-    assert!(landing_content.contains(r#"<a href="rank_1/index.html">"#));
-    Ok(())
 }
 
 #[test]
-fn test_all_ranks_no_browser() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
-    let temp_dir = tempdir().unwrap();
-    let out_dir = temp_dir.path().join("out");
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(&out_dir)
-        .arg("--no-browser");
-
-    cmd.assert().success().stdout(
-        str::contains("Multi-rank report generated").and(str::contains(out_dir.to_str().unwrap())),
-    );
-
-    // Check that files were created but don't try to open them
-    let rank0_index = out_dir.join("rank_0/index.html");
-    let rank1_index = out_dir.join("rank_1/index.html");
-    let landing_page = out_dir.join("index.html");
-
-    assert!(rank0_index.exists());
-    assert!(rank1_index.exists());
-    assert!(landing_page.exists());
-    Ok(())
+fn test_duplicate_graphs() {
+    let path = Path::new("tests/inputs/duplicate_graphs.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    // Layers 0-2 compile the same graph; the 128-wide graph differs in shapes
+    let report = &map[&PathBuf::from("duplicate_graphs.html")];
+    assert!(report.contains("3 compiles, 2.250 s wasted"));
+    assert!(report.contains(r#"<a href="-_2_0_0/dynamo_output_graph_4.txt">[2/0]</a>"#));
+    assert!(!report.contains("[3/0]"));
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("<strong>2.250 s</strong> of compile time"));
+
+    // No section without duplicates
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("duplicate_graphs.html")));
 }
 
 #[test]
-fn test_all_ranks_with_latest_fails() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
-    let temp_dir = tempdir().unwrap();
-    let out_dir = temp_dir.path().join("out");
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--latest")
-        .arg("-o")
-        .arg(&out_dir)
-        .arg("--no-browser");
-
-    cmd.assert().failure().stderr(str::contains(
-        "--latest cannot be used with --all-ranks-html",
+fn test_sources_index() {
+    let path = Path::new("tests/inputs/sources.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let sources = &map[&PathBuf::from("sources.html")];
+    assert!(sources.contains(
+        "<code>/home/user/model.py</code></a> </td> <td> 4 compile stacks, 1 graph break, 1 guard, 1 failure </td>"
     ));
-
-    Ok(())
+    // Frames of the graph break traceback, not just the compiled function
+    assert!(sources.contains(
+        r#"<li>line 7 in <code>helper</code>: graph break in <a href="index.html#[0/0]">[0/0]</a></li>"#
+    ));
+    assert!(
+        sources.contains(r#"<li>line 42: failure in <a href="index.html#[1/0]">[1/0]</a></li>"#)
+    );
+    // PyTorch frames are not user code
+    assert!(!sources.contains("module.py"));
+    assert!(map[&PathBuf::from("index.html")].contains("<a href='sources.html'>2 source files</a>"));
 }
 
 #[test]
-fn test_all_ranks_no_logs() -> Result<(), Box<dyn std::error::Error>> {
-    let temp_dir = tempdir()?;
-    let empty_dir = temp_dir.path();
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(empty_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("--no-browser");
-
-    cmd.assert()
-        .failure()
-        .stderr(str::contains("No rank log files found"));
+fn test_framework_internal_compiles() {
+    let path = Path::new("tests/inputs/framework_compiles.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(
+        "<li><strong>FSDP</strong> (2): <a href='#[1/0]'>[1/0]</a> <a href='#[3/0]'>[3/0]</a> </li>"
+    ));
+    assert!(index.contains(
+        "<li><strong>Distributed checkpoint</strong> (1): <a href='#[2/0]'>[2/0]</a> </li>"
+    ));
+    assert!(index.contains(r#"const frameworkCompileIds = ["[1/0]","[3/0]","[2/0]"];"#));
 
-    Ok(())
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("summary.json")]).unwrap();
+    let frameworks: Vec<&serde_json::Value> = summary["compiles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| &c["framework"])
+        .collect();
+    assert_eq!(
+        frameworks,
+        [
+            &serde_json::Value::Null,
+            &serde_json::json!("fsdp"),
+            &serde_json::json!("dcp"),
+            &serde_json::json!("fsdp")
+        ]
+    );
 }
 
 #[test]
-fn test_all_ranks_chromium_events_combined() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
-    let temp_out_dir = tempdir()?;
-    let out_dir = temp_out_dir.path();
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(out_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
-
-    // check that chromium_events.json is created and contains events from all ranks
-    let combined_events_path = out_dir.join("chromium_events.json");
-    assert!(combined_events_path.exists());
-
-    let events_content = fs::read_to_string(combined_events_path)?;
-    let events: Vec<serde_json::Value> = serde_json::from_str(&events_content)?;
-    assert!(!events.is_empty());
-
-    // collect all unique process IDs (ranks) from the events
-    let pids: std::collections::HashSet<u64> = events
-        .iter()
-        .filter_map(|event| event.get("pid").and_then(|v| v.as_u64()))
+fn test_abnormal_end_banner() {
+    // simple.log cut off right before its inductor_output_code entry
+    let path = Path::new("tests/inputs/crashed_mid_compile.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
         .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("<h2>Run ended abnormally</h2>"));
+    assert!(index.contains("The log ends in the middle of a compile"));
+    assert!(
+        index.contains("<a href='#[0/0]'>[0/0]</a> was in\n<strong>Inductor output code</strong>")
+    );
+    assert!(index.contains("test_torchinductor.py:13037 in &lt;module&gt;"));
 
-    let expected_pids: std::collections::HashSet<u64> = [0, 2, 3].iter().cloned().collect();
-    assert_eq!(pids, expected_pids);
-
-    // verify each rank-specific chromium_events.json file
-    for rank in 0u64..=3 {
-        let rank_events_path = out_dir.join(format!("rank_{}/chromium_events.json", rank));
-        assert!(rank_events_path.exists());
-        let rank_events_content = fs::read_to_string(&rank_events_path)?;
-        let rank_events: Vec<serde_json::Value> = serde_json::from_str(&rank_events_content)?;
-
-        if expected_pids.contains(&(rank as u64)) {
-            assert!(!rank_events.is_empty());
-            let combined_for_rank: Vec<&serde_json::Value> = events
-                .iter()
-                .filter(|ev| ev.get("pid").and_then(|v| v.as_u64()) == Some(rank as u64))
-                .collect();
-            assert_eq!(rank_events.len(), combined_for_rank.len());
-        } else {
-            assert!(rank_events.is_empty());
-        }
-    }
-
-    let landing_page_path = out_dir.join("index.html");
-    assert!(landing_page_path.exists());
-    let landing_content = fs::read_to_string(landing_page_path)?;
-    for i in 0..4 {
-        assert!(landing_content.contains(&format!("rank_{}", i)));
-        assert!(out_dir.join(format!("rank_{}/index.html", i)).exists());
-    }
-
-    Ok(())
+    // Ending with an exception names what was raised
+    let temp_dir = tempdir().unwrap();
+    let crashed = temp_dir.path().join("crashed.log");
+    let mut log = fs::read_to_string(&path).unwrap();
+    log.push_str(concat!(
+        "\nE1206 15:18:21.460000 1500233 torch/_dynamo/convert_frame.py:1000] ",
+        r#"{"artifact": {"name": "dynamo_error", "encoding": "string"}, "frame_id": 0, "frame_compile_id": 0, "attempt": 0, "has_payload": "c97b1ffb9116faf10394984195320b6b"}"#,
+        "\n\tTraceback (most recent call last):",
+        "\n\t  File \"/home/user/model.py\", line 3, in forward",
+        "\n\tRuntimeError: CUDA error: an illegal memory access was encountered\n",
+    ));
+    fs::write(&crashed, log).unwrap();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&crashed, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("The log ends with a <code>dynamo_error</code> artifact."));
+    assert!(index
+        .contains("<pre>RuntimeError: CUDA error: an illegal memory access was encountered</pre>"));
+
+    // Complete logs get no banner
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map[&PathBuf::from("index.html")].contains("Run ended abnormally"));
 }
 
 #[test]
-fn test_all_ranks_chromium_events_sparse() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
-    let temp_out_dir = tempdir()?;
-    let out_dir = temp_out_dir.path();
-
-    let chromium_log_source = Path::new("tests/inputs/chromium_events.log");
+fn test_rerender() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    // The report model is only written on request
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/simple.log")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser");
+    cmd.assert().success();
+    assert!(!out_dir.join("report_model.json").exists());
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("rerender").arg(&out_dir);
+    cmd.assert()
+        .failure()
+        .stderr(str::contains("parse the log with --report-model"));
 
-    // Rank 0 and 2 will have traces rank 1 will have an empty log (no trace events).
-    fs::copy(
-        &chromium_log_source,
-        input_dir.join("dedicated_log_torch_trace_rank_0.log"),
-    )?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("tests/inputs/simple.log")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--overwrite")
+        .arg("--report-model")
+        .arg("--no-browser");
+    cmd.assert().success();
+    let model: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out_dir.join("report_model.json"))?)?;
+    let pages: Vec<PathBuf> = model["pages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| out_dir.join(p["path"].as_str().unwrap()))
+        .collect();
+    assert!(pages.contains(&out_dir.join("index.html")));
+    assert!(pages
+        .iter()
+        .any(|p| p.ends_with("compilation_metrics_9.html")));
+    let before: Vec<String> = pages
+        .iter()
+        .map(|p| fs::read_to_string(p).unwrap())
+        .collect();
 
-    {
-        let rank1_path = input_dir.join("dedicated_log_torch_trace_rank_1.log");
-        fs::File::create(rank1_path)?;
+    // Without overrides the pages come out the same
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("rerender").arg(&out_dir);
+    cmd.assert()
+        .success()
+        .stdout(str::contains(format!("Re-rendered {} pages", pages.len())));
+    for (page, html) in pages.iter().zip(&before) {
+        assert_eq!(&fs::read_to_string(page)?, html, "{}", page.display());
     }
 
-    fs::copy(
-        &chromium_log_source,
-        input_dir.join("dedicated_log_torch_trace_rank_2.log"),
+    // A custom index.html replaces only the index; the header goes on every page
+    let template_dir = temp_dir.path().join("templates");
+    fs::create_dir(&template_dir)?;
+    fs::write(
+        template_dir.join("index.html"),
+        "<html><body>{custom_header_html | format_unescaped}{{ for d in directory_names }}<p>{d}</p>{{ endfor }}</body></html>",
     )?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("rerender")
+        .arg(&out_dir)
+        .arg("--template-dir")
+        .arg(&template_dir)
+        .arg("--custom-header-html")
+        .arg("<b>nightly</b>");
+    cmd.assert().success();
+    let index = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(index.starts_with("<html><body><b>nightly</b><p>"));
+    assert!(fs::read_to_string(out_dir.join("compare.html"))?.contains("<b>nightly</b>"));
+    assert_eq!(
+        fs::read_to_string(out_dir.join("failures_and_restarts.html"))?,
+        before[pages
+            .iter()
+            .position(|p| p.ends_with("failures_and_restarts.html"))
+            .unwrap()]
+    );
 
+    // Pages outside of a log's report, like the multi-rank landing page, have templates of
+    // their own to replace
+    let multi_rank_dir = temp_dir.path().join("multi_rank");
     let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(input_dir)
+    cmd.arg("tests/inputs/multi_rank_logs")
         .arg("--all-ranks-html")
-        .arg("--overwrite")
+        .arg("--report-model")
         .arg("-o")
-        .arg(out_dir)
+        .arg(&multi_rank_dir)
         .arg("--no-browser");
     cmd.assert().success();
+    fs::write(
+        template_dir.join("multi_rank_index.html"),
+        "<html><body>{num_ranks} ranks</body></html>",
+    )?;
+    let mut cmd = Command::cargo_bin("tlparse")?;
+    cmd.arg("rerender")
+        .arg(&multi_rank_dir)
+        .arg("--template-dir")
+        .arg(&template_dir);
+    cmd.assert()
+        .success()
+        .stdout(str::contains("Re-rendered 1 pages"));
+    assert_eq!(
+        fs::read_to_string(multi_rank_dir.join("index.html"))?,
+        "<html><body>4 ranks</body></html>"
+    );
+    Ok(())
+}
 
-    let combined_events_path = out_dir.join("chromium_events.json");
-    assert!(combined_events_path.exists());
-
-    let events_content = fs::read_to_string(combined_events_path)?;
-    let events: Vec<serde_json::Value> = serde_json::from_str(&events_content)?;
-    assert!(!events.is_empty());
+#[test]
+fn test_rendering_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tlparse"));
+    cmd.arg("tests/inputs/simple.log")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--report-model")
+        .arg("--no-browser");
+    cmd.assert().success();
+    assert!(!out_dir.join("rendering_errors.html").exists());
+
+    // A template referring to a field its context doesn't have fails that page only
+    let template_dir = temp_dir.path().join("templates");
+    fs::create_dir(&template_dir)?;
+    fs::write(
+        template_dir.join("failures_and_restarts.html"),
+        "<html><body>{no_such_field}</body></html>",
+    )?;
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tlparse"));
+    cmd.arg("rerender")
+        .arg(&out_dir)
+        .arg("--template-dir")
+        .arg(&template_dir);
+    cmd.assert()
+        .success()
+        .stderr(str::contains("Failed to render failures_and_restarts.html"));
+    let page = fs::read_to_string(out_dir.join("failures_and_restarts.html"))?;
+    assert!(page.contains("<h1>Couldn't render this page</h1>"));
+    assert!(page.contains("no_such_field"));
+    // The context is shown, with the built-in stylesheet by name
+    assert!(page.contains(
+        r#""css": {
+    "$static": "TEMPLATE_FAILURES_CSS"
+  }"#
+    ));
+    assert!(page.contains("Available values at this level are 'css', 'failures', 'qps'"));
+    let errors = fs::read_to_string(out_dir.join("rendering_errors.html"))?;
+    assert!(errors.contains(
+        "<a href=\"failures_and_restarts.html\">failures_and_restarts.html</a> </td> <td> <code>failures_and_restarts.html</code>"
+    ));
+    let index = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(index.contains(
+        "1 page failed to render; see <a href=\"rendering_errors.html\">rendering errors</a>."
+    ));
+    Ok(())
+}
 
-    // collect all unique process IDs (ranks) from the events
-    let pids: std::collections::HashSet<u64> = events
+#[test]
+fn test_size_badges() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let output = tlparse::parse_path(&path, &config).unwrap();
+    let graph = output
         .iter()
-        .filter_map(|event| event.get("pid").and_then(|v| v.as_u64()))
-        .collect();
-
-    let expected_pids: std::collections::HashSet<u64> = [0, 2, 3].iter().cloned().collect();
-    assert_eq!(pids, expected_pids);
-
-    // verify each rank-specific chromium_events.json file
-    for rank in 0u64..=3 {
-        let rank_events_path = out_dir.join(format!("rank_{}/chromium_events.json", rank));
-        assert!(rank_events_path.exists());
-        let rank_events_content = fs::read_to_string(&rank_events_path)?;
-        let rank_events: Vec<serde_json::Value> = serde_json::from_str(&rank_events_content)?;
-
-        if expected_pids.contains(&(rank as u64)) {
-            assert!(!rank_events.is_empty());
-            let combined_for_rank: Vec<&serde_json::Value> = events
-                .iter()
-                .filter(|ev| ev.get("pid").and_then(|v| v.as_u64()) == Some(rank as u64))
-                .collect();
-            assert_eq!(rank_events.len(), combined_for_rank.len());
-        } else {
-            assert!(rank_events.is_empty());
-        }
-    }
-
-    let landing_page_path = out_dir.join("index.html");
-    assert!(landing_page_path.exists());
-    let landing_content = fs::read_to_string(landing_page_path)?;
+        .find(|(p, _)| p.to_string_lossy().contains("aotautograd_cache_hash"))
+        .unwrap();
+    let map: HashMap<PathBuf, String> = output.clone().into_iter().collect();
+    let badge = format!(
+        r#"<span class="size-badge ">{:.1} KiB</span>"#,
+        graph.1.len() as f64 / 1024.0
+    );
+    assert!(map[&PathBuf::from("index.html")].contains(&badge));
+    let metrics = map
+        .iter()
+        .find(|(p, _)| p.to_string_lossy().contains("compilation_metrics"))
+        .unwrap();
+    assert!(metrics.1.contains(&badge));
 
-    for i in 0..4 {
-        assert!(landing_content.contains(&format!("rank_{}", i)));
+    // Large artifacts are color-graded
+    let temp_dir = tempdir().unwrap();
+    let big = temp_dir.path().join("big.log");
+    let mut log = String::from(concat!(
+        "V1206 15:18:21.460000 1500233 torch/_dynamo/convert_frame.py:1000] ",
+        r#"{"artifact": {"name": "big_dump", "encoding": "string"}, "frame_id": 0, "frame_compile_id": 0, "attempt": 0, "has_payload": "00000000000000000000000000000000"}"#,
+        "\n"
+    ));
+    for _ in 0..(20 << 10) {
+        log.push('\t');
+        log.push_str(&"x".repeat(1023));
+        log.push('\n');
     }
-
-    assert!(landing_content.contains("chromium_events.json"));
-
-    Ok(())
+    fs::write(&big, log).unwrap();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&big, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(map[&PathBuf::from("index.html")]
+        .contains(r#"<span class="size-badge size-hot">20.0 MiB</span>"#));
 }
 
-// Detect diverging compile-ID sets: should raise warning.
 #[test]
-fn test_diverging_compile_ids_warning() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
+fn test_windows_safe_filenames() {
     let temp_dir = tempdir().unwrap();
-    let out_dir = temp_dir.path();
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(out_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
+    let log_path = temp_dir.path().join("names.log");
+    let mut log = String::new();
+    for env in [
+        r#"{"artifact": {"name": "cache:key*lookup?", "encoding": "string"}, "frame_id": 0, "frame_compile_id": 0, "attempt": 0, "has_payload": "00000000000000000000000000000000"}"#,
+        r#"{"artifact": {"name": "aux", "encoding": "string"}, "frame_id": 0, "frame_compile_id": 0, "attempt": 0, "has_payload": "00000000000000000000000000000000"}"#,
+        r#"{"dump_file": {"name": "C:\\tmp\\kernel.py"}, "has_payload": "00000000000000000000000000000000"}"#,
+    ] {
+        log.push_str("V1206 15:18:21.460000 1500233 torch/_dynamo/convert_frame.py:1000] ");
+        log.push_str(env);
+        log.push_str("\n\tx = 1\n");
+    }
+    fs::write(&log_path, log).unwrap();
+    let config = tlparse::ParseConfig::default();
+    let output = tlparse::parse_path(&log_path, &config).unwrap();
+    let paths: Vec<String> = output
+        .iter()
+        .map(|(p, _)| p.to_string_lossy().into_owned())
+        .collect();
+    assert!(paths.contains(&"-_0_0_0/cache_key_lookup__0.txt".to_string()));
+    assert!(paths.contains(&"-_0_0_0/_aux_1.txt".to_string()));
+    assert!(paths.contains(&"dump_file/C__tmp_kernel.py.html".to_string()));
+    let index = &output
+        .iter()
+        .find(|(p, _)| p == Path::new("index.html"))
+        .unwrap()
+        .1;
+    assert!(index.contains(r#"<a href="-_0_0_0/cache_key_lookup__0.txt">"#));
 
-    let landing_page = out_dir.join("index.html");
+    // Names too long for the filesystem are shortened
+    assert_eq!(
+        tlparse::paths::sanitize_filename(&"a".repeat(300)).len(),
+        200
+    );
     assert!(
-        landing_page.exists(),
-        "Expected {} to exist",
-        landing_page.display()
+        tlparse::paths::sanitize_filename(&format!("{}.txt", "b".repeat(300))).ends_with(".txt")
     );
-    let landing_content = fs::read_to_string(&landing_page)?;
+}
+
+#[test]
+fn test_compile_explanation() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let metrics = &map[&PathBuf::from("-_0_0_0/compilation_metrics_9.html")];
+    assert!(metrics.contains(
+        "<p class=\"explanation\">Frame <code>forward</code> (test_torchinductor.py:11123) was compiled."
+    ));
     assert!(
-        landing_content.contains("Diverging Compilation IDs detected"),
-        "Expected divergence warning to be present"
+        metrics.contains("The captured graph has 3 ops on 1 input and is protected by 49 guards.")
     );
+    assert!(metrics.contains("Missed the FX graph cache (1 miss), so it compiled from scratch."));
 
-    Ok(())
+    // Graph breaks are grouped by reason and location
+    let path = Path::new("tests/inputs/sources.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("summary.json")]).unwrap();
+    assert_eq!(
+        summary["compiles"][0]["explanation"],
+        "Frame `forward` (model.py:20) was compiled. 1 graph break: Unsupported: call_function print at `model.py:25`."
+    );
+    assert!(summary["compiles"][1]["explanation"]
+        .as_str()
+        .unwrap()
+        .contains("failed to compile with Unsupported"));
 }
 
-// Two ranks with identical logs, no divergence warning
 #[test]
-fn test_no_compile_id_divergence() -> Result<(), Box<dyn std::error::Error>> {
-    // Create temp input dir with identical logs for rank 0 and 1
-    let temp_in = tempdir()?;
-    let src_log = PathBuf::from("tests/inputs/simple.log");
+fn test_guard_diff_between_recompiles() {
+    let path = Path::new("tests/inputs/guard_diff.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let first = &map[&PathBuf::from("-_0_0_0/compilation_metrics_1.html")];
+    assert!(!first.contains("Guards changed since"));
+    let recompile = &map[&PathBuf::from("-_0_1_0/compilation_metrics_3.html")];
+    assert!(recompile.contains("<h2>Guards changed since [0/0]</h2>"));
+    assert!(recompile.contains("2 added, 2 removed, 2 unchanged"));
+    assert!(recompile.contains(
+        "<li class=\"guard-added\">+ <code>DICT_LENGTH: len(L[&#39;cfg&#39;]) == 2</code></li>"
+    ));
+    assert!(recompile.contains(
+        "<li class=\"guard-removed\">- <code>ID_MATCH: ___check_obj_id(G[&#39;flag&#39;], 1234)</code></li>"
+    ));
+    // Trailing source comments are not part of the guard
+    assert!(!recompile.contains("init_ambient_guards"));
+}
 
-    for rank in 0..=1 {
-        let dest = temp_in
-            .path()
-            .join(format!("dedicated_log_torch_trace_rank_{}.log", rank));
-        fs::copy(&src_log, dest)?;
-    }
+#[test]
+fn test_browse_listing() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    tlparse::paths::write_output(&out_dir, tlparse::parse_path(&path, &config)?)?;
+
+    let root = tlparse::browse::render_listing(&out_dir, Path::new(""), "")?.unwrap();
+    assert!(root.contains("<a href=\"/browse/\">out</a> /"));
+    assert!(root.contains("<a href=\"/browse/-_0_0_0/\">-_0_0_0/</a>"));
+    assert!(root.contains("<a href=\"/raw.jsonl\">raw.jsonl</a>"));
+
+    let compile = tlparse::browse::render_listing(&out_dir, Path::new("-_0_0_0"), "")?.unwrap();
+    assert!(compile.contains("<a href=\"/browse/-_0_0_0/\">-_0_0_0</a> /"));
+    assert!(compile.contains(
+        "<a href=\"/-_0_0_0/dynamo_cpp_guards_str_8.txt\">dynamo_cpp_guards_str_8.txt</a> </td> <td> dynamo_cpp_guards_str </td>"
+    ));
+    assert!(compile.contains("<td> page </td>"));
 
-    let temp_out = tempdir()?;
+    // Only directories of the report are listed
+    assert!(tlparse::browse::render_listing(&out_dir, Path::new("index.html"), "")?.is_none());
+    assert!(tlparse::browse::render_listing(&out_dir, Path::new(".."), "")?.is_none());
+    Ok(())
+}
 
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(temp_in.path())
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(temp_out.path())
-        .arg("--no-browser");
-    cmd.assert().success();
+#[test]
+fn test_sampling() {
+    let path = Path::new("tests/inputs/sampling.log").to_path_buf();
+    let parse = |rate: Option<f64>, max_compiles: Option<usize>| -> HashMap<PathBuf, String> {
+        let config = tlparse::ParseConfig {
+            strict: true,
+            sampling: Some(tlparse::sampling::SamplingConfig { rate, max_compiles }),
+            ..Default::default()
+        };
+        tlparse::parse_path(&path, &config)
+            .unwrap()
+            .into_iter()
+            .collect()
+    };
+    let compile_dirs = |map: &HashMap<PathBuf, String>| -> Vec<String> {
+        let mut dirs: Vec<String> = map
+            .keys()
+            .filter_map(|p| p.to_str()?.strip_prefix("-_")?.split_once('/'))
+            .map(|(dir, _)| dir.to_string())
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+        dirs
+    };
 
-    let landing_page = temp_out.path().join("index.html");
-    assert!(
-        landing_page.exists(),
-        "Expected {} to exist",
-        landing_page.display()
+    // Failed compiles (frames 3 and 17), then the slowest ones (frames 20-29)
+    let map = parse(None, Some(12));
+    let mut expected: Vec<String> = [3, 17]
+        .into_iter()
+        .chain(20..30)
+        .map(|frame| format!("{frame}_0_0"))
+        .collect();
+    expected.sort();
+    assert_eq!(compile_dirs(&map), expected);
+    assert!(map[&PathBuf::from("index.html")].contains(
+        "Sampled report: 12 of 30 compiles were parsed, including all 2 failed compiles"
+    ));
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("summary.json")]).unwrap();
+    assert_eq!(summary["sampling"]["total_compiles"], 30);
+    assert_eq!(summary["compiles"].as_array().unwrap().len(), 12);
+
+    // A sample rate keeps those and a deterministic subset of the rest
+    let sampled = compile_dirs(&parse(Some(0.3), None));
+    assert!(sampled.len() > 12 && sampled.len() < 30);
+    assert!(expected.iter().all(|dir| sampled.contains(dir)));
+    assert_eq!(sampled, compile_dirs(&parse(Some(0.3), None)));
+
+    // Without sampling there is no notice
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map[&PathBuf::from("index.html")].contains("Sampled report"));
+    assert!(!map[&PathBuf::from("summary.json")].contains("\"sampling\""));
+}
+
+#[test]
+fn test_tail() {
+    let path = Path::new("tests/inputs/sources.log");
+    let tail = |compile_id: Option<&str>, lines: usize| -> String {
+        let options = tlparse::tail::TailOptions {
+            follow: false,
+            compile_id: compile_id.map(|c| tlparse::tail::CompileFilter::parse(c).unwrap()),
+            lines,
+        };
+        let mut out = Vec::new();
+        tlparse::tail::tail(path, &options, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    assert_eq!(
+        tail(None, 2),
+        "15:00:07 [1/0] dynamo_start  forward (model.py:21)\n\
+         15:00:08 [1/0] compilation_metrics  forward, failed with Unsupported: call_function print\n"
     );
-    let landing_content = fs::read_to_string(&landing_page)?;
+    let first = tail(Some("0/0"), 100);
+    assert!(first.starts_with("15:00:03 [0/0] dynamo_start  forward (model.py:20)\n"));
+    assert!(first.contains("[0/0] artifact  dynamo_graph_break_reason (+payload)\n"));
+    assert!(!first.contains("[1/0]"));
+    assert_eq!(tail(Some("[0/0_1]"), 100), "");
+    assert!(tlparse::tail::CompileFilter::parse("seven").is_err());
+}
+
+#[test]
+fn test_cat() {
+    let cat = |log: &str, compile_id: Option<&str>, artifact: &str, nth: usize| {
+        let options = tlparse::cat::CatOptions {
+            compile_id: compile_id.map(|c| tlparse::tail::CompileFilter::parse(c).unwrap()),
+            artifact: artifact.to_string(),
+            nth,
+        };
+        let mut out = Vec::new();
+        tlparse::cat::cat(Path::new(log), &options, &mut out)
+            .map(|_| String::from_utf8(out).unwrap())
+    };
+    let graph = cat(
+        "tests/inputs/comp_metrics.log",
+        Some("0/0"),
+        "dynamo_output_graph",
+        1,
+    )
+    .unwrap();
+    assert!(graph.starts_with("class GraphModule(torch.nn.Module):\n    def forward("));
+    assert!(!graph.contains("\n\t"));
+    // JSON payloads are pretty-printed; artifacts are found by name
+    let schedule = cat(
+        "tests/inputs/multi_rank_schedule/dedicated_log_torch_trace_rank_2.log",
+        Some("1/0"),
+        "inductor_collective_schedule",
+        1,
+    )
+    .unwrap();
+    assert_eq!(
+        schedule,
+        "[\n  \"torch.ops._c10d_functional.all_reduce_.default\",\n  \"torch.ops._c10d_functional.wait_tensor.default\"\n]\n"
+    );
+    let err = cat("tests/inputs/comp_metrics.log", Some("0/0"), "nope", 1).unwrap_err();
+    assert!(err
+        .to_string()
+        .ends_with("its payloads are: dynamo_output_graph, dynamo_guards"));
+    assert!(cat(
+        "tests/inputs/comp_metrics.log",
+        Some("0/0"),
+        "dynamo_output_graph",
+        2
+    )
+    .is_err());
+}
+
+#[test]
+fn test_parse_stream() -> Result<(), Box<dyn std::error::Error>> {
+    // The log of the first two compiles, then the rest of it appended while following
+    let log = std::fs::read_to_string("tests/inputs/comp_metrics.log")?;
+    let split = log.match_indices('\n').nth(47).unwrap().0 + 1;
+    let temp_dir = tempdir()?;
+    let path = temp_dir.path().join("growing.log");
+    fs::write(&path, &log[..split])?;
+
+    let mut updates = Vec::new();
+    tlparse::parse_stream(&path, &tlparse::ParseConfig::default(), |update| {
+        if updates.is_empty() {
+            let mut file = fs::OpenOptions::new().append(true).open(&path)?;
+            std::io::Write::write_all(&mut file, &log.as_bytes()[split..])?;
+        }
+        updates.push(update);
+        Ok(updates.len() < 2)
+    })?;
+
+    let (first, second) = (&updates[0], &updates[1]);
+    assert_eq!(first.changed.len(), first.output.len());
+    assert_eq!(second.log_bytes, log.len() as u64);
+    let changed: Vec<String> = second
+        .changed
+        .iter()
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect();
+    assert!(changed.contains(&"index.html".to_string()));
+    assert!(changed.iter().any(|p| p.starts_with("-_2_0_0/")));
+    // The first compile's files are untouched
+    assert!(!changed.iter().any(|p| p.starts_with("-_0_0_1/")));
+    assert!(second.output.iter().any(|(p, _)| p.starts_with("-_0_0_1")));
+    assert_eq!(second.changed_files().count(), second.changed.len());
+    Ok(())
+}
+
+#[test]
+fn test_selftest() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out = temp_dir.path().join("out");
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .args(["tests/inputs/comp_failure.log", "--no-browser", "-o"])
+        .arg(&out)
+        .status()?;
+    assert!(status.success());
+    let report = tlparse::selftest::selftest(&out)?;
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert!(report.pages > 0 && report.links > 0 && report.json_files > 0);
+
+    fs::remove_file(out.join("-_0_0_0/dynamo_output_graph_0.txt"))?;
+    fs::write(out.join("summary.json"), "{")?;
+    let report = tlparse::selftest::selftest(&out)?;
+    let problems = report.problems.join("\n");
+    assert!(problems.contains("index.html: broken link -_0_0_0/dynamo_output_graph_0.txt"));
     assert!(
-        !landing_content.contains("Diverging Compilation IDs detected"),
-        "Did not expect divergence warning for identical logs"
+        problems.contains("-_0_0_0/dynamo_output_graph_0.txt: listed in the manifest but missing")
     );
+    assert!(problems.contains("summary.json: invalid JSON"));
+    assert!(problems.contains("summary.json: 1 bytes, the manifest says"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("selftest")
+        .arg(&out)
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("problems"));
+    Ok(())
+}
 
+#[test]
+fn test_mtime_from_log() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out = temp_dir.path().join("out");
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .args([
+            "tests/inputs/comp_failure.log",
+            "--no-browser",
+            "--mtime-from-log",
+            "-o",
+        ])
+        .arg(&out)
+        .status()?;
+    assert!(status.success());
+    let manifest = tlparse::manifest::Manifest::load(&out)?;
+    let log_time = manifest
+        .log_time("-_0_0_0/dynamo_output_graph_0.txt")
+        .expect("artifact without a log_time");
+    assert!(log_time.ends_with("-12-06T15:20:14.084000Z"), "{log_time}");
+    assert_eq!(manifest.log_time("index.html"), None);
+
+    let mtime: chrono::DateTime<chrono::Utc> =
+        fs::metadata(out.join("-_0_0_0/dynamo_output_graph_0.txt"))?
+            .modified()?
+            .into();
+    assert_eq!(mtime, chrono::DateTime::parse_from_rfc3339(&log_time)?);
     Ok(())
 }
 
-// Detect diverging cache hit/miss patterns: should raise warning
 #[test]
-fn test_diverging_cache_events_warning() -> Result<(), Box<dyn std::error::Error>> {
-    // Create temp input dir with different logs for rank 0 and 1
-    let temp_in = tempdir()?;
-    let src_log_hits = PathBuf::from("tests/inputs/cache_hit_miss.log");
-    let src_log_no_hits = PathBuf::from("tests/inputs/simple.log");
+fn test_report_json() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out = temp_dir.path().join("json_only");
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .args(["tests/inputs/comp_failure.log", "--format", "json", "-o"])
+        .arg(&out)
+        .status()?;
+    assert!(status.success());
+    assert!(!out.join("index.html").exists());
+    assert!(!out.join("-_0_0_0/compilation_metrics_2.html").exists());
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out.join("report.json"))?)?;
+    assert_eq!(report["num_failures"], 1);
+    let compile = &report["compiles"][0];
+    assert_eq!(compile["compile_id"], "[0/0]");
+    assert_eq!(compile["failure"]["fail_type"], "BackendCompilerFailed");
+    assert_eq!(compile["metrics"]["co_name"], "fn");
+    // Only the artifacts that were written
+    assert_eq!(
+        compile["artifacts"],
+        serde_json::json!([
+            "-_0_0_0/dynamo_output_graph_0.txt",
+            "-_0_0_0/dynamo_error_1.txt"
+        ])
+    );
 
-    fs::copy(
-        &src_log_hits,
-        temp_in.path().join("dedicated_log_torch_trace_rank_0.log"),
-    )?;
-    fs::copy(
-        &src_log_no_hits,
-        temp_in.path().join("dedicated_log_torch_trace_rank_1.log"),
-    )?;
+    // Both, and the restart reasons of each compile
+    let out = temp_dir.path().join("both");
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .args([
+            "tests/inputs/comp_metrics.log",
+            "--format",
+            "html,json",
+            "-o",
+        ])
+        .arg(&out)
+        .status()?;
+    assert!(status.success());
+    assert!(out.join("index.html").exists());
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out.join("report.json"))?)?;
+    assert_eq!(report["num_compiles"], 3);
+    assert_eq!(report["num_restarts"], 2);
+    assert!(report["compiles"][1]["artifacts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|a| a.as_str().unwrap().ends_with(".html")));
+    assert!(report["compiles"][1]["restart_reasons"][0]
+        .as_str()
+        .unwrap()
+        .contains("graph_break"));
+    Ok(())
+}
 
-    let temp_out = tempdir()?;
+#[test]
+fn test_data_dependent_ops() {
+    let path = Path::new("tests/inputs/data_dependent.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("This run has 2 unbacked symbols, 1 specialized data-dependent expression, 1 data-dependent graph break and 1 data-dependent compile failure."));
+    let page = &map[&PathBuf::from("data_dependent.html")];
+    let rows: Vec<&str> = page.split("<tr").skip(2).collect();
+    assert_eq!(rows.len(), 5);
+    // The guard on `u0` is deferred to runtime; the one on the backed `s0` isn't listed
+    assert!(rows[0].contains("<code>u0</code> ([-int_oo, int_oo])"));
+    assert!(rows[0].contains("n = x.item()"));
+    assert!(rows[0].contains("1 runtime assert<ul><li><code>u0 &gt;= 0</code></li></ul>"));
+    assert!(rows[0].contains("User Stack"));
+    assert!(rows[1].starts_with(" class=\"data-dependent-problem\""));
+    assert!(rows[1].contains("<code>Data-dependent branching</code>"));
+    assert!(rows[1].contains("model.py:31"));
+    // The failed guard names the unbacked symbol that caused it
+    assert!(rows[2].contains("<code>u1</code>") && rows[2].contains("compile failed"));
+    assert!(rows[3].contains("(GuardOnDataDependentSymNode)"));
+    assert!(rows[4].contains("<code>Eq(u2, 3)</code> (True)"));
+    assert!(rows[4].contains("specialized, runtime assert"));
+    // `torch._dynamo.graph_break()` isn't data-dependent
+    assert!(!page.contains("graph_break()"));
 
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(temp_in.path())
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(temp_out.path())
-        .arg("--no-browser");
-    cmd.assert().success();
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("data_dependent.html")));
+}
+#[test]
+fn test_recompiles_page() {
+    let path = Path::new("tests/inputs/suggested_config.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(
+        index.contains("2 of 2 frames recompiled, 9 times in all; [1/*] (step) compiled 8 times.")
+    );
+    let page = &map[&PathBuf::from("recompiles.html")];
+    // Frames with the most compiles first, each recompile under the original compile
+    let step = page
+        .find("<h2>[1/*] <code>step</code> model.py:40: 8 compiles</h2>")
+        .unwrap();
+    let forward = page
+        .find("<h2>[0/*] <code>forward</code> model.py:20: 3 compiles</h2>")
+        .unwrap();
+    assert!(step < forward);
+    assert_eq!(page[step..forward].matches("recompiled").count(), 7);
+    assert!(page.contains(concat!(
+        r#"<li><a href="index.html#[0/1]">[0/1]</a> recompiled"#,
+        "\n    because\n    <ul><li><code>tensor &#39;L[&#39;x&#39;]&#39; size mismatch at index 0. expected 8, actual 16</code></li>"
+    )));
+    assert!(page.contains("<li>1 &times; <code>tensor &#39;L[&#39;y&#39;]&#39; size mismatch at index 1. expected 4, actual 5</code></li>"));
+
+    // Without recompile reasons, the guards the previous compile had and the recompile lost
+    let log = fs::read_to_string("tests/inputs/diff_compiles_old.log").unwrap();
+    let log: Vec<&str> = log
+        .lines()
+        .filter(|l| !l.contains("recompile_reasons") && !l.contains("size mismatch"))
+        .collect();
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("no_reasons.log");
+    fs::write(&path, log.join("\n")).unwrap();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    let page = &map[&PathBuf::from("recompiles.html")];
+    assert!(page.contains("without logging why; the guards of [0/0] it doesn't have, one of which failed:\n    <ul><li><code>TENSOR_MATCH: check_tensor(L[&#39;x&#39;], Tensor, torch.float32, size=[8], stride=[1])</code></li></ul>"));
 
-    let landing_page = temp_out.path().join("index.html");
-    let landing_content = fs::read_to_string(&landing_page)?;
-    assert!(landing_content.contains("Diverging Cache hit/miss patterns detected"));
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("recompiles.html")));
+}
 
-    Ok(())
+#[test]
+fn test_directory_indexes() {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    let page = &map[&PathBuf::from("-_0_0_1/index.html")];
+    assert!(page.contains(
+        r#"<p>The outputs of compile <a href="../index.html#[0/0_1]">[0/0_1]</a>.  Back to the <a href="../index.html">report</a>.</p>"#
+    ));
+    assert!(page.contains(r#"<tr> <td> <a href="dynamo_output_graph_0.txt">dynamo_output_graph_0.txt</a> </td> <td> FX graph Dynamo captured </td> <td> <span class="size-badge ">268 B</span> </td> </tr>"#));
+    // Every file of the directory is listed
+    let files = map
+        .keys()
+        .filter(|p| p.parent() == Some(Path::new("-_0_0_1")))
+        .count();
+    assert_eq!(page.matches("<tr> <td>").count(), files - 1);
+    assert!(map[&PathBuf::from("payloads/index.html")].contains("Raw payload from the log"));
 }
 
-// Two ranks with identical cache logs, no divergence warning
 #[test]
-fn test_no_cache_event_divergence() -> Result<(), Box<dyn std::error::Error>> {
-    // Create temp input dir with identical logs for rank 0 and 1
-    let temp_in = tempdir()?;
-    let src_log = PathBuf::from("tests/inputs/cache_hit_miss.log");
+fn test_graph_breaks_page() {
+    // The break of sources.log, hit again by a recompile and, from another caller, by another
+    // frame
+    let log = fs::read_to_string("tests/inputs/sources.log").unwrap();
+    let start = log
+        .find(r#"{"artifact": {"name": "dynamo_graph_break_reason""#)
+        .unwrap();
+    let start = log[..start].rfind('\n').unwrap() + 1;
+    let end = start + log[start..].find("\nV").unwrap() + 1;
+    let graph_break = &log[start..end];
+    let mut log = log.clone();
+    log.push_str(&graph_break.replace(r#""frame_compile_id": 0"#, r#""frame_compile_id": 1"#));
+    log.push_str(
+        &graph_break
+            .replace(r#""frame_id": 0"#, r#""frame_id": 1"#)
+            .replace("line 7, in helper", "line 9, in helper"),
+    );
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("graph_breaks.log");
+    fs::write(&path, log).unwrap();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("3 graph breaks, 2 of them unique; the most frequent, Unsupported: call_function print (model.py:25), was hit 2 times."));
+    let page = &map[&PathBuf::from("graph_breaks.html")];
+    let rows: Vec<&str> = page.split("<tr>").skip(2).collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0]
+        .starts_with(" <td> 2 </td> <td> <code>Unsupported: call_function print</code> </td>"));
+    assert!(rows[0].contains(r#"<a href="index.html#[0/0]">[0/0]</a> </td>"#));
+    assert!(rows[0].contains(r#"<td> <a href="index.html#[0/1]">[0/1]</a> </td>"#));
+    assert!(rows[0].contains("line 7, in helper"));
+    assert!(rows[1].starts_with(" <td> 1 </td>"));
+    assert!(rows[1].contains("line 9, in helper"));
+    assert!(rows[1].contains(r#"<a href="index.html#[1/0]">[1/0]</a> </td>"#));
 
-    for rank in 0..=1 {
-        let dest = temp_in
-            .path()
-            .join(format!("dedicated_log_torch_trace_rank_{}.log", rank));
-        fs::copy(&src_log, dest)?;
-    }
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("graph_breaks.html")));
+}
 
-    let temp_out = tempdir()?;
+#[test]
+fn test_compile_activity() -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(
+        "When compiles started over the run: 3 compiles over 0.0 s; 2 started in the second half of the run."
+    ));
+    assert!(index.contains("<svg class='compile-activity'"));
+    let activity: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("compile_activity.json")])?;
+    assert_eq!(activity["compiles"].as_array().unwrap().len(), 3);
+    assert_eq!(activity["compiles"][0][0], "[0/0]");
 
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(temp_in.path())
+    // Every rank against the span of the whole run on the landing page
+    let temp_dir = tempdir()?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/multi_rank_schedule")
         .arg("--all-ranks-html")
-        .arg("--overwrite")
         .arg("-o")
-        .arg(temp_out.path())
-        .arg("--no-browser");
-    cmd.assert().success();
-
-    let landing_page = temp_out.path().join("index.html");
-    let landing_content = fs::read_to_string(&landing_page)?;
-    assert!(!landing_content.contains("Diverging Cache hit/miss patterns detected"));
-
+        .arg(temp_dir.path())
+        .arg("--overwrite")
+        .arg("--no-browser")
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    let landing = fs::read_to_string(temp_dir.path().join("index.html"))?;
+    let section = landing
+        .split("<h3>Compile Activity</h3>")
+        .nth(1)
+        .expect("compile activity section");
+    let section = section.split("</table>").next().unwrap();
+    for rank in 0..=2 {
+        assert!(section.contains(&format!(
+            r#"<a href="rank_{rank}/index.html">Rank {rank}</a>"#
+        )));
+    }
+    assert_eq!(section.matches("<svg class='compile-activity'").count(), 3);
     Ok(())
 }
 
-// Test diverging cache hit/miss patterns using the existing multi_rank_logs directory should create > 2 groups
 #[test]
-fn test_diverging_cache_events_multiple_groups() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_logs");
-    let temp_out = tempdir()?;
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(temp_out.path())
-        .arg("--no-browser");
-    cmd.assert().success();
+fn test_graph_pass_diffs() {
+    let path = Path::new("tests/inputs/inductor_provenance_jit_debug_handle_log.txt").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let page_of = |prefix: &str| {
+        map.iter()
+            .find(|(p, _)| p.to_str().unwrap().starts_with(prefix))
+            .map(|(p, c)| (p.to_str().unwrap().to_string(), c))
+            .unwrap()
+    };
+    let (url, post_grad) = page_of("-_0_0_0/post_grad_passes_diff_");
+    assert!(map[&PathBuf::from("index.html")].contains(&format!("href=\"{url}\"")));
+    assert!(post_grad.contains(
+        "from 12 to 14 nodes: 4 added,\n    2 removed, 0 with a new target and 3 with new inputs"
+    ));
+    // The post-grad passes decompose addmm into mm and add
+    assert!(post_grad.contains(
+        "<code>addmm (aten.addmm.default)</code><br> </td> <td> <code>mm_default_1 (aten.mm.default)</code><br><code>add_tensor_1 (aten.add.Tensor)</code><br>"
+    ));
+    assert!(post_grad.contains(
+        r#"<span class="graph-diff-added">+ mm_default_1: &quot;f32[8, 16][16, 1]cuda:0&quot; = torch.ops.aten.mm.default(primals_3, permute)</span>  <span class="graph-diff-note"># replaces addmm</span>"#
+    ));
+    assert!(post_grad.contains(r#"<span class="graph-diff-removed">- addmm: "#));
+    assert!(post_grad.contains("# inputs were (addmm)</span>"));
+    let (_, pre_grad) = page_of("-_0_0_0/pre_grad_passes_diff_");
+    assert!(pre_grad.contains("left all"));
+}
+#[test]
+fn test_fallback_kernels() {
+    let path = Path::new("tests/inputs/fallback_kernels.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let page = &map[&PathBuf::from("fallback_kernels.html")];
+    // Fallbacks first, most called first, with the calls of each compile
+    let baz = page.find("<code>test.baz.default</code> </td> <td> fallback </td> <td> 3 </td>");
+    let sdpa = page.find("<code>aten._scaled_dot_product_efficient_attention.default</code>");
+    let mm = page.find("<code>extern_kernels.mm</code> </td> <td> extern kernel </td>");
+    assert!(baz.is_some() && sdpa.is_some() && mm.is_some());
+    assert!(baz < sdpa && sdpa < mm);
+    assert!(page.contains(
+        "<a href=\"-_0_0_0/inductor_output_code_out0_0.html\">[0/0]</a> (2) <a href=\"-_1_0_0/inductor_output_code_out1_2.html\">[1/0]</a> (1)"
+    ));
+    assert!(page.contains("<code>extern_kernels.addmm</code>"));
+    // Inductor helpers and ops in comments aren't eager kernels
+    assert!(!page.contains("_alloc_from_pool"));
+    assert!(!page.contains("aten.mul"));
+    assert!(map[&PathBuf::from("index.html")]
+        .contains("2 ops fall back to eager and 2 extern kernels are called"));
+
+    // Real output code: the RNG seeds are generated by an eager randint
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(map[&PathBuf::from("fallback_kernels.html")]
+        .contains("<code>aten.randint.low_out</code> </td> <td> fallback </td>"));
 
-    let landing_page = temp_out.path().join("index.html");
-    let landing_content = fs::read_to_string(&landing_page)?;
-    assert!(landing_content.contains("Diverging Cache hit/miss patterns detected"));
+    // Logs without output code have no audit
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("fallback_kernels.html")));
+}
 
-    Ok(())
+#[test]
+fn test_report_metadata() {
+    let path = PathBuf::from("tests/inputs/simple.log");
+    let config = tlparse::ParseConfig {
+        metadata: [("experiment", "llama-sweep"), ("git_sha", "abc123")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config).unwrap();
+    let mut manifest =
+        tlparse::manifest::Manifest::new(&path, &output, std::time::Duration::from_secs(1));
+    manifest.metadata = config.metadata.clone();
+    let map: HashMap<PathBuf, String> = output.into_iter().collect();
+
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(r#"<table class="report-metadata">"#));
+    assert!(index.contains("<th> git_sha </th> <td> abc123 </td>"));
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("summary.json")]).unwrap();
+    assert_eq!(summary["metadata"]["experiment"], "llama-sweep");
+    let manifest_json: serde_json::Value = serde_json::to_value(&manifest).unwrap();
+    assert_eq!(manifest_json["metadata"]["git_sha"], "abc123");
+    assert!(manifest.summary().contains("experiment:   llama-sweep"));
+
+    // Without metadata nothing is added
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map[&PathBuf::from("index.html")].contains(r#"<table class="report-metadata">"#));
+    assert!(!map[&PathBuf::from("summary.json")].contains("\"metadata\""));
 }
 
 #[test]
-fn test_collective_schedule_parsing() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_schedule");
+fn test_output_lock() {
+    use tlparse::lock::{lock_path, OutputLock};
     let temp_dir = tempdir().unwrap();
     let out_dir = temp_dir.path().join("out");
+    assert_eq!(
+        lock_path(&out_dir),
+        temp_dir.path().join("out.tlparse.lock")
+    );
 
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(&out_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
-
-    // Check that collective schedule files are created for each rank
-    for rank in 0..=2 {
-        let rank_dir = out_dir.join(format!("rank_{}", rank));
-        assert!(rank_dir.exists(), "rank_{} directory should exist", rank);
+    // A second run can't lock the directory while the first holds it
+    let lock = OutputLock::acquire(&out_dir, false).unwrap();
+    let err = OutputLock::acquire(&out_dir, false)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("is being written by tlparse"), "{err}");
+    drop(lock);
+    assert!(!lock_path(&out_dir).exists());
+
+    // A lock file left behind by a run that's gone isn't locked, so the next run takes it
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("--version")
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let dead_pid = child.id();
+    child.wait().unwrap();
+    let lock = OutputLock::acquire(&out_dir, false).unwrap();
+    let mut stale: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(lock_path(&out_dir)).unwrap()).unwrap();
+    drop(lock);
+    stale["pid"] = dead_pid.into();
+    fs::write(lock_path(&out_dir), stale.to_string()).unwrap();
+    let lock = OutputLock::acquire(&out_dir, false).unwrap();
+    let info: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(lock_path(&out_dir)).unwrap()).unwrap();
+    assert_eq!(info["pid"], std::process::id());
+
+    // --break-lock takes over a lock that is held
+    let broken = OutputLock::acquire(&out_dir, true).unwrap();
+    drop(lock);
+    assert!(OutputLock::acquire(&out_dir, false).is_err());
+    drop(broken);
+
+    // Output files are renamed into place, leaving no temporary files behind
+    tlparse::paths::write_output(
+        &out_dir,
+        vec![(PathBuf::from("a/b.txt"), "contents".to_string())],
+    )
+    .unwrap();
+    assert_eq!(
+        fs::read_to_string(out_dir.join("a/b.txt")).unwrap(),
+        "contents"
+    );
+    assert_eq!(fs::read_dir(out_dir.join("a")).unwrap().count(), 1);
+}
 
-        let index_file = rank_dir.join("index.html");
-        assert!(index_file.exists(), "rank_{} index.html should exist", rank);
+#[test]
+fn test_artifacts_across_ranks() {
+    let temp_dir = tempdir().unwrap();
+    let out_dir = temp_dir.path().to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let ranks: Vec<String> = (0..4).map(|r| r.to_string()).collect();
+    for rank in &ranks {
+        let log = PathBuf::from(format!(
+            "tests/inputs/rank_artifacts/dedicated_log_torch_trace_rank_{rank}.log"
+        ));
+        let output = tlparse::parse_path(&log, &config).unwrap();
+        tlparse::paths::write_output(&out_dir.join(format!("rank_{rank}")), output).unwrap();
     }
+    let ctx = tlparse::MultiRankContext {
+        css: "",
+        custom_header_html: "",
+        num_ranks: ranks.len(),
+        ranks,
+        qps: "",
+        has_chromium_events: false,
+        show_desync_warning: false,
+        compile_id_divergence: false,
+        diagnostics: Default::default(),
+    };
+    tlparse::generate_multi_rank_landing(&config, &ctx, &out_dir).unwrap();
 
-    // Check that landing page exists
-    let landing_page = out_dir.join("index.html");
-    assert!(landing_page.exists(), "Landing page should exist");
-
-    // Check collective_schedules.json exists and has correct structure
-    let collective_schedules_file = out_dir.join("collective_schedules.json");
-    assert!(collective_schedules_file.exists());
-
-    let schedules: Vec<serde_json::Value> =
-        serde_json::from_str(&fs::read_to_string(&collective_schedules_file)?)?;
-    assert!(!schedules.is_empty());
+    let landing = fs::read_to_string(out_dir.join("index.html")).unwrap();
+    assert!(landing.contains("Artifacts Across Ranks"));
+    assert!(landing.contains(
+        r#"<a href="across_ranks/-_0_0_0/dynamo_output_graph.html">dynamo_output_graph</a>"#
+    ));
+    assert!(landing.contains(
+        r#"<a href="across_ranks/-_0_0_0/dynamo_cpp_guards_str.html" class="rank-artifact-differs">dynamo_cpp_guards_str</a> (2 variants)"#
+    ));
+    // [0/1] only compiled on rank 3
+    assert!(!landing.contains("across_ranks/-_0_1_0"));
 
-    // Verify ranks 0 and 2 have same ops, rank 1 is different
-    let rank0_ops = schedules
-        .iter()
-        .find(|s| s["rank"] == 0 && s["graph"] == "-_0_0_0")
-        .map(|s| &s["ops"])
-        .unwrap();
-    let rank1_ops = schedules
-        .iter()
-        .find(|s| s["rank"] == 1 && s["graph"] == "-_0_0_0")
-        .map(|s| &s["ops"])
-        .unwrap();
-    let rank2_ops = schedules
-        .iter()
-        .find(|s| s["rank"] == 2 && s["graph"] == "-_0_0_0")
-        .map(|s| &s["ops"])
+    // Rank 2 specialized on another size than the other ranks
+    let page = fs::read_to_string(out_dir.join("across_ranks/-_0_0_0/dynamo_cpp_guards_str.html"))
         .unwrap();
-
-    assert_eq!(rank0_ops, rank2_ops);
-    assert_ne!(rank0_ops, rank1_ops);
-    assert_eq!(rank0_ops.as_array().unwrap().len(), 6);
-    assert_eq!(rank1_ops.as_array().unwrap().len(), 4);
-
-    Ok(())
+    assert!(page.contains("Logged by 4 ranks,"));
+    assert!(page.contains("in 2 distinct versions"));
+    let common = page.find("<h3>Ranks 0, 1, 3 (3)</h3>").unwrap();
+    let odd = page.find("<h3>Ranks 2 (1)</h3>").unwrap();
+    assert!(common < odd);
+    assert!(page[common..odd].contains("size=[8]"));
+    assert!(page[odd..].contains("size=[16]"));
+    assert!(page[odd..].contains(r#"<a href="../../rank_2/-_0_0_0/dynamo_cpp_guards_str_1.txt">"#));
+    // Pages are shown in frames rather than inline
+    let page =
+        fs::read_to_string(out_dir.join("across_ranks/-_0_0_0/compilation_metrics.html")).unwrap();
+    assert!(page.contains(r#"<iframe src="../../rank_0/-_0_0_0/compilation_metrics_2.html">"#));
 }
 
 #[test]
-fn test_collective_schedule_no_divergence() -> Result<(), Box<dyn std::error::Error>> {
+fn test_compilation_metrics_table() {
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(
+        &PathBuf::from("tests/inputs/comp_metrics.log"),
+        &Default::default(),
+    )
+    .unwrap()
+    .into_iter()
+    .collect();
+    let page = &map[&PathBuf::from("-_0_0_1/compilation_metrics_2.html")];
+    assert!(page.contains("<h2>Metrics</h2>"));
+    // Fields are grouped by phase, with durations and timestamps formatted and explained
+    let frame = page.find(r#"class="metrics-phase"> Frame </th>"#).unwrap();
+    let time = page
+        .find(r#"class="metrics-phase"> Compile time </th>"#)
+        .unwrap();
+    let dynamo = page.find(r#"class="metrics-phase"> Dynamo </th>"#).unwrap();
+    assert!(frame < time && time < dynamo);
+    assert!(page.contains(
+        r#"<td> <code>entire_frame_compile_time_s</code> </td> <td title="0.012439489364624023"> 12.4 ms </td> <td class="metrics-doc"> Total time spent in convert_frame"#
+    ));
+    assert!(page.contains("2024-04-03 14:28:48.052 UTC"));
+    assert!(page[dynamo..].contains(r#"<code>guard_count</code> </td> <td> 9 </td>"#));
+    // Null fields are left out of the table but kept in the raw JSON
+    assert!(!page.contains("<code>inductor_compile_time_s</code>"));
+    assert!(page.contains("<summary>Raw JSON</summary>"));
+    assert!(page.contains("&quot;inductor_compile_time_s&quot;: null"));
+
+    // Fields the catalog doesn't know are kept, under Other
     let temp_dir = tempdir().unwrap();
-    let input_dir = temp_dir.path();
+    let log = temp_dir.path().join("new_fields.log");
+    fs::write(
+        &log,
+        r#"V0403 14:28:48.052000 1543231 torch/_dynamo/utils.py:700] {"compilation_metrics": {"co_name": "fn", "gc_time_us": 1500, "brand_new_counter": 3}, "frame_id": 0, "frame_compile_id": 0, "attempt": 0}
+"#,
+    )
+    .unwrap();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&log, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    let page = &map[&PathBuf::from("-_0_0_0/compilation_metrics_0.html")];
+    assert!(page.contains(r#"<code>gc_time_us</code> </td> <td title="1500"> 1.5 ms </td>"#));
+    let other = page.find(r#"class="metrics-phase"> Other </th>"#).unwrap();
+    assert!(page[other..].contains(r#"<code>brand_new_counter</code> </td> <td> 3 </td>"#));
+}
 
-    // Copy identical logs (rank 0 and 2 have same collective schedule)
-    fs::copy(
-        "tests/inputs/multi_rank_schedule/dedicated_log_torch_trace_rank_0_6u3fubwl.log",
-        input_dir.join("dedicated_log_torch_trace_rank_0.log"),
-    )?;
-    fs::copy(
-        "tests/inputs/multi_rank_schedule/dedicated_log_torch_trace_rank_2.log",
-        input_dir.join("dedicated_log_torch_trace_rank_2.log"),
-    )?;
+#[test]
+fn test_suggested_config() {
+    // `forward` recompiles for new sizes of x and y; `step` compiles 8 times
+    let path = Path::new("tests/inputs/suggested_config.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let suggested = &map[&PathBuf::from("suggested_config.py")];
+    assert!(suggested.contains("# Frame `step` (model.py:40) was compiled 8 times"));
+    assert!(suggested.contains("torch._dynamo.config.cache_size_limit = 16\n"));
+    assert!(suggested.contains("def mark_dynamic_forward(x, y):\n"));
+    // From the guard sizes, including the dynamic size after automatic dynamic kicked in
+    assert!(
+        suggested.contains("    torch._dynamo.mark_dynamic(x, 0)  # sizes seen: 8, 16, dynamic\n")
+    );
+    // From the recompile reasons
+    assert!(suggested.contains("    torch._dynamo.mark_dynamic(y, 1)  # sizes seen: 4, 5\n"));
+    // Sizes that never changed aren't suggested
+    assert!(!suggested.contains("mark_dynamic(x, 1)"));
+    assert!(!suggested.contains("_parameters"));
+    assert!(map[&PathBuf::from("index.html")]
+        .contains("suggests a higher cache_size_limit and 2 tensor dims to mark dynamic."));
+
+    // Nothing to suggest for a log without recompiles
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("suggested_config.py")));
+}
 
-    let temp_out_dir = tempdir().unwrap();
-    let out_dir = temp_out_dir.path();
+#[test]
+fn test_serve_auth_and_access_log() {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tlparse::serve::{AccessLog, BasicAuth};
+
+    let auth = BasicAuth::parse("alice:s3cret").unwrap();
+    assert_eq!(auth.user(), "alice");
+    // base64 of alice:s3cret
+    assert!(auth.check(Some("Basic YWxpY2U6czNjcmV0")));
+    assert!(!auth.check(Some("Basic YWxpY2U6d3Jvbmc=")));
+    assert!(!auth.check(None));
+    assert!(BasicAuth::parse("alice").is_err());
+    assert!(BasicAuth::parse("alice:").is_err());
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let buffer = Buffer::default();
+    let log = AccessLog::new(Box::new(buffer.clone()));
+    log.record(
+        Some("10.0.0.5:51234".parse().unwrap()),
+        Some("alice"),
+        "GET /index.html HTTP/1.1",
+        200,
+        Some(5120),
+    );
+    log.record(None, None, "GET / HTTP/1.1", 401, None);
+    let lines = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = lines.lines().collect();
+    assert!(lines[0].starts_with("10.0.0.5 - alice ["));
+    assert!(lines[0].ends_with("] \"GET /index.html HTTP/1.1\" 200 5120"));
+    assert!(lines[1].starts_with("- - - ["));
+    assert!(lines[1].ends_with("] \"GET / HTTP/1.1\" 401 -"));
+}
 
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(out_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
+#[test]
+fn test_deterministic_output_order() -> Result<(), Box<dyn std::error::Error>> {
+    // The same logs, created in opposite orders, must produce the same report
+    let temp_dir = tempdir().unwrap();
+    let mut names: Vec<_> = fs::read_dir("tests/inputs/multi_rank_logs")?
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    names.sort();
+    let mut outputs = Vec::new();
+    for run in ["forward", "reverse"] {
+        let input_dir = temp_dir.path().join(run).join("logs");
+        fs::create_dir_all(&input_dir)?;
+        if run == "reverse" {
+            names.reverse();
+        }
+        for name in &names {
+            fs::copy(
+                Path::new("tests/inputs/multi_rank_logs").join(name),
+                input_dir.join(name),
+            )?;
+        }
+        let out_dir = temp_dir.path().join(run).join("out");
+        let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+            .arg(&input_dir)
+            .arg("--all-ranks-html")
+            .arg("--overwrite")
+            .arg("-o")
+            .arg(&out_dir)
+            .arg("--no-browser")
+            .status()?;
+        assert!(status.success());
+        outputs.push(out_dir);
+    }
 
-    let landing_page = out_dir.join("index.html");
-    assert!(landing_page.exists(), "Landing page should exist");
-    let html_content = fs::read_to_string(&landing_page)?;
+    let mut pending = vec![PathBuf::new()];
+    let mut compared = 0;
+    while let Some(rel) = pending.pop() {
+        for entry in fs::read_dir(outputs[0].join(&rel))? {
+            let entry = entry?;
+            let rel = rel.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(rel);
+                continue;
+            }
+            // Records the log path and when it was generated
+            if entry.file_name() == "manifest.json" {
+                continue;
+            }
+            let first = fs::read(outputs[0].join(&rel))?;
+            let second = fs::read(outputs[1].join(&rel))
+                .map_err(|e| format!("{} missing from second run: {e}", rel.display()))?;
+            assert!(first == second, "{} differs between runs", rel.display());
+            compared += 1;
+        }
+    }
+    assert!(compared > 10, "only compared {compared} files");
+    Ok(())
+}
 
-    // Should NOT have desync warning since ranks 0 and 2 have identical collective schedules
-    assert!(!html_content.contains("Warning:</strong> Diverging collective operation sequences"));
+#[test]
+fn test_all_ranks_jobs() -> Result<(), Box<dyn std::error::Error>> {
+    // Parsing ranks in parallel must produce the same report as one rank at a time
+    let temp_dir = tempdir()?;
+    let mut outputs = Vec::new();
+    for jobs in ["1", "3"] {
+        let out_dir = temp_dir.path().join(format!("jobs_{jobs}"));
+        let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+            .args([
+                "tests/inputs/multi_rank_runtime",
+                "--all-ranks-html",
+                "--no-browser",
+            ])
+            .args(["--jobs", jobs, "-o"])
+            .arg(&out_dir)
+            .status()?;
+        assert!(status.success());
+        outputs.push(out_dir);
+    }
 
+    let mut pending = vec![PathBuf::new()];
+    let mut compared = 0;
+    while let Some(rel) = pending.pop() {
+        for entry in fs::read_dir(outputs[0].join(&rel))? {
+            let entry = entry?;
+            let rel = rel.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(rel);
+                continue;
+            }
+            if entry.file_name() == "manifest.json" {
+                continue;
+            }
+            let sequential = fs::read(outputs[0].join(&rel))?;
+            let parallel = fs::read(outputs[1].join(&rel))
+                .map_err(|e| format!("{} missing with --jobs 3: {e}", rel.display()))?;
+            assert!(
+                sequential == parallel,
+                "{} differs with --jobs 3",
+                rel.display()
+            );
+            compared += 1;
+        }
+    }
+    assert!(compared > 10, "only compared {compared} files");
     Ok(())
 }
 
 #[test]
-fn test_collective_schedule_with_divergence() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_schedule");
-    let temp_dir = tempdir().unwrap();
-    let out_dir = temp_dir.path();
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(out_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
+fn test_hide_framework_frames() {
+    let path = Path::new("tests/inputs/frame_origins.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(
+        r#"value="all" onchange="applyFramePreset(this.value)" checked> All compiles (5)"#
+    ));
+    assert!(index
+        .contains(r#"value="user" onchange="applyFramePreset(this.value)"> User code only (2)"#));
+    assert!(index.contains(r#"<span class="frame-origin">1 in PyTorch</span>"#));
+    assert!(index.contains(r#"<span class="frame-origin">1 in vLLM</span>"#));
+    assert!(index.contains(r#"<span class="frame-origin">1 in Other libraries</span>"#));
+    assert!(index
+        .contains(r#"const frameOrigins = {"[1/0]":"torch","[2/0]":"vllm","[3/0]":"library"};"#));
+    assert!(!index.contains("DOMContentLoaded', () => applyFramePreset('user')"));
+
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("summary.json")]).unwrap();
+    let origins: Vec<&serde_json::Value> = summary["compiles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| &c["frame_origin"])
+        .collect();
+    assert_eq!(
+        origins,
+        [
+            &serde_json::Value::Null,
+            &serde_json::json!("torch"),
+            &serde_json::json!("vllm"),
+            &serde_json::json!("library"),
+            &serde_json::Value::Null
+        ]
+    );
 
-    let landing_page = out_dir.join("index.html");
-    assert!(landing_page.exists(), "Landing page should exist");
-    let html_content = fs::read_to_string(&landing_page)?;
+    let config = tlparse::ParseConfig {
+        hide_framework_frames: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(
+        r#"value="user" onchange="applyFramePreset(this.value)" checked> User code only (2)"#
+    ));
+    assert!(index.contains("DOMContentLoaded', () => applyFramePreset('user')"));
 
-    // Should have desync warning since rank 1 has different collective schedule
-    assert!(html_content.contains("Warning:</strong> Diverging collective operation sequences"));
+    // Without library-rooted compiles there is nothing to filter
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map[&PathBuf::from("index.html")].contains("frame-presets"));
+}
 
-    // Check that ranks 0 and 2 are grouped (same sequence)
-    assert!(html_content.contains("Ranks: 0, 2"));
+#[cfg(feature = "forward")]
+#[test]
+fn test_forward_events() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use tlparse::forward::ForwardTarget;
 
-    // Check that rank 1 separate (different sequence)
-    assert!(html_content.contains("Ranks: 1"));
+    assert_eq!(
+        ForwardTarget::parse("nats://mq.local/tlparse.compiles")?,
+        ForwardTarget::Nats {
+            addr: "mq.local:4222".to_string(),
+            subject: "tlparse.compiles".to_string()
+        }
+    );
+    assert!(ForwardTarget::parse("amqp://mq.local/x").is_err());
+    assert!(ForwardTarget::parse("nats://mq.local").is_err());
+
+    // A NATS server that records what it is sent until the confirming PING
+    let nats = TcpListener::bind("127.0.0.1:0")?;
+    let nats_port = nats.local_addr()?.port();
+    let nats_server = std::thread::spawn(move || {
+        let (mut stream, _) = nats.accept().unwrap();
+        stream
+            .write_all(b"INFO {\"server_id\":\"test\"}\r\n")
+            .unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut received = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "PING\r\n" {
+                stream.write_all(b"PONG\r\n").unwrap();
+                return received;
+            }
+            received.push_str(&line);
+        }
+    });
+    // A Kafka REST Proxy answering one request, failing to write the second record if asked
+    let kafka_proxy = |reject_second: bool| -> std::io::Result<_> {
+        let kafka = TcpListener::bind("127.0.0.1:0")?;
+        let port = kafka.local_addr()?.port();
+        Ok((
+            port,
+            std::thread::spawn(move || {
+                let (mut stream, _) = kafka.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(value) = line.strip_prefix("Content-Length: ") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    if line == "\r\n" {
+                        break;
+                    }
+                    head.push_str(&line);
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+                let records = serde_json::from_str::<serde_json::Value>(&body).unwrap()["records"]
+                    .as_array()
+                    .unwrap()
+                    .len();
+                let offsets: Vec<serde_json::Value> = (0..records)
+                    .map(|i| match i == 1 && reject_second {
+                        true => serde_json::json!({"partition": null, "offset": null,
+                    "error_code": 50002, "error": "Kafka error: record too large"}),
+                        false => serde_json::json!({"partition": 0, "offset": i,
+                    "error_code": null, "error": null}),
+                    })
+                    .collect();
+                let reply = serde_json::json!({"offsets": offsets}).to_string();
+                // Chunked like the proxy's own replies
+                stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{reply}\r\n0\r\n\r\n",
+                    reply.len()
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+                (head, body)
+            }),
+        ))
+    };
+    let (kafka_port, kafka_server) = kafka_proxy(false)?;
 
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/comp_failure.log")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .arg("--metadata")
+        .arg("job=nightly")
+        .arg("--forward")
+        .arg(format!("nats://127.0.0.1:{nats_port}/tlparse.compiles"))
+        .arg("--forward")
+        .arg(format!("kafka://127.0.0.1:{kafka_port}/compile-telemetry"))
+        .env("HOSTNAME", "node7")
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+
+    let received = nats_server.join().unwrap();
+    assert!(received.starts_with("CONNECT {"));
+    let events: Vec<serde_json::Value> = received
+        .lines()
+        .skip(1)
+        .collect::<Vec<_>>()
+        .chunks(2)
+        .map(|pub_and_payload| {
+            assert!(pub_and_payload[0].starts_with("PUB tlparse.compiles "));
+            serde_json::from_str(pub_and_payload[1]).unwrap()
+        })
+        .collect();
+    assert_eq!(events[0]["event"], "run");
+    assert_eq!(events[0]["host"], "node7");
+    assert_eq!(events[0]["metadata"]["job"], "nightly");
+    assert_eq!(events[0]["num_compiles"], events.len() - 1);
+    assert_eq!(events[0]["num_failed_compiles"], 1);
+    assert_eq!(events[1]["event"], "compile");
+    assert_eq!(events[1]["compile_id"], "[0/0]");
+    assert!(events[1]["rank"].is_null());
+    assert!(events[1].get("metrics_url").is_none());
+
+    let (head, body) = kafka_server.join().unwrap();
+    assert!(head.starts_with("POST /topics/compile-telemetry HTTP/1.1"));
+    assert!(head.contains("Content-Type: application/vnd.kafka.json.v2+json"));
+    let body: serde_json::Value = serde_json::from_str(&body)?;
+    let records = body["records"].as_array().unwrap();
+    assert_eq!(records.len(), events.len());
+    assert_eq!(records[1]["value"], events[1]);
+    assert!(String::from_utf8(output.stdout)?.contains(&format!(
+        "Forwarded {} events to nats://127.0.0.1:{nats_port}/tlparse.compiles",
+        events.len()
+    )));
+
+    // A record the proxy couldn't write fails the run despite the 200
+    let (kafka_port, kafka_server) = kafka_proxy(true)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/comp_failure.log")
+        .arg("-o")
+        .arg(temp_dir.path().join("out2"))
+        .arg("--no-browser")
+        .arg("--forward")
+        .arg(format!("kafka://127.0.0.1:{kafka_port}/compile-telemetry"))
+        .output()?;
+    kafka_server.join().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(
+        stderr.contains("failed to write 1 of") && stderr.contains("50002"),
+        "{stderr}"
+    );
     Ok(())
 }
 
 #[test]
-fn test_runtime_estimation_parsing() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
-    let out_dir = input_dir.join("out");
+fn test_compile_code_size() {
+    let path = Path::new("tests/inputs/code_size.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("summary.json")]).unwrap();
+    let compiles = summary["compiles"].as_array().unwrap();
+    assert_eq!(compiles.len(), 4);
+    // The only payloads of these compiles are their output code; the pages don't count
+    for (compile, bytes) in compiles.iter().zip([1038, 1230, 1134, 29378]) {
+        assert_eq!(compile["generated_code_bytes"], bytes);
+        assert_eq!(compile["artifact_bytes"], bytes);
+    }
+    let outliers: Vec<bool> = compiles
+        .iter()
+        .map(|c| c["code_size_outlier"].as_bool().unwrap())
+        .collect();
+    assert_eq!(outliers, [false, false, false, true]);
 
-    Command::cargo_bin("tlparse")?
-        .arg(&input_dir)
-        .args(&["--all-ranks-html", "--overwrite", "-o"])
-        .arg(&out_dir)
-        .arg("--no-browser")
-        .assert()
-        .success();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("<h2> Generated Code Size Outliers </h2>"));
+    assert!(index.contains("<li><a href='#[3/0]'>[3/0]</a>: 28.7 KiB (23.9x the median)</li>"));
+    assert!(index.contains(r#"<a id="[0/0]">[0/0]</a> <span class="size-badge ""#));
+    assert!(index.contains("28.7 KiB code, 28.7 KiB of artifacts, outlier</span>"));
 
-    let estimations: Vec<serde_json::Value> = serde_json::from_str(&fs::read_to_string(
-        out_dir.join("runtime_estimations.json"),
-    )?)?;
+    // Too few compiles generated code to tell what is unusual
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map[&PathBuf::from("index.html")].contains("Generated Code Size Outliers"));
+}
 
-    assert!(!estimations.is_empty());
-    assert!(estimations.iter().any(|e| e["rank"] == 0));
-    assert!(estimations.iter().any(|e| e["rank"] == 1));
+#[test]
+fn test_cache_key_baseline() {
+    let baseline =
+        tlparse::cache_keys::load_baseline(Path::new("tests/inputs/cache_key_baseline.log"))
+            .unwrap();
+    assert_eq!(baseline.keys.lookups.len(), 2);
 
-    // Verify structure
-    for estimation in &estimations {
-        for op in estimation["ops"].as_array().unwrap() {
-            assert!(op["name"].is_string() && op["estimated_runtime_ns"].is_number());
-            assert!(!op.as_object().unwrap().contains_key("type"));
-        }
-    }
+    let path = Path::new("tests/inputs/cache_key_new.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        baseline: Some(baseline),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("Compared with the baseline run, 1 of 3 cache misses changed key."));
 
-    Ok(())
+    let changes = &map[&PathBuf::from("cache_key_changes.html")];
+    assert!(changes.contains(
+        "Changed since the baseline: tensor metadata (1 component), config (1 component)."
+    ));
+    assert!(changes.contains(
+        "<td> config </td> <td> <code>inductor_config[max_autotune]</code> </td> <td> changed </td> <td> <code>False</code> </td> <td> <code>True</code> </td>"
+    ));
+    assert!(changes.contains("shape=torch.Size([16, 8])"));
+    assert!(changes.contains("Same key as the baseline: the entry was missing from the cache"));
+    assert!(changes.contains("The baseline made no such lookup for this compile."));
+    assert!(!changes.contains("<code>gm</code>"));
+
+    // A baseline's cache_keys.json loads the same as its log
+    let dir = tempdir().unwrap();
+    let json_path = dir.path().join("cache_keys.json");
+    fs::write(&json_path, &map[&PathBuf::from("cache_keys.json")]).unwrap();
+    let reloaded = tlparse::cache_keys::load_baseline(&json_path).unwrap();
+    assert_eq!(reloaded.keys.lookups.len(), 3);
+    assert!(tlparse::cache_keys::load_baseline(dir.path()).is_ok());
+
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("cache_key_changes.html")));
 }
 
-fn setup_runtime_test_with_ranks(
-    ranks: &[u32],
-) -> Result<(tempfile::TempDir, tempfile::TempDir), Box<dyn std::error::Error>> {
-    let temp_in = tempdir()?;
-    let temp_out = tempdir()?;
-    let src_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
+#[test]
+fn test_rpc_source_lookup() {
+    let index =
+        tlparse::rpc::ReportIndex::load(Path::new("tests/inputs/suggested_config.log")).unwrap();
+    assert_eq!(index.len(), 11);
+
+    // Nearest enclosing frame, matched by filename suffix
+    let compiles = index.compiles_for_source("model.py", 25);
+    assert!(!compiles.is_empty());
+    assert!(compiles
+        .iter()
+        .all(|c| c.co_firstlineno == Some(20) && c.co_name.as_deref() == Some("forward")));
+    assert!(compiles.iter().any(|c| c.num_guards > 0));
+    assert!(index.compiles_for_source("model.py", 1).is_empty());
+    assert!(index.compiles_for_source("other_model.py", 25).is_empty());
+
+    let body = r#"{"jsonrpc":"2.0","id":1,"method":"getGuardsForSource","params":{"file":"/home/user/model.py","line":25}}"#;
+    let input = format!(
+        "Content-Length: {}\r\n\r\n{body}\
+         {{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"getCompileForSource\",\"params\":[\"user/model.py\",20]}}\n\
+         {{\"jsonrpc\":\"2.0\",\"method\":\"initialized\"}}\n\
+         {{\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"getCompileForSource\",\"params\":{{}}}}\n\
+         {{\"jsonrpc\":\"2.0\",\"method\":\"exit\"}}\n\
+         {{\"jsonrpc\":\"2.0\",\"id\":4,\"method\":\"shutdown\"}}\n",
+        body.len()
+    );
+    let mut output = Vec::new();
+    index
+        .serve(std::io::Cursor::new(input), &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
 
-    for &rank in ranks {
-        let src_file = src_dir.join(format!("dedicated_log_torch_trace_rank_{}.log", rank));
-        let dest_file = temp_in
-            .path()
-            .join(format!("dedicated_log_torch_trace_rank_{}.log", rank));
-        fs::copy(&src_file, &dest_file)?;
-    }
+    let (header, rest) = output.split_once("\r\n\r\n").unwrap();
+    let length: usize = header
+        .strip_prefix("Content-Length: ")
+        .unwrap()
+        .parse()
+        .unwrap();
+    let guards: serde_json::Value = serde_json::from_str(&rest[..length]).unwrap();
+    assert_eq!(guards["id"], 1);
+    let guards = guards["result"].as_array().unwrap();
+    assert_eq!(guards.len(), compiles.len());
+    assert!(guards
+        .iter()
+        .flat_map(|g| g["guards"].as_array().unwrap())
+        .any(|g| g.as_str().unwrap().contains("check_tensor")));
 
-    Ok((temp_in, temp_out))
+    // Line-delimited requests get line-delimited replies; notifications none, and nothing
+    // after exit is answered
+    let replies: Vec<serde_json::Value> = rest[length..]
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(replies.len(), 2);
+    assert_eq!(replies[0]["id"], 2);
+    assert_eq!(
+        replies[0]["result"].as_array().unwrap().len(),
+        compiles.len()
+    );
+    assert_eq!(replies[1]["id"], 3);
+    assert_eq!(replies[1]["error"]["code"], -32602);
 }
 
 #[test]
-fn test_runtime_analysis_working() -> Result<(), Box<dyn std::error::Error>> {
-    let (input_dir, output_dir) = setup_runtime_test_with_ranks(&[0, 1, 2, 3])?;
-
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(input_dir.path())
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(output_dir.path())
-        .arg("--no-browser");
-    cmd.assert().success();
+fn test_vllm_view_autodetect() {
+    // Only a vLLM artifact no vLLM parser handles
+    let path = Path::new("tests/inputs/vllm_detect.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("This is the vLLM compilation view."));
+    assert!(map.contains_key(&PathBuf::from("vllm_summary.json")));
+    let tlparse_index = &map[&PathBuf::from("tlparse_index.html")];
+    assert!(tlparse_index.contains("<a href='index.html'>← Back to the vLLM compilation view</a>"));
 
-    let landing_page = output_dir.path().join("index.html");
-    assert!(landing_page.exists(), "Landing page should exist");
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(!map.contains_key(&PathBuf::from("tlparse_index.html")));
+    assert!(!map[&PathBuf::from("index.html")].contains("vLLM compilation view"));
+}
 
-    let html_content = fs::read_to_string(&landing_page)?;
+#[test]
+fn test_cpp_guards_tree() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        report_model: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    // The raw dump stays, with the tree as its readable page
+    assert!(map.contains_key(&PathBuf::from("-_0_0_0/dynamo_cpp_guards_str_8.txt")));
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains(r#"(<a href="-_0_0_0/dynamo_cpp_guards_str_8.html">readable_html</a>)"#));
+
+    let page = &map[&PathBuf::from("-_0_0_0/dynamo_cpp_guards_str_8.html")];
+    assert!(page.contains("34 guards in 34 guard managers."));
+    assert!(page.contains("<a href='dynamo_cpp_guards_str_8.txt'>raw guard dump</a>"));
+    assert!(page.contains(
+        r#"<li class="guard-manager"><details open><summary><span class="guard-kind">RootGuardManager</span> <span class="guard-count">34 guards</span></summary>"#
+    ));
+    assert!(page.contains(
+        r#"<span class="guard-kind">GuardManager</span>: <code>source=L['x'], accessed_by=DictGetItemGuardAccessor('x')</code> <span class="guard-count">2 guards</span>"#
+    ));
+    assert!(page.contains(
+        r#"<li class="guard"><span class="guard-kind">DEFAULT_DEVICE</span>: <code>utils_device.CURRENT_DEVICE == None</code> <span class="guard-comment"># _dynamo/output_graph.py:484 in init_ambient_guards</span></li>"#
+    ));
+    assert!(page.contains(
+        r#"<span class="guard-kind">KeyValueManager</span> <code>pair at index=0</code>"#
+    ));
+    // Only the top levels start open
+    assert!(page.contains(
+        r#"<details><summary><span class="guard-kind">GuardManager</span>: <code>source=L['self'].__dict__"#
+    ));
+    assert!(page.contains(r#"id="guard-search""#));
 
-    assert!(html_content.contains("Graph Runtime Analysis"));
-    assert!(!html_content.contains("Runtime analysis not available"));
-    assert!(html_content.contains("ms delta"));
+    let report_model: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("report_model.json")]).unwrap();
+    assert!(report_model["pages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|p| p["template"] == "dynamo_cpp_guards.html"));
+}
 
+#[test]
+fn test_merge_reports() -> Result<(), Box<dyn std::error::Error>> {
+    // Two nodes, each with local ranks 0 and 1, and a single-log report
+    let temp_dir = tempdir()?;
+    let tlparse = |args: &[&std::ffi::OsStr]| {
+        let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+            .args(args)
+            .arg("--no-browser")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    let mut nodes = Vec::new();
+    for node in ["node_a", "node_b"] {
+        let logs = temp_dir.path().join(node).join("logs");
+        fs::create_dir_all(&logs)?;
+        for rank in [0, 1] {
+            let name = format!("dedicated_log_torch_trace_rank_{rank}.log");
+            fs::copy(
+                Path::new("tests/inputs/multi_rank_logs").join(&name),
+                logs.join(&name),
+            )?;
+        }
+        let out = temp_dir.path().join(node).join("out");
+        tlparse(&[
+            logs.as_os_str(),
+            "--all-ranks-html".as_ref(),
+            "-o".as_ref(),
+            out.as_os_str(),
+        ]);
+        nodes.push(out);
+    }
+    let single = temp_dir.path().join("single");
+    tlparse(&[
+        "tests/inputs/simple.log".as_ref(),
+        "-o".as_ref(),
+        single.as_os_str(),
+    ]);
+
+    let merged = temp_dir.path().join("merged");
+    tlparse(&[
+        "merge".as_ref(),
+        nodes[0].as_os_str(),
+        nodes[1].as_os_str(),
+        single.as_os_str(),
+        "-o".as_ref(),
+        merged.as_os_str(),
+    ]);
+
+    let landing = fs::read_to_string(merged.join("index.html"))?;
+    for rank in 0..5 {
+        assert!(landing.contains(&format!("rank_{rank}/index.html")));
+        assert!(merged
+            .join(format!("rank_{rank}/compile_directory.json"))
+            .is_file());
+    }
+    assert!(!merged.join("rank_5").exists());
+    assert!(merged.join("chromium_events.json").is_file());
+
+    // Node b's ranks collide with node a's and are renumbered after them
+    let manifest = tlparse::manifest::Manifest::load(&merged.join("rank_3"))?;
+    assert_eq!(manifest.metadata["source_rank"], "1");
+    assert!(manifest.metadata["merged_from"].ends_with("rank_1"));
+    assert!(manifest.metadata["merged_from"].contains("node_b"));
+    let manifest = tlparse::manifest::Manifest::load(&merged.join("rank_0"))?;
+    assert!(!manifest.metadata.contains_key("source_rank"));
+    // Every file of the manifest was copied
+    let source = tlparse::manifest::Manifest::load(&nodes[0].join("rank_0"))?;
+    assert_eq!(manifest.files.len(), source.files.len());
+    for file in &manifest.files {
+        assert!(merged.join("rank_0").join(&file.path).is_file());
+    }
+    let manifest = tlparse::manifest::Manifest::load(&merged.join("rank_4"))?;
+    assert!(manifest.log.ends_with("simple.log"));
     Ok(())
 }
 
 #[test]
-fn test_runtime_analysis_mismatched_graphs() -> Result<(), Box<dyn std::error::Error>> {
-    // Use entire directory - rank 4 is missing a graph compared to ranks 0,1,2,3
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
-    let temp_out = tempdir()?;
-    let output_dir = temp_out.path();
+fn test_workspace_runs() -> Result<(), Box<dyn std::error::Error>> {
+    use tlparse::workspace::{render_landing, route, runs, Route};
+    let temp_dir = tempdir()?;
+    let workspace = temp_dir.path();
+    let tlparse = |args: &[&std::ffi::OsStr]| {
+        let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+            .args(args)
+            .arg("--no-browser")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    tlparse(&[
+        "tests/inputs/simple.log".as_ref(),
+        "-o".as_ref(),
+        workspace.join("simple").as_os_str(),
+    ]);
+    tlparse(&[
+        "tests/inputs/comp_metrics.log".as_ref(),
+        "-o".as_ref(),
+        workspace.join("metrics").as_os_str(),
+        "--metadata".as_ref(),
+        "commit=abc123".as_ref(),
+    ]);
+    // Not a report
+    fs::create_dir(workspace.join("scratch"))?;
+
+    let listed = runs(workspace)?;
+    let names: Vec<&str> = listed.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["metrics", "simple"], "newest first: {names:?}");
+    assert!(listed[0].num_compiles > 0);
+    assert_eq!(listed[0].metadata, "commit=abc123");
+    assert_eq!(listed[0].url, "/runs/metrics/");
+    let landing = render_landing(workspace)?;
+    assert!(landing.contains(r#"<a href="/runs/simple/">simple</a>"#));
+    assert!(landing.contains("commit=abc123"));
+    assert!(!landing.contains("scratch"));
+
+    assert_eq!(route(workspace, ""), Route::Landing);
+    assert_eq!(
+        route(workspace, "runs/simple"),
+        Route::Redirect("/runs/simple/".to_string())
+    );
+    assert_eq!(
+        route(workspace, "runs/simple/index.html"),
+        Route::Run {
+            name: "simple".to_string(),
+            dir: workspace.join("simple"),
+            path: "index.html".to_string(),
+        }
+    );
+    assert_eq!(
+        route(workspace, "runs/../simple/index.html"),
+        Route::NotFound
+    );
+    assert_eq!(route(workspace, "runs/missing/"), Route::NotFound);
+    // The file listing of a run links within the run
+    let listing =
+        tlparse::browse::render_listing(&workspace.join("simple"), Path::new(""), "/runs/simple")?
+            .unwrap();
+    assert!(listing.contains("<a href=\"/runs/simple/raw.jsonl\">raw.jsonl</a>"));
+
+    // Removing a run takes effect without a restart
+    fs::remove_dir_all(workspace.join("metrics"))?;
+    let names: Vec<String> = runs(workspace)?.into_iter().map(|r| r.name).collect();
+    assert_eq!(names, ["simple"]);
+    assert_eq!(route(workspace, "runs/metrics/"), Route::NotFound);
+    Ok(())
+}
 
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
-        .arg("-o")
-        .arg(&output_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
+#[test]
+fn test_parser_state() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tlparse::parser_state::ParserState;
+    use tlparse::parsers::{
+        CompileId, Envelope, Metadata, ParserOutput, ParserResults, StructuredLogParser,
+    };
 
-    let landing_page = output_dir.join("index.html");
-    assert!(landing_page.exists(), "Landing page should exist");
+    #[derive(Default)]
+    struct GraphsSeen(AtomicUsize);
 
-    let html_content = fs::read_to_string(&landing_page)?;
+    struct CountGraphs;
+    impl StructuredLogParser for CountGraphs {
+        fn name(&self) -> &'static str {
+            "count_graphs"
+        }
+        fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+            e.dynamo_output_graph
+                .as_ref()
+                .map(Metadata::DynamoOutputGraph)
+        }
+        fn parse_with_state<'e>(
+            &self,
+            state: &ParserState,
+            _lineno: usize,
+            _metadata: Metadata<'e>,
+            _rank: Option<u32>,
+            _compile_id: &Option<CompileId>,
+            _payload: &str,
+        ) -> anyhow::Result<ParserResults> {
+            state.get::<GraphsSeen>().0.fetch_add(1, Ordering::Relaxed);
+            Ok(Vec::new())
+        }
+    }
 
-    assert!(html_content.contains("Graph Runtime Analysis"));
-    assert!(html_content.contains("Runtime analysis not available"));
-    assert!(!html_content.contains("ms delta"));
+    struct ReportGraphs;
+    impl StructuredLogParser for ReportGraphs {
+        fn name(&self) -> &'static str {
+            "report_graphs"
+        }
+        fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+            e.inductor_output_code
+                .as_ref()
+                .map(Metadata::InductorOutputCode)
+        }
+        fn parse_with_state<'e>(
+            &self,
+            state: &ParserState,
+            _lineno: usize,
+            _metadata: Metadata<'e>,
+            _rank: Option<u32>,
+            _compile_id: &Option<CompileId>,
+            _payload: &str,
+        ) -> anyhow::Result<ParserResults> {
+            let seen = state.get::<GraphsSeen>().0.load(Ordering::Relaxed);
+            Ok(vec![ParserOutput::GlobalFile(
+                PathBuf::from("graphs_seen.txt"),
+                seen.to_string(),
+            )])
+        }
+    }
 
-    Ok(())
+    let config = tlparse::ParseConfig {
+        custom_parsers: vec![Arc::new(CountGraphs), Arc::new(ReportGraphs)],
+        ..Default::default()
+    };
+    // The parsers are shared, the state of each parse is its own
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let outputs: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..2)
+            .map(|_| scope.spawn(|| tlparse::parse_path(&path, &config)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for output in outputs {
+        let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+        assert_eq!(map[&PathBuf::from("graphs_seen.txt")], "1");
+    }
 }
 
 #[test]
-fn test_chromium_trace_with_runtime() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
-    let temp_out = tempdir()?;
-    let out_dir = temp_out.path();
-
-    Command::cargo_bin("tlparse")?
-        .arg(&input_dir)
-        .args(&["--all-ranks-html", "--overwrite", "-o"])
+fn test_parser_config() {
+    let temp_dir = tempdir().unwrap();
+    let parser_config = temp_dir.path().join("parsers.json");
+    fs::write(
+        &parser_config,
+        r#"{"parsers": [
+            {"artifact": "fx_graph_cache_.*", "filename": "team_{name}.txt"},
+            {"name": "runnable", "artifact": "fx_graph_runnable", "format": "html"}
+        ]}"#,
+    )
+    .unwrap();
+    let out_dir = temp_dir.path().join("out");
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/simple.log")
+        .arg("--parser-config")
+        .arg(&parser_config)
+        .args(["--no-browser", "-o"])
         .arg(&out_dir)
-        .arg("--no-browser")
-        .assert()
-        .success();
-
-    let runtime_trace_path = out_dir.join("chromium_trace_with_runtime.json");
-    assert!(runtime_trace_path.exists());
-
-    let trace_events: Vec<serde_json::Value> =
-        serde_json::from_str(&fs::read_to_string(&runtime_trace_path)?)?;
-    assert!(!trace_events.is_empty());
-
-    let runtime_events: Vec<&serde_json::Value> = trace_events
-        .iter()
-        .filter(|e| e["ph"] == "X" && e["cat"] == "runtime")
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let compile_dir = out_dir.join("-_0_0_0");
+    let files: Vec<String> = fs::read_dir(&compile_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
         .collect();
-    assert!(!runtime_events.is_empty());
+    assert!(
+        files
+            .iter()
+            .any(|f| f.starts_with("team_fx_graph_cache_miss_") && f.ends_with(".txt")),
+        "{files:?}"
+    );
+    let runnable = files
+        .iter()
+        .find(|f| f.starts_with("fx_graph_runnable_") && f.ends_with(".html"))
+        .expect("fx_graph_runnable page");
+    let html = fs::read_to_string(compile_dir.join(runnable)).unwrap();
+    assert!(html.contains(r#"<span class="line" id="L1">"#));
+    // The built-in artifact parser still writes its own copy
+    assert!(files
+        .iter()
+        .any(|f| f.starts_with("fx_graph_runnable_") && f.ends_with(".txt")));
+
+    fs::write(
+        &parser_config,
+        r#"{"parsers": [{"name": "broken", "artifact": "fx_graph_("}]}"#,
+    )
+    .unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/simple.log")
+        .arg("--parser-config")
+        .arg(&parser_config)
+        .args(["--no-browser", "--overwrite", "-o"])
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Invalid name pattern `fx_graph_(` in parser `broken`"));
+}
 
-    for e in &runtime_events {
-        assert!(e["name"].is_string());
-        let dur = e["dur"].as_u64().expect("dur should be u64");
-        assert!(dur > 0);
-        assert!(e["pid"].as_u64().is_some());
-        assert!(e["tid"].as_u64().is_some());
-        assert!(e["args"]["runtime_ns"].is_number());
-        assert!(e["args"]["graph"].is_string());
-        if let (Some(pid), Some(rank)) = (e["pid"].as_u64(), e["args"]["rank"].as_u64()) {
-            assert_eq!(pid, rank);
+#[test]
+fn test_output_collisions() {
+    use std::sync::Arc;
+    use tlparse::parsers::{
+        CompileId, Envelope, Metadata, ParserOutput, ParserResults, StructuredLogParser,
+    };
+
+    // Writes every artifact to the same few paths
+    struct SamePaths;
+    impl StructuredLogParser for SamePaths {
+        fn name(&self) -> &'static str {
+            "same_paths"
+        }
+        fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+            e.artifact.as_ref().map(Metadata::Artifact)
+        }
+        fn parse<'e>(
+            &self,
+            _lineno: usize,
+            metadata: Metadata<'e>,
+            _rank: Option<u32>,
+            _compile_id: &Option<CompileId>,
+            _payload: &str,
+        ) -> anyhow::Result<ParserResults> {
+            let Metadata::Artifact(artifact) = metadata else {
+                unreachable!()
+            };
+            Ok(vec![
+                ParserOutput::GlobalFile(PathBuf::from("notes.txt"), artifact.name.clone()),
+                ParserOutput::GlobalFile(PathBuf::from("same.txt"), "same".to_string()),
+                ParserOutput::GlobalFile(PathBuf::from("index.html"), "not the index".to_string()),
+            ])
         }
     }
 
-    // Verify exact rank set matches input logs
-    let expected_ranks: std::collections::HashSet<u64> = std::fs::read_dir(&input_dir)?
-        .filter_map(|e| e.ok())
-        .filter_map(|e| e.file_name().into_string().ok())
-        .filter_map(|name| {
-            name.strip_prefix("dedicated_log_torch_trace_rank_")
-                .and_then(|s| s.strip_suffix(".log"))
-                .and_then(|n| n.parse::<u64>().ok())
-        })
+    let config = tlparse::ParseConfig {
+        custom_parsers: vec![Arc::new(SamePaths)],
+        ..Default::default()
+    };
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let output = tlparse::parse_path(&path, &config).unwrap();
+    let map: HashMap<PathBuf, String> = output.into_iter().collect();
+    // simple.log has four artifacts: the first keeps the path, the others are renamed
+    assert_eq!(
+        map[&PathBuf::from("notes.txt")],
+        "aot_forward_graph_fw_metadata"
+    );
+    for n in 2..=4 {
+        assert!(map.contains_key(&PathBuf::from(format!("notes_dup{n}.txt"))));
+    }
+    assert_eq!(map[&PathBuf::from("same.txt")], "same");
+    assert!(!map.contains_key(&PathBuf::from("same_dup2.txt")));
+    // The report's own files are never taken
+    assert_eq!(map[&PathBuf::from("index_dup2.html")], "not the index");
+    assert!(map[&PathBuf::from("index.html")].contains(
+        "4 outputs mapped to paths already taken and were renamed.  See <a href='output_collisions.json'>"
+    ));
+    let collisions: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("output_collisions.json")]).unwrap();
+    let collisions = collisions.as_array().unwrap();
+    assert_eq!(collisions.len(), 4);
+    assert_eq!(collisions[0]["path"], "index.html");
+    assert_eq!(collisions[0]["renamed_to"], "index_dup2.html");
+    assert_eq!(collisions[0]["parser"], "same_paths");
+
+    // Writes an artifact to every top-level file of the report
+    struct ReportPaths(Vec<PathBuf>);
+    impl StructuredLogParser for ReportPaths {
+        fn name(&self) -> &'static str {
+            "report_paths"
+        }
+        fn get_metadata<'e>(&self, e: &'e Envelope) -> Option<Metadata<'e>> {
+            e.artifact.as_ref().map(Metadata::Artifact)
+        }
+        fn parse<'e>(
+            &self,
+            _lineno: usize,
+            _metadata: Metadata<'e>,
+            _rank: Option<u32>,
+            _compile_id: &Option<CompileId>,
+            _payload: &str,
+        ) -> anyhow::Result<ParserResults> {
+            Ok(self
+                .0
+                .iter()
+                .map(|path| ParserOutput::GlobalFile(path.clone(), "artifact".to_string()))
+                .collect())
+        }
+    }
+    let config = tlparse::ParseConfig {
+        report_json: true,
+        ..Default::default()
+    };
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let top_level: Vec<PathBuf> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .map(|(path, _)| path)
+        .filter(|path| path.components().count() == 1)
         .collect();
-
-    let pids: std::collections::HashSet<u64> = runtime_events
-        .iter()
-        .filter_map(|e| e["pid"].as_u64())
+    assert!(top_level.len() > 10, "{top_level:?}");
+    let config = tlparse::ParseConfig {
+        custom_parsers: vec![Arc::new(ReportPaths(top_level.clone()))],
+        ..config
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
         .collect();
-    assert_eq!(pids, expected_ranks, "pid set != expected rank set");
+    for path in &top_level {
+        assert_ne!(map[path], "artifact", "{} was taken", path.display());
+    }
+    let collisions: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("output_collisions.json")]).unwrap();
+    assert!(collisions
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|c| !c["renamed_to"].is_null()));
+}
 
+#[test]
+fn test_report_files_reserved() -> Result<(), Box<dyn std::error::Error>> {
+    // Every top-level file a report of any of the test logs has is reserved, so a file added
+    // to the report's top level without being added to the reserved ones fails here
+    let configs = [
+        tlparse::ParseConfig {
+            report_json: true,
+            report_model: true,
+            inductor_provenance: true,
+            ..Default::default()
+        },
+        tlparse::ParseConfig {
+            export: true,
+            ..Default::default()
+        },
+    ];
+    let mut logs: Vec<PathBuf> = fs::read_dir("tests/inputs")?
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "log"))
+        .collect();
+    logs.sort();
+    let mut unreserved = std::collections::BTreeSet::new();
+    for log in &logs {
+        // --export is for the logs of torch.export
+        let export = log.to_str().unwrap().contains("export");
+        for config in configs.iter().filter(|c| c.export == export) {
+            let Ok(output) = tlparse::parse_path(log, config) else {
+                continue;
+            };
+            unreserved.extend(
+                output
+                    .into_iter()
+                    .map(|(path, _)| path)
+                    .filter(|path| path.components().count() == 1)
+                    .filter(|path| !tlparse::collisions::is_report_file(path)),
+            );
+        }
+    }
+    assert!(unreserved.is_empty(), "not reserved: {unreserved:?}");
     Ok(())
 }
 
 #[test]
-fn test_tensor_meta_divergence_groups() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/multi_rank_runtime");
-    let temp_out = tempdir()?;
-    let out_dir = temp_out.path();
-
-    Command::cargo_bin("tlparse")?
-        .arg(&input_dir)
-        .args(&["--all-ranks-html", "--overwrite", "-o"])
-        .arg(&out_dir)
-        .arg("--no-browser")
-        .assert()
-        .success();
-
-    let landing_page = out_dir.join("index.html");
-    let html_content = fs::read_to_string(&landing_page)?;
-
-    // Should always show tensor meta analysis section
-    assert!(html_content.contains("Tensor Metadata Analysis"));
-
-    // Should show divergence since ranks have different tensor meta
-    assert!(html_content.contains("Ranks exhibit divergent inductor tensor meta"));
-
-    // Ranks 5 and 6 should be grouped together (same tensor meta)
-    assert!(html_content.contains("Ranks: 5, 6"));
-
+fn test_max_memory() -> Result<(), Box<dyn std::error::Error>> {
+    // Once the outputs go over the budget they are spilled to disk, raw.log always
+    let path = Path::new("tests/inputs/cache_hit_miss.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        max_memory: Some(64 << 10),
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)?.into_iter().collect();
+    assert!(!map.contains_key(&PathBuf::from("raw.log")));
+    let index: tlparse::lazy::LazyArtifactIndex =
+        serde_json::from_str(&map[&PathBuf::from("artifact_index.json")])?;
+    let raw_log = &index.artifacts["raw.log"];
+    let spill_file = raw_log.spill_file.clone().unwrap();
+    assert!(spill_file.is_file());
+    assert_eq!(raw_log.bytes, Some(fs::metadata(&path)?.len()));
+    assert!(index.artifacts.len() > 1);
+    // Pages rendered from spilled outputs still read them
+    assert!(map[&PathBuf::from("-_1_0_0/dynamo_cpp_guards_str_11.html")].contains("guards in"));
+
+    // Written out after the rest of the report, which comes out the same
+    let temp_dir = tempdir()?;
+    let run = |out: &Path, max_memory: Option<&str>| {
+        let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"));
+        cmd.arg(&path).arg("-o").arg(out).arg("--no-browser");
+        if let Some(max_memory) = max_memory {
+            cmd.args(["--max-memory", max_memory]);
+        }
+        let output = cmd.output().unwrap();
+        assert!(output.status.success(), "{output:?}");
+    };
+    let (normal, capped) = (
+        temp_dir.path().join("normal"),
+        temp_dir.path().join("capped"),
+    );
+    run(&normal, None);
+    run(&capped, Some("1KiB"));
+    assert!(!capped.join("artifact_index.json").exists());
+    let mut pending = vec![PathBuf::new()];
+    while let Some(rel) = pending.pop() {
+        for entry in fs::read_dir(normal.join(&rel))? {
+            let rel = rel.join(entry?.file_name());
+            if normal.join(&rel).is_dir() {
+                pending.push(rel);
+            } else if rel != Path::new("manifest.json") {
+                assert!(
+                    fs::read(normal.join(&rel))? == fs::read(capped.join(&rel))?,
+                    "{} differs",
+                    rel.display()
+                );
+            }
+        }
+    }
+    // The spill file of the library parse is left to whoever materializes it
+    fs::remove_file(spill_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(&path)
+        .args(["--max-memory", "4X", "--no-browser"])
+        .output()?;
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown size unit `x` in `4X`"));
     Ok(())
 }
 
 #[test]
-fn test_execution_order_multi_rank_divergence() -> anyhow::Result<()> {
-    // Test data: Two ranks with different execution orders
-    let exec_orders: fxhash::FxHashMap<u32, Vec<String>> = vec![
-        (
-            0_u32,
-            vec!["0/0".to_string(), "0/1".to_string(), "0/2".to_string()],
-        ),
-        (
-            1_u32,
-            vec!["0/1".to_string(), "0/0".to_string(), "0/2".to_string()],
-        ),
-    ]
-    .into_iter()
-    .collect();
+fn test_path_map() {
+    let path = Path::new("tests/inputs/sources.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        stack_render: tlparse::StackRenderOptions {
+            path_map: vec![("/home/user/".to_string(), "/src/app/".to_string())],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    // Tracebacks in payloads, like stack frames and the filenames of compilation metrics
+    let graph_breaks = &map[&PathBuf::from("graph_breaks.html")];
+    assert!(graph_breaks.contains("File &quot;/src/app/layers.py&quot;, line 7, in helper"));
+    let sources = &map[&PathBuf::from("sources.html")];
+    assert!(sources.contains("/src/app/model.py"));
+    assert!(sources.contains("/src/app/layers.py"));
+    for (path, contents) in &map {
+        // The raw log is kept as logged
+        if !path.to_str().unwrap().starts_with("raw.") {
+            assert!(!contents.contains("/home/user/"), "{}", path.display());
+        }
+    }
+}
 
-    // Mock collective schedules - different at index 0
-    let collective_schedule_by_graph: fxhash::FxHashMap<(u32, String), Vec<String>> = vec![
-        ((0_u32, "0/0".to_string()), vec!["all_reduce".to_string()]),
-        ((0_u32, "0/1".to_string()), vec!["all_gather".to_string()]),
-        ((1_u32, "0/0".to_string()), vec!["all_reduce".to_string()]),
-        ((1_u32, "0/1".to_string()), vec!["broadcast".to_string()]), // Different!
-    ]
-    .into_iter()
-    .collect();
+#[test]
+fn test_trace_archive() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let trace_dir = temp_dir.path().join("job/traces");
+    fs::create_dir_all(&trace_dir)?;
+    for rank in 0..2 {
+        let name = format!("dedicated_log_torch_trace_rank_{rank}.log");
+        fs::copy(
+            Path::new("tests/inputs/multi_rank_logs").join(&name),
+            trace_dir.join(&name),
+        )?;
+    }
+    let tar = |archive: &str, members: &[&str]| {
+        let status = std::process::Command::new("tar")
+            .arg("czf")
+            .arg(temp_dir.path().join(archive))
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(members)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
 
-    // Mock cache status - different at index 1
-    let cache_status: fxhash::FxHashMap<(u32, String), String> = vec![
-        ((0_u32, "0/0".to_string()), "✅".to_string()),
-        ((0_u32, "0/1".to_string()), "❌".to_string()),
-        ((1_u32, "0/0".to_string()), "✅".to_string()),
-        ((1_u32, "0/1".to_string()), "✅".to_string()), // Different!
-    ]
-    .into_iter()
-    .collect();
+    // Several ranks are reported on as with --all-ranks-html
+    tar("ranks.tar.gz", &["job"]);
+    let out_dir = temp_dir.path().join("out");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(temp_dir.path().join("ranks.tar.gz"))
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Unpacked 2 files from"));
+    assert!(out_dir.join("rank_0/index.html").exists());
+    assert!(out_dir.join("rank_1/index.html").exists());
+    let landing = fs::read_to_string(out_dir.join("index.html"))?;
+    assert!(landing.contains(r#"<a href="rank_1/index.html">"#));
+
+    // A single log is parsed on its own
+    fs::remove_file(trace_dir.join("dedicated_log_torch_trace_rank_1.log"))?;
+    tar("rank0.tar.gz", &["job"]);
+    let out_dir = temp_dir.path().join("out_single");
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg(temp_dir.path().join("rank0.tar.gz"))
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .status()?;
+    assert!(status.success());
+    assert!(out_dir.join("index.html").exists());
+    assert!(!out_dir.join("rank_0").exists());
+    Ok(())
+}
 
-    let report = tlparse::execution_order::analyze_execution_order(
-        &exec_orders,
-        &collective_schedule_by_graph,
-        &cache_status,
+#[test]
+fn test_single_file() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/simple.log")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .arg("--single-file")
+        .status()?;
+    assert!(status.success());
+    let bundle = fs::read_to_string(out_dir.join("tlparse_report.html"))?;
+    assert!(bundle.contains(r#"<script type="text/plain" data-path="index.html">"#));
+    assert!(bundle.contains(r#"data-path="-_0_0_0/inductor_output_code"#));
+    // Every other file is embedded, and none of them closes its block early
+    fn count_files(dir: &Path) -> usize {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .map(|p| if p.is_dir() { count_files(&p) } else { 1 })
+            .sum()
+    }
+    let files = count_files(&out_dir) - 1;
+    assert_eq!(
+        bundle.matches(r#"<script type="text/plain""#).count(),
+        files
     );
+    assert_eq!(bundle.matches("</script>").count(), files + 1);
+    Ok(())
+}
 
-    assert_eq!(report.by_index.len(), 3);
+#[test]
+fn test_vllm_eager_vs_compiled_ops() {
+    let path = Path::new("tests/inputs/vllm_eager_ops.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let summary: serde_json::Value =
+        serde_json::from_str(&map[&PathBuf::from("vllm_summary.json")]).unwrap();
+    let comparison = &summary["engines"][0]["op_comparison"];
+    assert_eq!(
+        comparison["summary"],
+        "4 custom ops in the eager graph: 1 fused, 1 decomposed, 1 split out, 1 unfused.  \
+         _C.rms_norm_static_fp8_quant only in the generated code, from fusion passes."
+    );
+    let status = |op: &str| {
+        let row = comparison["rows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["op"] == op)
+            .unwrap();
+        (
+            row["status"].as_str().unwrap().to_string(),
+            row["compiled_count"].as_u64().unwrap(),
+        )
+    };
+    assert_eq!(
+        status("_C.rms_norm"),
+        ("fused into _C.rms_norm_static_fp8_quant".to_string(), 0)
+    );
+    // The rotary embedding in a comment of the generated code isn't a call
+    assert_eq!(status("_C.rotary_embedding"), ("decomposed".to_string(), 0));
+    assert_eq!(
+        status("vllm.unified_attention"),
+        ("split out".to_string(), 0)
+    );
+    assert_eq!(status("_C.silu_and_mul"), ("unfused".to_string(), 1));
+    // Counted per size or range: both submods of the range call the fused op
+    assert_eq!(
+        status("_C.rms_norm_static_fp8_quant"),
+        ("from a fusion".to_string(), 2)
+    );
+    assert!(comparison["rows"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|r| r["op"] != "aten.mm"));
 
-    // Index 0: Rank 0 has "0/0", Rank 1 has "0/1"
-    // Should detect collective schedule mismatch
-    let row_0 = &report.by_index[0];
-    assert_eq!(row_0.by_rank.get(&0_u32), Some(&"0/0".to_string()));
-    assert_eq!(row_0.by_rank.get(&1_u32), Some(&"0/1".to_string()));
-    assert!(row_0
-        .issues
-        .contains(&tlparse::execution_order::ExecOrderIssue::ScheduleMismatch));
+    let index = &map[&PathBuf::from("index.html")];
+    assert!(index.contains("<h2>Eager vs Compiled Ops</h2>"));
+    assert!(index.contains(r#"<tr class="op-problem"><td><code>_C.rotary_embedding</code></td><td>1</td><td>0</td><td>decomposed</td></tr>"#));
+}
 
-    // Index 1: Rank 0 has "0/1", Rank 1 has "0/0"
-    // Should detect cache mismatch
-    let row_1 = &report.by_index[1];
-    assert_eq!(row_1.by_rank.get(&0_u32), Some(&"0/1".to_string()));
-    assert_eq!(row_1.by_rank.get(&1_u32), Some(&"0/0".to_string()));
-    assert!(row_1
-        .issues
-        .contains(&tlparse::execution_order::ExecOrderIssue::CacheMismatch));
+#[test]
+fn test_synthetic_trace() {
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    let trace: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("tlparse_trace.json")]).unwrap();
+    let spans = |name: &str| -> Vec<&serde_json::Value> {
+        trace
+            .iter()
+            .filter(|e| e["ph"] == "X" && e["name"].as_str().unwrap().starts_with(name))
+            .collect()
+    };
+    // A track per compile attempt, each with its compile span and the phases of its metrics
+    // in it
+    let compiles = spans("[");
+    assert_eq!(compiles.len(), 5);
+    let tracks: std::collections::HashSet<u64> = compiles
+        .iter()
+        .map(|e| e["tid"].as_u64().unwrap())
+        .collect();
+    assert_eq!(tracks.len(), 5);
+    for compile in &compiles {
+        let tid = &compile["tid"];
+        let start = compile["ts"].as_f64().unwrap();
+        let end = start + compile["dur"].as_f64().unwrap();
+        for phase in trace.iter().filter(|e| e["ph"] == "X" && e["tid"] == *tid) {
+            let ts = phase["ts"].as_f64().unwrap();
+            assert!(start <= ts && ts + phase["dur"].as_f64().unwrap() <= end);
+        }
+    }
+    assert!(!spans("backend_compile").is_empty());
+    assert!(trace
+        .iter()
+        .any(|e| e["ph"] == "i" && e["name"] == "dynamo_output_graph"));
+    assert!(trace
+        .iter()
+        .any(|e| e["ph"] == "M" && e["name"] == "thread_name"));
+    assert!(map[&PathBuf::from("index.html")].contains("tlparse_trace.json"));
+}
 
-    // Index 2: Both ranks have "0/2" - no issues
-    let row_2 = &report.by_index[2];
-    assert_eq!(row_2.by_rank.get(&0_u32), Some(&"0/2".to_string()));
-    assert_eq!(row_2.by_rank.get(&1_u32), Some(&"0/2".to_string()));
-    assert!(row_2.issues.is_empty());
+#[test]
+fn test_template_registry_across_threads() {
+    tlparse::report_model::validate_templates().unwrap();
+    let path = Path::new("tests/inputs/comp_metrics.log").to_path_buf();
+    let config = tlparse::ParseConfig::default();
+    let expected: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    // Each thread renders with its own registry, the same pages as the first one
+    std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..4)
+            .map(|_| scope.spawn(|| tlparse::parse_path(&path, &config).unwrap()))
+            .collect();
+        for thread in threads {
+            let output: HashMap<PathBuf, String> = thread.join().unwrap().into_iter().collect();
+            assert_eq!(output.len(), expected.len());
+            for (file, contents) in &output {
+                if file.extension().is_some_and(|e| e == "html") {
+                    assert_eq!(contents, &expected[file], "{}", file.display());
+                }
+            }
+        }
+    });
+}
+
+#[test]
+fn test_serve_directory_listing_and_caching() -> Result<(), Box<dyn std::error::Error>> {
+    use tlparse::serve::{CacheHeaders, Validators};
 
+    // A directory holding a report per rank and no index.html of its own
+    let temp_dir = tempdir()?;
+    let runs = temp_dir.path().join("runs");
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    for rank in ["rank_0", "rank_1"] {
+        let output = tlparse::parse_path(&path, &Default::default())?;
+        tlparse::paths::write_output(&runs.join(rank), output)?;
+    }
+    fs::write(runs.join("notes.txt"), "two ranks")?;
+    let root = tlparse::browse::render_directory(&runs, Path::new(""), "")?.unwrap();
+    assert!(root.contains("<a href=\"/rank_0/\">rank_0/</a> </td> <td> tlparse report, "));
+    assert!(root.contains("<a href=\"/rank_1/\">rank_1/</a>"));
+    assert!(root.contains("<a href=\"/notes.txt\">notes.txt</a>"));
+    assert!(root.contains("<a href=\"/\">runs</a> /"));
+    // There is no report to go back to
+    assert!(!root.contains("Back to the"));
+    let rank = tlparse::browse::render_directory(&runs, Path::new("rank_0"), "/w")?.unwrap();
+    assert!(rank.contains("<a href=\"/w/rank_0/-_0_0_0/\">-_0_0_0/</a>"));
+    assert!(tlparse::browse::render_directory(&runs, Path::new("notes.txt"), "")?.is_none());
+
+    let validators = Validators::of(&fs::metadata(runs.join("notes.txt"))?).unwrap();
+    assert!(validators.etag.starts_with("\"9-"));
+    assert!(validators.last_modified.ends_with(" GMT"));
+    let fresh = |if_none_match: Option<&str>, if_modified_since: Option<&str>| {
+        CacheHeaders {
+            if_none_match: if_none_match.map(str::to_string),
+            if_modified_since: if_modified_since.map(str::to_string),
+        }
+        .is_fresh(&validators)
+    };
+    assert!(!fresh(None, None));
+    assert!(fresh(Some(&validators.etag), None));
+    assert!(fresh(
+        Some(&format!("\"other\", W/{}", validators.etag)),
+        None
+    ));
+    assert!(!fresh(Some("\"other\""), Some(&validators.last_modified)));
+    assert!(fresh(None, Some(&validators.last_modified)));
+    assert!(!fresh(None, Some("Thu, 01 Jan 1970 00:00:00 GMT")));
     Ok(())
 }
 
 #[test]
-fn test_collectives_parity_detects_mismatch() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/inputs/collectives_parity");
-    let temp_out = tempdir()?;
-    let out_dir = temp_out.path();
+fn test_serve_ignores_query_string() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{Read, Write};
 
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
+    let temp_dir = tempdir()?;
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let mut server = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/simple.log")
         .arg("-o")
-        .arg(&out_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
-
-    // Run collectives parity check on generated outputs
-    let out_dir_buf = out_dir.to_path_buf();
-    parsers::check_collectives_parity(&out_dir_buf, &[0, 1, 2])?;
+        .arg(temp_dir.path().join("out"))
+        .args(["--serve", "--port", &port.to_string(), "--no-browser"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    let get = |url: &str| -> std::io::Result<String> {
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", port))?;
+        write!(stream, "GET {url} HTTP/1.0\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    };
+    let mut status = Err("server didn't start".to_string());
+    for _ in 0..100 {
+        match get("/compare.html?left=-_0_0_0/dynamo_output_graph_0.txt") {
+            Ok(response) => {
+                status = Ok(response.lines().next().unwrap_or_default().to_string());
+                break;
+            }
+            Err(_) => std::thread::sleep(std::time::Duration::from_millis(100)),
+        }
+    }
+    let fragment = get("/index.html#top");
+    server.kill()?;
+    server.wait()?;
+    // The links of the compile directories to the compare viewer carry the artifact to show
+    assert_eq!(status?, "HTTP/1.0 200 OK");
+    assert!(fragment?.starts_with("HTTP/1.0 200 OK"));
+    Ok(())
+}
 
-    // Verify rank 0 parity report exists and contains at least one mismatch
-    let rank_0_report_path = out_dir.join("rank_0").join("collectives_parity.json");
+#[test]
+fn test_compile_chromium_events() {
+    let path = Path::new("tests/inputs/chromium_events.log").to_path_buf();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &Default::default())
+        .unwrap()
+        .into_iter()
+        .collect();
+    let all: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("chromium_events.json")]).unwrap();
+    let compile: Vec<serde_json::Value> =
+        serde_json::from_str(&map[&PathBuf::from("-_0_0_0/chromium_events_-_0_0_0.json")]).unwrap();
+    // Events with the compile id; the run's file keeps those without one too
+    assert!(!compile.is_empty());
+    assert!(compile.len() <= all.len());
+    assert!(compile.iter().all(|e| all.contains(e)));
+    let (page, html) = map
+        .iter()
+        .find(|(p, _)| {
+            p.to_str()
+                .unwrap()
+                .starts_with("-_0_0_0/compilation_metrics")
+        })
+        .unwrap();
+    // Against the page's <base>, the report root
+    assert!(html.contains(&format!(
+        "<p>The {} <a href='-_0_0_0/chromium_events_-_0_0_0.json'>chromium events</a> of this compile",
+        compile.len()
+    )));
     assert!(
-        rank_0_report_path.exists(),
-        "collectives_parity.json for rank 0 should exist"
+        html.contains("<a href='chromium_events.json'>"),
+        "{}",
+        page.display()
     );
-    let rank_0_report: CollectivesParityReport =
-        serde_json::from_str(&fs::read_to_string(&rank_0_report_path)?)?;
-    // Expect mismatches: graph -_0_1_0 has offset 1, graph -_0_0_0 missing wait
-    assert_eq!(rank_0_report.graphs.len(), 2);
-    let mut by_graph = HashMap::new();
-    for g in &rank_0_report.graphs {
-        by_graph.insert(g.graph.as_str(), g);
-    }
-    let g = by_graph.get("-_0_1_0").expect("missing -_0_1_0 entry");
-    assert_eq!(g.compile_id, "[0/1]");
-    assert_eq!(g.offset, 0);
-    assert_eq!(g.missing_waits, 3);
-    let g0 = by_graph.get("-_0_0_0").expect("missing -_0_0_0 entry");
-    assert_eq!(g0.compile_id, "[0/0]");
-    assert_eq!(g0.offset, 0);
-    assert_eq!(g0.missing_waits, 1);
+    assert!(map[&PathBuf::from("-_0_0_0/index.html")].contains("Chromium events of the compile"));
+}
 
+#[test]
+fn test_watch_auto_refresh() -> Result<(), Box<dyn std::error::Error>> {
+    let script = tlparse::watch::auto_refresh_script(3);
+    assert!(script.contains("fetch('changes.json', { cache: 'no-store' })"));
+    assert!(script.contains("}, 3000);"));
+    // --watch puts it in the index at the root of the report, next to changes.json, and only
+    // there
+    let temp_dir = tempdir()?;
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let output = tlparse::parse_path(&path, &tlparse::ParseConfig::default())?;
+    tlparse::paths::write_output(temp_dir.path(), output)?;
+    tlparse::watch::add_auto_refresh(temp_dir.path(), 3)?;
+    tlparse::watch::add_auto_refresh(temp_dir.path(), 3)?;
+    let index = fs::read_to_string(temp_dir.path().join("index.html"))?;
+    assert_eq!(index.matches(&script).count(), 1);
+    assert!(index.find(&script) < index.find("</body>"));
+    assert!(!fs::read_to_string(temp_dir.path().join("compile_times.html"))?.contains(&script));
+
+    // Only watched reports regenerate
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .args(["tests/inputs/simple.log", "--refresh-interval", "3"])
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--watch"));
     Ok(())
 }
 
 #[test]
-fn test_graph_execution_order_diagnostics() -> Result<(), Box<dyn std::error::Error>> {
-    let input_dir = PathBuf::from("tests/graph_exec_order_tests");
-    let temp_out = tempdir()?;
-    let out_dir = temp_out.path();
+fn test_units_formatting() {
+    use tlparse::units;
+    assert_eq!(units::seconds(8.29, 1), "8.3 s");
+    assert_eq!(units::seconds(2.25, 3), "2.250 s");
+    assert_eq!(units::seconds(750.0, 2), "12.5 min");
+    assert_eq!(units::seconds(9000.0, 2), "2.5 h");
+    assert_eq!(units::duration(0.0124), "12.4 ms");
+    assert_eq!(units::duration(0.00085), "850 µs");
+    assert_eq!(units::duration(1.5), "1.500 s");
+    assert_eq!(units::duration(300.0), "5.0 min");
+    assert_eq!(units::bytes(512), "512 B");
+    assert_eq!(units::bytes(29389), "28.7 KiB");
+    assert_eq!(units::mib_delta(-350 * 1024 * 1024), "-350.0 MiB");
+    assert_eq!(units::count(7), "7");
+    assert_eq!(units::count(1234567), "1,234,567");
+
+    // Templates show raw values of their context through the same formatters
+    let mut tt = tinytemplate::TinyTemplate::new();
+    units::add_formatters(&mut tt);
+    tt.add_template(
+        "t",
+        "{t | format_duration}, {b | format_bytes}, {n | format_count}, {s | format_count}",
+    )
+    .unwrap();
+    let context = serde_json::json!({"t": 0.0015, "b": 20971520, "n": 12345, "s": "n/a"});
+    assert_eq!(
+        tt.render("t", &context).unwrap(),
+        "1.5 ms, 20.0 MiB, 12,345, n/a"
+    );
+}
 
-    let mut cmd = Command::cargo_bin("tlparse")?;
-    cmd.arg(&input_dir)
-        .arg("--all-ranks-html")
-        .arg("--overwrite")
+#[test]
+fn test_vllm_merge() -> Result<(), Box<dyn std::error::Error>> {
+    let input_dir = tempdir()?;
+    fs::copy(
+        "tests/inputs/vllm_cudagraphs.log",
+        input_dir.path().join("trace_worker_0.log"),
+    )?;
+    fs::copy(
+        "tests/inputs/vllm_cudagraphs.log",
+        input_dir.path().join("trace_worker_1.log"),
+    )?;
+    // Without a worker id in its name, numbered after the others
+    fs::copy(
+        "tests/inputs/vllm_compile_sizes.log",
+        input_dir.path().join("trace.log"),
+    )?;
+    let out_dir = tempdir()?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("--vllm-merge")
+        .arg(input_dir.path())
         .arg("-o")
-        .arg(&out_dir)
-        .arg("--no-browser");
-    cmd.assert().success();
-
-    let landing_page = out_dir.join("index.html");
-    assert!(landing_page.exists(), "Landing page should exist");
-
-    let html_content = fs::read_to_string(&landing_page)?;
-
-    assert!(html_content.contains("Graph Execution-Order Diagnostics"));
-
-    assert!(html_content.contains("Graph execution order differs across ranks"));
+        .arg(out_dir.path())
+        .args(["--overwrite", "--no-browser", "--jobs", "2"])
+        .output()?;
+    assert!(output.status.success(), "{output:?}");
+    for worker in 0..3 {
+        assert!(out_dir
+            .path()
+            .join(format!("worker_{worker}/vllm_summary.json"))
+            .is_file());
+    }
 
-    assert!(html_content.contains("Cache hit/miss sequence: Consistent across ranks"));
-    assert!(!html_content.contains("Warning:</strong> Cache hit/miss mismatch across ranks"));
+    let summary: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+        out_dir.path().join("vllm_summary.json"),
+    )?)?;
+    assert_eq!(
+        summary["summary"],
+        "3 workers; worker 2 compiled other subgraphs than worker 0"
+    );
+    let workers = summary["workers"].as_array().unwrap();
+    assert_eq!(workers.len(), 3);
+    assert!(workers[2]["log"].as_str().unwrap().ends_with("trace.log"));
+    assert_eq!(workers[0]["num_subgraphs"], 2);
+    assert_eq!(workers[0]["differs"], false);
+    assert_eq!(workers[2]["differs"], true);
+    assert_eq!(workers[0]["captures_summary"], "2 of 5 sizes captured");
+    assert_eq!(workers[0]["subgraphs"], workers[1]["subgraphs"]);
+    assert!(workers[0]["cudagraph_captures"][0]["size"].is_i64());
+
+    let index = fs::read_to_string(out_dir.path().join("index.html"))?;
+    assert!(index.contains("<h2><a href=\"worker_1/index.html\">worker 1</a></h2>"));
+    assert!(index.contains("<tr class=\"kernel-outlier\"><td><a href=\"worker_2/index.html\">"));
+    assert!(index.contains("<h3>CUDAGraph Captures</h3>"));
+
+    // Only --all-ranks-html and --vllm-merge parse several logs
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .args(["tests/inputs/simple.log", "--jobs", "2"])
+        .output()?;
+    assert!(!output.status.success());
+    Ok(())
+}
 
+#[test]
+fn test_strict_failures_report() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let out_dir = temp_dir.path().join("out");
+    // payload 6762d47fdbf80071626529f25dc69013 on line 13 is corrupted
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/simple_corrupted_json.log")
+        .arg("--strict")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--no-browser")
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("strict_failures.json"));
+
+    // The report is written all the same
+    assert!(out_dir.join("index.html").exists());
+    assert!(out_dir.join("manifest.json").exists());
+    let failures: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out_dir.join("strict_failures.json"))?)?;
+    assert_eq!(failures["reasons"][0], "Something went wrong");
+    assert_eq!(failures["stats"]["fail_payload_md5"], 1);
+    assert_eq!(failures["num_failures"], 1);
+    let failure = &failures["failures"][0];
+    assert_eq!(failure["kind"], "fail_payload_md5");
+    assert_eq!(failure["line"], 13);
+
+    // Without --strict there is nothing to report
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_tlparse"))
+        .arg("tests/inputs/simple_corrupted_json.log")
+        .arg("-o")
+        .arg(&out_dir)
+        .arg("--overwrite")
+        .arg("--no-browser")
+        .status()?;
+    assert!(status.success());
+    assert!(!out_dir.join("strict_failures.json").exists());
     Ok(())
 }
 
 #[test]
-fn test_parse_vllm_sample() {
-    let path = Path::new("tests/inputs/vllm_sample.log").to_path_buf();
+fn test_output_code_kernel_anchors() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
     let config = tlparse::ParseConfig {
         strict: true,
         ..Default::default()
     };
-    let output = tlparse::parse_path(&path, &config);
-    assert!(output.is_ok());
-    let map: HashMap<PathBuf, String> = output.unwrap().into_iter().collect();
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let (code_path, code) = map
+        .iter()
+        .find(|(p, _)| {
+            p.to_string_lossy()
+                .starts_with("-_0_0_0/inductor_output_code_")
+        })
+        .expect("output code not found");
+    let code_url = code_path.to_string_lossy();
 
-    // check for vLLM-specific artifacts
-    let expected_files = [
-        "-_0_0_0/vllm_compilation_config",
-        "-_0_0_0/vllm_piecewise_split_graph",
-        "-_-_-_-/vllm_submod_0",
-        "-_-_-_-/vllm_submod_2",
-    ];
+    // Both kernel definitions are anchored and listed with the lines launching them
+    assert!(code.contains(
+        r#"<span class="line" id="L48"><a class="lineno" href='#L48' data-line="48"></a><a class="kernel-anchor" id="kernel-triton_poi_fused_native_dropout_0"></a>"#
+    ));
+    assert!(code.contains(r#"id="kernel-triton_poi_fused_baz_gelu_native_dropout_1""#));
+    assert!(code.contains("<summary>2 kernels</summary>"));
+    assert!(code.contains(
+        "<li><a href='#kernel-triton_poi_fused_native_dropout_0'>triton_poi_fused_native_dropout_0</a> \
+         (line <a href='#L48'>48</a>, launched on line <a href='#L160'>160</a>)</li>"
+    ));
+    // The kernel's source is highlighted as code, not as a string
+    assert!(code.contains(
+        r#"data-line="50"></a><span style="font-weight:bold;color:#a71d5d;">import </span>"#
+    ));
 
-    for prefix in expected_files {
-        assert!(
-            prefix_exists(&map, prefix),
-            "{} not found in output",
-            prefix
-        );
-    }
+    // The metrics page of the compile links to each kernel
+    let metrics = map
+        .iter()
+        .find(|(p, _)| {
+            p.to_string_lossy()
+                .starts_with("-_0_0_0/compilation_metrics")
+        })
+        .map(|(_, html)| html)
+        .expect("compilation metrics not found");
+    assert!(metrics.contains("<h2>Kernels</h2>"));
+    assert!(metrics.contains(&format!(
+        "<a href='{code_url}#kernel-triton_poi_fused_baz_gelu_native_dropout_1'>triton_poi_fused_baz_gelu_native_dropout_1</a>"
+    )));
+
+    // Plain text output code is left as it is
+    let config = tlparse::ParseConfig {
+        plain_text: true,
+        ..Default::default()
+    };
+    let output = tlparse::parse_path(&path, &config).unwrap();
+    assert!(!output
+        .iter()
+        .any(|(_, content)| content.contains("kernel-anchor")));
+}
 
-    let index_html = &map[&PathBuf::from("index.html")];
+#[test]
+fn test_artifact_previews() {
+    let path = Path::new("tests/inputs/simple.log").to_path_buf();
+    let config = tlparse::ParseConfig {
+        strict: true,
+        ..Default::default()
+    };
+    let map: HashMap<PathBuf, String> = tlparse::parse_path(&path, &config)
+        .unwrap()
+        .into_iter()
+        .collect();
 
-    assert!(index_html.contains("vLLM Compilation Summary"),);
-    assert!(index_html.contains("Dynamo Compilation"),);
-    assert!(index_html.contains("Compilation Configuration"),);
-    assert!(index_html.contains("Piecewise Split Graph"),);
-    assert!(index_html.contains("range [1, 16384]"),);
-    assert!(index_html.contains("size 8"),);
-    assert!(index_html.contains("submod_0"),);
-    assert!(index_html.contains("submod_2"),);
+    let js = &map[&PathBuf::from("previews.js")];
+    let previews: serde_json::Value = serde_json::from_str(
+        js.strip_prefix("tlparsePreviewsLoaded(")
+            .and_then(|js| js.strip_suffix(");\n"))
+            .unwrap(),
+    )
+    .unwrap();
+    let (text, lines) = (
+        previews["-_0_0_0/dynamo_output_graph_0.txt"][0]
+            .as_str()
+            .unwrap(),
+        previews["-_0_0_0/dynamo_output_graph_0.txt"][1]
+            .as_u64()
+            .unwrap(),
+    );
+    assert!(text.starts_with("class GraphModule(torch.nn.Module):\n"));
+    assert!(text.lines().count() <= 20);
+    let graph = &map[&PathBuf::from("-_0_0_0/dynamo_output_graph_0.txt")];
+    assert_eq!(lines, graph.lines().count() as u64);
+
+    // Each page loads the previews relative to where its links resolve
+    assert!(map[&PathBuf::from("index.html")].contains(r#"<script data-previews="previews.js">"#));
+    assert!(map[&PathBuf::from("-_0_0_0/index.html")]
+        .contains(r#"<script data-previews="../previews.js">"#));
+    let metrics = map
+        .iter()
+        .find(|(p, _)| {
+            p.to_string_lossy()
+                .starts_with("-_0_0_0/compilation_metrics")
+        })
+        .map(|(_, html)| html)
+        .unwrap();
+    assert!(metrics.contains(r#"<script data-previews="previews.js">"#));
+    assert!(!map
+        .iter()
+        .any(|(p, html)| p.extension().is_some_and(|e| e == "html")
+            && html.contains("<!-- tlparse:previews -->")));
 }